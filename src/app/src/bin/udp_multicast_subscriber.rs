@@ -3,6 +3,8 @@
 /// 演示如何使用UDP组播接收市场数据
 
 use lib::multicase::domain::multicast::*;
+use lib::multicase::domain::session::RestartMarker;
+use lib::multicase::domain::stats::StatsMessage;
 use lib::multicase::outbound::udp_subscriber::UdpMulticastSubscriber;
 use std::time::Duration;
 use tokio::time;
@@ -82,6 +84,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         message.sequence, payload_str, latency_us
                     );
                 }
+                MessageType::Stats => match StatsMessage::decode(&message.payload) {
+                    Ok(stats) => {
+                        for s in &stats.symbols {
+                            println!(
+                                "📈 [Seq: {}] Stats: {} msgs={} last_seq={} bid={:?} ask={:?} health={:#04x}",
+                                message.sequence,
+                                s.symbol,
+                                s.message_count,
+                                s.last_sequence,
+                                s.best_bid,
+                                s.best_ask,
+                                s.health
+                            );
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to decode stats: {}", e),
+                },
+                MessageType::Restart => match RestartMarker::decode(&message.payload) {
+                    Ok(marker) => println!(
+                        "🔁 [Seq: {}] Restart: #{} resumed_seq={} subscriptions={}",
+                        message.sequence,
+                        marker.restart_count,
+                        marker.resumed_sequence,
+                        marker.subscriptions
+                    ),
+                    Err(e) => eprintln!("Failed to decode restart marker: {}", e),
+                },
             }
         })
         .await?;
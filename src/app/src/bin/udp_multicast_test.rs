@@ -55,6 +55,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     MessageType::OrderBook => "OrderBook",
                     MessageType::Trade => "Trade",
                     MessageType::Heartbeat => "Heartbeat",
+                    MessageType::Stats => "Stats",
+                    MessageType::Restart => "Restart",
                 },
                 payload
             );
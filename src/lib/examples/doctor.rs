@@ -0,0 +1,54 @@
+/// 启动前环境自检
+///
+/// 用法: cargo run --example doctor -- [--endpoint host:port]... [--config 配置文件]
+///       [--group 组播地址:端口] [--require-huge-pages] [--json]
+///
+/// 输出逐项检查结果；存在失败项时以退出码1结束。
+
+use lib::monitor::domain::doctor::{self, DoctorConfig};
+use std::net::SocketAddr;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let mut config = DoctorConfig::default();
+    let mut json = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--endpoint" | "--config" | "--group" => {
+                let Some(value) = args.next() else {
+                    return usage(&format!("{} requires a value", arg));
+                };
+                match arg.as_str() {
+                    "--endpoint" => config.endpoints.push(value),
+                    "--config" => config.config_path = Some(value.into()),
+                    _ => match value.parse::<SocketAddr>() {
+                        Ok(addr) => {
+                            config.multicast.multicast_addr = addr.ip();
+                            config.multicast.port = addr.port();
+                        }
+                        Err(e) => return usage(&format!("invalid group {}: {}", value, e)),
+                    },
+                }
+            }
+            "--require-huge-pages" => config.require_huge_pages = true,
+            "--json" => json = true,
+            _ => return usage(&format!("unexpected argument {}", arg)),
+        }
+    }
+
+    let report = doctor::run(&config);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).expect("report is serializable"));
+    } else {
+        print!("{}", report);
+    }
+    if report.passed() { ExitCode::SUCCESS } else { ExitCode::from(1) }
+}
+
+fn usage(error: &str) -> ExitCode {
+    eprintln!("{}", error);
+    eprintln!("usage: doctor [--endpoint host:port]... [--config path] [--group addr:port] [--require-huge-pages] [--json]");
+    ExitCode::from(2)
+}
@@ -0,0 +1,89 @@
+/// 订单索引基准测试
+///
+/// 对比标准库HashMap（SipHash）与OrderIndexMap在下单/撤单路径上的耗时，
+/// 以及订单簿整体的下单+撤单吞吐。
+///
+/// 运行: cargo run --release --example order_index_bench
+
+use lib::orderbook::{OrderBook, OrderIndexMap, Price, Quantity, Side, TimeInForce, TraderId};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::Instant;
+
+const ORDERS: u64 = 1_000_000;
+
+fn main() {
+    println!("=== 订单索引基准测试 ({} 订单) ===\n", ORDERS);
+
+    bench_hashmap();
+    bench_order_index_map();
+    bench_book_cancel();
+}
+
+fn bench_hashmap() {
+    let mut map: HashMap<u64, usize> = HashMap::with_capacity(ORDERS as usize);
+
+    let start = Instant::now();
+    for id in 1..=ORDERS {
+        map.insert(id, id as usize);
+    }
+    let insert = start.elapsed();
+
+    let start = Instant::now();
+    for id in 1..=ORDERS {
+        black_box(map.remove(&id));
+    }
+    let remove = start.elapsed();
+
+    report("HashMap", insert, remove);
+}
+
+fn bench_order_index_map() {
+    let mut map = OrderIndexMap::with_capacity(ORDERS as usize);
+
+    let start = Instant::now();
+    for id in 1..=ORDERS {
+        map.insert(id, id as usize);
+    }
+    let insert = start.elapsed();
+
+    let start = Instant::now();
+    for id in 1..=ORDERS {
+        black_box(map.remove(&id));
+    }
+    let remove = start.elapsed();
+
+    report("OrderIndexMap", insert, remove);
+}
+
+fn bench_book_cancel() {
+    let mut book = OrderBook::with_capacity(20_000, ORDERS as usize);
+    let trader = TraderId::from_str("BENCH");
+    let quantity = Quantity::new(10).unwrap();
+
+    let start = Instant::now();
+    let ids: Vec<_> = (0..ORDERS)
+        .map(|i| {
+            let price = Price::new(9_000 + (i % 1_000) as u64).unwrap();
+            book.limit_order(trader, Side::Buy, price, quantity, TimeInForce::Gtc).unwrap().0
+        })
+        .collect();
+    let insert = start.elapsed();
+
+    let start = Instant::now();
+    for id in ids {
+        black_box(book.cancel_order(id).is_ok());
+    }
+    let remove = start.elapsed();
+
+    report("OrderBook", insert, remove);
+}
+
+fn report(name: &str, insert: std::time::Duration, remove: std::time::Duration) {
+    println!(
+        "{:<14} 插入: {:>8.1} ns/op   删除: {:>8.1} ns/op",
+        name,
+        insert.as_nanos() as f64 / ORDERS as f64,
+        remove.as_nanos() as f64 / ORDERS as f64,
+    );
+}
@@ -2,7 +2,7 @@
 ///
 /// 本示例演示高性能订单簿实现和匹配引擎
 
-use lib::orderbook::{OrderBook, Side, TraderId};
+use lib::orderbook::{OrderBook, Price, PriceConverter, Quantity, Side, TimeInForce, TraderId};
 
 fn main() {
     println!("=== 高性能订单簿演示 ===\n");
@@ -31,6 +31,14 @@ fn main() {
     market_depth_demo();
 }
 
+fn px(ticks: u64) -> Price {
+    Price::new(ticks).expect("price must be non-zero")
+}
+
+fn qty(lots: u32) -> Quantity {
+    Quantity::new(lots).expect("quantity must be non-zero")
+}
+
 fn basic_matching_demo() {
     println!("1. 基础订单匹配");
     println!("   创建新订单簿并匹配订单...\n");
@@ -40,14 +48,15 @@ fn basic_matching_demo() {
     // 放置卖单
     let seller = TraderId::from_str("ALICE");
     println!("   ALICE 放置卖单: 100 @ $100.00");
-    book.limit_order(seller, Side::Sell, 10000, 100);
+    book.limit_order(seller, Side::Sell, px(10000), qty(100), TimeInForce::Gtc).unwrap();
 
-    println!("   最佳卖价: ${:.2}", book.best_ask().unwrap() as f64 / 100.0);
+    let conv = PriceConverter::cents();
+    println!("   最佳卖价: ${}", conv.format(book.best_ask().unwrap()));
 
     // 放置匹配的买单
     let buyer = TraderId::from_str("BOB");
     println!("\n   BOB 放置买单: 100 @ $100.00");
-    let (_order_id, trades) = book.limit_order(buyer, Side::Buy, 10000, 100);
+    let (_order_id, trades) = book.limit_order(buyer, Side::Buy, px(10000), qty(100), TimeInForce::Gtc).unwrap();
 
     println!("\n   ✅ 交易成功执行:");
     for trade in &trades {
@@ -68,21 +77,22 @@ fn partial_fill_demo() {
     // 放置大额卖单
     let seller = TraderId::from_str("CAROL");
     println!("   CAROL 放置卖单: 500 @ $99.50");
-    book.limit_order(seller, Side::Sell, 9950, 500);
+    book.limit_order(seller, Side::Sell, px(9950), qty(500), TimeInForce::Gtc).unwrap();
 
     // 放置较小的买单
     let buyer = TraderId::from_str("DAVE");
     println!("   DAVE 放置买单: 200 @ $99.50\n");
-    let (_order_id, trades) = book.limit_order(buyer, Side::Buy, 9950, 200);
+    let (_order_id, trades) = book.limit_order(buyer, Side::Buy, px(9950), qty(200), TimeInForce::Gtc).unwrap();
 
     println!("   ✅ 部分成交:");
     for trade in &trades {
         println!("      {}", trade);
     }
 
+    let conv = PriceConverter::cents();
     println!(
-        "\n   订单簿剩余: 300 @ ${:.2}",
-        book.best_ask().unwrap() as f64 / 100.0
+        "\n   订单簿剩余: 300 @ ${}",
+        conv.format(book.best_ask().unwrap())
     );
 }
 
@@ -95,26 +105,27 @@ fn price_improvement_demo() {
     // 在$100放置卖单
     let seller = TraderId::from_str("EVE");
     println!("   EVE 放置卖单: 100 @ $100.00");
-    book.limit_order(seller, Side::Sell, 10000, 100);
+    book.limit_order(seller, Side::Sell, px(10000), qty(100), TimeInForce::Gtc).unwrap();
 
     // 以更高价格放置买单
     let buyer = TraderId::from_str("FRANK");
     println!("   FRANK 放置买单: 100 @ $101.00 (愿意支付更多)\n");
-    let (_order_id, trades) = book.limit_order(buyer, Side::Buy, 10100, 100);
+    let (_order_id, trades) = book.limit_order(buyer, Side::Buy, px(10100), qty(100), TimeInForce::Gtc).unwrap();
 
+    let conv = PriceConverter::cents();
     println!("   ✅ 价格改善成交:");
     for trade in &trades {
         println!(
-            "      成交价 ${:.2} (节省 ${:.2})",
-            trade.price as f64 / 100.0,
-            (10100 - trade.price) as f64 / 100.0
+            "      成交价 ${} (节省 ${})",
+            conv.format(trade.price),
+            conv.format_ticks(px(10100).ticks_above(trade.price).unwrap_or(0))
         );
     }
 }
 
 fn cancellation_demo() {
     println!("4. 订单取消");
-    println!("   使用单次内存写入快速取消订单...\n");
+    println!("   从价格档位双向链表O(1)摘除订单...\n");
 
     let mut book = OrderBook::new();
 
@@ -122,22 +133,22 @@ fn cancellation_demo() {
 
     // 放置多个订单
     println!("   GRACE 放置 3 个买单:");
-    let (id1, _) = book.limit_order(trader, Side::Buy, 9900, 100);
+    let (id1, _) = book.limit_order(trader, Side::Buy, px(9900), qty(100), TimeInForce::Gtc).unwrap();
     println!("      订单 #{}: 100 @ $99.00", id1);
 
-    let (id2, _) = book.limit_order(trader, Side::Buy, 9950, 200);
+    let (id2, _) = book.limit_order(trader, Side::Buy, px(9950), qty(200), TimeInForce::Gtc).unwrap();
     println!("      订单 #{}: 200 @ $99.50", id2);
 
-    let (id3, _) = book.limit_order(trader, Side::Buy, 10000, 150);
+    let (id3, _) = book.limit_order(trader, Side::Buy, px(10000), qty(150), TimeInForce::Gtc).unwrap();
     println!("      订单 #{}: 150 @ $100.00", id3);
 
     // 取消中间订单
     println!("\n   取消订单 #{}...", id2);
-    let cancelled = book.cancel_order(id2);
+    let cancelled = book.cancel_order(id2).is_ok();
     println!("   ✅ 已取消: {}", cancelled);
 
     // 尝试再次取消
-    let cancelled_again = book.cancel_order(id2);
+    let cancelled_again = book.cancel_order(id2).is_ok();
     println!("   再次取消: {} (已经取消)", cancelled_again);
 }
 
@@ -149,34 +160,35 @@ fn market_depth_demo() {
 
     // 构建买方深度
     println!("   构建买单深度:");
-    book.limit_order(TraderId::from_str("B1"), Side::Buy, 9900, 100);
+    book.limit_order(TraderId::from_str("B1"), Side::Buy, px(9900), qty(100), TimeInForce::Gtc).unwrap();
     println!("      100 @ $99.00");
-    book.limit_order(TraderId::from_str("B2"), Side::Buy, 9950, 200);
+    book.limit_order(TraderId::from_str("B2"), Side::Buy, px(9950), qty(200), TimeInForce::Gtc).unwrap();
     println!("      200 @ $99.50");
-    book.limit_order(TraderId::from_str("B3"), Side::Buy, 9980, 150);
+    book.limit_order(TraderId::from_str("B3"), Side::Buy, px(9980), qty(150), TimeInForce::Gtc).unwrap();
     println!("      150 @ $99.80");
 
     // 构建卖方深度
     println!("\n   构建卖单深度:");
-    book.limit_order(TraderId::from_str("S1"), Side::Sell, 10020, 120);
+    book.limit_order(TraderId::from_str("S1"), Side::Sell, px(10020), qty(120), TimeInForce::Gtc).unwrap();
     println!("      120 @ $100.20");
-    book.limit_order(TraderId::from_str("S2"), Side::Sell, 10050, 180);
+    book.limit_order(TraderId::from_str("S2"), Side::Sell, px(10050), qty(180), TimeInForce::Gtc).unwrap();
     println!("      180 @ $100.50");
-    book.limit_order(TraderId::from_str("S3"), Side::Sell, 10100, 250);
+    book.limit_order(TraderId::from_str("S3"), Side::Sell, px(10100), qty(250), TimeInForce::Gtc).unwrap();
     println!("      250 @ $101.00");
 
     // 显示市场统计
+    let conv = PriceConverter::cents();
     println!("\n   📊 市场统计:");
     if let Some(bid) = book.best_bid() {
-        println!("      最佳买价:  ${:.2}", bid as f64 / 100.0);
+        println!("      最佳买价:  ${}", conv.format(bid));
     }
     if let Some(ask) = book.best_ask() {
-        println!("      最佳卖价:  ${:.2}", ask as f64 / 100.0);
+        println!("      最佳卖价:  ${}", conv.format(ask));
     }
     if let Some(spread) = book.spread() {
-        println!("      价差:      ${:.2}", spread as f64 / 100.0);
+        println!("      价差:      ${}", conv.format_ticks(spread));
     }
     if let Some(mid) = book.mid_price() {
-        println!("      中间价:    ${:.2}", mid as f64 / 100.0);
+        println!("      中间价:    ${}", conv.format(mid));
     }
 }
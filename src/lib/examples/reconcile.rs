@@ -0,0 +1,40 @@
+/// 组播抓包与指令日志核对工具
+///
+/// 用法: cargo run --example reconcile -- <指令日志文件> <组播抓包文件>
+///
+/// 输出核对报告；存在差异时以退出码1结束。
+
+use lib::multicase::domain::capture::CaptureReader;
+use lib::orderbook::{reconcile, wal_trades, JournalReader};
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: {} <journal> <capture>", args[0]);
+        return ExitCode::from(2);
+    }
+
+    match run(&args[1], &args[2]) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("reconcile failed: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(journal_path: &str, capture_path: &str) -> io::Result<bool> {
+    let journal = BufReader::new(File::open(journal_path)?);
+    let wal = wal_trades(JournalReader::new(journal))?;
+
+    let capture = BufReader::new(File::open(capture_path)?);
+    let messages = CaptureReader::new(capture).collect::<io::Result<Vec<_>>>()?;
+
+    let report = reconcile(&wal, messages);
+    print!("{}", report);
+    Ok(report.is_clean())
+}
@@ -12,3 +12,13 @@ pub mod exchange;
 pub mod multicast_v4;
 
 pub mod orderbook;
+
+pub mod tsdb;
+
+pub mod monitor;
+
+pub mod timing;
+
+pub mod testkit;
+
+pub mod loadgen;
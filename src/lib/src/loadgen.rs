@@ -0,0 +1,274 @@
+/// 压测负载生成
+///
+/// 用`testkit::OrderFlow`的合成订单流（泊松到达、中间价附近的价格分布、撤单比例）驱动撮合引擎，
+/// 统计吞吐量和逐条指令时延，用于容量规划:
+/// - `run_local`: 在当前线程直接调用`OrderBook`，时延为单条指令的撮合耗时
+/// - `run_tcp`: 经`TradingClient`发往`MatchingEngineServer`，时延为提交到收到首个回报的往返时间，
+///   最多`max_in_flight`条指令同时在途
+///
+/// 指令尽快提交（开环压测），订单流中的到达时间只决定指令内容，不用于限速。
+
+use crate::message::domain::ouch::{OuchRequest, OuchResponse};
+use crate::message::domain::wire::alpha8;
+use crate::orderbook::{Command, CommandResult, LatencyPercentiles, OrderBook, OrderId};
+use crate::testkit::OrderFlow;
+use crate::unicase::domain::unicase::UnicastError;
+use crate::unicase::outbound::trading_client::{TradingClient, TradingEvent};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// TCP压测参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TcpLoadConfig {
+    /// 品种代码（与服务端一致）
+    pub symbol: String,
+    /// 指令数
+    pub commands: usize,
+    /// 最多同时在途的指令数
+    pub max_in_flight: usize,
+    /// 等待回报的超时
+    pub response_timeout: Duration,
+}
+
+impl Default for TcpLoadConfig {
+    fn default() -> Self {
+        Self {
+            symbol: "BTCUSDT".to_string(),
+            commands: 10_000,
+            max_in_flight: 64,
+            response_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// 压测结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    /// 已完成的指令数
+    pub commands: u64,
+    /// 被拒绝的指令数（含撤销不存在的订单）
+    pub rejected: u64,
+    /// 被服务端限流的指令数
+    pub throttled: u64,
+    /// 成交笔数
+    pub trades: u64,
+    /// 总耗时
+    pub elapsed: Duration,
+    /// 逐条指令时延分位数
+    pub latency: LatencyPercentiles,
+}
+
+impl LoadReport {
+    /// 吞吐量（指令/秒）
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 { self.commands as f64 / secs } else { 0.0 }
+    }
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} commands in {:.3}s ({:.0}/s), {} rejected, {} throttled, {} trades, latency ns p50={} p99={} p99.9={} max={}",
+            self.commands,
+            self.elapsed.as_secs_f64(),
+            self.throughput(),
+            self.rejected,
+            self.throttled,
+            self.trades,
+            self.latency.p50_ns,
+            self.latency.p99_ns,
+            self.latency.p999_ns,
+            self.latency.max_ns,
+        )
+    }
+}
+
+/// 时延样本（结束时排序求分位数）
+#[derive(Debug, Default)]
+struct Samples(Vec<u64>);
+
+impl Samples {
+    fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
+    fn record(&mut self, latency_ns: u64) {
+        self.0.push(latency_ns);
+    }
+
+    fn percentiles(mut self) -> LatencyPercentiles {
+        if self.0.is_empty() {
+            return LatencyPercentiles::default();
+        }
+        self.0.sort_unstable();
+        let at = |q: f64| self.0[((self.0.len() as f64 * q).ceil() as usize).clamp(1, self.0.len()) - 1];
+        LatencyPercentiles {
+            count: self.0.len() as u64,
+            p50_ns: at(0.50),
+            p90_ns: at(0.90),
+            p99_ns: at(0.99),
+            p999_ns: at(0.999),
+            max_ns: self.0[self.0.len() - 1],
+        }
+    }
+}
+
+/// 在订单簿上直接执行`commands`条指令
+pub fn run_local(flow: &mut OrderFlow, book: &mut OrderBook, commands: usize) -> LoadReport {
+    let mut report = LoadReport::default();
+    let mut samples = Samples::with_capacity(commands);
+    let started = Instant::now();
+
+    for _ in 0..commands {
+        let event = flow.next_event();
+        let ticks = crate::timing::ticks();
+        let result = event.command.execute(book);
+        samples.record(crate::timing::elapsed_ns(ticks));
+
+        match &result {
+            CommandResult::Accepted { trades, .. } => report.trades += trades.len() as u64,
+            CommandResult::Rejected(_) | CommandResult::Cancelled { success: false, .. } => report.rejected += 1,
+            _ => {}
+        }
+        flow.observe(&result);
+        report.commands += 1;
+    }
+
+    report.elapsed = started.elapsed();
+    report.latency = samples.percentiles();
+    report
+}
+
+/// 经TCP向撮合服务提交订单流（`client`需已连接）
+///
+/// 成交按本方作为主动方的成交回报计数；最后一条指令的首个回报之后到达的回报不计入。
+pub async fn run_tcp(
+    flow: &mut OrderFlow,
+    client: &mut TradingClient,
+    config: &TcpLoadConfig,
+) -> Result<LoadReport, UnicastError> {
+    let symbol = alpha8(&config.symbol);
+    let mut report = LoadReport::default();
+    let mut samples = Samples::with_capacity(config.commands);
+    // 在途指令的提交时间
+    let mut in_flight: HashMap<u64, Instant> = HashMap::with_capacity(config.max_in_flight);
+    // 最近受理的(消息ID, 订单ID)，其后同一消息的成交回报为主动方成交
+    let mut taker: Option<(u64, OrderId)> = None;
+    let mut submitted = 0;
+    let started = Instant::now();
+
+    while submitted < config.commands || !in_flight.is_empty() {
+        if submitted < config.commands && in_flight.len() < config.max_in_flight.max(1) {
+            let request = match flow.next_event().command {
+                Command::Limit { trader, side, price, quantity, tif } => OuchRequest::EnterOrder {
+                    token: submitted as u64 + 1,
+                    trader,
+                    symbol,
+                    side,
+                    quantity,
+                    price,
+                    tif,
+                },
+                Command::Cancel { order_id } => OuchRequest::CancelOrder { order_id, quantity: 0 },
+                // 订单流只生成限价单和撤单
+                _ => continue,
+            };
+            let message_id = client.submit(&request).await?;
+            in_flight.insert(message_id, Instant::now());
+            submitted += 1;
+            continue;
+        }
+
+        let event = tokio::time::timeout(config.response_timeout, client.next_event())
+            .await
+            .map_err(|_| UnicastError::Timeout)??;
+        match event {
+            TradingEvent::Response { message_id, response } => {
+                if let Some(sent_at) = in_flight.remove(&message_id) {
+                    samples.record(sent_at.elapsed().as_nanos() as u64);
+                    report.commands += 1;
+                }
+                match response {
+                    OuchResponse::Accepted { order_id, .. } => {
+                        taker = Some((message_id, order_id));
+                        flow.track(order_id);
+                    }
+                    OuchResponse::Executed { order_id, .. } if taker == Some((message_id, order_id)) => {
+                        report.trades += 1;
+                    }
+                    OuchResponse::Rejected { .. } => report.rejected += 1,
+                    _ => {}
+                }
+            }
+            TradingEvent::Throttled { message_id, .. } => {
+                if let Some(sent_at) = in_flight.remove(&message_id) {
+                    samples.record(sent_at.elapsed().as_nanos() as u64);
+                    report.commands += 1;
+                }
+                report.throttled += 1;
+            }
+        }
+    }
+
+    report.elapsed = started.elapsed();
+    report.latency = samples.percentiles();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{FlowConfig, Scenario};
+    use crate::unicase::domain::unicase::TcpConfig;
+    use crate::unicase::outbound::engine_server::MatchingEngineServer;
+
+    fn book() -> OrderBook {
+        OrderBook::with_capacity(FlowConfig::default().max_price as usize + 1, 100_000)
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut samples = Samples::default();
+        for latency in (1..=1_000).rev() {
+            samples.record(latency);
+        }
+        let latency = samples.percentiles();
+        assert_eq!((latency.count, latency.p50_ns, latency.p99_ns, latency.max_ns), (1_000, 500, 990, 1_000));
+        assert_eq!(Samples::default().percentiles(), LatencyPercentiles::default());
+    }
+
+    #[test]
+    fn test_run_local() {
+        let mut flow = OrderFlow::scenario(Scenario::Volatile, 5);
+        let report = run_local(&mut flow, &mut book(), 5_000);
+        assert_eq!((report.commands, report.latency.count), (5_000, 5_000));
+        assert!(report.trades > 0);
+        assert!(report.throughput() > 0.0);
+        assert!(report.latency.p50_ns <= report.latency.max_ns);
+        assert!(report.to_string().starts_with("5000 commands"));
+    }
+
+    #[tokio::test]
+    async fn test_run_tcp() {
+        let mut server = MatchingEngineServer::new("127.0.0.1:0".parse().unwrap(), "BTCUSDT", book());
+        server.start().await.unwrap();
+        let mut client = TradingClient::new(TcpConfig {
+            server_addr: server.local_addr().unwrap(),
+            ..Default::default()
+        });
+        client.connect().await.unwrap();
+
+        let config = TcpLoadConfig { commands: 500, max_in_flight: 8, ..TcpLoadConfig::default() };
+        let mut flow = OrderFlow::scenario(Scenario::Volatile, 5);
+        let report = run_tcp(&mut flow, &mut client, &config).await.unwrap();
+        assert_eq!((report.commands, report.latency.count), (500, 500));
+        assert_eq!(report.throttled, 0);
+        assert!(report.trades > 0);
+
+        client.disconnect().await.unwrap();
+        server.stop().await.unwrap();
+    }
+}
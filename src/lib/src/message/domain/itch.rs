@@ -0,0 +1,268 @@
+/// ITCH风格的逐笔行情二进制协议
+///
+/// 参照NASDAQ ITCH的定长、大端报文，把订单簿事件（`BookEvent`）编码为匿名的L3行情，
+/// 经现有UDP组播传输发布。下游按订单ID维护完整的逐笔订单簿。
+///
+/// 公共头: 类型1 + 品种编号2 + 时间戳8（纳秒）
+/// - `A` 新增: 订单ID8 + 方向1 + 数量4 + 价格8
+/// - `E` 成交: 订单ID8 + 成交数量4 + 成交价8 + 剩余数量4
+/// - `X` 减量（保留优先级）: 订单ID8 + 减少数量4
+/// - `U` 改单（失去优先级）: 订单ID8 + 新数量4 + 新价格8
+/// - `D` 删除（撤单或到期）: 订单ID8
+/// - `H` 交易状态: 状态1（N正常 / C只撤单 / H熔断）
+///
+/// 编解码直接读写调用方提供的缓冲区，不分配内存。
+
+use super::wire::{CodecError, Reader, Writer};
+use crate::orderbook::{BookEvent, OrderId, Price, Quantity, Side, TradingMode};
+
+/// 公共头长度
+pub const HEADER_LEN: usize = 1 + 2 + 8;
+/// 最长报文长度
+pub const MAX_MESSAGE_LEN: usize = HEADER_LEN + 24;
+
+/// 报文体
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItchBody {
+    AddOrder { order_id: OrderId, side: Side, quantity: Quantity, price: Price },
+    OrderExecuted { order_id: OrderId, quantity: Quantity, price: Price, remaining: u32 },
+    OrderCancel { order_id: OrderId, cancelled: Quantity },
+    OrderReplace { order_id: OrderId, quantity: Quantity, price: Price },
+    OrderDelete { order_id: OrderId },
+    TradingAction { mode: TradingMode },
+}
+
+/// 一条行情报文
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItchMessage {
+    /// 品种编号（由发布方分配）
+    pub locate: u16,
+    pub timestamp_ns: u64,
+    pub body: ItchBody,
+}
+
+impl ItchBody {
+    /// 订单簿事件对应的报文体；拒单和容量告警不属于行情，返回None
+    pub fn from_event(event: &BookEvent) -> Option<Self> {
+        let body = match *event {
+            BookEvent::OrderAdded { order_id, side, price, quantity, .. } => {
+                ItchBody::AddOrder { order_id, side, quantity, price }
+            }
+            BookEvent::OrderExecuted { order_id, price, quantity, remaining, .. } => ItchBody::OrderExecuted {
+                order_id,
+                quantity,
+                price,
+                remaining: remaining.get(),
+            },
+            BookEvent::OrderCancelled { order_id, .. } | BookEvent::OrderExpired { order_id, .. } => {
+                ItchBody::OrderDelete { order_id }
+            }
+            // 保留优先级的改单价格不变，报文不携带价格
+            BookEvent::OrderAmended { order_id, old_quantity, new_quantity, priority_retained: true, .. } => {
+                ItchBody::OrderCancel {
+                    order_id,
+                    cancelled: Quantity::new(old_quantity.get().saturating_sub(new_quantity.get()))?,
+                }
+            }
+            BookEvent::OrderAmended { order_id, new_quantity, new_price, .. } => ItchBody::OrderReplace {
+                order_id,
+                quantity: new_quantity,
+                price: new_price,
+            },
+            BookEvent::ModeChanged { mode } => ItchBody::TradingAction { mode },
+            BookEvent::OrderRejected { .. } | BookEvent::CapacityWarning { .. } => return None,
+        };
+        Some(body)
+    }
+
+    fn kind(&self) -> u8 {
+        match self {
+            ItchBody::AddOrder { .. } => b'A',
+            ItchBody::OrderExecuted { .. } => b'E',
+            ItchBody::OrderCancel { .. } => b'X',
+            ItchBody::OrderReplace { .. } => b'U',
+            ItchBody::OrderDelete { .. } => b'D',
+            ItchBody::TradingAction { .. } => b'H',
+        }
+    }
+
+    fn body_len(kind: u8) -> Result<usize, CodecError> {
+        match kind {
+            b'A' => Ok(8 + 1 + 4 + 8),
+            b'E' => Ok(8 + 4 + 8 + 4),
+            b'X' => Ok(8 + 4),
+            b'U' => Ok(8 + 4 + 8),
+            b'D' => Ok(8),
+            b'H' => Ok(1),
+            other => Err(CodecError::UnknownType(other)),
+        }
+    }
+}
+
+impl ItchMessage {
+    /// 由订单簿事件构造报文
+    pub fn from_event(locate: u16, timestamp_ns: u64, event: &BookEvent) -> Option<Self> {
+        Some(Self {
+            locate,
+            timestamp_ns,
+            body: ItchBody::from_event(event)?,
+        })
+    }
+
+    /// 编码长度
+    pub fn encoded_len(&self) -> usize {
+        HEADER_LEN + ItchBody::body_len(self.body.kind()).unwrap_or(0)
+    }
+
+    /// 编码到`buf`，返回写入的字节数
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, CodecError> {
+        let mut w = Writer::new(buf, self.encoded_len())?;
+        w.u8(self.body.kind()).u16(self.locate).u64(self.timestamp_ns);
+        match self.body {
+            ItchBody::AddOrder { order_id, side, quantity, price } => {
+                w.u64(order_id).u8(side as u8).u32(quantity.get()).u64(price.get());
+            }
+            ItchBody::OrderExecuted { order_id, quantity, price, remaining } => {
+                w.u64(order_id).u32(quantity.get()).u64(price.get()).u32(remaining);
+            }
+            ItchBody::OrderCancel { order_id, cancelled } => {
+                w.u64(order_id).u32(cancelled.get());
+            }
+            ItchBody::OrderReplace { order_id, quantity, price } => {
+                w.u64(order_id).u32(quantity.get()).u64(price.get());
+            }
+            ItchBody::OrderDelete { order_id } => {
+                w.u64(order_id);
+            }
+            ItchBody::TradingAction { mode } => {
+                w.u8(match mode {
+                    TradingMode::Normal => b'N',
+                    TradingMode::CancelOnly => b'C',
+                    TradingMode::Halted => b'H',
+                });
+            }
+        }
+        Ok(w.len())
+    }
+
+    /// 从`buf`开头解码一条报文，返回报文及其长度
+    pub fn decode(buf: &[u8]) -> Result<(Self, usize), CodecError> {
+        let kind = *buf.first().ok_or(CodecError::Truncated { needed: HEADER_LEN, available: 0 })?;
+        let len = HEADER_LEN + ItchBody::body_len(kind)?;
+        let mut r = Reader::new(buf, len)?;
+        r.u8();
+        let locate = r.u16();
+        let timestamp_ns = r.u64();
+
+        let quantity = |value: u32| Quantity::new(value).ok_or(CodecError::InvalidField("quantity"));
+        let price = |value: u64| Price::new(value).ok_or(CodecError::InvalidField("price"));
+        let body = match kind {
+            b'A' => ItchBody::AddOrder {
+                order_id: r.u64(),
+                side: match r.u8() {
+                    b'B' => Side::Buy,
+                    b'S' => Side::Sell,
+                    _ => return Err(CodecError::InvalidField("side")),
+                },
+                quantity: quantity(r.u32())?,
+                price: price(r.u64())?,
+            },
+            b'E' => ItchBody::OrderExecuted {
+                order_id: r.u64(),
+                quantity: quantity(r.u32())?,
+                price: price(r.u64())?,
+                remaining: r.u32(),
+            },
+            b'X' => ItchBody::OrderCancel {
+                order_id: r.u64(),
+                cancelled: quantity(r.u32())?,
+            },
+            b'U' => ItchBody::OrderReplace {
+                order_id: r.u64(),
+                quantity: quantity(r.u32())?,
+                price: price(r.u64())?,
+            },
+            b'D' => ItchBody::OrderDelete { order_id: r.u64() },
+            _ => ItchBody::TradingAction {
+                mode: match r.u8() {
+                    b'N' => TradingMode::Normal,
+                    b'C' => TradingMode::CancelOnly,
+                    b'H' => TradingMode::Halted,
+                    _ => return Err(CodecError::InvalidField("trading state")),
+                },
+            },
+        };
+        Ok((Self { locate, timestamp_ns, body }, len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty, TimeInForce, TraderId};
+    use crate::orderbook::{BookEventListener, OrderBook};
+    use std::sync::{Arc, Mutex};
+
+    struct Encoder(Arc<Mutex<Vec<u8>>>);
+
+    impl BookEventListener for Encoder {
+        fn on_event(&mut self, event: &BookEvent) {
+            let mut buf = [0u8; MAX_MESSAGE_LEN];
+            if let Some(message) = ItchMessage::from_event(3, 42, event) {
+                let len = message.encode(&mut buf).unwrap();
+                self.0.lock().unwrap().extend_from_slice(&buf[..len]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_event_stream_roundtrip() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let stream = Arc::new(Mutex::new(Vec::new()));
+        book.set_event_listener(Box::new(Encoder(Arc::clone(&stream))));
+
+        let mm = TraderId::from_str("MM");
+        let (ask, _) = book.limit_order(mm, Side::Sell, px(101), qty(10), TimeInForce::Gtc).unwrap();
+        book.reduce_order(ask, qty(8));
+        book.replace_order(ask, px(102), qty(8));
+        book.limit_order(TraderId::from_str("TK"), Side::Buy, px(102), qty(3), TimeInForce::Ioc).unwrap();
+        book.cancel_order(ask).unwrap();
+
+        let stream = stream.lock().unwrap();
+        let mut offset = 0;
+        let mut bodies = Vec::new();
+        while offset < stream.len() {
+            let (message, len) = ItchMessage::decode(&stream[offset..]).unwrap();
+            assert_eq!((message.locate, message.timestamp_ns), (3, 42));
+            bodies.push(message.body);
+            offset += len;
+        }
+        assert_eq!(bodies, vec![
+            ItchBody::AddOrder { order_id: ask, side: Side::Sell, quantity: qty(10), price: px(101) },
+            ItchBody::OrderCancel { order_id: ask, cancelled: qty(2) },
+            ItchBody::OrderReplace { order_id: ask, quantity: qty(8), price: px(102) },
+            ItchBody::OrderExecuted { order_id: ask, quantity: qty(3), price: px(102), remaining: 5 },
+            ItchBody::OrderDelete { order_id: ask },
+        ]);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let message = ItchMessage {
+            locate: 1,
+            timestamp_ns: 2,
+            body: ItchBody::TradingAction { mode: TradingMode::Halted },
+        };
+        let mut buf = [0u8; MAX_MESSAGE_LEN];
+        let len = message.encode(&mut buf).unwrap();
+        assert_eq!(len, HEADER_LEN + 1);
+        assert_eq!(ItchMessage::decode(&buf[..len]).unwrap(), (message, len));
+        assert_eq!(
+            ItchMessage::decode(&buf[..len - 1]),
+            Err(CodecError::Truncated { needed: len, available: len - 1 })
+        );
+        buf[0] = b'?';
+        assert_eq!(ItchMessage::decode(&buf[..len]), Err(CodecError::UnknownType(b'?')));
+        assert!(message.encode(&mut buf[..4]).is_err());
+    }
+}
@@ -1 +1,4 @@
-pub mod message;
\ No newline at end of file
+pub mod itch;
+pub mod message;
+pub mod ouch;
+pub mod wire;
\ No newline at end of file
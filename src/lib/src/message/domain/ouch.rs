@@ -0,0 +1,370 @@
+/// OUCH风格的下单二进制协议
+///
+/// 参照NASDAQ OUCH的定长、大端报文，承载于现有TCP单播传输之上:
+/// - 入站: 下单(`O`)、撤单/减量(`X`)
+/// - 出站: 受理(`A`)、成交(`E`)、撤销/减量确认(`C`)、拒绝(`J`)
+///
+/// 编解码直接读写调用方提供的缓冲区，不分配内存。
+///
+/// 报文布局（字节）:
+/// - `O`: 类型1 + 客户令牌8 + 交易员8 + 品种8 + 方向1 + 数量4 + 价格8 + 有效期1 + GTD到期时间8
+/// - `X`: 类型1 + 订单ID8 + 剩余数量4（0表示全部撤销）
+/// - `A`: 类型1 + 客户令牌8 + 订单ID8
+/// - `E`: 类型1 + 订单ID8 + 数量4 + 价格8 + 成交ID8
+/// - `C`: 类型1 + 订单ID8 + 剩余数量4
+/// - `J`: 类型1 + 客户令牌或订单ID8 + 原因1
+
+use super::wire::{alpha8_str, Alpha8, CodecError, Reader, Writer};
+use crate::orderbook::{Command, CommandResult, OrderId, Price, Quantity, RejectReason, Side, TimeInForce, Trade, TraderId};
+
+pub const ENTER_ORDER_LEN: usize = 47;
+pub const CANCEL_ORDER_LEN: usize = 13;
+pub const ACCEPTED_LEN: usize = 17;
+pub const EXECUTED_LEN: usize = 29;
+pub const CANCELED_LEN: usize = 13;
+pub const REJECTED_LEN: usize = 10;
+
+/// 拒绝原因：撤单的订单不存在、已完结或不属于本连接
+pub const REJECT_UNKNOWN_ORDER: u8 = 0xFF;
+/// 拒绝原因：报文无法解码（`reference`为0）
+pub const REJECT_MALFORMED: u8 = 0xFE;
+
+const TIF_GTC: u8 = 0;
+const TIF_IOC: u8 = 1;
+const TIF_FOK: u8 = 2;
+const TIF_GTD: u8 = 3;
+
+/// 入站报文
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OuchRequest {
+    EnterOrder {
+        /// 客户端令牌，受理和拒绝时原样返回
+        token: u64,
+        trader: TraderId,
+        symbol: Alpha8,
+        side: Side,
+        quantity: Quantity,
+        price: Price,
+        tif: TimeInForce,
+    },
+    /// `quantity`为撤单后希望保留的数量
+    CancelOrder { order_id: OrderId, quantity: u32 },
+}
+
+impl OuchRequest {
+    /// 编码到`buf`，返回写入的字节数
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, CodecError> {
+        match *self {
+            OuchRequest::EnterOrder { token, trader, symbol, side, quantity, price, tif } => {
+                let (tif, expires_at) = match tif {
+                    TimeInForce::Gtc => (TIF_GTC, 0),
+                    TimeInForce::Ioc => (TIF_IOC, 0),
+                    TimeInForce::Fok => (TIF_FOK, 0),
+                    TimeInForce::Gtd(expires_at) => (TIF_GTD, expires_at),
+                };
+                let mut w = Writer::new(buf, ENTER_ORDER_LEN)?;
+                w.u8(b'O')
+                    .u64(token)
+                    .bytes(trader.as_bytes())
+                    .bytes(&symbol)
+                    .u8(side as u8)
+                    .u32(quantity.get())
+                    .u64(price.get())
+                    .u8(tif)
+                    .u64(expires_at);
+                Ok(w.len())
+            }
+            OuchRequest::CancelOrder { order_id, quantity } => {
+                let mut w = Writer::new(buf, CANCEL_ORDER_LEN)?;
+                w.u8(b'X').u64(order_id).u32(quantity);
+                Ok(w.len())
+            }
+        }
+    }
+
+    /// 从`buf`开头解码一条报文，返回报文及其长度
+    pub fn decode(buf: &[u8]) -> Result<(Self, usize), CodecError> {
+        match buf.first() {
+            Some(b'O') => {
+                let mut r = Reader::new(buf, ENTER_ORDER_LEN)?;
+                r.u8();
+                let token = r.u64();
+                let trader = TraderId::new(r.array());
+                let symbol = r.array();
+                let side = side(r.u8())?;
+                let quantity = Quantity::new(r.u32()).ok_or(CodecError::InvalidField("quantity"))?;
+                let price = Price::new(r.u64()).ok_or(CodecError::InvalidField("price"))?;
+                let (tif, expires_at) = (r.u8(), r.u64());
+                let tif = match tif {
+                    TIF_GTC => TimeInForce::Gtc,
+                    TIF_IOC => TimeInForce::Ioc,
+                    TIF_FOK => TimeInForce::Fok,
+                    TIF_GTD => TimeInForce::Gtd(expires_at),
+                    _ => return Err(CodecError::InvalidField("time in force")),
+                };
+                let request = OuchRequest::EnterOrder { token, trader, symbol, side, quantity, price, tif };
+                Ok((request, ENTER_ORDER_LEN))
+            }
+            Some(b'X') => {
+                let mut r = Reader::new(buf, CANCEL_ORDER_LEN)?;
+                r.u8();
+                let request = OuchRequest::CancelOrder {
+                    order_id: r.u64(),
+                    quantity: r.u32(),
+                };
+                Ok((request, CANCEL_ORDER_LEN))
+            }
+            Some(&other) => Err(CodecError::UnknownType(other)),
+            None => Err(CodecError::Truncated { needed: 1, available: 0 }),
+        }
+    }
+
+    /// 下单报文的品种（撤单按订单ID路由，返回None）
+    pub fn symbol(&self) -> Option<&str> {
+        match self {
+            OuchRequest::EnterOrder { symbol, .. } => Some(alpha8_str(symbol)),
+            OuchRequest::CancelOrder { .. } => None,
+        }
+    }
+
+    /// 对应的引擎指令（保留数量大于0的撤单为减量改单）
+    pub fn command(&self) -> Command {
+        match *self {
+            OuchRequest::EnterOrder { trader, side, quantity, price, tif, .. } => Command::Limit {
+                trader,
+                side,
+                price,
+                quantity,
+                tif,
+            },
+            OuchRequest::CancelOrder { order_id, quantity } => match Quantity::new(quantity) {
+                Some(quantity) => Command::Amend { order_id, quantity },
+                None => Command::Cancel { order_id },
+            },
+        }
+    }
+}
+
+fn side(value: u8) -> Result<Side, CodecError> {
+    match value {
+        b'B' => Ok(Side::Buy),
+        b'S' => Ok(Side::Sell),
+        _ => Err(CodecError::InvalidField("side")),
+    }
+}
+
+/// 拒绝原因编码
+pub fn reject_code(reason: RejectReason) -> u8 {
+    match reason {
+        RejectReason::TooLate => 1,
+        RejectReason::UnknownSymbol => 2,
+        RejectReason::CancelOnly => 3,
+        RejectReason::PriceOutOfRange => 4,
+        RejectReason::CapacityExhausted => 5,
+        RejectReason::Halted => 6,
+        RejectReason::OffTick => 7,
+        RejectReason::InvalidLotSize => 8,
+        RejectReason::PriceBand => 9,
+        RejectReason::ReduceOnly => 10,
+        RejectReason::DuplicateOrder => 11,
+    }
+}
+
+/// 出站报文
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OuchResponse {
+    Accepted { token: u64, order_id: OrderId },
+    Executed { order_id: OrderId, quantity: Quantity, price: Price, match_number: u64 },
+    Canceled { order_id: OrderId, remaining: u32 },
+    Rejected { reference: u64, reason: u8 },
+}
+
+impl OuchResponse {
+    /// 订单`order_id`在一笔成交中的成交回报
+    pub fn executed(trade: &Trade, order_id: OrderId) -> Self {
+        OuchResponse::Executed {
+            order_id,
+            quantity: trade.quantity,
+            price: trade.price,
+            match_number: trade.trade_id,
+        }
+    }
+
+    /// 请求执行结果对应的回报：受理/拒绝，随后是该订单作为主动方的成交
+    ///
+    /// 被动方的成交回报由其所属会话通过`executed`生成。
+    pub fn for_result<'a>(request: &OuchRequest, result: &'a CommandResult) -> impl Iterator<Item = OuchResponse> + 'a {
+        let (first, trades) = match (*request, result) {
+            (OuchRequest::EnterOrder { token, .. }, CommandResult::Accepted { order_id, trades }) => {
+                (OuchResponse::Accepted { token, order_id: *order_id }, trades.as_slice())
+            }
+            (OuchRequest::EnterOrder { token, .. }, CommandResult::Rejected(reason)) => {
+                (OuchResponse::Rejected { reference: token, reason: reject_code(*reason) }, &[][..])
+            }
+            (OuchRequest::CancelOrder { order_id, quantity }, CommandResult::Cancelled { success: true, .. })
+            | (OuchRequest::CancelOrder { order_id, quantity }, CommandResult::Amended { success: true, .. }) => {
+                (OuchResponse::Canceled { order_id, remaining: quantity }, &[][..])
+            }
+            (OuchRequest::CancelOrder { order_id, .. }, CommandResult::Rejected(reason)) => {
+                (OuchResponse::Rejected { reference: order_id, reason: reject_code(*reason) }, &[][..])
+            }
+            (OuchRequest::CancelOrder { order_id, .. }, _) | (OuchRequest::EnterOrder { token: order_id, .. }, _) => {
+                (OuchResponse::Rejected { reference: order_id, reason: REJECT_UNKNOWN_ORDER }, &[][..])
+            }
+        };
+        let taker = match first {
+            OuchResponse::Accepted { order_id, .. } => Some(order_id),
+            _ => None,
+        };
+        std::iter::once(first).chain(
+            trades
+                .iter()
+                .filter(move |trade| Some(trade.taker_order_id) == taker)
+                .map(move |trade| OuchResponse::executed(trade, trade.taker_order_id)),
+        )
+    }
+
+    /// 编码到`buf`，返回写入的字节数
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, CodecError> {
+        match *self {
+            OuchResponse::Accepted { token, order_id } => {
+                let mut w = Writer::new(buf, ACCEPTED_LEN)?;
+                w.u8(b'A').u64(token).u64(order_id);
+                Ok(w.len())
+            }
+            OuchResponse::Executed { order_id, quantity, price, match_number } => {
+                let mut w = Writer::new(buf, EXECUTED_LEN)?;
+                w.u8(b'E').u64(order_id).u32(quantity.get()).u64(price.get()).u64(match_number);
+                Ok(w.len())
+            }
+            OuchResponse::Canceled { order_id, remaining } => {
+                let mut w = Writer::new(buf, CANCELED_LEN)?;
+                w.u8(b'C').u64(order_id).u32(remaining);
+                Ok(w.len())
+            }
+            OuchResponse::Rejected { reference, reason } => {
+                let mut w = Writer::new(buf, REJECTED_LEN)?;
+                w.u8(b'J').u64(reference).u8(reason);
+                Ok(w.len())
+            }
+        }
+    }
+
+    /// 从`buf`开头解码一条报文，返回报文及其长度
+    pub fn decode(buf: &[u8]) -> Result<(Self, usize), CodecError> {
+        let response = match buf.first() {
+            Some(b'A') => {
+                let mut r = Reader::new(buf, ACCEPTED_LEN)?;
+                r.u8();
+                OuchResponse::Accepted { token: r.u64(), order_id: r.u64() }
+            }
+            Some(b'E') => {
+                let mut r = Reader::new(buf, EXECUTED_LEN)?;
+                r.u8();
+                OuchResponse::Executed {
+                    order_id: r.u64(),
+                    quantity: Quantity::new(r.u32()).ok_or(CodecError::InvalidField("quantity"))?,
+                    price: Price::new(r.u64()).ok_or(CodecError::InvalidField("price"))?,
+                    match_number: r.u64(),
+                }
+            }
+            Some(b'C') => {
+                let mut r = Reader::new(buf, CANCELED_LEN)?;
+                r.u8();
+                OuchResponse::Canceled { order_id: r.u64(), remaining: r.u32() }
+            }
+            Some(b'J') => {
+                let mut r = Reader::new(buf, REJECTED_LEN)?;
+                r.u8();
+                OuchResponse::Rejected { reference: r.u64(), reason: r.u8() }
+            }
+            Some(&other) => return Err(CodecError::UnknownType(other)),
+            None => return Err(CodecError::Truncated { needed: 1, available: 0 }),
+        };
+        Ok((response, response.encoded_len()))
+    }
+
+    /// 编码长度
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            OuchResponse::Accepted { .. } => ACCEPTED_LEN,
+            OuchResponse::Executed { .. } => EXECUTED_LEN,
+            OuchResponse::Canceled { .. } => CANCELED_LEN,
+            OuchResponse::Rejected { .. } => REJECTED_LEN,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::domain::wire::alpha8;
+    use crate::orderbook::types::{px, qty};
+    use crate::orderbook::OrderBook;
+
+    fn enter(token: u64, side: Side, price: u32, quantity: u32, tif: TimeInForce) -> OuchRequest {
+        OuchRequest::EnterOrder {
+            token,
+            trader: TraderId::from_str("T1"),
+            symbol: alpha8("BTCUSDT"),
+            side,
+            quantity: qty(quantity),
+            price: px(price),
+            tif,
+        }
+    }
+
+    #[test]
+    fn test_request_roundtrip() {
+        let mut buf = [0u8; 64];
+        for request in [
+            enter(7, Side::Buy, 100, 5, TimeInForce::Gtd(123)),
+            OuchRequest::CancelOrder { order_id: 9, quantity: 0 },
+        ] {
+            let len = request.encode(&mut buf).unwrap();
+            assert_eq!(OuchRequest::decode(&buf[..len]).unwrap(), (request, len));
+        }
+        assert_eq!(enter(7, Side::Buy, 100, 5, TimeInForce::Gtc).symbol(), Some("BTCUSDT"));
+
+        assert_eq!(
+            OuchRequest::decode(&buf[..5]),
+            Err(CodecError::Truncated { needed: CANCEL_ORDER_LEN, available: 5 })
+        );
+        assert_eq!(OuchRequest::decode(b"Z"), Err(CodecError::UnknownType(b'Z')));
+        assert!(enter(1, Side::Sell, 1, 1, TimeInForce::Gtc).encode(&mut buf[..10]).is_err());
+    }
+
+    #[test]
+    fn test_drive_engine() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let mut buf = [0u8; 64];
+
+        let maker = enter(1, Side::Sell, 100, 5, TimeInForce::Gtc);
+        let result = maker.command().execute(&mut book);
+        let responses: Vec<_> = OuchResponse::for_result(&maker, &result).collect();
+        assert_eq!(responses, vec![OuchResponse::Accepted { token: 1, order_id: 1 }]);
+
+        let taker = enter(2, Side::Buy, 100, 3, TimeInForce::Ioc);
+        let result = taker.command().execute(&mut book);
+        let responses: Vec<_> = OuchResponse::for_result(&taker, &result).collect();
+        assert_eq!(responses[1], OuchResponse::Executed { order_id: 2, quantity: qty(3), price: px(100), match_number: 1 });
+        for response in &responses {
+            let len = response.encode(&mut buf).unwrap();
+            assert_eq!(OuchResponse::decode(&buf[..len]).unwrap(), (*response, len));
+        }
+
+        // 保留数量大于0为减量，0为撤单
+        let reduce = OuchRequest::CancelOrder { order_id: 1, quantity: 1 };
+        assert_eq!(reduce.command(), Command::Amend { order_id: 1, quantity: qty(1) });
+        let result = reduce.command().execute(&mut book);
+        assert_eq!(OuchResponse::for_result(&reduce, &result).next(), Some(OuchResponse::Canceled { order_id: 1, remaining: 1 }));
+
+        let cancel = OuchRequest::CancelOrder { order_id: 1, quantity: 0 };
+        cancel.command().execute(&mut book);
+        let result = cancel.command().execute(&mut book);
+        assert_eq!(
+            OuchResponse::for_result(&cancel, &result).next(),
+            Some(OuchResponse::Rejected { reference: 1, reason: REJECT_UNKNOWN_ORDER })
+        );
+    }
+}
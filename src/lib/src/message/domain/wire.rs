@@ -0,0 +1,138 @@
+/// 定长二进制报文的读写工具
+///
+/// OUCH/ITCH风格的报文都是定长字段、大端字节序。读写前一次性检查缓冲区长度，
+/// 之后按固定偏移读写，不分配内存。
+
+use thiserror::Error;
+
+/// 编解码错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum CodecError {
+    #[error("buffer too short: need {needed} bytes, have {available}")]
+    Truncated { needed: usize, available: usize },
+    #[error("unknown message type {0:#04x}")]
+    UnknownType(u8),
+    #[error("invalid field {0}")]
+    InvalidField(&'static str),
+}
+
+/// 8字节字母数字字段（右补空格）
+pub type Alpha8 = [u8; 8];
+
+/// 字符串转为右补空格的8字节字段（超长截断）
+pub fn alpha8(s: &str) -> Alpha8 {
+    let mut field = [b' '; 8];
+    let len = s.len().min(8);
+    field[..len].copy_from_slice(&s.as_bytes()[..len]);
+    field
+}
+
+/// 8字节字段去掉右侧空格和NUL
+pub fn alpha8_str(field: &Alpha8) -> &str {
+    std::str::from_utf8(field)
+        .unwrap_or("")
+        .trim_end_matches([' ', '\0'])
+}
+
+/// 顺序写入器
+pub struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> Writer<'a> {
+    /// 检查缓冲区至少有`len`字节
+    pub fn new(buf: &'a mut [u8], len: usize) -> Result<Self, CodecError> {
+        if buf.len() < len {
+            return Err(CodecError::Truncated {
+                needed: len,
+                available: buf.len(),
+            });
+        }
+        Ok(Self { buf, pos: 0 })
+    }
+
+    #[inline]
+    pub fn bytes(&mut self, value: &[u8]) -> &mut Self {
+        self.buf[self.pos..self.pos + value.len()].copy_from_slice(value);
+        self.pos += value.len();
+        self
+    }
+
+    #[inline]
+    pub fn u8(&mut self, value: u8) -> &mut Self {
+        self.bytes(&[value])
+    }
+
+    #[inline]
+    pub fn u16(&mut self, value: u16) -> &mut Self {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    #[inline]
+    pub fn u32(&mut self, value: u32) -> &mut Self {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    #[inline]
+    pub fn u64(&mut self, value: u64) -> &mut Self {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    /// 已写入的字节数
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pos == 0
+    }
+}
+
+/// 顺序读取器
+pub struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// 检查缓冲区至少有`len`字节
+    pub fn new(buf: &'a [u8], len: usize) -> Result<Self, CodecError> {
+        if buf.len() < len {
+            return Err(CodecError::Truncated {
+                needed: len,
+                available: buf.len(),
+            });
+        }
+        Ok(Self { buf, pos: 0 })
+    }
+
+    #[inline]
+    pub fn array<const N: usize>(&mut self) -> [u8; N] {
+        let value = self.buf[self.pos..self.pos + N].try_into().unwrap();
+        self.pos += N;
+        value
+    }
+
+    #[inline]
+    pub fn u8(&mut self) -> u8 {
+        self.array::<1>()[0]
+    }
+
+    #[inline]
+    pub fn u16(&mut self) -> u16 {
+        u16::from_be_bytes(self.array())
+    }
+
+    #[inline]
+    pub fn u32(&mut self) -> u32 {
+        u32::from_be_bytes(self.array())
+    }
+
+    #[inline]
+    pub fn u64(&mut self) -> u64 {
+        u64::from_be_bytes(self.array())
+    }
+}
@@ -0,0 +1,438 @@
+/// 管理通道的客户端证书鉴权与角色授权
+///
+/// 管理通道要求双向TLS（见`monitor::outbound::admin_tls`）：握手时按配置的CA校验客户端证书链，
+/// 未提供证书或校验失败的连接直接断开。握手后把对端证书（DER）交给`AdminAccessControl`，
+/// 身份取自证书的X.509主题：CN为操作者，OU为角色（按属性解析，而不是解析主题字符串，
+/// CN中的转义逗号不会被当作另一个属性）:
+/// - `read-only`: 仅可查询配置
+/// - `admin`: 可修改运行时配置、暂停/恢复撮合、开关紧急开关
+///
+/// 每条变更类指令（无论是否放行）都记入审计日志。
+
+use super::admin::{AdminCommand, AdminResponse, RuntimeConfig};
+use crate::orderbook::{OrderBookManager, TradingMode};
+use crate::unicase::domain::unicase::{MessageType, UnicastMessage};
+use parking_lot::Mutex;
+use rustls::pki_types::CertificateDer;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// 默认保留的审计记录条数
+pub const DEFAULT_AUDIT_CAPACITY: usize = 1024;
+
+/// 管理通道角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AdminRole {
+    ReadOnly,
+    Admin,
+}
+
+impl AdminRole {
+    /// 角色是否允许执行该指令
+    pub fn permits(self, command: &AdminCommand) -> bool {
+        self == AdminRole::Admin || !command.is_privileged()
+    }
+}
+
+impl fmt::Display for AdminRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdminRole::ReadOnly => write!(f, "read-only"),
+            AdminRole::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl FromStr for AdminRole {
+    type Err = AccessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "admin" => Ok(AdminRole::Admin),
+            "read-only" | "readonly" | "read_only" => Ok(AdminRole::ReadOnly),
+            other => Err(AccessError::UnknownRole(other.to_string())),
+        }
+    }
+}
+
+/// 鉴权/授权错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AccessError {
+    #[error("Client certificate required")]
+    MissingCertificate,
+    #[error("Invalid client certificate: {0}")]
+    InvalidCertificate(String),
+    #[error("Certificate subject has no CN")]
+    MissingCommonName,
+    #[error("Certificate subject has no role (OU)")]
+    MissingRole,
+    #[error("Unknown role: {0}")]
+    UnknownRole(String),
+    #[error("Role {role} may not run {command}")]
+    Forbidden { role: AdminRole, command: String },
+}
+
+/// 由客户端证书得到的身份
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientIdentity {
+    pub common_name: String,
+    pub role: AdminRole,
+}
+
+impl ClientIdentity {
+    /// 从已校验的客户端证书（DER）的主题解析身份（多个OU时取第一个可识别的角色）
+    pub fn from_certificate(der: &[u8]) -> Result<Self, AccessError> {
+        let (_, certificate) =
+            X509Certificate::from_der(der).map_err(|e| AccessError::InvalidCertificate(e.to_string()))?;
+        let subject = certificate.subject();
+        let text = |attribute: &x509_parser::x509::AttributeTypeAndValue| {
+            attribute
+                .as_str()
+                .map(str::to_string)
+                .map_err(|e| AccessError::InvalidCertificate(e.to_string()))
+        };
+
+        let common_name = subject
+            .iter_common_name()
+            .next()
+            .map(text)
+            .transpose()?
+            .filter(|name| !name.is_empty())
+            .ok_or(AccessError::MissingCommonName)?;
+        let mut role = None;
+        let mut unknown = None;
+        for attribute in subject.iter_organizational_unit() {
+            match text(attribute)?.parse() {
+                Ok(parsed) => {
+                    role = Some(parsed);
+                    break;
+                }
+                Err(e) => unknown = unknown.or(Some(e)),
+            }
+        }
+        Ok(Self {
+            common_name,
+            role: match (role, unknown) {
+                (Some(role), _) => role,
+                (None, Some(e)) => return Err(e),
+                (None, None) => return Err(AccessError::MissingRole),
+            },
+        })
+    }
+}
+
+/// 审计记录
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AdminAuditRecord {
+    pub timestamp_ns: u64,
+    /// 证书CN（无证书时为None）
+    pub principal: Option<String>,
+    pub role: Option<AdminRole>,
+    pub command: AdminCommand,
+    /// 是否通过授权
+    pub allowed: bool,
+    /// 是否执行成功
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+type AuditHook = Box<dyn Fn(&AdminAuditRecord) + Send + Sync>;
+
+/// 带角色授权的管理通道
+pub struct AdminAccessControl {
+    runtime: Arc<RuntimeConfig>,
+    books: Option<Arc<Mutex<OrderBookManager>>>,
+    audit: Mutex<VecDeque<AdminAuditRecord>>,
+    audit_capacity: usize,
+    hooks: Vec<AuditHook>,
+}
+
+impl AdminAccessControl {
+    pub fn new(runtime: Arc<RuntimeConfig>) -> Self {
+        Self {
+            runtime,
+            books: None,
+            audit: Mutex::new(VecDeque::new()),
+            audit_capacity: DEFAULT_AUDIT_CAPACITY,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// 同时管理各品种订单簿（暂停/恢复撮合和紧急开关）
+    pub fn with_books(mut self, books: Arc<Mutex<OrderBookManager>>) -> Self {
+        self.books = Some(books);
+        self
+    }
+
+    /// 设置保留的审计记录条数
+    pub fn with_audit_capacity(mut self, capacity: usize) -> Self {
+        self.audit_capacity = capacity.max(1);
+        self
+    }
+
+    /// 注册审计回调（如写入外部审计存储）
+    pub fn on_audit<F>(&mut self, hook: F)
+    where
+        F: Fn(&AdminAuditRecord) + Send + Sync + 'static,
+    {
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// 检查身份是否允许执行指令
+    pub fn authorize(identity: Option<&ClientIdentity>, command: &AdminCommand) -> Result<(), AccessError> {
+        let identity = identity.ok_or(AccessError::MissingCertificate)?;
+        if identity.role.permits(command) {
+            Ok(())
+        } else {
+            Err(AccessError::Forbidden {
+                role: identity.role,
+                command: command_name(command),
+            })
+        }
+    }
+
+    /// 授权并执行指令
+    pub fn execute(&self, identity: Option<&ClientIdentity>, command: &AdminCommand) -> AdminResponse {
+        let authorized = Self::authorize(identity, command);
+        let response = match &authorized {
+            Ok(()) if command.is_trading_control() => self.control(command),
+            Ok(()) => self.runtime.apply(command),
+            Err(e) => AdminResponse::error(e.to_string()),
+        };
+
+        if command.is_privileged() || authorized.is_err() {
+            self.record(AdminAuditRecord {
+                timestamp_ns: crate::timing::now_ns(),
+                principal: identity.map(|id| id.common_name.clone()),
+                role: identity.map(|id| id.role),
+                command: command.clone(),
+                allowed: authorized.is_ok(),
+                ok: response.ok,
+                error: response.error.clone(),
+            });
+        }
+        response
+    }
+
+    /// 处理单播管理消息，`certificate`为TLS握手校验过的客户端证书
+    ///
+    /// 消息格式和响应与`RuntimeConfig::handle_message`一致。
+    pub fn handle_message(
+        &self,
+        certificate: Option<&CertificateDer<'_>>,
+        message: &UnicastMessage,
+    ) -> Option<UnicastMessage> {
+        if message.msg_type != MessageType::ConfigSync {
+            return None;
+        }
+
+        let identity = certificate.map(|der| ClientIdentity::from_certificate(der));
+        let (msg_type, response) = match (serde_json::from_slice::<AdminCommand>(&message.payload), identity) {
+            (Ok(command), Some(Err(e))) => {
+                let response = AdminResponse::error(e.to_string());
+                self.record(AdminAuditRecord {
+                    timestamp_ns: crate::timing::now_ns(),
+                    principal: None,
+                    role: None,
+                    command,
+                    allowed: false,
+                    ok: false,
+                    error: response.error.clone(),
+                });
+                (MessageType::Ack, response)
+            }
+            (Ok(command), identity) => {
+                let identity = identity.and_then(Result::ok);
+                let msg_type = match command {
+                    AdminCommand::GetConfig => MessageType::QueryResponse,
+                    _ => MessageType::Ack,
+                };
+                (msg_type, self.execute(identity.as_ref(), &command))
+            }
+            (Err(e), _) => (MessageType::Ack, AdminResponse::error(format!("Invalid command: {}", e))),
+        };
+
+        Some(UnicastMessage {
+            message_id: message.message_id,
+            timestamp_ns: message.timestamp_ns,
+            msg_type,
+            payload: serde_json::to_vec(&response).unwrap_or_default(),
+        })
+    }
+
+    /// 最近的审计记录（由旧到新）
+    pub fn audit_log(&self) -> Vec<AdminAuditRecord> {
+        self.audit.lock().iter().cloned().collect()
+    }
+
+    fn record(&self, record: AdminAuditRecord) {
+        for hook in &self.hooks {
+            hook(&record);
+        }
+        let mut audit = self.audit.lock();
+        if audit.len() == self.audit_capacity {
+            audit.pop_front();
+        }
+        audit.push_back(record);
+    }
+
+    fn control(&self, command: &AdminCommand) -> AdminResponse {
+        let Some(books) = &self.books else {
+            return AdminResponse::error("No order books attached");
+        };
+        let mut books = books.lock();
+        match command {
+            AdminCommand::Halt { symbol } | AdminCommand::Resume { symbol } => {
+                let Some(book) = books.book_mut(symbol) else {
+                    return AdminResponse::error(format!("Unknown symbol: {}", symbol));
+                };
+                if matches!(command, AdminCommand::Halt { .. }) {
+                    book.set_trading_mode(TradingMode::Halted);
+                } else {
+                    book.resume();
+                }
+            }
+            AdminCommand::KillSwitch { engaged } => {
+                let symbols: Vec<String> = books.symbols().map(str::to_string).collect();
+                for symbol in symbols {
+                    if let Some(book) = books.book_mut(&symbol) {
+                        if *engaged {
+                            book.halt();
+                        } else {
+                            book.resume();
+                        }
+                    }
+                }
+            }
+            _ => return self.runtime.apply(command),
+        }
+        AdminResponse::ok()
+    }
+}
+
+fn command_name(command: &AdminCommand) -> String {
+    serde_json::to_value(command)
+        .ok()
+        .and_then(|value| value.get("cmd").and_then(|cmd| cmd.as_str().map(str::to_string)))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty};
+    use crate::orderbook::{Side, TimeInForce, TraderId};
+    use rcgen::{CertificateParams, DistinguishedName, DnType, KeyPair};
+
+    fn config_sync(json: &str) -> UnicastMessage {
+        UnicastMessage {
+            message_id: 7,
+            timestamp_ns: 0,
+            msg_type: MessageType::ConfigSync,
+            payload: json.as_bytes().to_vec(),
+        }
+    }
+
+    fn response(reply: Option<UnicastMessage>) -> AdminResponse {
+        serde_json::from_slice(&reply.unwrap().payload).unwrap()
+    }
+
+    /// 主题为给定属性（按顺序）的自签名证书
+    fn certificate(subject: &[(DnType, &str)]) -> CertificateDer<'static> {
+        let mut params = CertificateParams::default();
+        params.distinguished_name = DistinguishedName::new();
+        for (kind, value) in subject {
+            params.distinguished_name.push(kind.clone(), *value);
+        }
+        params.self_signed(&KeyPair::generate().unwrap()).unwrap().der().clone()
+    }
+
+    fn identity(subject: &[(DnType, &str)]) -> Result<ClientIdentity, AccessError> {
+        ClientIdentity::from_certificate(&certificate(subject))
+    }
+
+    #[test]
+    fn test_identity_from_certificate() {
+        use DnType::{CommonName as CN, OrganizationName as O, OrganizationalUnitName as OU};
+
+        let admin = identity(&[(CN, "ops1"), (OU, "admin"), (O, "rlob")]).unwrap();
+        assert_eq!((admin.common_name.as_str(), admin.role), ("ops1", AdminRole::Admin));
+        let viewer = identity(&[(O, "rlob"), (OU, "desk"), (OU, "read-only"), (CN, "dash")]).unwrap();
+        assert_eq!(viewer.role, AdminRole::ReadOnly);
+
+        assert_eq!(identity(&[(OU, "admin")]), Err(AccessError::MissingCommonName));
+        assert_eq!(identity(&[(CN, "x")]), Err(AccessError::MissingRole));
+        assert_eq!(identity(&[(CN, "x"), (OU, "root")]), Err(AccessError::UnknownRole("root".to_string())));
+        assert!(matches!(
+            ClientIdentity::from_certificate(b"not a certificate"),
+            Err(AccessError::InvalidCertificate(_))
+        ));
+    }
+
+    #[test]
+    fn test_escaped_comma_in_common_name_grants_no_role() {
+        use DnType::{CommonName as CN, OrganizationalUnitName as OU};
+
+        // 主题字符串形如`CN=x\,OU=admin,OU=viewer`，但CN只是一个属性值
+        assert_eq!(
+            identity(&[(CN, "x,OU=admin"), (OU, "viewer")]),
+            Err(AccessError::UnknownRole("viewer".to_string()))
+        );
+        let viewer = identity(&[(CN, "x,OU=admin"), (OU, "read-only")]).unwrap();
+        assert_eq!((viewer.common_name.as_str(), viewer.role), ("x,OU=admin", AdminRole::ReadOnly));
+        assert_eq!(identity(&[(CN, "x/OU=admin")]), Err(AccessError::MissingRole));
+    }
+
+    #[test]
+    fn test_roles_gate_privileged_commands() {
+        let runtime = Arc::new(RuntimeConfig::new());
+        let books = Arc::new(Mutex::new(OrderBookManager::new(1_000, 100)));
+        books.lock().add_symbol("BTCUSDT");
+        books.lock().add_symbol("ETHUSDT");
+        let trader = TraderId::from_str("T");
+        books.lock().book_mut("ETHUSDT").unwrap().limit_order(trader, Side::Buy, px(99), qty(1), TimeInForce::Gtc).unwrap();
+        let mut access = AdminAccessControl::new(Arc::clone(&runtime)).with_books(Arc::clone(&books));
+        let hooked = Arc::new(Mutex::new(0));
+        let counter = Arc::clone(&hooked);
+        access.on_audit(move |_| *counter.lock() += 1);
+
+        let viewer = certificate(&[(DnType::CommonName, "dash"), (DnType::OrganizationalUnitName, "read-only")]);
+        let admin = certificate(&[(DnType::CommonName, "ops1"), (DnType::OrganizationalUnitName, "admin")]);
+        let (viewer, admin) = (Some(&viewer), Some(&admin));
+        let halt = r#"{"cmd":"halt","symbol":"BTCUSDT"}"#;
+
+        // 只读角色可以查询，不能暂停撮合或修改配置
+        let reply = access.handle_message(viewer, &config_sync(r#"{"cmd":"get_config"}"#)).unwrap();
+        assert_eq!(reply.msg_type, MessageType::QueryResponse);
+        assert!(!response(access.handle_message(viewer, &config_sync(halt))).ok);
+        let denied = response(access.handle_message(viewer, &config_sync(r#"{"cmd":"set_feature","name":"f","enabled":true}"#)));
+        assert_eq!(denied.error.as_deref(), Some("Role read-only may not run set_feature"));
+        assert!(!runtime.feature("f", false).is_enabled());
+        assert!(!response(access.handle_message(None, &config_sync(r#"{"cmd":"get_config"}"#))).ok);
+        assert_eq!(books.lock().book("BTCUSDT").unwrap().trading_mode(), TradingMode::Normal);
+
+        assert!(response(access.handle_message(admin, &config_sync(halt))).ok);
+        assert_eq!(books.lock().book("BTCUSDT").unwrap().trading_mode(), TradingMode::Halted);
+        assert!(response(access.handle_message(admin, &config_sync(r#"{"cmd":"kill_switch","engaged":true}"#))).ok);
+        assert_eq!(books.lock().book("ETHUSDT").unwrap().trading_mode(), TradingMode::CancelOnly);
+        assert_eq!(books.lock().book("ETHUSDT").unwrap().best_bid(), None);
+        assert!(response(access.handle_message(admin, &config_sync(r#"{"cmd":"kill_switch","engaged":false}"#))).ok);
+        assert_eq!(books.lock().book("BTCUSDT").unwrap().trading_mode(), TradingMode::Normal);
+
+        // 查询不记审计；拒绝和放行的变更指令都记录
+        let log = access.audit_log();
+        assert_eq!(log.len(), 6);
+        assert_eq!(*hooked.lock(), 6);
+        assert_eq!((log[0].principal.as_deref(), log[0].allowed), (Some("dash"), false));
+        assert_eq!((log[2].principal.as_deref(), log[2].command.clone()), (None, AdminCommand::GetConfig));
+        assert_eq!(log[3].command, AdminCommand::Halt { symbol: "BTCUSDT".to_string() });
+        assert!(log[3].allowed && log[3].ok);
+        assert_eq!(log[3].role, Some(AdminRole::Admin));
+    }
+}
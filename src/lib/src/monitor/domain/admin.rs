@@ -0,0 +1,515 @@
+/// 运行时管理通道
+///
+/// 运维通过单播`ConfigSync`消息下发JSON管理指令，在不重启引擎或网关的情况下:
+/// - 按模块调整日志级别
+/// - 调整行情合并（conflation）间隔
+/// - 调整限流速率
+/// - 开关功能标志（如延迟直方图）
+///
+/// 各组件启动时从`RuntimeConfig`取得句柄，热路径上只做一次原子读取:
+/// - 日志：`runtime_log!`按调用模块查询进程级配置`RuntimeConfig::global()`
+/// - 合并间隔：`DeltaConflator::with_runtime_interval`，每次`poll`读取
+/// - 限流：`ThrottleConfig::with_runtime_rate`（网关和撮合服务的会话限流），每条指令读取
+/// - 功能标志：如`OrderBook::set_latency_flag`开关时延直方图采样
+///
+/// 管理通道和热加载应作用于同一个`RuntimeConfig`（通常为`global()`），否则日志级别不生效。
+
+use crate::unicase::domain::unicase::{MessageType, UnicastMessage};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+/// 按运行时日志级别输出到标准错误
+///
+/// `runtime_log!(Warn, "...", args)`：调用模块（`module_path!()`）在`RuntimeConfig::global()`中
+/// 生效的级别不低于给定级别时输出。
+#[macro_export]
+macro_rules! runtime_log {
+    ($level:ident, $($arg:tt)+) => {
+        if $crate::monitor::domain::admin::RuntimeConfig::global()
+            .log_enabled(module_path!(), $crate::monitor::domain::admin::LogLevel::$level)
+        {
+            eprintln!($($arg)+);
+        }
+    };
+}
+
+/// 日志级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Off = 0,
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl LogLevel {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => LogLevel::Off,
+            1 => LogLevel::Error,
+            2 => LogLevel::Warn,
+            3 => LogLevel::Info,
+            4 => LogLevel::Debug,
+            _ => LogLevel::Trace,
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(LogLevel::Off),
+            "error" => Ok(LogLevel::Error),
+            "warn" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            other => Err(format!("Unknown log level: {}", other)),
+        }
+    }
+}
+
+/// 管理指令
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum AdminCommand {
+    /// 设置模块日志级别（module为空表示默认级别）
+    SetLogLevel {
+        #[serde(default)]
+        module: Option<String>,
+        level: LogLevel,
+    },
+    /// 清除模块日志级别覆盖
+    ClearLogLevel { module: String },
+    /// 设置合并间隔（毫秒，0表示不合并）
+    SetConflation { stream: String, interval_ms: u64 },
+    /// 设置限流速率（每秒，0表示不限流）
+    SetRateLimit { name: String, per_second: u64 },
+    /// 开关功能标志
+    SetFeature { name: String, enabled: bool },
+    /// 查询当前运行时配置
+    GetConfig,
+    /// 暂停品种撮合（需管理员角色，见`access`）
+    Halt { symbol: String },
+    /// 恢复品种撮合（需管理员角色）
+    Resume { symbol: String },
+    /// 全局紧急开关：开启后撤销所有品种的全部订单并拒绝新订单（需管理员角色）
+    KillSwitch { engaged: bool },
+}
+
+impl AdminCommand {
+    /// 是否为变更类指令（查询以外的指令）
+    pub fn is_privileged(&self) -> bool {
+        !matches!(self, AdminCommand::GetConfig)
+    }
+
+    /// 是否为撮合控制指令（由`access::AdminAccessControl`执行）
+    pub fn is_trading_control(&self) -> bool {
+        matches!(
+            self,
+            AdminCommand::Halt { .. } | AdminCommand::Resume { .. } | AdminCommand::KillSwitch { .. }
+        )
+    }
+}
+
+/// 管理指令响应
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdminResponse {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<ConfigView>,
+}
+
+impl AdminResponse {
+    pub(crate) fn ok() -> Self {
+        Self { ok: true, error: None, config: None }
+    }
+
+    pub(crate) fn error(msg: impl Into<String>) -> Self {
+        Self { ok: false, error: Some(msg.into()), config: None }
+    }
+}
+
+/// 运行时配置视图（用于查询响应）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigView {
+    pub default_log_level: Option<LogLevel>,
+    pub log_levels: BTreeMap<String, LogLevel>,
+    pub conflation_ms: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub rate_limits: BTreeMap<String, u64>,
+    pub features: BTreeMap<String, bool>,
+}
+
+/// 撮合引擎时延直方图采样的功能标志名
+pub const LATENCY_HISTOGRAMS: &str = "latency_histograms";
+
+/// 功能标志句柄
+#[derive(Debug, Clone)]
+pub struct FeatureFlag(Arc<AtomicBool>);
+
+impl FeatureFlag {
+    /// 是否启用
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// 合并间隔句柄
+#[derive(Debug, Clone)]
+pub struct ConflationInterval(Arc<AtomicU64>);
+
+impl ConflationInterval {
+    /// 当前合并间隔（None表示不合并）
+    #[inline]
+    pub fn get(&self) -> Option<Duration> {
+        match self.0.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+}
+
+/// 限流速率句柄
+#[derive(Debug, Clone)]
+pub struct RateLimit(Arc<AtomicU64>);
+
+impl RateLimit {
+    /// 当前每秒允许的数量（None表示不限流）
+    #[inline]
+    pub fn per_second(&self) -> Option<u64> {
+        match self.0.load(Ordering::Relaxed) {
+            0 => None,
+            rate => Some(rate),
+        }
+    }
+}
+
+/// 运行时可调配置
+///
+/// 默认日志级别为Info；模块级别按`::`前缀最长匹配。
+pub struct RuntimeConfig {
+    default_level: AtomicU8,
+    log_levels: RwLock<HashMap<String, LogLevel>>,
+    conflation: RwLock<HashMap<String, Arc<AtomicU64>>>,
+    rate_limits: RwLock<HashMap<String, Arc<AtomicU64>>>,
+    features: RwLock<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl RuntimeConfig {
+    /// 进程级运行时配置（`runtime_log!`读取的配置）
+    pub fn global() -> &'static Arc<RuntimeConfig> {
+        static GLOBAL: OnceLock<Arc<RuntimeConfig>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(RuntimeConfig::new()))
+    }
+
+    /// 创建运行时配置
+    pub fn new() -> Self {
+        Self {
+            default_level: AtomicU8::new(LogLevel::Info as u8),
+            log_levels: RwLock::new(HashMap::new()),
+            conflation: RwLock::new(HashMap::new()),
+            rate_limits: RwLock::new(HashMap::new()),
+            features: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 获取默认日志级别
+    pub fn default_log_level(&self) -> LogLevel {
+        LogLevel::from_u8(self.default_level.load(Ordering::Relaxed))
+    }
+
+    /// 设置默认日志级别
+    pub fn set_default_log_level(&self, level: LogLevel) {
+        self.default_level.store(level as u8, Ordering::Relaxed);
+    }
+
+    /// 设置模块日志级别
+    pub fn set_log_level(&self, module: &str, level: LogLevel) {
+        self.log_levels.write().insert(module.to_string(), level);
+    }
+
+    /// 清除模块日志级别覆盖
+    pub fn clear_log_level(&self, module: &str) {
+        self.log_levels.write().remove(module);
+    }
+
+    /// 获取模块生效的日志级别
+    pub fn log_level(&self, module: &str) -> LogLevel {
+        let levels = self.log_levels.read();
+        if !levels.is_empty() {
+            let mut path = module;
+            loop {
+                if let Some(level) = levels.get(path) {
+                    return *level;
+                }
+                match path.rfind("::") {
+                    Some(pos) => path = &path[..pos],
+                    None => break,
+                }
+            }
+        }
+        self.default_log_level()
+    }
+
+    /// 判断模块是否输出该级别日志
+    #[inline]
+    pub fn log_enabled(&self, module: &str, level: LogLevel) -> bool {
+        level != LogLevel::Off && level <= self.log_level(module)
+    }
+
+    /// 获取（或创建）合并间隔句柄
+    pub fn conflation(&self, stream: &str, default: Option<Duration>) -> ConflationInterval {
+        let ms = default.map_or(0, |d| d.as_millis() as u64);
+        let mut conflation = self.conflation.write();
+        let cell = conflation
+            .entry(stream.to_string())
+            .or_insert_with(|| Arc::new(AtomicU64::new(ms)));
+        ConflationInterval(Arc::clone(cell))
+    }
+
+    /// 设置合并间隔
+    pub fn set_conflation(&self, stream: &str, interval: Option<Duration>) {
+        let ms = interval.map_or(0, |d| d.as_millis() as u64);
+        self.conflation(stream, None).0.store(ms, Ordering::Relaxed);
+    }
+
+    /// 获取（或创建）限流速率句柄
+    pub fn rate_limit(&self, name: &str, default: Option<u64>) -> RateLimit {
+        let mut rate_limits = self.rate_limits.write();
+        let cell = rate_limits
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(AtomicU64::new(default.unwrap_or(0))));
+        RateLimit(Arc::clone(cell))
+    }
+
+    /// 设置限流速率（None表示不限流）
+    pub fn set_rate_limit(&self, name: &str, per_second: Option<u64>) {
+        self.rate_limit(name, None).0.store(per_second.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// 获取（或创建）功能标志句柄
+    pub fn feature(&self, name: &str, default: bool) -> FeatureFlag {
+        let mut features = self.features.write();
+        let cell = features
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(AtomicBool::new(default)));
+        FeatureFlag(Arc::clone(cell))
+    }
+
+    /// 开关功能标志
+    pub fn set_feature(&self, name: &str, enabled: bool) {
+        self.feature(name, enabled).0.store(enabled, Ordering::Relaxed);
+    }
+
+    /// 当前配置视图
+    pub fn view(&self) -> ConfigView {
+        ConfigView {
+            default_log_level: Some(self.default_log_level()),
+            log_levels: self
+                .log_levels
+                .read()
+                .iter()
+                .map(|(k, v)| (k.clone(), *v))
+                .collect(),
+            conflation_ms: self
+                .conflation
+                .read()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.load(Ordering::Relaxed)))
+                .collect(),
+            rate_limits: self
+                .rate_limits
+                .read()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.load(Ordering::Relaxed)))
+                .collect(),
+            features: self
+                .features
+                .read()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.load(Ordering::Relaxed)))
+                .collect(),
+        }
+    }
+
+    /// 执行管理指令
+    pub fn apply(&self, command: &AdminCommand) -> AdminResponse {
+        match command {
+            AdminCommand::SetLogLevel { module: None, level } => {
+                self.set_default_log_level(*level);
+            }
+            AdminCommand::SetLogLevel { module: Some(module), level } => {
+                self.set_log_level(module, *level);
+            }
+            AdminCommand::ClearLogLevel { module } => self.clear_log_level(module),
+            AdminCommand::SetConflation { stream, interval_ms } => {
+                self.set_conflation(stream, Some(Duration::from_millis(*interval_ms)));
+            }
+            AdminCommand::SetRateLimit { name, per_second } => {
+                self.set_rate_limit(name, Some(*per_second));
+            }
+            AdminCommand::SetFeature { name, enabled } => self.set_feature(name, *enabled),
+            AdminCommand::GetConfig => {
+                return AdminResponse {
+                    config: Some(self.view()),
+                    ..AdminResponse::ok()
+                };
+            }
+            AdminCommand::Halt { .. } | AdminCommand::Resume { .. } | AdminCommand::KillSwitch { .. } => {
+                return AdminResponse::error("Trading control is not available on this channel");
+            }
+        }
+        AdminResponse::ok()
+    }
+
+    /// 处理单播管理消息
+    ///
+    /// 仅处理`ConfigSync`消息，其他类型返回None；
+    /// 响应以`Ack`（成功）或`QueryResponse`（查询）形式返回，message_id与请求一致。
+    pub fn handle_message(&self, message: &UnicastMessage) -> Option<UnicastMessage> {
+        if message.msg_type != MessageType::ConfigSync {
+            return None;
+        }
+
+        let (msg_type, response) = match serde_json::from_slice::<AdminCommand>(&message.payload) {
+            Ok(AdminCommand::GetConfig) => {
+                (MessageType::QueryResponse, self.apply(&AdminCommand::GetConfig))
+            }
+            Ok(command) => (MessageType::Ack, self.apply(&command)),
+            Err(e) => (MessageType::Ack, AdminResponse::error(format!("Invalid command: {}", e))),
+        };
+
+        Some(UnicastMessage {
+            message_id: message.message_id,
+            timestamp_ns: message.timestamp_ns,
+            msg_type,
+            payload: serde_json::to_vec(&response).unwrap_or_default(),
+        })
+    }
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_sync(json: &str) -> UnicastMessage {
+        UnicastMessage {
+            message_id: 9,
+            timestamp_ns: 0,
+            msg_type: MessageType::ConfigSync,
+            payload: json.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_module_log_levels() {
+        let config = RuntimeConfig::new();
+        assert!(config.log_enabled("lib::orderbook", LogLevel::Info));
+        assert!(!config.log_enabled("lib::orderbook", LogLevel::Debug));
+
+        config.set_log_level("lib::orderbook", LogLevel::Trace);
+        assert!(config.log_enabled("lib::orderbook::engine", LogLevel::Trace));
+        assert!(!config.log_enabled("lib::multicase", LogLevel::Debug));
+
+        config.clear_log_level("lib::orderbook");
+        assert_eq!(config.log_level("lib::orderbook::engine"), LogLevel::Info);
+    }
+
+    #[test]
+    fn test_admin_commands_retune_running_components() {
+        use crate::orderbook::types::{px, qty};
+        use crate::orderbook::{
+            Command, DeltaConflator, DepthDeltaGenerator, OrderBook, SessionThrottle, Side, ThrottleConfig,
+            TimeInForce, TraderId,
+        };
+        use std::time::Instant;
+
+        let config = RuntimeConfig::new();
+        let mut conflator =
+            DeltaConflator::new(1, Duration::from_secs(1)).with_runtime_interval(config.conflation("bbo", None));
+        let mut throttle = SessionThrottle::new(
+            ThrottleConfig::default().with_runtime_rate(config.rate_limit("session", Some(1))),
+        );
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let mut generator = DepthDeltaGenerator::new(1);
+        let bid = |book: &mut OrderBook, price: u32| Command::Limit {
+            trader: TraderId::from_str("T"),
+            side: Side::Buy,
+            price: px(price),
+            quantity: qty(1),
+            tif: TimeInForce::Gtc,
+        }
+        .execute(book);
+        let mut push = |book: &mut OrderBook, conflator: &mut DeltaConflator| {
+            conflator.push(&generator.update(book).unwrap());
+        };
+
+        // 不合并：每次poll都发布；限流每秒1条
+        bid(&mut book, 100);
+        push(&mut book, &mut conflator);
+        assert!(conflator.poll(0).is_some());
+        bid(&mut book, 101);
+        push(&mut book, &mut conflator);
+        assert!(conflator.poll(1).is_some());
+        let t0 = Instant::now();
+        assert!(throttle.check(1, 0, t0).is_ok());
+        assert!(throttle.check(1, 0, t0).is_err());
+
+        config.handle_message(&config_sync(r#"{"cmd":"set_conflation","stream":"bbo","interval_ms":50}"#));
+        config.handle_message(&config_sync(r#"{"cmd":"set_rate_limit","name":"session","per_second":0}"#));
+        bid(&mut book, 102);
+        push(&mut book, &mut conflator);
+        assert!(conflator.poll(2).is_none());
+        assert!(conflator.poll(50_000_001).is_some());
+        assert!((0..10).all(|_| throttle.check(1, 0, t0).is_ok()));
+    }
+
+    #[test]
+    fn test_admin_messages_update_handles() {
+        let config = RuntimeConfig::new();
+        let histograms = config.feature("latency_histograms", false);
+        let bbo = config.conflation("bbo", None);
+
+        let ack = config
+            .handle_message(&config_sync(r#"{"cmd":"set_feature","name":"latency_histograms","enabled":true}"#))
+            .unwrap();
+        assert_eq!(ack.msg_type, MessageType::Ack);
+        assert_eq!(ack.message_id, 9);
+        assert!(histograms.is_enabled());
+
+        config.handle_message(&config_sync(r#"{"cmd":"set_conflation","stream":"bbo","interval_ms":50}"#));
+        assert_eq!(bbo.get(), Some(Duration::from_millis(50)));
+
+        config.handle_message(&config_sync(r#"{"cmd":"set_log_level","module":"web3","level":"debug"}"#));
+        let reply = config.handle_message(&config_sync(r#"{"cmd":"get_config"}"#)).unwrap();
+        assert_eq!(reply.msg_type, MessageType::QueryResponse);
+        let response: AdminResponse = serde_json::from_slice(&reply.payload).unwrap();
+        let view = response.config.unwrap();
+        assert_eq!(view.log_levels.get("web3"), Some(&LogLevel::Debug));
+        assert_eq!(view.features.get("latency_histograms"), Some(&true));
+
+        let reply = config.handle_message(&config_sync(r#"{"cmd":"bogus"}"#)).unwrap();
+        let response: AdminResponse = serde_json::from_slice(&reply.payload).unwrap();
+        assert!(!response.ok);
+    }
+}
@@ -0,0 +1,361 @@
+/// 启动自检与环境诊断
+///
+/// 服务启动前检查运行环境，输出逐项通过/告警/失败的结构化报告（可序列化为JSON）:
+/// - 组播: 本机加入组播组并经环回收到自己发送的探测包
+/// - 套接字缓冲区上限（Linux: `net.core.rmem_max`/`wmem_max`）
+/// - 时钟分辨率（连续读取`timing::now_ns`的最小非零间隔）
+/// - 大页内存（Linux: `/proc/meminfo`）
+/// - 交易所/上游端点TCP可达性
+/// - 组播配置和热加载配置文件的合法性
+///
+/// 只有`Fail`使报告不通过；性能相关的项目不满足时为`Warn`。
+
+use super::reload::ReloadableConfig;
+use crate::multicase::domain::multicast::MulticastConfig;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 检查结果状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Pass => write!(f, "PASS"),
+            CheckStatus::Warn => write!(f, "WARN"),
+            CheckStatus::Fail => write!(f, "FAIL"),
+        }
+    }
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn new(name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// 诊断报告
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl DoctorReport {
+    /// 是否没有失败项
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.status != CheckStatus::Fail)
+    }
+
+    /// 指定状态的检查项数
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.checks.iter().filter(|check| check.status == status).count()
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(f, "[{}] {:<24} {}", check.status, check.name, check.detail)?;
+        }
+        writeln!(
+            f,
+            "{} passed, {} warnings, {} failed",
+            self.count(CheckStatus::Pass),
+            self.count(CheckStatus::Warn),
+            self.count(CheckStatus::Fail)
+        )
+    }
+}
+
+/// 诊断参数
+#[derive(Debug, Clone)]
+pub struct DoctorConfig {
+    /// 要检查的组播配置（环回探测使用其组播地址和接口，端口由系统分配）
+    pub multicast: MulticastConfig,
+    /// 期望的套接字收发缓冲区上限（字节）
+    pub min_socket_buffer: usize,
+    /// 可接受的时钟分辨率
+    pub max_clock_resolution: Duration,
+    /// 没有大页时是否判为失败（否则为告警）
+    pub require_huge_pages: bool,
+    /// 需要可达的端点（host:port）
+    pub endpoints: Vec<String>,
+    /// 端点连接和组播探测超时
+    pub timeout: Duration,
+    /// 热加载配置文件（可选）
+    pub config_path: Option<PathBuf>,
+}
+
+impl Default for DoctorConfig {
+    fn default() -> Self {
+        Self {
+            multicast: MulticastConfig::default(),
+            min_socket_buffer: 4 * 1024 * 1024,
+            max_clock_resolution: Duration::from_micros(1),
+            require_huge_pages: false,
+            endpoints: Vec::new(),
+            timeout: Duration::from_secs(2),
+            config_path: None,
+        }
+    }
+}
+
+/// 执行全部检查
+pub fn run(config: &DoctorConfig) -> DoctorReport {
+    let mut checks = vec![
+        check_multicast_config(&config.multicast),
+        check_multicast_loopback(&config.multicast, config.timeout),
+    ];
+    checks.extend(check_socket_buffers(config.min_socket_buffer));
+    checks.push(check_clock(config.max_clock_resolution));
+    checks.push(check_huge_pages(config.require_huge_pages));
+    for endpoint in &config.endpoints {
+        checks.push(check_endpoint(endpoint, config.timeout));
+    }
+    if let Some(path) = &config.config_path {
+        checks.push(check_config_file(path));
+    }
+    DoctorReport { checks }
+}
+
+/// 组播配置合法性
+pub fn check_multicast_config(config: &MulticastConfig) -> CheckResult {
+    let name = "multicast config";
+    let mut problems = Vec::new();
+    if !config.multicast_addr.is_multicast() {
+        problems.push(format!("{} is not a multicast address", config.multicast_addr));
+    }
+    if config.port == 0 {
+        problems.push("port must be non-zero".to_string());
+    }
+    if config.ttl == 0 {
+        problems.push("ttl must be positive".to_string());
+    }
+    if let Some(interface) = config.interface
+        && interface.is_ipv4() != config.multicast_addr.is_ipv4()
+    {
+        problems.push(format!("interface {} does not match address family", interface));
+    }
+    if problems.is_empty() {
+        let detail = format!("{}:{} ttl={}", config.multicast_addr, config.port, config.ttl);
+        CheckResult::new(name, CheckStatus::Pass, detail)
+    } else {
+        CheckResult::new(name, CheckStatus::Fail, problems.join("; "))
+    }
+}
+
+/// 组播环回探测：加入组播组，发送一个探测包并在超时内收到
+pub fn check_multicast_loopback(config: &MulticastConfig, timeout: Duration) -> CheckResult {
+    let name = "multicast loopback";
+    match probe_multicast(config, timeout) {
+        Ok(elapsed) => CheckResult::new(name, CheckStatus::Pass, format!("round trip {:?}", elapsed)),
+        Err(e) => CheckResult::new(name, CheckStatus::Fail, e),
+    }
+}
+
+fn probe_multicast(config: &MulticastConfig, timeout: Duration) -> Result<Duration, String> {
+    let IpAddr::V4(group) = config.multicast_addr else {
+        return Err("loopback probe supports IPv4 groups only".to_string());
+    };
+    let interface = match config.interface {
+        Some(IpAddr::V4(interface)) => interface,
+        _ => Ipv4Addr::UNSPECIFIED,
+    };
+    let describe = |what: &str, e: std::io::Error| format!("{}: {}", what, e);
+
+    let receiver = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).map_err(|e| describe("bind", e))?;
+    receiver
+        .join_multicast_v4(&group, &interface)
+        .map_err(|e| describe("join group (no multicast route?)", e))?;
+    receiver.set_read_timeout(Some(timeout)).map_err(|e| describe("set timeout", e))?;
+    let port = receiver.local_addr().map_err(|e| describe("local addr", e))?.port();
+
+    let sender = UdpSocket::bind((interface, 0)).map_err(|e| describe("bind sender", e))?;
+    sender.set_multicast_loop_v4(true).map_err(|e| describe("enable loopback", e))?;
+    sender.set_multicast_ttl_v4(config.ttl.max(1)).map_err(|e| describe("set ttl", e))?;
+
+    let token = crate::timing::now_ns().to_le_bytes();
+    let started = std::time::Instant::now();
+    sender
+        .send_to(&token, SocketAddr::new(IpAddr::V4(group), port))
+        .map_err(|e| describe("send", e))?;
+    let mut buf = [0u8; 16];
+    loop {
+        let (len, _) = receiver.recv_from(&mut buf).map_err(|e| describe("no loopback packet", e))?;
+        if buf[..len] == token {
+            return Ok(started.elapsed());
+        }
+        if started.elapsed() >= timeout {
+            return Err("no loopback packet before timeout".to_string());
+        }
+    }
+}
+
+/// 套接字缓冲区上限（非Linux平台为告警）
+pub fn check_socket_buffers(min_bytes: usize) -> Vec<CheckResult> {
+    [("socket rmem_max", "/proc/sys/net/core/rmem_max"), ("socket wmem_max", "/proc/sys/net/core/wmem_max")]
+        .into_iter()
+        .map(|(name, path)| match read_number(path) {
+            Some(value) if value >= min_bytes as u64 => CheckResult::new(name, CheckStatus::Pass, format!("{} bytes", value)),
+            Some(value) => CheckResult::new(
+                name,
+                CheckStatus::Warn,
+                format!("{} bytes < {} (raise with sysctl)", value, min_bytes),
+            ),
+            None => CheckResult::new(name, CheckStatus::Warn, format!("cannot read {}", path)),
+        })
+        .collect()
+}
+
+fn read_number(path: &str) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// 时钟分辨率：连续读取时钟的最小非零间隔
+pub fn check_clock(max_resolution: Duration) -> CheckResult {
+    let name = "clock resolution";
+    let mut resolution = u64::MAX;
+    let mut previous = crate::timing::now_ns();
+    for _ in 0..10_000 {
+        let now = crate::timing::now_ns();
+        if now < previous {
+            return CheckResult::new(name, CheckStatus::Fail, "clock went backwards");
+        }
+        if now > previous {
+            resolution = resolution.min(now - previous);
+        }
+        previous = now;
+    }
+    if resolution == u64::MAX {
+        return CheckResult::new(name, CheckStatus::Fail, "clock did not advance");
+    }
+    let resolution = Duration::from_nanos(resolution);
+    let status = if resolution <= max_resolution { CheckStatus::Pass } else { CheckStatus::Warn };
+    CheckResult::new(name, status, format!("{:?}", resolution))
+}
+
+/// 大页内存
+pub fn check_huge_pages(required: bool) -> CheckResult {
+    let name = "huge pages";
+    let missing = if required { CheckStatus::Fail } else { CheckStatus::Warn };
+    let Ok(meminfo) = fs::read_to_string("/proc/meminfo") else {
+        return CheckResult::new(name, missing, "cannot read /proc/meminfo");
+    };
+    let field = |key: &str| {
+        meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.split_whitespace().next()?.parse::<u64>().ok())
+    };
+    match (field("HugePages_Total:"), field("HugePages_Free:")) {
+        (Some(total), Some(free)) if total > 0 => {
+            CheckResult::new(name, CheckStatus::Pass, format!("{} free of {}", free, total))
+        }
+        _ => CheckResult::new(name, missing, "no huge pages reserved (vm.nr_hugepages)"),
+    }
+}
+
+/// 端点TCP可达性
+pub fn check_endpoint(endpoint: &str, timeout: Duration) -> CheckResult {
+    let name = format!("endpoint {}", endpoint);
+    let addrs: Vec<SocketAddr> = match endpoint.to_socket_addrs() {
+        Ok(addrs) => addrs.collect(),
+        Err(e) => return CheckResult::new(name, CheckStatus::Fail, format!("resolve failed: {}", e)),
+    };
+    let mut last_error = "no addresses".to_string();
+    for addr in addrs {
+        let started = std::time::Instant::now();
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(_) => {
+                return CheckResult::new(name, CheckStatus::Pass, format!("{} connected in {:?}", addr, started.elapsed()));
+            }
+            Err(e) => last_error = format!("{}: {}", addr, e),
+        }
+    }
+    CheckResult::new(name, CheckStatus::Fail, last_error)
+}
+
+/// 热加载配置文件合法性
+pub fn check_config_file(path: &std::path::Path) -> CheckResult {
+    let name = "config file";
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(e) => return CheckResult::new(name, CheckStatus::Fail, format!("{}: {}", path.display(), e)),
+    };
+    match ReloadableConfig::parse(&data, &BTreeSet::new()) {
+        Ok(config) => CheckResult::new(name, CheckStatus::Pass, format!("{} symbols", config.symbols.len())),
+        Err(errors) => CheckResult::new(name, CheckStatus::Fail, errors.join("; ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_config_and_endpoint_checks() {
+        let bad = MulticastConfig {
+            multicast_addr: "10.0.0.1".parse().unwrap(),
+            port: 0,
+            ..Default::default()
+        };
+        assert_eq!(check_multicast_config(&MulticastConfig::default()).status, CheckStatus::Pass);
+        let result = check_multicast_config(&bad);
+        assert_eq!(result.status, CheckStatus::Fail);
+        assert!(result.detail.contains("not a multicast address") && result.detail.contains("port"));
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let open = listener.local_addr().unwrap().to_string();
+        assert_eq!(check_endpoint(&open, Duration::from_secs(1)).status, CheckStatus::Pass);
+        assert_eq!(check_endpoint("not a host", Duration::from_secs(1)).status, CheckStatus::Fail);
+
+        let path = std::env::temp_dir().join(format!("doctor-{}.json", std::process::id()));
+        fs::write(&path, br#"{"symbols": ["BTC"], "price_bands": {"ETH": {"max_move_bps": 1, "window_ms": 1}}}"#).unwrap();
+        let result = check_config_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(result.status, CheckStatus::Fail);
+        assert!(result.detail.contains("unknown symbol ETH"));
+    }
+
+    #[test]
+    fn test_report() {
+        assert_ne!(check_clock(Duration::from_secs(1)).status, CheckStatus::Fail);
+        assert_ne!(check_huge_pages(false).status, CheckStatus::Fail);
+
+        let mut report = DoctorReport {
+            checks: vec![
+                CheckResult::new("a", CheckStatus::Pass, "ok"),
+                CheckResult::new("b", CheckStatus::Warn, "slow"),
+            ],
+        };
+        assert!(report.passed());
+        report.checks.push(CheckResult::new("c", CheckStatus::Fail, "down"));
+        assert!(!report.passed());
+        assert!(report.to_string().ends_with("1 passed, 1 warnings, 1 failed\n"));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["checks"][2]["status"], "fail");
+    }
+}
@@ -0,0 +1,214 @@
+/// 撮合引擎Prometheus指标
+///
+/// 各撮合引擎以名称注册快照闭包，`render`时逐一采集并输出Prometheus文本格式（0.0.4），
+/// 引擎名称作为`engine`标签。由`StatsHttpServer`的`GET /metrics`暴露，
+/// Prometheus直接抓取即可监控，无需额外的导出程序。
+///
+/// 计数器来自`OrderBook::counters`，不受`clear_trades`影响；
+/// 时延分位数需开启`latency-histogram`特性，未开启时不输出。
+
+use crate::orderbook::{LatencyPercentiles, OrderBookSnapshot};
+use parking_lot::RwLock;
+use std::fmt::Write;
+
+/// 指标名前缀
+const PREFIX: &str = "orderbook";
+
+/// Prometheus文本格式的Content-Type
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// 快照采集闭包
+type SnapshotSource = Box<dyn Fn() -> OrderBookSnapshot + Send + Sync>;
+
+/// (指标名, 说明, 取值)
+type Metric<T> = (&'static str, &'static str, fn(&OrderBookSnapshot) -> T);
+
+/// 指标类型
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Counter,
+    Gauge,
+}
+
+impl Kind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Kind::Counter => "counter",
+            Kind::Gauge => "gauge",
+        }
+    }
+}
+
+/// 引擎指标注册表
+#[derive(Default)]
+pub struct MetricsRegistry {
+    engines: RwLock<Vec<(String, SnapshotSource)>>,
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册撮合引擎快照（同名引擎会被替换）
+    pub fn add_engine<F>(&self, name: &str, f: F)
+    where
+        F: Fn() -> OrderBookSnapshot + Send + Sync + 'static,
+    {
+        let mut engines = self.engines.write();
+        engines.retain(|(n, _)| n != name);
+        engines.push((name.to_string(), Box::new(f)));
+    }
+
+    /// 移除撮合引擎
+    pub fn remove_engine(&self, name: &str) {
+        self.engines.write().retain(|(n, _)| n != name);
+    }
+
+    /// 输出全部引擎的Prometheus文本格式指标
+    pub fn render(&self) -> String {
+        let snapshots: Vec<(String, OrderBookSnapshot)> = self
+            .engines
+            .read()
+            .iter()
+            .map(|(name, source)| (escape_label(name), source()))
+            .collect();
+
+        let mut out = String::new();
+        let counters: [Metric<u64>; 5] = [
+            ("orders_accepted_total", "Orders accepted by the matching engine", |s| s.counters.orders_accepted),
+            ("orders_rejected_total", "Orders rejected by the matching engine", |s| s.counters.orders_rejected),
+            ("orders_cancelled_total", "Orders cancelled on request", |s| s.counters.orders_cancelled),
+            ("trades_total", "Trades executed", |s| s.counters.trades),
+            ("traded_volume_total", "Quantity executed in lots", |s| s.counters.traded_volume),
+        ];
+        for (name, help, value) in counters {
+            family(&mut out, name, help, Kind::Counter, &snapshots, |s| value(s) as f64);
+        }
+
+        let gauges: [Metric<f64>; 4] = [
+            ("active_orders", "Orders resting on the book", |s| s.active_orders as f64),
+            ("arena_used", "Order arena slots in use", |s| s.arena_used as f64),
+            ("arena_capacity", "Order arena capacity", |s| s.arena_capacity as f64),
+            ("arena_utilization", "Order arena utilization ratio", |s| {
+                if s.arena_capacity == 0 { 0.0 } else { s.arena_used as f64 / s.arena_capacity as f64 }
+            }),
+        ];
+        for (name, help, value) in gauges {
+            family(&mut out, name, help, Kind::Gauge, &snapshots, value);
+        }
+
+        latency_family(&mut out, &snapshots);
+        out
+    }
+}
+
+/// 输出一个每引擎一条样本的指标
+fn family(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    kind: Kind,
+    snapshots: &[(String, OrderBookSnapshot)],
+    value: impl Fn(&OrderBookSnapshot) -> f64,
+) {
+    let _ = writeln!(out, "# HELP {}_{} {}", PREFIX, name, help);
+    let _ = writeln!(out, "# TYPE {}_{} {}", PREFIX, name, kind.as_str());
+    for (engine, snapshot) in snapshots {
+        let _ = writeln!(out, "{}_{}{{engine=\"{}\"}} {}", PREFIX, name, engine, value(snapshot));
+    }
+}
+
+/// 下单/撤单时延分位数（只输出开启了时延直方图的引擎）
+fn latency_family(out: &mut String, snapshots: &[(String, OrderBookSnapshot)]) {
+    let with_latency: Vec<_> = snapshots
+        .iter()
+        .filter_map(|(engine, snapshot)| snapshot.latency.map(|latency| (engine, latency)))
+        .collect();
+    if with_latency.is_empty() {
+        return;
+    }
+
+    let _ = writeln!(out, "# HELP {}_latency_ns Operation latency percentiles in nanoseconds", PREFIX);
+    let _ = writeln!(out, "# TYPE {}_latency_ns gauge", PREFIX);
+    for (engine, latency) in &with_latency {
+        for (operation, percentiles) in [("limit_order", &latency.limit_order), ("cancel_order", &latency.cancel_order)] {
+            for (quantile, value) in quantiles(percentiles) {
+                let _ = writeln!(
+                    out,
+                    "{}_latency_ns{{engine=\"{}\",operation=\"{}\",quantile=\"{}\"}} {}",
+                    PREFIX, engine, operation, quantile, value
+                );
+            }
+        }
+    }
+
+    let _ = writeln!(out, "# HELP {}_latency_samples Operation latency samples recorded", PREFIX);
+    let _ = writeln!(out, "# TYPE {}_latency_samples gauge", PREFIX);
+    for (engine, latency) in &with_latency {
+        for (operation, percentiles) in [("limit_order", &latency.limit_order), ("cancel_order", &latency.cancel_order)] {
+            let _ = writeln!(
+                out,
+                "{}_latency_samples{{engine=\"{}\",operation=\"{}\"}} {}",
+                PREFIX, engine, operation, percentiles.count
+            );
+        }
+    }
+}
+
+fn quantiles(percentiles: &LatencyPercentiles) -> [(&'static str, u64); 5] {
+    [
+        ("0.5", percentiles.p50_ns),
+        ("0.9", percentiles.p90_ns),
+        ("0.99", percentiles.p99_ns),
+        ("0.999", percentiles.p999_ns),
+        ("1", percentiles.max_ns),
+    ]
+}
+
+/// 转义标签值中的反斜杠、双引号和换行
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty};
+    use crate::orderbook::{OrderBook, Side, TimeInForce, TraderId};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_renders_engine_metrics() {
+        let book = Arc::new(Mutex::new(OrderBook::with_capacity(1_000, 10)));
+        {
+            let mut book = book.lock();
+            let trader = TraderId::from_str("T");
+            let (id, _) = book.limit_order(trader, Side::Sell, px(101), qty(5), TimeInForce::Gtc).unwrap();
+            book.limit_order(trader, Side::Buy, px(99), qty(1), TimeInForce::Gtc).unwrap();
+            book.limit_order(trader, Side::Buy, px(101), qty(2), TimeInForce::Ioc).unwrap();
+            assert!(book.limit_order(trader, Side::Buy, px(5_000), qty(1), TimeInForce::Gtc).is_err());
+            book.cancel_order(id).unwrap();
+            book.clear_trades();
+        }
+
+        let registry = MetricsRegistry::new();
+        let source = Arc::clone(&book);
+        registry.add_engine("BTC\"USDT", move || source.lock().snapshot());
+        let text = registry.render();
+
+        assert!(text.contains("# TYPE orderbook_orders_accepted_total counter\n"));
+        assert!(text.contains("orderbook_orders_accepted_total{engine=\"BTC\\\"USDT\"} 3\n"));
+        assert!(text.contains("orderbook_orders_rejected_total{engine=\"BTC\\\"USDT\"} 1\n"));
+        assert!(text.contains("orderbook_orders_cancelled_total{engine=\"BTC\\\"USDT\"} 1\n"));
+        assert!(text.contains("orderbook_trades_total{engine=\"BTC\\\"USDT\"} 1\n"));
+        assert!(text.contains("orderbook_traded_volume_total{engine=\"BTC\\\"USDT\"} 2\n"));
+        assert!(text.contains("orderbook_active_orders{engine=\"BTC\\\"USDT\"} 1\n"));
+        assert!(text.contains("orderbook_arena_utilization{engine=\"BTC\\\"USDT\"} 0.1\n"));
+        assert_eq!(text.contains("orderbook_latency_ns"), cfg!(feature = "latency-histogram"));
+
+        registry.remove_engine("BTC\"USDT");
+        assert!(!registry.render().contains("engine="));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod access;
+pub mod admin;
+pub mod doctor;
+pub mod metrics;
+pub mod pnl;
+pub mod reload;
+pub mod report;
\ No newline at end of file
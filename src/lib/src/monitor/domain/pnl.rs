@@ -0,0 +1,397 @@
+/// 组合盈亏与敞口
+///
+/// `PositionTracker`按(交易员, 品种)累计成交形成持仓（平均成本法），结合手续费模型
+/// 计算已实现盈亏；再以标记价格计算浮动盈亏与敞口:
+/// - 总敞口 = Σ |持仓| × 标记价
+/// - 净敞口 = Σ 持仓 × 标记价
+///
+/// 标记价由调用方按品种推送（例如聚合后的综合报价）；未推送时取该品种最新成交价。
+/// `PnlService`将持仓表挂到各订单簿的成交输出上，并按周期把快照交给发布闭包
+/// （事件总线、WS推送等由调用方接入）。
+///
+/// 金额单位为“价格刻度 × 数量”，与`Price`相同的刻度，换算显示价格见`PriceConverter`。
+
+use crate::orderbook::{Price, Side, Trade, TradeSink, TraderId};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// 基点分母
+const BPS: f64 = 10_000.0;
+
+/// 手续费模型（基点，负数表示返佣）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeSchedule {
+    /// 被动方（挂单）费率
+    pub maker_bps: i32,
+    /// 主动方费率
+    pub taker_bps: i32,
+}
+
+impl FeeSchedule {
+    /// 按成交金额计算手续费
+    #[inline]
+    pub fn fee(&self, notional: f64, is_taker: bool) -> f64 {
+        let bps = if is_taker { self.taker_bps } else { self.maker_bps };
+        notional * bps as f64 / BPS
+    }
+}
+
+/// 单个持仓
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Position {
+    /// 净持仓（多为正，空为负）
+    pub quantity: i64,
+    /// 持仓平均成本
+    pub avg_price: f64,
+    /// 已实现盈亏（未扣手续费）
+    pub realized_pnl: f64,
+    /// 累计手续费
+    pub fees: f64,
+    /// 累计成交量
+    pub volume: u64,
+}
+
+impl Position {
+    /// 记入一笔成交
+    pub fn apply(&mut self, side: Side, price: Price, quantity: u64, fee: f64) {
+        let price = price.get() as f64;
+        let signed = match side {
+            Side::Buy => quantity as i64,
+            Side::Sell => -(quantity as i64),
+        };
+
+        if self.quantity == 0 || self.quantity.signum() == signed.signum() {
+            // 开仓或加仓
+            let held = self.quantity.unsigned_abs() as f64;
+            self.avg_price = (self.avg_price * held + price * quantity as f64) / (held + quantity as f64);
+        } else {
+            // 减仓，超出部分反向开仓
+            let closed = self.quantity.unsigned_abs().min(quantity);
+            self.realized_pnl += closed as f64 * (price - self.avg_price) * self.quantity.signum() as f64;
+            if quantity > self.quantity.unsigned_abs() {
+                self.avg_price = price;
+            } else if closed == self.quantity.unsigned_abs() {
+                self.avg_price = 0.0;
+            }
+        }
+
+        self.quantity += signed;
+        self.fees += fee;
+        self.volume += quantity;
+    }
+
+    /// 以标记价计算浮动盈亏
+    #[inline]
+    pub fn unrealized_pnl(&self, mark: f64) -> f64 {
+        self.quantity as f64 * (mark - self.avg_price)
+    }
+}
+
+/// 单个持仓的盈亏视图
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PositionView {
+    pub symbol: String,
+    pub quantity: i64,
+    pub avg_price: f64,
+    /// 标记价（没有任何价格时为None，浮动盈亏与敞口按0计）
+    pub mark: Option<u64>,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub fees: f64,
+    pub gross_exposure: f64,
+    pub net_exposure: f64,
+}
+
+/// 交易员汇总
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TraderExposure {
+    pub trader: String,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub fees: f64,
+    /// 已实现 + 浮动 - 手续费
+    pub net_pnl: f64,
+    pub gross_exposure: f64,
+    pub net_exposure: f64,
+    /// 各品种持仓（按品种排序）
+    pub positions: Vec<PositionView>,
+}
+
+/// 品种汇总
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SymbolExposure {
+    pub symbol: String,
+    pub mark: Option<u64>,
+    /// 多头持仓合计
+    pub long_quantity: u64,
+    /// 空头持仓合计
+    pub short_quantity: u64,
+    pub gross_exposure: f64,
+    pub net_exposure: f64,
+    pub volume: u64,
+}
+
+/// 组合快照
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PortfolioSnapshot {
+    pub timestamp_ns: u64,
+    /// 按交易员排序
+    pub traders: Vec<TraderExposure>,
+    /// 按品种排序
+    pub symbols: Vec<SymbolExposure>,
+}
+
+/// 持仓表
+#[derive(Debug, Default)]
+pub struct PositionTracker {
+    fees: FeeSchedule,
+    positions: HashMap<(TraderId, String), Position>,
+    marks: HashMap<String, Price>,
+    last_prices: HashMap<String, Price>,
+}
+
+impl PositionTracker {
+    pub fn new(fees: FeeSchedule) -> Self {
+        Self {
+            fees,
+            ..Self::default()
+        }
+    }
+
+    pub fn fees(&self) -> FeeSchedule {
+        self.fees
+    }
+
+    /// 记入一笔成交（买卖双方各一条）
+    pub fn on_trade(&mut self, symbol: &str, trade: &Trade) {
+        let quantity = trade.quantity.get() as u64;
+        let notional = trade.price.get() as f64 * quantity as f64;
+
+        for (trader, side) in [(trade.buyer, Side::Buy), (trade.seller, Side::Sell)] {
+            let fee = self.fees.fee(notional, side == trade.aggressor_side);
+            self.positions
+                .entry((trader, symbol.to_string()))
+                .or_default()
+                .apply(side, trade.price, quantity, fee);
+        }
+        self.last_prices.insert(symbol.to_string(), trade.price);
+    }
+
+    /// 更新品种标记价
+    pub fn set_mark(&mut self, symbol: &str, price: Price) {
+        self.marks.insert(symbol.to_string(), price);
+    }
+
+    /// 品种当前标记价（未设置时取最新成交价）
+    pub fn mark(&self, symbol: &str) -> Option<Price> {
+        self.marks.get(symbol).or_else(|| self.last_prices.get(symbol)).copied()
+    }
+
+    /// 查询持仓
+    pub fn position(&self, trader: TraderId, symbol: &str) -> Option<&Position> {
+        self.positions.get(&(trader, symbol.to_string()))
+    }
+
+    /// 生成组合快照
+    pub fn snapshot(&self, timestamp_ns: u64) -> PortfolioSnapshot {
+        let mut traders: BTreeMap<String, TraderExposure> = BTreeMap::new();
+        let mut symbols: BTreeMap<&str, SymbolExposure> = BTreeMap::new();
+
+        for ((trader, symbol), position) in &self.positions {
+            let mark = self.mark(symbol);
+            let mark_value = mark.map_or(0.0, |p| p.get() as f64);
+            let unrealized = if mark.is_some() { position.unrealized_pnl(mark_value) } else { 0.0 };
+            let net = position.quantity as f64 * mark_value;
+
+            let view = PositionView {
+                symbol: symbol.clone(),
+                quantity: position.quantity,
+                avg_price: position.avg_price,
+                mark: mark.map(Price::get),
+                realized_pnl: position.realized_pnl,
+                unrealized_pnl: unrealized,
+                fees: position.fees,
+                gross_exposure: net.abs(),
+                net_exposure: net,
+            };
+
+            let entry = traders.entry(trader.to_string()).or_insert_with(|| TraderExposure {
+                trader: trader.to_string(),
+                realized_pnl: 0.0,
+                unrealized_pnl: 0.0,
+                fees: 0.0,
+                net_pnl: 0.0,
+                gross_exposure: 0.0,
+                net_exposure: 0.0,
+                positions: Vec::new(),
+            });
+            entry.realized_pnl += view.realized_pnl;
+            entry.unrealized_pnl += view.unrealized_pnl;
+            entry.fees += view.fees;
+            entry.net_pnl += view.realized_pnl + view.unrealized_pnl - view.fees;
+            entry.gross_exposure += view.gross_exposure;
+            entry.net_exposure += view.net_exposure;
+            entry.positions.push(view);
+
+            let entry = symbols.entry(symbol).or_insert_with(|| SymbolExposure {
+                symbol: symbol.clone(),
+                mark: mark.map(Price::get),
+                long_quantity: 0,
+                short_quantity: 0,
+                gross_exposure: 0.0,
+                net_exposure: 0.0,
+                volume: 0,
+            });
+            if position.quantity > 0 {
+                entry.long_quantity += position.quantity as u64;
+            } else {
+                entry.short_quantity += position.quantity.unsigned_abs();
+            }
+            entry.gross_exposure += net.abs();
+            entry.net_exposure += net;
+            entry.volume += position.volume;
+        }
+
+        let mut traders: Vec<_> = traders.into_values().collect();
+        for trader in &mut traders {
+            trader.positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        }
+
+        PortfolioSnapshot {
+            timestamp_ns,
+            traders,
+            symbols: symbols.into_values().collect(),
+        }
+    }
+}
+
+/// 组合盈亏服务
+///
+/// 多个订单簿共享一个持仓表；通过`sink`为每个品种取得成交输出并挂到对应订单簿上。
+#[derive(Clone)]
+pub struct PnlService {
+    tracker: Arc<Mutex<PositionTracker>>,
+}
+
+impl PnlService {
+    pub fn new(fees: FeeSchedule) -> Self {
+        Self {
+            tracker: Arc::new(Mutex::new(PositionTracker::new(fees))),
+        }
+    }
+
+    /// 品种的成交输出（传给`OrderBook::set_trade_sink`）
+    pub fn sink(&self, symbol: &str) -> PositionSink {
+        PositionSink {
+            symbol: symbol.to_string(),
+            tracker: Arc::clone(&self.tracker),
+        }
+    }
+
+    /// 更新品种标记价
+    pub fn set_mark(&self, symbol: &str, price: Price) {
+        self.tracker.lock().set_mark(symbol, price);
+    }
+
+    /// 查询持仓
+    pub fn position(&self, trader: TraderId, symbol: &str) -> Option<Position> {
+        self.tracker.lock().position(trader, symbol).copied()
+    }
+
+    /// 当前组合快照
+    pub fn snapshot(&self) -> PortfolioSnapshot {
+        self.tracker.lock().snapshot(crate::timing::now_ns())
+    }
+
+    /// 启动周期发布任务，每`interval`生成一次快照交给`publish`
+    pub fn spawn_publisher<F>(&self, interval: Duration, publish: F) -> JoinHandle<()>
+    where
+        F: Fn(&PortfolioSnapshot) + Send + 'static,
+    {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+
+                publish(&service.snapshot());
+            }
+        })
+    }
+}
+
+/// 单个品种的成交输出
+pub struct PositionSink {
+    symbol: String,
+    tracker: Arc<Mutex<PositionTracker>>,
+}
+
+impl TradeSink for PositionSink {
+    fn on_trade(&mut self, trade: &Trade) {
+        self.tracker.lock().on_trade(&self.symbol, trade);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty};
+    use crate::orderbook::{OrderBook, TimeInForce};
+
+    #[test]
+    fn test_position_average_cost_and_flip() {
+        let mut position = Position::default();
+        position.apply(Side::Buy, px(100), 10, 0.0);
+        position.apply(Side::Buy, px(110), 10, 0.0);
+        assert_eq!((position.quantity, position.avg_price), (20, 105.0));
+
+        position.apply(Side::Sell, px(120), 5, 0.0);
+        assert_eq!((position.quantity, position.realized_pnl), (15, 75.0));
+        assert_eq!(position.unrealized_pnl(100.0), -75.0);
+
+        // 反手做空
+        position.apply(Side::Sell, px(90), 20, 1.5);
+        assert_eq!((position.quantity, position.avg_price), (-5, 90.0));
+        assert_eq!(position.realized_pnl, 75.0 - 225.0);
+        assert_eq!((position.fees, position.volume), (1.5, 45));
+    }
+
+    #[test]
+    fn test_service_tracks_book_trades() {
+        let service = PnlService::new(FeeSchedule {
+            maker_bps: -1,
+            taker_bps: 5,
+        });
+        let mut book = OrderBook::new();
+        book.set_trade_sink(Box::new(service.sink("BTC")));
+
+        let maker = TraderId::from_str("MM");
+        let taker = TraderId::from_str("TK");
+        book.limit_order(maker, Side::Sell, px(10_000), qty(10), TimeInForce::Gtc).unwrap();
+        book.limit_order(taker, Side::Buy, px(10_000), qty(4), TimeInForce::Gtc).unwrap();
+
+        let position = service.position(taker, "BTC").unwrap();
+        assert_eq!((position.quantity, position.fees), (4, 20.0));
+        assert_eq!(service.position(maker, "BTC").unwrap().fees, -4.0);
+
+        service.set_mark("BTC", px(10_500));
+        let snapshot = service.snapshot();
+        assert_eq!(snapshot.traders.len(), 2);
+
+        let mm = &snapshot.traders[0];
+        assert_eq!(mm.trader, "MM");
+        assert_eq!((mm.unrealized_pnl, mm.net_pnl), (-2_000.0, -1_996.0));
+        assert_eq!((mm.gross_exposure, mm.net_exposure), (42_000.0, -42_000.0));
+
+        let tk = &snapshot.traders[1];
+        assert_eq!((tk.unrealized_pnl, tk.net_pnl), (2_000.0, 1_980.0));
+
+        let btc = &snapshot.symbols[0];
+        assert_eq!((btc.mark, btc.long_quantity, btc.short_quantity), (Some(10_500), 4, 4));
+        assert_eq!((btc.gross_exposure, btc.net_exposure, btc.volume), (84_000.0, 0.0, 8));
+    }
+}
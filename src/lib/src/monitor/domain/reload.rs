@@ -0,0 +1,475 @@
+/// 配置热加载
+///
+/// 运维修改配置文件（JSON）后发送SIGHUP，或由文件轮询发现内容变化，引擎在不重启的情况下
+/// 重新加载可调参数:
+/// - 日志级别、行情合并间隔、限流速率、功能标志（写入`RuntimeConfig`，运行中的组件通过句柄读取，
+///   见`admin`模块；日志级别只对`RuntimeConfig::global()`生效）
+/// - 各品种价格带（波动熔断配置）
+/// - 新增品种
+///
+/// 整个文件先完整校验再应用，任何一项不合法则整体拒绝，运行中的配置保持不变。
+/// 每次加载（无论应用还是拒绝）都向审计回调发送一条`ConfigChangeEvent`。
+///
+/// 文件中删除的模块日志级别覆盖会被清除；删除的合并间隔、限流和功能标志保持最后的值；
+/// 删除品种需要重启，视为非法配置。受管订单簿的时延采样由`latency_histograms`功能标志开关。
+
+use super::admin::{LogLevel, RuntimeConfig, LATENCY_HISTOGRAMS};
+use crate::orderbook::{CircuitBreakerConfig, OrderBookManager};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// 价格带（对应`CircuitBreakerConfig`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceBandConfig {
+    /// 窗口内允许的最大价格变动（基点）
+    pub max_move_bps: u32,
+    /// 滚动窗口长度（毫秒）
+    pub window_ms: u64,
+}
+
+impl From<PriceBandConfig> for CircuitBreakerConfig {
+    fn from(band: PriceBandConfig) -> Self {
+        CircuitBreakerConfig {
+            max_move_bps: band.max_move_bps,
+            window: Duration::from_millis(band.window_ms),
+        }
+    }
+}
+
+/// 配置文件内容
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReloadableConfig {
+    pub default_log_level: Option<LogLevel>,
+    /// 模块 -> 日志级别
+    pub log_levels: BTreeMap<String, LogLevel>,
+    /// 行情流 -> 合并间隔（毫秒，0表示不合并）
+    pub conflation_ms: BTreeMap<String, u64>,
+    /// 限流名 -> 每秒数量（0表示不限流）
+    pub rate_limits: BTreeMap<String, u64>,
+    pub features: BTreeMap<String, bool>,
+    /// 品种 -> 价格带（未列出的品种关闭熔断）
+    pub price_bands: BTreeMap<String, PriceBandConfig>,
+    /// 品种全集（只能增加）
+    pub symbols: BTreeSet<String>,
+}
+
+impl ReloadableConfig {
+    /// 解析并校验配置文件内容
+    ///
+    /// `current_symbols`为当前已加载的品种，返回全部校验错误。
+    pub fn parse(data: &[u8], current_symbols: &BTreeSet<String>) -> Result<Self, Vec<String>> {
+        let config: Self = serde_json::from_slice(data).map_err(|e| vec![format!("invalid config: {}", e)])?;
+
+        let mut errors = Vec::new();
+        for symbol in current_symbols.difference(&config.symbols) {
+            errors.push(format!("symbol {} cannot be removed without restart", symbol));
+        }
+        if config.symbols.iter().any(|s| s.trim().is_empty()) {
+            errors.push("empty symbol name".to_string());
+        }
+        for (symbol, band) in &config.price_bands {
+            if !config.symbols.contains(symbol) {
+                errors.push(format!("price band for unknown symbol {}", symbol));
+            }
+            if band.max_move_bps == 0 || band.window_ms == 0 {
+                errors.push(format!("price band for {} must have positive max_move_bps and window_ms", symbol));
+            }
+        }
+        if config.log_levels.keys().any(|module| module.is_empty()) {
+            errors.push("empty module name in log_levels (use default_log_level)".to_string());
+        }
+
+        if errors.is_empty() { Ok(config) } else { Err(errors) }
+    }
+
+    /// 相对`previous`的变更描述
+    fn changes(&self, previous: &Self) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.default_log_level != previous.default_log_level
+            && let Some(level) = self.default_log_level
+        {
+            changes.push(format!("default_log_level={:?}", level));
+        }
+        diff_maps(&mut changes, "log_level", &previous.log_levels, &self.log_levels);
+        diff_maps(&mut changes, "conflation_ms", &previous.conflation_ms, &self.conflation_ms);
+        diff_maps(&mut changes, "rate_limit", &previous.rate_limits, &self.rate_limits);
+        diff_maps(&mut changes, "feature", &previous.features, &self.features);
+        diff_maps(&mut changes, "price_band", &previous.price_bands, &self.price_bands);
+        for symbol in self.symbols.difference(&previous.symbols) {
+            changes.push(format!("symbol {} added", symbol));
+        }
+        changes
+    }
+}
+
+fn diff_maps<V: PartialEq + std::fmt::Debug>(
+    changes: &mut Vec<String>,
+    kind: &str,
+    old: &BTreeMap<String, V>,
+    new: &BTreeMap<String, V>,
+) {
+    for (key, value) in new {
+        if old.get(key) != Some(value) {
+            changes.push(format!("{} {}={:?}", kind, key, value));
+        }
+    }
+    for key in old.keys().filter(|key| !new.contains_key(*key)) {
+        changes.push(format!("{} {} removed", kind, key));
+    }
+}
+
+/// 触发加载的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReloadTrigger {
+    Startup,
+    Signal,
+    FileWatch,
+    Manual,
+}
+
+/// 配置变更审计事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigChangeEvent {
+    /// 已应用的配置版本（拒绝时为当前版本）
+    pub version: u64,
+    pub timestamp_ns: u64,
+    pub trigger: ReloadTrigger,
+    pub path: PathBuf,
+    pub applied: bool,
+    /// 应用的变更
+    pub changes: Vec<String>,
+    /// 拒绝原因
+    pub errors: Vec<String>,
+}
+
+type AuditHook = Box<dyn Fn(&ConfigChangeEvent) + Send + Sync>;
+
+/// 配置热加载器
+pub struct ConfigReloader {
+    path: PathBuf,
+    runtime: Arc<RuntimeConfig>,
+    books: Option<Arc<Mutex<OrderBookManager>>>,
+    current: ReloadableConfig,
+    version: u64,
+    /// 最近一次读取的文件内容（用于轮询发现变化）
+    last_seen: Option<Vec<u8>>,
+    audit: Vec<AuditHook>,
+}
+
+impl ConfigReloader {
+    pub fn new(path: impl AsRef<Path>, runtime: Arc<RuntimeConfig>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            runtime,
+            books: None,
+            current: ReloadableConfig::default(),
+            version: 0,
+            last_seen: None,
+            audit: Vec::new(),
+        }
+    }
+
+    /// 同时管理各品种订单簿（价格带和新增品种）
+    pub fn with_books(mut self, books: Arc<Mutex<OrderBookManager>>) -> Self {
+        self.current.symbols = books.lock().symbols().map(str::to_string).collect();
+        self.books = Some(books);
+        self
+    }
+
+    /// 注册审计回调
+    pub fn on_change<F>(&mut self, hook: F)
+    where
+        F: Fn(&ConfigChangeEvent) + Send + Sync + 'static,
+    {
+        self.audit.push(Box::new(hook));
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 已应用的配置版本（0表示尚未加载）
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// 已应用的配置
+    pub fn current(&self) -> &ReloadableConfig {
+        &self.current
+    }
+
+    /// 读取并加载配置文件
+    pub fn reload(&mut self, trigger: ReloadTrigger) -> ConfigChangeEvent {
+        let result = fs::read(&self.path)
+            .map_err(|e| vec![format!("cannot read {}: {}", self.path.display(), e)])
+            .and_then(|data| {
+                let parsed = ReloadableConfig::parse(&data, &self.current.symbols);
+                self.last_seen = Some(data);
+                parsed
+            });
+
+        let (applied, changes, errors) = match result {
+            Ok(config) => {
+                let changes = config.changes(&self.current);
+                self.apply(config);
+                self.version += 1;
+                (true, changes, Vec::new())
+            }
+            Err(errors) => (false, Vec::new(), errors),
+        };
+
+        let event = ConfigChangeEvent {
+            version: self.version,
+            timestamp_ns: crate::timing::now_ns(),
+            trigger,
+            path: self.path.clone(),
+            applied,
+            changes,
+            errors,
+        };
+        for hook in &self.audit {
+            hook(&event);
+        }
+        event
+    }
+
+    /// 文件内容与上次读取不同时重新加载
+    pub fn poll(&mut self) -> Option<ConfigChangeEvent> {
+        let data = fs::read(&self.path).ok()?;
+        if self.last_seen.as_deref() == Some(data.as_slice()) {
+            return None;
+        }
+        Some(self.reload(ReloadTrigger::FileWatch))
+    }
+
+    fn apply(&mut self, config: ReloadableConfig) {
+        let runtime = &self.runtime;
+        if let Some(level) = config.default_log_level {
+            runtime.set_default_log_level(level);
+        }
+        for module in self.current.log_levels.keys().filter(|m| !config.log_levels.contains_key(*m)) {
+            runtime.clear_log_level(module);
+        }
+        for (module, level) in &config.log_levels {
+            runtime.set_log_level(module, *level);
+        }
+        for (stream, ms) in &config.conflation_ms {
+            runtime.set_conflation(stream, Some(Duration::from_millis(*ms)));
+        }
+        for (name, rate) in &config.rate_limits {
+            runtime.set_rate_limit(name, Some(*rate));
+        }
+        for (name, enabled) in &config.features {
+            runtime.set_feature(name, *enabled);
+        }
+
+        if let Some(books) = &self.books {
+            let mut books = books.lock();
+            for symbol in &config.symbols {
+                books.add_symbol(symbol);
+            }
+            for symbol in &config.symbols {
+                let band = config.price_bands.get(symbol).copied().map(CircuitBreakerConfig::from);
+                let Some(book) = books.book_mut(symbol) else {
+                    continue;
+                };
+                if book.circuit_breaker() != band.as_ref() {
+                    book.set_circuit_breaker(band);
+                }
+                book.set_latency_flag(runtime.feature(LATENCY_HISTOGRAMS, true));
+            }
+        }
+        self.current = config;
+    }
+
+    /// 启动后台加载任务：收到SIGHUP（Unix）或轮询发现文件变化时重新加载
+    pub fn spawn(reloader: Arc<Mutex<ConfigReloader>>, poll_interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            #[cfg(unix)]
+            let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok();
+            let mut ticker = tokio::time::interval(poll_interval);
+            loop {
+                #[cfg(unix)]
+                let signalled = match hangup.as_mut() {
+                    Some(hangup) => tokio::select! {
+                        _ = hangup.recv() => true,
+                        _ = ticker.tick() => false,
+                    },
+                    None => {
+                        ticker.tick().await;
+                        false
+                    }
+                };
+                #[cfg(not(unix))]
+                let signalled = {
+                    ticker.tick().await;
+                    false
+                };
+
+                let mut reloader = reloader.lock();
+                if signalled {
+                    reloader.reload(ReloadTrigger::Signal);
+                } else {
+                    reloader.poll();
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rlob-reload-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("config.json")
+    }
+
+    fn reloader(path: &Path) -> (ConfigReloader, Arc<RuntimeConfig>, Arc<Mutex<OrderBookManager>>) {
+        let runtime = Arc::new(RuntimeConfig::new());
+        let books = Arc::new(Mutex::new(OrderBookManager::new(1_000, 100)));
+        books.lock().add_symbol("BTC");
+        let reloader = ConfigReloader::new(path, Arc::clone(&runtime)).with_books(Arc::clone(&books));
+        (reloader, runtime, books)
+    }
+
+    #[test]
+    fn test_reload_applies_all_sections() {
+        let path = temp_file("apply");
+        fs::write(&path, r#"{
+            "default_log_level": "warn",
+            "log_levels": {"lib::orderbook": "debug"},
+            "conflation_ms": {"bbo": 20},
+            "rate_limits": {"gateway": 5000},
+            "features": {"latency_histograms": true},
+            "price_bands": {"ETH": {"max_move_bps": 500, "window_ms": 60000}},
+            "symbols": ["BTC", "ETH"]
+        }"#).unwrap();
+        let (mut reloader, runtime, books) = reloader(&path);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        reloader.on_change(move |event| sink.lock().push(event.clone()));
+
+        let event = reloader.reload(ReloadTrigger::Startup);
+        assert!(event.applied, "{:?}", event.errors);
+        assert_eq!(event.version, 1);
+        assert!(event.changes.contains(&"symbol ETH added".to_string()));
+
+        assert_eq!(runtime.log_level("lib::orderbook::engine"), LogLevel::Debug);
+        assert_eq!(runtime.log_level("web3"), LogLevel::Warn);
+        assert_eq!(runtime.conflation("bbo", None).get(), Some(Duration::from_millis(20)));
+        assert_eq!(runtime.rate_limit("gateway", None).per_second(), Some(5000));
+        assert!(runtime.feature("latency_histograms", false).is_enabled());
+        let books = books.lock();
+        assert_eq!(books.book("ETH").unwrap().circuit_breaker().unwrap().max_move_bps, 500);
+        assert!(books.book("BTC").unwrap().circuit_breaker().is_none());
+        assert_eq!(events.lock().len(), 1);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_reload_retunes_running_components() {
+        use crate::orderbook::types::{px, qty};
+        use crate::orderbook::{
+            Command, DeltaConflator, DepthDeltaGenerator, GatewayConfig, OrderBook, OrderGateway, Side,
+            ThrottleConfig, ThrottleReason, TimeInForce, TimedCommand, TraderId,
+        };
+
+        let path = temp_file("retune");
+        fs::write(&path, r#"{"symbols": ["BTC"], "conflation_ms": {"bbo": 0}, "rate_limits": {"session": 1}}"#)
+            .unwrap();
+        let (mut reloader, runtime, _) = reloader(&path);
+        assert!(reloader.reload(ReloadTrigger::Startup).applied);
+
+        // 组件启动时取得句柄，之后不再接触RuntimeConfig
+        let mut conflator = DeltaConflator::new(1, Duration::ZERO).with_runtime_interval(runtime.conflation("bbo", None));
+        let throttle = ThrottleConfig::default().with_runtime_rate(runtime.rate_limit("session", None));
+        let mut gateway = OrderGateway::new(
+            OrderBook::with_capacity(1_000, 100),
+            GatewayConfig { throttle, ..Default::default() },
+        );
+        let mut generator = DepthDeltaGenerator::new(1);
+        let mut price = 100;
+        let mut bid = |gateway: &mut OrderGateway, conflator: &mut DeltaConflator| {
+            price += 1;
+            let command = Command::Limit {
+                trader: TraderId::from_str("T"),
+                side: Side::Buy,
+                price: px(price),
+                quantity: qty(1),
+                tif: TimeInForce::Gtc,
+            };
+            let result = gateway.submit_session(7, TimedCommand::now(command), 0);
+            if let Some(batch) = generator.update(gateway.book()) {
+                conflator.push(&batch);
+            }
+            result
+        };
+
+        assert!(bid(&mut gateway, &mut conflator).is_ok());
+        assert_eq!(bid(&mut gateway, &mut conflator).unwrap_err().reason, ThrottleReason::SessionRate);
+        assert!(conflator.poll(0).is_some());
+
+        fs::write(&path, r#"{"symbols": ["BTC"], "conflation_ms": {"bbo": 100}, "rate_limits": {"session": 0}}"#)
+            .unwrap();
+        assert!(reloader.reload(ReloadTrigger::Signal).applied);
+        assert!((0..5).all(|_| bid(&mut gateway, &mut conflator).is_ok()));
+        assert!(conflator.poll(1).is_none());
+        assert!(conflator.poll(100_000_000).is_some());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_config_is_rejected_atomically() {
+        let path = temp_file("reject");
+        fs::write(&path, r#"{"log_levels": {"web3": "debug"}, "symbols": ["BTC"]}"#).unwrap();
+        let (mut reloader, runtime, books) = reloader(&path);
+        assert!(reloader.reload(ReloadTrigger::Startup).applied);
+
+        // 合法项与非法项混在一起：整体拒绝
+        fs::write(&path, r#"{
+            "log_levels": {"web3": "trace"},
+            "price_bands": {"DOGE": {"max_move_bps": 0, "window_ms": 1}},
+            "symbols": ["ETH"]
+        }"#).unwrap();
+        let event = reloader.reload(ReloadTrigger::Signal);
+        assert!(!event.applied);
+        assert_eq!(event.version, 1);
+        assert_eq!(event.errors.len(), 3, "{:?}", event.errors);
+        assert_eq!(runtime.log_level("web3"), LogLevel::Debug);
+        assert!(books.lock().book("ETH").is_none());
+
+        fs::write(&path, r#"{"symbols": ["BTC"], "bogus": 1}"#).unwrap();
+        assert!(!reloader.reload(ReloadTrigger::Manual).applied);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_poll_detects_changes() {
+        let path = temp_file("poll");
+        fs::write(&path, r#"{"symbols": ["BTC"], "log_levels": {"web3": "debug"}}"#).unwrap();
+        let (mut reloader, runtime, _) = reloader(&path);
+
+        assert_eq!(reloader.poll().unwrap().trigger, ReloadTrigger::FileWatch);
+        assert!(reloader.poll().is_none());
+
+        // 删除的模块覆盖被清除
+        fs::write(&path, r#"{"symbols": ["BTC"]}"#).unwrap();
+        let event = reloader.poll().unwrap();
+        assert_eq!(event.changes, vec!["log_level web3 removed".to_string()]);
+        assert_eq!(runtime.log_level("web3"), LogLevel::Info);
+        assert_eq!(reloader.version(), 2);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,255 @@
+/// 运行状态汇总报告
+///
+/// 将各组件的统计信息（单播客户端/服务器、组播发送/接收、撮合引擎、网关）
+/// 汇总为一个JSON文档，附带版本号和运行时长，便于运维快速查看或由Grafana JSON数据源抓取。
+/// 组件通过注册闭包提供统计，生成报告时按需采集。
+
+use crate::multicase::domain::multicast::{PublisherStats, SubscriberStats};
+use crate::orderbook::OrderBookSnapshot;
+use crate::unicase::domain::unicase::{ClientStats, ServerStats};
+use parking_lot::RwLock;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// 统计采集闭包
+type StatsSource = Box<dyn Fn() -> Value + Send + Sync>;
+
+/// 组件类别（对应报告中的一级字段）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    Client,
+    Server,
+    Publisher,
+    Subscriber,
+    Engine,
+    Gateway,
+}
+
+impl Component {
+    /// 全部组件类别（按报告输出顺序）
+    pub const ALL: [Component; 6] = [
+        Component::Client,
+        Component::Server,
+        Component::Publisher,
+        Component::Subscriber,
+        Component::Engine,
+        Component::Gateway,
+    ];
+
+    /// 报告中的字段名
+    pub fn key(&self) -> &'static str {
+        match self {
+            Component::Client => "clients",
+            Component::Server => "servers",
+            Component::Publisher => "publishers",
+            Component::Subscriber => "subscribers",
+            Component::Engine => "engines",
+            Component::Gateway => "gateways",
+        }
+    }
+}
+
+/// 交易所网关指标
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GatewayMetrics {
+    /// 是否已连接
+    pub connected: bool,
+    /// 接收的消息数
+    pub messages_received: u64,
+    /// 当前重连尝试次数
+    pub reconnect_attempts: u32,
+    /// 距上一条消息的毫秒数
+    pub last_message_age_ms: Option<u64>,
+}
+
+/// 统计注册表
+///
+/// 各组件以名称注册统计来源，`report`时逐一调用生成完整文档。
+pub struct StatsRegistry {
+    version: String,
+    started_at: Instant,
+    sources: RwLock<Vec<(Component, String, StatsSource)>>,
+}
+
+impl StatsRegistry {
+    /// 创建注册表（版本号取自crate版本）
+    pub fn new() -> Self {
+        Self::with_version(env!("CARGO_PKG_VERSION"))
+    }
+
+    /// 使用指定版本号创建注册表
+    pub fn with_version(version: impl Into<String>) -> Self {
+        Self {
+            version: version.into(),
+            started_at: Instant::now(),
+            sources: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// 注册单播客户端统计
+    pub fn add_client<F>(&self, name: &str, f: F)
+    where
+        F: Fn() -> ClientStats + Send + Sync + 'static,
+    {
+        self.register(Component::Client, name, f);
+    }
+
+    /// 注册单播服务器统计
+    pub fn add_server<F>(&self, name: &str, f: F)
+    where
+        F: Fn() -> ServerStats + Send + Sync + 'static,
+    {
+        self.register(Component::Server, name, f);
+    }
+
+    /// 注册组播发送统计
+    pub fn add_publisher<F>(&self, name: &str, f: F)
+    where
+        F: Fn() -> PublisherStats + Send + Sync + 'static,
+    {
+        self.register(Component::Publisher, name, f);
+    }
+
+    /// 注册组播接收统计
+    pub fn add_subscriber<F>(&self, name: &str, f: F)
+    where
+        F: Fn() -> SubscriberStats + Send + Sync + 'static,
+    {
+        self.register(Component::Subscriber, name, f);
+    }
+
+    /// 注册撮合引擎快照
+    pub fn add_engine<F>(&self, name: &str, f: F)
+    where
+        F: Fn() -> OrderBookSnapshot + Send + Sync + 'static,
+    {
+        self.register(Component::Engine, name, f);
+    }
+
+    /// 注册网关指标
+    pub fn add_gateway<F>(&self, name: &str, f: F)
+    where
+        F: Fn() -> GatewayMetrics + Send + Sync + 'static,
+    {
+        self.register(Component::Gateway, name, f);
+    }
+
+    /// 注册任意可序列化的统计来源（同名来源会被替换）
+    pub fn register<T, F>(&self, component: Component, name: &str, f: F)
+    where
+        T: Serialize,
+        F: Fn() -> T + Send + Sync + 'static,
+    {
+        let source: StatsSource =
+            Box::new(move || serde_json::to_value(f()).unwrap_or(Value::Null));
+
+        let mut sources = self.sources.write();
+        sources.retain(|(c, n, _)| !(*c == component && n == name));
+        sources.push((component, name.to_string(), source));
+    }
+
+    /// 移除统计来源
+    pub fn unregister(&self, component: Component, name: &str) {
+        self.sources
+            .write()
+            .retain(|(c, n, _)| !(*c == component && n == name));
+    }
+
+    /// 运行时长（秒）
+    pub fn uptime_secs(&self) -> u64 {
+        self.started_at.elapsed().as_secs()
+    }
+
+    /// 生成统计报告
+    ///
+    /// 文档结构:
+    /// ```text
+    /// {
+    ///   "version": "0.1.0",
+    ///   "uptime_secs": 42,
+    ///   "timestamp_ms": 1700000000000,
+    ///   "clients": { "<name>": { ... } },
+    ///   "servers": { ... },
+    ///   "publishers": { ... },
+    ///   "subscribers": { ... },
+    ///   "engines": { ... },
+    ///   "gateways": { ... }
+    /// }
+    /// ```
+    pub fn report(&self) -> Value {
+        let mut doc = Map::new();
+        doc.insert("version".to_string(), Value::from(self.version.clone()));
+        doc.insert("uptime_secs".to_string(), Value::from(self.uptime_secs()));
+        doc.insert("timestamp_ms".to_string(), Value::from(now_ms()));
+
+        for component in Component::ALL {
+            doc.insert(component.key().to_string(), Value::Object(Map::new()));
+        }
+
+        let sources = self.sources.read();
+        for (component, name, source) in sources.iter() {
+            if let Some(Value::Object(section)) = doc.get_mut(component.key()) {
+                section.insert(name.clone(), source());
+            }
+        }
+
+        Value::Object(doc)
+    }
+}
+
+impl Default for StatsRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty};
+    use crate::orderbook::{OrderBook, Side, TimeInForce, TraderId};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_report_aggregates_components() {
+        let registry = StatsRegistry::with_version("1.2.3");
+
+        let book = Arc::new(Mutex::new(OrderBook::new()));
+        book.lock()
+            .limit_order(TraderId::from_str("S"), Side::Sell, px(10000), qty(5), TimeInForce::Gtc).unwrap();
+        let engine = Arc::clone(&book);
+        registry.add_engine("BTCUSDT", move || engine.lock().snapshot());
+
+        registry.add_publisher("md", || PublisherStats {
+            messages_sent: 10,
+            bytes_sent: 100,
+            errors: 0,
+        });
+        registry.add_gateway("binance", || GatewayMetrics {
+            connected: true,
+            messages_received: 7,
+            ..Default::default()
+        });
+
+        let report = registry.report();
+        assert_eq!(report["version"], "1.2.3");
+        assert!(report["uptime_secs"].is_u64());
+        assert_eq!(report["engines"]["BTCUSDT"]["active_orders"], 1);
+        assert_eq!(report["engines"]["BTCUSDT"]["ask_min"], 10000);
+        assert_eq!(report["publishers"]["md"]["messages_sent"], 10);
+        assert_eq!(report["gateways"]["binance"]["connected"], true);
+        assert!(report["clients"].as_object().unwrap().is_empty());
+
+        registry.unregister(Component::Gateway, "binance");
+        assert!(registry.report()["gateways"].as_object().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,344 @@
+/// 管理通道TLS监听
+///
+/// 双向TLS：除服务器证书外，用配置的CA（`WebPkiClientVerifier`）校验客户端证书，
+/// 未提供证书或证书链不能校验到该CA的连接在握手阶段即被拒绝。握手成功后按单播帧格式
+/// （见`TcpUnicastServer`）读取管理指令，连同对端证书交给`AdminAccessControl`授权执行，
+/// 并在原连接回复。
+
+use crate::monitor::domain::access::AdminAccessControl;
+use crate::unicase::outbound::tcp_client::TcpUnicastClient;
+use crate::unicase::outbound::tcp_server::TcpUnicastServer;
+use rustls::crypto::ring;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::{VerifierBuilderError, WebPkiClientVerifier};
+use rustls::{RootCertStore, ServerConfig};
+use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio_rustls::TlsAcceptor;
+
+/// TLS握手超时
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 管理指令帧的最大长度
+const MAX_FRAME_SIZE: usize = 64 * 1024;
+
+/// 单播帧头长度（长度4 + 消息ID8 + 时间戳8 + 类型1）
+const FRAME_HEADER_SIZE: usize = 21;
+
+/// TLS配置错误
+#[derive(Debug, Error)]
+pub enum AdminTlsError {
+    #[error("Failed to read {path}: {message}")]
+    Pem { path: String, message: String },
+    #[error("No certificates in {0}")]
+    NoCertificates(String),
+    #[error("TLS error: {0}")]
+    Tls(#[from] rustls::Error),
+    #[error("Client verifier error: {0}")]
+    Verifier(#[from] VerifierBuilderError),
+}
+
+/// 构造要求客户端证书的服务端配置
+///
+/// `client_ca`为签发管理员证书的CA，客户端证书必须能校验到其中之一。
+pub fn server_config(
+    cert_chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+    client_ca: Vec<CertificateDer<'static>>,
+) -> Result<Arc<ServerConfig>, AdminTlsError> {
+    let provider = Arc::new(ring::default_provider());
+    let mut roots = RootCertStore::empty();
+    for ca in client_ca {
+        roots.add(ca)?;
+    }
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), Arc::clone(&provider)).build()?;
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(cert_chain, key)?;
+    Ok(Arc::new(config))
+}
+
+/// 从PEM文件构造服务端配置（服务器证书链、私钥、客户端CA）
+pub fn server_config_from_pem(
+    cert_chain: impl AsRef<Path>,
+    key: impl AsRef<Path>,
+    client_ca: impl AsRef<Path>,
+) -> Result<Arc<ServerConfig>, AdminTlsError> {
+    server_config(
+        read_certificates(cert_chain.as_ref())?,
+        PrivateKeyDer::from_pem_file(key.as_ref()).map_err(|e| pem_error(key.as_ref(), e))?,
+        read_certificates(client_ca.as_ref())?,
+    )
+}
+
+fn read_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, AdminTlsError> {
+    let certificates = CertificateDer::pem_file_iter(path)
+        .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+        .map_err(|e| pem_error(path, e))?;
+    if certificates.is_empty() {
+        return Err(AdminTlsError::NoCertificates(path.display().to_string()));
+    }
+    Ok(certificates)
+}
+
+fn pem_error(path: &Path, e: rustls::pki_types::pem::Error) -> AdminTlsError {
+    AdminTlsError::Pem {
+        path: path.display().to_string(),
+        message: e.to_string(),
+    }
+}
+
+/// 管理通道服务器
+pub struct AdminTlsServer {
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    access: Arc<AdminAccessControl>,
+}
+
+impl AdminTlsServer {
+    /// 绑定监听地址，`tls`须要求客户端证书（见`server_config`）
+    pub async fn bind(addr: SocketAddr, tls: Arc<ServerConfig>, access: Arc<AdminAccessControl>) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        Ok(Self {
+            listener,
+            acceptor: TlsAcceptor::from(tls),
+            access,
+        })
+    }
+
+    /// 获取实际监听地址（绑定端口0时使用）
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// 启动服务
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match self.listener.accept().await {
+                    Ok((stream, addr)) => {
+                        tokio::spawn(Self::handle(stream, addr, self.acceptor.clone(), Arc::clone(&self.access)));
+                    }
+                    Err(e) => {
+                        crate::runtime_log!(Warn, "Failed to accept admin connection: {}", e);
+                    }
+                }
+            }
+        })
+    }
+
+    async fn handle(stream: TcpStream, addr: SocketAddr, acceptor: TlsAcceptor, access: Arc<AdminAccessControl>) {
+        let _ = stream.set_nodelay(true);
+        let mut stream = match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+            Ok(Ok(stream)) => stream,
+            Ok(Err(e)) => {
+                crate::runtime_log!(Warn, "Admin TLS handshake with {} failed: {}", addr, e);
+                return;
+            }
+            Err(_) => return,
+        };
+        // 校验器要求客户端证书，握手成功即有对端证书；取叶证书
+        let Some(certificate) = stream.get_ref().1.peer_certificates().and_then(|chain| chain.first()).cloned() else {
+            return;
+        };
+
+        loop {
+            let frame = match read_frame(&mut stream).await {
+                Ok(frame) => frame,
+                Err(_) => break,
+            };
+            let message = match TcpUnicastClient::deserialize_message(&frame) {
+                Ok(message) => message,
+                Err(e) => {
+                    crate::runtime_log!(Warn, "Invalid admin message from {}: {}", addr, e);
+                    break;
+                }
+            };
+            if let Some(reply) = access.handle_message(Some(&certificate), &message)
+                && stream.write_all(&TcpUnicastServer::serialize_message(&reply)).await.is_err()
+            {
+                break;
+            }
+        }
+        let _ = stream.shutdown().await;
+    }
+}
+
+/// 读取一个完整的单播帧（含长度前缀）
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut len_buf = [0u8; 4];
+    reader.read_exact(&mut len_buf).await?;
+    let len = u32::from_be_bytes(len_buf) as usize;
+    if !(FRAME_HEADER_SIZE..=MAX_FRAME_SIZE).contains(&len) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid frame length {}", len)));
+    }
+    let mut frame = vec![0u8; len];
+    frame[..4].copy_from_slice(&len_buf);
+    reader.read_exact(&mut frame[4..]).await?;
+    Ok(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::domain::admin::{AdminResponse, RuntimeConfig};
+    use crate::orderbook::{OrderBookManager, TradingMode};
+    use crate::unicase::domain::unicase::{MessageType, UnicastMessage};
+    use parking_lot::Mutex;
+    use rcgen::{
+        BasicConstraints, CertificateParams, DistinguishedName, DnType, ExtendedKeyUsagePurpose, IsCa, Issuer, KeyPair,
+    };
+    use rustls::pki_types::ServerName;
+    use rustls::ClientConfig;
+    use tokio_rustls::client::TlsStream;
+    use tokio_rustls::TlsConnector;
+
+    /// 测试用CA及其签发的证书
+    struct Pki {
+        ca: CertificateDer<'static>,
+        issuer: Issuer<'static, KeyPair>,
+    }
+
+    impl Pki {
+        fn new(name: &str) -> Self {
+            let mut params = CertificateParams::default();
+            params.distinguished_name = DistinguishedName::new();
+            params.distinguished_name.push(DnType::CommonName, name);
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let key = KeyPair::generate().unwrap();
+            let ca = params.self_signed(&key).unwrap().der().clone();
+            Self {
+                ca,
+                issuer: Issuer::new(params, key),
+            }
+        }
+
+        fn issue(
+            &self,
+            names: Vec<String>,
+            subject: &[(DnType, &str)],
+            usage: ExtendedKeyUsagePurpose,
+        ) -> (Vec<CertificateDer<'static>>, PrivateKeyDer<'static>) {
+            let mut params = CertificateParams::new(names).unwrap();
+            params.distinguished_name = DistinguishedName::new();
+            for (kind, value) in subject {
+                params.distinguished_name.push(kind.clone(), *value);
+            }
+            params.extended_key_usages = vec![usage];
+            let key = KeyPair::generate().unwrap();
+            let certificate = params.signed_by(&key, &self.issuer).unwrap().der().clone();
+            let key = PrivateKeyDer::try_from(key.serialize_der()).unwrap();
+            (vec![certificate], key)
+        }
+
+        fn client(&self, common_name: &str, role: &str) -> (Vec<CertificateDer<'static>>, PrivateKeyDer<'static>) {
+            let subject = [(DnType::CommonName, common_name), (DnType::OrganizationalUnitName, role)];
+            self.issue(vec![], &subject, ExtendedKeyUsagePurpose::ClientAuth)
+        }
+    }
+
+    struct Fixture {
+        pki: Pki,
+        addr: SocketAddr,
+        books: Arc<Mutex<OrderBookManager>>,
+        access: Arc<AdminAccessControl>,
+    }
+
+    async fn start() -> Fixture {
+        let pki = Pki::new("rlob admin CA");
+        let (chain, key) =
+            pki.issue(vec!["localhost".to_string()], &[(DnType::CommonName, "localhost")], ExtendedKeyUsagePurpose::ServerAuth);
+        let tls = server_config(chain, key, vec![pki.ca.clone()]).unwrap();
+
+        let books = Arc::new(Mutex::new(OrderBookManager::new(1_000, 100)));
+        books.lock().add_symbol("BTCUSDT");
+        let access = Arc::new(AdminAccessControl::new(Arc::new(RuntimeConfig::new())).with_books(Arc::clone(&books)));
+        let server = AdminTlsServer::bind("127.0.0.1:0".parse().unwrap(), tls, Arc::clone(&access)).await.unwrap();
+        let addr = server.local_addr().unwrap();
+        server.spawn();
+        Fixture { pki, addr, books, access }
+    }
+
+    async fn connect(
+        fixture: &Fixture,
+        identity: Option<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)>,
+    ) -> io::Result<TlsStream<TcpStream>> {
+        let mut roots = RootCertStore::empty();
+        roots.add(fixture.pki.ca.clone()).unwrap();
+        let builder = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots);
+        let config = match identity {
+            Some((chain, key)) => builder.with_client_auth_cert(chain, key).unwrap(),
+            None => builder.with_no_client_auth(),
+        };
+        let stream = TcpStream::connect(fixture.addr).await?;
+        TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+    }
+
+    /// 发送管理指令并读取回复（连接被拒绝时返回错误）
+    async fn call(stream: &mut TlsStream<TcpStream>, json: &str) -> io::Result<AdminResponse> {
+        let message = UnicastMessage {
+            message_id: 1,
+            timestamp_ns: 0,
+            msg_type: MessageType::ConfigSync,
+            payload: json.as_bytes().to_vec(),
+        };
+        stream.write_all(&TcpUnicastServer::serialize_message(&message)).await?;
+        let frame = tokio::time::timeout(Duration::from_secs(5), read_frame(stream)).await??;
+        let reply = TcpUnicastClient::deserialize_message(&frame).unwrap();
+        Ok(serde_json::from_slice(&reply.payload).unwrap())
+    }
+
+    const HALT: &str = r#"{"cmd":"halt","symbol":"BTCUSDT"}"#;
+
+    #[tokio::test]
+    async fn test_roles_from_verified_client_certificate() {
+        let fixture = start().await;
+        let mode = || fixture.books.lock().book("BTCUSDT").unwrap().trading_mode();
+
+        let mut viewer = connect(&fixture, Some(fixture.pki.client("dash", "read-only"))).await.unwrap();
+        assert!(call(&mut viewer, r#"{"cmd":"get_config"}"#).await.unwrap().ok);
+        assert!(!call(&mut viewer, HALT).await.unwrap().ok);
+        assert_eq!(mode(), TradingMode::Normal);
+
+        // CN中的逗号不会被解析成OU
+        let mut forged = connect(&fixture, Some(fixture.pki.client("x,OU=admin", "read-only"))).await.unwrap();
+        assert!(!call(&mut forged, HALT).await.unwrap().ok);
+        assert_eq!(mode(), TradingMode::Normal);
+
+        let mut admin = connect(&fixture, Some(fixture.pki.client("ops1", "admin"))).await.unwrap();
+        assert!(call(&mut admin, HALT).await.unwrap().ok);
+        assert_eq!(mode(), TradingMode::Halted);
+
+        let log = fixture.access.audit_log();
+        let principals: Vec<_> = log.iter().map(|record| (record.principal.as_deref(), record.allowed)).collect();
+        assert_eq!(principals, [(Some("dash"), false), (Some("x,OU=admin"), false), (Some("ops1"), true)]);
+    }
+
+    #[tokio::test]
+    async fn test_connections_without_valid_certificate_are_refused() {
+        let fixture = start().await;
+
+        // TLS 1.3下客户端先完成握手，服务端校验失败后发送告警并断开
+        for identity in [None, Some(Pki::new("other CA").client("ops1", "admin"))] {
+            if let Ok(mut stream) = connect(&fixture, identity).await {
+                assert!(call(&mut stream, HALT).await.is_err());
+            }
+        }
+        assert_eq!(fixture.books.lock().book("BTCUSDT").unwrap().trading_mode(), TradingMode::Normal);
+        assert!(fixture.access.audit_log().is_empty());
+    }
+}
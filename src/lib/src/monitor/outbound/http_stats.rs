@@ -0,0 +1,178 @@
+/// 统计HTTP端点
+///
+/// 极简HTTP/1.1服务，响应`GET /stats`，返回`StatsRegistry`生成的JSON文档；
+/// 挂载`MetricsRegistry`后另响应`GET /metrics`，返回Prometheus文本格式指标。
+/// 每个请求处理完即关闭连接，不依赖额外的HTTP框架。
+
+use crate::monitor::domain::metrics::{self, MetricsRegistry};
+use crate::monitor::domain::report::StatsRegistry;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// 统计端点路径
+pub const STATS_PATH: &str = "/stats";
+
+/// Prometheus指标端点路径
+pub const METRICS_PATH: &str = "/metrics";
+
+/// 请求头最大长度
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// 读取请求超时
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 统计HTTP服务器
+pub struct StatsHttpServer {
+    listener: TcpListener,
+    registry: Arc<StatsRegistry>,
+    metrics: Option<Arc<MetricsRegistry>>,
+}
+
+impl StatsHttpServer {
+    /// 绑定监听地址
+    pub async fn bind(addr: SocketAddr, registry: Arc<StatsRegistry>) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        Ok(Self { listener, registry, metrics: None })
+    }
+
+    /// 挂载Prometheus指标端点
+    pub fn with_metrics(mut self, metrics: Arc<MetricsRegistry>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// 获取实际监听地址（绑定端口0时使用）
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// 启动服务
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match self.listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(Self::handle(stream, Arc::clone(&self.registry), self.metrics.clone()));
+                    }
+                    Err(e) => {
+                        crate::runtime_log!(Warn, "Failed to accept stats connection: {}", e);
+                    }
+                }
+            }
+        })
+    }
+
+    async fn handle(mut stream: TcpStream, registry: Arc<StatsRegistry>, metrics: Option<Arc<MetricsRegistry>>) {
+        let request = match tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await {
+            Ok(Ok(request)) => request,
+            _ => return,
+        };
+
+        let response = match parse_request_line(&request) {
+            Some(("GET", path)) if path.split('?').next() == Some(STATS_PATH) => {
+                let body = registry.report().to_string();
+                http_response("200 OK", "application/json", &body)
+            }
+            Some(("GET", path)) if path.split('?').next() == Some(METRICS_PATH) && metrics.is_some() => {
+                let body = metrics.as_ref().map(|m| m.render()).unwrap_or_default();
+                http_response("200 OK", metrics::CONTENT_TYPE, &body)
+            }
+            Some(("GET", _)) => http_response("404 Not Found", "text/plain", "Not Found"),
+            Some(_) => http_response("405 Method Not Allowed", "text/plain", "Method Not Allowed"),
+            None => http_response("400 Bad Request", "text/plain", "Bad Request"),
+        };
+
+        let _ = stream.write_all(response.as_bytes()).await;
+        let _ = stream.shutdown().await;
+    }
+}
+
+/// 读取请求头（直到空行）
+async fn read_request(stream: &mut TcpStream) -> io::Result<String> {
+    let mut buf = Vec::with_capacity(512);
+    let mut chunk = [0u8; 512];
+
+    loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if buf.windows(4).any(|w| w == b"\r\n\r\n") || buf.len() >= MAX_REQUEST_SIZE {
+            break;
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// 解析请求行，返回(方法, 路径)
+fn parse_request_line(request: &str) -> Option<(&str, &str)> {
+    let mut parts = request.lines().next()?.split_whitespace();
+    let method = parts.next()?;
+    let path = parts.next()?;
+    Some((method, path))
+}
+
+fn http_response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unicase::domain::unicase::ServerStats;
+
+    async fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_stats_endpoint() {
+        let registry = Arc::new(StatsRegistry::with_version("test"));
+        registry.add_server("orders", || ServerStats {
+            active_connections: 3,
+            ..Default::default()
+        });
+
+        let metrics = Arc::new(MetricsRegistry::new());
+        metrics.add_engine("BTCUSDT", || crate::orderbook::OrderBook::with_capacity(1_000, 10).snapshot());
+
+        let server = StatsHttpServer::bind("127.0.0.1:0".parse().unwrap(), registry)
+            .await
+            .unwrap()
+            .with_metrics(metrics);
+        let addr = server.local_addr().unwrap();
+        let handle = server.spawn();
+
+        let response = get(addr, "/stats").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let doc: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(doc["version"], "test");
+        assert_eq!(doc["servers"]["orders"]["active_connections"], 3);
+
+        let response = get(addr, "/metrics").await;
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4"));
+        assert!(response.contains("orderbook_arena_capacity{engine=\"BTCUSDT\"} 10\n"));
+
+        assert!(get(addr, "/other").await.starts_with("HTTP/1.1 404"));
+        handle.abort();
+    }
+}
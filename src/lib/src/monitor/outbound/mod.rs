@@ -0,0 +1,2 @@
+pub mod admin_tls;
+pub mod http_stats;
@@ -0,0 +1,419 @@
+/// eth_getProof（EIP-1186）响应的验证
+///
+/// 以太坊节点的`eth_getProof`返回账户证明和若干存储证明，全部以十六进制字符串表示：
+/// - `accountProof`：状态trie中从根到账户的节点RLP编码，键为`keccak256(address)`
+/// - `storageProof[i].proof`：账户存储trie中从根到槽位的节点RLP编码，键为`keccak256(pad32(key))`
+/// - `nonce`、`balance`、`storage[i].value`为数量（quantity），`storageHash`、`codeHash`为32字节哈希
+///
+/// 验证用本crate的证明验证器对区块头的`stateRoot`逐级检查，并要求响应中声明的账户字段和
+/// 槽位值与证明出的值一致。不存在的账户只要求nonce和余额为零（各客户端对其哈希字段的
+/// 填法不同），其存储证明对空trie根验证。空trie的证明可以是空列表。
+
+use super::hash::{hex_to_bytes, keccak256};
+use super::node::Node;
+use super::proof::{MerkleProof, ProofError};
+use super::rlp::RlpError;
+use super::state::{decode_slot_value, empty_storage_root, Account, Address, StorageSlot};
+use serde::Deserialize;
+use thiserror::Error;
+
+/// eth_getProof验证错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EthProofError {
+    #[error("invalid eth_getProof JSON: {0}")]
+    Json(String),
+    #[error("field `{0}` is not valid hex")]
+    InvalidHex(&'static str),
+    #[error("proof entry {0} is not a canonical node encoding")]
+    MalformedNode(usize),
+    #[error("account proof: {0}")]
+    Account(ProofError),
+    #[error("proven account: {0}")]
+    InvalidAccount(RlpError),
+    #[error("account field `{0}` does not match the proof")]
+    AccountMismatch(&'static str),
+    #[error("storage proof {index}: {error}")]
+    Storage { index: usize, error: ProofError },
+    #[error("storage proof {index}: proven value: {error}")]
+    InvalidStorageValue { index: usize, error: RlpError },
+    #[error("storage proof {0}: value does not match the proof")]
+    StorageMismatch(usize),
+}
+
+/// 单个存储槽位的证明
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EthStorageProof {
+    /// 请求的槽位（不足32字节时左补零）
+    pub key: String,
+    /// 槽位值（数量，零表示不存在）
+    pub value: String,
+    /// 存储trie节点的RLP编码
+    pub proof: Vec<String>,
+}
+
+/// `eth_getProof`的响应（JSON-RPC的`result`字段）
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthProof {
+    pub address: String,
+    /// 状态trie节点的RLP编码
+    pub account_proof: Vec<String>,
+    pub balance: String,
+    pub code_hash: String,
+    pub nonce: String,
+    pub storage_hash: String,
+    pub storage_proof: Vec<EthStorageProof>,
+}
+
+impl EthProof {
+    /// 从`result`的JSON文本解析
+    pub fn from_json(json: &str) -> Result<Self, EthProofError> {
+        serde_json::from_str(json).map_err(|e| EthProofError::Json(e.to_string()))
+    }
+
+    /// 对区块的`stateRoot`验证，返回证明出的账户（None表示账户不存在）
+    pub fn verify(&self, state_root: &[u8]) -> Result<Option<Account>, EthProofError> {
+        let address: Address = fixed(&self.address, "address")?;
+        let key = keccak256(&address).to_vec();
+        let account = match prove(state_root, key, &self.account_proof).map_err(|e| e.into_account())? {
+            Some(encoded) => Some(Account::decode(&encoded).map_err(EthProofError::InvalidAccount)?),
+            None => None,
+        };
+
+        let nonce = quantity(&self.nonce, "nonce")?;
+        let balance = quantity(&self.balance, "balance")?;
+        match &account {
+            Some(account) => {
+                if u128::from(account.nonce) != nonce {
+                    return Err(EthProofError::AccountMismatch("nonce"));
+                }
+                if account.balance != balance {
+                    return Err(EthProofError::AccountMismatch("balance"));
+                }
+                if account.storage_root != fixed::<32>(&self.storage_hash, "storageHash")? {
+                    return Err(EthProofError::AccountMismatch("storageHash"));
+                }
+                if account.code_hash != fixed::<32>(&self.code_hash, "codeHash")? {
+                    return Err(EthProofError::AccountMismatch("codeHash"));
+                }
+            }
+            None if nonce != 0 => return Err(EthProofError::AccountMismatch("nonce")),
+            None if balance != 0 => return Err(EthProofError::AccountMismatch("balance")),
+            None => {}
+        }
+
+        let storage_root = account.map_or_else(empty_storage_root, |account| account.storage_root);
+        for (index, slot) in self.storage_proof.iter().enumerate() {
+            let key = keccak256(&word(&slot.key, "storageProof.key")?).to_vec();
+            let proven = match prove(&storage_root, key, &slot.proof).map_err(|e| e.into_storage(index))? {
+                Some(encoded) => decode_slot_value(&encoded)
+                    .map_err(|error| EthProofError::InvalidStorageValue { index, error })?,
+                None => StorageSlot::default(),
+            };
+            if proven != word(&slot.value, "storageProof.value")? {
+                return Err(EthProofError::StorageMismatch(index));
+            }
+        }
+        Ok(account)
+    }
+}
+
+/// 证明节点解码或验证失败（尚未区分是账户证明还是存储证明）
+enum PathError {
+    MalformedNode(usize),
+    Proof(ProofError),
+    Hex(&'static str),
+}
+
+impl PathError {
+    fn into_account(self) -> EthProofError {
+        match self {
+            PathError::MalformedNode(i) => EthProofError::MalformedNode(i),
+            PathError::Proof(error) => EthProofError::Account(error),
+            PathError::Hex(field) => EthProofError::InvalidHex(field),
+        }
+    }
+
+    fn into_storage(self, index: usize) -> EthProofError {
+        match self {
+            PathError::MalformedNode(i) => EthProofError::MalformedNode(i),
+            PathError::Proof(error) => EthProofError::Storage { index, error },
+            PathError::Hex(field) => EthProofError::InvalidHex(field),
+        }
+    }
+}
+
+/// 解码节点并沿`key`验证，返回根下该键的值
+fn prove(root: &[u8], key: Vec<u8>, encoded_nodes: &[String]) -> Result<Option<Vec<u8>>, PathError> {
+    // 空trie的证明可以不含任何节点
+    if encoded_nodes.is_empty() && root == empty_storage_root() {
+        return Ok(None);
+    }
+    let mut nodes = Vec::with_capacity(encoded_nodes.len());
+    for (i, hex) in encoded_nodes.iter().enumerate() {
+        let raw = hex_to_bytes(hex).ok_or(PathError::Hex("proof"))?;
+        // 重新编码必须与原始字节一致，否则节点哈希对不上原始引用
+        match Node::decode(&raw) {
+            Ok(node) if node.encode() == raw => nodes.push(node),
+            _ => return Err(PathError::MalformedNode(i)),
+        }
+    }
+    MerkleProof::new(key, None, nodes).verified_value(root).map_err(PathError::Proof)
+}
+
+/// 解析定长十六进制字段（地址、哈希）
+fn fixed<const N: usize>(hex: &str, field: &'static str) -> Result<[u8; N], EthProofError> {
+    hex_to_bytes(hex)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(EthProofError::InvalidHex(field))
+}
+
+/// 解析最多32字节的十六进制值，左补零为一个字
+fn word(hex: &str, field: &'static str) -> Result<StorageSlot, EthProofError> {
+    let bytes = hex_to_bytes(hex).ok_or(EthProofError::InvalidHex(field))?;
+    let skip = bytes.iter().take_while(|&&b| b == 0).count();
+    let bytes = &bytes[skip..];
+    if bytes.len() > 32 {
+        return Err(EthProofError::InvalidHex(field));
+    }
+    let mut word = StorageSlot::default();
+    word[32 - bytes.len()..].copy_from_slice(bytes);
+    Ok(word)
+}
+
+/// 解析数量字段（nonce、余额）
+fn quantity(hex: &str, field: &'static str) -> Result<u128, EthProofError> {
+    let word = word(hex, field)?;
+    if word[..16].iter().any(|&b| b != 0) {
+        return Err(EthProofError::InvalidHex(field));
+    }
+    Ok(u128::from_be_bytes(word[16..].try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpt::hash::hash_to_hex;
+    use crate::mpt::state::WorldState;
+    use serde_json::{json, Value};
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    const CONTRACT: Address = [0xcc; 20];
+
+    fn slot(n: u8) -> StorageSlot {
+        let mut slot = [0u8; 32];
+        slot[31] = n;
+        slot
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        format!("0x{}", hash_to_hex(bytes))
+    }
+
+    /// 数量的十六进制表示（无前导零）
+    fn quantity_hex(value: u128) -> String {
+        format!("{:#x}", value)
+    }
+
+    fn state() -> WorldState {
+        let mut state = WorldState::new();
+        for i in 0..50u8 {
+            state.set_account(&[i; 20], Account::new(i as u64, i as u128 * 1_000_000_007));
+        }
+        state.set_account(&CONTRACT, Account::new(1, 10u128.pow(21)));
+        for i in 1..30u8 {
+            state.set_storage(&CONTRACT, &slot(i), &[i; 32]);
+        }
+        state
+    }
+
+    /// 按节点返回格式构造eth_getProof响应
+    fn get_proof(state: &WorldState, address: &Address, slots: &[StorageSlot]) -> Value {
+        let nodes = |proof: MerkleProof| -> Vec<String> {
+            proof.proof_nodes.iter().map(|node| hex(&node.encode())).collect()
+        };
+        let account = state.account(address).unwrap_or_default();
+        let storage: Vec<Value> = slots
+            .iter()
+            .map(|slot| {
+                let value = state.storage_at(address, slot);
+                let skip = value.iter().take_while(|&&b| b == 0).count();
+                json!({
+                    "key": hex(slot),
+                    "value": if skip == 32 { "0x0".to_string() } else { hex(&value[skip..]) },
+                    "proof": nodes(state.storage_proof(address, slot)),
+                })
+            })
+            .collect();
+        json!({
+            "address": hex(address),
+            "accountProof": nodes(state.account_proof(address)),
+            "balance": quantity_hex(account.balance),
+            "codeHash": hex(&account.code_hash),
+            "nonce": quantity_hex(account.nonce as u128),
+            "storageHash": hex(&state.storage_root(address)),
+            "storageProof": storage,
+        })
+    }
+
+    fn parse(value: &Value) -> EthProof {
+        EthProof::from_json(&value.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_verify_get_proof_response() {
+        let state = state();
+        let root = state.state_root();
+
+        let response = get_proof(&state, &CONTRACT, &[slot(3), slot(29), slot(200)]);
+        let account = parse(&response).verify(&root).unwrap().unwrap();
+        assert_eq!(account, state.account(&CONTRACT).unwrap());
+
+        // 不存在的账户：nonce、余额为零，存储证明为空列表（对空trie根）
+        let mut absent = get_proof(&state, &[0xee; 20], &[]);
+        absent["storageProof"] = json!([{ "key": "0x0", "value": "0x0", "proof": [] }]);
+        assert_eq!(parse(&absent).verify(&root), Ok(None));
+
+        // 短槽位键和值按数量左补零
+        let mut short = response.clone();
+        short["storageProof"][0]["key"] = json!("0x3");
+        assert_eq!(parse(&short).verify(&root), Ok(Some(account)));
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering() {
+        let state = state();
+        let root = state.state_root();
+        let response = get_proof(&state, &CONTRACT, &[slot(3), slot(200)]);
+        let tamper = |f: &dyn Fn(&mut Value)| {
+            let mut forged = response.clone();
+            f(&mut forged);
+            parse(&forged).verify(&root)
+        };
+
+        assert_eq!(tamper(&|r| r["balance"] = json!("0x1")), Err(EthProofError::AccountMismatch("balance")));
+        assert_eq!(tamper(&|r| r["nonce"] = json!("0x2")), Err(EthProofError::AccountMismatch("nonce")));
+        assert_eq!(
+            tamper(&|r| r["storageHash"] = json!(hex(&empty_storage_root()))),
+            Err(EthProofError::AccountMismatch("storageHash"))
+        );
+        assert_eq!(tamper(&|r| r["storageProof"][0]["value"] = json!("0x4")), Err(EthProofError::StorageMismatch(0)));
+        // 不存在的槽位不能声明为非零
+        assert_eq!(tamper(&|r| r["storageProof"][1]["value"] = json!("0x1")), Err(EthProofError::StorageMismatch(1)));
+        // 其它账户的证明
+        assert!(tamper(&|r| r["address"] = json!(hex(&[0x01; 20]))).is_err());
+        assert!(matches!(
+            tamper(&|r| { r["accountProof"].as_array_mut().unwrap().pop(); }),
+            Err(EthProofError::Account(ProofError::Incomplete))
+        ));
+        assert!(matches!(
+            tamper(&|r| { r["storageProof"][0]["proof"].as_array_mut().unwrap().pop(); }),
+            Err(EthProofError::Storage { index: 0, error: ProofError::Incomplete })
+        ));
+        assert_eq!(
+            tamper(&|r| r["accountProof"][0] = json!("0xc0c0")),
+            Err(EthProofError::MalformedNode(0))
+        );
+        assert_eq!(tamper(&|r| r["address"] = json!("0x1234")), Err(EthProofError::InvalidHex("address")));
+
+        assert_eq!(parse(&response).verify(&[0; 32]), Err(EthProofError::Account(ProofError::RootMismatch)));
+        assert!(matches!(EthProof::from_json("{}"), Err(EthProofError::Json(_))));
+    }
+
+    /// JSON-RPC调用（仅支持http://，HTTP/1.0短连接）
+    fn rpc(url: &str, method: &str, params: Value) -> Value {
+        let rest = url.strip_prefix("http://").expect("ETH_RPC_URL must be an http:// URL");
+        let (host, path) = rest.split_once('/').map_or((rest, "/".to_string()), |(h, p)| (h, format!("/{}", p)));
+        let addr = if host.contains(':') { host.to_string() } else { format!("{}:80", host) };
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }).to_string();
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "POST {} HTTP/1.0\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            path,
+            host,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").expect("HTTP response has a body");
+        let mut reply: Value = serde_json::from_str(body).unwrap();
+        assert!(reply["error"].is_null(), "{} failed: {}", method, reply["error"]);
+        reply["result"].take()
+    }
+
+    /// 主网eth_getProof响应采集文件：`{"blockNumber", "stateRoot", "proof"}`，由`test_verify_live_node_proof`写入
+    const MAINNET_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/eth_get_proof_mainnet.json");
+
+    /// 离线验证采集的主网证明（账户证明、存储证明与区块stateRoot），并检查错误根与篡改节点被拒绝
+    ///
+    /// 采集文件缺失时失败而不是跳过。
+    #[test]
+    fn test_verify_captured_mainnet_proof() {
+        let text = std::fs::read_to_string(MAINNET_FIXTURE).unwrap_or_else(|error| {
+            panic!(
+                "{}: {} (capture it with ETH_RPC_URL=<mainnet node> ETH_PROOF_FIXTURE=1 \
+                 cargo test -p lib eth_proof -- --ignored and commit the file)",
+                MAINNET_FIXTURE, error
+            )
+        });
+        let fixture: Value = serde_json::from_str(&text).unwrap();
+        let state_root = hex_to_bytes(fixture["stateRoot"].as_str().unwrap()).unwrap();
+        let proof = EthProof::from_json(&fixture["proof"].to_string()).unwrap();
+        assert!(!proof.storage_proof.is_empty() && !proof.storage_proof[0].proof.is_empty());
+
+        let account = proof.verify(&state_root).unwrap().expect("captured account exists");
+        assert_eq!(account.storage_root.as_slice(), hex_to_bytes(&proof.storage_hash).unwrap().as_slice());
+
+        let mut wrong_root = state_root.clone();
+        wrong_root[0] ^= 1;
+        assert_eq!(proof.verify(&wrong_root), Err(EthProofError::Account(ProofError::RootMismatch)));
+
+        // 篡改中间节点的一个字节：父节点引用的哈希不再匹配
+        let mut tampered = proof.clone();
+        let node = &mut tampered.account_proof[1];
+        let flipped = if node.ends_with('0') { '1' } else { '0' };
+        node.pop();
+        node.push(flipped);
+        assert!(matches!(tampered.verify(&state_root), Err(EthProofError::Account(_) | EthProofError::MalformedNode(1))));
+
+        let mut tampered = proof.clone();
+        let node = &mut tampered.storage_proof[0].proof[0];
+        node.replace_range(node.len() - 2.., if node.ends_with("00") { "01" } else { "00" });
+        assert!(tampered.verify(&state_root).is_err());
+    }
+
+    /// 对真实节点验证：ETH_RPC_URL=http://host:8545 cargo test -p lib eth_proof -- --ignored
+    ///
+    /// 可用ETH_PROOF_ADDRESS和ETH_PROOF_SLOT指定账户和槽位（默认WETH合约的槽位0）；
+    /// 设置ETH_PROOF_FIXTURE=1时把响应和区块stateRoot写入离线测试使用的采集文件。
+    #[test]
+    #[ignore = "requires ETH_RPC_URL pointing at an Ethereum node"]
+    fn test_verify_live_node_proof() {
+        let url = std::env::var("ETH_RPC_URL").expect("ETH_RPC_URL is not set");
+        let address = std::env::var("ETH_PROOF_ADDRESS")
+            .unwrap_or_else(|_| "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string());
+        let slot = std::env::var("ETH_PROOF_SLOT").unwrap_or_else(|_| "0x0".to_string());
+
+        let block = rpc(&url, "eth_getBlockByNumber", json!(["latest", false]));
+        let state_root = hex_to_bytes(block["stateRoot"].as_str().unwrap()).unwrap();
+        let result = rpc(&url, "eth_getProof", json!([address, [slot], block["number"]]));
+
+        let proof = EthProof::from_json(&result.to_string()).unwrap();
+        let account = proof.verify(&state_root).unwrap();
+        if std::env::var_os("ETH_PROOF_FIXTURE").is_some() {
+            let fixture = json!({ "blockNumber": block["number"], "stateRoot": block["stateRoot"], "proof": result });
+            std::fs::create_dir_all(std::path::Path::new(MAINNET_FIXTURE).parent().unwrap()).unwrap();
+            std::fs::write(MAINNET_FIXTURE, serde_json::to_string_pretty(&fixture).unwrap()).unwrap();
+        }
+        assert!(account.is_some(), "{} does not exist at block {}", address, block["number"]);
+
+        let mut forged = proof.clone();
+        forged.balance = quantity_hex(account.unwrap().balance + 1);
+        assert_eq!(forged.verify(&state_root), Err(EthProofError::AccountMismatch("balance")));
+    }
+}
@@ -1,32 +1,94 @@
 /// Keccak256 hash function for Ethereum
 ///
-/// For now, we'll use a simplified version for demonstration.
-/// In production, use a proper crypto library.
+/// Self-contained Keccak-f[1600] sponge with Ethereum's parameters: 136-byte
+/// rate and the original Keccak padding (0x01 ... 0x80), which differs from
+/// the NIST SHA3-256 padding (0x06 ... 0x80).
 
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+/// Length of a node hash; shorter node references are embedded encodings
+pub const HASH_LEN: usize = 32;
 
-/// Compute Keccak256 hash (simplified version for MVP)
-///
-/// Note: This is NOT the real Keccak256! For production use,
-/// integrate with a proper crypto library like tiny-keccak.
-///
-/// For demonstration purposes, we use a simple hash function.
-pub fn keccak256(data: &[u8]) -> [u8; 32] {
-    // WARNING: This is a placeholder!
-    // Real implementation should use proper Keccak256
-    let mut hasher = DefaultHasher::new();
-    data.hash(&mut hasher);
-    let hash_val = hasher.finish();
+/// Sponge rate in bytes for a 256-bit output
+const RATE: usize = 136;
 
-    let mut result = [0u8; 32];
-    result[0..8].copy_from_slice(&hash_val.to_le_bytes());
+/// Round constants (iota step)
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+/// Rotation offsets along the rho-pi lane walk
+const RHO: [u32; 24] = [1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44];
+
+/// Lane visited at each step of the rho-pi walk (starting from lane 1)
+const PI: [usize; 24] = [10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1];
+
+/// Keccak-f[1600] permutation over 25 lanes (lane x + 5y)
+fn keccak_f(state: &mut [u64; 25]) {
+    for round_constant in ROUND_CONSTANTS {
+        // theta
+        let mut columns = [0u64; 5];
+        for (x, column) in columns.iter_mut().enumerate() {
+            *column = (0..5).fold(0, |parity, y| parity ^ state[x + 5 * y]);
+        }
+        for x in 0..5 {
+            let d = columns[(x + 4) % 5] ^ columns[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+
+        // rho + pi
+        let mut carried = state[1];
+        for (&lane, &rotation) in PI.iter().zip(&RHO) {
+            let next = state[lane];
+            state[lane] = carried.rotate_left(rotation);
+            carried = next;
+        }
 
-    // Fill rest with deterministic pattern
-    for i in 8..32 {
-        result[i] = ((hash_val >> ((i - 8) % 8)) & 0xFF) as u8;
+        // chi
+        for row in state.chunks_exact_mut(5) {
+            let lanes = [row[0], row[1], row[2], row[3], row[4]];
+            for (x, lane) in row.iter_mut().enumerate() {
+                *lane = lanes[x] ^ (!lanes[(x + 1) % 5] & lanes[(x + 2) % 5]);
+            }
+        }
+
+        // iota
+        state[0] ^= round_constant;
     }
+}
 
+/// XOR one rate-sized block into the state (lanes are little-endian)
+fn absorb(state: &mut [u64; 25], block: &[u8; RATE]) {
+    for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+        *lane ^= u64::from_le_bytes(bytes.try_into().unwrap());
+    }
+    keccak_f(state);
+}
+
+/// Compute Keccak256 hash
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut state = [0u64; 25];
+    let mut blocks = data.chunks_exact(RATE);
+    for block in &mut blocks {
+        absorb(&mut state, block.try_into().unwrap());
+    }
+
+    let tail = blocks.remainder();
+    let mut last = [0u8; RATE];
+    last[..tail.len()].copy_from_slice(tail);
+    last[tail.len()] ^= 0x01;
+    last[RATE - 1] ^= 0x80;
+    absorb(&mut state, &last);
+
+    let mut result = [0u8; 32];
+    for (bytes, lane) in result.chunks_exact_mut(8).zip(&state) {
+        bytes.copy_from_slice(&lane.to_le_bytes());
+    }
     result
 }
 
@@ -37,10 +99,51 @@ pub fn hash_to_hex(hash: &[u8]) -> String {
         .collect()
 }
 
+/// Parse a hex string (optional `0x` prefix; an odd digit count is left-padded with 0)
+pub fn hex_to_bytes(hex: &str) -> Option<Vec<u8>> {
+    let digits = hex.strip_prefix("0x").unwrap_or(hex);
+    let padded = if digits.len() % 2 == 1 { format!("0{}", digits) } else { digits.to_string() };
+    (0..padded.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(padded.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_keccak256_vectors() {
+        assert_eq!(
+            hash_to_hex(&keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hash_to_hex(&keccak256(b"abc")),
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+        // 空trie根：keccak256(rlp(""))
+        assert_eq!(
+            hash_to_hex(&keccak256(&[0x80])),
+            "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+        );
+
+        // 跨越速率边界的输入
+        let long = [0x61u8; 300];
+        for len in [135, 136, 137, 272, 300] {
+            assert_ne!(keccak256(&long[..len]), keccak256(&long[..len - 1]));
+        }
+    }
+
+    #[test]
+    fn test_hex_to_bytes() {
+        assert_eq!(hex_to_bytes("0x1234"), Some(vec![0x12, 0x34]));
+        assert_eq!(hex_to_bytes("abc"), Some(vec![0x0a, 0xbc]));
+        assert_eq!(hex_to_bytes("0x"), Some(vec![]));
+        assert_eq!(hex_to_bytes("0xzz"), None);
+    }
+
     #[test]
     fn test_keccak256() {
         let data = b"hello world";
@@ -0,0 +1,153 @@
+/// Trie iteration
+///
+/// Walks the trie depth-first in lexicographic key order. A branch's own value
+/// (a key that is a prefix of others) is yielded before its children, and
+/// children are visited from nibble 0 to 15, which matches byte ordering.
+
+use super::nibbles::{bytes_to_nibbles, nibbles_to_bytes};
+use super::node::Node;
+use super::trie::MerklePatriciaTrie;
+use std::sync::Arc;
+
+/// Iterator over (key, value) pairs in lexicographic key order
+pub struct TrieIter<'a> {
+    trie: &'a MerklePatriciaTrie,
+    /// Pending nodes with the nibble path leading to them (next to visit on top)
+    stack: Vec<(Arc<Node>, Vec<u8>)>,
+}
+
+impl<'a> TrieIter<'a> {
+    /// Iterate over every entry
+    pub(super) fn new(trie: &'a MerklePatriciaTrie) -> Self {
+        Self {
+            trie,
+            stack: vec![(trie.shared_root(), Vec::new())],
+        }
+    }
+
+    /// Iterate over the entries whose key starts with `prefix`
+    pub(super) fn with_prefix(trie: &'a MerklePatriciaTrie, prefix: &[u8]) -> Self {
+        let stack = seek(trie, trie.shared_root(), &bytes_to_nibbles(prefix), Vec::new())
+            .map(|start| vec![start])
+            .unwrap_or_default();
+        Self { trie, stack }
+    }
+}
+
+/// Find the highest node whose subtree holds exactly the keys extending `prefix` (in nibbles)
+fn seek(trie: &MerklePatriciaTrie, node: Arc<Node>, prefix: &[u8], walked: Vec<u8>) -> Option<(Arc<Node>, Vec<u8>)> {
+    match node.as_ref() {
+        Node::Empty => None,
+
+        Node::Leaf { path, .. } => path.starts_with(prefix).then_some((node, walked)),
+
+        Node::Extension { path, child_hash } => {
+            if path.starts_with(prefix) {
+                Some((node, walked))
+            } else if prefix.starts_with(path) {
+                let child = trie.resolve(child_hash)?;
+                seek(trie, child, &prefix[path.len()..], [walked.as_slice(), path].concat())
+            } else {
+                None
+            }
+        }
+
+        Node::Branch { children, .. } => match prefix.split_first() {
+            None => Some((node, walked)),
+            Some((&nibble, rest)) => {
+                let child = trie.resolve(children[nibble as usize].as_ref()?)?;
+                seek(trie, child, rest, [walked, vec![nibble]].concat())
+            }
+        },
+    }
+}
+
+impl Iterator for TrieIter<'_> {
+    type Item = (Vec<u8>, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, walked)) = self.stack.pop() {
+            match node.as_ref() {
+                Node::Empty => {}
+
+                Node::Leaf { path, value } => {
+                    return Some((nibbles_to_bytes(&[walked.as_slice(), path].concat()), value.clone()));
+                }
+
+                Node::Extension { path, child_hash } => {
+                    if let Some(child) = self.trie.resolve(child_hash) {
+                        self.stack.push((child, [walked.as_slice(), path].concat()));
+                    }
+                }
+
+                Node::Branch { children, value } => {
+                    // 逆序入栈，使nibble 0先出栈
+                    for (nibble, child_ref) in children.iter().enumerate().rev() {
+                        if let Some(child) = child_ref.as_ref().and_then(|r| self.trie.resolve(r)) {
+                            let mut path = walked.clone();
+                            path.push(nibble as u8);
+                            self.stack.push((child, path));
+                        }
+                    }
+                    if let Some(value) = value {
+                        return Some((nibbles_to_bytes(&walked), value.clone()));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trie(entries: &[(&[u8], &[u8])]) -> MerklePatriciaTrie {
+        let mut trie = MerklePatriciaTrie::new();
+        for (key, value) in entries {
+            trie.insert(key, value);
+        }
+        trie
+    }
+
+    #[test]
+    fn test_iter_in_key_order() {
+        let entries: [(&[u8], &[u8]); 7] = [
+            (b"horse", b"stallion"),
+            (b"doge", b"coin"),
+            (b"do", b"verb"),
+            (b"dog", b"puppy"),
+            (b"\x00", b"zero"),
+            (b"\xff\x01", b"high"),
+            (b"dogecoin-with-a-long-key", b"a value long enough to be stored by hash"),
+        ];
+        let trie = trie(&entries);
+
+        let mut expected: Vec<(Vec<u8>, Vec<u8>)> =
+            entries.iter().map(|(k, v)| (k.to_vec(), v.to_vec())).collect();
+        expected.sort();
+        assert_eq!(trie.iter().collect::<Vec<_>>(), expected);
+        assert_eq!(MerklePatriciaTrie::new().iter().count(), 0);
+    }
+
+    #[test]
+    fn test_iter_prefix() {
+        let trie = trie(&[
+            (b"do", b"verb"),
+            (b"dog", b"puppy"),
+            (b"doge", b"coin"),
+            (b"dot", b"point"),
+            (b"horse", b"stallion"),
+        ]);
+        let keys = |prefix: &[u8]| trie.iter_prefix(prefix).map(|(key, _)| key).collect::<Vec<_>>();
+
+        assert_eq!(keys(b"dog"), vec![b"dog".to_vec(), b"doge".to_vec()]);
+        assert_eq!(keys(b"do").len(), 4);
+        assert_eq!(keys(b"h"), vec![b"horse".to_vec()]);
+        assert_eq!(keys(b"hors"), vec![b"horse".to_vec()]);
+        assert_eq!(keys(b"").len(), 5);
+        assert!(keys(b"cat").is_empty());
+        assert!(keys(b"horses").is_empty());
+    }
+}
@@ -11,7 +11,14 @@ pub mod trie;
 pub mod nibbles;
 pub mod hash;
 pub mod proof;
+pub mod iter;
+pub mod rlp;
+pub mod state;
+pub mod eth_proof;
 
 pub use trie::MerklePatriciaTrie;
 pub use node::{Node, NodeType};
-pub use proof::MerkleProof;
+pub use proof::{MerkleProof, ProofError, RangeProof};
+pub use iter::TrieIter;
+pub use state::{Account, WorldState};
+pub use eth_proof::{EthProof, EthProofError};
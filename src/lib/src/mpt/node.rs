@@ -2,16 +2,24 @@
 ///
 /// Ethereum MPT has 4 types of nodes:
 /// 1. Branch Node: 17 items (16 hex + 1 value)
-/// 2. Extension Node: 2 items [encoded_path, child_hash]
+/// 2. Extension Node: 2 items [encoded_path, child_ref]
 /// 3. Leaf Node: 2 items [encoded_path, value]
 /// 4. Empty Node: null
+///
+/// Children are referenced the Ethereum way: a node whose RLP encoding is
+/// shorter than 32 bytes is embedded in its parent (the reference is the
+/// encoding itself), otherwise the reference is the Keccak256 of the encoding.
 
+use super::hash::{keccak256, HASH_LEN};
+use super::nibbles::{compact_decode, compact_encode};
+use super::rlp::{self, RlpError, RlpItem};
 use std::fmt;
 
 /// Node types in Merkle Patricia Trie
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum Node {
     /// Empty node (null)
+    #[default]
     Empty,
 
     /// Leaf node: [encoded_path, value]
@@ -22,16 +30,16 @@ pub enum Node {
         value: Vec<u8>,
     },
 
-    /// Extension node: [encoded_path, child_hash]
+    /// Extension node: [encoded_path, child_ref]
     /// - encoded_path: common path prefix
-    /// - child_hash: hash of child node
+    /// - child_hash: reference to child node (hash, or embedded encoding)
     Extension {
         path: Vec<u8>,     // Nibbles (hex digits)
-        child_hash: Vec<u8>, // Hash of child node
+        child_hash: Vec<u8>, // Reference to child node
     },
 
     /// Branch node: [v0, v1, ..., v15, value]
-    /// - v0-v15: hashes of 16 possible children (for hex digits 0-F)
+    /// - v0-v15: references to 16 possible children (for hex digits 0-F)
     /// - value: optional value stored at this node
     Branch {
         children: [Option<Vec<u8>>; 16], // 16 children for hex digits
@@ -68,6 +76,71 @@ impl Node {
         matches!(self, Node::Empty)
     }
 
+    /// RLP encoding of the node
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Node::Empty => rlp::encode_bytes(&[]),
+            Node::Leaf { path, value } => {
+                rlp::encode_list(&[rlp::encode_bytes(&compact_encode(path, true)), rlp::encode_bytes(value)])
+            }
+            Node::Extension { path, child_hash } => {
+                rlp::encode_list(&[rlp::encode_bytes(&compact_encode(path, false)), encode_reference(child_hash)])
+            }
+            Node::Branch { children, value } => {
+                let mut items: Vec<Vec<u8>> = children
+                    .iter()
+                    .map(|child| child.as_deref().map_or_else(|| rlp::encode_bytes(&[]), encode_reference))
+                    .collect();
+                items.push(rlp::encode_bytes(value.as_deref().unwrap_or_default()));
+                rlp::encode_list(&items)
+            }
+        }
+    }
+
+    /// Decode a node from its RLP encoding
+    pub fn decode(data: &[u8]) -> Result<Self, RlpError> {
+        match rlp::decode(data)? {
+            RlpItem::Bytes(bytes) if bytes.is_empty() => Ok(Node::Empty),
+            RlpItem::List(items) if items.len() == 2 => {
+                let encoded_path = items[0].as_bytes().ok_or(RlpError::InvalidNode)?;
+                let (path, is_leaf) = compact_decode(encoded_path);
+                if is_leaf {
+                    let value = items[1].as_bytes().ok_or(RlpError::InvalidNode)?;
+                    Ok(Node::leaf(path, value.to_vec()))
+                } else {
+                    Ok(Node::extension(path, decode_reference(&items[1])?.ok_or(RlpError::InvalidNode)?))
+                }
+            }
+            RlpItem::List(items) if items.len() == 17 => {
+                let mut children: [Option<Vec<u8>>; 16] = Default::default();
+                for (child, item) in children.iter_mut().zip(&items) {
+                    *child = decode_reference(item)?;
+                }
+                let value = items[16].as_bytes().ok_or(RlpError::InvalidNode)?;
+                Ok(Node::Branch {
+                    children,
+                    value: (!value.is_empty()).then(|| value.to_vec()),
+                })
+            }
+            _ => Err(RlpError::InvalidNode),
+        }
+    }
+
+    /// Keccak256 of the encoding (how a root or any hashed node is identified)
+    pub fn hash(&self) -> Vec<u8> {
+        keccak256(&self.encode()).to_vec()
+    }
+
+    /// Reference to this node from its parent
+    pub fn reference(&self) -> Vec<u8> {
+        let encoded = self.encode();
+        if is_inline(&encoded) {
+            encoded
+        } else {
+            keccak256(&encoded).to_vec()
+        }
+    }
+
     /// Get node type as string
     pub fn node_type(&self) -> &str {
         match self {
@@ -97,6 +170,33 @@ impl fmt::Display for Node {
     }
 }
 
+/// Whether a reference is an embedded encoding rather than a hash
+pub fn is_inline(reference: &[u8]) -> bool {
+    reference.len() < HASH_LEN
+}
+
+/// Encode a child reference as a list item: embedded nodes are spliced in as-is
+fn encode_reference(reference: &[u8]) -> Vec<u8> {
+    if is_inline(reference) {
+        reference.to_vec()
+    } else {
+        rlp::encode_bytes(reference)
+    }
+}
+
+/// Decode a child reference list item (None for an empty slot)
+fn decode_reference(item: &RlpItem) -> Result<Option<Vec<u8>>, RlpError> {
+    match item {
+        RlpItem::Bytes(bytes) if bytes.is_empty() => Ok(None),
+        RlpItem::Bytes(hash) if hash.len() == HASH_LEN => Ok(Some(hash.clone())),
+        RlpItem::List(_) => {
+            let embedded = item.encode();
+            if is_inline(&embedded) { Ok(Some(embedded)) } else { Err(RlpError::InvalidNode) }
+        }
+        RlpItem::Bytes(_) => Err(RlpError::InvalidNode),
+    }
+}
+
 /// Node type enum for pattern matching
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NodeType {
@@ -137,6 +237,28 @@ mod tests {
         assert_eq!(branch.node_type(), "Branch");
     }
 
+    #[test]
+    fn test_encode_decode() {
+        let leaf = Node::leaf(vec![1, 2, 3], b"value".to_vec());
+        assert_eq!(leaf.encode(), b"\xc9\x82\x31\x23\x85value".to_vec());
+        assert_eq!(leaf.reference(), leaf.encode());
+
+        let mut branch = Node::branch();
+        if let Node::Branch { children, value } = &mut branch {
+            children[3] = Some(leaf.reference());
+            children[9] = Some(vec![0xaa; 32]);
+            *value = Some(b"v".to_vec());
+        }
+        let extension = Node::extension(vec![0xa, 0xb], branch.reference());
+        for node in [Node::Empty, leaf, branch.clone(), extension] {
+            assert_eq!(Node::decode(&node.encode()), Ok(node));
+        }
+        assert!(!is_inline(&branch.reference()));
+
+        assert_eq!(Node::decode(b"\xc1\x80"), Err(RlpError::InvalidNode));
+        assert_eq!(Node::decode(b"\xc2\x00\x05"), Err(RlpError::InvalidNode));
+    }
+
     #[test]
     fn test_node_type_conversion() {
         let leaf = Node::leaf(vec![1], vec![2]);
@@ -2,9 +2,28 @@
 ///
 /// Merkle证明允许在不访问整个trie的情况下验证某个键值对是否存在
 
-use super::node::Node;
+use super::node::{is_inline, Node};
 use super::nibbles::bytes_to_nibbles;
-use super::hash::keccak256;
+use super::trie::MerklePatriciaTrie;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// 证明验证错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ProofError {
+    #[error("proof has no nodes")]
+    NoNodes,
+    #[error("root node does not hash to the expected root")]
+    RootMismatch,
+    #[error("proof node {0} does not match its parent's reference")]
+    HashMismatch(usize),
+    #[error("proof ends before the key's path terminates")]
+    Incomplete,
+    #[error("{0} proof nodes left after the key's path terminates")]
+    UnusedNodes(usize),
+    #[error("non-canonical node on the key's path")]
+    MalformedNode,
+}
 
 /// Merkle证明
 #[derive(Debug, Clone, PartialEq)]
@@ -33,117 +52,155 @@ impl MerkleProof {
     /// - `root_hash`: 已知的根哈希
     ///
     /// # 返回
-    /// - `true`: 证明有效
+    /// - `true`: 证明有效，且证明的值（或不存在）与`value`一致
     /// - `false`: 证明无效
     pub fn verify(&self, root_hash: &[u8]) -> bool {
-        if self.proof_nodes.is_empty() {
-            return false;
-        }
+        self.verified_value(root_hash).is_ok_and(|value| value == self.value)
+    }
 
-        let nibbles = bytes_to_nibbles(&self.key);
-        self.verify_at(&self.proof_nodes[0], &nibbles, 0, root_hash)
+    /// 验证不存在证明：`value`为None且证明有效
+    pub fn verify_exclusion(&self, root_hash: &[u8]) -> bool {
+        self.value.is_none() && self.verify(root_hash)
     }
 
-    /// 递归验证节点
-    fn verify_at(&self, node: &Node, path: &[u8], node_index: usize, expected_hash: &[u8]) -> bool {
-        // 验证当前节点的哈希
-        let node_hash = self.hash_node(node);
-        if node_hash != expected_hash {
-            return false;
+    /// 沿键路径走完证明，返回根下该键的值（Some）或其不存在（None）
+    ///
+    /// 不存在必须由路径终止处的节点直接证明：
+    /// - 空trie（根为空节点）
+    /// - 叶节点路径与剩余路径不同
+    /// - 扩展节点路径不是剩余路径的前缀
+    /// - 分支节点在下一个nibble处没有子节点
+    /// - 路径在分支节点处结束且分支节点没有值
+    ///
+    /// 以下情况视为证明有歧义而拒绝：终止后仍有未使用的证明节点、
+    /// 路径未终止证明就结束、以及非规范节点（根以外的空节点、空路径的扩展节点、
+    /// 子节点不是分支节点的扩展节点、少于两项内容的分支节点）。
+    pub fn verified_value(&self, root_hash: &[u8]) -> Result<Option<Vec<u8>>, ProofError> {
+        let root = self.proof_nodes.first().ok_or(ProofError::NoNodes)?;
+        if root.hash() != root_hash {
+            return Err(ProofError::RootMismatch);
         }
 
-        match node {
-            Node::Empty => {
-                // 空节点：值应该不存在
-                self.value.is_none()
-            }
-
-            Node::Leaf { path: leaf_path, value: leaf_value } => {
-                // 叶节点：路径和值都应该匹配
-                if path != leaf_path.as_slice() {
-                    // 路径不匹配：这证明了键不存在
-                    return self.value.is_none();
-                }
-                match (&self.value, leaf_value) {
-                    (Some(expected), actual) => expected == actual,
-                    (None, _) => false,
-                }
-            }
-
-            Node::Extension { path: ext_path, child_hash } => {
-                // 扩展节点：路径应该匹配前缀，继续验证子节点
-                if !path.starts_with(ext_path) {
-                    // 路径不匹配：这证明了键不存在
-                    return self.value.is_none();
-                }
+        let nibbles = bytes_to_nibbles(&self.key);
+        let mut path = nibbles.as_slice();
+        let mut node = root.clone();
+        let mut index = 0;
 
-                let remaining = &path[ext_path.len()..];
-                let next_index = node_index + 1;
+        let value = loop {
+            let child_ref = match &node {
+                Node::Empty => break None,
 
-                if next_index >= self.proof_nodes.len() {
-                    return false;
+                Node::Leaf { path: leaf_path, value } => {
+                    break (path == leaf_path.as_slice()).then(|| value.clone());
                 }
 
-                self.verify_at(&self.proof_nodes[next_index], remaining, next_index, child_hash)
-            }
-
-            Node::Branch { children, value: branch_value } => {
-                if path.is_empty() {
-                    // 路径到达分支节点：验证值
-                    match (&self.value, branch_value) {
-                        (Some(expected), Some(actual)) => expected == actual,
-                        (None, None) => true,
-                        _ => false,
+                Node::Extension { path: ext_path, child_hash } => {
+                    if ext_path.is_empty() {
+                        return Err(ProofError::MalformedNode);
                     }
-                } else {
-                    // 继续沿路径前进
-                    let nibble = path[0] as usize;
-                    let remaining = &path[1..];
-
-                    match &children[nibble] {
-                        Some(child_hash) => {
-                            let next_index = node_index + 1;
-                            if next_index >= self.proof_nodes.len() {
-                                return false;
-                            }
-                            self.verify_at(&self.proof_nodes[next_index], remaining, next_index, child_hash)
-                        }
-                        None => self.value.is_none(), // 子节点不存在，值应该不存在
+                    if !path.starts_with(ext_path) {
+                        break None;
                     }
+                    path = &path[ext_path.len()..];
+                    child_hash.clone()
                 }
-            }
-        }
-    }
 
-    /// 计算节点哈希（与trie中的实现相同）
-    fn hash_node(&self, node: &Node) -> Vec<u8> {
-        match node {
-            Node::Empty => vec![],
-            Node::Leaf { path, value } => {
-                let encoded_path = super::nibbles::compact_encode(path, true);
-                let mut data = encoded_path;
-                data.extend_from_slice(value);
-                keccak256(&data).to_vec()
-            }
-            Node::Extension { path, child_hash } => {
-                let encoded_path = super::nibbles::compact_encode(path, false);
-                let mut data = encoded_path;
-                data.extend_from_slice(child_hash);
-                keccak256(&data).to_vec()
-            }
-            Node::Branch { children, value } => {
-                let mut data = Vec::new();
-                for child in children.iter() {
-                    if let Some(hash) = child {
-                        data.extend_from_slice(hash);
+                Node::Branch { children, value } => {
+                    if children.iter().flatten().count() + usize::from(value.is_some()) < 2 {
+                        return Err(ProofError::MalformedNode);
                     }
+                    let Some((&nibble, rest)) = path.split_first() else {
+                        break value.clone();
+                    };
+                    let Some(child_ref) = &children[nibble as usize] else {
+                        break None;
+                    };
+                    path = rest;
+                    child_ref.clone()
                 }
-                if let Some(v) = value {
-                    data.extend_from_slice(v);
+            };
+
+            // 内嵌子节点直接从父节点中解码，哈希引用的子节点是证明中的下一个节点
+            let child = if is_inline(&child_ref) {
+                Node::decode(&child_ref).map_err(|_| ProofError::MalformedNode)?
+            } else {
+                index += 1;
+                let child = self.proof_nodes.get(index).ok_or(ProofError::Incomplete)?;
+                if child.hash() != child_ref {
+                    return Err(ProofError::HashMismatch(index));
                 }
-                keccak256(&data).to_vec()
+                child.clone()
+            };
+            let is_extension = matches!(node, Node::Extension { .. });
+            if child.is_empty() || (is_extension && !matches!(child, Node::Branch { .. })) {
+                return Err(ProofError::MalformedNode);
             }
+            node = child;
+        };
+
+        match self.proof_nodes.len() - 1 - index {
+            0 => Ok(value),
+            unused => Err(ProofError::UnusedNodes(unused)),
+        }
+    }
+}
+
+/// 区间证明（snap同步式的状态分片验证）
+///
+/// 覆盖键区间`[start, end]`：区间内的全部键值对，加上两端边界键证明路径上的哈希引用节点
+/// （边界键本身不必存在）。验证时用证明节点重建两条边界路径，删除区间内的全部内容后
+/// 插入给出的键值对，重新计算的根哈希与已知根一致即证明区间内恰好是这些键值对，不多不少。
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeProof {
+    /// 区间起点（含）
+    pub start: Vec<u8>,
+    /// 区间终点（含）
+    pub end: Vec<u8>,
+    /// 区间内的全部键值对（按键升序）
+    pub entries: Vec<(Vec<u8>, Vec<u8>)>,
+    /// 两条边界路径上的节点（去重）
+    pub proof_nodes: Vec<Node>,
+}
+
+impl RangeProof {
+    /// 创建新的区间证明
+    pub fn new(start: Vec<u8>, end: Vec<u8>, entries: Vec<(Vec<u8>, Vec<u8>)>, proof_nodes: Vec<Node>) -> Self {
+        Self {
+            start,
+            end,
+            entries,
+            proof_nodes,
+        }
+    }
+
+    /// 验证区间证明
+    ///
+    /// 键值对必须严格升序、落在区间内且值非空。
+    pub fn verify(&self, root_hash: &[u8]) -> bool {
+        if self.start > self.end {
+            return false;
+        }
+        let ordered = self.entries.windows(2).all(|pair| pair[0].0 < pair[1].0);
+        let in_range = self
+            .entries
+            .iter()
+            .all(|(key, value)| *key >= self.start && *key <= self.end && !value.is_empty());
+        if !ordered || !in_range {
+            return false;
+        }
+
+        let nodes: HashMap<Vec<u8>, Node> = self.proof_nodes.iter().map(|node| (node.hash(), node.clone())).collect();
+        let Some(root) = nodes.get(root_hash).cloned() else {
+            return false;
+        };
+        let mut trie = MerklePatriciaTrie::from_parts(root, nodes);
+        if trie.unset_range(&bytes_to_nibbles(&self.start), &bytes_to_nibbles(&self.end)).is_none() {
+            return false;
         }
+        for (key, value) in &self.entries {
+            trie.insert(key, value);
+        }
+        trie.root_hash() == root_hash
     }
 }
 
@@ -179,7 +236,7 @@ mod tests {
         );
 
         // 计算根哈希
-        let root_hash = proof.hash_node(&leaf);
+        let root_hash = leaf.hash();
 
         // 验证证明
         assert!(proof.verify(&root_hash));
@@ -198,9 +255,127 @@ mod tests {
             vec![leaf.clone()],
         );
 
-        let root_hash = proof.hash_node(&leaf);
+        let root_hash = leaf.hash();
 
         // 验证应该失败
         assert!(!proof.verify(&root_hash));
     }
+
+    #[test]
+    fn test_exclusion_proofs() {
+        let mut trie = MerklePatriciaTrie::new();
+        trie.insert(b"do", b"verb");
+        trie.insert(b"dog", b"puppy");
+        trie.insert(b"doge", b"coin");
+        trie.insert(b"horse", &[9; 40]);
+        trie.insert(b"ab\x10", b"x");
+        trie.insert(b"ab\x20", b"y");
+        let root = trie.root_hash();
+
+        // 扩展节点不匹配、分支空槽、叶节点不匹配、路径终止于无值分支
+        for key in [&b"x"[..], b"cat", b"horsa", b"ab"] {
+            let proof = trie.get_proof(key);
+            assert_eq!(proof.verified_value(&root), Ok(None), "{:?}", key);
+            assert!(proof.verify_exclusion(&root));
+        }
+        let empty = MerklePatriciaTrie::new();
+        assert!(empty.get_proof(b"any").verify_exclusion(&empty.root_hash()));
+
+        // 存在的键不能被证明为不存在
+        let present = trie.get_proof(b"dog");
+        let forged = MerkleProof::new(b"dog".to_vec(), None, present.proof_nodes.clone());
+        assert_eq!(forged.verified_value(&root), Ok(Some(b"puppy".to_vec())));
+        assert!(!forged.verify_exclusion(&root));
+    }
+
+    #[test]
+    fn test_ambiguous_proofs_rejected() {
+        let mut trie = MerklePatriciaTrie::new();
+        trie.insert(b"do", b"verb");
+        trie.insert(b"horse", &[9; 40]);
+        let root = trie.root_hash();
+        let horse = trie.get_proof(b"horse");
+        assert!(horse.proof_nodes.len() >= 2);
+
+        let mut extra = trie.get_proof(b"cat");
+        extra.proof_nodes.push(horse.proof_nodes.last().unwrap().clone());
+        assert_eq!(extra.verified_value(&root), Err(ProofError::UnusedNodes(1)));
+
+        let mut truncated = horse.clone();
+        truncated.proof_nodes.pop();
+        assert_eq!(truncated.verified_value(&root), Err(ProofError::Incomplete));
+
+        let mut swapped = horse.clone();
+        let last = swapped.proof_nodes.len() - 1;
+        swapped.proof_nodes[last] = Node::leaf(vec![], vec![1; 40]);
+        assert_eq!(swapped.verified_value(&root), Err(ProofError::HashMismatch(last)));
+
+        assert_eq!(horse.verified_value(&[0; 32]), Err(ProofError::RootMismatch));
+        assert_eq!(MerkleProof::new(b"horse".to_vec(), None, vec![]).verified_value(&root), Err(ProofError::NoNodes));
+
+        // 只有一个子节点的分支节点不是规范形式
+        let mut branch = Node::branch();
+        if let Node::Branch { children, .. } = &mut branch {
+            children[1] = Some(Node::leaf(vec![0], b"v".to_vec()).reference());
+        }
+        let lone = MerkleProof::new(b"\x20".to_vec(), None, vec![branch.clone()]);
+        assert_eq!(lone.verified_value(&branch.hash()), Err(ProofError::MalformedNode));
+    }
+
+    fn state_trie() -> MerklePatriciaTrie {
+        let mut trie = MerklePatriciaTrie::new();
+        for i in 0..200u32 {
+            let key = format!("account-{:03}", i * 5);
+            // 部分值较长，混合内嵌和哈希引用的叶节点
+            let value = if i % 3 == 0 { vec![i as u8; 40] } else { vec![i as u8 + 1] };
+            trie.insert(key.as_bytes(), &value);
+        }
+        trie
+    }
+
+    #[test]
+    fn test_range_proof() {
+        let trie = state_trie();
+        let root = trie.root_hash();
+
+        let ranges: [(&[u8], &[u8], usize); 6] = [
+            (b"account-100", b"account-300", 41), // 两端都存在
+            (b"account-101", b"account-299", 39), // 两端都不存在
+            (b"", b"account-050", 11),            // 从最小键开始
+            (b"account-900", b"\xff", 20),        // 到最大键结束
+            (b"account-101", b"account-104", 0),  // 区间内没有键
+            (b"", b"\xff", 200),                  // 整个trie
+        ];
+        for (start, end, count) in ranges {
+            let proof = trie.get_range_proof(start, end);
+            assert_eq!(proof.entries.len(), count);
+            assert!(proof.verify(&root), "{:?}..{:?}", start, end);
+        }
+
+        let empty = MerklePatriciaTrie::new();
+        assert!(empty.get_range_proof(b"a", b"z").verify(&empty.root_hash()));
+    }
+
+    #[test]
+    fn test_range_proof_rejects_tampering() {
+        let trie = state_trie();
+        let root = trie.root_hash();
+        let proof = trie.get_range_proof(b"account-101", b"account-299");
+        assert!(proof.verify(&root));
+
+        let tamper = |f: &dyn Fn(&mut RangeProof)| {
+            let mut forged = proof.clone();
+            f(&mut forged);
+            forged.verify(&root)
+        };
+        assert!(!tamper(&|p| { p.entries.remove(10); }));
+        assert!(!tamper(&|p| { p.entries.remove(0); }));
+        assert!(!tamper(&|p| { p.entries.pop(); }));
+        assert!(!tamper(&|p| p.entries[5].1 = b"forged".to_vec()));
+        assert!(!tamper(&|p| p.entries.insert(1, (b"account-106".to_vec(), b"x".to_vec()))));
+        assert!(!tamper(&|p| p.entries.swap(1, 2)));
+        assert!(!tamper(&|p| p.start = b"account-090".to_vec()));
+        assert!(!tamper(&|p| { p.proof_nodes.pop(); }));
+        assert!(!proof.verify(&MerklePatriciaTrie::new().root_hash()));
+    }
 }
@@ -0,0 +1,229 @@
+/// RLP (Recursive Length Prefix) encoding
+///
+/// Ethereum's serialization format for trie nodes:
+/// - a single byte in [0x00, 0x7f] is its own encoding
+/// - a string of 0-55 bytes: [0x80 + len, bytes...]
+/// - a longer string: [0xb7 + len(len), big-endian len, bytes...]
+/// - a list with a 0-55 byte payload: [0xc0 + len, items...]
+/// - a longer list: [0xf7 + len(len), big-endian len, items...]
+///
+/// Decoding is strict: non-canonical encodings are rejected so that
+/// decode followed by encode always reproduces the input bytes.
+
+use thiserror::Error;
+
+/// A decoded RLP item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RlpItem {
+    Bytes(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+/// RLP decoding errors
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RlpError {
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+    #[error("non-canonical encoding")]
+    NonCanonical,
+    #[error("{0} trailing bytes after item")]
+    TrailingBytes(usize),
+    #[error("integer wider than {0} bytes")]
+    IntegerOverflow(usize),
+    #[error("item is not a valid trie node")]
+    InvalidNode,
+    #[error("item is not a valid account")]
+    InvalidAccount,
+}
+
+impl RlpItem {
+    /// Re-encode the item
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            RlpItem::Bytes(bytes) => encode_bytes(bytes),
+            RlpItem::List(items) => {
+                let encoded: Vec<Vec<u8>> = items.iter().map(RlpItem::encode).collect();
+                encode_list(&encoded)
+            }
+        }
+    }
+
+    /// Byte string payload (None for lists)
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            RlpItem::Bytes(bytes) => Some(bytes),
+            RlpItem::List(_) => None,
+        }
+    }
+
+    /// List items (None for byte strings)
+    pub fn as_list(&self) -> Option<&[RlpItem]> {
+        match self {
+            RlpItem::Bytes(_) => None,
+            RlpItem::List(items) => Some(items),
+        }
+    }
+}
+
+/// Encode a byte string
+pub fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return vec![bytes[0]];
+    }
+    let mut out = Vec::with_capacity(bytes.len() + 9);
+    encode_length(bytes.len(), 0x80, &mut out);
+    out.extend_from_slice(bytes);
+    out
+}
+
+/// Encode an unsigned integer (big-endian without leading zeros; zero is the empty string)
+pub fn encode_uint(value: u128) -> Vec<u8> {
+    let be = value.to_be_bytes();
+    let skip = be.iter().take_while(|&&b| b == 0).count();
+    encode_bytes(&be[skip..])
+}
+
+/// Decode the payload of an unsigned integer item
+pub fn decode_uint(bytes: &[u8]) -> Result<u128, RlpError> {
+    if bytes.first() == Some(&0) {
+        return Err(RlpError::NonCanonical);
+    }
+    if bytes.len() > 16 {
+        return Err(RlpError::IntegerOverflow(16));
+    }
+    Ok(bytes.iter().fold(0u128, |value, &b| (value << 8) | b as u128))
+}
+
+/// Encode a list of already-encoded items
+pub fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload_len = items.iter().map(Vec::len).sum();
+    let mut out = Vec::with_capacity(payload_len + 9);
+    encode_length(payload_len, 0xc0, &mut out);
+    for item in items {
+        out.extend_from_slice(item);
+    }
+    out
+}
+
+/// Write the length prefix (offset is 0x80 for strings, 0xc0 for lists)
+fn encode_length(len: usize, offset: u8, out: &mut Vec<u8>) {
+    if len <= 55 {
+        out.push(offset + len as u8);
+    } else {
+        let be = len.to_be_bytes();
+        let skip = be.iter().take_while(|&&b| b == 0).count();
+        out.push(offset + 55 + (be.len() - skip) as u8);
+        out.extend_from_slice(&be[skip..]);
+    }
+}
+
+/// Decode exactly one item spanning all of `data`
+pub fn decode(data: &[u8]) -> Result<RlpItem, RlpError> {
+    let (item, consumed) = decode_item(data)?;
+    if consumed != data.len() {
+        return Err(RlpError::TrailingBytes(data.len() - consumed));
+    }
+    Ok(item)
+}
+
+/// Decode one item from the front of `data`, returning it and the bytes consumed
+fn decode_item(data: &[u8]) -> Result<(RlpItem, usize), RlpError> {
+    let (&prefix, rest) = data.split_first().ok_or(RlpError::UnexpectedEnd)?;
+    match prefix {
+        0x00..=0x7f => Ok((RlpItem::Bytes(vec![prefix]), 1)),
+        0x80..=0xbf => {
+            let (header, len) = decode_length(prefix - 0x80, rest)?;
+            let payload = payload(rest, header, len)?;
+            if len == 1 && payload[0] < 0x80 {
+                return Err(RlpError::NonCanonical);
+            }
+            Ok((RlpItem::Bytes(payload.to_vec()), 1 + header + len))
+        }
+        0xc0..=0xff => {
+            let (header, len) = decode_length(prefix - 0xc0, rest)?;
+            let mut payload = payload(rest, header, len)?;
+            let mut items = Vec::new();
+            while !payload.is_empty() {
+                let (item, consumed) = decode_item(payload)?;
+                items.push(item);
+                payload = &payload[consumed..];
+            }
+            Ok((RlpItem::List(items), 1 + header + len))
+        }
+    }
+}
+
+/// The `len` payload bytes following a `header`-byte length
+fn payload(rest: &[u8], header: usize, len: usize) -> Result<&[u8], RlpError> {
+    let end = header.checked_add(len).ok_or(RlpError::UnexpectedEnd)?;
+    rest.get(header..end).ok_or(RlpError::UnexpectedEnd)
+}
+
+/// Decode the length following a prefix, returning (length-of-length, length)
+fn decode_length(short: u8, rest: &[u8]) -> Result<(usize, usize), RlpError> {
+    if short <= 55 {
+        return Ok((0, short as usize));
+    }
+    let len_of_len = (short - 55) as usize;
+    let be = rest.get(..len_of_len).ok_or(RlpError::UnexpectedEnd)?;
+    if be[0] == 0 || len_of_len > std::mem::size_of::<usize>() {
+        return Err(RlpError::NonCanonical);
+    }
+    let len = be.iter().fold(0usize, |len, &b| (len << 8) | b as usize);
+    if len <= 55 {
+        return Err(RlpError::NonCanonical);
+    }
+    Ok((len_of_len, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_vectors() {
+        assert_eq!(encode_bytes(b""), vec![0x80]);
+        assert_eq!(encode_bytes(&[0x0f]), vec![0x0f]);
+        assert_eq!(encode_bytes(&[0x80]), vec![0x81, 0x80]);
+        assert_eq!(encode_bytes(b"dog"), b"\x83dog".to_vec());
+        assert_eq!(encode_list(&[]), vec![0xc0]);
+        assert_eq!(encode_list(&[encode_bytes(b"cat"), encode_bytes(b"dog")]), b"\xc8\x83cat\x83dog".to_vec());
+
+        let long = [b'a'; 56];
+        let encoded = encode_bytes(&long);
+        assert_eq!(&encoded[..2], &[0xb8, 56]);
+        assert_eq!(encoded.len(), 58);
+    }
+
+    #[test]
+    fn test_uint() {
+        assert_eq!(encode_uint(0), vec![0x80]);
+        assert_eq!(encode_uint(15), vec![0x0f]);
+        assert_eq!(encode_uint(1024), vec![0x82, 0x04, 0x00]);
+        assert_eq!(decode_uint(&[0x04, 0x00]), Ok(1024));
+        assert_eq!(decode_uint(&[]), Ok(0));
+        assert_eq!(decode_uint(&[0x00, 0x01]), Err(RlpError::NonCanonical));
+        assert_eq!(decode_uint(&[0xff; 17]), Err(RlpError::IntegerOverflow(16)));
+    }
+
+    #[test]
+    fn test_decode_roundtrip() {
+        let nested = RlpItem::List(vec![
+            RlpItem::Bytes(b"cat".to_vec()),
+            RlpItem::List(vec![RlpItem::Bytes(vec![]), RlpItem::Bytes(vec![0x7f])]),
+            RlpItem::Bytes(vec![0xab; 300]),
+        ]);
+        let encoded = nested.encode();
+        assert_eq!(decode(&encoded), Ok(nested));
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed() {
+        assert_eq!(decode(&[]), Err(RlpError::UnexpectedEnd));
+        assert_eq!(decode(&[0x83, b'd', b'o']), Err(RlpError::UnexpectedEnd));
+        assert_eq!(decode(&[0x81, 0x05]), Err(RlpError::NonCanonical));
+        assert_eq!(decode(&[0xb8, 0x02, 0x00, 0x00]), Err(RlpError::NonCanonical));
+        assert_eq!(decode(&[0x80, 0x80]), Err(RlpError::TrailingBytes(1)));
+        assert_eq!(decode(&[0xbf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]), Err(RlpError::UnexpectedEnd));
+    }
+}
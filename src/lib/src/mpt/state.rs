@@ -0,0 +1,258 @@
+/// 以太坊账户与存储trie
+///
+/// 两级状态布局：
+/// - 状态trie：键为`keccak256(地址)`，值为账户的RLP编码 [nonce, balance, storage_root, code_hash]
+/// - 每个账户一棵存储trie：键为`keccak256(槽位)`，值为槽位值去掉前导零后的RLP编码，
+///   写入零即删除该槽位
+///
+/// 账户的`storage_root`总是其存储trie的根，由`WorldState`在写入时维护。
+/// 余额以u128表示（足以容纳以太坊全部发行量），解码更宽的余额会报错。
+
+use super::hash::keccak256;
+use super::proof::MerkleProof;
+use super::rlp::{self, RlpError, RlpItem};
+use super::trie::MerklePatriciaTrie;
+use std::collections::HashMap;
+
+/// 账户地址
+pub type Address = [u8; 20];
+
+/// 存储槽位（键和值均为32字节大端）
+pub type StorageSlot = [u8; 32];
+
+/// 空trie的根哈希（`keccak256(rlp(""))`）
+pub fn empty_storage_root() -> [u8; 32] {
+    keccak256(&rlp::encode_bytes(&[]))
+}
+
+/// 无代码账户的代码哈希（`keccak256("")`）
+pub fn empty_code_hash() -> [u8; 32] {
+    keccak256(&[])
+}
+
+/// 以太坊账户状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Account {
+    pub nonce: u64,
+    /// 余额（wei）
+    pub balance: u128,
+    /// 存储trie根
+    pub storage_root: [u8; 32],
+    /// 合约代码哈希
+    pub code_hash: [u8; 32],
+}
+
+impl Default for Account {
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+impl Account {
+    /// 无存储、无代码的账户
+    pub fn new(nonce: u64, balance: u128) -> Self {
+        Self {
+            nonce,
+            balance,
+            storage_root: empty_storage_root(),
+            code_hash: empty_code_hash(),
+        }
+    }
+
+    /// RLP编码
+    pub fn encode(&self) -> Vec<u8> {
+        rlp::encode_list(&[
+            rlp::encode_uint(self.nonce as u128),
+            rlp::encode_uint(self.balance),
+            rlp::encode_bytes(&self.storage_root),
+            rlp::encode_bytes(&self.code_hash),
+        ])
+    }
+
+    /// RLP解码
+    pub fn decode(data: &[u8]) -> Result<Self, RlpError> {
+        let item = rlp::decode(data)?;
+        let [nonce, balance, storage_root, code_hash] = item.as_list().ok_or(RlpError::InvalidAccount)? else {
+            return Err(RlpError::InvalidAccount);
+        };
+        let uint = |item: &RlpItem| rlp::decode_uint(item.as_bytes().ok_or(RlpError::InvalidAccount)?);
+        let hash = |item: &RlpItem| -> Result<[u8; 32], RlpError> {
+            item.as_bytes().and_then(|bytes| bytes.try_into().ok()).ok_or(RlpError::InvalidAccount)
+        };
+        Ok(Self {
+            nonce: u64::try_from(uint(nonce)?).map_err(|_| RlpError::IntegerOverflow(8))?,
+            balance: uint(balance)?,
+            storage_root: hash(storage_root)?,
+            code_hash: hash(code_hash)?,
+        })
+    }
+}
+
+/// 槽位值在存储trie中的编码（None表示零值，不存储）
+fn encode_slot_value(value: &StorageSlot) -> Option<Vec<u8>> {
+    let skip = value.iter().take_while(|&&b| b == 0).count();
+    (skip < value.len()).then(|| rlp::encode_bytes(&value[skip..]))
+}
+
+/// 解码存储trie中的槽位值
+pub fn decode_slot_value(data: &[u8]) -> Result<StorageSlot, RlpError> {
+    let item = rlp::decode(data)?;
+    let bytes = item.as_bytes().ok_or(RlpError::NonCanonical)?;
+    if bytes.is_empty() || bytes[0] == 0 {
+        return Err(RlpError::NonCanonical);
+    }
+    if bytes.len() > 32 {
+        return Err(RlpError::IntegerOverflow(32));
+    }
+    let mut value = [0u8; 32];
+    value[32 - bytes.len()..].copy_from_slice(bytes);
+    Ok(value)
+}
+
+/// 世界状态：状态trie加每个账户的存储trie
+#[derive(Default)]
+pub struct WorldState {
+    accounts: MerklePatriciaTrie,
+    storage: HashMap<Address, MerklePatriciaTrie>,
+}
+
+impl WorldState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 状态根
+    pub fn state_root(&self) -> Vec<u8> {
+        self.accounts.root_hash()
+    }
+
+    /// 读取账户
+    pub fn account(&self, address: &Address) -> Option<Account> {
+        let encoded = self.accounts.get(&keccak256(address))?;
+        Some(Account::decode(&encoded).expect("state trie holds encoded accounts"))
+    }
+
+    /// 写入账户（`storage_root`以账户当前的存储trie为准）
+    pub fn set_account(&mut self, address: &Address, mut account: Account) {
+        account.storage_root = self.storage_root(address);
+        self.accounts.insert(&keccak256(address), &account.encode());
+    }
+
+    /// 删除账户及其存储
+    pub fn remove_account(&mut self, address: &Address) -> bool {
+        self.storage.remove(address);
+        self.accounts.remove(&keccak256(address))
+    }
+
+    /// 读取存储槽位（未写入的槽位为零）
+    pub fn storage_at(&self, address: &Address, slot: &StorageSlot) -> StorageSlot {
+        self.storage
+            .get(address)
+            .and_then(|trie| trie.get(&keccak256(slot)))
+            .map(|encoded| decode_slot_value(&encoded).expect("storage trie holds encoded values"))
+            .unwrap_or_default()
+    }
+
+    /// 写入存储槽位（写入零即删除），并更新账户的存储根；账户不存在时创建空账户
+    pub fn set_storage(&mut self, address: &Address, slot: &StorageSlot, value: &StorageSlot) {
+        let trie = self.storage.entry(*address).or_default();
+        match encode_slot_value(value) {
+            Some(encoded) => trie.insert(&keccak256(slot), &encoded),
+            None => {
+                trie.remove(&keccak256(slot));
+            }
+        }
+        let account = self.account(address).unwrap_or_default();
+        self.set_account(address, account);
+    }
+
+    /// 账户存储trie的根（无存储时为空trie根）
+    pub fn storage_root(&self, address: &Address) -> [u8; 32] {
+        self.storage
+            .get(address)
+            .map(|trie| trie.root_hash().try_into().expect("root hash is 32 bytes"))
+            .unwrap_or_else(empty_storage_root)
+    }
+
+    /// 账户证明（对状态根），值为账户的RLP编码
+    pub fn account_proof(&self, address: &Address) -> MerkleProof {
+        self.accounts.get_proof(&keccak256(address))
+    }
+
+    /// 存储证明（对账户的`storage_root`），值为槽位值的RLP编码
+    pub fn storage_proof(&self, address: &Address, slot: &StorageSlot) -> MerkleProof {
+        match self.storage.get(address) {
+            Some(trie) => trie.get_proof(&keccak256(slot)),
+            None => MerklePatriciaTrie::new().get_proof(&keccak256(slot)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(n: u8) -> StorageSlot {
+        let mut slot = [0u8; 32];
+        slot[31] = n;
+        slot
+    }
+
+    #[test]
+    fn test_account_codec() {
+        let empty = Account::default().encode();
+        assert_eq!(&empty[..5], &[0xf8, 0x44, 0x80, 0x80, 0xa0]);
+        assert_eq!(empty.len(), 70);
+
+        let account = Account::new(7, 1_000_000_000_000_000_000);
+        let encoded = account.encode();
+        assert_eq!(&encoded[2..12], &[0x07, 0x88, 0x0d, 0xe0, 0xb6, 0xb3, 0xa7, 0x64, 0x00, 0x00]);
+        assert_eq!(Account::decode(&encoded), Ok(account));
+
+        let short_hash = rlp::encode_list(&[
+            rlp::encode_uint(1),
+            rlp::encode_uint(1),
+            rlp::encode_bytes(&[0; 31]),
+            rlp::encode_bytes(&[0; 32]),
+        ]);
+        assert_eq!(Account::decode(&short_hash), Err(RlpError::InvalidAccount));
+        assert_eq!(Account::decode(&rlp::encode_list(&[])), Err(RlpError::InvalidAccount));
+    }
+
+    #[test]
+    fn test_world_state() {
+        let alice: Address = [0xaa; 20];
+        let contract: Address = [0xcc; 20];
+        let mut state = WorldState::new();
+        assert_eq!(state.state_root(), empty_storage_root().to_vec());
+
+        state.set_account(&alice, Account::new(1, 500));
+        state.set_storage(&contract, &slot(0), &slot(42));
+        let before = state.state_root();
+
+        // 账户的存储根跟随存储trie
+        state.set_storage(&contract, &slot(1), &[0xff; 32]);
+        let account = state.account(&contract).unwrap();
+        assert_eq!(account.storage_root, state.storage_root(&contract));
+        assert_ne!(account.storage_root, empty_storage_root());
+        assert_eq!(state.storage_at(&contract, &slot(1)), [0xff; 32]);
+        assert_eq!(state.storage_at(&contract, &slot(9)), [0; 32]);
+
+        // 写入零删除槽位，状态回到写入之前
+        state.set_storage(&contract, &slot(1), &[0; 32]);
+        assert_eq!(state.state_root(), before);
+
+        // 账户证明对状态根、存储证明对存储根
+        let proof = state.account_proof(&contract);
+        assert!(proof.verify(&state.state_root()));
+        assert_eq!(Account::decode(&proof.value.unwrap()), Ok(state.account(&contract).unwrap()));
+        let proof = state.storage_proof(&contract, &slot(0));
+        assert!(proof.verify(&state.storage_root(&contract)));
+        assert_eq!(decode_slot_value(&proof.value.unwrap()), Ok(slot(42)));
+        assert!(state.storage_proof(&alice, &slot(0)).verify_exclusion(&empty_storage_root()));
+
+        assert!(state.remove_account(&contract));
+        assert_eq!(state.account(&contract), None);
+        assert_eq!(state.storage_at(&contract, &slot(0)), [0; 32]);
+    }
+}
@@ -2,40 +2,73 @@
 ///
 /// This is a simplified implementation for educational purposes.
 /// A production implementation would need:
-/// - Proper RLP encoding
 /// - Database backend for persistence
 /// - Proper Keccak256 hashing
 /// - Proof generation/verification
 
-use super::node::Node;
-use super::nibbles::{bytes_to_nibbles, common_prefix, compact_encode};
-use super::hash::keccak256;
-use super::proof::MerkleProof;
-use std::collections::HashMap;
+use super::node::{is_inline, Node};
+use super::nibbles::{bytes_to_nibbles, common_prefix};
+use super::proof::{MerkleProof, RangeProof};
+use super::iter::TrieIter;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 /// Merkle Patricia Trie
 pub struct MerklePatriciaTrie {
     /// Root node
-    root: Node,
+    root: Arc<Node>,
     /// Node storage (hash -> node)
     /// In production, this would be a database
-    storage: HashMap<Vec<u8>, Node>,
+    ///
+    /// Nodes are immutable once stored and shared through `Arc`: every version,
+    /// resolved child and iterator refers to the same allocation, and a new
+    /// version only allocates the nodes on the updated path.
+    storage: HashMap<Vec<u8>, Arc<Node>>,
+    /// Root node of every version (root hash -> node)
+    ///
+    /// Inserts never modify stored nodes, so each retained root still
+    /// reaches its whole version through `storage`.
+    roots: HashMap<Vec<u8>, Arc<Node>>,
+    /// Root hashes in the order the versions were produced
+    root_history: Vec<Vec<u8>>,
 }
 
 impl MerklePatriciaTrie {
     /// Create a new empty trie
     pub fn new() -> Self {
+        let mut trie = Self::from_parts(Node::empty(), HashMap::new());
+        trie.record_root();
+        trie
+    }
+
+    /// Partial trie made of a root and the hashed nodes collected from proofs
+    ///
+    /// Subtrees whose nodes are missing stay as bare hash references.
+    pub(super) fn from_parts(root: Node, storage: HashMap<Vec<u8>, Node>) -> Self {
         Self {
-            root: Node::empty(),
-            storage: HashMap::new(),
+            root: Arc::new(root),
+            storage: storage.into_iter().map(|(hash, node)| (hash, Arc::new(node))).collect(),
+            roots: HashMap::new(),
+            root_history: Vec::new(),
         }
     }
 
     /// Insert a key-value pair into the trie
     pub fn insert(&mut self, key: &[u8], value: &[u8]) {
         let nibbles = bytes_to_nibbles(key);
-        let root = self.root.clone();
-        self.root = self.insert_at(&root, &nibbles, value);
+        let root = Arc::clone(&self.root);
+        self.root = Arc::new(self.insert_at(&root, &nibbles, value));
+        self.record_root();
+    }
+
+    /// Retain the current root as a queryable version
+    fn record_root(&mut self) {
+        let hash = self.root.hash();
+        if self.root_history.last() != Some(&hash) {
+            self.roots.insert(hash.clone(), Arc::clone(&self.root));
+            self.root_history.push(hash);
+        }
     }
 
     /// Recursive insert at a node
@@ -50,99 +83,19 @@ impl MerklePatriciaTrie {
                 path: leaf_path,
                 value: leaf_value,
             } => {
-                // Check if paths match
                 let prefix_len = common_prefix(path, leaf_path);
 
                 if prefix_len == leaf_path.len() && prefix_len == path.len() {
                     // Exact match: update value
-                    Node::leaf(path.to_vec(), value.to_vec())
-                } else if prefix_len == leaf_path.len() {
-                    // Current leaf path is prefix of new path
-                    // Convert leaf to branch
-                    let mut branch = Node::branch();
-
-                    if let Node::Branch { ref mut children, value: ref mut branch_value } = branch {
-                        // Set the leaf value at this branch
-                        *branch_value = Some(leaf_value.clone());
-
-                        // Insert remaining path
-                        if prefix_len < path.len() {
-                            let remaining = &path[prefix_len..];
-                            let nibble = remaining[0] as usize;
-                            let rest = &remaining[1..];
-
-                            let child = Node::leaf(rest.to_vec(), value.to_vec());
-                            let child_hash = self.hash_node(&child);
-                            self.storage.insert(child_hash.clone(), child);
-                            children[nibble] = Some(child_hash);
-                        }
-                    }
-
-                    // If the leaf had a path, wrap branch in extension
-                    if prefix_len > 0 {
-                        let branch_hash = self.hash_node(&branch);
-                        self.storage.insert(branch_hash.clone(), branch);
-                        Node::extension(leaf_path.to_vec(), branch_hash)
-                    } else {
-                        branch
-                    }
-                } else if prefix_len == 0 {
-                    // No common prefix: create branch directly
-                    let mut branch = Node::branch();
-
-                    if let Node::Branch { ref mut children, .. } = branch {
-                        // Add old leaf
-                        let old_nibble = leaf_path[0] as usize;
-                        let old_rest = &leaf_path[1..];
-                        let old_node = Node::leaf(old_rest.to_vec(), leaf_value.clone());
-                        let old_hash = self.hash_node(&old_node);
-                        self.storage.insert(old_hash.clone(), old_node);
-                        children[old_nibble] = Some(old_hash);
-
-                        // Add new leaf
-                        let new_nibble = path[0] as usize;
-                        let new_rest = &path[1..];
-                        let new_node = Node::leaf(new_rest.to_vec(), value.to_vec());
-                        let new_hash = self.hash_node(&new_node);
-                        self.storage.insert(new_hash.clone(), new_node);
-                        children[new_nibble] = Some(new_hash);
-                    }
-
-                    branch
-                } else {
-                    // Common prefix: create extension
-                    let common = &path[..prefix_len];
-
-                    // Create branch for divergence point
-                    let mut branch = Node::branch();
-
-                    if let Node::Branch { ref mut children, .. } = branch {
-                        // Add old path
-                        let old_rest = &leaf_path[prefix_len..];
-                        if !old_rest.is_empty() {
-                            let old_nibble = old_rest[0] as usize;
-                            let old_node = Node::leaf(old_rest[1..].to_vec(), leaf_value.clone());
-                            let old_hash = self.hash_node(&old_node);
-                            self.storage.insert(old_hash.clone(), old_node);
-                            children[old_nibble] = Some(old_hash);
-                        }
-
-                        // Add new path
-                        let new_rest = &path[prefix_len..];
-                        if !new_rest.is_empty() {
-                            let new_nibble = new_rest[0] as usize;
-                            let new_node = Node::leaf(new_rest[1..].to_vec(), value.to_vec());
-                            let new_hash = self.hash_node(&new_node);
-                            self.storage.insert(new_hash.clone(), new_node);
-                            children[new_nibble] = Some(new_hash);
-                        }
-                    }
-
-                    // Create extension node
-                    let branch_hash = self.hash_node(&branch);
-                    self.storage.insert(branch_hash.clone(), branch);
-                    Node::extension(common.to_vec(), branch_hash)
+                    return Node::leaf(path.to_vec(), value.to_vec());
                 }
+
+                // Paths diverge (or one ends) at prefix_len: both continue from a branch there
+                let mut children = Default::default();
+                let mut branch_value = None;
+                self.attach(&mut children, &mut branch_value, &leaf_path[prefix_len..], leaf_value);
+                self.attach(&mut children, &mut branch_value, &path[prefix_len..], value);
+                self.under_prefix(&path[..prefix_len], Node::Branch { children, value: branch_value })
             }
 
             Node::Extension { path: ext_path, child_hash } => {
@@ -151,97 +104,265 @@ impl MerklePatriciaTrie {
                 if prefix_len == ext_path.len() {
                     // Path continues through extension
                     let remaining = &path[prefix_len..];
-                    let child = self.storage.get(child_hash).cloned()
-                        .unwrap_or(Node::empty());
+                    let child = self.resolve(child_hash).unwrap_or_default();
 
                     let new_child = self.insert_at(&child, remaining, value);
-                    let new_child_hash = self.hash_node(&new_child);
-                    self.storage.insert(new_child_hash.clone(), new_child);
+                    let new_child_ref = self.store(new_child);
 
-                    Node::extension(ext_path.clone(), new_child_hash)
+                    Node::extension(ext_path.clone(), new_child_ref)
                 } else {
                     // Split extension
-                    let common = &path[..prefix_len];
-                    let mut branch = Node::branch();
-
-                    if let Node::Branch { ref mut children, .. } = branch {
-                        // Add old extension continuation
-                        let old_rest = &ext_path[prefix_len..];
-                        if !old_rest.is_empty() {
-                            let old_nibble = old_rest[0] as usize;
-                            if old_rest.len() > 1 {
-                                let old_ext = Node::extension(old_rest[1..].to_vec(), child_hash.clone());
-                                let old_hash = self.hash_node(&old_ext);
-                                self.storage.insert(old_hash.clone(), old_ext);
-                                children[old_nibble] = Some(old_hash);
-                            } else {
-                                children[old_nibble] = Some(child_hash.clone());
-                            }
-                        }
-
-                        // Add new path
-                        let new_rest = &path[prefix_len..];
-                        if !new_rest.is_empty() {
-                            let new_nibble = new_rest[0] as usize;
-                            let new_node = Node::leaf(new_rest[1..].to_vec(), value.to_vec());
-                            let new_hash = self.hash_node(&new_node);
-                            self.storage.insert(new_hash.clone(), new_node);
-                            children[new_nibble] = Some(new_hash);
-                        }
-                    }
-
-                    if prefix_len > 0 {
-                        let branch_hash = self.hash_node(&branch);
-                        self.storage.insert(branch_hash.clone(), branch);
-                        Node::extension(common.to_vec(), branch_hash)
+                    let mut children: [Option<Vec<u8>>; 16] = Default::default();
+                    let mut branch_value = None;
+
+                    // Old extension continuation (never empty since prefix_len < ext_path.len())
+                    let old_rest = &ext_path[prefix_len..];
+                    let old_nibble = old_rest[0] as usize;
+                    if old_rest.len() > 1 {
+                        let old_ext = Node::extension(old_rest[1..].to_vec(), child_hash.clone());
+                        children[old_nibble] = Some(self.store(old_ext));
                     } else {
-                        branch
+                        children[old_nibble] = Some(child_hash.clone());
                     }
+
+                    self.attach(&mut children, &mut branch_value, &path[prefix_len..], value);
+                    self.under_prefix(&path[..prefix_len], Node::Branch { children, value: branch_value })
                 }
             }
 
             Node::Branch { children, value: branch_value } => {
+                let mut new_children = children.clone();
                 if path.is_empty() {
                     // Update value at branch
-                    let mut new_branch = Node::branch();
-                    if let Node::Branch { children: ref mut new_children, value: ref mut new_value } = new_branch {
-                        new_children.clone_from(children);
-                        *new_value = Some(value.to_vec());
-                    }
-                    new_branch
-                } else {
-                    // Navigate to child
-                    let nibble = path[0] as usize;
-                    let remaining = &path[1..];
+                    return Node::Branch {
+                        children: new_children,
+                        value: Some(value.to_vec()),
+                    };
+                }
 
-                    let child = children[nibble]
-                        .as_ref()
-                        .and_then(|hash| self.storage.get(hash).cloned())
-                        .unwrap_or(Node::empty());
+                // Navigate to child
+                let nibble = path[0] as usize;
+                let remaining = &path[1..];
 
-                    let new_child = self.insert_at(&child, remaining, value);
-                    let new_child_hash = self.hash_node(&new_child);
-                    self.storage.insert(new_child_hash.clone(), new_child);
-
-                    let mut new_branch = Node::branch();
-                    if let Node::Branch { children: ref mut new_children, value: ref mut new_value } = new_branch {
-                        new_children.clone_from(children);
-                        new_children[nibble] = Some(new_child_hash);
-                        *new_value = branch_value.clone();
+                let child = children[nibble]
+                    .as_ref()
+                    .and_then(|reference| self.resolve(reference))
+                    .unwrap_or_default();
+
+                let new_child = self.insert_at(&child, remaining, value);
+                new_children[nibble] = Some(self.store(new_child));
+
+                Node::Branch {
+                    children: new_children,
+                    value: branch_value.clone(),
+                }
+            }
+        }
+    }
+
+    /// Remove a key; returns whether it was present
+    ///
+    /// Nodes left with a single entry are collapsed so the trie keeps the same
+    /// shape (and root) as one built without the key.
+    pub fn remove(&mut self, key: &[u8]) -> bool {
+        let nibbles = bytes_to_nibbles(key);
+        let root = Arc::clone(&self.root);
+        let Some(new_root) = self.remove_at(&root, &nibbles) else {
+            return false;
+        };
+        self.root = Arc::new(new_root);
+        self.record_root();
+        true
+    }
+
+    /// Recursive removal; None if the key is not in this subtree
+    fn remove_at(&mut self, node: &Node, path: &[u8]) -> Option<Node> {
+        match node {
+            Node::Empty => None,
+
+            Node::Leaf { path: leaf_path, .. } => (path == leaf_path.as_slice()).then_some(Node::Empty),
+
+            Node::Extension { path: ext_path, child_hash } => {
+                let remaining = path.strip_prefix(ext_path.as_slice())?;
+                let child = self.resolve(child_hash)?;
+                let new_child = self.remove_at(&child, remaining)?;
+                Some(self.under_path(ext_path, new_child))
+            }
+
+            Node::Branch { children, value } => {
+                let mut children = children.clone();
+                let mut value = value.clone();
+                match path.split_first() {
+                    None => {
+                        value.take()?;
                     }
+                    Some((&nibble, rest)) => {
+                        let child = self.resolve(children[nibble as usize].as_ref()?)?;
+                        children[nibble as usize] = match self.remove_at(&child, rest)? {
+                            Node::Empty => None,
+                            new_child => Some(self.store(new_child)),
+                        };
+                    }
+                }
 
-                    new_branch
+                let mut occupied = children.iter().enumerate().filter(|(_, child)| child.is_some());
+                match (occupied.next(), occupied.next(), value) {
+                    // A lone value becomes a leaf at the branch position
+                    (None, _, Some(value)) => Some(Node::leaf(Vec::new(), value)),
+                    // A lone child absorbs the branch nibble into its path
+                    (Some((nibble, Some(child_ref))), None, None) => {
+                        let child = self.resolve(child_ref)?;
+                        Some(self.under_path(&[nibble as u8], child.as_ref().clone()))
+                    }
+                    (_, _, value) => Some(Node::Branch { children, value }),
                 }
             }
         }
     }
 
+    /// Put a node below a path prefix, merging the prefix into a leaf or extension
+    fn under_path(&mut self, prefix: &[u8], node: Node) -> Node {
+        match node {
+            Node::Empty => Node::Empty,
+            Node::Leaf { path, value } => Node::leaf([prefix, &path].concat(), value),
+            Node::Extension { path, child_hash } => Node::extension([prefix, &path].concat(), child_hash),
+            branch @ Node::Branch { .. } => self.under_prefix(prefix, branch),
+        }
+    }
+
+    /// Place a value under a branch: at the branch itself if `rest` is empty,
+    /// otherwise as a leaf in the child slot of its first nibble
+    fn attach(&mut self, children: &mut [Option<Vec<u8>>; 16], branch_value: &mut Option<Vec<u8>>, rest: &[u8], value: &[u8]) {
+        match rest.split_first() {
+            None => *branch_value = Some(value.to_vec()),
+            Some((&nibble, leaf_path)) => {
+                let leaf = Node::leaf(leaf_path.to_vec(), value.to_vec());
+                children[nibble as usize] = Some(self.store(leaf));
+            }
+        }
+    }
+
+    /// Wrap a branch in an extension when it sits below a non-empty shared prefix
+    fn under_prefix(&mut self, prefix: &[u8], branch: Node) -> Node {
+        if prefix.is_empty() {
+            branch
+        } else {
+            let branch_ref = self.store(branch);
+            Node::extension(prefix.to_vec(), branch_ref)
+        }
+    }
+
+    /// Store a child node and return its reference
+    ///
+    /// Nodes whose encoding is shorter than 32 bytes are embedded in the parent
+    /// and never reach `storage`.
+    fn store(&mut self, node: Node) -> Vec<u8> {
+        let reference = node.reference();
+        if !is_inline(&reference) {
+            // 相同内容的节点已存储时沿用原有分配
+            self.storage.entry(reference.clone()).or_insert_with(|| Arc::new(node));
+        }
+        reference
+    }
+
+    /// Resolve a child reference to its node
+    ///
+    /// Hashed children are shared with storage; embedded ones are decoded afresh.
+    pub(super) fn resolve(&self, reference: &[u8]) -> Option<Arc<Node>> {
+        if is_inline(reference) {
+            Node::decode(reference).ok().map(Arc::new)
+        } else {
+            self.storage.get(reference).cloned()
+        }
+    }
+
+    /// Shared handle to the current root
+    pub(super) fn shared_root(&self) -> Arc<Node> {
+        Arc::clone(&self.root)
+    }
+
     /// Get a value from the trie
     pub fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
         let nibbles = bytes_to_nibbles(key);
         self.get_at(&self.root, &nibbles)
     }
 
+    /// Get a value as of a historical version
+    ///
+    /// Returns None if the key is absent in that version or the root is unknown.
+    pub fn get_at_root(&self, root_hash: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+        let root = self.roots.get(root_hash)?;
+        self.get_at(root, &bytes_to_nibbles(key))
+    }
+
+    /// Whether `root_hash` is a retained version of this trie
+    pub fn has_root(&self, root_hash: &[u8]) -> bool {
+        self.roots.contains_key(root_hash)
+    }
+
+    /// Root hashes of all retained versions, oldest first
+    pub fn root_history(&self) -> &[Vec<u8>] {
+        &self.root_history
+    }
+
+    /// Stop retaining a historical version so `prune` can reclaim its nodes
+    ///
+    /// The current root is always retained; returns whether the root was released.
+    pub fn release_root(&mut self, root_hash: &[u8]) -> bool {
+        if root_hash == self.root.hash().as_slice() || self.roots.remove(root_hash).is_none() {
+            return false;
+        }
+        self.root_history.retain(|hash| hash.as_slice() != root_hash);
+        true
+    }
+
+    /// Release all but the latest `versions` versions (at least the current one) and prune
+    ///
+    /// Returns the number of nodes removed.
+    pub fn keep_latest(&mut self, versions: usize) -> usize {
+        let count = self.root_history.len().saturating_sub(versions.max(1));
+        let released: Vec<Vec<u8>> = self.root_history.drain(..count).collect();
+        for hash in released {
+            // 同一根可能在保留的版本中再次出现
+            if !self.root_history.contains(&hash) {
+                self.release_root(&hash);
+            }
+        }
+        self.prune()
+    }
+
+    /// Mark-and-sweep: drop stored nodes not reachable from any retained root
+    ///
+    /// Returns the number of nodes removed.
+    pub fn prune(&mut self) -> usize {
+        let mut marked = HashSet::new();
+        let mut pending: Vec<&Node> = self.roots.values().chain([&self.root]).map(Arc::as_ref).collect();
+        while let Some(node) = pending.pop() {
+            let children: Vec<&Vec<u8>> = match node {
+                Node::Empty | Node::Leaf { .. } => continue,
+                Node::Extension { child_hash, .. } => vec![child_hash],
+                Node::Branch { children, .. } => children.iter().flatten().collect(),
+            };
+            // 内嵌节点不足32字节，容纳不下哈希引用，无需展开
+            for reference in children.into_iter().filter(|r| !is_inline(r)) {
+                if marked.insert(reference.clone())
+                    && let Some(child) = self.storage.get(reference)
+                {
+                    pending.push(child.as_ref());
+                }
+            }
+        }
+
+        let before = self.storage.len();
+        self.storage.retain(|hash, _| marked.contains(hash));
+        before - self.storage.len()
+    }
+
+    /// Number of hashed nodes held in storage
+    pub fn node_count(&self) -> usize {
+        self.storage.len()
+    }
+
     /// Recursive get at a node
     fn get_at(&self, node: &Node, path: &[u8]) -> Option<Vec<u8>> {
         match node {
@@ -258,8 +379,8 @@ impl MerklePatriciaTrie {
             Node::Extension { path: ext_path, child_hash } => {
                 if path.starts_with(ext_path) {
                     let remaining = &path[ext_path.len()..];
-                    let child = self.storage.get(child_hash)?;
-                    self.get_at(child, remaining)
+                    let child = self.resolve(child_hash)?;
+                    self.get_at(&child, remaining)
                 } else {
                     None
                 }
@@ -271,21 +392,32 @@ impl MerklePatriciaTrie {
                 } else {
                     let nibble = path[0] as usize;
                     let remaining = &path[1..];
-                    let child_hash = children[nibble].as_ref()?;
-                    let child = self.storage.get(child_hash)?;
-                    self.get_at(child, remaining)
+                    let child_ref = children[nibble].as_ref()?;
+                    let child = self.resolve(child_ref)?;
+                    self.get_at(&child, remaining)
                 }
             }
         }
     }
 
+    /// Iterate over all (key, value) pairs in lexicographic key order
+    pub fn iter(&self) -> TrieIter<'_> {
+        TrieIter::new(self)
+    }
+
+    /// Iterate over the (key, value) pairs whose key starts with `prefix`, in key order
+    pub fn iter_prefix(&self, prefix: &[u8]) -> TrieIter<'_> {
+        TrieIter::with_prefix(self, prefix)
+    }
+
     /// Generate a Merkle proof for a key
     ///
     /// Returns a proof that can be used to verify the existence (or non-existence)
-    /// of a key-value pair in the trie
+    /// of a key-value pair in the trie. Only hashed nodes are listed; embedded
+    /// nodes travel inside their parent's encoding.
     pub fn get_proof(&self, key: &[u8]) -> MerkleProof {
         let nibbles = bytes_to_nibbles(key);
-        let mut proof_nodes = Vec::new();
+        let mut proof_nodes = vec![self.root.as_ref().clone()];
         let value = self.get_proof_at(&self.root, &nibbles, &mut proof_nodes);
 
         MerkleProof::new(key.to_vec(), value, proof_nodes)
@@ -293,86 +425,188 @@ impl MerklePatriciaTrie {
 
     /// Recursive proof generation
     fn get_proof_at(&self, node: &Node, path: &[u8], proof_nodes: &mut Vec<Node>) -> Option<Vec<u8>> {
-        // 将当前节点添加到证明路径
-        proof_nodes.push(node.clone());
-
-        match node {
-            Node::Empty => None,
+        let (child_ref, remaining) = match node {
+            Node::Empty => return None,
 
             Node::Leaf { path: leaf_path, value } => {
-                if path == leaf_path.as_slice() {
-                    Some(value.clone())
-                } else {
-                    None
-                }
+                return (path == leaf_path.as_slice()).then(|| value.clone());
             }
 
             Node::Extension { path: ext_path, child_hash } => {
-                if path.starts_with(ext_path) {
-                    let remaining = &path[ext_path.len()..];
-                    let child = self.storage.get(child_hash)?;
-                    self.get_proof_at(child, remaining, proof_nodes)
-                } else {
-                    None
+                if !path.starts_with(ext_path) {
+                    return None;
                 }
+                (child_hash, &path[ext_path.len()..])
             }
 
             Node::Branch { children, value } => {
                 if path.is_empty() {
-                    value.clone()
-                } else {
-                    let nibble = path[0] as usize;
-                    let remaining = &path[1..];
-                    let child_hash = children[nibble].as_ref()?;
-                    let child = self.storage.get(child_hash)?;
-                    self.get_proof_at(child, remaining, proof_nodes)
+                    return value.clone();
                 }
+                (children[path[0] as usize].as_ref()?, &path[1..])
             }
+        };
+
+        // 哈希引用的子节点加入证明路径，内嵌子节点已包含在父节点中
+        let child = self.resolve(child_ref)?;
+        if !is_inline(child_ref) {
+            proof_nodes.push(child.as_ref().clone());
         }
+        self.get_proof_at(&child, remaining, proof_nodes)
     }
 
-    /// Compute the Merkle root hash
-    pub fn root_hash(&self) -> Vec<u8> {
-        self.hash_node(&self.root)
+    /// Generate a range proof covering every key in `[start, end]`
+    ///
+    /// The proof carries the entries in range plus the hashed nodes on the paths
+    /// of both boundary keys (which need not exist in the trie).
+    pub fn get_range_proof(&self, start: &[u8], end: &[u8]) -> RangeProof {
+        let entries = self
+            .iter()
+            .skip_while(|(key, _)| key.as_slice() < start)
+            .take_while(|(key, _)| key.as_slice() <= end)
+            .collect();
+
+        let mut proof_nodes = self.get_proof(start).proof_nodes;
+        for node in self.get_proof(end).proof_nodes {
+            if !proof_nodes.contains(&node) {
+                proof_nodes.push(node);
+            }
+        }
+        RangeProof::new(start.to_vec(), end.to_vec(), entries, proof_nodes)
     }
 
-    /// Hash a node (simplified)
-    fn hash_node(&self, node: &Node) -> Vec<u8> {
+    /// Remove every key whose nibble path lies in `[start, end]`
+    ///
+    /// Subtrees entirely inside the range are dropped without being resolved and
+    /// subtrees entirely outside are left untouched, so only the nodes on the two
+    /// boundary paths are needed. Returns None if one of those is missing.
+    /// Emptied nodes are not collapsed; re-inserting the range restores the shape.
+    pub(super) fn unset_range(&mut self, start: &[u8], end: &[u8]) -> Option<()> {
+        let root = Arc::clone(&self.root);
+        self.root = Arc::new(self.unset_at(&root, Some(start), Some(end))?);
+        Some(())
+    }
+
+    /// Recursive range removal (a None bound means the subtree is already past it)
+    fn unset_at(&mut self, node: &Node, left: Option<&[u8]>, right: Option<&[u8]>) -> Option<Node> {
         match node {
-            Node::Empty => vec![],
-            Node::Leaf { path, value } => {
-                let encoded_path = compact_encode(path, true);
-                let mut data = encoded_path;
-                data.extend_from_slice(value);
-                keccak256(&data).to_vec()
+            Node::Empty => Some(Node::Empty),
+
+            Node::Leaf { path, .. } => {
+                let path = path.as_slice();
+                let in_range = left.is_none_or(|l| path >= l) && right.is_none_or(|r| path <= r);
+                Some(if in_range { Node::Empty } else { node.clone() })
             }
+
             Node::Extension { path, child_hash } => {
-                let encoded_path = compact_encode(path, false);
-                let mut data = encoded_path;
-                data.extend_from_slice(child_hash);
-                keccak256(&data).to_vec()
-            }
-            Node::Branch { children, value } => {
-                let mut data = Vec::new();
-                for child in children.iter() {
-                    if let Some(hash) = child {
-                        data.extend_from_slice(hash);
+                let Some((left, right)) = narrow(path, left, right) else {
+                    return Some(node.clone());
+                };
+                if left.is_none() && right.is_none() {
+                    return Some(Node::Empty);
+                }
+
+                let child = self.resolve(child_hash)?;
+                match self.unset_at(&child, left, right)? {
+                    Node::Empty => Some(Node::Empty),
+                    new_child => {
+                        let new_child_ref = self.store(new_child);
+                        Some(Node::extension(path.clone(), new_child_ref))
                     }
                 }
-                if let Some(v) = value {
-                    data.extend_from_slice(v);
+            }
+
+            Node::Branch { children, value } => {
+                let mut new_children = children.clone();
+                for (nibble, child_ref) in children.iter().enumerate() {
+                    let Some(child_ref) = child_ref else { continue };
+                    let Some((left, right)) = narrow(&[nibble as u8], left, right) else {
+                        continue;
+                    };
+
+                    new_children[nibble] = if left.is_none() && right.is_none() {
+                        None
+                    } else {
+                        let child = self.resolve(child_ref)?;
+                        match self.unset_at(&child, left, right)? {
+                            Node::Empty => None,
+                            new_child => Some(self.store(new_child)),
+                        }
+                    };
                 }
-                keccak256(&data).to_vec()
+
+                // The branch value sits at the empty suffix, which is below any non-empty left bound
+                let value_in_range = left.is_none_or(<[u8]>::is_empty);
+                Some(Node::Branch {
+                    children: new_children,
+                    value: if value_in_range { None } else { value.clone() },
+                })
             }
         }
     }
 
+    /// Compute the Merkle root hash
+    ///
+    /// The root is always hashed, even when its encoding is shorter than 32 bytes;
+    /// the empty trie hashes to Keccak256 of the empty string's RLP (0x80).
+    pub fn root_hash(&self) -> Vec<u8> {
+        self.root.hash()
+    }
+
     /// Get the root node (for inspection)
     pub fn root(&self) -> &Node {
         &self.root
     }
 }
 
+/// Where the subtree under a path prefix lies relative to a range bound
+enum Position<'a> {
+    /// Every key in the subtree is below the bound
+    Below,
+    /// Every key in the subtree is above the bound
+    Above,
+    /// The bound runs through the subtree; this is its remainder below the prefix
+    Within(&'a [u8]),
+}
+
+fn position<'a>(path: &[u8], bound: &'a [u8]) -> Position<'a> {
+    let n = path.len().min(bound.len());
+    match path[..n].cmp(&bound[..n]) {
+        Ordering::Less => Position::Below,
+        Ordering::Greater => Position::Above,
+        Ordering::Equal if bound.len() >= path.len() => Position::Within(&bound[path.len()..]),
+        // The bound is a proper prefix of the path, so every key extending the path is greater
+        Ordering::Equal => Position::Above,
+    }
+}
+
+/// (left, right) range bounds relative to a subtree; None means the subtree is past that bound
+type Bounds<'a> = (Option<&'a [u8]>, Option<&'a [u8]>);
+
+/// Bounds for the subtree under `path`, or None if it lies entirely outside the range
+fn narrow<'a>(path: &[u8], left: Option<&'a [u8]>, right: Option<&'a [u8]>) -> Option<Bounds<'a>> {
+    let left = match left.map(|l| position(path, l)) {
+        None | Some(Position::Above) => None,
+        Some(Position::Below) => return None,
+        Some(Position::Within(rest)) => Some(rest),
+    };
+    let right = match right.map(|r| position(path, r)) {
+        None | Some(Position::Below) => None,
+        Some(Position::Above) => return None,
+        Some(Position::Within(rest)) => Some(rest),
+    };
+    Some((left, right))
+}
+
+impl<'a> IntoIterator for &'a MerklePatriciaTrie {
+    type Item = (Vec<u8>, Vec<u8>);
+    type IntoIter = TrieIter<'a>;
+
+    fn into_iter(self) -> TrieIter<'a> {
+        self.iter()
+    }
+}
+
 impl Default for MerklePatriciaTrie {
     fn default() -> Self {
         Self::new()
@@ -382,6 +616,7 @@ impl Default for MerklePatriciaTrie {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mpt::hash::hash_to_hex;
 
     #[test]
     fn test_insert_and_get() {
@@ -527,4 +762,201 @@ mod tests {
             assert!(proof.verify(&root_hash));
         }
     }
+
+    #[test]
+    fn test_inline_nodes() {
+        let mut trie = MerklePatriciaTrie::new();
+        trie.insert(b"a", b"1");
+        trie.insert(b"b", b"2");
+
+        // 叶节点和分支节点编码都不足32字节，全部内嵌在根扩展节点中
+        assert!(trie.storage.is_empty());
+        assert_eq!(trie.get(b"b"), Some(b"2".to_vec()));
+        let proof = trie.get_proof(b"a");
+        assert_eq!(proof.proof_nodes.len(), 1);
+        assert!(proof.verify(&trie.root_hash()));
+        assert!(trie.get_proof(b"c").verify(&trie.root_hash()));
+
+        // 长值使叶节点和其所在分支节点都改为哈希引用
+        trie.insert(b"c", &[0x55; 40]);
+        assert_eq!(trie.storage.len(), 2);
+        assert_eq!(trie.get_proof(b"a").proof_nodes.len(), 2);
+        let proof = trie.get_proof(b"c");
+        assert_eq!(proof.proof_nodes.len(), 3);
+        assert!(proof.verify(&trie.root_hash()));
+    }
+
+    #[test]
+    fn test_ethereum_trie_vectors() {
+        // ethereum/tests TrieTests/trietest.json 中的用例
+        let vectors: [(&[(&[u8], &[u8])], &str); 3] = [
+            (&[], "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"),
+            (
+                &[(b"doe", b"reindeer"), (b"dog", b"puppy"), (b"dogglesworth", b"cat")],
+                "8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3",
+            ),
+            (
+                &[(b"do", b"verb"), (b"horse", b"stallion"), (b"doge", b"coin"), (b"dog", b"puppy")],
+                "5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84",
+            ),
+        ];
+        for (entries, root) in vectors {
+            let mut trie = MerklePatriciaTrie::new();
+            for (key, value) in entries {
+                trie.insert(key, value);
+            }
+            assert_eq!(hash_to_hex(&trie.root_hash()), root);
+        }
+    }
+
+    #[test]
+    fn test_root_independent_of_insert_order() {
+        let entries: [(&[u8], &[u8]); 6] = [
+            (b"d", b"letter"),
+            (b"do", b"verb"),
+            (b"dog", b"puppy"),
+            (b"doge", b"coin"),
+            (b"horse", b"stallion"),
+            (b"dogecoin-with-a-long-key", b"a value long enough to be stored by hash"),
+        ];
+        let build = |order: &[usize]| {
+            let mut trie = MerklePatriciaTrie::new();
+            for &i in order {
+                trie.insert(entries[i].0, entries[i].1);
+            }
+            trie
+        };
+
+        let trie = build(&[0, 1, 2, 3, 4, 5]);
+        for order in [[5, 4, 3, 2, 1, 0], [2, 5, 0, 4, 3, 1], [3, 1, 4, 0, 5, 2]] {
+            assert_eq!(build(&order).root_hash(), trie.root_hash());
+        }
+        for (key, value) in entries {
+            assert_eq!(trie.get(key), Some(value.to_vec()));
+            assert!(trie.get_proof(key).verify(&trie.root_hash()));
+        }
+    }
+
+    #[test]
+    fn test_get_at_root() {
+        let mut trie = MerklePatriciaTrie::new();
+        let genesis = trie.root_hash();
+
+        trie.insert(b"alice", b"100");
+        trie.insert(b"bob", b"50");
+        let block1 = trie.root_hash();
+
+        trie.insert(b"alice", b"70");
+        trie.insert(b"carol", &[7; 40]);
+        let block2 = trie.root_hash();
+
+        assert_eq!(trie.get_at_root(&block1, b"alice"), Some(b"100".to_vec()));
+        assert_eq!(trie.get_at_root(&block1, b"carol"), None);
+        assert_eq!(trie.get_at_root(&block2, b"alice"), Some(b"70".to_vec()));
+        assert_eq!(trie.get_at_root(&block2, b"carol"), Some(vec![7; 40]));
+        assert_eq!(trie.get_at_root(&block2, b"bob"), trie.get(b"bob"));
+        assert_eq!(trie.get_at_root(&genesis, b"alice"), None);
+        assert_eq!(trie.get_at_root(b"unknown", b"alice"), None);
+
+        // 重复写入相同值不产生新版本
+        trie.insert(b"bob", b"50");
+        assert_eq!(trie.root_history().len(), 5);
+        assert_eq!(trie.root_history().first(), Some(&genesis));
+        assert_eq!(trie.root_history().last(), Some(&block2));
+        assert!(trie.has_root(&block1) && !trie.has_root(b"unknown"));
+    }
+
+    #[test]
+    fn test_prune_stale_nodes() {
+        let mut trie = MerklePatriciaTrie::new();
+        for round in 0..50u8 {
+            for i in 0..20u8 {
+                trie.insert(&[i, i], &[round; 40]);
+            }
+        }
+        // 倒数第21个版本是第48轮写完时的状态
+        let retained = trie.root_history()[trie.root_history().len() - 21].clone();
+        let grown = trie.node_count();
+
+        // 保留当前版本和一个历史版本
+        let others: Vec<_> = trie.root_history().iter().filter(|&h| *h != retained).cloned().collect();
+        for hash in others {
+            trie.release_root(&hash);
+        }
+        assert!(!trie.release_root(&trie.root_hash()));
+        let removed = trie.prune();
+        assert_eq!(trie.node_count(), grown - removed);
+        assert_eq!(trie.root_history().len(), 2);
+        assert_eq!(trie.get_at_root(&retained, &[0, 0]), Some(vec![48; 40]));
+        assert_eq!(trie.get_at_root(&retained, &[19, 19]), Some(vec![48; 40]));
+        assert_eq!(trie.get(&[0, 0]), Some(vec![49; 40]));
+
+        // 只保留当前版本后，存储与直接写入最终内容的trie一致
+        trie.keep_latest(1);
+        let mut fresh = MerklePatriciaTrie::new();
+        for i in 0..20u8 {
+            fresh.insert(&[i, i], &[49; 40]);
+        }
+        fresh.keep_latest(1);
+        assert_eq!(trie.node_count(), fresh.node_count());
+        assert_eq!(trie.root_hash(), fresh.root_hash());
+        assert!(trie.iter().all(|(_, value)| value == vec![49; 40]));
+        assert_eq!(trie.prune(), 0);
+    }
+
+    #[test]
+    fn test_versions_share_nodes() {
+        let mut trie = MerklePatriciaTrie::new();
+        for i in 0..16u8 {
+            trie.insert(&[i << 4, 1], &[i; 40]);
+        }
+        let shared: Vec<(Vec<u8>, Arc<Node>)> =
+            trie.storage.iter().map(|(hash, node)| (hash.clone(), Arc::clone(node))).collect();
+        let count = trie.node_count();
+
+        // 更新一个键只新建路径上的节点，其余子树与上一版本共享同一分配
+        trie.insert(&[0x30, 1], &[99; 40]);
+        assert_eq!(trie.node_count(), count + 1);
+        for (hash, node) in &shared {
+            assert!(Arc::ptr_eq(node, &trie.storage[hash]));
+            assert!(Arc::ptr_eq(&trie.resolve(hash).unwrap(), node));
+        }
+
+        // 写入相同的值不产生新节点
+        trie.insert(&[0x50, 1], &[5; 40]);
+        assert_eq!(trie.node_count(), count + 1);
+        assert_eq!(trie.get(&[0x30, 1]), Some(vec![99; 40]));
+    }
+
+    #[test]
+    fn test_remove() {
+        let entries: [(&[u8], &[u8]); 7] = [
+            (b"d", b"letter"),
+            (b"do", b"verb"),
+            (b"dog", b"puppy"),
+            (b"doge", b"coin"),
+            (b"dogs", &[3; 40]),
+            (b"horse", b"stallion"),
+            (b"dogecoin-with-a-long-key", b"a value long enough to be stored by hash"),
+        ];
+        let mut trie = MerklePatriciaTrie::new();
+        for (key, value) in entries {
+            trie.insert(key, value);
+        }
+        assert!(!trie.remove(b"cat"));
+        assert!(!trie.remove(b"dogecoin"));
+
+        // 每次删除后与只写入剩余键的trie根一致
+        for (removed, (key, _)) in entries.iter().enumerate() {
+            assert!(trie.remove(key));
+            assert_eq!(trie.get(key), None);
+            let mut expected = MerklePatriciaTrie::new();
+            for (key, value) in &entries[removed + 1..] {
+                expected.insert(key, value);
+                assert_eq!(trie.get(key), Some(value.to_vec()));
+            }
+            assert_eq!(trie.root_hash(), expected.root_hash(), "after removing {:?}", key);
+        }
+        assert_eq!(trie.root(), &Node::Empty);
+    }
 }
@@ -0,0 +1,162 @@
+/// 组播抓包文件
+///
+/// 订阅端将收到的消息原样落盘，供事后与撮合日志核对（见`orderbook::reconcile`）。
+/// 每条记录与线上格式一致:
+/// - 8字节序列号 + 8字节时间戳 + 1字节消息类型 + 4字节载荷长度 + N字节载荷（little-endian）
+///
+/// 文件末尾的半条记录（写入时崩溃）视为结束，不报错。
+
+use super::multicast::{MessageType, MulticastMessage};
+use std::io::{self, Read, Write};
+
+/// 记录头长度
+const HEADER_LEN: usize = 8 + 8 + 1 + 4;
+
+/// 抓包写入器
+pub struct CaptureWriter<W: Write> {
+    writer: W,
+    records: u64,
+}
+
+impl<W: Write> CaptureWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, records: 0 }
+    }
+
+    /// 追加一条消息
+    pub fn record(&mut self, message: &MulticastMessage) -> io::Result<()> {
+        let mut buf = Vec::with_capacity(HEADER_LEN + message.payload.len());
+        buf.extend_from_slice(&message.sequence.to_le_bytes());
+        buf.extend_from_slice(&message.timestamp_ns.to_le_bytes());
+        buf.push(message.msg_type.to_u8());
+        buf.extend_from_slice(&(message.payload.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&message.payload);
+        self.writer.write_all(&buf)?;
+        self.records += 1;
+        Ok(())
+    }
+
+    /// 已写入的记录数
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// 抓包读取器，按顺序迭代消息
+pub struct CaptureReader<R: Read> {
+    reader: R,
+    done: bool,
+}
+
+impl<R: Read> CaptureReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, done: false }
+    }
+
+    fn read_message(&mut self) -> io::Result<Option<MulticastMessage>> {
+        let mut header = [0u8; HEADER_LEN];
+        if !read_full(&mut self.reader, &mut header)? {
+            return Ok(None);
+        }
+
+        let sequence = u64::from_le_bytes(header[0..8].try_into().unwrap());
+        let timestamp_ns = u64::from_le_bytes(header[8..16].try_into().unwrap());
+        let msg_type = MessageType::from_u8(header[16]).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("invalid message type {}", header[16]))
+        })?;
+        let payload_len = u32::from_le_bytes(header[17..21].try_into().unwrap()) as usize;
+
+        let mut payload = vec![0u8; payload_len];
+        if !read_full(&mut self.reader, &mut payload)? {
+            return Ok(None);
+        }
+
+        Ok(Some(MulticastMessage {
+            sequence,
+            timestamp_ns,
+            msg_type,
+            payload,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for CaptureReader<R> {
+    type Item = io::Result<MulticastMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.read_message() {
+            Ok(Some(message)) => Some(Ok(message)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// 读满缓冲区；在任何字节之前或记录中途遇到EOF都返回false
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(sequence: u64, msg_type: MessageType, payload: &[u8]) -> MulticastMessage {
+        MulticastMessage {
+            sequence,
+            timestamp_ns: sequence * 10,
+            msg_type,
+            payload: payload.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_capture_roundtrip_and_torn_tail() {
+        let mut writer = CaptureWriter::new(Vec::new());
+        writer.record(&message(1, MessageType::Trade, b"abc")).unwrap();
+        writer.record(&message(2, MessageType::Heartbeat, b"")).unwrap();
+        writer.record(&message(3, MessageType::Trade, b"xyz")).unwrap();
+        assert_eq!(writer.records(), 3);
+        let mut data = writer.into_inner();
+        data.truncate(data.len() - 2);
+
+        let messages: Vec<_> = CaptureReader::new(data.as_slice()).collect::<io::Result<_>>().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!((messages[0].sequence, messages[0].msg_type), (1, MessageType::Trade));
+        assert_eq!(messages[0].payload, b"abc");
+        assert_eq!((messages[1].sequence, messages[1].timestamp_ns), (2, 20));
+    }
+
+    #[test]
+    fn test_capture_invalid_type() {
+        let mut data = CaptureWriter::new(Vec::new());
+        data.record(&message(1, MessageType::Trade, b"")).unwrap();
+        let mut data = data.into_inner();
+        data[16] = 99;
+
+        let mut reader = CaptureReader::new(data.as_slice());
+        assert_eq!(reader.next().unwrap().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(reader.next().is_none());
+    }
+}
@@ -0,0 +1,289 @@
+/// 组播控制通道
+///
+/// 订阅端通过独立的控制通道（单播或单独的组播组）周期性发送存活通告，
+/// 发布端据此维护活跃订阅者集合：
+/// - 运维可查看当前有哪些消费者、各自接收进度
+/// - 预期的消费者超过超时时间未通告时产生告警，恢复通告时产生恢复事件
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+use parking_lot::Mutex;
+use serde::Serialize;
+use super::multicast::MulticastError;
+
+/// 存活通告
+const KIND_ANNOUNCE: u8 = 1;
+/// 主动离开
+const KIND_GOODBYE: u8 = 2;
+
+/// 控制消息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlMessage {
+    /// 订阅端存活通告
+    Announce {
+        listener_id: String,
+        /// 已收到的最大行情序列号
+        last_sequence: u64,
+        /// 已收到的行情消息数
+        messages_received: u64,
+    },
+    /// 订阅端正常退出
+    Goodbye { listener_id: String },
+}
+
+impl ControlMessage {
+    /// 订阅端标识
+    pub fn listener_id(&self) -> &str {
+        match self {
+            ControlMessage::Announce { listener_id, .. } => listener_id,
+            ControlMessage::Goodbye { listener_id } => listener_id,
+        }
+    }
+
+    /// 序列化
+    ///
+    /// 格式:
+    /// - 1字节: 类型（1=通告, 2=离开）
+    /// - 1字节: 标识长度 + N字节标识（UTF-8，最长255字节）
+    /// - 通告: 8字节最大序列号 + 8字节消息数 (little-endian u64)
+    pub fn encode(&self) -> Vec<u8> {
+        let id = self.listener_id().as_bytes();
+        let id = &id[..id.len().min(u8::MAX as usize)];
+
+        let mut buf = Vec::with_capacity(2 + id.len() + 16);
+        match self {
+            ControlMessage::Announce { .. } => buf.push(KIND_ANNOUNCE),
+            ControlMessage::Goodbye { .. } => buf.push(KIND_GOODBYE),
+        }
+        buf.push(id.len() as u8);
+        buf.extend_from_slice(id);
+
+        if let ControlMessage::Announce { last_sequence, messages_received, .. } = self {
+            buf.extend_from_slice(&last_sequence.to_le_bytes());
+            buf.extend_from_slice(&messages_received.to_le_bytes());
+        }
+        buf
+    }
+
+    /// 反序列化
+    pub fn decode(data: &[u8]) -> Result<Self, MulticastError> {
+        let incomplete = || MulticastError::Deserialization("Incomplete control message".to_string());
+
+        let (&kind, rest) = data.split_first().ok_or_else(incomplete)?;
+        let (&len, rest) = rest.split_first().ok_or_else(incomplete)?;
+        let id = rest.get(..len as usize).ok_or_else(incomplete)?;
+        let listener_id = String::from_utf8(id.to_vec())
+            .map_err(|_| MulticastError::Deserialization("Invalid listener id".to_string()))?;
+        let rest = &rest[len as usize..];
+
+        match kind {
+            KIND_ANNOUNCE => {
+                if rest.len() < 16 {
+                    return Err(incomplete());
+                }
+                Ok(ControlMessage::Announce {
+                    listener_id,
+                    last_sequence: u64::from_le_bytes(rest[0..8].try_into().unwrap()),
+                    messages_received: u64::from_le_bytes(rest[8..16].try_into().unwrap()),
+                })
+            }
+            KIND_GOODBYE => Ok(ControlMessage::Goodbye { listener_id }),
+            other => Err(MulticastError::InvalidMessageType(other)),
+        }
+    }
+}
+
+/// 订阅者状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ListenerInfo {
+    pub listener_id: String,
+    /// 最后一次通告时间（纳秒）
+    pub last_seen_ns: u64,
+    /// 已收到的最大行情序列号
+    pub last_sequence: u64,
+    /// 已收到的行情消息数
+    pub messages_received: u64,
+    /// 是否在超时时间内通告过
+    pub active: bool,
+}
+
+/// 订阅者告警
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenerAlert {
+    /// 预期的订阅者超时未通告（`last_seen_ns`为None表示从未出现）
+    Missing {
+        listener_id: String,
+        last_seen_ns: Option<u64>,
+    },
+    /// 订阅者恢复通告
+    Recovered { listener_id: String },
+}
+
+struct RegistryInner {
+    listeners: BTreeMap<String, ListenerInfo>,
+    expected: BTreeSet<String>,
+    /// 已发出Missing告警、尚未恢复的订阅者
+    missing: BTreeSet<String>,
+}
+
+/// 活跃订阅者登记表（发布端使用）
+pub struct ListenerRegistry {
+    timeout_ns: u64,
+    inner: Mutex<RegistryInner>,
+}
+
+impl ListenerRegistry {
+    /// 创建登记表，超过`timeout`未通告的订阅者视为失联
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout_ns: timeout.as_nanos() as u64,
+            inner: Mutex::new(RegistryInner {
+                listeners: BTreeMap::new(),
+                expected: BTreeSet::new(),
+                missing: BTreeSet::new(),
+            }),
+        }
+    }
+
+    /// 登记预期的订阅者（失联时告警）
+    pub fn expect(&self, listener_id: &str) {
+        self.inner.lock().expected.insert(listener_id.to_string());
+    }
+
+    /// 处理控制消息
+    pub fn record(&self, message: &ControlMessage, now_ns: u64) {
+        let mut inner = self.inner.lock();
+        match message {
+            ControlMessage::Announce { listener_id, last_sequence, messages_received } => {
+                inner.listeners.insert(
+                    listener_id.clone(),
+                    ListenerInfo {
+                        listener_id: listener_id.clone(),
+                        last_seen_ns: now_ns,
+                        last_sequence: *last_sequence,
+                        messages_received: *messages_received,
+                        active: true,
+                    },
+                );
+            }
+            ControlMessage::Goodbye { listener_id } => {
+                // 预期的订阅者离开后仍会在下次检查时告警
+                inner.listeners.remove(listener_id);
+            }
+        }
+    }
+
+    /// 当前订阅者列表（按标识排序）
+    pub fn listeners(&self, now_ns: u64) -> Vec<ListenerInfo> {
+        let inner = self.inner.lock();
+        inner
+            .listeners
+            .values()
+            .map(|info| ListenerInfo {
+                active: self.is_alive(info, now_ns),
+                ..info.clone()
+            })
+            .collect()
+    }
+
+    /// 活跃订阅者数量
+    pub fn active_count(&self, now_ns: u64) -> usize {
+        let inner = self.inner.lock();
+        inner.listeners.values().filter(|info| self.is_alive(info, now_ns)).count()
+    }
+
+    /// 检查预期订阅者，返回状态变化产生的告警（每次失联只告警一次）
+    pub fn check(&self, now_ns: u64) -> Vec<ListenerAlert> {
+        let mut inner = self.inner.lock();
+        let inner = &mut *inner;
+        let mut alerts = Vec::new();
+
+        for listener_id in &inner.expected {
+            let info = inner.listeners.get(listener_id);
+            let alive = info.is_some_and(|info| self.is_alive(info, now_ns));
+
+            if alive && inner.missing.remove(listener_id) {
+                alerts.push(ListenerAlert::Recovered {
+                    listener_id: listener_id.clone(),
+                });
+            } else if !alive && inner.missing.insert(listener_id.clone()) {
+                alerts.push(ListenerAlert::Missing {
+                    listener_id: listener_id.clone(),
+                    last_seen_ns: info.map(|info| info.last_seen_ns),
+                });
+            }
+        }
+
+        alerts
+    }
+
+    #[inline]
+    fn is_alive(&self, info: &ListenerInfo, now_ns: u64) -> bool {
+        now_ns.saturating_sub(info.last_seen_ns) <= self.timeout_ns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn announce(id: &str, last_sequence: u64) -> ControlMessage {
+        ControlMessage::Announce {
+            listener_id: id.to_string(),
+            last_sequence,
+            messages_received: last_sequence,
+        }
+    }
+
+    #[test]
+    fn test_control_message_roundtrip() {
+        let messages = [
+            announce("risk-1", 42),
+            ControlMessage::Goodbye { listener_id: "risk-1".to_string() },
+        ];
+        for message in messages {
+            let encoded = message.encode();
+            assert_eq!(ControlMessage::decode(&encoded).unwrap(), message);
+            assert!(ControlMessage::decode(&encoded[..encoded.len() - 1]).is_err());
+        }
+        assert!(ControlMessage::decode(&[9, 0]).is_err());
+    }
+
+    #[test]
+    fn test_registry_tracks_and_alerts() {
+        let registry = ListenerRegistry::new(Duration::from_nanos(100));
+        registry.expect("risk");
+        registry.expect("archiver");
+
+        registry.record(&announce("risk", 10), 0);
+        registry.record(&announce("adhoc", 5), 0);
+        assert_eq!(registry.active_count(50), 2);
+
+        // 从未出现的预期订阅者立即告警，且只告警一次
+        let alerts = registry.check(50);
+        assert_eq!(
+            alerts,
+            vec![ListenerAlert::Missing { listener_id: "archiver".to_string(), last_seen_ns: None }]
+        );
+        assert!(registry.check(60).is_empty());
+
+        // risk超时
+        let alerts = registry.check(200);
+        assert_eq!(
+            alerts,
+            vec![ListenerAlert::Missing { listener_id: "risk".to_string(), last_seen_ns: Some(0) }]
+        );
+        let listeners = registry.listeners(200);
+        assert_eq!(listeners.len(), 2);
+        assert!(listeners.iter().all(|info| !info.active));
+
+        registry.record(&announce("risk", 20), 250);
+        registry.record(&announce("archiver", 1), 250);
+        let alerts = registry.check(260);
+        assert_eq!(alerts.len(), 2);
+        assert!(alerts.contains(&ListenerAlert::Recovered { listener_id: "risk".to_string() }));
+
+        registry.record(&ControlMessage::Goodbye { listener_id: "adhoc".to_string() }, 260);
+        assert_eq!(registry.listeners(260).len(), 2);
+    }
+}
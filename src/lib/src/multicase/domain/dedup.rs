@@ -0,0 +1,176 @@
+/// 组播重复报文过滤
+///
+/// 重传、补缺或A/B双路行情（未使用仲裁时）会让同一条消息到达多次。
+/// 按(来源, 序列号)维护有界滑动窗口：每个来源只记住最近`window`个序列号是否已收到，
+/// 内存占用与消息量无关。
+///
+/// 早于窗口的序列号无法判断是否重复，按`Expired`返回，由调用方决定是否投递。
+/// 来源发送重启标记后应调用`reset`，否则其新序列会被误判为重复。
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// 默认窗口大小（序列号个数）
+pub const DEFAULT_DEDUP_WINDOW: usize = 4096;
+
+/// 判定结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupVerdict {
+    /// 首次收到
+    New,
+    /// 窗口内已收到过
+    Duplicate,
+    /// 早于窗口，无法判断
+    Expired,
+}
+
+/// 单个来源的窗口
+#[derive(Debug, Clone)]
+struct SourceWindow {
+    /// 已收到的最大序列号
+    highest: u64,
+    /// 位图，第`seq % window`位表示`seq`是否已收到
+    seen: Vec<u64>,
+}
+
+impl SourceWindow {
+    fn new(window: usize) -> Self {
+        Self {
+            highest: 0,
+            seen: vec![0; window.div_ceil(64)],
+        }
+    }
+
+    #[inline]
+    fn slot(&self, sequence: u64) -> (usize, u64) {
+        let bit = sequence % (self.seen.len() as u64 * 64);
+        ((bit / 64) as usize, 1 << (bit % 64))
+    }
+
+    fn check(&mut self, sequence: u64) -> DedupVerdict {
+        let window = self.seen.len() as u64 * 64;
+        if sequence > self.highest {
+            // 窗口前移：清除新进入窗口的序列号对应的位
+            if sequence - self.highest >= window {
+                self.seen.fill(0);
+            } else {
+                for skipped in self.highest + 1..=sequence {
+                    let (word, mask) = self.slot(skipped);
+                    self.seen[word] &= !mask;
+                }
+            }
+            self.highest = sequence;
+        } else if self.highest - sequence >= window {
+            return DedupVerdict::Expired;
+        }
+
+        let (word, mask) = self.slot(sequence);
+        if self.seen[word] & mask != 0 {
+            return DedupVerdict::Duplicate;
+        }
+        self.seen[word] |= mask;
+        DedupVerdict::New
+    }
+}
+
+/// 按来源的去重窗口
+#[derive(Debug, Clone)]
+pub struct DedupWindow<S> {
+    window: usize,
+    sources: HashMap<S, SourceWindow>,
+    duplicates: u64,
+    expired: u64,
+}
+
+impl<S: Eq + Hash> DedupWindow<S> {
+    /// 每个来源记住最近`window`个序列号（向上取整到64的倍数）
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            sources: HashMap::new(),
+            duplicates: 0,
+            expired: 0,
+        }
+    }
+
+    /// 判定一条消息
+    pub fn check(&mut self, source: S, sequence: u64) -> DedupVerdict {
+        let window = self.window;
+        let verdict = self
+            .sources
+            .entry(source)
+            .or_insert_with(|| SourceWindow::new(window))
+            .check(sequence);
+        match verdict {
+            DedupVerdict::Duplicate => self.duplicates += 1,
+            DedupVerdict::Expired => self.expired += 1,
+            DedupVerdict::New => {}
+        }
+        verdict
+    }
+
+    /// 清除来源的窗口（来源重启、序列号重新开始时）
+    pub fn reset(&mut self, source: &S) {
+        self.sources.remove(source);
+    }
+
+    /// 累计重复数
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// 累计早于窗口的消息数
+    pub fn expired(&self) -> u64 {
+        self.expired
+    }
+
+    /// 跟踪的来源数
+    pub fn sources(&self) -> usize {
+        self.sources.len()
+    }
+}
+
+impl<S: Eq + Hash> Default for DedupWindow<S> {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEDUP_WINDOW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicates_within_window() {
+        let mut dedup = DedupWindow::new(64);
+        assert_eq!(dedup.check("A", 1), DedupVerdict::New);
+        assert_eq!(dedup.check("A", 3), DedupVerdict::New);
+        assert_eq!(dedup.check("A", 1), DedupVerdict::Duplicate);
+        // 补缺到达
+        assert_eq!(dedup.check("A", 2), DedupVerdict::New);
+        assert_eq!(dedup.check("A", 2), DedupVerdict::Duplicate);
+        // 不同来源互不影响
+        assert_eq!(dedup.check("B", 1), DedupVerdict::New);
+        assert_eq!((dedup.duplicates(), dedup.sources()), (2, 2));
+    }
+
+    #[test]
+    fn test_window_slides() {
+        let mut dedup = DedupWindow::new(64);
+        for sequence in 1..=10 {
+            dedup.check(0u8, sequence);
+        }
+        // 跳过的序列号位被清除，不会残留旧轮次的标记
+        assert_eq!(dedup.check(0, 70), DedupVerdict::New);
+        assert_eq!(dedup.check(0, 69), DedupVerdict::New);
+        assert_eq!(dedup.check(0, 10), DedupVerdict::Duplicate);
+        assert_eq!(dedup.check(0, 6), DedupVerdict::Expired);
+
+        assert_eq!(dedup.check(0, 1_000), DedupVerdict::New);
+        assert_eq!(dedup.check(0, 70), DedupVerdict::Expired);
+        assert_eq!(dedup.expired(), 2);
+
+        dedup.reset(&0);
+        assert_eq!(dedup.check(0, 1), DedupVerdict::New);
+    }
+}
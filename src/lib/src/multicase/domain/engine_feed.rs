@@ -0,0 +1,297 @@
+/// 撮合引擎实时行情桥接
+///
+/// 把引擎的逐笔成交（`TradeSink`）和最优价变化转为组播载荷，经通道交给
+/// `UdpMulticastPublisher::spawn_engine_feed`，以现有的序列号/时间戳信封发布:
+/// - `Trade`通道: `TradeUpdate`
+/// - `Ticker`通道: `BboUpdate`（仅在最优价或该档数量变化时发布）
+///
+/// 撮合线程上只做一次无阻塞的通道发送，不等待网络。
+
+use super::multicast::{MessageType, MulticastError};
+use crate::orderbook::{DepthLevel, OrderBook, Price, Quantity, Side, Trade, TradeSink};
+use std::collections::HashMap;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// 成交载荷
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeUpdate {
+    pub symbol: String,
+    pub trade_id: u64,
+    /// 成交时间（纳秒）
+    pub timestamp_ns: u64,
+    /// 成交价（最小价位数）
+    pub price: Price,
+    pub quantity: Quantity,
+    pub aggressor_side: Side,
+}
+
+/// 最优价载荷
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BboUpdate {
+    pub symbol: String,
+    pub best_bid: Option<DepthLevel>,
+    pub best_ask: Option<DepthLevel>,
+}
+
+/// 引擎行情更新
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeedUpdate {
+    Trade(TradeUpdate),
+    Bbo(BboUpdate),
+}
+
+impl TradeUpdate {
+    pub fn new(symbol: &str, trade: &Trade) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            trade_id: trade.trade_id,
+            timestamp_ns: trade.timestamp_ns,
+            price: trade.price,
+            quantity: trade.quantity,
+            aggressor_side: trade.aggressor_side,
+        }
+    }
+
+    /// 序列化为载荷
+    ///
+    /// 载荷格式（little-endian）:
+    /// - 1字节: 品种代码长度 + N字节品种代码
+    /// - 8字节: 成交ID
+    /// - 8字节: 成交时间戳
+    /// - 8字节: 成交价
+    /// - 4字节: 成交数量
+    /// - 1字节: 主动方（'B'/'S'）
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(1 + self.symbol.len() + 29);
+        put_symbol(&mut buf, &self.symbol);
+        buf.extend_from_slice(&self.trade_id.to_le_bytes());
+        buf.extend_from_slice(&self.timestamp_ns.to_le_bytes());
+        buf.extend_from_slice(&self.price.get().to_le_bytes());
+        buf.extend_from_slice(&self.quantity.get().to_le_bytes());
+        buf.push(self.aggressor_side as u8);
+        buf
+    }
+
+    /// 从载荷反序列化
+    pub fn decode(data: &[u8]) -> Result<Self, MulticastError> {
+        let mut reader = Reader { data, pos: 0 };
+        Ok(Self {
+            symbol: reader.symbol()?,
+            trade_id: u64::from_le_bytes(reader.take()?),
+            timestamp_ns: u64::from_le_bytes(reader.take()?),
+            price: Price::new(u64::from_le_bytes(reader.take()?)).ok_or_else(|| invalid("price"))?,
+            quantity: Quantity::new(u32::from_le_bytes(reader.take()?)).ok_or_else(|| invalid("quantity"))?,
+            aggressor_side: match reader.take::<1>()?[0] {
+                b'B' => Side::Buy,
+                b'S' => Side::Sell,
+                _ => return Err(invalid("side")),
+            },
+        })
+    }
+}
+
+impl BboUpdate {
+    /// 序列化为载荷
+    ///
+    /// 载荷格式（little-endian）:
+    /// - 1字节: 品种代码长度 + N字节品种代码
+    /// - 1字节: 存在标志 (bit0=买价, bit1=卖价)
+    /// - 买、卖各16字节: 价格8 + 数量4 + 订单数4（不存在时为0）
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(1 + self.symbol.len() + 33);
+        put_symbol(&mut buf, &self.symbol);
+        buf.push(self.best_bid.is_some() as u8 | (self.best_ask.is_some() as u8) << 1);
+        for level in [self.best_bid, self.best_ask] {
+            let (price, quantity, orders) = level.map_or((0, 0, 0), |l| (l.price.get(), l.quantity.get(), l.order_count));
+            buf.extend_from_slice(&price.to_le_bytes());
+            buf.extend_from_slice(&quantity.to_le_bytes());
+            buf.extend_from_slice(&orders.to_le_bytes());
+        }
+        buf
+    }
+
+    /// 从载荷反序列化
+    pub fn decode(data: &[u8]) -> Result<Self, MulticastError> {
+        let mut reader = Reader { data, pos: 0 };
+        let symbol = reader.symbol()?;
+        let presence = reader.take::<1>()?[0];
+        let mut levels = [None, None];
+        for (index, level) in levels.iter_mut().enumerate() {
+            let price = u64::from_le_bytes(reader.take()?);
+            let quantity = u32::from_le_bytes(reader.take()?);
+            let order_count = u32::from_le_bytes(reader.take()?);
+            if presence & (1 << index) != 0 {
+                *level = Some(DepthLevel {
+                    price: Price::new(price).ok_or_else(|| invalid("price"))?,
+                    quantity: Quantity::new(quantity).ok_or_else(|| invalid("quantity"))?,
+                    order_count,
+                });
+            }
+        }
+        Ok(Self {
+            symbol,
+            best_bid: levels[0],
+            best_ask: levels[1],
+        })
+    }
+}
+
+impl FeedUpdate {
+    /// 品种代码
+    pub fn symbol(&self) -> &str {
+        match self {
+            FeedUpdate::Trade(update) => &update.symbol,
+            FeedUpdate::Bbo(update) => &update.symbol,
+        }
+    }
+
+    /// 组播消息类型和载荷
+    pub fn to_payload(&self) -> (MessageType, Vec<u8>) {
+        match self {
+            FeedUpdate::Trade(update) => (MessageType::Trade, update.encode()),
+            FeedUpdate::Bbo(update) => (MessageType::Ticker, update.encode()),
+        }
+    }
+}
+
+/// 引擎侧的行情桥接句柄
+///
+/// 每个品种的订单簿通过`trade_sink`注册成交输出；每条指令执行后调用`on_book`检查最优价。
+pub struct EngineFeed {
+    tx: UnboundedSender<FeedUpdate>,
+    last_bbo: HashMap<String, (Option<DepthLevel>, Option<DepthLevel>)>,
+}
+
+impl EngineFeed {
+    /// 创建桥接句柄和更新通道（接收端交给发布任务）
+    pub fn new() -> (Self, UnboundedReceiver<FeedUpdate>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let feed = Self {
+            tx,
+            last_bbo: HashMap::new(),
+        };
+        (feed, rx)
+    }
+
+    /// 品种的成交输出（设置为订单簿的`TradeSink`）
+    pub fn trade_sink(&self, symbol: &str) -> Box<dyn TradeSink> {
+        Box::new(FeedTradeSink {
+            symbol: symbol.to_string(),
+            tx: self.tx.clone(),
+        })
+    }
+
+    /// 检查品种最优价，有变化时发布，返回是否发布
+    pub fn on_book(&mut self, symbol: &str, book: &OrderBook) -> bool {
+        let depth = book.depth(1);
+        let bbo = (depth.bids.first().copied(), depth.asks.first().copied());
+        if self.last_bbo.get(symbol) == Some(&bbo) {
+            return false;
+        }
+        self.last_bbo.insert(symbol.to_string(), bbo);
+        let update = BboUpdate {
+            symbol: symbol.to_string(),
+            best_bid: bbo.0,
+            best_ask: bbo.1,
+        };
+        self.tx.send(FeedUpdate::Bbo(update)).is_ok()
+    }
+}
+
+struct FeedTradeSink {
+    symbol: String,
+    tx: UnboundedSender<FeedUpdate>,
+}
+
+impl TradeSink for FeedTradeSink {
+    fn on_trade(&mut self, trade: &Trade) {
+        let _ = self.tx.send(FeedUpdate::Trade(TradeUpdate::new(&self.symbol, trade)));
+    }
+}
+
+fn put_symbol(buf: &mut Vec<u8>, symbol: &str) {
+    let symbol = &symbol.as_bytes()[..symbol.floor_char_boundary(u8::MAX as usize)];
+    buf.push(symbol.len() as u8);
+    buf.extend_from_slice(symbol);
+}
+
+fn invalid(field: &str) -> MulticastError {
+    MulticastError::Deserialization(format!("Invalid {} in feed payload", field))
+}
+
+/// 载荷读取辅助
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn slice(&mut self, len: usize) -> Result<&'a [u8], MulticastError> {
+        if self.pos + len > self.data.len() {
+            return Err(MulticastError::Deserialization("Incomplete feed payload".to_string()));
+        }
+        let slice = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], MulticastError> {
+        Ok(self.slice(N)?.try_into().unwrap())
+    }
+
+    fn symbol(&mut self) -> Result<String, MulticastError> {
+        let len = self.take::<1>()?[0] as usize;
+        std::str::from_utf8(self.slice(len)?)
+            .map(str::to_string)
+            .map_err(|_| invalid("symbol"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty};
+    use crate::orderbook::{TimeInForce, TraderId};
+
+    #[test]
+    fn test_trades_and_bbo_changes() {
+        let (mut feed, mut rx) = EngineFeed::new();
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        book.set_trade_sink(feed.trade_sink("BTCUSDT"));
+        let mm = TraderId::from_str("MM");
+
+        book.limit_order(mm, Side::Sell, px(101), qty(5), TimeInForce::Gtc).unwrap();
+        assert!(feed.on_book("BTCUSDT", &book));
+        // 最优价不变时不重复发布
+        book.limit_order(mm, Side::Sell, px(105), qty(5), TimeInForce::Gtc).unwrap();
+        assert!(!feed.on_book("BTCUSDT", &book));
+
+        book.limit_order(TraderId::from_str("TK"), Side::Buy, px(101), qty(2), TimeInForce::Ioc).unwrap();
+        assert!(feed.on_book("BTCUSDT", &book));
+
+        let mut updates = Vec::new();
+        while let Ok(update) = rx.try_recv() {
+            updates.push(update);
+        }
+        assert_eq!(updates.len(), 3);
+        let FeedUpdate::Trade(trade) = &updates[1] else {
+            panic!("expected trade");
+        };
+        assert_eq!((trade.price, trade.quantity, trade.aggressor_side), (px(101), qty(2), Side::Buy));
+        let FeedUpdate::Bbo(bbo) = &updates[2] else {
+            panic!("expected bbo");
+        };
+        assert_eq!(bbo.best_bid, None);
+        assert_eq!(bbo.best_ask.map(|level| level.quantity), Some(qty(3)));
+
+        for update in &updates {
+            let (msg_type, payload) = update.to_payload();
+            let decoded = match msg_type {
+                MessageType::Trade => FeedUpdate::Trade(TradeUpdate::decode(&payload).unwrap()),
+                _ => FeedUpdate::Bbo(BboUpdate::decode(&payload).unwrap()),
+            };
+            assert_eq!(&decoded, update);
+        }
+        assert!(TradeUpdate::decode(&[7, b'B']).is_err());
+    }
+}
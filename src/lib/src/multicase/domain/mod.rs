@@ -1 +1,7 @@
+pub mod capture;
+pub mod control;
+pub mod dedup;
+pub mod engine_feed;
 pub mod multicast;
+pub mod session;
+pub mod stats;
\ No newline at end of file
@@ -8,6 +8,7 @@
 
 use std::net::IpAddr;
 use async_trait::async_trait;
+use serde::Serialize;
 use thiserror::Error;
 
 /// 组播消息
@@ -34,6 +35,10 @@ pub enum MessageType {
     Trade = 3,
     /// 心跳
     Heartbeat = 4,
+    /// 按品种统计
+    Stats = 5,
+    /// 发送端重启标记
+    Restart = 6,
 }
 
 impl MessageType {
@@ -43,6 +48,8 @@ impl MessageType {
             2 => Some(Self::OrderBook),
             3 => Some(Self::Trade),
             4 => Some(Self::Heartbeat),
+            5 => Some(Self::Stats),
+            6 => Some(Self::Restart),
             _ => None,
         }
     }
@@ -105,7 +112,7 @@ pub trait MulticastSubscriber: Send + Sync {
 }
 
 /// 发送统计
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct PublisherStats {
     /// 发送的消息数
     pub messages_sent: u64,
@@ -116,7 +123,7 @@ pub struct PublisherStats {
 }
 
 /// 接收统计
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct SubscriberStats {
     /// 接收的消息数
     pub messages_received: u64,
@@ -126,6 +133,12 @@ pub struct SubscriberStats {
     pub packets_lost: u64,
     /// 解析错误数
     pub parse_errors: u64,
+    /// 被去重窗口丢弃的重复消息数
+    pub duplicates: u64,
+    /// 超过时效的消息数（含已丢弃）
+    pub stale_messages: u64,
+    /// 因过期而丢弃的消息数
+    pub stale_dropped: u64,
 }
 
 /// 组播错误
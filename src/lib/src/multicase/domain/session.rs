@@ -0,0 +1,259 @@
+/// 行情桥接会话状态
+///
+/// 持久化桥接进程的活跃订阅集合与序列号，重启后：
+/// - 恢复相同的订阅通道
+/// - 序列号从上次预留的位置继续，而不是归零（避免破坏订阅端的缺口检测）
+/// - 发布一条Restart标记消息，订阅端据此重置缺口检测
+///
+/// 序列号按块预留：每用完一块才写一次状态，崩溃时未用完的部分被跳过，
+/// 保证重启后序列号严格递增且不重复。
+
+use std::collections::{BTreeMap, BTreeSet};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use super::multicast::MulticastError;
+
+/// 默认序列号预留块大小
+pub const DEFAULT_SEQUENCE_BLOCK: u64 = 10_000;
+
+/// 持久化的桥接状态
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BridgeState {
+    /// 活跃订阅的品种
+    pub subscriptions: BTreeSet<String>,
+    /// 各品种最后发布的序列号
+    pub symbol_sequences: BTreeMap<String, u64>,
+    /// 已预留的序列号上界（不含），重启后从这里继续
+    pub sequence_reserved: u64,
+    /// 重启次数
+    pub restarts: u32,
+}
+
+/// 桥接状态存储
+pub trait BridgeStateStore: Send + Sync {
+    /// 读取状态（首次启动时返回None）
+    fn load(&self) -> Result<Option<BridgeState>, MulticastError>;
+
+    /// 写入状态
+    fn save(&self, state: &BridgeState) -> Result<(), MulticastError>;
+}
+
+/// Restart标记消息载荷
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartMarker {
+    /// 重启次数
+    pub restart_count: u32,
+    /// 重启后的第一个序列号
+    pub resumed_sequence: u64,
+    /// 恢复的订阅数
+    pub subscriptions: u32,
+}
+
+impl RestartMarker {
+    /// 载荷长度
+    pub const ENCODED_LEN: usize = 16;
+
+    /// 序列化为载荷
+    ///
+    /// 载荷格式:
+    /// - 4字节: 重启次数 (little-endian u32)
+    /// - 8字节: 重启后的第一个序列号 (little-endian u64)
+    /// - 4字节: 恢复的订阅数 (little-endian u32)
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::ENCODED_LEN);
+        buf.extend_from_slice(&self.restart_count.to_le_bytes());
+        buf.extend_from_slice(&self.resumed_sequence.to_le_bytes());
+        buf.extend_from_slice(&self.subscriptions.to_le_bytes());
+        buf
+    }
+
+    /// 从载荷反序列化
+    pub fn decode(data: &[u8]) -> Result<Self, MulticastError> {
+        if data.len() < Self::ENCODED_LEN {
+            return Err(MulticastError::Deserialization(
+                "Incomplete restart marker".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            restart_count: u32::from_le_bytes(data[0..4].try_into().unwrap()),
+            resumed_sequence: u64::from_le_bytes(data[4..12].try_into().unwrap()),
+            subscriptions: u32::from_le_bytes(data[12..16].try_into().unwrap()),
+        })
+    }
+}
+
+struct SessionInner {
+    state: BridgeState,
+    next_sequence: u64,
+}
+
+/// 桥接会话（订阅集合与序列号分配）
+pub struct BridgeSession<S: BridgeStateStore> {
+    store: S,
+    inner: Mutex<SessionInner>,
+    block: u64,
+    resumed: bool,
+}
+
+impl<S: BridgeStateStore> BridgeSession<S> {
+    /// 打开会话：读取上次状态，重启时计数并从已预留位置继续
+    pub fn open(store: S, block: u64) -> Result<Self, MulticastError> {
+        let block = block.max(1);
+        let (mut state, resumed) = match store.load()? {
+            Some(mut state) => {
+                state.restarts += 1;
+                (state, true)
+            }
+            None => (BridgeState::default(), false),
+        };
+
+        let next_sequence = state.sequence_reserved;
+        state.sequence_reserved = next_sequence + block;
+        store.save(&state)?;
+
+        Ok(Self {
+            store,
+            inner: Mutex::new(SessionInner {
+                state,
+                next_sequence,
+            }),
+            block,
+            resumed,
+        })
+    }
+
+    /// 是否从已有状态恢复
+    #[inline]
+    pub fn is_resumed(&self) -> bool {
+        self.resumed
+    }
+
+    /// 重启标记（首次启动时为None）
+    pub fn restart_marker(&self) -> Option<RestartMarker> {
+        if !self.resumed {
+            return None;
+        }
+        let inner = self.inner.lock();
+        Some(RestartMarker {
+            restart_count: inner.state.restarts,
+            resumed_sequence: inner.next_sequence,
+            subscriptions: inner.state.subscriptions.len() as u32,
+        })
+    }
+
+    /// 需要恢复的订阅
+    pub fn subscriptions(&self) -> Vec<String> {
+        self.inner.lock().state.subscriptions.iter().cloned().collect()
+    }
+
+    /// 品种最后发布的序列号
+    pub fn last_sequence(&self, symbol: &str) -> Option<u64> {
+        self.inner.lock().state.symbol_sequences.get(symbol).copied()
+    }
+
+    /// 记录新订阅（已存在时不写存储）
+    pub fn add_subscription(&self, symbol: &str) -> Result<(), MulticastError> {
+        let mut inner = self.inner.lock();
+        if inner.state.subscriptions.insert(symbol.to_string()) {
+            self.store.save(&inner.state)?;
+        }
+        Ok(())
+    }
+
+    /// 移除订阅
+    pub fn remove_subscription(&self, symbol: &str) -> Result<(), MulticastError> {
+        let mut inner = self.inner.lock();
+        if inner.state.subscriptions.remove(symbol) {
+            inner.state.symbol_sequences.remove(symbol);
+            self.store.save(&inner.state)?;
+        }
+        Ok(())
+    }
+
+    /// 分配下一个序列号（`symbol`为None时用于非品种消息）
+    ///
+    /// 当前预留块用完时先写入新的预留上界。
+    pub fn next_sequence(&self, symbol: Option<&str>) -> Result<u64, MulticastError> {
+        let mut inner = self.inner.lock();
+        let sequence = inner.next_sequence;
+
+        if sequence >= inner.state.sequence_reserved {
+            inner.state.sequence_reserved = sequence + self.block;
+            self.store.save(&inner.state)?;
+        }
+
+        inner.next_sequence += 1;
+        if let Some(symbol) = symbol {
+            inner.state.symbol_sequences.insert(symbol.to_string(), sequence);
+        }
+        Ok(sequence)
+    }
+
+    /// 写入当前状态（例如定期或退出前调用）
+    pub fn flush(&self) -> Result<(), MulticastError> {
+        let inner = self.inner.lock();
+        self.store.save(&inner.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct MemoryStore(Arc<Mutex<Option<BridgeState>>>);
+
+    impl BridgeStateStore for MemoryStore {
+        fn load(&self) -> Result<Option<BridgeState>, MulticastError> {
+            Ok(self.0.lock().clone())
+        }
+
+        fn save(&self, state: &BridgeState) -> Result<(), MulticastError> {
+            *self.0.lock() = Some(state.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_resume_continues_sequence_and_subscriptions() {
+        let store = MemoryStore::default();
+
+        let session = BridgeSession::open(store.clone(), 4).unwrap();
+        assert!(!session.is_resumed());
+        assert_eq!(session.restart_marker(), None);
+        session.add_subscription("BTCUSDT").unwrap();
+        session.add_subscription("ETHUSDT").unwrap();
+        for expected in 0..6 {
+            assert_eq!(session.next_sequence(Some("BTCUSDT")).unwrap(), expected);
+        }
+        // 模拟崩溃：不调用flush
+        drop(session);
+
+        let session = BridgeSession::open(store.clone(), 4).unwrap();
+        assert!(session.is_resumed());
+        assert_eq!(session.subscriptions(), vec!["BTCUSDT", "ETHUSDT"]);
+
+        // 序列号跳到已预留块之后，不会与崩溃前已发布的重复
+        let marker = session.restart_marker().unwrap();
+        assert_eq!(marker.restart_count, 1);
+        assert_eq!(marker.resumed_sequence, 8);
+        assert_eq!(marker.subscriptions, 2);
+        assert_eq!(session.next_sequence(None).unwrap(), 8);
+
+        session.remove_subscription("ETHUSDT").unwrap();
+        assert_eq!(store.load().unwrap().unwrap().subscriptions.len(), 1);
+    }
+
+    #[test]
+    fn test_restart_marker_roundtrip() {
+        let marker = RestartMarker {
+            restart_count: 3,
+            resumed_sequence: 1_000_000,
+            subscriptions: 12,
+        };
+        assert_eq!(RestartMarker::decode(&marker.encode()).unwrap(), marker);
+        assert!(RestartMarker::decode(&marker.encode()[..8]).is_err());
+    }
+}
@@ -0,0 +1,368 @@
+/// 组播行情按品种统计
+///
+/// 发送端按固定间隔在Stats通道上发布每个品种的统计信息，
+/// 订阅端无需自行维护定时器即可发现单个品种的静默中断。
+/// 统计内容:
+/// - 消息计数
+/// - 最后序列号
+/// - 最佳买卖价
+/// - 行情健康标志
+
+use std::collections::HashMap;
+use std::time::Duration;
+use parking_lot::Mutex;
+use super::multicast::MulticastError;
+
+/// 行情健康标志：超过阈值时间未收到更新
+pub const HEALTH_STALE: u8 = 0x01;
+/// 行情健康标志：检测到序列号缺口
+pub const HEALTH_GAP: u8 = 0x02;
+/// 行情健康标志：买卖价交叉（买价 >= 卖价）
+pub const HEALTH_CROSSED: u8 = 0x04;
+
+/// 统计通道配置
+#[derive(Debug, Clone)]
+pub struct StatsConfig {
+    /// 发布间隔
+    pub interval: Duration,
+    /// 超过该时间未更新则标记为STALE
+    pub stale_after: Duration,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            stale_after: Duration::from_secs(5),
+        }
+    }
+}
+
+/// 单个品种的统计信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolStats {
+    /// 品种代码
+    pub symbol: String,
+    /// 累计消息数
+    pub message_count: u64,
+    /// 最后序列号
+    pub last_sequence: u64,
+    /// 最后更新时间戳（纳秒）
+    pub last_update_ns: u64,
+    /// 最佳买价
+    pub best_bid: Option<f64>,
+    /// 最佳卖价
+    pub best_ask: Option<f64>,
+    /// 健康标志位（HEALTH_*）
+    pub health: u8,
+}
+
+impl SymbolStats {
+    /// 是否健康（无任何标志位）
+    #[inline]
+    pub fn is_healthy(&self) -> bool {
+        self.health == 0
+    }
+
+    /// 是否超时未更新
+    #[inline]
+    pub fn is_stale(&self) -> bool {
+        self.health & HEALTH_STALE != 0
+    }
+}
+
+/// Stats通道消息（一次发布包含所有品种）
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StatsMessage {
+    /// 各品种统计
+    pub symbols: Vec<SymbolStats>,
+}
+
+impl StatsMessage {
+    /// 序列化为载荷
+    ///
+    /// 载荷格式:
+    /// - 2字节: 品种数量 (little-endian u16)
+    /// - 每个品种:
+    ///   - 1字节: 品种代码长度 + N字节品种代码
+    ///   - 8字节: 消息计数
+    ///   - 8字节: 最后序列号
+    ///   - 8字节: 最后更新时间戳
+    ///   - 1字节: 买卖价存在标志 (bit0=买价, bit1=卖价)
+    ///   - 8字节: 买价 (f64)
+    ///   - 8字节: 卖价 (f64)
+    ///   - 1字节: 健康标志
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(2 + self.symbols.len() * 48);
+        buf.extend_from_slice(&(self.symbols.len() as u16).to_le_bytes());
+
+        for stats in &self.symbols {
+            // 超长品种代码在字符边界截断，保证解码仍是合法UTF-8
+            let symbol = &stats.symbol.as_bytes()[..stats.symbol.floor_char_boundary(u8::MAX as usize)];
+            buf.push(symbol.len() as u8);
+            buf.extend_from_slice(symbol);
+            buf.extend_from_slice(&stats.message_count.to_le_bytes());
+            buf.extend_from_slice(&stats.last_sequence.to_le_bytes());
+            buf.extend_from_slice(&stats.last_update_ns.to_le_bytes());
+
+            let presence = stats.best_bid.is_some() as u8 | (stats.best_ask.is_some() as u8) << 1;
+            buf.push(presence);
+            buf.extend_from_slice(&stats.best_bid.unwrap_or(0.0).to_le_bytes());
+            buf.extend_from_slice(&stats.best_ask.unwrap_or(0.0).to_le_bytes());
+            buf.push(stats.health);
+        }
+
+        buf
+    }
+
+    /// 从载荷反序列化
+    pub fn decode(data: &[u8]) -> Result<Self, MulticastError> {
+        let mut reader = Reader { data, pos: 0 };
+        let count = u16::from_le_bytes(reader.take::<2>()?) as usize;
+        let mut symbols = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let len = reader.take::<1>()?[0] as usize;
+            let symbol = std::str::from_utf8(reader.slice(len)?)
+                .map_err(|_| MulticastError::Deserialization("Invalid symbol".to_string()))?
+                .to_string();
+            let message_count = u64::from_le_bytes(reader.take::<8>()?);
+            let last_sequence = u64::from_le_bytes(reader.take::<8>()?);
+            let last_update_ns = u64::from_le_bytes(reader.take::<8>()?);
+            let presence = reader.take::<1>()?[0];
+            let bid = f64::from_le_bytes(reader.take::<8>()?);
+            let ask = f64::from_le_bytes(reader.take::<8>()?);
+            let health = reader.take::<1>()?[0];
+
+            symbols.push(SymbolStats {
+                symbol,
+                message_count,
+                last_sequence,
+                last_update_ns,
+                best_bid: (presence & 0x01 != 0).then_some(bid),
+                best_ask: (presence & 0x02 != 0).then_some(ask),
+                health,
+            });
+        }
+
+        Ok(Self { symbols })
+    }
+
+    /// 查找指定品种
+    pub fn get(&self, symbol: &str) -> Option<&SymbolStats> {
+        self.symbols.iter().find(|s| s.symbol == symbol)
+    }
+}
+
+/// 载荷读取辅助
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn slice(&mut self, len: usize) -> Result<&'a [u8], MulticastError> {
+        if self.pos + len > self.data.len() {
+            return Err(MulticastError::Deserialization(
+                "Incomplete stats payload".to_string(),
+            ));
+        }
+        let slice = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], MulticastError> {
+        Ok(self.slice(N)?.try_into().unwrap())
+    }
+}
+
+/// 按品种统计追踪器
+///
+/// 发送端在每条消息发布成功后以全行情（feed-wide）序列号调用`record`或`record_sequence`，
+/// 统计任务定期调用`snapshot`生成Stats消息。序列号在整个组播行情上连续，
+/// 出现缺口时无法判断丢失的是哪个品种的消息，因此所有品种都标记GAP。
+#[derive(Default)]
+pub struct FeedStatsTracker {
+    state: Mutex<TrackerState>,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    symbols: HashMap<String, SymbolStats>,
+    /// 最后记录的全行情序列号
+    last_sequence: Option<u64>,
+}
+
+impl TrackerState {
+    /// 推进全行情序列号，出现缺口时标记所有品种
+    fn advance(&mut self, sequence: u64) {
+        if self.last_sequence.is_some_and(|last| sequence > last + 1) {
+            for stats in self.symbols.values_mut() {
+                stats.health |= HEALTH_GAP;
+            }
+        }
+        self.last_sequence = Some(self.last_sequence.map_or(sequence, |last| last.max(sequence)));
+    }
+}
+
+impl FeedStatsTracker {
+    /// 创建新的统计追踪器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一条品种消息
+    pub fn record(&self, symbol: &str, sequence: u64, timestamp_ns: u64) {
+        let mut state = self.state.lock();
+        if !state.symbols.contains_key(symbol) {
+            state.symbols.insert(
+                symbol.to_string(),
+                SymbolStats {
+                    symbol: symbol.to_string(),
+                    message_count: 0,
+                    last_sequence: 0,
+                    last_update_ns: 0,
+                    best_bid: None,
+                    best_ask: None,
+                    health: 0,
+                },
+            );
+        }
+        state.advance(sequence);
+
+        let stats = state.symbols.get_mut(symbol).unwrap();
+        stats.message_count += 1;
+        stats.last_sequence = sequence;
+        stats.last_update_ns = timestamp_ns;
+    }
+
+    /// 记录一条非品种消息（Stats、Restart等）占用的序列号
+    pub fn record_sequence(&self, sequence: u64) {
+        self.state.lock().advance(sequence);
+    }
+
+    /// 更新品种最佳买卖价
+    pub fn update_bbo(&self, symbol: &str, best_bid: Option<f64>, best_ask: Option<f64>) {
+        let mut state = self.state.lock();
+        if let Some(stats) = state.symbols.get_mut(symbol) {
+            stats.best_bid = best_bid;
+            stats.best_ask = best_ask;
+        }
+    }
+
+    /// 生成当前统计快照
+    ///
+    /// STALE/CROSSED标志在快照时计算，GAP标志发布后清除。
+    pub fn snapshot(&self, now_ns: u64, stale_after: Duration) -> StatsMessage {
+        let stale_ns = stale_after.as_nanos() as u64;
+        let mut state = self.state.lock();
+
+        let mut entries: Vec<SymbolStats> = state
+            .symbols
+            .values_mut()
+            .map(|stats| {
+                let mut snapshot = stats.clone();
+                if now_ns.saturating_sub(stats.last_update_ns) > stale_ns {
+                    snapshot.health |= HEALTH_STALE;
+                }
+                if matches!((stats.best_bid, stats.best_ask), (Some(bid), Some(ask)) if bid >= ask) {
+                    snapshot.health |= HEALTH_CROSSED;
+                }
+                stats.health &= !HEALTH_GAP;
+                snapshot
+            })
+            .collect();
+
+        entries.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        StatsMessage { symbols: entries }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_roundtrip() {
+        let message = StatsMessage {
+            symbols: vec![
+                SymbolStats {
+                    symbol: "BTCUSDT".to_string(),
+                    message_count: 42,
+                    last_sequence: 1000,
+                    last_update_ns: 123456789,
+                    best_bid: Some(95000.5),
+                    best_ask: Some(95001.0),
+                    health: 0,
+                },
+                SymbolStats {
+                    symbol: "ETHUSDT".to_string(),
+                    message_count: 1,
+                    last_sequence: 7,
+                    last_update_ns: 1,
+                    best_bid: None,
+                    best_ask: None,
+                    health: HEALTH_STALE,
+                },
+            ],
+        };
+
+        let decoded = StatsMessage::decode(&message.encode()).unwrap();
+        assert_eq!(decoded, message);
+        assert!(StatsMessage::decode(&message.encode()[..10]).is_err());
+    }
+
+    #[test]
+    fn test_tracker_health_flags() {
+        let tracker = FeedStatsTracker::new();
+        tracker.record("BTCUSDT", 1, 1_000);
+        tracker.update_bbo("BTCUSDT", Some(100.0), Some(99.0));
+        tracker.record("ETHUSDT", 2, 10_000);
+
+        let stats = tracker.snapshot(10_500, Duration::from_nanos(1_000));
+        let btc = stats.get("BTCUSDT").unwrap();
+        assert_eq!((btc.message_count, btc.last_sequence), (1, 1));
+        assert_eq!(btc.health, HEALTH_STALE | HEALTH_CROSSED);
+        assert!(stats.get("ETHUSDT").unwrap().is_healthy());
+
+        // 全行情序列号缺口：所有品种标记GAP，发布后清除
+        tracker.record("ETHUSDT", 4, 10_000);
+        let stats = tracker.snapshot(10_500, Duration::from_nanos(1_000));
+        assert!(stats.symbols.iter().all(|s| s.health & HEALTH_GAP != 0));
+        let stats = tracker.snapshot(10_500, Duration::from_nanos(1_000));
+        assert_eq!(stats.get("ETHUSDT").unwrap().health, 0);
+    }
+
+    #[test]
+    fn test_interleaved_symbols_are_not_gaps() {
+        let tracker = FeedStatsTracker::new();
+        for (sequence, symbol) in ["BTCUSDT", "ETHUSDT", "BTCUSDT", "SOLUSDT", "ETHUSDT"].into_iter().enumerate() {
+            tracker.record(symbol, sequence as u64, 1_000);
+        }
+        tracker.record_sequence(5);
+        tracker.record("BTCUSDT", 6, 1_000);
+
+        let stats = tracker.snapshot(1_000, Duration::from_secs(1));
+        assert!(stats.symbols.iter().all(SymbolStats::is_healthy), "{:?}", stats);
+        assert_eq!(stats.get("BTCUSDT").map(|s| (s.message_count, s.last_sequence)), Some((3, 6)));
+    }
+
+    #[test]
+    fn test_long_symbol_truncated_on_char_boundary() {
+        let symbol = "币".repeat(100);
+        let message = StatsMessage {
+            symbols: vec![SymbolStats {
+                symbol: symbol.clone(),
+                message_count: 1,
+                last_sequence: 1,
+                last_update_ns: 1,
+                best_bid: None,
+                best_ask: None,
+                health: 0,
+            }],
+        };
+        let decoded = StatsMessage::decode(&message.encode()).unwrap();
+        assert_eq!(decoded.symbols[0].symbol, "币".repeat(85));
+    }
+}
@@ -0,0 +1,415 @@
+/// 传输故障注入（测试用）
+///
+/// 在真实传输外包一层，按配置注入丢包、重复、乱序和延迟抖动，
+/// 用固定随机种子保证每次运行产生相同的故障序列，便于确定性地验证
+/// 缺口检测、重传和仲裁逻辑:
+/// - `ChaosPublisher`: 包装组播发送端
+/// - `ChaosSubscriber`: 包装组播接收端（不注入延迟）
+/// - `ChaosInjector`: 与传输无关的故障决策，单播包装复用
+
+use crate::multicase::domain::multicast::{
+    MulticastError, MulticastMessage, MulticastPublisher, MulticastSubscriber, PublisherStats, SubscriberStats,
+};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 故障注入配置
+///
+/// 各概率取值 [0, 1]，默认全部为0（直通）
+#[derive(Debug, Clone)]
+pub struct ChaosConfig {
+    /// 丢弃概率
+    pub drop_rate: f64,
+    /// 重复投递概率
+    pub duplicate_rate: f64,
+    /// 扣留（延后到后续消息之后投递）概率
+    pub reorder_rate: f64,
+    /// 最多同时扣留的消息数
+    pub reorder_depth: usize,
+    /// 每次发送前的最大随机延迟（均匀分布于 [0, jitter]）
+    pub jitter: Duration,
+    /// 随机种子，相同种子产生相同故障序列
+    pub seed: u64,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            drop_rate: 0.0,
+            duplicate_rate: 0.0,
+            reorder_rate: 0.0,
+            reorder_depth: 4,
+            jitter: Duration::ZERO,
+            seed: 0,
+        }
+    }
+}
+
+/// 故障注入统计
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChaosStats {
+    /// 进入注入器的消息数
+    pub messages_in: u64,
+    /// 实际投递的消息数（含重复）
+    pub messages_out: u64,
+    /// 丢弃数
+    pub dropped: u64,
+    /// 重复数
+    pub duplicated: u64,
+    /// 扣留后乱序投递数
+    pub reordered: u64,
+}
+
+/// 故障决策器
+///
+/// 每条消息经`apply`后返回应立即投递的消息序列（可能为空、重复或带出此前扣留的消息）
+pub struct ChaosInjector<T> {
+    config: ChaosConfig,
+    rng: u64,
+    held: VecDeque<T>,
+    stats: ChaosStats,
+}
+
+impl<T: Clone> ChaosInjector<T> {
+    pub fn new(config: ChaosConfig) -> Self {
+        Self {
+            rng: config.seed,
+            config,
+            held: VecDeque::new(),
+            stats: ChaosStats::default(),
+        }
+    }
+
+    /// 处理一条消息，返回需要立即投递的消息
+    pub fn apply(&mut self, item: T) -> Vec<T> {
+        self.stats.messages_in += 1;
+
+        if self.roll(self.config.drop_rate) {
+            self.stats.dropped += 1;
+            return Vec::new();
+        }
+
+        if self.held.len() < self.config.reorder_depth && self.roll(self.config.reorder_rate) {
+            self.held.push_back(item);
+            return Vec::new();
+        }
+
+        let mut out = Vec::with_capacity(2 + self.held.len());
+        if self.roll(self.config.duplicate_rate) {
+            self.stats.duplicated += 1;
+            out.push(item.clone());
+        }
+        out.push(item);
+
+        // 扣留的消息跟在较新的消息之后投递
+        self.stats.reordered += self.held.len() as u64;
+        out.extend(self.held.drain(..));
+
+        self.stats.messages_out += out.len() as u64;
+        out
+    }
+
+    /// 取出所有扣留的消息（按进入顺序）
+    pub fn flush(&mut self) -> Vec<T> {
+        let out: Vec<T> = self.held.drain(..).collect();
+        self.stats.messages_out += out.len() as u64;
+        out
+    }
+
+    /// 下一次发送的随机延迟
+    pub fn jitter(&mut self) -> Duration {
+        let max = self.config.jitter.as_nanos() as u64;
+        if max == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos(self.next_u64() % (max + 1))
+    }
+
+    /// 当前扣留的消息数
+    pub fn held(&self) -> usize {
+        self.held.len()
+    }
+
+    pub fn stats(&self) -> ChaosStats {
+        self.stats.clone()
+    }
+
+    #[inline]
+    fn roll(&mut self, rate: f64) -> bool {
+        if rate <= 0.0 {
+            return false;
+        }
+        // 取高53位映射到 [0, 1)
+        let sample = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        sample < rate
+    }
+
+    /// splitmix64
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// 发送端待投递数据
+#[derive(Clone)]
+enum Outgoing {
+    Message(MulticastMessage),
+    Raw(Vec<u8>),
+}
+
+/// 注入故障的组播发送端
+pub struct ChaosPublisher<P> {
+    inner: P,
+    injector: Mutex<ChaosInjector<Outgoing>>,
+}
+
+impl<P: MulticastPublisher> ChaosPublisher<P> {
+    pub fn new(inner: P, config: ChaosConfig) -> Self {
+        Self {
+            inner,
+            injector: Mutex::new(ChaosInjector::new(config)),
+        }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// 发送所有扣留中的消息
+    pub async fn flush(&self) -> Result<(), MulticastError> {
+        let held = self.injector.lock().flush();
+        self.deliver(held).await
+    }
+
+    pub fn chaos_stats(&self) -> ChaosStats {
+        self.injector.lock().stats()
+    }
+
+    async fn send(&self, item: Outgoing) -> Result<(), MulticastError> {
+        let (out, delay) = {
+            let mut injector = self.injector.lock();
+            (injector.apply(item), injector.jitter())
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        self.deliver(out).await
+    }
+
+    async fn deliver(&self, items: Vec<Outgoing>) -> Result<(), MulticastError> {
+        for item in items {
+            match item {
+                Outgoing::Message(message) => self.inner.publish(&message).await?,
+                Outgoing::Raw(data) => self.inner.publish_raw(&data).await?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<P: MulticastPublisher> MulticastPublisher for ChaosPublisher<P> {
+    async fn publish(&self, message: &MulticastMessage) -> Result<(), MulticastError> {
+        self.send(Outgoing::Message(message.clone())).await
+    }
+
+    async fn publish_raw(&self, data: &[u8]) -> Result<(), MulticastError> {
+        self.send(Outgoing::Raw(data.to_vec())).await
+    }
+
+    fn stats(&self) -> PublisherStats {
+        self.inner.stats()
+    }
+}
+
+/// 注入故障的组播接收端
+///
+/// 在回调前对收到的消息做丢弃/重复/乱序，不注入延迟
+pub struct ChaosSubscriber<S> {
+    inner: S,
+    injector: Arc<Mutex<ChaosInjector<MulticastMessage>>>,
+}
+
+impl<S: MulticastSubscriber> ChaosSubscriber<S> {
+    pub fn new(inner: S, config: ChaosConfig) -> Self {
+        Self {
+            inner,
+            injector: Arc::new(Mutex::new(ChaosInjector::new(config))),
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn chaos_stats(&self) -> ChaosStats {
+        self.injector.lock().stats()
+    }
+}
+
+#[async_trait]
+impl<S: MulticastSubscriber> MulticastSubscriber for ChaosSubscriber<S> {
+    async fn subscribe<F>(&self, callback: F) -> Result<(), MulticastError>
+    where
+        F: Fn(MulticastMessage) + Send + Sync + 'static,
+    {
+        let injector = Arc::clone(&self.injector);
+        self.inner
+            .subscribe(move |message| {
+                let out = injector.lock().apply(message);
+                for message in out {
+                    callback(message);
+                }
+            })
+            .await
+    }
+
+    fn stats(&self) -> SubscriberStats {
+        self.inner.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multicase::domain::multicast::MessageType;
+
+    fn message(sequence: u64) -> MulticastMessage {
+        MulticastMessage {
+            sequence,
+            timestamp_ns: 0,
+            msg_type: MessageType::Trade,
+            payload: Vec::new(),
+        }
+    }
+
+    /// 记录发送序列号的发送端
+    #[derive(Default)]
+    struct Recorder {
+        sent: Mutex<Vec<u64>>,
+    }
+
+    #[async_trait]
+    impl MulticastPublisher for Recorder {
+        async fn publish(&self, message: &MulticastMessage) -> Result<(), MulticastError> {
+            self.sent.lock().push(message.sequence);
+            Ok(())
+        }
+
+        async fn publish_raw(&self, data: &[u8]) -> Result<(), MulticastError> {
+            self.sent.lock().push(data.len() as u64);
+            Ok(())
+        }
+
+        fn stats(&self) -> PublisherStats {
+            PublisherStats::default()
+        }
+    }
+
+    fn run(config: ChaosConfig, count: u64) -> (Vec<u64>, ChaosStats) {
+        let mut injector = ChaosInjector::new(config);
+        let mut out: Vec<u64> = (1..=count).flat_map(|seq| injector.apply(seq)).collect();
+        out.extend(injector.flush());
+        (out, injector.stats())
+    }
+
+    #[test]
+    fn test_default_is_passthrough() {
+        let (out, stats) = run(ChaosConfig::default(), 100);
+        assert_eq!(out, (1..=100).collect::<Vec<_>>());
+        assert_eq!((stats.dropped, stats.duplicated, stats.reordered), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_same_seed_same_faults() {
+        let config = ChaosConfig {
+            drop_rate: 0.1,
+            duplicate_rate: 0.1,
+            reorder_rate: 0.2,
+            seed: 42,
+            ..Default::default()
+        };
+        let (a, stats) = run(config.clone(), 1_000);
+        let (b, _) = run(config.clone(), 1_000);
+        assert_eq!(a, b);
+        assert!(stats.dropped > 0 && stats.duplicated > 0 && stats.reordered > 0);
+        assert_eq!(stats.messages_out, a.len() as u64);
+        assert_eq!(stats.messages_in + stats.duplicated - stats.dropped, stats.messages_out);
+
+        let (c, _) = run(ChaosConfig { seed: 43, ..config }, 1_000);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_reorder_keeps_every_message() {
+        let config = ChaosConfig {
+            reorder_rate: 0.5,
+            reorder_depth: 3,
+            seed: 7,
+            ..Default::default()
+        };
+        let (out, stats) = run(config, 200);
+        assert_ne!(out, (1..=200).collect::<Vec<_>>());
+        let mut sorted = out.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (1..=200).collect::<Vec<_>>());
+        assert!(stats.reordered > 0);
+    }
+
+    #[test]
+    fn test_full_drop_and_duplicate() {
+        let (out, stats) = run(ChaosConfig { drop_rate: 1.0, ..Default::default() }, 10);
+        assert!(out.is_empty());
+        assert_eq!(stats.dropped, 10);
+
+        let (out, _) = run(ChaosConfig { duplicate_rate: 1.0, ..Default::default() }, 3);
+        assert_eq!(out, vec![1, 1, 2, 2, 3, 3]);
+    }
+
+    #[test]
+    fn test_jitter_bounded() {
+        let jitter = Duration::from_micros(50);
+        let mut injector = ChaosInjector::<u64>::new(ChaosConfig { jitter, seed: 1, ..Default::default() });
+        assert!((0..100).all(|_| injector.jitter() <= jitter));
+        assert_eq!(ChaosInjector::<u64>::new(ChaosConfig::default()).jitter(), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_publisher_wraps_inner() {
+        let publisher = ChaosPublisher::new(
+            Recorder::default(),
+            ChaosConfig {
+                drop_rate: 0.2,
+                reorder_rate: 0.2,
+                seed: 9,
+                ..Default::default()
+            },
+        );
+        for seq in 1..=50 {
+            publisher.publish(&message(seq)).await.unwrap();
+        }
+        publisher.flush().await.unwrap();
+
+        let sent = publisher.inner().sent.lock().clone();
+        let (expected, _) = run(
+            ChaosConfig {
+                drop_rate: 0.2,
+                reorder_rate: 0.2,
+                seed: 9,
+                ..Default::default()
+            },
+            50,
+        );
+        assert_eq!(sent, expected);
+        assert_eq!(publisher.chaos_stats().messages_out, sent.len() as u64);
+    }
+}
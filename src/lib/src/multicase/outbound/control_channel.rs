@@ -0,0 +1,185 @@
+/// 组播控制通道实现
+///
+/// - `ControlAnnouncer`: 订阅端，周期性向发布端控制地址发送存活通告
+/// - `ControlListener`: 发布端，接收通告并登记到`ListenerRegistry`
+
+use crate::multicase::domain::control::{ControlMessage, ListenerAlert, ListenerRegistry};
+use crate::multicase::domain::multicast::MulticastError;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+/// 获取当前纳秒时间戳
+fn now_ns() -> u64 {
+    crate::timing::now_ns()
+}
+
+/// 订阅端存活通告发送器
+pub struct ControlAnnouncer {
+    socket: UdpSocket,
+    target_addr: SocketAddr,
+    listener_id: String,
+}
+
+impl ControlAnnouncer {
+    /// 创建发送器，`target_addr`为发布端控制地址（单播或控制组播组）
+    pub async fn new(target_addr: SocketAddr, listener_id: &str) -> Result<Self, MulticastError> {
+        let bind_addr: SocketAddr = if target_addr.is_ipv4() {
+            "0.0.0.0:0".parse().unwrap()
+        } else {
+            "[::]:0".parse().unwrap()
+        };
+        let socket = UdpSocket::bind(bind_addr)
+            .await
+            .map_err(|e| MulticastError::Socket(format!("Failed to bind control socket: {}", e)))?;
+
+        Ok(Self {
+            socket,
+            target_addr,
+            listener_id: listener_id.to_string(),
+        })
+    }
+
+    /// 发送一次存活通告
+    pub async fn announce(&self, last_sequence: u64, messages_received: u64) -> Result<(), MulticastError> {
+        self.send(&ControlMessage::Announce {
+            listener_id: self.listener_id.clone(),
+            last_sequence,
+            messages_received,
+        })
+        .await
+    }
+
+    /// 发送离开通知
+    pub async fn goodbye(&self) -> Result<(), MulticastError> {
+        self.send(&ControlMessage::Goodbye {
+            listener_id: self.listener_id.clone(),
+        })
+        .await
+    }
+
+    async fn send(&self, message: &ControlMessage) -> Result<(), MulticastError> {
+        self.socket.send_to(&message.encode(), self.target_addr).await?;
+        Ok(())
+    }
+
+    /// 启动周期通告任务
+    ///
+    /// `progress`返回 (最大序列号, 消息数)，例如取自订阅端统计
+    pub fn spawn<F>(self: Arc<Self>, interval: Duration, progress: F) -> JoinHandle<()>
+    where
+        F: Fn() -> (u64, u64) + Send + Sync + 'static,
+    {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+
+                let (last_sequence, messages_received) = progress();
+                if let Err(e) = self.announce(last_sequence, messages_received).await {
+                    crate::runtime_log!(Warn, "Failed to send control announcement: {}", e);
+                }
+            }
+        })
+    }
+}
+
+/// 发布端控制通道接收器
+pub struct ControlListener {
+    socket: UdpSocket,
+    registry: Arc<ListenerRegistry>,
+}
+
+impl ControlListener {
+    /// 绑定控制地址
+    pub async fn bind(addr: SocketAddr, registry: Arc<ListenerRegistry>) -> Result<Self, MulticastError> {
+        let socket = UdpSocket::bind(addr)
+            .await
+            .map_err(|e| MulticastError::Socket(format!("Failed to bind control socket: {}", e)))?;
+        Ok(Self { socket, registry })
+    }
+
+    /// 获取实际监听地址（绑定端口0时使用）
+    pub fn local_addr(&self) -> Result<SocketAddr, MulticastError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// 启动接收任务
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            loop {
+                match self.socket.recv_from(&mut buf).await {
+                    Ok((size, _addr)) => match ControlMessage::decode(&buf[..size]) {
+                        Ok(message) => self.registry.record(&message, now_ns()),
+                        Err(e) => crate::runtime_log!(Warn, "Failed to parse control message: {}", e),
+                    },
+                    Err(e) => {
+                        crate::runtime_log!(Warn, "Control socket error: {}", e);
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                }
+            }
+        })
+    }
+
+    /// 启动失联检查任务，每条告警调用一次`on_alert`
+    pub fn spawn_monitor<F>(registry: Arc<ListenerRegistry>, interval: Duration, on_alert: F) -> JoinHandle<()>
+    where
+        F: Fn(ListenerAlert) + Send + Sync + 'static,
+    {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+
+                for alert in registry.check(now_ns()) {
+                    on_alert(alert);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_announcements_reach_registry() {
+        let registry = Arc::new(ListenerRegistry::new(Duration::from_secs(5)));
+        let listener = ControlListener::bind("127.0.0.1:0".parse().unwrap(), Arc::clone(&registry))
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let task = listener.spawn();
+
+        let announcer = Arc::new(ControlAnnouncer::new(addr, "risk-1").await.unwrap());
+        let announce = Arc::clone(&announcer).spawn(Duration::from_millis(10), || (7, 8));
+
+        let mut listeners = Vec::new();
+        for _ in 0..50 {
+            listeners = registry.listeners(now_ns());
+            if !listeners.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        announce.abort();
+        assert_eq!(listeners.len(), 1);
+        assert_eq!(listeners[0].listener_id, "risk-1");
+        assert_eq!((listeners[0].last_sequence, listeners[0].messages_received), (7, 8));
+
+        announcer.goodbye().await.unwrap();
+        for _ in 0..50 {
+            if registry.listeners(now_ns()).is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(registry.listeners(now_ns()).is_empty());
+        task.abort();
+    }
+}
@@ -1,2 +1,5 @@
+pub mod chaos;
+pub mod control_channel;
+pub mod state_store;
 pub mod udp_publisher;
-pub mod udp_subscriber;
\ No newline at end of file
+pub mod udp_subscriber;
@@ -0,0 +1,79 @@
+/// 桥接状态文件存储
+///
+/// 以JSON保存桥接会话状态，先写临时文件再重命名，
+/// 进程在写入过程中崩溃也不会留下半截文件。
+
+use crate::multicase::domain::multicast::MulticastError;
+use crate::multicase::domain::session::{BridgeState, BridgeStateStore};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// 基于文件的桥接状态存储
+pub struct FileBridgeStateStore {
+    path: PathBuf,
+}
+
+impl FileBridgeStateStore {
+    /// 创建文件存储
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// 状态文件路径
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+}
+
+impl BridgeStateStore for FileBridgeStateStore {
+    fn load(&self) -> Result<Option<BridgeState>, MulticastError> {
+        let data = match fs::read(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|e| MulticastError::Deserialization(format!("Invalid bridge state: {}", e)))
+    }
+
+    fn save(&self, state: &BridgeState) -> Result<(), MulticastError> {
+        let data = serde_json::to_vec_pretty(state)
+            .map_err(|e| MulticastError::Serialization(e.to_string()))?;
+
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multicase::domain::session::BridgeSession;
+
+    #[test]
+    fn test_file_store_resume() {
+        let path = std::env::temp_dir().join(format!("rlob-bridge-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let store = FileBridgeStateStore::new(&path);
+        assert_eq!(store.load().unwrap(), None);
+
+        let session = BridgeSession::open(FileBridgeStateStore::new(&path), 100).unwrap();
+        session.add_subscription("BTCUSDT").unwrap();
+        assert_eq!(session.next_sequence(Some("BTCUSDT")).unwrap(), 0);
+        session.flush().unwrap();
+
+        let session = BridgeSession::open(FileBridgeStateStore::new(&path), 100).unwrap();
+        assert!(session.is_resumed());
+        assert_eq!(session.subscriptions(), vec!["BTCUSDT"]);
+        assert_eq!(session.last_sequence("BTCUSDT"), Some(0));
+        assert_eq!(session.next_sequence(None).unwrap(), 100);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
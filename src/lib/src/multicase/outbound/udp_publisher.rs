@@ -2,12 +2,18 @@
 ///
 /// 高性能UDP组播发送，用于市场数据分发
 
+use crate::multicase::domain::engine_feed::FeedUpdate;
 use crate::multicase::domain::multicast::*;
+use crate::multicase::domain::session::{BridgeSession, BridgeStateStore};
+use crate::multicase::domain::stats::{FeedStatsTracker, StatsConfig};
+use crate::orderbook::{DepthLevel, PriceConverter};
+use crate::timing::{system_clock, Clock};
 use async_trait::async_trait;
 use std::net::{IpAddr, SocketAddr, UdpSocket};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task::JoinHandle;
 
 /// UDP组播发送器
 pub struct UdpMulticastPublisher {
@@ -15,6 +21,11 @@ pub struct UdpMulticastPublisher {
     target_addr: SocketAddr,
     sequence: Arc<AtomicU64>,
     stats: Arc<PublisherStatsImpl>,
+    tracker: Arc<FeedStatsTracker>,
+    /// 消息时间戳的时间源
+    clock: Arc<dyn Clock>,
+    /// 统计中最优价的小数换算
+    converter: PriceConverter,
 }
 
 struct PublisherStatsImpl {
@@ -54,7 +65,7 @@ impl UdpMulticastPublisher {
         // 如果需要指定接口，需要使用tokio::net::UdpSocket或socket2 crate
         // 这里我们跳过接口设置
         if config.interface.is_some() {
-            eprintln!("Warning: Interface setting not implemented for std::net::UdpSocket");
+            crate::runtime_log!(Warn, "Warning: Interface setting not implemented for std::net::UdpSocket");
         }
 
         // 设置为非阻塞模式
@@ -69,9 +80,29 @@ impl UdpMulticastPublisher {
             target_addr,
             sequence: Arc::new(AtomicU64::new(0)),
             stats: Arc::new(PublisherStatsImpl::default()),
+            tracker: Arc::new(FeedStatsTracker::new()),
+            clock: system_clock(),
+            converter: PriceConverter::default(),
         })
     }
 
+    /// 设置时间源（默认读全局时钟，通常与撮合引擎共用同一时钟）
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置统计中最优价的换算（默认`PriceConverter::cents()`，通常取`InstrumentSpec::converter`）
+    pub fn with_converter(mut self, converter: PriceConverter) -> Self {
+        self.converter = converter;
+        self
+    }
+
+    /// 按品种统计追踪器（每条消息发布成功后以全行情序列号记录）
+    pub fn stats_tracker(&self) -> &Arc<FeedStatsTracker> {
+        &self.tracker
+    }
+
     /// 序列化消息为二进制格式
     ///
     /// 消息格式:
@@ -103,14 +134,6 @@ impl UdpMulticastPublisher {
 
         buffer
     }
-
-    /// 获取当前纳秒时间戳
-    fn get_timestamp_ns() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64
-    }
 }
 
 #[async_trait]
@@ -159,9 +182,19 @@ impl UdpMulticastPublisher {
         &self,
         msg_type: MessageType,
         payload: Vec<u8>,
+    ) -> Result<(), MulticastError> {
+        self.send_for(None, msg_type, payload).await
+    }
+
+    /// 发送消息，成功后计入`symbol`的统计（None时只推进全行情序列号）
+    pub async fn send_for(
+        &self,
+        symbol: Option<&str>,
+        msg_type: MessageType,
+        payload: Vec<u8>,
     ) -> Result<(), MulticastError> {
         let sequence = self.sequence.fetch_add(1, Ordering::SeqCst);
-        let timestamp_ns = Self::get_timestamp_ns();
+        let timestamp_ns = self.clock.now_ns();
 
         let message = MulticastMessage {
             sequence,
@@ -170,6 +203,155 @@ impl UdpMulticastPublisher {
             payload,
         };
 
-        self.publish(&message).await
+        self.publish(&message).await?;
+        self.track(symbol, &message);
+        Ok(())
+    }
+
+    fn track(&self, symbol: Option<&str>, message: &MulticastMessage) {
+        match symbol {
+            Some(symbol) => self.tracker.record(symbol, message.sequence, message.timestamp_ns),
+            None => self.tracker.record_sequence(message.sequence),
+        }
+    }
+
+    /// 以会话分配的序列号发送品种消息
+    ///
+    /// 序列号跨重启连续，同时记录为该品种最后发布的序列号。
+    pub async fn send_with_session<S: BridgeStateStore>(
+        &self,
+        session: &BridgeSession<S>,
+        symbol: Option<&str>,
+        msg_type: MessageType,
+        payload: Vec<u8>,
+    ) -> Result<(), MulticastError> {
+        let message = MulticastMessage {
+            sequence: session.next_sequence(symbol)?,
+            timestamp_ns: self.clock.now_ns(),
+            msg_type,
+            payload,
+        };
+
+        self.publish(&message).await?;
+        self.track(symbol, &message);
+        Ok(())
+    }
+
+    /// 会话从已有状态恢复时发布Restart标记（首次启动时不发送）
+    pub async fn publish_restart_marker<S: BridgeStateStore>(
+        &self,
+        session: &BridgeSession<S>,
+    ) -> Result<bool, MulticastError> {
+        let Some(marker) = session.restart_marker() else {
+            return Ok(false);
+        };
+
+        self.send_with_session(session, None, MessageType::Restart, marker.encode())
+            .await?;
+        Ok(true)
+    }
+
+    /// 启动按品种统计发布任务
+    ///
+    /// 按`config.interval`周期在Stats通道上发布`stats_tracker`中所有品种的统计
+    pub fn spawn_stats_publisher(self: &Arc<Self>, config: StatsConfig) -> JoinHandle<()> {
+        let publisher = Arc::clone(self);
+        let tracker = Arc::clone(&self.tracker);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(config.interval);
+            loop {
+                ticker.tick().await;
+
+                let stats = tracker.snapshot(publisher.clock.now_ns(), config.stale_after);
+                if stats.symbols.is_empty() {
+                    continue;
+                }
+
+                if let Err(e) = publisher.send(MessageType::Stats, stats.encode()).await {
+                    crate::runtime_log!(Warn, "Failed to publish stats: {}", e);
+                }
+            }
+        })
+    }
+
+    /// 启动引擎行情发布任务
+    ///
+    /// 按到达顺序发布`EngineFeed`产生的成交和最优价更新，通道关闭时结束。
+    /// 发布成功的更新计入品种统计，最优价按`PriceConverter`换算为小数记录。
+    pub fn spawn_engine_feed(self: &Arc<Self>, mut updates: UnboundedReceiver<FeedUpdate>) -> JoinHandle<()> {
+        let publisher = Arc::clone(self);
+
+        tokio::spawn(async move {
+            while let Some(update) = updates.recv().await {
+                let (msg_type, payload) = update.to_payload();
+                if let Err(e) = publisher.send_for(Some(update.symbol()), msg_type, payload).await {
+                    crate::runtime_log!(Warn, "Failed to publish {} update: {}", update.symbol(), e);
+                    continue;
+                }
+                if let FeedUpdate::Bbo(bbo) = &update {
+                    let price = |level: Option<DepthLevel>| level.map(|l| publisher.converter.to_decimal(l.price));
+                    publisher.tracker.update_bbo(&bbo.symbol, price(bbo.best_bid), price(bbo.best_ask));
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multicase::domain::engine_feed::EngineFeed;
+    use crate::orderbook::types::{px, qty};
+    use crate::orderbook::{OrderBook, Side, TimeInForce, TraderId};
+    use crate::timing::MockClock;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_engine_feed_updates_stats() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let clock = MockClock::new(5_000);
+        let publisher = Arc::new(
+            UdpMulticastPublisher::new(MulticastConfig {
+                multicast_addr: "127.0.0.1".parse().unwrap(),
+                port: receiver.local_addr().unwrap().port(),
+                ..Default::default()
+            })
+            .unwrap()
+            .with_clock(Arc::new(clock.clone())),
+        );
+
+        let (mut feed, rx) = EngineFeed::new();
+        let mut books: Vec<_> = ["BTCUSDT", "ETHUSDT"]
+            .into_iter()
+            .map(|symbol| {
+                let mut book = OrderBook::with_capacity(1_000, 100);
+                book.set_trade_sink(feed.trade_sink(symbol));
+                (symbol, book)
+            })
+            .collect();
+        // 两个品种交替更新，共用全行情序列号
+        for round in 0..3 {
+            for (symbol, book) in &mut books {
+                book.limit_order(TraderId::from_str("S"), Side::Sell, px(101), qty(5), TimeInForce::Gtc).unwrap();
+                book.limit_order(TraderId::from_str("B"), Side::Buy, px(100 - round), qty(1), TimeInForce::Gtc).unwrap();
+                book.limit_order(TraderId::from_str("T"), Side::Buy, px(101), qty(1), TimeInForce::Ioc).unwrap();
+                feed.on_book(symbol, book);
+            }
+        }
+        drop(feed);
+        drop(books);
+        publisher.spawn_engine_feed(rx).await.unwrap();
+
+        clock.advance(Duration::from_secs(1));
+        let stats = publisher.stats_tracker().snapshot(clock.now_ns(), Duration::from_secs(60));
+        assert_eq!(stats.symbols.len(), 2);
+        assert!(stats.symbols.iter().all(|s| s.is_healthy()), "{:?}", stats);
+        let eth = stats.get("ETHUSDT").unwrap();
+        assert_eq!(eth.message_count, 6);
+        // 消息按注入的时钟打时间戳
+        assert_eq!(eth.last_update_ns, 5_000);
+        assert_eq!((eth.best_bid, eth.best_ask), (Some(1.0), Some(1.01)));
+        assert_eq!(publisher.stats().messages_sent, 12);
     }
 }
@@ -2,8 +2,11 @@
 ///
 /// 高性能UDP组播接收，用于市场数据接收
 
+use crate::multicase::domain::dedup::{DedupVerdict, DedupWindow};
 use crate::multicase::domain::multicast::*;
+use crate::timing::{StalenessGuard, StalenessPolicy};
 use async_trait::async_trait;
+use parking_lot::Mutex;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
@@ -13,6 +16,8 @@ pub struct UdpMulticastSubscriber {
     socket: Arc<UdpSocket>,
     stats: Arc<SubscriberStatsImpl>,
     last_sequence: Arc<AtomicU64>,
+    staleness: Option<Arc<StalenessGuard>>,
+    dedup: Option<Arc<Mutex<DedupWindow<SocketAddr>>>>,
 }
 
 struct SubscriberStatsImpl {
@@ -20,6 +25,7 @@ struct SubscriberStatsImpl {
     bytes_received: AtomicU64,
     packets_lost: AtomicU64,
     parse_errors: AtomicU64,
+    duplicates: AtomicU64,
 }
 
 impl Default for SubscriberStatsImpl {
@@ -29,6 +35,7 @@ impl Default for SubscriberStatsImpl {
             bytes_received: AtomicU64::new(0),
             packets_lost: AtomicU64::new(0),
             parse_errors: AtomicU64::new(0),
+            duplicates: AtomicU64::new(0),
         }
     }
 }
@@ -71,9 +78,28 @@ impl UdpMulticastSubscriber {
             socket: Arc::new(socket),
             stats: Arc::new(SubscriberStatsImpl::default()),
             last_sequence: Arc::new(AtomicU64::new(0)),
+            staleness: None,
+            dedup: None,
         })
     }
 
+    /// 启用消息时效检查（在`subscribe`之前调用）
+    ///
+    /// 发送端重启标记不受时效限制。
+    pub fn with_staleness(mut self, policy: StalenessPolicy) -> Self {
+        self.staleness = Some(Arc::new(StalenessGuard::new(policy)));
+        self
+    }
+
+    /// 启用重复报文过滤（在`subscribe`之前调用）
+    ///
+    /// 按(发送端地址, 序列号)判重，每个发送端记住最近`window`个序列号；
+    /// 早于窗口的消息无法判断，照常投递。
+    pub fn with_dedup(mut self, window: usize) -> Self {
+        self.dedup = Some(Arc::new(Mutex::new(DedupWindow::new(window))));
+        self
+    }
+
     /// 反序列化消息
     ///
     /// 消息格式:
@@ -157,6 +183,8 @@ impl MulticastSubscriber for UdpMulticastSubscriber {
         let socket = self.socket.clone();
         let stats = self.stats.clone();
         let last_sequence = self.last_sequence.clone();
+        let staleness = self.staleness.clone();
+        let dedup = self.dedup.clone();
 
         let callback = Arc::new(callback);
 
@@ -174,23 +202,47 @@ impl MulticastSubscriber for UdpMulticastSubscriber {
                 })
                 .await
                 {
-                    Ok((Ok((size, _addr)), buf)) => {
+                    Ok((Ok((size, addr)), buf)) => {
                         stats.bytes_received.fetch_add(size as u64, Ordering::Relaxed);
 
                         // 反序列化消息
                         match Self::deserialize_message_static(&buf[..size]) {
                             Ok(message) => {
-                                // 检测丢包
-                                Self::check_packet_loss_static(&last_sequence, &stats, message.sequence);
+                                // 重复报文不计入接收、不参与丢包检测
+                                if let Some(dedup) = &dedup {
+                                    let mut dedup = dedup.lock();
+                                    if message.msg_type == MessageType::Restart {
+                                        dedup.reset(&addr);
+                                    }
+                                    if dedup.check(addr, message.sequence) == DedupVerdict::Duplicate {
+                                        stats.duplicates.fetch_add(1, Ordering::Relaxed);
+                                        continue;
+                                    }
+                                }
+
+                                // 检测丢包（发送端重启标记重置缺口检测）
+                                if message.msg_type == MessageType::Restart {
+                                    last_sequence.store(message.sequence, Ordering::Relaxed);
+                                } else {
+                                    Self::check_packet_loss_static(&last_sequence, &stats, message.sequence);
+                                }
 
                                 stats.messages_received.fetch_add(1, Ordering::Relaxed);
 
+                                // 过期消息在回调前丢弃
+                                if message.msg_type != MessageType::Restart
+                                    && let Some(guard) = &staleness
+                                    && !guard.admit(message.timestamp_ns)
+                                {
+                                    continue;
+                                }
+
                                 // 调用回调
                                 callback(message);
                             }
                             Err(e) => {
                                 stats.parse_errors.fetch_add(1, Ordering::Relaxed);
-                                eprintln!("Failed to parse message: {}", e);
+                                crate::runtime_log!(Warn, "Failed to parse message: {}", e);
                             }
                         }
                     }
@@ -199,11 +251,11 @@ impl MulticastSubscriber for UdpMulticastSubscriber {
                         tokio::time::sleep(tokio::time::Duration::from_micros(100)).await;
                     }
                     Ok((Err(e), _)) => {
-                        eprintln!("Socket error: {}", e);
+                        crate::runtime_log!(Warn, "Socket error: {}", e);
                         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
                     }
                     Err(e) => {
-                        eprintln!("Task error: {}", e);
+                        crate::runtime_log!(Warn, "Task error: {}", e);
                         break;
                     }
                 }
@@ -219,6 +271,9 @@ impl MulticastSubscriber for UdpMulticastSubscriber {
             bytes_received: self.stats.bytes_received.load(Ordering::Relaxed),
             packets_lost: self.stats.packets_lost.load(Ordering::Relaxed),
             parse_errors: self.stats.parse_errors.load(Ordering::Relaxed),
+            duplicates: self.stats.duplicates.load(Ordering::Relaxed),
+            stale_messages: self.staleness.as_ref().map_or(0, |guard| guard.stale()),
+            stale_dropped: self.staleness.as_ref().map_or(0, |guard| guard.dropped()),
         }
     }
 }
@@ -0,0 +1,410 @@
+/// 执行算法容器
+///
+/// 母单（TWAP、冰山）按时间切片在订单簿上派生子限价单，
+/// 容器负责进度跟踪、暂停/恢复以及完成报告。
+/// 算法由调用方周期性调用`tick`驱动，与撮合线程共享同一订单簿。
+
+use super::engine::OrderBook;
+use super::types::{OrderId, Price, Quantity, Side, TimeInForce, Trade, TraderId};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// 算法标识符
+pub type AlgoId = u64;
+
+/// 算法类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlgoKind {
+    /// 时间加权：总量均分为`slices`份，每隔`interval`发送一份，
+    /// 上一份未成交部分撤单后并入下一份
+    Twap { slices: u32, interval: Duration },
+    /// 冰山：每次只显示`display_quantity`，子单完全成交后补充下一份
+    Iceberg { display_quantity: Quantity },
+}
+
+/// 母单
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParentOrder {
+    pub trader: TraderId,        // 交易员ID
+    pub side: Side,              // 方向
+    pub limit_price: Price,      // 子单限价
+    pub quantity: Quantity,      // 总数量
+    pub kind: AlgoKind,          // 算法类型
+}
+
+/// 算法状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlgoState {
+    Running,
+    Paused,
+    Completed,
+    Cancelled,
+}
+
+impl AlgoState {
+    /// 是否已结束
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        matches!(self, AlgoState::Completed | AlgoState::Cancelled)
+    }
+}
+
+/// 算法进度（结束时即为完成报告）
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlgoProgress {
+    pub algo_id: AlgoId,           // 算法ID
+    pub state: AlgoState,          // 当前状态
+    pub quantity: Quantity,        // 母单总量
+    pub filled: Quantity,          // 已成交数量
+    pub child_orders: u32,         // 已派生子单数
+    pub avg_price: Option<f64>,    // 成交均价（tick）
+}
+
+impl AlgoProgress {
+    /// 剩余未成交数量
+    #[inline]
+    pub fn remaining(&self) -> Quantity {
+        self.quantity - self.filled
+    }
+}
+
+/// 当前挂出的子单
+#[derive(Debug, Clone, Copy)]
+struct ChildOrder {
+    order_id: OrderId,
+    resting: Quantity,
+}
+
+/// 运行中的算法
+#[derive(Debug)]
+struct Algo {
+    parent: ParentOrder,
+    state: AlgoState,
+    started_at: Instant,
+    paused_at: Option<Instant>,
+    next_slice: u32,
+    child: Option<ChildOrder>,
+    child_orders: u32,
+    filled: Quantity,
+    notional: u64,
+}
+
+impl Algo {
+    fn progress(&self, algo_id: AlgoId) -> AlgoProgress {
+        AlgoProgress {
+            algo_id,
+            state: self.state,
+            quantity: self.parent.quantity,
+            filled: self.filled,
+            child_orders: self.child_orders,
+            avg_price: (!self.filled.is_zero()).then(|| self.notional as f64 / self.filled.get() as f64),
+        }
+    }
+
+    fn record_fill(&mut self, price: Price, quantity: Quantity) {
+        self.filled += quantity;
+        self.notional += price.notional(quantity);
+    }
+
+    /// 同步挂出子单的被动成交
+    fn sync_child(&mut self, book: &OrderBook) {
+        if let Some(child) = self.child {
+            let remaining = book.order_quantity(child.order_id).unwrap_or_default();
+            let filled = child.resting - remaining;
+            if !filled.is_zero() {
+                // 挂单被动成交价格即子单限价
+                self.record_fill(self.parent.limit_price, filled);
+            }
+            self.child = (!remaining.is_zero()).then_some(ChildOrder {
+                order_id: child.order_id,
+                resting: remaining,
+            });
+        }
+    }
+
+    /// 撤销挂出的子单
+    fn withdraw_child(&mut self, book: &mut OrderBook) {
+        self.sync_child(book);
+        if let Some(child) = self.child.take() {
+            let _ = book.cancel_order(child.order_id);
+        }
+    }
+
+    /// 派生子单
+    fn send_child(&mut self, book: &mut OrderBook, quantity: Quantity, trades: &mut Vec<Trade>) {
+        if quantity.is_zero() {
+            return;
+        }
+
+        let start = trades.len();
+        let result = book.limit_order_into(
+            self.parent.trader,
+            self.parent.side,
+            self.parent.limit_price,
+            quantity,
+            TimeInForce::Gtc,
+            trades,
+        );
+        self.child_orders += 1;
+        // 被拒绝的子单不产生成交，下一步重新派生
+        let Ok(order_id) = result else {
+            return;
+        };
+
+        let mut immediate = Quantity::ZERO;
+        for trade in &trades[start..] {
+            let ours = match self.parent.side {
+                Side::Buy => trade.buyer == self.parent.trader,
+                Side::Sell => trade.seller == self.parent.trader,
+            };
+            if ours {
+                immediate += trade.quantity;
+                self.notional += trade.price.notional(trade.quantity);
+            }
+        }
+        self.filled += immediate;
+
+        let resting = book.order_quantity(order_id).unwrap_or_default();
+        self.child = (!resting.is_zero()).then_some(ChildOrder { order_id, resting });
+    }
+
+    fn step(&mut self, now: Instant, book: &mut OrderBook, trades: &mut Vec<Trade>) {
+        self.sync_child(book);
+
+        match self.parent.kind {
+            AlgoKind::Twap { slices, interval } => {
+                let slices = slices.max(1);
+                let elapsed = now.saturating_duration_since(self.started_at);
+                let due = if interval.is_zero() {
+                    slices
+                } else {
+                    ((elapsed.as_nanos() / interval.as_nanos()) as u32 + 1).min(slices)
+                };
+
+                if due > self.next_slice {
+                    // 到达新切片：撤销上一子单，补齐到累计目标
+                    self.withdraw_child(book);
+                    self.next_slice = due;
+                    let target = self.parent.quantity.get() as u64 * due as u64 / slices as u64;
+                    let target = Quantity::new(target as u32).unwrap_or_default();
+                    self.send_child(book, target.saturating_sub(self.filled), trades);
+                }
+            }
+            AlgoKind::Iceberg { display_quantity } => {
+                if self.child.is_none() {
+                    let remaining = self.parent.quantity - self.filled;
+                    self.send_child(book, remaining.min(display_quantity.max(Quantity::ONE)), trades);
+                }
+            }
+        }
+
+        if self.filled >= self.parent.quantity {
+            self.state = AlgoState::Completed;
+        }
+    }
+}
+
+/// 执行算法容器
+#[derive(Debug, Default)]
+pub struct AlgoEngine {
+    algos: BTreeMap<AlgoId, Algo>,
+    next_id: AlgoId,
+}
+
+impl AlgoEngine {
+    /// 创建算法容器
+    pub fn new() -> Self {
+        Self {
+            algos: BTreeMap::new(),
+            next_id: 1,
+        }
+    }
+
+    /// 提交母单（在下一次`tick`时开始派生子单）
+    pub fn submit(&mut self, parent: ParentOrder, now: Instant) -> AlgoId {
+        let algo_id = self.next_id;
+        self.next_id += 1;
+
+        self.algos.insert(
+            algo_id,
+            Algo {
+                parent,
+                state: if parent.quantity.is_zero() { AlgoState::Completed } else { AlgoState::Running },
+                started_at: now,
+                paused_at: None,
+                next_slice: 0,
+                child: None,
+                child_orders: 0,
+                filled: Quantity::ZERO,
+                notional: 0,
+            },
+        );
+        algo_id
+    }
+
+    /// 暂停算法（撤销挂出的子单，TWAP时间表顺延）
+    pub fn pause(&mut self, algo_id: AlgoId, now: Instant, book: &mut OrderBook) -> bool {
+        match self.algos.get_mut(&algo_id) {
+            Some(algo) if algo.state == AlgoState::Running => {
+                algo.withdraw_child(book);
+                algo.state = AlgoState::Paused;
+                algo.paused_at = Some(now);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 恢复算法
+    pub fn resume(&mut self, algo_id: AlgoId, now: Instant) -> bool {
+        match self.algos.get_mut(&algo_id) {
+            Some(algo) if algo.state == AlgoState::Paused => {
+                if let Some(paused_at) = algo.paused_at.take() {
+                    algo.started_at += now.saturating_duration_since(paused_at);
+                }
+                // 恢复后重新发送当前切片
+                algo.next_slice = algo.next_slice.saturating_sub(1);
+                algo.state = AlgoState::Running;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 取消算法（撤销挂出的子单），返回完成报告
+    pub fn cancel(&mut self, algo_id: AlgoId, book: &mut OrderBook) -> Option<AlgoProgress> {
+        let algo = self.algos.get_mut(&algo_id)?;
+        if algo.state.is_finished() {
+            return None;
+        }
+        algo.withdraw_child(book);
+        algo.state = AlgoState::Cancelled;
+        Some(algo.progress(algo_id))
+    }
+
+    /// 查询算法进度
+    pub fn progress(&self, algo_id: AlgoId) -> Option<AlgoProgress> {
+        self.algos.get(&algo_id).map(|algo| algo.progress(algo_id))
+    }
+
+    /// 运行中（含暂停）的算法数量
+    pub fn active_count(&self) -> usize {
+        self.algos.values().filter(|a| !a.state.is_finished()).count()
+    }
+
+    /// 驱动所有运行中的算法
+    ///
+    /// 子单立即成交产生的成交追加到`trades`；
+    /// 返回本次完成的算法报告（已完成的算法随后从容器中移除）。
+    pub fn tick(&mut self, now: Instant, book: &mut OrderBook, trades: &mut Vec<Trade>) -> Vec<AlgoProgress> {
+        let mut reports = Vec::new();
+
+        for (&algo_id, algo) in self.algos.iter_mut() {
+            if algo.state == AlgoState::Running {
+                algo.step(now, book, trades);
+            }
+            if algo.state.is_finished() {
+                reports.push(algo.progress(algo_id));
+            }
+        }
+
+        self.algos.retain(|_, algo| !algo.state.is_finished());
+        reports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty};
+
+    fn book_with_asks(levels: &[(u32, u32)]) -> OrderBook {
+        let mut book = OrderBook::with_capacity(20_000, 1024);
+        for &(price, quantity) in levels {
+            book.limit_order(TraderId::from_str("MM"), Side::Sell, px(price), qty(quantity), TimeInForce::Gtc).unwrap();
+        }
+        book
+    }
+
+    #[test]
+    fn test_twap_slices_over_time() {
+        let mut book = book_with_asks(&[(10000, 1000)]);
+        let mut engine = AlgoEngine::new();
+        let t0 = Instant::now();
+        let interval = Duration::from_secs(1);
+
+        let id = engine.submit(
+            ParentOrder {
+                trader: TraderId::from_str("ALGO"),
+                side: Side::Buy,
+                limit_price: px(10000),
+                quantity: qty(100),
+                kind: AlgoKind::Twap { slices: 4, interval },
+            },
+            t0,
+        );
+
+        let mut trades = Vec::new();
+        engine.tick(t0, &mut book, &mut trades);
+        assert_eq!(engine.progress(id).unwrap().filled, qty(25));
+
+        // 同一切片内不重复发送
+        engine.tick(t0 + interval / 2, &mut book, &mut trades);
+        assert_eq!(engine.progress(id).unwrap().child_orders, 1);
+
+        engine.tick(t0 + interval * 2, &mut book, &mut trades);
+        assert_eq!(engine.progress(id).unwrap().filled, qty(75));
+
+        let reports = engine.tick(t0 + interval * 3, &mut book, &mut trades);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].state, AlgoState::Completed);
+        assert_eq!(reports[0].filled, qty(100));
+        assert_eq!(reports[0].avg_price, Some(10000.0));
+        assert_eq!(trades.iter().map(|t| t.quantity).sum::<Quantity>(), qty(100));
+        assert_eq!(engine.active_count(), 0);
+    }
+
+    #[test]
+    fn test_iceberg_replenishes_and_pause_resume() {
+        let mut book = OrderBook::with_capacity(20_000, 1024);
+        let mut engine = AlgoEngine::new();
+        let now = Instant::now();
+        let trader = TraderId::from_str("ICE");
+
+        let id = engine.submit(
+            ParentOrder {
+                trader,
+                side: Side::Sell,
+                limit_price: px(10100),
+                quantity: qty(30),
+                kind: AlgoKind::Iceberg { display_quantity: qty(10) },
+            },
+            now,
+        );
+
+        let mut trades = Vec::new();
+        engine.tick(now, &mut book, &mut trades);
+        assert_eq!(book.best_ask(), Some(px(10100)));
+
+        // 对手方吃掉显示部分后补充下一份
+        book.limit_order(TraderId::from_str("B"), Side::Buy, px(10100), qty(10), TimeInForce::Gtc).unwrap();
+        engine.tick(now, &mut book, &mut trades);
+        let progress = engine.progress(id).unwrap();
+        assert_eq!(progress.filled, qty(10));
+        assert_eq!(progress.child_orders, 2);
+
+        // 暂停撤销子单
+        assert!(engine.pause(id, now, &mut book));
+        assert_eq!(book.order_quantity(book.next_order_id() - 1), None);
+        engine.tick(now, &mut book, &mut trades);
+        assert_eq!(engine.progress(id).unwrap().child_orders, 2);
+
+        assert!(engine.resume(id, now));
+        engine.tick(now, &mut book, &mut trades);
+        assert_eq!(engine.progress(id).unwrap().child_orders, 3);
+
+        let report = engine.cancel(id, &mut book).unwrap();
+        assert_eq!(report.state, AlgoState::Cancelled);
+        assert_eq!(report.remaining(), qty(20));
+    }
+}
@@ -0,0 +1,264 @@
+/// 订单簿分析指标
+///
+/// 基于聚合价位计算策略常用的指标，避免各处重复遍历内部结构:
+/// - 深度加权中间价: 买卖各N档的成交量加权均价的中点
+/// - 买卖盘不平衡度: (买量 - 卖量) / (买量 + 卖量)，取值[-1, 1]
+/// - 微观价格: 按对手方最优档数量加权的最优买卖价
+/// - 近期成交VWAP: 滚动时间窗内的成交量加权均价（历史区间从时序存储计算）
+///
+/// 价格均为`Price`刻度的浮点值。
+
+use super::engine::OrderBook;
+use super::types::{DepthLevel, Price, Quantity, Trade};
+use crate::tsdb::TimeSeriesStore;
+use std::collections::VecDeque;
+use std::io;
+use std::time::Duration;
+
+/// 订单簿指标快照
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookAnalytics {
+    /// 最优买卖价中点
+    pub mid: Option<f64>,
+    pub depth_weighted_mid: Option<f64>,
+    pub imbalance: Option<f64>,
+    pub microprice: Option<f64>,
+}
+
+/// 一次性计算N档指标
+pub fn analyze(book: &OrderBook, levels: usize) -> BookAnalytics {
+    let bids: Vec<DepthLevel> = book.iter_bids().take(levels).collect();
+    let asks: Vec<DepthLevel> = book.iter_asks().take(levels).collect();
+
+    BookAnalytics {
+        mid: match (bids.first(), asks.first()) {
+            (Some(bid), Some(ask)) => Some((bid.price.get() as f64 + ask.price.get() as f64) / 2.0),
+            _ => None,
+        },
+        depth_weighted_mid: weighted_mid(&bids, &asks),
+        imbalance: level_imbalance(&bids, &asks),
+        microprice: top_microprice(bids.first(), asks.first()),
+    }
+}
+
+/// 买卖各`levels`档的深度加权中间价
+pub fn depth_weighted_mid(book: &OrderBook, levels: usize) -> Option<f64> {
+    let bids: Vec<DepthLevel> = book.iter_bids().take(levels).collect();
+    let asks: Vec<DepthLevel> = book.iter_asks().take(levels).collect();
+    weighted_mid(&bids, &asks)
+}
+
+/// 买卖各`levels`档的挂单量不平衡度（任一侧为空时返回None）
+pub fn imbalance(book: &OrderBook, levels: usize) -> Option<f64> {
+    let bids: Vec<DepthLevel> = book.iter_bids().take(levels).collect();
+    let asks: Vec<DepthLevel> = book.iter_asks().take(levels).collect();
+    level_imbalance(&bids, &asks)
+}
+
+/// 微观价格（最优档数量加权）
+pub fn microprice(book: &OrderBook) -> Option<f64> {
+    top_microprice(book.iter_bids().next().as_ref(), book.iter_asks().next().as_ref())
+}
+
+/// 总量与成交量加权均价
+fn volume_weighted(levels: &[DepthLevel]) -> Option<(f64, f64)> {
+    let volume: f64 = levels.iter().map(|l| l.quantity.get() as f64).sum();
+    if volume == 0.0 {
+        return None;
+    }
+    let notional: f64 = levels
+        .iter()
+        .map(|l| l.price.get() as f64 * l.quantity.get() as f64)
+        .sum();
+    Some((volume, notional / volume))
+}
+
+fn weighted_mid(bids: &[DepthLevel], asks: &[DepthLevel]) -> Option<f64> {
+    let (_, bid) = volume_weighted(bids)?;
+    let (_, ask) = volume_weighted(asks)?;
+    Some((bid + ask) / 2.0)
+}
+
+fn level_imbalance(bids: &[DepthLevel], asks: &[DepthLevel]) -> Option<f64> {
+    let (bid_volume, _) = volume_weighted(bids)?;
+    let (ask_volume, _) = volume_weighted(asks)?;
+    Some((bid_volume - ask_volume) / (bid_volume + ask_volume))
+}
+
+fn top_microprice(bid: Option<&DepthLevel>, ask: Option<&DepthLevel>) -> Option<f64> {
+    let (bid, ask) = (bid?, ask?);
+    let bid_qty = bid.quantity.get() as f64;
+    let ask_qty = ask.quantity.get() as f64;
+    // 买盘越厚，价格越靠近卖价
+    Some((bid.price.get() as f64 * ask_qty + ask.price.get() as f64 * bid_qty) / (bid_qty + ask_qty))
+}
+
+/// 滚动时间窗成交VWAP
+#[derive(Debug)]
+pub struct TradeVwap {
+    window: Duration,
+    /// (成交时间, 价格, 数量)
+    trades: VecDeque<(u64, u64, u32)>,
+    notional: u128,
+    volume: u64,
+}
+
+impl TradeVwap {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            trades: VecDeque::new(),
+            notional: 0,
+            volume: 0,
+        }
+    }
+
+    /// 记录成交（须按成交时间顺序）
+    pub fn record(&mut self, trade: &Trade) {
+        self.record_fill(trade.timestamp_ns, trade.price, trade.quantity);
+    }
+
+    fn record_fill(&mut self, timestamp_ns: u64, price: Price, quantity: Quantity) {
+        let (price, quantity) = (price.get(), quantity.get());
+        self.trades.push_back((timestamp_ns, price, quantity));
+        self.notional += price as u128 * quantity as u128;
+        self.volume += quantity as u64;
+    }
+
+    /// 从时序存储载入截至`now_ns`窗口内的成交（在接入实时成交前调用），返回载入笔数
+    pub fn backfill(&mut self, store: &mut TimeSeriesStore, symbol: &str, now_ns: u64) -> io::Result<usize> {
+        let cutoff = now_ns.saturating_sub(self.window.as_nanos() as u64);
+        let trades = store.trades_between(symbol, cutoff, now_ns)?;
+        for trade in &trades {
+            self.record_fill(trade.timestamp_ns, trade.price, trade.quantity);
+        }
+        Ok(trades.len())
+    }
+
+    /// 窗口内的VWAP；窗口内没有成交时返回None
+    pub fn vwap(&mut self, now_ns: u64) -> Option<f64> {
+        self.evict(now_ns);
+        (self.volume > 0).then(|| self.notional as f64 / self.volume as f64)
+    }
+
+    /// 窗口内的成交量
+    pub fn volume(&mut self, now_ns: u64) -> u64 {
+        self.evict(now_ns);
+        self.volume
+    }
+
+    fn evict(&mut self, now_ns: u64) {
+        let cutoff = now_ns.saturating_sub(self.window.as_nanos() as u64);
+        while let Some(&(ts, price, quantity)) = self.trades.front() {
+            if ts >= cutoff {
+                break;
+            }
+            self.trades.pop_front();
+            self.notional -= price as u128 * quantity as u128;
+            self.volume -= quantity as u64;
+        }
+    }
+}
+
+/// 时序存储中`[from_ns, to_ns]`内成交的VWAP；区间内没有成交时返回None
+pub fn vwap_between(store: &mut TimeSeriesStore, symbol: &str, from_ns: u64, to_ns: u64) -> io::Result<Option<f64>> {
+    let (notional, volume) = store
+        .trades_between(symbol, from_ns, to_ns)?
+        .iter()
+        .fold((0u128, 0u64), |(notional, volume), t| {
+            let quantity = t.quantity.get();
+            (notional + t.price.get() as u128 * quantity as u128, volume + quantity as u64)
+        });
+    Ok((volume > 0).then(|| notional as f64 / volume as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty, Side, TimeInForce, TraderId};
+    use crate::tsdb::{StoreConfig, TradeRecord};
+
+    fn book() -> OrderBook {
+        let mut book = OrderBook::new();
+        let mm = TraderId::from_str("MM");
+        book.limit_order(mm, Side::Buy, px(99), qty(30), TimeInForce::Gtc).unwrap();
+        book.limit_order(mm, Side::Buy, px(98), qty(10), TimeInForce::Gtc).unwrap();
+        book.limit_order(mm, Side::Sell, px(101), qty(10), TimeInForce::Gtc).unwrap();
+        book.limit_order(mm, Side::Sell, px(103), qty(10), TimeInForce::Gtc).unwrap();
+        book
+    }
+
+    #[test]
+    fn test_book_metrics() {
+        let book = book();
+        // 买盘: 40 @ 98.75，卖盘: 20 @ 102
+        assert_eq!(depth_weighted_mid(&book, 2), Some((98.75 + 102.0) / 2.0));
+        assert_eq!(imbalance(&book, 2), Some(20.0 / 60.0));
+        assert_eq!(imbalance(&book, 1), Some(0.5));
+        // (99 × 10 + 101 × 30) / 40
+        assert_eq!(microprice(&book), Some(100.5));
+
+        let all = analyze(&book, 2);
+        assert_eq!(all.mid, Some(100.0));
+        assert_eq!(all.microprice, microprice(&book));
+
+        assert_eq!(analyze(&OrderBook::new(), 5), BookAnalytics {
+            mid: None,
+            depth_weighted_mid: None,
+            imbalance: None,
+            microprice: None,
+        });
+    }
+
+    #[test]
+    fn test_trade_vwap_window() {
+        let mut book = book();
+        let taker = TraderId::from_str("TK");
+        let (_, first) = book.limit_order(taker, Side::Buy, px(101), qty(10), TimeInForce::Ioc).unwrap();
+        let (_, second) = book.limit_order(taker, Side::Buy, px(103), qty(5), TimeInForce::Ioc).unwrap();
+
+        let mut vwap = TradeVwap::new(Duration::from_nanos(100));
+        let mut trade = first[0];
+        trade.timestamp_ns = 1_000;
+        vwap.record(&trade);
+        let mut trade = second[0];
+        trade.timestamp_ns = 1_050;
+        vwap.record(&trade);
+
+        assert_eq!(vwap.vwap(1_060), Some((101.0 * 10.0 + 103.0 * 5.0) / 15.0));
+        assert_eq!(vwap.vwap(1_120), Some(103.0));
+        assert_eq!(vwap.volume(1_200), 0);
+        assert_eq!(vwap.vwap(1_200), None);
+    }
+
+    #[test]
+    fn test_trade_vwap_from_store() {
+        let dir = std::env::temp_dir().join(format!("rlob-vwap-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut store = TimeSeriesStore::open(&dir, StoreConfig::default()).unwrap();
+        for (ts, price, quantity) in [(900, 90, 1), (1_000, 101, 10), (1_050, 103, 5)] {
+            store
+                .append_trade(&TradeRecord {
+                    timestamp_ns: ts,
+                    symbol: "BTCUSDT".to_string(),
+                    buyer: TraderId::from_str("B"),
+                    seller: TraderId::from_str("S"),
+                    price: px(price),
+                    quantity: qty(quantity),
+                })
+                .unwrap();
+        }
+
+        let expected = (101.0 * 10.0 + 103.0 * 5.0) / 15.0;
+        assert_eq!(vwap_between(&mut store, "BTCUSDT", 1_000, 1_050).unwrap(), Some(expected));
+        assert_eq!(vwap_between(&mut store, "BTCUSDT", 1_100, 2_000).unwrap(), None);
+
+        let mut vwap = TradeVwap::new(Duration::from_nanos(100));
+        assert_eq!(vwap.backfill(&mut store, "BTCUSDT", 1_060).unwrap(), 2);
+        assert_eq!(vwap.vwap(1_060), Some(expected));
+        assert_eq!(vwap.vwap(1_120), Some(103.0));
+
+        drop(store);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
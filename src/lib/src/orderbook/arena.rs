@@ -1,39 +1,110 @@
 /// 订单簿条目的内存池分配器
 ///
 /// 提供快速、缓存友好的分配，无堆开销。
-/// 订单从预分配池中使用bump-pointer分配。
+/// 订单优先从空闲链表复用已释放的槽位，否则使用bump-pointer分配。
+/// 每个槽位带有代数计数，释放时递增，使指向旧条目的句柄失效。
 
 use super::types::OrderEntry;
+use std::hint;
+use std::mem::{self, MaybeUninit};
+
+/// 带代数的槽位句柄（低32位为索引，高32位为代数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArenaHandle(u64);
+
+impl ArenaHandle {
+    #[inline]
+    fn new(idx: usize, generation: u32) -> Self {
+        Self(((generation as u64) << 32) | idx as u64)
+    }
+
+    /// 槽位索引
+    #[inline]
+    pub fn index(&self) -> usize {
+        (self.0 & 0xFFFF_FFFF) as usize
+    }
+
+    /// 分配时的代数
+    #[inline]
+    pub fn generation(&self) -> u32 {
+        (self.0 >> 32) as u32
+    }
+
+    /// 转换为原始整数（用于存入索引表）
+    #[inline]
+    pub fn into_raw(self) -> u64 {
+        self.0
+    }
+
+    /// 从原始整数恢复
+    #[inline]
+    pub fn from_raw(raw: u64) -> Self {
+        Self(raw)
+    }
+}
 
 /// 固定大小的订单条目内存池
 pub struct OrderArena {
     entries: Vec<OrderEntry>,  // 订单条目数组
-    next_free: usize,          // 下一个空闲位置
+    generations: Vec<u32>,     // 各槽位的代数
+    free: Vec<usize>,          // 已释放的槽位
+    capacity: usize,           // 最大槽位数
 }
 
 impl OrderArena {
     /// 创建指定容量的新内存池
     #[inline]
     pub fn new(capacity: usize) -> Self {
+        assert!(capacity <= u32::MAX as usize, "arena capacity exceeds u32 index space");
         Self {
             entries: Vec::with_capacity(capacity),
-            next_free: 0,
+            generations: Vec::with_capacity(capacity),
+            free: Vec::with_capacity(capacity),
+            capacity,
         }
     }
 
     /// 分配新的订单条目，返回其索引
     #[inline]
     pub fn allocate(&mut self, entry: OrderEntry) -> Option<usize> {
-        if self.next_free >= self.entries.capacity() {
+        if let Some(idx) = self.free.pop() {
+            self.entries[idx] = entry;
+            return Some(idx);
+        }
+
+        if self.entries.len() >= self.capacity {
             return None; // 内存池已满
         }
 
-        let idx = self.next_free;
+        let idx = self.entries.len();
         self.entries.push(entry);
-        self.next_free += 1;
+        self.generations.push(0);
         Some(idx)
     }
 
+    /// 释放槽位以便复用，同时使该槽位的旧句柄失效
+    ///
+    /// 调用方须保证该索引已不再被价格链表引用。
+    #[inline]
+    pub fn free(&mut self, idx: usize) {
+        debug_assert!(!self.free.contains(&idx), "double free of arena slot {}", idx);
+        self.generations[idx] = self.generations[idx].wrapping_add(1);
+        self.free.push(idx);
+    }
+
+    /// 获取槽位当前代数的句柄
+    #[inline]
+    pub fn handle(&self, idx: usize) -> ArenaHandle {
+        ArenaHandle::new(idx, self.generations[idx])
+    }
+
+    /// 解析句柄，槽位已被释放或复用时返回None
+    #[inline]
+    pub fn resolve(&self, handle: ArenaHandle) -> Option<usize> {
+        let idx = handle.index();
+        (self.generations.get(idx) == Some(&handle.generation())).then_some(idx)
+    }
+
     /// 通过索引获取条目的引用
     #[inline]
     pub fn get(&self, idx: usize) -> Option<&OrderEntry> {
@@ -46,42 +117,78 @@ impl OrderArena {
         self.entries.get_mut(idx)
     }
 
-    /// 获取已分配条目的数量
+    /// 获取已分配（未释放）条目的数量
     #[inline]
     pub fn len(&self) -> usize {
-        self.entries.len()
+        self.entries.len() - self.free.len()
     }
 
     /// 检查内存池是否为空
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.entries.is_empty()
+        self.len() == 0
     }
 
     /// 获取内存池容量
     #[inline]
     pub fn capacity(&self) -> usize {
-        self.entries.capacity()
+        self.capacity
     }
 
     /// 获取剩余容量
     #[inline]
     pub fn remaining_capacity(&self) -> usize {
-        self.entries.capacity() - self.entries.len()
+        self.capacity - self.len()
     }
 
     /// 清空内存池（用于重置）
     #[inline]
     pub fn clear(&mut self) {
         self.entries.clear();
-        self.next_free = 0;
+        self.generations.clear();
+        self.free.clear();
+    }
+
+    /// 预留全部容量并逐页写入未使用的存储，使首批订单不再触发缺页
+    pub fn prewarm(&mut self) {
+        self.entries.reserve_exact(self.capacity - self.entries.len());
+        self.generations.reserve_exact(self.capacity - self.generations.len());
+        self.free.reserve_exact(self.capacity - self.free.len());
+        zero_spare(&mut self.entries);
+        zero_spare(&mut self.generations);
+        zero_spare(&mut self.free);
+    }
+
+    /// 已分配存储的槽位数
+    #[inline]
+    pub fn reserved(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// 占用的存储字节数
+    pub fn allocated_bytes(&self) -> usize {
+        self.entries.capacity() * mem::size_of::<OrderEntry>()
+            + self.generations.capacity() * mem::size_of::<u32>()
+            + self.free.capacity() * mem::size_of::<usize>()
     }
 
     /// 预留额外容量
     #[inline]
     pub fn reserve(&mut self, additional: usize) {
+        self.capacity += additional;
         self.entries.reserve(additional);
+        self.generations.reserve(additional);
+        self.free.reserve(additional);
+    }
+}
+
+/// 把Vec未使用的容量清零
+fn zero_spare<T>(vec: &mut Vec<T>) {
+    let spare = vec.spare_capacity_mut();
+    for slot in spare.iter_mut() {
+        *slot = MaybeUninit::zeroed();
     }
+    hint::black_box(spare);
 }
 
 impl Default for OrderArena {
@@ -93,27 +200,27 @@ impl Default for OrderArena {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::orderbook::types::TraderId;
+    use crate::orderbook::types::{px, qty, Side, TraderId};
 
     #[test]
     fn test_arena_allocation() {
         let mut arena = OrderArena::new(10);
 
-        let entry = OrderEntry::new(1, TraderId::from_str("TRADER1"), 100);
+        let entry = OrderEntry::new(1, TraderId::from_str("TRADER1"), Side::Buy, px(100), qty(100));
         let idx = arena.allocate(entry).unwrap();
 
         assert_eq!(idx, 0);
         assert_eq!(arena.len(), 1);
-        assert_eq!(arena.get(idx).unwrap().quantity, 100);
+        assert_eq!(arena.get(idx).unwrap().quantity, qty(100));
     }
 
     #[test]
     fn test_arena_full() {
         let mut arena = OrderArena::new(2);
 
-        let entry1 = OrderEntry::new(1, TraderId::from_str("T1"), 100);
-        let entry2 = OrderEntry::new(2, TraderId::from_str("T2"), 200);
-        let entry3 = OrderEntry::new(3, TraderId::from_str("T3"), 300);
+        let entry1 = OrderEntry::new(1, TraderId::from_str("T1"), Side::Buy, px(100), qty(100));
+        let entry2 = OrderEntry::new(2, TraderId::from_str("T2"), Side::Buy, px(100), qty(200));
+        let entry3 = OrderEntry::new(3, TraderId::from_str("T3"), Side::Buy, px(100), qty(300));
 
         assert!(arena.allocate(entry1).is_some());
         assert!(arena.allocate(entry2).is_some());
@@ -124,11 +231,30 @@ mod tests {
     fn test_arena_clear() {
         let mut arena = OrderArena::new(10);
 
-        arena.allocate(OrderEntry::new(1, TraderId::from_str("T1"), 100));
+        arena.allocate(OrderEntry::new(1, TraderId::from_str("T1"), Side::Buy, px(100), qty(100)));
         assert_eq!(arena.len(), 1);
 
         arena.clear();
         assert_eq!(arena.len(), 0);
         assert_eq!(arena.remaining_capacity(), 10);
     }
+
+    #[test]
+    fn test_free_list_reuse_invalidates_handles() {
+        let mut arena = OrderArena::new(1);
+
+        let idx = arena.allocate(OrderEntry::new(1, TraderId::from_str("T1"), Side::Buy, px(100), qty(100))).unwrap();
+        let stale = arena.handle(idx);
+        assert_eq!(arena.resolve(stale), Some(idx));
+
+        arena.free(idx);
+        assert_eq!(arena.len(), 0);
+        assert_eq!(arena.resolve(stale), None);
+
+        let reused = arena.allocate(OrderEntry::new(2, TraderId::from_str("T2"), Side::Buy, px(100), qty(200))).unwrap();
+        assert_eq!(reused, idx);
+        assert_eq!(arena.resolve(stale), None);
+        assert_eq!(arena.resolve(arena.handle(reused)), Some(reused));
+        assert_eq!(arena.get(reused).unwrap().order_id, 2);
+    }
 }
@@ -0,0 +1,388 @@
+/// 成交审计日志（Merkle Patricia Trie）
+///
+/// 每笔成交以成交ID（8字节big-endian）为键、固定格式编码为值写入当日的MPT。
+/// 跨日时封存前一日的trie并发布其根哈希（`DailyRoot`）；交易员可为自己参与的
+/// 任意成交申请包含证明，对照已发布的日根哈希独立验证，无需信任撮合方的完整数据。
+///
+/// 日期按成交时间戳划分（UTC，自1970-01-01的天数）。
+
+use super::engine::TradeSink;
+use super::journal::{invalid, read_array, read_price, read_quantity, read_side};
+use super::types::{OrderId, Trade, TradeId, TraderId};
+use crate::mpt::{MerklePatriciaTrie, MerkleProof};
+use std::collections::HashMap;
+use std::io;
+
+const NANOS_PER_DAY: u64 = 86_400_000_000_000;
+
+/// 成交编码长度
+pub const ENCODED_TRADE_LEN: usize = 8 + 8 + 8 + 8 + 8 + 4 + 1 + 8 + 8;
+
+/// 成交时间戳所在日期（自1970-01-01的天数）
+#[inline]
+pub fn trade_day(trade: &Trade) -> u64 {
+    trade.timestamp_ns / NANOS_PER_DAY
+}
+
+/// 成交在trie中的键
+#[inline]
+pub fn trade_key(trade_id: TradeId) -> [u8; 8] {
+    trade_id.to_be_bytes()
+}
+
+/// 编码成交
+///
+/// 格式（little-endian）: 8字节成交ID + 8字节时间戳 + 8字节买方 + 8字节卖方
+/// + 8字节价格 + 4字节数量 + 1字节主动方 + 8字节挂单ID + 8字节吃单ID
+pub fn encode_trade(trade: &Trade) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(ENCODED_TRADE_LEN);
+    buf.extend_from_slice(&trade.trade_id.to_le_bytes());
+    buf.extend_from_slice(&trade.timestamp_ns.to_le_bytes());
+    buf.extend_from_slice(trade.buyer.as_bytes());
+    buf.extend_from_slice(trade.seller.as_bytes());
+    buf.extend_from_slice(&trade.price.get().to_le_bytes());
+    buf.extend_from_slice(&trade.quantity.get().to_le_bytes());
+    buf.push(trade.aggressor_side as u8);
+    buf.extend_from_slice(&trade.maker_order_id.to_le_bytes());
+    buf.extend_from_slice(&trade.taker_order_id.to_le_bytes());
+    buf
+}
+
+/// 解码成交
+pub fn decode_trade(data: &[u8]) -> io::Result<Trade> {
+    if data.len() != ENCODED_TRADE_LEN {
+        return Err(invalid(format!("trade record has {} bytes", data.len())));
+    }
+    let reader = &mut &data[..];
+    Ok(Trade {
+        trade_id: u64::from_le_bytes(read_array(reader)?),
+        timestamp_ns: u64::from_le_bytes(read_array(reader)?),
+        buyer: TraderId::new(read_array(reader)?),
+        seller: TraderId::new(read_array(reader)?),
+        price: read_price(reader)?,
+        quantity: read_quantity(reader)?,
+        aggressor_side: read_side(reader)?,
+        maker_order_id: OrderId::from_le_bytes(read_array(reader)?),
+        taker_order_id: OrderId::from_le_bytes(read_array(reader)?),
+    })
+}
+
+/// 已发布的日根哈希
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyRoot {
+    /// 日期（自1970-01-01的天数）
+    pub day: u64,
+    /// 当日trie的根哈希
+    pub root: Vec<u8>,
+    /// 当日成交笔数
+    pub trade_count: u64,
+}
+
+/// 成交包含证明
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeProof {
+    /// 成交所在日期
+    pub day: u64,
+    pub trade: Trade,
+    pub proof: MerkleProof,
+}
+
+impl TradeProof {
+    /// 对照日根哈希验证：证明的键和值必须与成交一致，且路径能还原到根哈希
+    pub fn verify(&self, root: &[u8]) -> bool {
+        self.proof.key == trade_key(self.trade.trade_id)
+            && self.proof.value.as_deref() == Some(encode_trade(&self.trade).as_slice())
+            && trade_day(&self.trade) == self.day
+            && self.proof.verify(root)
+    }
+}
+
+/// 一天的审计trie
+struct DayLog {
+    day: u64,
+    trie: MerklePatriciaTrie,
+    trade_count: u64,
+}
+
+impl DayLog {
+    fn new(day: u64) -> Self {
+        Self {
+            day,
+            trie: MerklePatriciaTrie::new(),
+            trade_count: 0,
+        }
+    }
+
+    fn root(&self) -> DailyRoot {
+        DailyRoot {
+            day: self.day,
+            root: self.trie.root_hash(),
+            trade_count: self.trade_count,
+        }
+    }
+}
+
+/// 日根哈希发布回调
+pub type RootPublisher = Box<dyn FnMut(&DailyRoot) + Send>;
+
+/// 成交审计日志
+///
+/// 实现`TradeSink`，可直接挂接到订单簿。已封存日期的trie保留在内存中以便出具证明。
+pub struct TradeAuditLog {
+    /// 当前日期（未封存）
+    current: Option<DayLog>,
+    /// 已封存日期
+    sealed: HashMap<u64, DayLog>,
+    /// 已发布的日根哈希（按封存顺序）
+    roots: Vec<DailyRoot>,
+    /// 成交ID -> 日期
+    trade_days: HashMap<TradeId, u64>,
+    /// 交易员 -> 参与的成交ID
+    by_trader: HashMap<TraderId, Vec<TradeId>>,
+    publisher: Option<RootPublisher>,
+}
+
+impl TradeAuditLog {
+    pub fn new() -> Self {
+        Self {
+            current: None,
+            sealed: HashMap::new(),
+            roots: Vec::new(),
+            trade_days: HashMap::new(),
+            by_trader: HashMap::new(),
+            publisher: None,
+        }
+    }
+
+    /// 设置日根哈希发布回调（每次封存时调用）
+    pub fn set_root_publisher<F>(&mut self, publisher: F)
+    where
+        F: FnMut(&DailyRoot) + Send + 'static,
+    {
+        self.publisher = Some(Box::new(publisher));
+    }
+
+    /// 记录成交
+    ///
+    /// 成交日期晚于当前日期时先封存当前日期；早于当前日期的成交被拒绝（返回false），
+    /// 已封存的根哈希不可再变更。重复的成交ID同样被拒绝。
+    pub fn record(&mut self, trade: &Trade) -> bool {
+        let day = trade_day(trade);
+        if self.trade_days.contains_key(&trade.trade_id) {
+            return false;
+        }
+        match &self.current {
+            Some(current) if day < current.day => return false,
+            Some(current) if day > current.day => {
+                self.seal_day();
+            }
+            _ => {}
+        }
+        if day < self.roots.last().map_or(0, |root| root.day + 1) {
+            return false;
+        }
+
+        let current = self.current.get_or_insert_with(|| DayLog::new(day));
+        current.trie.insert(&trade_key(trade.trade_id), &encode_trade(trade));
+        current.trade_count += 1;
+
+        self.trade_days.insert(trade.trade_id, day);
+        self.by_trader.entry(trade.buyer).or_default().push(trade.trade_id);
+        if trade.seller != trade.buyer {
+            self.by_trader.entry(trade.seller).or_default().push(trade.trade_id);
+        }
+        true
+    }
+
+    /// 封存当前日期并发布根哈希
+    pub fn seal_day(&mut self) -> Option<DailyRoot> {
+        let log = self.current.take()?;
+        let root = log.root();
+        self.sealed.insert(log.day, log);
+        if let Some(publisher) = &mut self.publisher {
+            publisher(&root);
+        }
+        self.roots.push(root.clone());
+        Some(root)
+    }
+
+    /// 当前（未封存）日期的根哈希
+    pub fn current_root(&self) -> Option<DailyRoot> {
+        self.current.as_ref().map(DayLog::root)
+    }
+
+    /// 已发布的日根哈希
+    pub fn published_roots(&self) -> &[DailyRoot] {
+        &self.roots
+    }
+
+    /// 指定日期已发布的根哈希
+    pub fn published_root(&self, day: u64) -> Option<&DailyRoot> {
+        self.roots.iter().find(|root| root.day == day)
+    }
+
+    /// 交易员参与的成交ID
+    pub fn trades_of(&self, trader: TraderId) -> &[TradeId] {
+        self.by_trader.get(&trader).map_or(&[], Vec::as_slice)
+    }
+
+    /// 为交易员出具成交包含证明
+    ///
+    /// 只有成交的买方或卖方可以申请；当日未封存时证明对应`current_root`。
+    pub fn prove(&self, trader: TraderId, trade_id: TradeId) -> Option<TradeProof> {
+        let day = *self.trade_days.get(&trade_id)?;
+        let log = match &self.current {
+            Some(current) if current.day == day => current,
+            _ => self.sealed.get(&day)?,
+        };
+
+        let proof = log.trie.get_proof(&trade_key(trade_id));
+        let trade = decode_trade(proof.value.as_deref()?).ok()?;
+        if trade.buyer != trader && trade.seller != trader {
+            return None;
+        }
+        Some(TradeProof { day, trade, proof })
+    }
+
+    /// 已记录的成交笔数
+    pub fn len(&self) -> usize {
+        self.trade_days.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trade_days.is_empty()
+    }
+}
+
+impl Default for TradeAuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TradeSink for TradeAuditLog {
+    fn on_trade(&mut self, trade: &Trade) {
+        if !self.record(trade) {
+            crate::runtime_log!(Warn, "Audit log rejected trade {}", trade.trade_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty, Side};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    fn trade(trade_id: TradeId, day: u64, buyer: &str, seller: &str) -> Trade {
+        Trade {
+            trade_id,
+            timestamp_ns: day * NANOS_PER_DAY + trade_id,
+            buyer: TraderId::from_str(buyer),
+            seller: TraderId::from_str(seller),
+            price: px(100 + trade_id as u32),
+            quantity: qty(10),
+            aggressor_side: Side::Buy,
+            maker_order_id: trade_id * 2,
+            taker_order_id: trade_id * 2 + 1,
+        }
+    }
+
+    #[test]
+    fn test_trade_encoding_roundtrip() {
+        let t = trade(7, 3, "ALICE", "BOB");
+        let encoded = encode_trade(&t);
+        assert_eq!(encoded.len(), ENCODED_TRADE_LEN);
+        assert_eq!(decode_trade(&encoded).unwrap(), t);
+        assert!(decode_trade(&encoded[1..]).is_err());
+    }
+
+    #[test]
+    fn test_daily_roots_and_proofs() {
+        let published = Arc::new(Mutex::new(Vec::new()));
+        let mut log = TradeAuditLog::new();
+        let sink = Arc::clone(&published);
+        log.set_root_publisher(move |root| sink.lock().push(root.clone()));
+
+        for id in 1..=20 {
+            let (buyer, seller) = if id % 2 == 0 { ("ALICE", "BOB") } else { ("CAROL", "BOB") };
+            assert!(log.record(&trade(id, 10, buyer, seller)));
+        }
+        assert!(published.lock().is_empty());
+
+        // 跨日时封存前一日
+        assert!(log.record(&trade(21, 11, "ALICE", "CAROL")));
+        let day10 = published.lock()[0].clone();
+        assert_eq!((day10.day, day10.trade_count), (10, 20));
+        assert_eq!(log.published_root(10), Some(&day10));
+
+        // 已封存日期不能再写入，重复成交被拒绝
+        assert!(!log.record(&trade(22, 10, "ALICE", "BOB")));
+        assert!(!log.record(&trade(21, 11, "ALICE", "CAROL")));
+
+        let alice = TraderId::from_str("ALICE");
+        assert_eq!(log.trades_of(alice).len(), 11);
+        for &id in log.trades_of(alice) {
+            let proof = log.prove(alice, id).unwrap();
+            let root = match proof.day {
+                10 => day10.root.clone(),
+                _ => log.current_root().unwrap().root,
+            };
+            assert!(proof.verify(&root));
+        }
+
+        // 非成交参与方不能申请证明
+        assert!(log.prove(alice, 1).is_none());
+        assert!(log.prove(TraderId::from_str("BOB"), 1).is_some());
+        assert!(log.prove(alice, 99).is_none());
+    }
+
+    #[test]
+    fn test_tampered_proof_rejected() {
+        let mut log = TradeAuditLog::new();
+        for id in 1..=5 {
+            log.record(&trade(id, 1, "ALICE", "BOB"));
+        }
+        let root = log.seal_day().unwrap();
+        let alice = TraderId::from_str("ALICE");
+
+        let proof = log.prove(alice, 3).unwrap();
+        assert!(proof.verify(&root.root));
+
+        let mut forged = proof.clone();
+        forged.trade.quantity = qty(1000);
+        assert!(!forged.verify(&root.root));
+
+        let mut forged = proof.clone();
+        forged.trade.trade_id = 4;
+        assert!(!forged.verify(&root.root));
+
+        let other = log.prove(alice, 2).unwrap();
+        assert!(!other.verify(&[0u8; 32]));
+    }
+
+    #[test]
+    fn test_as_trade_sink() {
+        use crate::orderbook::{OrderBook, TimeInForce};
+
+        let log = Arc::new(Mutex::new(TradeAuditLog::new()));
+        struct Shared(Arc<Mutex<TradeAuditLog>>);
+        impl TradeSink for Shared {
+            fn on_trade(&mut self, trade: &Trade) {
+                self.0.lock().on_trade(trade);
+            }
+        }
+
+        let mut book = OrderBook::new();
+        book.set_trade_sink(Box::new(Shared(Arc::clone(&log))));
+        let seller = TraderId::from_str("SELLER");
+        let buyer = TraderId::from_str("BUYER");
+        book.limit_order(seller, Side::Sell, px(100), qty(10), TimeInForce::Gtc).unwrap();
+        let (_, trades) = book.limit_order(buyer, Side::Buy, px(100), qty(4), TimeInForce::Gtc).unwrap();
+
+        let log = log.lock();
+        let proof = log.prove(buyer, trades[0].trade_id).unwrap();
+        assert!(proof.verify(&log.current_root().unwrap().root));
+    }
+}
@@ -0,0 +1,337 @@
+/// 最新价与OHLCV K线聚合
+///
+/// 从订单簿的成交流按固定周期聚合K线，周期起点按成交时间戳对齐到周期的整数倍；
+/// 没有成交的周期不生成K线。只保留最近`capacity`根已完成的K线。
+///
+/// `BarSeries`可克隆共享：`trade_sink`挂到订单簿上，图表和策略线程查询序列。
+/// 历史区间从时序存储查询（`bars_between`），启动或恢复时用`BarSeries::backfill`从存储回放。
+
+use super::engine::TradeSink;
+use super::types::{Price, Trade};
+use crate::tsdb::TimeSeriesStore;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 一根K线
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Bar {
+    /// 周期起点（纳秒，周期的整数倍）
+    pub start_ns: u64,
+    pub open: Price,
+    pub high: Price,
+    pub low: Price,
+    pub close: Price,
+    /// 成交数量合计
+    pub volume: u64,
+    /// 成交笔数
+    pub trades: u32,
+}
+
+impl Bar {
+    fn new(start_ns: u64, price: Price, quantity: u64) -> Self {
+        Self {
+            start_ns,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: quantity,
+            trades: 1,
+        }
+    }
+
+    fn update(&mut self, price: Price, quantity: u64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += quantity;
+        self.trades += 1;
+    }
+}
+
+/// K线聚合器
+#[derive(Debug, Clone)]
+pub struct BarAggregator {
+    interval_ns: u64,
+    capacity: usize,
+    completed: VecDeque<Bar>,
+    current: Option<Bar>,
+    last_price: Option<Price>,
+}
+
+impl BarAggregator {
+    /// 按`interval`周期聚合，最多保留`capacity`根已完成K线
+    ///
+    /// # Panics
+    /// interval必须为正
+    pub fn new(interval: Duration, capacity: usize) -> Self {
+        let interval_ns = interval.as_nanos() as u64;
+        assert!(interval_ns > 0, "bar interval must be positive");
+        Self {
+            interval_ns,
+            capacity,
+            completed: VecDeque::with_capacity(capacity),
+            current: None,
+            last_price: None,
+        }
+    }
+
+    /// K线周期
+    #[inline]
+    pub fn interval(&self) -> Duration {
+        Duration::from_nanos(self.interval_ns)
+    }
+
+    /// 计入一笔成交
+    ///
+    /// 成交应按时间顺序到达；早于当前K线周期的成交计入当前K线。
+    pub fn record(&mut self, trade: &Trade) {
+        self.record_fill(trade.timestamp_ns, trade.price, trade.quantity.get() as u64);
+    }
+
+    fn record_fill(&mut self, timestamp_ns: u64, price: Price, quantity: u64) {
+        self.last_price = Some(price);
+        let start_ns = timestamp_ns - timestamp_ns % self.interval_ns;
+        match self.current.as_mut() {
+            Some(bar) if start_ns <= bar.start_ns => bar.update(price, quantity),
+            _ => {
+                self.complete();
+                self.current = Some(Bar::new(start_ns, price, quantity));
+            }
+        }
+    }
+
+    /// 回放时序存储中`[from_ns, to_ns]`内的成交，返回回放笔数
+    pub fn replay(&mut self, store: &mut TimeSeriesStore, symbol: &str, from_ns: u64, to_ns: u64) -> io::Result<usize> {
+        let trades = store.trades_between(symbol, from_ns, to_ns)?;
+        for trade in &trades {
+            self.record_fill(trade.timestamp_ns, trade.price, trade.quantity.get() as u64);
+        }
+        Ok(trades.len())
+    }
+
+    /// 时间已越过当前K线周期时将其完成（没有新成交时由定时器调用）
+    pub fn roll(&mut self, now_ns: u64) {
+        if self.current.is_some_and(|bar| now_ns >= bar.start_ns + self.interval_ns) {
+            self.complete();
+        }
+    }
+
+    fn complete(&mut self) {
+        let Some(bar) = self.current.take() else {
+            return;
+        };
+        if self.completed.len() == self.capacity {
+            self.completed.pop_front();
+        }
+        if self.capacity > 0 {
+            self.completed.push_back(bar);
+        }
+    }
+
+    /// 最新成交价
+    #[inline]
+    pub fn last_price(&self) -> Option<Price> {
+        self.last_price
+    }
+
+    /// 尚未完成的当前K线
+    #[inline]
+    pub fn current(&self) -> Option<&Bar> {
+        self.current.as_ref()
+    }
+
+    /// 已完成的K线（按时间升序）
+    pub fn completed(&self) -> impl Iterator<Item = &Bar> {
+        self.completed.iter()
+    }
+
+    /// 起点在`[from_ns, to_ns)`内的K线（含当前K线）
+    pub fn range(&self, from_ns: u64, to_ns: u64) -> Vec<Bar> {
+        self.completed
+            .iter()
+            .chain(self.current.as_ref())
+            .filter(|bar| (from_ns..to_ns).contains(&bar.start_ns))
+            .copied()
+            .collect()
+    }
+}
+
+/// 可跨线程共享的K线序列
+#[derive(Debug, Clone)]
+pub struct BarSeries {
+    inner: Arc<Mutex<BarAggregator>>,
+}
+
+impl BarSeries {
+    pub fn new(interval: Duration, capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(BarAggregator::new(interval, capacity))),
+        }
+    }
+
+    /// 订单簿成交输出（设置为订单簿的`TradeSink`）
+    pub fn trade_sink(&self) -> Box<dyn TradeSink> {
+        Box::new(BarSink(Arc::clone(&self.inner)))
+    }
+
+    /// 最新成交价
+    pub fn last_price(&self) -> Option<Price> {
+        self.inner.lock().last_price()
+    }
+
+    /// 当前K线
+    pub fn current(&self) -> Option<Bar> {
+        self.inner.lock().current().copied()
+    }
+
+    /// 起点在`[from_ns, to_ns)`内的K线（含当前K线）
+    pub fn range(&self, from_ns: u64, to_ns: u64) -> Vec<Bar> {
+        self.inner.lock().range(from_ns, to_ns)
+    }
+
+    /// 见`BarAggregator::roll`
+    pub fn roll(&self, now_ns: u64) {
+        self.inner.lock().roll(now_ns);
+    }
+
+    /// 从时序存储回放`[from_ns, to_ns]`内的成交（在接入实时成交前调用），返回回放笔数
+    pub fn backfill(&self, store: &mut TimeSeriesStore, symbol: &str, from_ns: u64, to_ns: u64) -> io::Result<usize> {
+        self.inner.lock().replay(store, symbol, from_ns, to_ns)
+    }
+}
+
+/// 从时序存储按成交时间聚合起点在`[from_ns, to_ns)`内的K线
+///
+/// # Panics
+/// interval必须为正
+pub fn bars_between(
+    store: &mut TimeSeriesStore,
+    symbol: &str,
+    interval: Duration,
+    from_ns: u64,
+    to_ns: u64,
+) -> io::Result<Vec<Bar>> {
+    let interval_ns = interval.as_nanos() as u64;
+    assert!(interval_ns > 0, "bar interval must be positive");
+    if from_ns >= to_ns {
+        return Ok(Vec::new());
+    }
+    // 首尾对齐到整周期，边界K线包含周期内的全部成交
+    let first = from_ns - from_ns % interval_ns;
+    let last = to_ns - 1 - (to_ns - 1) % interval_ns;
+    let trades = store.trades_between(symbol, first, last.saturating_add(interval_ns - 1))?;
+    let mut bars = BarAggregator::new(interval, trades.len());
+    for trade in &trades {
+        bars.record_fill(trade.timestamp_ns, trade.price, trade.quantity.get() as u64);
+    }
+    Ok(bars.range(from_ns, to_ns))
+}
+
+struct BarSink(Arc<Mutex<BarAggregator>>);
+
+impl TradeSink for BarSink {
+    fn on_trade(&mut self, trade: &Trade) {
+        self.0.lock().record(trade);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::engine::OrderBook;
+    use crate::orderbook::types::{px, qty, Side, TimeInForce, TraderId};
+    use crate::tsdb::{StoreConfig, TradeRecord};
+
+    fn trade(timestamp_ns: u64, price: u32, quantity: u32) -> Trade {
+        Trade {
+            trade_id: 0,
+            timestamp_ns,
+            buyer: TraderId::from_str("B"),
+            seller: TraderId::from_str("S"),
+            price: px(price),
+            quantity: qty(quantity),
+            aggressor_side: Side::Buy,
+            maker_order_id: 1,
+            taker_order_id: 2,
+        }
+    }
+
+    #[test]
+    fn test_aggregates_bars() {
+        let mut bars = BarAggregator::new(Duration::from_nanos(100), 2);
+        assert_eq!(bars.last_price(), None);
+
+        for (ts, price, quantity) in [(10, 100, 1), (50, 105, 2), (99, 98, 3), (120, 101, 1), (350, 110, 4)] {
+            bars.record(&trade(ts, price, quantity));
+        }
+        assert_eq!(bars.last_price(), Some(px(110)));
+
+        let series = bars.range(0, u64::MAX);
+        assert_eq!(series.len(), 3);
+        assert_eq!(
+            series[0],
+            Bar { start_ns: 0, open: px(100), high: px(105), low: px(98), close: px(98), volume: 6, trades: 3 }
+        );
+        assert_eq!(series[1].start_ns, 100);
+        // 空周期不生成K线
+        assert_eq!(series[2].start_ns, 300);
+        assert_eq!(bars.current().map(|bar| bar.close), Some(px(110)));
+        assert_eq!(bars.range(100, 300).len(), 1);
+
+        // 容量2：完成第三根后最早的K线被淘汰
+        bars.roll(399);
+        assert!(bars.current().is_some());
+        bars.roll(400);
+        assert!(bars.current().is_none());
+        assert_eq!(bars.completed().map(|bar| bar.start_ns).collect::<Vec<_>>(), vec![100, 300]);
+    }
+
+    #[test]
+    fn test_series_from_book() {
+        let series = BarSeries::new(Duration::from_secs(60), 100);
+        let mut book = OrderBook::new();
+        book.set_trade_sink(series.trade_sink());
+
+        let mm = TraderId::from_str("MM");
+        book.limit_order(mm, Side::Sell, px(101), qty(5), TimeInForce::Gtc).unwrap();
+        book.limit_order(mm, Side::Sell, px(103), qty(5), TimeInForce::Gtc).unwrap();
+        book.limit_order(TraderId::from_str("TK"), Side::Buy, px(103), qty(7), TimeInForce::Ioc).unwrap();
+
+        assert_eq!(series.last_price(), Some(px(103)));
+        let bar = series.current().unwrap();
+        assert_eq!((bar.open, bar.high, bar.low, bar.close), (px(101), px(103), px(101), px(103)));
+        assert_eq!((bar.volume, bar.trades), (7, 2));
+    }
+
+    #[test]
+    fn test_bars_from_store() {
+        let dir = std::env::temp_dir().join(format!("rlob-bars-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut store = TimeSeriesStore::open(&dir, StoreConfig::default()).unwrap();
+        for (ts, price, quantity) in [(10, 100, 1), (50, 105, 2), (99, 98, 3), (120, 101, 1), (350, 110, 4)] {
+            store.append_trade(&TradeRecord::from_trade("BTCUSDT", ts, &trade(ts, price, quantity))).unwrap();
+        }
+
+        // 边界K线包含整周期的成交
+        let bars = bars_between(&mut store, "BTCUSDT", Duration::from_nanos(100), 50, 200).unwrap();
+        assert_eq!(bars.len(), 1);
+        assert_eq!((bars[0].start_ns, bars[0].volume, bars[0].trades), (100, 1, 1));
+        let bars = bars_between(&mut store, "BTCUSDT", Duration::from_nanos(100), 0, u64::MAX).unwrap();
+        assert_eq!(bars.iter().map(|bar| bar.start_ns).collect::<Vec<_>>(), vec![0, 100, 300]);
+        assert_eq!((bars[0].open, bars[0].close, bars[0].volume), (px(100), px(98), 6));
+        assert!(bars_between(&mut store, "ETHUSDT", Duration::from_nanos(100), 0, u64::MAX).unwrap().is_empty());
+
+        let series = BarSeries::new(Duration::from_nanos(100), 10);
+        assert_eq!(series.backfill(&mut store, "BTCUSDT", 0, 200).unwrap(), 4);
+        assert_eq!(series.last_price(), Some(px(101)));
+        assert_eq!(series.range(0, u64::MAX).len(), 2);
+
+        drop(store);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
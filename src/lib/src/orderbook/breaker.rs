@@ -0,0 +1,277 @@
+/// 波动熔断（动态价格限制）与集合竞价撮合价计算
+///
+/// `VolatilityGuard`记录滚动时间窗内的成交价。新订单撮合前按窗口内最低/最高成交价
+/// 计算允许的价格带：
+/// - 买单最多成交到 窗口最低价 × (1 + 幅度)
+/// - 卖单最多成交到 窗口最高价 × (1 - 幅度)
+///
+/// 窗口为空时以最新成交价为参考。订单吃穿价格带时在带边停止撮合，订单簿进入
+/// `TradingMode::Halted`（见`OrderBook::resume`与`OrderBook::reopen_auction`）。
+///
+/// 窗口使用系统时间，配置熔断时日志重放不保证复现熔断时点。
+///
+/// `PriceBand`是受理前的静态限价保护：限价偏离参考价过远的新订单直接拒绝，
+/// 不进入撮合也不挂单，避免明显错误的价格撑大稠密价格阶梯。
+
+use super::types::{Price, Quantity, Side};
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// 基点分母
+const BPS: u64 = 10_000;
+
+/// 熔断配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// 窗口内允许的最大价格变动（基点，1000 = 10%）
+    pub max_move_bps: u32,
+    /// 滚动窗口长度
+    pub window: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            max_move_bps: 1_000,
+            window: Duration::from_secs(300),
+        }
+    }
+}
+
+/// 滚动窗口价格带
+#[derive(Debug)]
+pub struct VolatilityGuard {
+    config: CircuitBreakerConfig,
+    /// 单调递增队列（队首为窗口最低价）
+    lows: VecDeque<(u64, Price)>,
+    /// 单调递减队列（队首为窗口最高价）
+    highs: VecDeque<(u64, Price)>,
+    /// 窗口为空时的参考价
+    anchor: Option<Price>,
+}
+
+impl VolatilityGuard {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            lows: VecDeque::new(),
+            highs: VecDeque::new(),
+            anchor: None,
+        }
+    }
+
+    pub fn config(&self) -> &CircuitBreakerConfig {
+        &self.config
+    }
+
+    /// 记录成交价
+    pub fn record(&mut self, timestamp_ns: u64, price: Price) {
+        while self.lows.back().is_some_and(|&(_, p)| p >= price) {
+            self.lows.pop_back();
+        }
+        self.lows.push_back((timestamp_ns, price));
+        while self.highs.back().is_some_and(|&(_, p)| p <= price) {
+            self.highs.pop_back();
+        }
+        self.highs.push_back((timestamp_ns, price));
+        self.anchor = Some(price);
+    }
+
+    /// 清空窗口，以`anchor`为新的参考价
+    pub fn reset(&mut self, anchor: Option<Price>) {
+        self.lows.clear();
+        self.highs.clear();
+        self.anchor = anchor;
+    }
+
+    /// 当前允许的成交价格带 (下限, 上限)；没有任何参考价时返回None
+    pub fn band(&mut self, now_ns: u64) -> Option<(Price, Price)> {
+        let cutoff = now_ns.saturating_sub(self.config.window.as_nanos() as u64);
+        while self.lows.front().is_some_and(|&(ts, _)| ts < cutoff) {
+            self.lows.pop_front();
+        }
+        while self.highs.front().is_some_and(|&(ts, _)| ts < cutoff) {
+            self.highs.pop_front();
+        }
+
+        let low = self.lows.front().map(|&(_, p)| p).or(self.anchor)?;
+        let high = self.highs.front().map(|&(_, p)| p).or(self.anchor)?;
+        let bps = self.config.max_move_bps as u64;
+
+        let upper = (low.get() as u128 * (BPS + bps) as u128 / BPS as u128).min(u64::MAX as u128) as u64;
+        let lower = (high.get() as u128 * BPS.saturating_sub(bps) as u128).div_ceil(BPS as u128) as u64;
+        Some((
+            Price::new(lower.max(1)).unwrap_or(Price::MIN),
+            Price::new(upper).unwrap_or(Price::MAX),
+        ))
+    }
+}
+
+/// 限价保护配置
+///
+/// 新订单限价偏离参考价超过`max_deviation_bps`时拒绝（`RejectReason::PriceBand`）。
+/// 参考价由订单簿提供（中间价，单边或空簿时取最新成交价），没有参考价时不检查。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceBand {
+    /// 允许的最大偏离（基点，1000 = 10%）
+    pub max_deviation_bps: u32,
+}
+
+impl PriceBand {
+    pub fn new(max_deviation_bps: u32) -> Self {
+        Self { max_deviation_bps }
+    }
+
+    /// 参考价下允许的限价范围 (下限, 上限)，含边界
+    pub fn limits(&self, reference: Price) -> (Price, Price) {
+        let bps = self.max_deviation_bps as u64;
+        let upper = (reference.get() as u128 * (BPS + bps) as u128 / BPS as u128).min(u64::MAX as u128) as u64;
+        let lower = (reference.get() as u128 * BPS.saturating_sub(bps) as u128).div_ceil(BPS as u128) as u64;
+        (
+            Price::new(lower.max(1)).unwrap_or(Price::MIN),
+            Price::new(upper).unwrap_or(Price::MAX),
+        )
+    }
+
+    /// 限价是否在参考价的价格带内
+    pub fn contains(&self, reference: Price, price: Price) -> bool {
+        let (lower, upper) = self.limits(reference);
+        (lower..=upper).contains(&price)
+    }
+}
+
+/// 集合竞价撮合结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Uncross {
+    /// 撮合价
+    pub price: Price,
+    /// 可成交数量
+    pub volume: u64,
+    /// 撮合价上的买卖量差（买 - 卖）
+    pub imbalance: i64,
+}
+
+/// 计算使成交量最大的单一撮合价
+///
+/// `orders`为(方向, 限价, 数量)。成交量相同时取买卖量差绝对值最小的价格，
+/// 仍相同时取最接近`reference`的价格，再相同取较低价格。没有可成交数量时返回None。
+pub fn uncross(orders: &[(Side, Price, Quantity)], reference: Option<Price>) -> Option<Uncross> {
+    let mut buys: Vec<(Price, u64)> = Vec::new();
+    let mut sells: Vec<(Price, u64)> = Vec::new();
+    for &(side, price, quantity) in orders {
+        match side {
+            Side::Buy => buys.push((price, quantity.get() as u64)),
+            Side::Sell => sells.push((price, quantity.get() as u64)),
+        }
+    }
+    if buys.is_empty() || sells.is_empty() {
+        return None;
+    }
+    buys.sort_unstable_by_key(|&(price, _)| price);
+    sells.sort_unstable_by_key(|&(price, _)| price);
+
+    // 前缀和: 卖方价格升序累计；买方价格升序的后缀累计
+    let mut sell_cum = Vec::with_capacity(sells.len());
+    let mut total = 0;
+    for &(_, quantity) in &sells {
+        total += quantity;
+        sell_cum.push(total);
+    }
+    let mut buy_suffix = vec![0u64; buys.len() + 1];
+    for i in (0..buys.len()).rev() {
+        buy_suffix[i] = buy_suffix[i + 1] + buys[i].1;
+    }
+
+    let key = |u: &Uncross| {
+        let distance = reference.map_or(0, |r| u.price.get().abs_diff(r.get()));
+        (u.volume, Reverse(u.imbalance.unsigned_abs()), Reverse(distance), Reverse(u.price))
+    };
+    let mut best: Option<Uncross> = None;
+    for price in buys.iter().chain(sells.iter()).map(|&(price, _)| price) {
+        let demand = buy_suffix[buys.partition_point(|&(p, _)| p < price)];
+        let sold = sells.partition_point(|&(p, _)| p <= price);
+        let supply = if sold == 0 { 0 } else { sell_cum[sold - 1] };
+        let volume = demand.min(supply);
+        if volume == 0 {
+            continue;
+        }
+
+        let candidate = Uncross {
+            price,
+            volume,
+            imbalance: demand as i64 - supply as i64,
+        };
+        if best.is_none_or(|b| key(&candidate) > key(&b)) {
+            best = Some(candidate);
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty};
+
+    #[test]
+    fn test_price_band_limits() {
+        let band = PriceBand::new(500);
+        assert_eq!(band.limits(px(1_000)), (px(950), px(1_050)));
+        assert!(band.contains(px(1_000), px(950)));
+        assert!(band.contains(px(1_000), px(1_050)));
+        assert!(!band.contains(px(1_000), px(1_051)));
+        assert!(!band.contains(px(1_000), px(949)));
+        // 偏离超过100%时下限为最低价
+        assert_eq!(PriceBand::new(20_000).limits(px(10)).0, Price::MIN);
+    }
+
+    #[test]
+    fn test_band_tracks_window_extremes() {
+        let mut guard = VolatilityGuard::new(CircuitBreakerConfig {
+            max_move_bps: 1_000,
+            window: Duration::from_nanos(100),
+        });
+        assert_eq!(guard.band(0), None);
+
+        guard.record(10, px(1_000));
+        guard.record(20, px(1_050));
+        guard.record(30, px(980));
+        // 上限基于窗口最低价，下限基于窗口最高价
+        assert_eq!(guard.band(40), Some((px(945), px(1_078))));
+
+        // 1_050 滑出窗口
+        assert_eq!(guard.band(125), Some((px(882), px(1_078))));
+        // 全部滑出后以最新成交价为参考
+        assert_eq!(guard.band(1_000), Some((px(882), px(1_078))));
+
+        guard.reset(Some(px(2_000)));
+        assert_eq!(guard.band(1_000), Some((px(1_800), px(2_200))));
+    }
+
+    #[test]
+    fn test_uncross_maximizes_volume() {
+        let orders = [
+            (Side::Buy, px(102), qty(10)),
+            (Side::Buy, px(101), qty(5)),
+            (Side::Buy, px(99), qty(20)),
+            (Side::Sell, px(98), qty(8)),
+            (Side::Sell, px(100), qty(6)),
+            (Side::Sell, px(103), qty(30)),
+        ];
+        let result = uncross(&orders, None).unwrap();
+        // 100..=101: 需求15，供给14
+        assert_eq!(result.volume, 14);
+        assert_eq!((result.price, result.imbalance), (px(100), 1));
+
+        assert_eq!(uncross(&[(Side::Buy, px(99), qty(1)), (Side::Sell, px(100), qty(1))], None), None);
+        assert_eq!(uncross(&[(Side::Buy, px(99), qty(1))], None), None);
+    }
+
+    #[test]
+    fn test_uncross_tie_breaks_toward_reference() {
+        let orders = [(Side::Buy, px(105), qty(10)), (Side::Sell, px(95), qty(10))];
+        assert_eq!(uncross(&orders, Some(px(104))).unwrap().price, px(105));
+        assert_eq!(uncross(&orders, Some(px(90))).unwrap().price, px(95));
+    }
+}
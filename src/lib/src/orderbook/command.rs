@@ -0,0 +1,234 @@
+/// 订单指令定义
+///
+/// 网关接收的指令在进入撮合引擎前统一表示为`Command`，
+/// 执行结果为`CommandResult`。
+
+use super::engine::OrderBook;
+use super::types::{OrderId, Price, Quantity, Side, TimeInForce, Trade, TraderId, TradingMode};
+use std::fmt;
+use thiserror::Error;
+
+/// 订单指令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// 限价单
+    Limit {
+        trader: TraderId,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        tif: TimeInForce,
+    },
+    /// 止损市价单
+    Stop {
+        trader: TraderId,
+        side: Side,
+        stop_price: Price,
+        quantity: Quantity,
+    },
+    /// 止损限价单
+    StopLimit {
+        trader: TraderId,
+        side: Side,
+        stop_price: Price,
+        limit_price: Price,
+        quantity: Quantity,
+    },
+    /// 撤单
+    Cancel { order_id: OrderId },
+    /// 改单（减少数量，保留时间优先级）
+    Amend { order_id: OrderId, quantity: Quantity },
+    /// 撤销到期时间不晚于`now_ns`的GTD挂单
+    Expire { now_ns: u64 },
+}
+
+impl Command {
+    /// 在订单簿上执行指令
+    pub fn execute(&self, book: &mut OrderBook) -> CommandResult {
+        match *self {
+            Command::Limit { side, price, quantity, .. }
+            | Command::StopLimit { side, limit_price: price, quantity, .. }
+                if let Err(reason) = book.admit_order(side, price, quantity) =>
+            {
+                CommandResult::Rejected(reason)
+            }
+            Command::Stop { .. } if book.trading_mode() == TradingMode::CancelOnly => {
+                CommandResult::Rejected(RejectReason::CancelOnly)
+            }
+            Command::Stop { quantity, .. } if let Err(reason) = book.instrument().check_quantity(quantity) => {
+                CommandResult::Rejected(reason)
+            }
+            Command::Amend { quantity, .. } if let Err(reason) = book.instrument().check_quantity(quantity) => {
+                CommandResult::Rejected(reason)
+            }
+            Command::Limit { trader, side, price, quantity, tif } => {
+                CommandResult::from_order(book.limit_order(trader, side, price, quantity, tif))
+            }
+            Command::Stop { trader, side, stop_price, quantity } => {
+                let (order_id, trades) = book.stop_order(trader, side, stop_price, quantity);
+                CommandResult::Accepted { order_id, trades }
+            }
+            Command::StopLimit { trader, side, stop_price, limit_price, quantity } => {
+                let (order_id, trades) =
+                    book.stop_limit_order(trader, side, stop_price, limit_price, quantity);
+                CommandResult::Accepted { order_id, trades }
+            }
+            Command::Cancel { order_id } => CommandResult::Cancelled {
+                order_id,
+                success: book.cancel_order(order_id).is_ok(),
+            },
+            Command::Amend { order_id, quantity } => CommandResult::Amended {
+                order_id,
+                success: book.reduce_order(order_id, quantity),
+            },
+            Command::Expire { now_ns } => CommandResult::Expired {
+                order_ids: book.expire_orders(now_ns),
+            },
+        }
+    }
+
+    /// 在订单簿上执行指令，限价单附带客户端订单号（同一交易员重复的订单号以`DuplicateOrder`拒绝）
+    ///
+    /// 其他指令或`client_order_id`为None时同`execute`。
+    pub fn execute_with_client_id(&self, book: &mut OrderBook, client_order_id: Option<u64>) -> CommandResult {
+        let Some(client_order_id) = client_order_id else {
+            return self.execute(book);
+        };
+        match *self {
+            Command::Limit { trader, .. } if book.client_order(trader, client_order_id).is_some() => {
+                CommandResult::Rejected(RejectReason::DuplicateOrder)
+            }
+            Command::Limit { side, price, quantity, .. }
+                if let Err(reason) = book.admit_order(side, price, quantity) =>
+            {
+                CommandResult::Rejected(reason)
+            }
+            Command::Limit { trader, side, price, quantity, tif } => CommandResult::from_order(
+                book.limit_order_with_client_id(trader, client_order_id, side, price, quantity, tif),
+            ),
+            _ => self.execute(book),
+        }
+    }
+}
+
+/// 拒绝原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// 排队时间超过延迟预算
+    TooLate,
+    /// 未知品种
+    UnknownSymbol,
+    /// 订单簿处于只撤单模式
+    CancelOnly,
+    /// 价格超出价格阶梯范围
+    PriceOutOfRange,
+    /// 订单内存池已满
+    CapacityExhausted,
+    /// 波动熔断期间需立即成交的订单
+    Halted,
+    /// 价格不在品种tick上
+    OffTick,
+    /// 数量不是整手
+    InvalidLotSize,
+    /// 限价偏离参考价超出价格带
+    PriceBand,
+    /// 只减仓订单没有可减的持仓
+    ReduceOnly,
+    /// 客户端订单号重复（重发的订单）
+    DuplicateOrder,
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectReason::TooLate => write!(f, "TOO_LATE"),
+            RejectReason::UnknownSymbol => write!(f, "UNKNOWN_SYMBOL"),
+            RejectReason::CancelOnly => write!(f, "CANCEL_ONLY"),
+            RejectReason::PriceOutOfRange => write!(f, "PRICE_OUT_OF_RANGE"),
+            RejectReason::CapacityExhausted => write!(f, "CAPACITY_EXHAUSTED"),
+            RejectReason::Halted => write!(f, "HALTED"),
+            RejectReason::OffTick => write!(f, "OFF_TICK"),
+            RejectReason::InvalidLotSize => write!(f, "INVALID_LOT_SIZE"),
+            RejectReason::PriceBand => write!(f, "PRICE_BAND"),
+            RejectReason::ReduceOnly => write!(f, "REDUCE_ONLY"),
+            RejectReason::DuplicateOrder => write!(f, "DUPLICATE_ORDER"),
+        }
+    }
+}
+
+/// 订单簿操作错误
+///
+/// 容量、价格和数量问题带出错的值，其余拒绝原因原样携带。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum OrderBookError {
+    #[error("order arena capacity exceeded")]
+    CapacityExceeded,
+    #[error("price {0} out of range")]
+    PriceOutOfRange(Price),
+    #[error("invalid quantity {0}")]
+    InvalidQuantity(Quantity),
+    #[error("price {price} outside band around reference {reference}")]
+    PriceOutsideBand { price: Price, reference: Price },
+    #[error("duplicate client order id {client_order_id} (order {order_id})")]
+    DuplicateClientOrderId { client_order_id: u64, order_id: OrderId },
+    #[error("order rejected: {0}")]
+    Rejected(RejectReason),
+    #[error("unknown order {0}")]
+    UnknownOrder(OrderId),
+}
+
+impl OrderBookError {
+    /// 由拒绝原因和订单参数构造
+    pub fn rejection(reason: RejectReason, price: Price, quantity: Quantity) -> Self {
+        match reason {
+            RejectReason::CapacityExhausted => OrderBookError::CapacityExceeded,
+            RejectReason::PriceOutOfRange => OrderBookError::PriceOutOfRange(price),
+            RejectReason::InvalidLotSize => OrderBookError::InvalidQuantity(quantity),
+            reason => OrderBookError::Rejected(reason),
+        }
+    }
+
+    /// 对应的拒绝原因（未知订单没有对应原因）
+    pub fn reject_reason(&self) -> Option<RejectReason> {
+        match *self {
+            OrderBookError::CapacityExceeded => Some(RejectReason::CapacityExhausted),
+            OrderBookError::PriceOutOfRange(_) => Some(RejectReason::PriceOutOfRange),
+            OrderBookError::InvalidQuantity(_) => Some(RejectReason::InvalidLotSize),
+            OrderBookError::PriceOutsideBand { .. } => Some(RejectReason::PriceBand),
+            OrderBookError::DuplicateClientOrderId { .. } => Some(RejectReason::DuplicateOrder),
+            OrderBookError::Rejected(reason) => Some(reason),
+            OrderBookError::UnknownOrder(_) => None,
+        }
+    }
+}
+
+/// 指令执行结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandResult {
+    /// 新订单已受理
+    Accepted { order_id: OrderId, trades: Vec<Trade> },
+    /// 撤单结果
+    Cancelled { order_id: OrderId, success: bool },
+    /// 改单结果
+    Amended { order_id: OrderId, success: bool },
+    /// 到期撤销的订单
+    Expired { order_ids: Vec<OrderId> },
+    /// 指令被拒绝（未进入撮合）
+    Rejected(RejectReason),
+}
+
+impl CommandResult {
+    /// 由新订单的下单结果构造
+    fn from_order(result: Result<(OrderId, Vec<Trade>), OrderBookError>) -> Self {
+        match result {
+            Ok((order_id, trades)) => CommandResult::Accepted { order_id, trades },
+            Err(error) => CommandResult::Rejected(error.reject_reason().unwrap_or(RejectReason::CapacityExhausted)),
+        }
+    }
+
+    /// 是否被拒绝
+    #[inline]
+    pub fn is_rejected(&self) -> bool {
+        matches!(self, CommandResult::Rejected(_))
+    }
+}
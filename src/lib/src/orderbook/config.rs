@@ -0,0 +1,212 @@
+/// 撮合引擎配置
+///
+/// 把订单簿的可调参数（价格范围与tick、每手数量、成交分配、订单容量、价格阶梯、自成交防范、
+/// 资源压力、熔断和限价保护）汇总为`EngineConfig`，由`OrderBook::with_config`校验后创建订单簿。
+/// 每个订单簿持有自己的配置，同一进程内可以有多个参数不同的订单簿。
+
+use super::breaker::{CircuitBreakerConfig, PriceBand};
+use super::engine::ResourcePolicy;
+use super::instrument::InstrumentSpec;
+use super::ladder::LadderKind;
+use super::types::Price;
+use thiserror::Error;
+
+/// 默认品种的最高价格（以分为单位，$99,999.99）
+pub const DEFAULT_MAX_PRICE: u64 = 9_999_999;
+
+/// 默认订单容量
+pub const DEFAULT_MAX_ORDERS: usize = 1_000_000;
+
+/// 价格阶梯容量超过该值时自动选择稀疏阶梯，稠密阶梯也不允许超过该容量
+pub const DENSE_LADDER_LIMIT: usize = 1 << 24;
+
+/// 自成交防范（来单与同一交易员的挂单相遇时的处理）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelfTradePrevention {
+    /// 允许自成交
+    #[default]
+    Allow,
+    /// 撤销本人挂单（`OrderCancelled`），来单继续与后续挂单撮合
+    CancelResting,
+    /// 撤销来单剩余部分，已成交部分保留
+    CancelIncoming,
+}
+
+/// 配置错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ConfigError {
+    #[error("tick size must be positive")]
+    ZeroTickSize,
+    #[error("lot size must be positive")]
+    ZeroLotSize,
+    #[error("empty price range {min}..={max}")]
+    EmptyPriceRange { min: Price, max: Price },
+    #[error("order capacity must be between 1 and {}", u32::MAX)]
+    InvalidCapacity(usize),
+    #[error("dense ladder of {0} levels exceeds limit {limit}", limit = DENSE_LADDER_LIMIT)]
+    LadderTooLarge(usize),
+    #[error("utilization threshold {0}% out of range")]
+    InvalidThreshold(u8),
+    #[error("circuit breaker needs a positive move limit and window")]
+    InvalidCircuitBreaker,
+    #[error("price band deviation must be positive")]
+    InvalidPriceBand,
+}
+
+/// 撮合引擎配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineConfig {
+    /// 品种规格（价格范围、tick、每手数量、成交分配方式）
+    pub spec: InstrumentSpec,
+    /// 订单容量（内存池槽位数）
+    pub max_orders: usize,
+    /// 价格阶梯后端（None表示按价格范围自动选择）
+    pub ladder: Option<LadderKind>,
+    /// 自成交防范
+    pub self_trade: SelfTradePrevention,
+    /// 资源压力策略
+    pub resource_policy: ResourcePolicy,
+    /// 波动熔断（None表示关闭）
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// 限价保护（None表示关闭）
+    pub price_band: Option<PriceBand>,
+}
+
+impl Default for EngineConfig {
+    /// 默认品种规格、最高价格`DEFAULT_MAX_PRICE`、容量`DEFAULT_MAX_ORDERS`，不启用可选保护
+    fn default() -> Self {
+        let spec = InstrumentSpec::default().with_price_range(Price::MIN, Price::new(DEFAULT_MAX_PRICE).unwrap());
+        Self::new(spec)
+    }
+}
+
+impl EngineConfig {
+    /// 按品种规格创建配置（其余参数取默认值）
+    pub fn new(spec: InstrumentSpec) -> Self {
+        Self {
+            spec,
+            max_orders: DEFAULT_MAX_ORDERS,
+            ladder: None,
+            self_trade: SelfTradePrevention::Allow,
+            resource_policy: ResourcePolicy::default(),
+            circuit_breaker: None,
+            price_band: None,
+        }
+    }
+
+    /// 设置订单容量
+    pub fn with_max_orders(mut self, max_orders: usize) -> Self {
+        self.max_orders = max_orders;
+        self
+    }
+
+    /// 指定价格阶梯后端
+    pub fn with_ladder(mut self, ladder: LadderKind) -> Self {
+        self.ladder = Some(ladder);
+        self
+    }
+
+    /// 设置自成交防范
+    pub fn with_self_trade(mut self, self_trade: SelfTradePrevention) -> Self {
+        self.self_trade = self_trade;
+        self
+    }
+
+    /// 设置资源压力策略
+    pub fn with_resource_policy(mut self, policy: ResourcePolicy) -> Self {
+        self.resource_policy = policy;
+        self
+    }
+
+    /// 启用波动熔断
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(config);
+        self
+    }
+
+    /// 启用限价保护
+    pub fn with_price_band(mut self, band: PriceBand) -> Self {
+        self.price_band = Some(band);
+        self
+    }
+
+    /// 实际使用的价格阶梯后端
+    pub fn ladder_kind(&self) -> LadderKind {
+        self.ladder.unwrap_or(if self.spec.ladder_size() > DENSE_LADDER_LIMIT {
+            LadderKind::Sparse
+        } else {
+            LadderKind::Dense
+        })
+    }
+
+    /// 校验配置
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let spec = &self.spec;
+        if spec.tick_size == 0 {
+            return Err(ConfigError::ZeroTickSize);
+        }
+        if spec.lot_size == 0 {
+            return Err(ConfigError::ZeroLotSize);
+        }
+        if spec.min_price > spec.max_price {
+            return Err(ConfigError::EmptyPriceRange { min: spec.min_price, max: spec.max_price });
+        }
+        if self.max_orders == 0 || self.max_orders > u32::MAX as usize {
+            return Err(ConfigError::InvalidCapacity(self.max_orders));
+        }
+        if self.ladder_kind() == LadderKind::Dense && spec.ladder_size() > DENSE_LADDER_LIMIT {
+            return Err(ConfigError::LadderTooLarge(spec.ladder_size()));
+        }
+
+        let policy = &self.resource_policy;
+        if let Some(&pct) = policy.warn_thresholds.iter().chain(&policy.resume_below_pct).find(|&&pct| pct > 100) {
+            return Err(ConfigError::InvalidThreshold(pct));
+        }
+        if let Some(breaker) = &self.circuit_breaker
+            && (breaker.max_move_bps == 0 || breaker.window.is_zero())
+        {
+            return Err(ConfigError::InvalidCircuitBreaker);
+        }
+        if self.price_band.is_some_and(|band| band.max_deviation_bps == 0) {
+            return Err(ConfigError::InvalidPriceBand);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::px;
+    use std::time::Duration;
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(EngineConfig::default().validate(), Ok(()));
+        assert_eq!(EngineConfig::default().ladder_kind(), LadderKind::Dense);
+
+        let wide = InstrumentSpec::default().with_price_range(px(1), px(1 << 30));
+        assert_eq!(EngineConfig::new(wide).ladder_kind(), LadderKind::Sparse);
+        assert_eq!(
+            EngineConfig::new(wide).with_ladder(LadderKind::Dense).validate(),
+            Err(ConfigError::LadderTooLarge((1 << 30) + 1))
+        );
+
+        let mut spec = InstrumentSpec::default();
+        spec.tick_size = 0;
+        assert_eq!(EngineConfig::new(spec).validate(), Err(ConfigError::ZeroTickSize));
+        let inverted = InstrumentSpec::default().with_price_range(px(200), px(100));
+        assert_eq!(
+            EngineConfig::new(inverted).validate(),
+            Err(ConfigError::EmptyPriceRange { min: px(200), max: px(100) })
+        );
+
+        let config = EngineConfig::default();
+        assert_eq!(config.clone().with_max_orders(0).validate(), Err(ConfigError::InvalidCapacity(0)));
+        let policy = ResourcePolicy { warn_thresholds: vec![80, 120], resume_below_pct: None };
+        assert_eq!(config.clone().with_resource_policy(policy).validate(), Err(ConfigError::InvalidThreshold(120)));
+        let breaker = CircuitBreakerConfig { max_move_bps: 500, window: Duration::ZERO };
+        assert_eq!(config.clone().with_circuit_breaker(breaker).validate(), Err(ConfigError::InvalidCircuitBreaker));
+        assert_eq!(config.with_price_band(PriceBand::new(0)).validate(), Err(ConfigError::InvalidPriceBand));
+    }
+}
@@ -0,0 +1,603 @@
+/// L2增量深度生成
+///
+/// 每条指令执行后比较订单簿前N档与上次发布的视图，生成价格档位的增量
+/// （新增/变化/删除），并按批次分配单调递增的行情序列号，
+/// 下游只需按序应用增量即可维护与引擎一致的N档深度，无需自行比较全量快照。
+///
+/// 档位被挤出前N档时以`Removed`发布，重新进入时以`Added`发布。
+/// 晚加入的订阅者先取`snapshot()`（附带其对应的序列号，可经`DepthSnapshot`序列化传输），
+/// 再应用序列号更大的批次。
+/// 每个批次附带应用后N档的校验和（`BookDepth::checksum`），下游据此校验镜像。
+///
+/// 带宽受限的下游可经`DeltaConflator`合并：每个周期内同一档位的多次变化只发布最终状态。
+///
+/// 二进制格式（little-endian）:
+/// - 8字节序列号 + 4字节校验和 + 2字节增量数
+/// - 每条增量: 1字节动作 + 1字节方向 + 8字节价格 + 4字节数量 + 4字节订单数
+///
+/// 快照格式（little-endian）:
+/// - 8字节序列号 + 4字节校验和 + 2字节买方档数 + 2字节卖方档数
+/// - 每档（先买后卖，按价格优先顺序）: 8字节价格 + 4字节数量 + 4字节订单数
+
+use super::command::{Command, CommandResult};
+use super::engine::OrderBook;
+use super::journal::{invalid, read_array, read_price, read_side};
+use super::types::{BookDepth, DepthLevel, Price, Quantity, Side};
+use crate::monitor::domain::admin::ConflationInterval;
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+/// 单条增量编码长度
+pub const ENCODED_DELTA_LEN: usize = 1 + 1 + 8 + 4 + 4;
+
+/// 快照单档编码长度
+pub const ENCODED_LEVEL_LEN: usize = 8 + 4 + 4;
+
+/// 档位变化类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DeltaAction {
+    Added = 0,
+    Changed = 1,
+    Removed = 2,
+}
+
+impl DeltaAction {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(DeltaAction::Added),
+            1 => Some(DeltaAction::Changed),
+            2 => Some(DeltaAction::Removed),
+            _ => None,
+        }
+    }
+}
+
+/// 单个价格档位的变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthDelta {
+    pub action: DeltaAction,
+    pub side: Side,
+    pub price: Price,
+    /// 变化后的档位总量（删除时为0）
+    pub quantity: Quantity,
+    /// 变化后的订单数（删除时为0）
+    pub order_count: u32,
+}
+
+/// 一条指令产生的全部增量
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeltaBatch {
+    /// 行情序列号（从1开始，每个非空批次加1）
+    pub sequence: u64,
+    /// 应用本批次后前N档的校验和
+    pub checksum: u32,
+    /// 先删除后新增/变化，每侧按价格优先顺序
+    pub deltas: Vec<DepthDelta>,
+}
+
+impl DeltaBatch {
+    /// 编码为二进制
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let count = u16::try_from(self.deltas.len()).map_err(|_| invalid("too many deltas in batch".to_string()))?;
+        let mut buf = Vec::with_capacity(8 + 4 + 2 + self.deltas.len() * ENCODED_DELTA_LEN);
+        buf.extend_from_slice(&self.sequence.to_le_bytes());
+        buf.extend_from_slice(&self.checksum.to_le_bytes());
+        buf.extend_from_slice(&count.to_le_bytes());
+        for delta in &self.deltas {
+            buf.push(delta.action as u8);
+            buf.push(delta.side as u8);
+            buf.extend_from_slice(&delta.price.get().to_le_bytes());
+            buf.extend_from_slice(&delta.quantity.get().to_le_bytes());
+            buf.extend_from_slice(&delta.order_count.to_le_bytes());
+        }
+        writer.write_all(&buf)
+    }
+
+    /// 从二进制解码
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let sequence = u64::from_le_bytes(read_array(reader)?);
+        let checksum = u32::from_le_bytes(read_array(reader)?);
+        let count = u16::from_le_bytes(read_array(reader)?);
+
+        let mut deltas = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let [action] = read_array(reader)?;
+            let action = DeltaAction::from_u8(action).ok_or_else(|| invalid(format!("unknown delta action {}", action)))?;
+            let side = read_side(reader)?;
+            let price = read_price(reader)?;
+            // 删除的档位数量为0
+            let quantity = Quantity::new(u32::from_le_bytes(read_array(reader)?)).unwrap_or(Quantity::ZERO);
+            let order_count = u32::from_le_bytes(read_array(reader)?);
+            deltas.push(DepthDelta {
+                action,
+                side,
+                price,
+                quantity,
+                order_count,
+            });
+        }
+        Ok(Self { sequence, checksum, deltas })
+    }
+
+    /// 应用到本地N档深度上（下游维护镜像订单簿用）
+    pub fn apply_to(&self, depth: &mut BookDepth) {
+        for delta in &self.deltas {
+            delta.apply_to(depth);
+        }
+    }
+
+    /// 应用后的本地前`levels`档是否与引擎一致
+    #[inline]
+    pub fn verify(&self, depth: &BookDepth, levels: usize) -> bool {
+        depth.checksum(levels) == self.checksum
+    }
+}
+
+/// 序列化传输的深度快照（晚加入的订阅者以此初始化镜像）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DepthSnapshot {
+    /// 快照已包含的最后一个批次的序列号
+    pub sequence: u64,
+    /// 快照前N档的校验和
+    pub checksum: u32,
+    pub depth: BookDepth,
+}
+
+impl DepthSnapshot {
+    /// 编码为二进制
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let bids = u16::try_from(self.depth.bids.len()).map_err(|_| invalid("too many levels in snapshot".to_string()))?;
+        let asks = u16::try_from(self.depth.asks.len()).map_err(|_| invalid("too many levels in snapshot".to_string()))?;
+        let mut buf = Vec::with_capacity(8 + 4 + 2 + 2 + (bids as usize + asks as usize) * ENCODED_LEVEL_LEN);
+        buf.extend_from_slice(&self.sequence.to_le_bytes());
+        buf.extend_from_slice(&self.checksum.to_le_bytes());
+        buf.extend_from_slice(&bids.to_le_bytes());
+        buf.extend_from_slice(&asks.to_le_bytes());
+        for level in self.depth.bids.iter().chain(&self.depth.asks) {
+            buf.extend_from_slice(&level.price.get().to_le_bytes());
+            buf.extend_from_slice(&level.quantity.get().to_le_bytes());
+            buf.extend_from_slice(&level.order_count.to_le_bytes());
+        }
+        writer.write_all(&buf)
+    }
+
+    /// 从二进制解码
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let sequence = u64::from_le_bytes(read_array(reader)?);
+        let checksum = u32::from_le_bytes(read_array(reader)?);
+        let bids = u16::from_le_bytes(read_array(reader)?);
+        let asks = u16::from_le_bytes(read_array(reader)?);
+
+        let mut read_levels = |count: u16| -> io::Result<Vec<DepthLevel>> {
+            (0..count)
+                .map(|_| {
+                    let price = read_price(reader)?;
+                    let quantity = Quantity::new(u32::from_le_bytes(read_array(reader)?))
+                        .ok_or_else(|| invalid("zero quantity in snapshot level".to_string()))?;
+                    let order_count = u32::from_le_bytes(read_array(reader)?);
+                    Ok(DepthLevel { price, quantity, order_count })
+                })
+                .collect()
+        };
+        let bids = read_levels(bids)?;
+        let asks = read_levels(asks)?;
+        Ok(Self {
+            sequence,
+            checksum,
+            depth: BookDepth { bids, asks },
+        })
+    }
+}
+
+impl DepthDelta {
+    /// 应用到本地深度上
+    pub fn apply_to(&self, depth: &mut BookDepth) {
+        let levels = match self.side {
+            Side::Buy => &mut depth.bids,
+            Side::Sell => &mut depth.asks,
+        };
+        let position = levels.iter().position(|level| level.price == self.price);
+        match (self.action, position) {
+            (DeltaAction::Removed, Some(index)) => {
+                levels.remove(index);
+            }
+            (DeltaAction::Added | DeltaAction::Changed, Some(index)) => {
+                levels[index].quantity = self.quantity;
+                levels[index].order_count = self.order_count;
+            }
+            (DeltaAction::Added | DeltaAction::Changed, None) => {
+                let level = DepthLevel {
+                    price: self.price,
+                    quantity: self.quantity,
+                    order_count: self.order_count,
+                };
+                let index = levels.partition_point(|l| better(self.side, l.price, self.price));
+                levels.insert(index, level);
+            }
+            (DeltaAction::Removed, None) => {}
+        }
+    }
+
+    /// 相对于`depth`（应用前的状态）的逆操作
+    pub fn inverse(&self, depth: &BookDepth) -> DepthDelta {
+        let levels = match self.side {
+            Side::Buy => &depth.bids,
+            Side::Sell => &depth.asks,
+        };
+        match levels.iter().find(|level| level.price == self.price) {
+            Some(previous) => DepthDelta {
+                action: DeltaAction::Changed,
+                side: self.side,
+                price: previous.price,
+                quantity: previous.quantity,
+                order_count: previous.order_count,
+            },
+            None => DepthDelta {
+                action: DeltaAction::Removed,
+                side: self.side,
+                price: self.price,
+                quantity: Quantity::ZERO,
+                order_count: 0,
+            },
+        }
+    }
+}
+
+/// `a`是否比`b`优先
+#[inline]
+fn better(side: Side, a: Price, b: Price) -> bool {
+    match side {
+        Side::Buy => a > b,
+        Side::Sell => a < b,
+    }
+}
+
+/// L2增量生成器
+#[derive(Debug)]
+pub struct DepthDeltaGenerator {
+    depth: usize,
+    sequence: u64,
+    view: BookDepth,
+}
+
+impl DepthDeltaGenerator {
+    /// 跟踪前`depth`档
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            sequence: 0,
+            view: BookDepth::default(),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// 最后发布的序列号
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// 当前发布视图及其序列号（晚加入的订阅者以此初始化）
+    pub fn snapshot(&self) -> (u64, &BookDepth) {
+        (self.sequence, &self.view)
+    }
+
+    /// 当前发布视图的校验和
+    pub fn checksum(&self) -> u32 {
+        self.view.checksum(self.depth)
+    }
+
+    /// 当前发布视图的可序列化快照
+    pub fn depth_snapshot(&self) -> DepthSnapshot {
+        DepthSnapshot {
+            sequence: self.sequence,
+            checksum: self.checksum(),
+            depth: self.view.clone(),
+        }
+    }
+
+    /// 与订单簿当前状态比较，生成增量批次；没有变化时返回None
+    pub fn update(&mut self, book: &OrderBook) -> Option<DeltaBatch> {
+        let current = book.depth(self.depth);
+        let deltas = diff(&self.view, &current);
+        self.view = current;
+
+        if deltas.is_empty() {
+            return None;
+        }
+        self.sequence += 1;
+        Some(DeltaBatch {
+            sequence: self.sequence,
+            checksum: self.checksum(),
+            deltas,
+        })
+    }
+
+    /// 执行指令并生成其增量
+    pub fn execute(&mut self, book: &mut OrderBook, command: &Command) -> (CommandResult, Option<DeltaBatch>) {
+        let result = command.execute(book);
+        let batch = self.update(book);
+        (result, batch)
+    }
+}
+
+/// L2增量合并器
+///
+/// 应用上游逐条指令的批次维护最新视图，按固定周期与上次发布的视图比较后发布一个合并批次，
+/// 每个档位每周期至多一条增量；周期内出现又消失的档位不发布。
+/// 合并批次使用自己的序列号，校验和含义与上游相同。
+/// 设置运行时句柄（`with_runtime_interval`）后每次`poll`读取当前周期，管理通道的调整立即生效。
+#[derive(Debug)]
+pub struct DeltaConflator {
+    depth: usize,
+    interval_ns: u64,
+    /// 运行时可调周期（设置后取代`interval_ns`，None值表示不合并）
+    runtime_interval: Option<ConflationInterval>,
+    sequence: u64,
+    /// 上次发布时的视图
+    published: BookDepth,
+    /// 应用上游批次后的最新视图
+    latest: BookDepth,
+    last_emit_ns: Option<u64>,
+}
+
+impl DeltaConflator {
+    /// 对前`depth`档（与上游生成器一致）按`interval`周期合并
+    pub fn new(depth: usize, interval: Duration) -> Self {
+        Self {
+            depth,
+            interval_ns: interval.as_nanos() as u64,
+            runtime_interval: None,
+            sequence: 0,
+            published: BookDepth::default(),
+            latest: BookDepth::default(),
+            last_emit_ns: None,
+        }
+    }
+
+    /// 从运行时配置读取合并周期（如`RuntimeConfig::conflation("bbo", ..)`）
+    pub fn with_runtime_interval(mut self, interval: ConflationInterval) -> Self {
+        self.runtime_interval = Some(interval);
+        self
+    }
+
+    /// 当前合并周期（零表示每次`poll`都发布）
+    #[inline]
+    pub fn interval(&self) -> Duration {
+        match &self.runtime_interval {
+            Some(interval) => interval.get().unwrap_or(Duration::ZERO),
+            None => Duration::from_nanos(self.interval_ns),
+        }
+    }
+
+    /// 最后发布的合并序列号
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// 最后发布的视图及其序列号（晚加入的订阅者以此初始化）
+    pub fn snapshot(&self) -> (u64, &BookDepth) {
+        (self.sequence, &self.published)
+    }
+
+    /// 计入一个上游批次
+    pub fn push(&mut self, batch: &DeltaBatch) {
+        batch.apply_to(&mut self.latest);
+    }
+
+    /// 距上次发布已满一个周期时发布合并批次；未到时间或没有净变化时返回None
+    pub fn poll(&mut self, now_ns: u64) -> Option<DeltaBatch> {
+        let interval_ns = self.interval().as_nanos() as u64;
+        if self.last_emit_ns.is_some_and(|last| now_ns.saturating_sub(last) < interval_ns) {
+            return None;
+        }
+        let batch = self.flush()?;
+        self.last_emit_ns = Some(now_ns);
+        Some(batch)
+    }
+
+    /// 立即发布合并批次（不检查周期）；没有净变化时返回None
+    pub fn flush(&mut self) -> Option<DeltaBatch> {
+        let deltas = diff(&self.published, &self.latest);
+        if deltas.is_empty() {
+            return None;
+        }
+        self.published.clone_from(&self.latest);
+        self.sequence += 1;
+        Some(DeltaBatch {
+            sequence: self.sequence,
+            checksum: self.published.checksum(self.depth),
+            deltas,
+        })
+    }
+}
+
+/// `old`到`new`的增量（先删除后新增/变化，每侧按价格优先顺序）
+fn diff(old: &BookDepth, new: &BookDepth) -> Vec<DepthDelta> {
+    let mut deltas = Vec::new();
+    diff_side(Side::Buy, &old.bids, &new.bids, &mut deltas);
+    diff_side(Side::Sell, &old.asks, &new.asks, &mut deltas);
+    deltas
+}
+
+fn diff_side(side: Side, old: &[DepthLevel], new: &[DepthLevel], deltas: &mut Vec<DepthDelta>) {
+    for level in old {
+        if !new.iter().any(|l| l.price == level.price) {
+            deltas.push(DepthDelta {
+                action: DeltaAction::Removed,
+                side,
+                price: level.price,
+                quantity: Quantity::ZERO,
+                order_count: 0,
+            });
+        }
+    }
+
+    for level in new {
+        let action = match old.iter().find(|l| l.price == level.price) {
+            None => DeltaAction::Added,
+            Some(previous) if previous != level => DeltaAction::Changed,
+            Some(_) => continue,
+        };
+        deltas.push(DepthDelta {
+            action,
+            side,
+            price: level.price,
+            quantity: level.quantity,
+            order_count: level.order_count,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty, TimeInForce, TraderId};
+
+    fn limit(side: Side, price: u32, quantity: u32) -> Command {
+        Command::Limit {
+            trader: TraderId::from_str("T"),
+            side,
+            price: px(price),
+            quantity: qty(quantity),
+            tif: TimeInForce::Gtc,
+        }
+    }
+
+    fn delta(action: DeltaAction, side: Side, price: u32, quantity: u32, order_count: u32) -> DepthDelta {
+        DepthDelta {
+            action,
+            side,
+            price: px(price),
+            quantity: Quantity::new(quantity).unwrap_or(Quantity::ZERO),
+            order_count,
+        }
+    }
+
+    #[test]
+    fn test_deltas_per_command() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let mut generator = DepthDeltaGenerator::new(2);
+
+        let (_, batch) = generator.execute(&mut book, &limit(Side::Buy, 100, 5));
+        assert_eq!(batch.unwrap(), DeltaBatch {
+            sequence: 1,
+            checksum: crate::orderbook::wal::crc32(b"100:5"),
+            deltas: vec![delta(DeltaAction::Added, Side::Buy, 100, 5, 1)],
+        });
+
+        let (_, batch) = generator.execute(&mut book, &limit(Side::Buy, 100, 3));
+        assert_eq!(batch.unwrap().deltas, vec![delta(DeltaAction::Changed, Side::Buy, 100, 8, 2)]);
+
+        generator.execute(&mut book, &limit(Side::Buy, 99, 1));
+        // 新的最优价把99挤出前2档
+        let (_, batch) = generator.execute(&mut book, &limit(Side::Buy, 101, 1));
+        assert_eq!(batch.unwrap(), DeltaBatch {
+            sequence: 4,
+            checksum: crate::orderbook::wal::crc32(b"101:1:100:8"),
+            deltas: vec![
+                delta(DeltaAction::Removed, Side::Buy, 99, 0, 0),
+                delta(DeltaAction::Added, Side::Buy, 101, 1, 1),
+            ],
+        });
+
+        // 吃掉101整档和100的一部分
+        let (_, batch) = generator.execute(&mut book, &limit(Side::Sell, 100, 2));
+        assert_eq!(batch.unwrap().deltas, vec![
+            delta(DeltaAction::Removed, Side::Buy, 101, 0, 0),
+            delta(DeltaAction::Changed, Side::Buy, 100, 7, 2),
+            delta(DeltaAction::Added, Side::Buy, 99, 1, 1),
+        ]);
+
+        // 不影响前N档的指令不产生批次，序列号不变
+        let (_, batch) = generator.execute(&mut book, &Command::Cancel { order_id: 999 });
+        assert!(batch.is_none());
+        assert_eq!(generator.sequence(), 5);
+    }
+
+    #[test]
+    fn test_checksum_interleaves_levels() {
+        let level = |price, quantity| DepthLevel { price: px(price), quantity: qty(quantity), order_count: 1 };
+        let depth = BookDepth {
+            bids: vec![level(100, 5), level(99, 2), level(98, 7)],
+            asks: vec![level(101, 3)],
+        };
+        assert_eq!(depth.checksum(2), crate::orderbook::wal::crc32(b"100:5:101:3:99:2"));
+        assert_eq!(depth.checksum(10), crate::orderbook::wal::crc32(b"100:5:101:3:99:2:98:7"));
+
+        let mut stale = depth.clone();
+        stale.bids[1].quantity = qty(1);
+        assert_ne!(stale.checksum(2), depth.checksum(2));
+        // 超出校验档数的差异不影响结果
+        assert_eq!(stale.checksum(1), depth.checksum(1));
+    }
+
+    #[test]
+    fn test_mirror_follows_engine() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let mut generator = DepthDeltaGenerator::new(3);
+        let mut mirror = BookDepth::default();
+        assert_eq!(mirror.checksum(3), 0);
+
+        let commands = [
+            limit(Side::Sell, 105, 4),
+            limit(Side::Sell, 103, 2),
+            limit(Side::Buy, 98, 6),
+            limit(Side::Buy, 104, 5),
+            Command::Cancel { order_id: 1 },
+            limit(Side::Sell, 97, 10),
+        ];
+        for command in &commands {
+            if let (_, Some(batch)) = generator.execute(&mut book, command) {
+                let mut encoded = Vec::new();
+                batch.write_to(&mut encoded).unwrap();
+                let decoded = DeltaBatch::read_from(&mut encoded.as_slice()).unwrap();
+                assert_eq!(decoded, batch);
+                decoded.apply_to(&mut mirror);
+                assert!(decoded.verify(&mirror, 3));
+            }
+            assert_eq!(mirror, book.depth(3));
+            assert_eq!(mirror.checksum(3), generator.checksum());
+        }
+    }
+
+    #[test]
+    fn test_conflates_per_interval() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let mut generator = DepthDeltaGenerator::new(3);
+        let mut conflator = DeltaConflator::new(3, Duration::from_millis(50));
+        let mut mirror = BookDepth::default();
+        let ms = 1_000_000;
+
+        let mut run = |book: &mut OrderBook, conflator: &mut DeltaConflator, command: Command| {
+            if let (_, Some(batch)) = generator.execute(book, &command) {
+                conflator.push(&batch);
+            }
+        };
+        run(&mut book, &mut conflator, limit(Side::Buy, 100, 5));
+        let batch = conflator.poll(0).unwrap();
+        batch.apply_to(&mut mirror);
+        assert_eq!(batch.sequence, 1);
+
+        // 周期内同一档位多次变化只发布最终状态，出现又消失的档位不发布
+        run(&mut book, &mut conflator, limit(Side::Buy, 100, 1));
+        run(&mut book, &mut conflator, limit(Side::Buy, 100, 2));
+        run(&mut book, &mut conflator, limit(Side::Sell, 105, 4));
+        run(&mut book, &mut conflator, limit(Side::Buy, 105, 4));
+        assert!(conflator.poll(49 * ms).is_none());
+
+        let batch = conflator.poll(50 * ms).unwrap();
+        assert_eq!(batch.sequence, 2);
+        assert_eq!(batch.deltas, vec![delta(DeltaAction::Changed, Side::Buy, 100, 8, 3)]);
+        batch.apply_to(&mut mirror);
+        assert!(batch.verify(&mirror, 3));
+        assert_eq!(mirror, book.depth(3));
+
+        // 没有净变化时不发布
+        assert!(conflator.poll(200 * ms).is_none());
+        assert_eq!(conflator.snapshot(), (2, &book.depth(3)));
+    }
+}
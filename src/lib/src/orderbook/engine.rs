@@ -3,50 +3,672 @@
 /// 实现价格-时间优先的限价订单簿，具有O(1)订单放置
 /// 和使用线性价格点数组的高效匹配。
 
-use super::arena::OrderArena;
-use super::types::{OrderEntry, OrderId, Price, PricePoint, Quantity, Side, Trade, TraderId};
-use std::collections::HashMap;
+use super::arena::{ArenaHandle, OrderArena};
+use super::breaker::{uncross, CircuitBreakerConfig, PriceBand, Uncross, VolatilityGuard};
+use super::command::{Command, CommandResult, OrderBookError, RejectReason};
+use super::config::{ConfigError, EngineConfig, SelfTradePrevention, DENSE_LADDER_LIMIT};
+use super::instrument::{AllocationPolicy, InstrumentSpec};
+use super::journal::{invalid, read_array, read_price, read_quantity, read_side};
+use super::ladder::{LadderKind, PriceLadder};
+#[cfg(feature = "latency-histogram")]
+use super::latency::LatencyRecorder;
+use super::latency::LatencySummary;
+use super::order_map::OrderIndexMap;
+use super::stop::{StopBook, StopOrder};
+use super::types::{
+    BookDepth, BookEvent, DepthLevel, FillEstimate, OrderEntry, OrderId, Price, PricePoint, Quantity, Side, TimeInForce,
+    Trade, TradeId, TraderId, TraderStats, TradingMode,
+};
+use crate::monitor::domain::admin::FeatureFlag;
+use crate::timing::{system_clock, Clock};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// 状态快照魔数与版本
+const STATE_MAGIC: &[u8; 4] = b"RLOB";
+const STATE_VERSION: u16 = 2;
+
+/// 成交输出接口（例如持久化到时序存储）
+///
+/// 成交按`trade_id`顺序逐笔推送，下游无需读取`trades()`。
+/// 闭包和`crossbeam`通道发送端可直接作为成交输出；通道有界且已满时撮合线程会阻塞等待，
+/// 接收端已关闭时成交被丢弃。
+pub trait TradeSink: Send {
+    /// 每笔成交执行后调用
+    fn on_trade(&mut self, trade: &Trade);
+}
+
+impl<F> TradeSink for F
+where
+    F: FnMut(&Trade) + Send,
+{
+    fn on_trade(&mut self, trade: &Trade) {
+        self(trade)
+    }
+}
+
+impl TradeSink for crossbeam::channel::Sender<Trade> {
+    fn on_trade(&mut self, trade: &Trade) {
+        let _ = self.send(*trade);
+    }
+}
+
+/// 多个成交输出（见`OrderBook::add_trade_sink`）
+struct TradeFanout(Vec<Box<dyn TradeSink>>);
+
+impl TradeSink for TradeFanout {
+    fn on_trade(&mut self, trade: &Trade) {
+        for sink in &mut self.0 {
+            sink.on_trade(trade);
+        }
+    }
+}
+
+/// 最优价变化监听接口（报价策略无需轮询`best_bid`/`best_ask`）
+pub trait BboListener: Send {
+    /// 最佳买价或最佳卖价变化时调用，`side`为变化的一侧
+    fn on_bbo_change(&mut self, side: Side, old: Option<Price>, new: Option<Price>);
+}
+
+/// 逐笔订单簿事件监听接口（用于下游重建MBO视图）
+pub trait BookEventListener: Send {
+    /// 订单簿每次变更时按发生顺序调用
+    fn on_event(&mut self, event: &BookEvent);
+}
+
+/// 资源压力策略
+///
+/// 订单内存池利用率每次向上越过告警阈值时发送`CapacityWarning`事件；
+/// 内存池耗尽时进入只撤单模式（`ModeChanged`），拒绝新订单而不是panic。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourcePolicy {
+    /// 告警阈值（百分比，升序）
+    pub warn_thresholds: Vec<u8>,
+    /// 因耗尽进入只撤单模式后，利用率低于该百分比时自动恢复（None表示只能手动恢复）
+    pub resume_below_pct: Option<u8>,
+}
 
-/// 最大价格级别（以分为单位）- 根据预期价格范围调整
-const MAX_PRICE: usize = 10_000_000; // 最高价格 $100,000
+impl Default for ResourcePolicy {
+    fn default() -> Self {
+        Self {
+            warn_thresholds: vec![80, 95],
+            resume_below_pct: Some(90),
+        }
+    }
+}
+
+/// 熔断期间排队等待集合竞价的订单
+#[derive(Debug, Clone, Copy)]
+struct QueuedOrder {
+    order_id: OrderId,
+    trader: TraderId,
+    side: Side,
+    price: Price,
+    quantity: Quantity,
+    tif: TimeInForce,
+}
 
 /// 订单簿匹配引擎
 pub struct OrderBook {
     /// 买单价格点（出价）
-    bids: Vec<PricePoint>,
+    bids: Box<dyn PriceLadder>,
     /// 卖单价格点（要价）
-    asks: Vec<PricePoint>,
+    asks: Box<dyn PriceLadder>,
     /// 订单条目的内存池
     arena: OrderArena,
     /// 订单ID到内存池索引的映射（用于快速取消）
-    order_index: HashMap<OrderId, usize>,
+    order_index: OrderIndexMap,
     /// 最佳买价（最高买入价）
     bid_max: Option<Price>,
     /// 最佳卖价（最低卖出价）
     ask_min: Option<Price>,
     /// 下一个订单ID
     next_order_id: OrderId,
+    /// 下一个成交ID
+    next_trade_id: TradeId,
     /// 交易执行历史
     trades: Vec<Trade>,
+    /// 可选的成交输出
+    trade_sink: Option<Box<dyn TradeSink>>,
+    /// 可选的逐笔事件监听器
+    event_listener: Option<Box<dyn BookEventListener>>,
+    /// 可选的最优价变化监听器
+    bbo_listener: Option<Box<dyn BboListener>>,
+    /// 等待触发的止损单
+    stops: StopBook,
+    /// GTD挂单到期索引 (到期时间, 订单ID)，已成交或已撤销的订单在到期扫描时跳过
+    expiries: BTreeSet<(u64, OrderId)>,
+    /// 最新成交价
+    last_trade_price: Option<Price>,
+    /// 资源压力策略
+    policy: ResourcePolicy,
+    /// 当前撮合模式
+    mode: TradingMode,
+    /// 是否因内存池耗尽自动进入只撤单模式
+    auto_halted: bool,
+    /// 当前已越过的告警阈值个数
+    warned: usize,
+    /// 波动熔断价格带（未配置时为None）
+    breaker: Option<VolatilityGuard>,
+    /// 熔断期间排队的穿价订单（按到达顺序）
+    auction: Vec<QueuedOrder>,
+    /// 限价保护（未配置时为None）
+    price_band: Option<PriceBand>,
+    /// 品种规格（tick、价格范围、每手数量）
+    spec: InstrumentSpec,
+    /// 自成交防范
+    self_trade: SelfTradePrevention,
+    /// 各交易员的挂单与成交汇总
+    traders: HashMap<TraderId, TraderStats>,
+    /// (交易员, 客户端订单号) 到订单ID的去重索引
+    client_orders: HashMap<(TraderId, u64), OrderId>,
+    /// 成交时间戳、熔断时间窗和到期扫描使用的时间源
+    clock: Arc<dyn Clock>,
+    /// 累计计数（监控指标）
+    counters: EngineCounters,
+    /// 下单与撤单时延直方图
+    #[cfg(feature = "latency-histogram")]
+    latency: LatencyRecorder,
+    /// 时延采样的运行时开关（未设置时始终采样）
+    #[cfg(feature = "latency-histogram")]
+    latency_flag: Option<FeatureFlag>,
 }
 
 impl OrderBook {
     /// 创建新的订单簿
     pub fn new() -> Self {
-        Self::with_capacity(MAX_PRICE, 1_000_000)
+        Self::with_config(EngineConfig::default()).expect("default engine config is valid")
+    }
+
+    /// 按引擎配置创建订单簿（配置无效时返回错误）
+    pub fn with_config(config: EngineConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+        let mut book = Self::with_ladder(config.ladder_kind(), config.spec.ladder_size(), config.max_orders);
+        book.spec = config.spec;
+        book.self_trade = config.self_trade;
+        book.set_resource_policy(config.resource_policy);
+        book.set_circuit_breaker(config.circuit_breaker);
+        book.set_price_band(config.price_band);
+        Ok(book)
     }
 
-    /// 创建指定容量的新订单簿
+    /// 按品种规格创建订单簿（价格阶梯容量由最高价格决定，过大时使用稀疏阶梯）
+    pub fn with_spec(spec: InstrumentSpec, max_orders: usize) -> Self {
+        let size = spec.ladder_size();
+        let kind = if size > DENSE_LADDER_LIMIT { LadderKind::Sparse } else { LadderKind::Dense };
+        let mut book = Self::with_ladder(kind, size, max_orders);
+        book.spec = spec;
+        book
+    }
+
+    /// 创建指定容量的新订单簿（稠密价格阶梯）
     pub fn with_capacity(max_price: usize, max_orders: usize) -> Self {
+        Self::with_ladder(LadderKind::Dense, max_price, max_orders)
+    }
+
+    /// 创建使用指定价格阶梯后端的订单簿
+    pub fn with_ladder(kind: LadderKind, max_price: usize, max_orders: usize) -> Self {
+        Self::with_price_ladders(kind.build(max_price), kind.build(max_price), max_orders)
+    }
+
+    /// 使用自定义买卖价格阶梯创建订单簿
+    pub fn with_price_ladders(
+        bids: Box<dyn PriceLadder>,
+        asks: Box<dyn PriceLadder>,
+        max_orders: usize,
+    ) -> Self {
         Self {
-            bids: vec![PricePoint::default(); max_price],
-            asks: vec![PricePoint::default(); max_price],
+            bids,
+            asks,
             arena: OrderArena::new(max_orders),
-            order_index: HashMap::with_capacity(max_orders),
+            order_index: OrderIndexMap::with_capacity(max_orders),
             bid_max: None,
             ask_min: None,
             next_order_id: 1,
+            next_trade_id: 1,
             trades: Vec::new(),
+            trade_sink: None,
+            event_listener: None,
+            bbo_listener: None,
+            stops: StopBook::new(),
+            expiries: BTreeSet::new(),
+            last_trade_price: None,
+            policy: ResourcePolicy::default(),
+            mode: TradingMode::Normal,
+            auto_halted: false,
+            warned: 0,
+            breaker: None,
+            auction: Vec::new(),
+            price_band: None,
+            spec: InstrumentSpec::default(),
+            self_trade: SelfTradePrevention::Allow,
+            traders: HashMap::new(),
+            client_orders: HashMap::new(),
+            clock: system_clock(),
+            counters: EngineCounters::default(),
+            #[cfg(feature = "latency-histogram")]
+            latency: LatencyRecorder::new(),
+            #[cfg(feature = "latency-histogram")]
+            latency_flag: None,
+        }
+    }
+
+    /// 品种规格
+    #[inline]
+    pub fn instrument(&self) -> &InstrumentSpec {
+        &self.spec
+    }
+
+    /// 替换时间源（测试中注入`MockClock`得到确定的成交时间戳）
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// 当前时间源
+    #[inline]
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// 设置资源压力策略
+    pub fn set_resource_policy(&mut self, mut policy: ResourcePolicy) {
+        policy.warn_thresholds.sort_unstable();
+        self.policy = policy;
+        self.warned = 0;
+        self.update_pressure();
+    }
+
+    /// 资源压力策略
+    #[inline]
+    pub fn resource_policy(&self) -> &ResourcePolicy {
+        &self.policy
+    }
+
+    /// 当前撮合模式
+    #[inline]
+    pub fn trading_mode(&self) -> TradingMode {
+        self.mode
+    }
+
+    /// 手动切换撮合模式（发送`ModeChanged`事件）
+    pub fn set_trading_mode(&mut self, mode: TradingMode) {
+        self.auto_halted = false;
+        if self.mode != mode {
+            self.mode = mode;
+            self.emit(BookEvent::ModeChanged { mode });
+        }
+    }
+
+    /// 设置波动熔断（None表示关闭），以最新成交价为初始参考价
+    pub fn set_circuit_breaker(&mut self, config: Option<CircuitBreakerConfig>) {
+        self.breaker = config.map(|config| {
+            let mut guard = VolatilityGuard::new(config);
+            guard.reset(self.last_trade_price);
+            guard
+        });
+    }
+
+    /// 波动熔断配置
+    #[inline]
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreakerConfig> {
+        self.breaker.as_ref().map(VolatilityGuard::config)
+    }
+
+    /// 设置自成交防范
+    pub fn set_self_trade_prevention(&mut self, self_trade: SelfTradePrevention) {
+        self.self_trade = self_trade;
+    }
+
+    /// 自成交防范
+    #[inline]
+    pub fn self_trade_prevention(&self) -> SelfTradePrevention {
+        self.self_trade
+    }
+
+    /// 设置限价保护（None表示关闭）
+    pub fn set_price_band(&mut self, band: Option<PriceBand>) {
+        self.price_band = band;
+    }
+
+    /// 限价保护配置
+    #[inline]
+    pub fn price_band(&self) -> Option<&PriceBand> {
+        self.price_band.as_ref()
+    }
+
+    /// 限价保护的参考价：中间价，单边或空簿时取最新成交价
+    pub fn band_reference(&self) -> Option<Price> {
+        self.mid_price().or(self.last_trade_price)
+    }
+
+    /// 检查限价是否在价格带内（未配置或没有参考价时通过）
+    pub fn check_price_band(&self, price: Price) -> Result<(), RejectReason> {
+        match (self.price_band, self.band_reference()) {
+            (Some(band), Some(reference)) if !band.contains(reference, price) => Err(RejectReason::PriceBand),
+            _ => Ok(()),
+        }
+    }
+
+    /// 熔断期间排队等待集合竞价的订单数
+    #[inline]
+    pub fn queued_orders(&self) -> usize {
+        self.auction.len()
+    }
+
+    /// 集合竞价参考撮合价（撮合价、可成交量、买卖量差）
+    ///
+    /// 熔断或开盘前（`Halted`）阶段按当前排队订单与挂单计算，即此刻调用`reopen_auction`使用的撮合价；
+    /// 排队期间转入只撤单（资源压力或`halt`）时同样按剩余排队订单计算。
+    /// 连续撮合阶段或没有可成交数量时返回None。手动`set_trading_mode(Halted)`即进入开盘前阶段，
+    /// 穿价订单排队累积而不成交。
+    pub fn indicative_price(&self) -> Option<Uncross> {
+        if self.mode == TradingMode::Normal {
+            return None;
+        }
+        self.auction_uncross()
+    }
+
+    /// 价格是否与对手方最优价交叉
+    #[inline]
+    fn crosses(&self, side: Side, price: Price) -> bool {
+        match side {
+            Side::Buy => self.ask_min.is_some_and(|ask| price >= ask),
+            Side::Sell => self.bid_max.is_some_and(|bid| price <= bid),
+        }
+    }
+
+    /// 紧急停止（kill switch）：进入只撤单模式并撤销全部订单，返回被撤销的订单ID
+    ///
+    /// 停止期间新订单以`CancelOnly`拒绝，资源压力恢复不会自动解除，需调用`resume`。
+    /// 熔断排队订单一并撤出，`indicative_price`按空队列报告（None）。
+    pub fn halt(&mut self) -> Vec<OrderId> {
+        self.set_trading_mode(TradingMode::CancelOnly);
+        self.pull_all_orders()
+    }
+
+    /// 撤销全部挂单、等待触发的止损单和熔断排队订单，返回被撤销的订单ID
+    ///
+    /// 挂单按订单ID顺序撤销，逐笔发送`OrderCancelled`事件；撮合模式不变。
+    pub fn pull_all_orders(&mut self) -> Vec<OrderId> {
+        let mut pulled: Vec<OrderId> = self.order_index.iter().map(|(order_id, _)| order_id).collect();
+        pulled.sort_unstable();
+        for &order_id in &pulled {
+            if let Some(quantity) = self.deactivate(order_id) {
+                self.emit(BookEvent::OrderCancelled { order_id, quantity });
+            }
+        }
+        self.expiries.clear();
+        pulled.extend(self.stops.iter().map(|stop| stop.order_id));
+        self.stops = StopBook::new();
+        pulled.extend(self.auction.drain(..).map(|order| order.order_id));
+        self.relieve_pressure();
+        pulled
+    }
+
+    /// 恢复连续撮合（解除熔断或`halt`），返回排队订单进入连续撮合产生的成交
+    ///
+    /// 熔断排队的订单按到达顺序提交，可能再次触发熔断（此后的穿价订单继续排队）。
+    /// 直接`set_trading_mode(Normal)`不会释放排队订单。
+    pub fn resume(&mut self) -> Vec<Trade> {
+        self.set_trading_mode(TradingMode::Normal);
+        if let Some(guard) = self.breaker.as_mut() {
+            guard.reset(self.last_trade_price);
+        }
+        let mut trades = Vec::new();
+        let queued = std::mem::take(&mut self.auction);
+        self.release_queued(queued, &mut trades);
+        trades
+    }
+
+    /// 以集合竞价重新开盘，返回全部成交
+    ///
+    /// 按使成交量最大的单一价格撮合排队订单与挂单，竞价成交均以该价格成交；
+    /// 限价优于撮合价的排队订单未成交部分撤出（`OrderCancelled`）后以原限价重新进入连续撮合。
+    /// 没有可成交数量时等同于`resume`。
+    pub fn reopen_auction(&mut self) -> Vec<Trade> {
+        let Some(Uncross { price, .. }) = self.auction_uncross() else {
+            return self.resume();
+        };
+        self.set_trading_mode(TradingMode::Normal);
+        // 竞价阶段不受价格带限制
+        let guard = self.breaker.take();
+
+        let mut trades = Vec::new();
+        let mut repriced = Vec::new();
+        let mut later = Vec::new();
+        for order in std::mem::take(&mut self.auction) {
+            let eligible = match order.side {
+                Side::Buy => order.price >= price,
+                Side::Sell => order.price <= price,
+            };
+            if !eligible {
+                later.push(order);
+                continue;
+            }
+            self.execute(order.order_id, order.trader, order.side, price, order.quantity, order.tif, Some(price), &mut trades);
+            if order.price != price {
+                repriced.push(order);
+            }
+        }
+
+        self.breaker = guard;
+        if let Some(guard) = self.breaker.as_mut() {
+            guard.reset(self.last_trade_price);
+        }
+
+        for order in repriced {
+            if let Some(quantity) = self.deactivate(order.order_id) {
+                self.emit(BookEvent::OrderCancelled { order_id: order.order_id, quantity });
+                later.push(QueuedOrder { quantity, ..order });
+            }
+        }
+
+        self.activate_stops(0, &mut trades);
+        self.record_trades(&trades);
+
+        later.sort_unstable_by_key(|order| order.order_id);
+        self.release_queued(later, &mut trades);
+        trades
+    }
+
+    /// 按顺序提交排队订单；处于熔断且仍穿价的订单继续排队
+    fn release_queued(&mut self, queued: Vec<QueuedOrder>, trades: &mut Vec<Trade>) {
+        for order in queued {
+            if self.mode == TradingMode::Halted && self.crosses(order.side, order.price) {
+                self.auction.push(order);
+                continue;
+            }
+            let first_fill = trades.len();
+            self.execute(order.order_id, order.trader, order.side, order.price, order.quantity, order.tif, None, trades);
+            self.activate_stops(first_fill, trades);
+            self.record_trades(&trades[first_fill..]);
+        }
+    }
+
+    /// 排队订单与可成交挂单的集合竞价撮合价
+    fn auction_uncross(&self) -> Option<Uncross> {
+        if self.auction.is_empty() {
+            return None;
+        }
+        let mut orders: Vec<(Side, Price, Quantity)> =
+            self.auction.iter().map(|order| (order.side, order.price, order.quantity)).collect();
+
+        let highest_buy = orders.iter().filter(|o| o.0 == Side::Buy).map(|o| o.1).max();
+        let lowest_sell = orders.iter().filter(|o| o.0 == Side::Sell).map(|o| o.1).min();
+
+        // 只有价格不劣于对手方排队订单的挂单档位可能参与竞价
+        if let Some(sell) = lowest_sell {
+            let levels = self.iter_bids().take_while(|level| level.price >= sell);
+            orders.extend(levels.map(|level| (Side::Buy, level.price, level.quantity)));
+        }
+        if let Some(buy) = highest_buy {
+            let levels = self.iter_asks().take_while(|level| level.price <= buy);
+            orders.extend(levels.map(|level| (Side::Sell, level.price, level.quantity)));
+        }
+
+        uncross(&orders, self.last_trade_price)
+    }
+
+    /// 启动预热：写入稠密价格阶梯和内存池的全部存储，并把订单索引预留到内存池容量
+    ///
+    /// 在接入流量前调用一次，首批订单不再承担缺页和扩容开销；不改变订单簿内容。
+    pub fn prewarm(&mut self) {
+        self.bids.prewarm();
+        self.asks.prewarm();
+        self.arena.prewarm();
+        self.order_index.reserve(self.arena.capacity());
+    }
+
+    /// 预分配存储统计（用于确认预热效果）
+    pub fn allocation_stats(&self) -> AllocationStats {
+        let ladder_bytes = self.bids.allocated_bytes() + self.asks.allocated_bytes();
+        let arena_bytes = self.arena.allocated_bytes();
+        let index_bytes = self.order_index.allocated_bytes();
+        AllocationStats {
+            arena_capacity: self.arena.capacity(),
+            arena_reserved: self.arena.reserved(),
+            index_capacity: self.order_index.capacity(),
+            ladder_bytes,
+            arena_bytes,
+            index_bytes,
+        }
+    }
+
+    /// 订单内存池使用情况 (已用, 容量)
+    #[inline]
+    pub fn arena_usage(&self) -> (usize, usize) {
+        (self.arena.len(), self.arena.capacity())
+    }
+
+    /// 检查新订单能否被接受（撮合模式和价格范围）
+    pub fn check_new_order(&self, side: Side, price: Price) -> Result<(), RejectReason> {
+        if self.mode == TradingMode::CancelOnly {
+            return Err(RejectReason::CancelOnly);
+        }
+        let ladder = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        if price.as_index() >= ladder.max_price() || !self.spec.in_range(price) {
+            return Err(RejectReason::PriceOutOfRange);
+        }
+        if !self.spec.is_on_tick(price) {
+            return Err(RejectReason::OffTick);
+        }
+        Ok(())
+    }
+
+    /// 按品种规格校验新订单（价格按规格取整），返回实际使用的价格
+    pub fn admit_order(&self, side: Side, price: Price, quantity: Quantity) -> Result<Price, RejectReason> {
+        let price = self.spec.normalize_price(side, price)?;
+        self.check_new_order(side, price)?;
+        self.check_price_band(price)?;
+        self.spec.check_quantity(quantity)?;
+        Ok(price)
+    }
+
+    /// 拒绝新订单：消耗订单ID并发送`OrderRejected`事件
+    fn reject(&mut self, reason: RejectReason) -> OrderId {
+        let order_id = self.next_order_id;
+        self.next_order_id += 1;
+        self.counters.orders_rejected += 1;
+        self.emit(BookEvent::OrderRejected { order_id, reason });
+        order_id
+    }
+
+    /// 按内存池利用率发送告警并切换撮合模式
+    fn update_pressure(&mut self) {
+        let (used, capacity) = self.arena_usage();
+        let pct = used * 100 / capacity.max(1);
+
+        let crossed = self
+            .policy
+            .warn_thresholds
+            .iter()
+            .take_while(|&&threshold| pct >= threshold as usize)
+            .count();
+        for i in self.warned..crossed {
+            let threshold_pct = self.policy.warn_thresholds[i];
+            self.emit(BookEvent::CapacityWarning { used, capacity, threshold_pct });
+        }
+        self.warned = crossed;
+
+        if used >= capacity {
+            if self.mode == TradingMode::Normal {
+                self.set_trading_mode(TradingMode::CancelOnly);
+                self.auto_halted = true;
+            }
+        } else if self.auto_halted
+            && self.policy.resume_below_pct.is_some_and(|resume| pct < resume as usize)
+        {
+            self.set_trading_mode(TradingMode::Normal);
+        }
+    }
+
+    /// 设置成交输出（每笔成交都会转发给它）
+    pub fn set_trade_sink(&mut self, sink: Box<dyn TradeSink>) {
+        self.trade_sink = Some(sink);
+    }
+
+    /// 追加成交输出，已设置的输出保留（按添加顺序推送）
+    pub fn add_trade_sink(&mut self, sink: Box<dyn TradeSink>) {
+        self.trade_sink = Some(match self.trade_sink.take() {
+            Some(existing) => Box::new(TradeFanout(vec![existing, sink])),
+            None => sink,
+        });
+    }
+
+    /// 移除成交输出（包括全部追加的输出）
+    pub fn take_trade_sink(&mut self) -> Option<Box<dyn TradeSink>> {
+        self.trade_sink.take()
+    }
+
+    /// 设置逐笔事件监听器
+    pub fn set_event_listener(&mut self, listener: Box<dyn BookEventListener>) {
+        self.event_listener = Some(listener);
+    }
+
+    /// 移除逐笔事件监听器
+    pub fn take_event_listener(&mut self) -> Option<Box<dyn BookEventListener>> {
+        self.event_listener.take()
+    }
+
+    /// 设置最优价变化监听器
+    pub fn set_bbo_listener(&mut self, listener: Box<dyn BboListener>) {
+        self.bbo_listener = Some(listener);
+    }
+
+    /// 移除最优价变化监听器
+    pub fn take_bbo_listener(&mut self) -> Option<Box<dyn BboListener>> {
+        self.bbo_listener.take()
+    }
+
+    /// 更新一侧的最优价，变化时通知监听器
+    #[inline]
+    fn set_best(&mut self, side: Side, price: Option<Price>) {
+        let best = match side {
+            Side::Buy => &mut self.bid_max,
+            Side::Sell => &mut self.ask_min,
+        };
+        if *best == price {
+            return;
+        }
+        let old = std::mem::replace(best, price);
+        if let Some(listener) = self.bbo_listener.as_mut() {
+            listener.on_bbo_change(side, old, price);
+        }
+    }
+
+    /// 发送逐笔事件
+    #[inline]
+    fn emit(&mut self, event: BookEvent) {
+        if let Some(listener) = self.event_listener.as_mut() {
+            listener.on_event(&event);
         }
     }
 
@@ -62,195 +684,666 @@ impl OrderBook {
         self.next_order_id = id;
     }
 
-    /// 获取最佳买价
+    /// 获取下一个成交ID
+    #[inline]
+    pub fn next_trade_id(&self) -> TradeId {
+        self.next_trade_id
+    }
+
+    /// 设置下一个成交ID（用于状态恢复）
+    #[inline]
+    pub fn set_next_trade_id(&mut self, id: TradeId) {
+        self.next_trade_id = id;
+    }
+
+    /// 获取最佳买价
+    #[inline]
+    pub fn best_bid(&self) -> Option<Price> {
+        self.bid_max
+    }
+
+    /// 获取最佳卖价
+    #[inline]
+    pub fn best_ask(&self) -> Option<Price> {
+        self.ask_min
+    }
+
+    /// 获取买卖价差（卖价 - 买价，以tick计）
+    #[inline]
+    pub fn spread(&self) -> Option<u64> {
+        match (self.ask_min, self.bid_max) {
+            (Some(ask), Some(bid)) if ask > bid => ask.ticks_above(bid),
+            _ => None,
+        }
+    }
+
+    /// 获取中间价
+    #[inline]
+    pub fn mid_price(&self) -> Option<Price> {
+        match (self.ask_min, self.bid_max) {
+            (Some(ask), Some(bid)) => Some(ask.midpoint(bid)),
+            _ => None,
+        }
+    }
+
+    /// 提交新的限价订单
+    ///
+    /// - `Gtc`: 未成交部分挂单
+    /// - `Ioc`: 未成交部分直接取消
+    /// - `Fok`: 对手方可成交数量不足时整单取消（不产生任何成交）
+    /// - `Gtd`: 同`Gtc`，到期后由`expire_orders`撤销
+    ///
+    /// 返回 (订单ID, 成交列表)。价格越界、数量无效、内存池已满（可挂单的订单）
+    /// 等情况返回错误，订单不进入撮合，仍消耗订单ID并发送`OrderRejected`事件。
+    pub fn limit_order(
+        &mut self,
+        trader: TraderId,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        tif: TimeInForce,
+    ) -> Result<(OrderId, Vec<Trade>), OrderBookError> {
+        let mut trades = Vec::new();
+        let order_id = self.limit_order_into(trader, side, price, quantity, tif, &mut trades)?;
+        Ok((order_id, trades))
+    }
+
+    /// 提交新的限价订单，成交追加到调用方提供的缓冲区
+    ///
+    /// 缓冲区可跨调用复用（调用方负责`clear`），
+    /// 未成交或成交笔数不超过缓冲区容量时不产生堆分配。
+    /// 返回订单ID，错误同`limit_order`。
+    pub fn limit_order_into(
+        &mut self,
+        trader: TraderId,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        tif: TimeInForce,
+        trades: &mut Vec<Trade>,
+    ) -> Result<OrderId, OrderBookError> {
+        #[cfg(feature = "latency-histogram")]
+        let started = crate::timing::ticks();
+        let result = self.place_limit(trader, side, price, quantity, tif, trades);
+        #[cfg(feature = "latency-histogram")]
+        if self.latency_sampling() {
+            self.latency.record_limit(crate::timing::elapsed_ns(started));
+        }
+        result
+    }
+
+    fn place_limit(
+        &mut self,
+        trader: TraderId,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        tif: TimeInForce,
+        trades: &mut Vec<Trade>,
+    ) -> Result<OrderId, OrderBookError> {
+        let price = match self.admit_order(side, price, quantity) {
+            Ok(price) => price,
+            Err(RejectReason::PriceBand) => {
+                self.reject(RejectReason::PriceBand);
+                let reference = self.band_reference().unwrap_or(price);
+                return Err(OrderBookError::PriceOutsideBand { price, reference });
+            }
+            Err(reason) => {
+                self.reject(reason);
+                return Err(OrderBookError::rejection(reason, price, quantity));
+            }
+        };
+        if self.mode == TradingMode::Halted && self.crosses(side, price) {
+            if !tif.rests() {
+                self.reject(RejectReason::Halted);
+                return Err(OrderBookError::Rejected(RejectReason::Halted));
+            }
+            let order_id = self.next_order_id;
+            self.next_order_id += 1;
+            self.counters.orders_accepted += 1;
+            self.auction.push(QueuedOrder { order_id, trader, side, price, quantity, tif });
+            return Ok(order_id);
+        }
+        // 撮合只会释放槽位：受理前有空槽位，剩余部分就一定能挂上
+        if tif.rests() && self.arena.len() == self.arena.capacity() {
+            self.reject(RejectReason::CapacityExhausted);
+            return Err(OrderBookError::CapacityExceeded);
+        }
+        let order_id = self.next_order_id;
+        self.next_order_id += 1;
+        self.counters.orders_accepted += 1;
+
+        let first_fill = trades.len(); // 本次成交在缓冲区中的起始位置
+        self.execute(order_id, trader, side, price, quantity, tif, None, trades);
+        self.activate_stops(first_fill, trades);
+        self.record_trades(&trades[first_fill..]);
+
+        Ok(order_id)
+    }
+
+    /// 提交附带客户端订单号的限价订单（幂等下单）
+    ///
+    /// 同一交易员的客户端订单号只受理一次，重复提交（如断线重连后重发）以`DuplicateClientOrderId`拒绝，
+    /// 错误中带出首次受理的订单ID；被拒绝的订单不占用订单号。
+    /// 去重索引不随订单成交或撤销释放，交易日结束时由`clear_client_orders`清空。
+    pub fn limit_order_with_client_id(
+        &mut self,
+        trader: TraderId,
+        client_order_id: u64,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        tif: TimeInForce,
+    ) -> Result<(OrderId, Vec<Trade>), OrderBookError> {
+        if let Some(order_id) = self.client_order(trader, client_order_id) {
+            self.reject(RejectReason::DuplicateOrder);
+            return Err(OrderBookError::DuplicateClientOrderId { client_order_id, order_id });
+        }
+        let (order_id, trades) = self.limit_order(trader, side, price, quantity, tif)?;
+        self.client_orders.insert((trader, client_order_id), order_id);
+        Ok((order_id, trades))
+    }
+
+    /// 客户端订单号对应的订单ID
+    #[inline]
+    pub fn client_order(&self, trader: TraderId, client_order_id: u64) -> Option<OrderId> {
+        self.client_orders.get(&(trader, client_order_id)).copied()
+    }
+
+    /// 清空客户端订单号去重索引
+    pub fn clear_client_orders(&mut self) {
+        self.client_orders.clear();
+    }
+
+    /// 提交只减仓的限价订单
+    ///
+    /// 数量截断为交易员在该方向还能减仓的数量（反向净持仓减去同方向已挂数量，按整手向下取整），
+    /// 可减仓数量为0时以`ReduceOnly`拒绝（消耗订单ID并发送`OrderRejected`事件）。
+    /// 截断只在受理时进行，挂单期间持仓变化不会调整已挂的订单。
+    pub fn reduce_only_order(
+        &mut self,
+        trader: TraderId,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        tif: TimeInForce,
+    ) -> Result<(OrderId, Vec<Trade>), OrderBookError> {
+        let reducible = self.trader_stats(trader).reducible_quantity(side);
+        let reducible = reducible - reducible % self.spec.lot_size as u64;
+        let Some(quantity) = Quantity::new(quantity.get().min(reducible.min(u32::MAX as u64) as u32)) else {
+            self.reject(RejectReason::ReduceOnly);
+            return Err(OrderBookError::Rejected(RejectReason::ReduceOnly));
+        };
+        self.limit_order(trader, side, price, quantity, tif)
+    }
+
+    /// 登记止损市价单
+    ///
+    /// 最新成交价穿越触发价时以市价（IOC）执行；若最新成交价已穿越则立即执行。
+    /// 返回 (订单ID, 立即执行产生的成交列表)
+    pub fn stop_order(
+        &mut self,
+        trader: TraderId,
+        side: Side,
+        stop_price: Price,
+        quantity: Quantity,
+    ) -> (OrderId, Vec<Trade>) {
+        self.register_stop(trader, side, stop_price, None, quantity)
+    }
+
+    /// 登记止损限价单
+    ///
+    /// 最新成交价穿越触发价时以`limit_price`作为GTC限价单执行；若最新成交价已穿越则立即执行。
+    /// 返回 (订单ID, 立即执行产生的成交列表)
+    pub fn stop_limit_order(
+        &mut self,
+        trader: TraderId,
+        side: Side,
+        stop_price: Price,
+        limit_price: Price,
+        quantity: Quantity,
+    ) -> (OrderId, Vec<Trade>) {
+        self.register_stop(trader, side, stop_price, Some(limit_price), quantity)
+    }
+
+    fn register_stop(
+        &mut self,
+        trader: TraderId,
+        side: Side,
+        stop_price: Price,
+        limit_price: Option<Price>,
+        quantity: Quantity,
+    ) -> (OrderId, Vec<Trade>) {
+        // 熔断期间不接受会立即触发的止损单
+        let triggered = self.last_trade_price.is_some_and(|last| match side {
+            Side::Buy => last >= stop_price,
+            Side::Sell => last <= stop_price,
+        });
+        let admission = match limit_price {
+            Some(limit_price) => self.admit_order(side, limit_price, quantity).map(Some),
+            None if self.mode == TradingMode::CancelOnly => Err(RejectReason::CancelOnly),
+            None => self.spec.check_quantity(quantity).map(|()| None),
+        };
+        let admission = match admission {
+            Ok(_) if triggered && self.mode == TradingMode::Halted => Err(RejectReason::Halted),
+            other => other,
+        };
+        let limit_price = match admission {
+            Ok(limit_price) => limit_price,
+            Err(reason) => return (self.reject(reason), Vec::new()),
+        };
+        let order_id = self.next_order_id;
+        self.next_order_id += 1;
+
+        let stop = StopOrder {
+            order_id,
+            trader,
+            side,
+            stop_price,
+            limit_price,
+            quantity,
+        };
+
+        let mut trades = Vec::new();
+        if self.last_trade_price.is_some_and(|last| stop.is_triggered_by(last)) {
+            self.execute_stop(&stop, &mut trades);
+            self.activate_stops(0, &mut trades);
+            self.record_trades(&trades);
+        } else {
+            self.stops.insert(stop);
+        }
+
+        (order_id, trades)
+    }
+
+    /// 按顺序执行一批指令，返回与指令一一对应的结果
+    ///
+    /// 结果与逐条调用`Command::execute`完全相同，用于定序器成批取出队列时摊薄单次调用开销。
+    pub fn apply_batch(&mut self, commands: &[Command]) -> Vec<CommandResult> {
+        let mut results = Vec::with_capacity(commands.len());
+        self.apply_batch_into(commands, &mut results);
+        results
+    }
+
+    /// 按顺序执行一批指令，结果追加到调用方提供的缓冲区（可跨批次复用）
+    pub fn apply_batch_into(&mut self, commands: &[Command], results: &mut Vec<CommandResult>) {
+        results.reserve(commands.len());
+        for command in commands {
+            results.push(command.execute(self));
+        }
+    }
+
+    /// 最新成交价
     #[inline]
-    pub fn best_bid(&self) -> Option<Price> {
-        self.bid_max
+    pub fn last_trade_price(&self) -> Option<Price> {
+        self.last_trade_price
     }
 
-    /// 获取最佳卖价
+    /// 获取等待触发的止损单
     #[inline]
-    pub fn best_ask(&self) -> Option<Price> {
-        self.ask_min
+    pub fn stop_orders(&self) -> &StopBook {
+        &self.stops
     }
 
-    /// 获取买卖价差（卖价 - 买价）
-    #[inline]
-    pub fn spread(&self) -> Option<Price> {
-        match (self.ask_min, self.bid_max) {
-            (Some(ask), Some(bid)) if ask > bid => Some(ask - bid),
-            _ => None,
+    /// 成交后激活被触发的止损单（触发产生的成交可能继续触发，直到稳定）
+    fn activate_stops(&mut self, mut checked: usize, trades: &mut Vec<Trade>) {
+        if self.stops.is_empty() {
+            return;
+        }
+
+        let mut triggered = Vec::new();
+        while checked < trades.len() {
+            let last_price = trades[trades.len() - 1].price;
+            checked = trades.len();
+
+            self.stops.take_triggered(last_price, &mut triggered);
+            for stop in triggered.drain(..) {
+                // 熔断后剩余的触发单放回，恢复后由下一笔成交再次触发
+                if self.mode == TradingMode::Halted {
+                    self.stops.insert(stop);
+                } else {
+                    self.execute_stop(&stop, trades);
+                }
+            }
         }
     }
 
-    /// 获取中间价
-    #[inline]
-    pub fn mid_price(&self) -> Option<Price> {
-        match (self.ask_min, self.bid_max) {
-            (Some(ask), Some(bid)) => Some((ask + bid) / 2),
-            _ => None,
+    /// 以止损单原订单ID执行
+    fn execute_stop(&mut self, stop: &StopOrder, trades: &mut Vec<Trade>) {
+        let (price, tif) = match (stop.limit_price, stop.side) {
+            (Some(limit), _) => (limit, TimeInForce::Gtc),
+            (None, Side::Buy) => (self.asks.highest_price(), TimeInForce::Ioc),
+            (None, Side::Sell) => (Price::MIN, TimeInForce::Ioc),
+        };
+        self.execute(stop.order_id, stop.trader, stop.side, price, stop.quantity, tif, None, trades);
+    }
+
+    /// 记录成交历史并转发到成交输出
+    fn record_trades(&mut self, fills: &[Trade]) {
+        // 存储交易记录
+        self.trades.extend_from_slice(fills);
+        self.counters.trades += fills.len() as u64;
+        self.counters.traded_volume += fills.iter().map(|trade| trade.quantity.get() as u64).sum::<u64>();
+
+        // 转发到成交输出
+        if let Some(sink) = self.trade_sink.as_mut() {
+            for trade in fills {
+                sink.on_trade(trade);
+            }
         }
     }
 
-    /// 提交新的限价订单
+    /// 执行订单撮合，未成交部分按有效期类型挂单或取消
     ///
-    /// 返回 (订单ID, 成交列表)
-    pub fn limit_order(
+    /// `fill_price`为集合竞价的统一成交价，None时按挂单价成交。
+    #[allow(clippy::too_many_arguments)]
+    fn execute(
         &mut self,
+        order_id: OrderId,
         trader: TraderId,
         side: Side,
         price: Price,
         quantity: Quantity,
-    ) -> (OrderId, Vec<Trade>) {
-        let order_id = self.next_order_id;
-        self.next_order_id += 1;
+        tif: TimeInForce,
+        fill_price: Option<Price>,
+        trades: &mut Vec<Trade>,
+    ) {
+        // 熔断价格带：最多成交到带边
+        let limit = match self.breaker.as_mut().and_then(|guard| guard.band(self.clock.now_ns())) {
+            Some((_, upper)) if side == Side::Buy => price.min(upper),
+            Some((lower, _)) => price.max(lower),
+            None => price,
+        };
+
+        // FOK: 预先探测对手方流动性，不足则整单取消
+        if tif == TimeInForce::Fok && self.available_liquidity(trader, side, limit, quantity) < quantity {
+            return;
+        }
 
         let mut remaining = quantity;  // 剩余未成交数量
-        let mut trades = Vec::new();   // 成交记录
+        let first_fill = trades.len();
+        let breached;  // 对手方在价格带外仍有可成交挂单
 
         // 尝试与对手方匹配
         match side {
             Side::Buy => {
                 // 从最佳（最低）卖价开始匹配卖单
                 if let Some(mut ask_price) = self.ask_min {
-                    while remaining > 0 && ask_price <= price {
-                        let fills = self.match_at_price(
+                    while !remaining.is_zero() && ask_price <= limit {
+                        self.match_at_price(
                             order_id,
                             trader,
                             side,
                             ask_price,
+                            fill_price,
                             &mut remaining,
+                            trades,
                         );
-                        trades.extend(fills);
 
                         // 移动到下一个卖价级别
-                        ask_price = self.find_next_ask(ask_price).unwrap_or(price + 1);
+                        match self.find_next_ask(ask_price) {
+                            Some(next) => ask_price = next,
+                            None => break,
+                        }
                     }
                     // 更新最佳卖价
-                    self.ask_min = self.find_next_ask(0);
+                    self.set_best(Side::Sell, self.find_next_ask(Price::MIN));
                 }
 
-                // 如果未完全成交，将剩余部分添加到买单侧
-                if remaining > 0 {
-                    self.add_order(order_id, trader, side, price, remaining);
+                breached = limit != price && !remaining.is_zero() && self.crosses(side, price);
+
+                // 如果未完全成交，将剩余部分添加到买单侧（IOC/FOK不挂单）
+                if !remaining.is_zero() && tif.rests() && !breached && self.add_order(order_id, trader, side, price, remaining) {
                     // 更新最佳买价
                     if self.bid_max.map_or(true, |max| price > max) {
-                        self.bid_max = Some(price);
+                        self.set_best(Side::Buy, Some(price));
                     }
                 }
             }
             Side::Sell => {
                 // 从最佳（最高）买价开始匹配买单
                 if let Some(mut bid_price) = self.bid_max {
-                    while remaining > 0 && bid_price >= price {
-                        let fills = self.match_at_price(
+                    while !remaining.is_zero() && bid_price >= limit {
+                        self.match_at_price(
                             order_id,
                             trader,
                             side,
                             bid_price,
+                            fill_price,
                             &mut remaining,
+                            trades,
                         );
-                        trades.extend(fills);
 
                         // 移动到下一个买价级别
-                        bid_price = self.find_prev_bid(bid_price).unwrap_or(0);
+                        match self.find_prev_bid(bid_price) {
+                            Some(next) => bid_price = next,
+                            None => break,
+                        }
                     }
                     // 更新最佳买价
-                    self.bid_max = self.find_prev_bid(u32::MAX);
+                    self.set_best(Side::Buy, self.find_prev_bid(Price::MAX));
                 }
 
-                // 如果未完全成交，将剩余部分添加到卖单侧
-                if remaining > 0 {
-                    self.add_order(order_id, trader, side, price, remaining);
+                breached = limit != price && !remaining.is_zero() && self.crosses(side, price);
+
+                // 如果未完全成交，将剩余部分添加到卖单侧（IOC/FOK不挂单）
+                if !remaining.is_zero() && tif.rests() && !breached && self.add_order(order_id, trader, side, price, remaining) {
                     // 更新最佳卖价
                     if self.ask_min.map_or(true, |min| price < min) {
-                        self.ask_min = Some(price);
+                        self.set_best(Side::Sell, Some(price));
                     }
                 }
             }
         }
 
-        // 存储交易记录
-        self.trades.extend(&trades);
+        // 熔断：穿价的剩余部分排队等待重新开盘
+        if breached && tif.rests() {
+            self.auction.push(QueuedOrder { order_id, trader, side, price, quantity: remaining, tif });
+        }
 
-        (order_id, trades)
+        if let Some(expires_at) = tif.expires_at()
+            && self.order_index.contains_key(&order_id)
+        {
+            self.expiries.insert((expires_at, order_id));
+        }
+
+        // 分配成交ID和时间戳，更新最新成交价
+        if trades.len() > first_fill {
+            let timestamp_ns = self.clock.now_ns();
+            for trade in &mut trades[first_fill..] {
+                trade.trade_id = self.next_trade_id;
+                trade.timestamp_ns = timestamp_ns;
+                self.next_trade_id += 1;
+                if let Some(guard) = self.breaker.as_mut() {
+                    guard.record(timestamp_ns, trade.price);
+                }
+            }
+            self.last_trade_price = Some(trades[trades.len() - 1].price);
+        }
+
+        if breached && self.mode == TradingMode::Normal {
+            self.set_trading_mode(TradingMode::Halted);
+        }
+
+        self.update_pressure();
     }
 
-    /// 在特定价格级别匹配订单
+    /// 探测对手方在限价内的可成交数量
+    ///
+    /// 累计达到`needed`后提前返回
+    fn available_liquidity(&self, trader: TraderId, side: Side, limit: Price, needed: Quantity) -> Quantity {
+        let mut total = Quantity::ZERO;
+        let mut level = match side {
+            Side::Buy => self.ask_min.filter(|&p| p <= limit),
+            Side::Sell => self.bid_max.filter(|&p| p >= limit),
+        };
+
+        while let Some(price) = level {
+            let price_point = match side {
+                Side::Buy => self.asks.level(price),
+                Side::Sell => self.bids.level(price),
+            };
+
+            if let Some(point) = price_point {
+                if self.self_trade == SelfTradePrevention::Allow {
+                    total = total.saturating_add(point.quantity());
+                } else {
+                    // 不计本人挂单；撤销来单模式下遇到本人挂单即停止
+                    let mut current_idx = point.first_order_idx;
+                    while let Some(idx) = current_idx {
+                        let entry = self.arena.get(idx).unwrap();
+                        if entry.trader != trader {
+                            total = total.saturating_add(entry.quantity);
+                        } else if self.self_trade == SelfTradePrevention::CancelIncoming {
+                            return total;
+                        }
+                        current_idx = entry.next_idx;
+                    }
+                }
+                if total >= needed {
+                    return total;
+                }
+            }
+
+            level = match side {
+                Side::Buy => price
+                    .checked_add(1)
+                    .and_then(|p| self.find_next_ask(p))
+                    .filter(|&p| p <= limit),
+                Side::Sell => price
+                    .checked_sub(1)
+                    .and_then(|p| self.find_prev_bid(p))
+                    .filter(|&p| p >= limit),
+            };
+        }
+
+        total
+    }
+
+    /// 在特定价格级别匹配订单，成交和`OrderExecuted`事件按`fill_price`（默认档位价）报告
+    #[allow(clippy::too_many_arguments)]
     fn match_at_price(
         &mut self,
-        _order_id: OrderId,
+        order_id: OrderId,
         trader: TraderId,
         side: Side,
         price: Price,
+        fill_price: Option<Price>,
         remaining: &mut Quantity,
-    ) -> Vec<Trade> {
-        let mut trades = Vec::new();
-        let price_idx = price as usize;
-
-        let price_point = match side {
-            Side::Buy => &mut self.asks[price_idx],
-            Side::Sell => &mut self.bids[price_idx],
-        };
-
-        let mut current_idx = price_point.first_order_idx;
-        let mut first_active_idx = None;
-
-        while *remaining > 0 && current_idx.is_some() {
-            let idx = current_idx.unwrap();
+        trades: &mut Vec<Trade>,
+    ) {
+        let maker_side = side.opposite();
+        let fill_price = fill_price.unwrap_or(price);
+        let mut allocations = self.pro_rata_allocations(side, price, *remaining);
+        let mut current_idx = self.price_point_mut(maker_side, price).first_order_idx;
+
+        while !remaining.is_zero()
+            && let Some(idx) = current_idx
+        {
             let entry = self.arena.get_mut(idx).unwrap();
-
-            if entry.is_active() {
-                // Track first active order for price point update
-                if first_active_idx.is_none() {
-                    first_active_idx = Some(idx);
-                }
-
-                let fill_qty = (*remaining).min(entry.quantity);
-
-                // Create trade record
-                let trade = match side {
-                    Side::Buy => Trade::new(trader, entry.trader, price, fill_qty),
-                    Side::Sell => Trade::new(entry.trader, trader, price, fill_qty),
-                };
-                trades.push(trade);
-
-                // Update quantities
-                *remaining -= fill_qty;
-                entry.quantity -= fill_qty;
-
-                // If order fully filled, mark as inactive
-                if entry.quantity == 0 {
-                    self.order_index.remove(&entry.order_id);
-                    // Update first active if this was it
-                    if first_active_idx == Some(idx) {
-                        first_active_idx = None;
+            current_idx = entry.next_idx;
+
+            let fill_qty = match allocations.as_mut() {
+                Some(allocations) => Quantity::new(allocations.next().unwrap_or(0)).unwrap_or(Quantity::ZERO),
+                None => (*remaining).min(entry.quantity),
+            };
+            if entry.trader == trader {
+                match self.self_trade {
+                    SelfTradePrevention::Allow => {}
+                    SelfTradePrevention::CancelResting => {
+                        // 撤销本人挂单（按比例分配时其分配量不转给其他挂单）
+                        let (maker_id, maker_left) = (entry.order_id, entry.quantity);
+                        self.order_index.remove(&maker_id);
+                        self.price_point_mut(maker_side, price).remove_order(maker_left);
+                        self.unlink(idx);
+                        self.emit(BookEvent::OrderCancelled { order_id: maker_id, quantity: maker_left });
+                        continue;
+                    }
+                    SelfTradePrevention::CancelIncoming => {
+                        *remaining = Quantity::ZERO;
+                        return;
                     }
                 }
             }
+            if fill_qty.is_zero() {
+                // 按比例分配时未分到数量的挂单
+                continue;
+            }
 
-            current_idx = self.arena.get(idx).unwrap().next_idx;
+            // 成交ID和时间戳由execute统一分配
+            let (buyer, seller) = match side {
+                Side::Buy => (trader, entry.trader),
+                Side::Sell => (entry.trader, trader),
+            };
+            trades.push(Trade {
+                trade_id: 0,
+                timestamp_ns: 0,
+                buyer,
+                seller,
+                price: fill_price,
+                quantity: fill_qty,
+                aggressor_side: side,
+                maker_order_id: entry.order_id,
+                taker_order_id: order_id,
+            });
+
+            *remaining -= fill_qty;
+            entry.quantity -= fill_qty;
+            let (maker_id, maker_left) = (entry.order_id, entry.quantity);
+
+            let maker = self.traders.entry(entry.trader).or_default();
+            maker.reduce(maker_side, fill_qty);
+            maker.record_fill(maker_side, fill_qty);
+            self.traders.entry(trader).or_default().record_fill(side, fill_qty);
+
+            if let Some(listener) = self.event_listener.as_mut() {
+                listener.on_event(&BookEvent::OrderExecuted {
+                    order_id: maker_id,
+                    aggressor_id: order_id,
+                    price: fill_price,
+                    quantity: fill_qty,
+                    remaining: maker_left,
+                });
+            }
 
-            // Update first_active_idx if we haven't found one yet
-            if first_active_idx.is_none() && current_idx.is_some() {
-                let next_entry = self.arena.get(current_idx.unwrap()).unwrap();
-                if next_entry.is_active() {
-                    first_active_idx = current_idx;
-                }
+            // 完全成交的挂单立即从档位链表摘除
+            let price_point = self.price_point_mut(maker_side, price);
+            if maker_left.is_zero() {
+                price_point.remove_order(fill_qty);
+                self.order_index.remove(&maker_id);
+                self.unlink(idx);
+            } else {
+                price_point.reduce(fill_qty);
             }
         }
+    }
 
-        // Update price point to reflect first active order
-        if first_active_idx.is_none() && current_idx.is_none() {
-            // All orders consumed, clear price level
-            price_point.first_order_idx = None;
-            price_point.last_order_idx = None;
-        } else if first_active_idx.is_some() {
-            // Update to first active order
-            price_point.first_order_idx = first_active_idx;
+    /// 按比例分配模式下，来单不足以吃完档位时各有效挂单（按时间顺序）的分配量
+    fn pro_rata_allocations(&self, side: Side, price: Price, incoming: Quantity) -> Option<std::vec::IntoIter<u32>> {
+        let AllocationPolicy::ProRata { min_allocation } = self.spec.allocation else {
+            return None;
+        };
+        let ladder = match side {
+            Side::Buy => &self.asks,
+            Side::Sell => &self.bids,
+        };
+        let point = ladder.level(price).filter(|point| (incoming.get() as u64) < point.total_quantity)?;
+
+        let mut resting = Vec::with_capacity(point.order_count as usize);
+        let mut current_idx = point.first_order_idx;
+        while let Some(idx) = current_idx {
+            let entry = self.arena.get(idx).unwrap();
+            resting.push(entry.quantity.get());
+            current_idx = entry.next_idx;
         }
+        Some(AllocationPolicy::pro_rata(incoming.get(), &resting, min_allocation, self.spec.lot_size).into_iter())
+    }
 
-        trades
+    /// 解析订单ID对应的内存池索引（句柄已失效时返回None）
+    #[inline]
+    fn order_slot(&self, order_id: OrderId) -> Option<usize> {
+        let &raw = self.order_index.get(&order_id)?;
+        self.arena.resolve(ArenaHandle::from_raw(raw as u64))
     }
 
     /// 将新订单添加到订单簿
@@ -261,70 +1354,670 @@ impl OrderBook {
         side: Side,
         price: Price,
         quantity: Quantity,
-    ) {
-        let entry = OrderEntry::new(order_id, trader, quantity);
-        let idx = self
-            .arena
-            .allocate(entry)
-            .expect("Order arena capacity exceeded");
-
-        self.order_index.insert(order_id, idx);
-
-        let price_idx = price as usize;
-        let price_point = match side {
-            Side::Buy => &mut self.bids[price_idx],
-            Side::Sell => &mut self.asks[price_idx],
+    ) -> bool {
+        if !self.link_order(OrderEntry::new(order_id, trader, side, price, quantity)) {
+            // 触发的止损单等在内存池已满时到达：剩余部分不挂单
+            self.emit(BookEvent::OrderRejected {
+                order_id,
+                reason: RejectReason::CapacityExhausted,
+            });
+            return false;
+        }
+
+        self.emit(BookEvent::OrderAdded {
+            order_id,
+            trader,
+            side,
+            price,
+            quantity,
+        });
+        true
+    }
+
+    /// 分配槽位并把条目挂到其价格档位队尾（内存池已满时返回false）
+    fn link_order(&mut self, entry: OrderEntry) -> bool {
+        let Some(idx) = self.arena.allocate(entry) else {
+            return false;
+        };
+
+        self.order_index.insert(entry.order_id, self.arena.handle(idx).into_raw() as usize);
+
+        let price_point = match entry.side {
+            Side::Buy => self.bids.level_mut(entry.price),
+            Side::Sell => self.asks.level_mut(entry.price),
+        };
+
+        // Link to existing orders at this price level
+        if let Some(last_idx) = price_point.last_order_idx {
+            self.arena.get_mut(last_idx).unwrap().next_idx = Some(idx);
+            self.arena.get_mut(idx).unwrap().prev_idx = Some(last_idx);
+        }
+
+        price_point.push_back(idx);
+        price_point.add_order(entry.quantity);
+        self.traders.entry(entry.trader).or_default().add_order(entry.side, entry.quantity);
+        true
+    }
+
+    /// 查询挂单剩余数量（已完全成交、已取消或不存在时返回None）
+    #[inline]
+    pub fn order_quantity(&self, order_id: OrderId) -> Option<Quantity> {
+        let idx = self.order_slot(order_id)?;
+        self.arena.get(idx).map(|entry| entry.quantity)
+    }
+
+    /// 取消订单（包括等待触发的止损单和熔断排队订单）
+    ///
+    /// 订单不存在、已成交或已撤销时返回`UnknownOrder`。
+    pub fn cancel_order(&mut self, order_id: OrderId) -> Result<(), OrderBookError> {
+        #[cfg(feature = "latency-histogram")]
+        let started = crate::timing::ticks();
+        let cancelled = self.cancel_any(order_id);
+        #[cfg(feature = "latency-histogram")]
+        if self.latency_sampling() {
+            self.latency.record_cancel(crate::timing::elapsed_ns(started));
+        }
+        if cancelled { Ok(()) } else { Err(OrderBookError::UnknownOrder(order_id)) }
+    }
+
+    fn cancel_any(&mut self, order_id: OrderId) -> bool {
+        if let Some(quantity) = self.deactivate(order_id) {
+            self.emit(BookEvent::OrderCancelled { order_id, quantity });
+            self.relieve_pressure();
+        } else if let Some(pos) = self.auction.iter().position(|order| order.order_id == order_id) {
+            self.auction.remove(pos);
+        } else if self.stops.cancel(order_id).is_none() {
+            return false;
+        }
+        self.counters.orders_cancelled += 1;
+        true
+    }
+
+    /// 把条目从其价格档位的双向链表摘除并归还槽位（O(1)）
+    ///
+    /// 档位因此变空时释放价格点；若它是最优价，最优价移到下一个非空档位。
+    /// 条目的剩余数量从交易员汇总中扣除，调用方负责更新档位汇总和订单索引。
+    fn unlink(&mut self, idx: usize) {
+        let entry = *self.arena.get(idx).unwrap();
+        if let Some(stats) = self.traders.get_mut(&entry.trader) {
+            stats.remove_order(entry.side, entry.quantity);
+        }
+        if let Some(prev_idx) = entry.prev_idx {
+            self.arena.get_mut(prev_idx).unwrap().next_idx = entry.next_idx;
+        }
+        if let Some(next_idx) = entry.next_idx {
+            self.arena.get_mut(next_idx).unwrap().prev_idx = entry.prev_idx;
+        }
+        self.arena.free(idx);
+
+        let (side, price) = (entry.side, entry.price);
+        let point = self.price_point_mut(side, price);
+        if entry.prev_idx.is_none() {
+            point.first_order_idx = entry.next_idx;
+        }
+        if entry.next_idx.is_none() {
+            point.last_order_idx = entry.prev_idx;
+        }
+        if !point.is_empty() {
+            return;
+        }
+        match side {
+            Side::Buy => {
+                self.bids.release(price);
+                if self.bid_max == Some(price) {
+                    self.set_best(Side::Buy, price.checked_sub(1).and_then(|p| self.find_prev_bid(p)));
+                }
+            }
+            Side::Sell => {
+                self.asks.release(price);
+                if self.ask_min == Some(price) {
+                    self.set_best(Side::Sell, price.checked_add(1).and_then(|p| self.find_next_ask(p)));
+                }
+            }
+        }
+    }
+
+    /// 自动只撤单模式下重新评估资源压力
+    fn relieve_pressure(&mut self) {
+        if self.auto_halted {
+            self.update_pressure();
+        }
+    }
+
+    /// 撤销所有到期时间不晚于`now_ns`的GTD挂单，返回被撤销的订单ID
+    ///
+    /// 每笔到期订单发送`OrderExpired`事件。
+    pub fn expire_orders(&mut self, now_ns: u64) -> Vec<OrderId> {
+        let mut expired = Vec::new();
+        while let Some(&(expires_at, order_id)) = self.expiries.first() {
+            if expires_at > now_ns {
+                break;
+            }
+            self.expiries.pop_first();
+
+            if let Some(quantity) = self.deactivate(order_id) {
+                self.emit(BookEvent::OrderExpired { order_id, quantity });
+                expired.push(order_id);
+            }
+        }
+        self.auction.retain(|order| match order.tif.expires_at() {
+            Some(expires_at) if expires_at <= now_ns => {
+                expired.push(order.order_id);
+                false
+            }
+            _ => true,
+        });
+        if !expired.is_empty() {
+            self.relieve_pressure();
+        }
+        expired
+    }
+
+    /// 最早的GTD到期时间
+    #[inline]
+    pub fn next_expiry(&self) -> Option<u64> {
+        self.expiries.first().map(|&(expires_at, _)| expires_at)
+    }
+
+    /// 启动后台到期扫描任务
+    ///
+    /// 按`interval`周期以订单簿时间源的当前时间调用`expire_orders`。
+    pub fn spawn_expiry_sweep(book: Arc<Mutex<OrderBook>>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+
+                let mut book = book.lock();
+                let now_ns = book.clock.now_ns();
+                book.expire_orders(now_ns);
+            }
+        })
+    }
+
+    /// 将挂单置为无效，返回其剩余数量
+    fn deactivate(&mut self, order_id: OrderId) -> Option<Quantity> {
+        let idx = self.order_slot(order_id)?;
+        let entry = self.arena.get_mut(idx)?;
+        let (side, price, quantity) = (entry.side, entry.price, entry.quantity);
+        self.order_index.remove(&order_id);
+        self.price_point_mut(side, price).remove_order(quantity);
+        self.unlink(idx);
+        Some(quantity)
+    }
+
+    /// 获取价格点（可变）
+    #[inline]
+    fn price_point_mut(&mut self, side: Side, price: Price) -> &mut PricePoint {
+        match side {
+            Side::Buy => self.bids.level_mut(price),
+            Side::Sell => self.asks.level_mut(price),
+        }
+    }
+
+    /// 减少挂单数量（保留时间优先级）
+    ///
+    /// `new_quantity`须为整手且小于当前剩余数量，否则返回false。
+    pub fn reduce_order(&mut self, order_id: OrderId, new_quantity: Quantity) -> bool {
+        if self.spec.check_quantity(new_quantity).is_err() {
+            return false;
+        }
+        let Some(idx) = self.order_slot(order_id) else {
+            // 熔断排队订单没有时间优先级可言，直接修改
+            let Some(order) = self.auction.iter_mut().find(|order| order.order_id == order_id) else {
+                return false;
+            };
+            if new_quantity.is_zero() || new_quantity >= order.quantity {
+                return false;
+            }
+            order.quantity = new_quantity;
+            return true;
+        };
+        let Some(entry) = self.arena.get_mut(idx) else {
+            return false;
+        };
+        let old_quantity = entry.quantity;
+        if new_quantity.is_zero() || new_quantity >= old_quantity {
+            return false;
+        }
+
+        entry.quantity = new_quantity;
+        let (trader, side, price) = (entry.trader, entry.side, entry.price);
+        self.price_point_mut(side, price).reduce(old_quantity - new_quantity);
+        if let Some(stats) = self.traders.get_mut(&trader) {
+            stats.reduce(side, old_quantity - new_quantity);
+        }
+        self.emit(BookEvent::OrderAmended {
+            order_id,
+            side,
+            old_price: price,
+            new_price: price,
+            old_quantity,
+            new_quantity,
+            priority_retained: true,
+        });
+        true
+    }
+
+    /// 改单（撤单重挂，保留订单ID）
+    ///
+    /// 价格不变且只减少数量时等同`reduce_order`，保留时间优先级；
+    /// 改价或增加数量时订单移到新价格档位队尾，失去优先级。
+    /// 以下情况返回false且挂单不变:
+    /// - 订单不在订单簿上（含熔断排队订单）或修改前后相同
+    /// - 新数量不是整手，或新价格会与对手方成交（应撤单后重新下单）
+    /// - 新订单不被接受（只撤单模式、价格越界或不在tick上）
+    /// - 内存池已满
+    pub fn replace_order(&mut self, order_id: OrderId, new_price: Price, new_quantity: Quantity) -> bool {
+        let Some(entry) = self.order_slot(order_id).and_then(|idx| self.arena.get(idx)).copied() else {
+            return false;
+        };
+        if self.spec.check_quantity(new_quantity).is_err()
+            || (new_price == entry.price && new_quantity == entry.quantity)
+        {
+            return false;
+        }
+        if new_price == entry.price && new_quantity < entry.quantity {
+            return self.reduce_order(order_id, new_quantity);
+        }
+        if self.check_new_order(entry.side, new_price).is_err()
+            || self.check_price_band(new_price).is_err()
+            || self.crosses(entry.side, new_price)
+        {
+            return false;
+        }
+
+        // 先挂新条目再作废旧条目，内存池已满时原挂单不受影响
+        let old_idx = self.order_slot(order_id);
+        let replacement = OrderEntry::new(order_id, entry.trader, entry.side, new_price, new_quantity);
+        if !self.link_order(replacement) {
+            return false;
+        }
+        if let Some(old_idx) = old_idx {
+            self.price_point_mut(entry.side, entry.price).remove_order(entry.quantity);
+            self.unlink(old_idx);
+        }
+
+        match entry.side {
+            Side::Buy if self.bid_max.is_none_or(|max| new_price > max) => self.set_best(Side::Buy, Some(new_price)),
+            Side::Sell if self.ask_min.is_none_or(|min| new_price < min) => self.set_best(Side::Sell, Some(new_price)),
+            _ => {}
+        }
+        self.emit(BookEvent::OrderAmended {
+            order_id,
+            side: entry.side,
+            old_price: entry.price,
+            new_price,
+            old_quantity: entry.quantity,
+            new_quantity,
+            priority_retained: false,
+        });
+        self.update_pressure();
+        true
+    }
+
+    /// 查找下一个非空的卖价级别
+    #[inline]
+    fn find_next_ask(&self, start_price: Price) -> Option<Price> {
+        self.asks.next_non_empty(start_price)
+    }
+
+    /// 查找上一个非空的买价级别
+    #[inline]
+    fn find_prev_bid(&self, start_price: Price) -> Option<Price> {
+        self.bids.prev_non_empty(start_price)
+    }
+
+    /// 获取买卖双方前N个聚合价格档位（跳过只剩已取消订单的价格）
+    pub fn depth(&self, n: usize) -> BookDepth {
+        BookDepth {
+            bids: self.iter_bids().take(n).collect(),
+            asks: self.iter_asks().take(n).collect(),
+        }
+    }
+
+    /// 按优先顺序（价格从高到低）迭代买方非空价格档位
+    #[inline]
+    pub fn iter_bids(&self) -> LevelIter<'_> {
+        LevelIter {
+            book: self,
+            side: Side::Buy,
+            next: self.bid_max,
+        }
+    }
+
+    /// 按优先顺序（价格从低到高）迭代卖方非空价格档位
+    #[inline]
+    pub fn iter_asks(&self) -> LevelIter<'_> {
+        LevelIter {
+            book: self,
+            side: Side::Sell,
+            next: self.ask_min,
+        }
+    }
+
+    /// 单个价格点的有效订单汇总（O(1)）
+    #[inline]
+    fn aggregate_level(price_point: &PricePoint, price: Price) -> Option<DepthLevel> {
+        (price_point.order_count > 0).then(|| DepthLevel {
+            price,
+            quantity: price_point.quantity(),
+            order_count: price_point.order_count,
+        })
+    }
+
+    /// 指定价格档位的有效订单汇总（没有有效订单时返回None）
+    pub fn level_at(&self, side: Side, price: Price) -> Option<DepthLevel> {
+        let ladder = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
         };
+        ladder.level(price).and_then(|point| Self::aggregate_level(point, price))
+    }
+
+    /// 估算`side`方向`quantity`数量的市价单成交情况（不修改订单簿）
+    ///
+    /// 按价格优先遍历对手方聚合档位，用于下单前的冲击成本分析；
+    /// 不考虑自成交防护、熔断价格带等撮合时才生效的限制。
+    pub fn estimate_fill(&self, side: Side, quantity: Quantity) -> FillEstimate {
+        let levels = match side {
+            Side::Buy => self.iter_asks(),
+            Side::Sell => self.iter_bids(),
+        };
+        let mut estimate = FillEstimate { unfilled: quantity, ..FillEstimate::default() };
+        for level in levels {
+            if estimate.is_complete() {
+                break;
+            }
+            let take = estimate.unfilled.min(level.quantity);
+            estimate.filled += take;
+            estimate.unfilled -= take;
+            estimate.notional += level.price.get() as u128 * take.get() as u128;
+            estimate.worst_price = Some(level.price);
+            estimate.levels += 1;
+        }
+        estimate
+    }
+
+    /// 获取交易历史
+    pub fn trades(&self) -> &[Trade] {
+        &self.trades
+    }
+
+    /// 清空交易历史
+    pub fn clear_trades(&mut self) {
+        self.trades.clear();
+    }
+
+    /// 导出完整状态（所有挂单、止损单、GTD到期时间和ID计数器），用于热备引擎快速启动
+    ///
+    /// 格式（little-endian）:
+    /// - 4字节魔数 "RLOB" + 2字节版本
+    /// - 8字节下一个订单ID + 8字节下一个成交ID + 8字节最新成交价（0表示无）
+    /// - 4字节挂单数；每笔: 8字节订单ID + 8字节交易员 + 1字节方向 + 8字节价格 + 4字节数量 + 8字节GTD到期时间（0表示无）
+    ///   买方从高到低、卖方从低到高，同价位按时间优先顺序
+    /// - 4字节止损单数；每笔: 8字节订单ID + 8字节交易员 + 1字节方向 + 8字节触发价 + 8字节限价（0表示市价）+ 4字节数量
+    ///
+    /// 成交历史和熔断排队订单不导出。
+    pub fn export_state(&self) -> Vec<u8> {
+        let expiries: HashMap<OrderId, u64> = self
+            .expiries
+            .iter()
+            .map(|&(expires_at, order_id)| (order_id, expires_at))
+            .collect();
+
+        let mut resting = Vec::new();
+        let mut count = 0u32;
+        for (side, ladder, best) in [
+            (Side::Buy, &self.bids, self.bid_max),
+            (Side::Sell, &self.asks, self.ask_min),
+        ] {
+            let mut level = best;
+            while let Some(price) = level {
+                let mut current_idx = ladder.level(price).and_then(|point| point.first_order_idx);
+                while let Some(idx) = current_idx {
+                    let entry = self.arena.get(idx).unwrap();
+                    if entry.is_active() {
+                        resting.extend_from_slice(&entry.order_id.to_le_bytes());
+                        resting.extend_from_slice(entry.trader.as_bytes());
+                        resting.push(side as u8);
+                        resting.extend_from_slice(&price.get().to_le_bytes());
+                        resting.extend_from_slice(&entry.quantity.get().to_le_bytes());
+                        let expires_at = expiries.get(&entry.order_id).copied().unwrap_or(0);
+                        resting.extend_from_slice(&expires_at.to_le_bytes());
+                        count += 1;
+                    }
+                    current_idx = entry.next_idx;
+                }
+
+                level = match side {
+                    Side::Buy => price.checked_sub(1).and_then(|p| self.find_prev_bid(p)),
+                    Side::Sell => price.checked_add(1).and_then(|p| self.find_next_ask(p)),
+                };
+            }
+        }
+
+        let mut buf = Vec::with_capacity(38 + resting.len() + self.stops.len() * 33);
+        buf.extend_from_slice(STATE_MAGIC);
+        buf.extend_from_slice(&STATE_VERSION.to_le_bytes());
+        buf.extend_from_slice(&self.next_order_id.to_le_bytes());
+        buf.extend_from_slice(&self.next_trade_id.to_le_bytes());
+        buf.extend_from_slice(&self.last_trade_price.map_or(0, Price::get).to_le_bytes());
+        buf.extend_from_slice(&count.to_le_bytes());
+        buf.extend_from_slice(&resting);
+
+        buf.extend_from_slice(&(self.stops.len() as u32).to_le_bytes());
+        for stop in self.stops.iter() {
+            buf.extend_from_slice(&stop.order_id.to_le_bytes());
+            buf.extend_from_slice(stop.trader.as_bytes());
+            buf.push(stop.side as u8);
+            buf.extend_from_slice(&stop.stop_price.get().to_le_bytes());
+            buf.extend_from_slice(&stop.limit_price.map_or(0, Price::get).to_le_bytes());
+            buf.extend_from_slice(&stop.quantity.get().to_le_bytes());
+        }
+        buf
+    }
+
+    /// 从`export_state`的输出恢复状态
+    ///
+    /// 只能导入到空订单簿（没有挂单和止损单），否则返回`InvalidInput`；
+    /// 每笔恢复的挂单会发送`OrderAdded`事件。
+    pub fn import_state(&mut self, data: &[u8]) -> io::Result<()> {
+        if !self.order_index.is_empty() || !self.stops.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "order book is not empty"));
+        }
+
+        let reader = &mut &data[..];
+        let magic: [u8; 4] = read_array(reader)?;
+        if &magic != STATE_MAGIC {
+            return Err(invalid("not an order book state".to_string()));
+        }
+        let version = u16::from_le_bytes(read_array(reader)?);
+        if version != STATE_VERSION {
+            return Err(invalid(format!("unsupported state version {}", version)));
+        }
+
+        let next_order_id = u64::from_le_bytes(read_array(reader)?);
+        let next_trade_id = u64::from_le_bytes(read_array(reader)?);
+        let last_trade_price = Price::new(u64::from_le_bytes(read_array(reader)?));
+
+        let count = u32::from_le_bytes(read_array(reader)?);
+        for _ in 0..count {
+            let order_id = u64::from_le_bytes(read_array(reader)?);
+            let trader = TraderId::new(read_array(reader)?);
+            let side = read_side(reader)?;
+            let price = read_price(reader)?;
+            let quantity = read_quantity(reader)?;
+            let expires_at = u64::from_le_bytes(read_array(reader)?);
+
+            self.check_new_order(side, price).map_err(|reason| invalid(reason.to_string()))?;
+            if !self.add_order(order_id, trader, side, price, quantity) {
+                return Err(io::Error::new(io::ErrorKind::OutOfMemory, "order arena capacity exceeded"));
+            }
+            match side {
+                Side::Buy if self.bid_max.is_none_or(|max| price > max) => self.set_best(Side::Buy, Some(price)),
+                Side::Sell if self.ask_min.is_none_or(|min| price < min) => self.set_best(Side::Sell, Some(price)),
+                _ => {}
+            }
+            if expires_at != 0 {
+                self.expiries.insert((expires_at, order_id));
+            }
+        }
+
+        let count = u32::from_le_bytes(read_array(reader)?);
+        for _ in 0..count {
+            self.stops.insert(StopOrder {
+                order_id: u64::from_le_bytes(read_array(reader)?),
+                trader: TraderId::new(read_array(reader)?),
+                side: read_side(reader)?,
+                stop_price: read_price(reader)?,
+                limit_price: Price::new(u64::from_le_bytes(read_array(reader)?)),
+                quantity: read_quantity(reader)?,
+            });
+        }
+
+        if !reader.is_empty() {
+            return Err(invalid("trailing bytes after order book state".to_string()));
+        }
+
+        self.next_order_id = next_order_id;
+        self.next_trade_id = next_trade_id;
+        self.last_trade_price = last_trade_price;
+        Ok(())
+    }
+
+    /// 内部一致性自检（用于性质测试和排查，遍历全部价格档位，开销较大）
+    ///
+    /// - 档位链表前后指针一致，条目均为有效订单，方向、价格与所在档位一致，汇总量等于条目之和
+    /// - 交易员挂单汇总等于其在簿挂单之和
+    /// - 订单索引恰好覆盖全部有效条目，且指向其所在的内存池槽位
+    /// - 最佳买价不低于任何有效买单价格、最佳卖价不高于任何有效卖单价格
+    /// - 有效挂单不交叉（最高买价低于最低卖价）
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let mut active_orders = 0;
+        let mut traders: HashMap<TraderId, TraderStats> = HashMap::new();
+        let (mut best_bid, mut best_ask) = (None, None);
+        for (side, ladder) in [(Side::Buy, &self.bids), (Side::Sell, &self.asks)] {
+            let mut next = ladder.next_non_empty(Price::MIN);
+            while let Some(price) = next {
+                let point = ladder.level(price).unwrap();
+                let (mut quantity, mut count) = (0u64, 0u32);
+                let mut prev_idx = None;
+                let mut current_idx = point.first_order_idx;
+                while let Some(idx) = current_idx {
+                    let entry = self.arena.get(idx).ok_or_else(|| format!("dangling index {} at {} {}", idx, side, price))?;
+                    if entry.side != side || entry.price != price {
+                        return Err(format!("order {} linked at {} {} but is {} {}", entry.order_id, side, price, entry.side, entry.price));
+                    }
+                    if !entry.is_active() {
+                        return Err(format!("inactive order {} still linked at {} {}", entry.order_id, side, price));
+                    }
+                    if entry.prev_idx != prev_idx {
+                        return Err(format!("order {} prev link {:?}, expected {:?}", entry.order_id, entry.prev_idx, prev_idx));
+                    }
+                    if self.order_slot(entry.order_id) != Some(idx) {
+                        return Err(format!("order {} not indexed at slot {}", entry.order_id, idx));
+                    }
+                    quantity += entry.quantity.get() as u64;
+                    count += 1;
+                    traders.entry(entry.trader).or_default().add_order(side, entry.quantity);
+                    prev_idx = current_idx;
+                    current_idx = entry.next_idx;
+                }
+                if point.last_order_idx != prev_idx {
+                    return Err(format!("level {} {} tail mismatch", side, price));
+                }
+                if (quantity, count) != (point.total_quantity, point.order_count) {
+                    return Err(format!(
+                        "level {} {} totals {}/{} but entries sum to {}/{}",
+                        side, price, point.total_quantity, point.order_count, quantity, count
+                    ));
+                }
+                if count > 0 {
+                    active_orders += count as usize;
+                    match side {
+                        Side::Buy => best_bid = Some(price),
+                        Side::Sell => best_ask = best_ask.or(Some(price)),
+                    }
+                }
+                next = price.checked_add(1).and_then(|p| ladder.next_non_empty(p));
+            }
+        }
 
-        // Link to existing orders at this price level
-        if let Some(last_idx) = price_point.last_order_idx {
-            self.arena.get_mut(last_idx).unwrap().next_idx = Some(idx);
+        if active_orders != self.order_index.len() {
+            return Err(format!("{} active entries but {} indexed orders", active_orders, self.order_index.len()));
+        }
+        for (trader, stats) in &self.traders {
+            let expected = traders.get(trader).copied().unwrap_or_default();
+            let open = |s: &TraderStats| (s.open_orders, s.open_buy_quantity, s.open_sell_quantity);
+            if open(stats) != open(&expected) {
+                return Err(format!("trader {} stats {:?} but book holds {:?}", trader, stats, expected));
+            }
         }
+        if let Some(trader) = traders.keys().find(|trader| !self.traders.contains_key(trader)) {
+            return Err(format!("trader {} has resting orders but no stats", trader));
+        }
+        if best_bid.is_some_and(|bid| self.bid_max.is_none_or(|max| bid > max)) {
+            return Err(format!("bid_max {:?} below active bid {:?}", self.bid_max, best_bid));
+        }
+        if best_ask.is_some_and(|ask| self.ask_min.is_none_or(|min| ask < min)) {
+            return Err(format!("ask_min {:?} above active ask {:?}", self.ask_min, best_ask));
+        }
+        if let (Some(bid), Some(ask)) = (best_bid, best_ask)
+            && bid >= ask
+        {
+            return Err(format!("crossed book: bid {} >= ask {}", bid, ask));
+        }
+        Ok(())
+    }
 
-        price_point.push_back(idx);
+    /// 设定交易员的净持仓（如开盘前从风控系统载入隔夜持仓）
+    pub fn set_position(&mut self, trader: TraderId, net_position: i64) {
+        self.traders.entry(trader).or_default().net_position = net_position;
     }
 
-    /// 取消订单
-    pub fn cancel_order(&mut self, order_id: OrderId) -> bool {
-        if let Some(&idx) = self.order_index.get(&order_id) {
-            if let Some(entry) = self.arena.get_mut(idx) {
-                entry.cancel();
-                self.order_index.remove(&order_id);
-                return true;
-            }
-        }
-        false
+    /// 交易员的挂单与成交汇总（从未下单的交易员返回全零）
+    #[inline]
+    pub fn trader_stats(&self, trader: TraderId) -> TraderStats {
+        self.traders.get(&trader).copied().unwrap_or_default()
     }
 
-    /// 查找下一个非空的卖价级别
-    fn find_next_ask(&self, start_price: Price) -> Option<Price> {
-        for price in (start_price as usize)..self.asks.len() {
-            if !self.asks[price].is_empty() {
-                return Some(price as Price);
-            }
-        }
+    /// 下单与撤单时延分位数（未开启`latency-histogram`特性时为None）
+    pub fn latency_summary(&self) -> Option<LatencySummary> {
+        #[cfg(feature = "latency-histogram")]
+        return Some(self.latency.summary());
+        #[cfg(not(feature = "latency-histogram"))]
         None
     }
 
-    /// 查找上一个非空的买价级别
-    fn find_prev_bid(&self, start_price: Price) -> Option<Price> {
-        let max_price = start_price.min((self.bids.len() - 1) as u32);
-        for price in (0..=max_price as usize).rev() {
-            if !self.bids[price].is_empty() {
-                return Some(price as Price);
-            }
+    /// 清空时延样本（未开启`latency-histogram`特性时无操作）
+    pub fn reset_latency(&mut self) {
+        #[cfg(feature = "latency-histogram")]
+        self.latency.reset();
+    }
+
+    /// 以运行时功能标志（如`RuntimeConfig::feature("latency_histograms", ..)`）开关时延采样
+    ///
+    /// 未开启`latency-histogram`特性时无操作。
+    pub fn set_latency_flag(&mut self, flag: FeatureFlag) {
+        #[cfg(feature = "latency-histogram")]
+        {
+            self.latency_flag = Some(flag);
         }
-        None
+        #[cfg(not(feature = "latency-histogram"))]
+        let _ = flag;
     }
 
-    /// 获取交易历史
-    pub fn trades(&self) -> &[Trade] {
-        &self.trades
+    #[cfg(feature = "latency-histogram")]
+    #[inline]
+    fn latency_sampling(&self) -> bool {
+        self.latency_flag.as_ref().is_none_or(FeatureFlag::is_enabled)
     }
 
-    /// 清空交易历史
-    pub fn clear_trades(&mut self) {
-        self.trades.clear();
+    /// 累计计数（不受`clear_trades`影响）
+    #[inline]
+    pub fn counters(&self) -> EngineCounters {
+        self.counters
     }
 
     /// 获取订单簿状态快照
@@ -335,7 +2028,40 @@ impl OrderBook {
             ask_min: self.ask_min,
             active_orders: self.order_index.len(),
             total_trades: self.trades.len(),
+            arena_used: self.arena.len(),
+            arena_capacity: self.arena.capacity(),
+            trading_mode: self.mode,
+            counters: self.counters,
+            latency: self.latency_summary(),
+        }
+    }
+}
+
+/// 价格档位迭代器（见`OrderBook::iter_bids`/`iter_asks`）
+///
+/// 每个档位汇总有效订单的数量和笔数，跳过只剩已取消订单的价格。
+pub struct LevelIter<'a> {
+    book: &'a OrderBook,
+    side: Side,
+    next: Option<Price>,
+}
+
+impl Iterator for LevelIter<'_> {
+    type Item = DepthLevel;
+
+    fn next(&mut self) -> Option<DepthLevel> {
+        while let Some(price) = self.next {
+            let book = self.book;
+            let (ladder, next) = match self.side {
+                Side::Buy => (&book.bids, price.checked_sub(1).and_then(|p| book.find_prev_bid(p))),
+                Side::Sell => (&book.asks, price.checked_add(1).and_then(|p| book.find_next_ask(p))),
+            };
+            self.next = next;
+            if let Some(level) = ladder.level(price).and_then(|point| OrderBook::aggregate_level(point, price)) {
+                return Some(level);
+            }
         }
+        None
     }
 }
 
@@ -345,30 +2071,64 @@ impl Default for OrderBook {
     }
 }
 
+/// 撮合引擎累计计数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct EngineCounters {
+    pub orders_accepted: u64,         // 受理的订单（含熔断排队）
+    pub orders_rejected: u64,         // 被拒绝的订单
+    pub orders_cancelled: u64,        // 主动撤单
+    pub trades: u64,                  // 成交笔数
+    pub traded_volume: u64,           // 成交数量合计
+}
+
+/// 订单簿预分配存储统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct AllocationStats {
+    pub arena_capacity: usize,        // 内存池槽位容量
+    pub arena_reserved: usize,        // 内存池已分配存储的槽位数
+    pub index_capacity: usize,        // 订单索引不扩容可容纳的条目数
+    pub ladder_bytes: usize,          // 买卖价格阶梯占用字节数
+    pub arena_bytes: usize,           // 内存池占用字节数
+    pub index_bytes: usize,           // 订单索引占用字节数
+}
+
+impl AllocationStats {
+    /// 合计字节数
+    pub fn total_bytes(&self) -> usize {
+        self.ladder_bytes + self.arena_bytes + self.index_bytes
+    }
+}
+
 /// 订单簿状态快照
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
 pub struct OrderBookSnapshot {
     pub next_order_id: OrderId,       // 下一个订单ID
     pub bid_max: Option<Price>,       // 最佳买价
     pub ask_min: Option<Price>,       // 最佳卖价
     pub active_orders: usize,         // 活跃订单数
     pub total_trades: usize,          // 总交易数
+    pub arena_used: usize,            // 订单内存池已用槽位
+    pub arena_capacity: usize,        // 订单内存池容量
+    pub trading_mode: TradingMode,    // 撮合模式
+    pub counters: EngineCounters,     // 累计计数
+    pub latency: Option<LatencySummary>,  // 下单/撤单时延分位数（需开启latency-histogram特性）
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::orderbook::types::{px, qty};
 
     #[test]
     fn test_simple_buy_order() {
         let mut book = OrderBook::new();
         let trader = TraderId::from_str("TRADER1");
 
-        let (order_id, trades) = book.limit_order(trader, Side::Buy, 10000, 100);
+        let (order_id, trades) = book.limit_order(trader, Side::Buy, px(10000), qty(100), TimeInForce::Gtc).unwrap();
 
         assert_eq!(order_id, 1);
         assert_eq!(trades.len(), 0); // No matches
-        assert_eq!(book.best_bid(), Some(10000));
+        assert_eq!(book.best_bid(), Some(px(10000)));
         assert_eq!(book.best_ask(), None);
     }
 
@@ -379,14 +2139,35 @@ mod tests {
         let seller = TraderId::from_str("SELLER");
 
         // Place sell order
-        book.limit_order(seller, Side::Sell, 10000, 100);
+        book.limit_order(seller, Side::Sell, px(10000), qty(100), TimeInForce::Gtc).unwrap();
 
         // Place matching buy order
-        let (_order_id, trades) = book.limit_order(buyer, Side::Buy, 10000, 100);
+        let (_order_id, trades) = book.limit_order(buyer, Side::Buy, px(10000), qty(100), TimeInForce::Gtc).unwrap();
 
         assert_eq!(trades.len(), 1);
-        assert_eq!(trades[0].quantity, 100);
-        assert_eq!(trades[0].price, 10000);
+        assert_eq!(trades[0].quantity, qty(100));
+        assert_eq!(trades[0].price, px(10000));
+    }
+
+    #[test]
+    fn test_trades_carry_ids_and_order_attribution() {
+        let mut book = OrderBook::new();
+        let (maker1, _) = book.limit_order(TraderId::from_str("S1"), Side::Sell, px(10000), qty(5), TimeInForce::Gtc).unwrap();
+        let (maker2, _) = book.limit_order(TraderId::from_str("S2"), Side::Sell, px(10001), qty(5), TimeInForce::Gtc).unwrap();
+
+        let (taker, trades) = book.limit_order(TraderId::from_str("B"), Side::Buy, px(10001), qty(12), TimeInForce::Gtc).unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!((trades[0].trade_id, trades[1].trade_id), (1, 2));
+        assert_eq!((trades[0].maker_order_id, trades[1].maker_order_id), (maker1, maker2));
+        assert!(trades.iter().all(|t| t.taker_order_id == taker && t.aggressor_side == Side::Buy));
+        assert!(trades[0].timestamp_ns > 0);
+        assert_eq!(trades[0].timestamp_ns, trades[1].timestamp_ns);
+
+        let (seller, trades) = book.limit_order(TraderId::from_str("S3"), Side::Sell, px(10000), qty(1), TimeInForce::Gtc).unwrap();
+        assert_eq!(trades[0].trade_id, 3);
+        assert_eq!(trades[0].aggressor_side, Side::Sell);
+        assert_eq!((trades[0].maker_order_id, trades[0].taker_order_id), (taker, seller));
+        assert_eq!(book.next_trade_id(), 4);
     }
 
     #[test]
@@ -396,14 +2177,14 @@ mod tests {
         let seller = TraderId::from_str("SELLER");
 
         // Place large sell order
-        book.limit_order(seller, Side::Sell, 10000, 200);
+        book.limit_order(seller, Side::Sell, px(10000), qty(200), TimeInForce::Gtc).unwrap();
 
         // Place smaller buy order
-        let (_order_id, trades) = book.limit_order(buyer, Side::Buy, 10000, 50);
+        let (_order_id, trades) = book.limit_order(buyer, Side::Buy, px(10000), qty(50), TimeInForce::Gtc).unwrap();
 
         assert_eq!(trades.len(), 1);
-        assert_eq!(trades[0].quantity, 50);
-        assert_eq!(book.best_ask(), Some(10000)); // Still has remaining
+        assert_eq!(trades[0].quantity, qty(50));
+        assert_eq!(book.best_ask(), Some(px(10000))); // Still has remaining
     }
 
     #[test]
@@ -413,13 +2194,13 @@ mod tests {
         let seller = TraderId::from_str("SELLER");
 
         // Place sell order at 10000
-        book.limit_order(seller, Side::Sell, 10000, 100);
+        book.limit_order(seller, Side::Sell, px(10000), qty(100), TimeInForce::Gtc).unwrap();
 
         // Place buy order at higher price (11000)
-        let (_order_id, trades) = book.limit_order(buyer, Side::Buy, 11000, 100);
+        let (_order_id, trades) = book.limit_order(buyer, Side::Buy, px(11000), qty(100), TimeInForce::Gtc).unwrap();
 
         assert_eq!(trades.len(), 1);
-        assert_eq!(trades[0].price, 10000); // Matched at seller's price
+        assert_eq!(trades[0].price, px(10000)); // Matched at seller's price
     }
 
     #[test]
@@ -427,21 +2208,1348 @@ mod tests {
         let mut book = OrderBook::new();
         let trader = TraderId::from_str("TRADER1");
 
-        let (order_id, _) = book.limit_order(trader, Side::Buy, 10000, 100);
-        assert!(book.cancel_order(order_id));
-        assert!(!book.cancel_order(order_id)); // Already cancelled
+        let (order_id, _) = book.limit_order(trader, Side::Buy, px(10000), qty(100), TimeInForce::Gtc).unwrap();
+        assert!(book.cancel_order(order_id).is_ok());
+        assert!(book.cancel_order(order_id).is_err()); // Already cancelled
+    }
+
+    #[test]
+    fn test_limit_order_into_reuses_buffer() {
+        let mut book = OrderBook::new();
+        let mut fills = Vec::with_capacity(4);
+
+        book.limit_order_into(TraderId::from_str("S1"), Side::Sell, px(10000), qty(10), TimeInForce::Gtc, &mut fills).unwrap();
+        book.limit_order_into(TraderId::from_str("S2"), Side::Sell, px(10001), qty(10), TimeInForce::Gtc, &mut fills).unwrap();
+        assert!(fills.is_empty());
+
+        let ptr = fills.as_ptr();
+        book.limit_order_into(TraderId::from_str("B"), Side::Buy, px(10001), qty(15), TimeInForce::Gtc, &mut fills).unwrap();
+        assert_eq!(fills.len(), 2);
+        assert_eq!(fills[0].quantity, qty(10));
+        assert_eq!(fills[1].quantity, qty(5));
+        assert_eq!(fills.as_ptr(), ptr); // 未重新分配
+        assert_eq!(book.trades().len(), 2);
+
+        // 缓冲区中已有的成交不会重复记录
+        book.limit_order_into(TraderId::from_str("B"), Side::Buy, px(10001), qty(5), TimeInForce::Gtc, &mut fills).unwrap();
+        assert_eq!(fills.len(), 3);
+        assert_eq!(book.trades().len(), 3);
+    }
+
+    #[test]
+    fn test_ioc_cancels_remainder() {
+        let mut book = OrderBook::new();
+        book.limit_order(TraderId::from_str("S"), Side::Sell, px(10000), qty(30), TimeInForce::Gtc).unwrap();
+
+        let (_, trades) = book.limit_order(TraderId::from_str("B"), Side::Buy, px(10000), qty(50), TimeInForce::Ioc).unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].quantity, qty(30));
+        assert_eq!(book.best_bid(), None); // 剩余部分未挂单
+        assert_eq!(book.best_ask(), None);
+
+        let (_, trades) = book.limit_order(TraderId::from_str("B"), Side::Buy, px(10000), qty(50), TimeInForce::Ioc).unwrap();
+        assert!(trades.is_empty());
+        assert_eq!(book.snapshot().active_orders, 0);
+    }
+
+    #[test]
+    fn test_fok_all_or_nothing() {
+        let mut book = OrderBook::new();
+        book.limit_order(TraderId::from_str("S1"), Side::Sell, px(10000), qty(30), TimeInForce::Gtc).unwrap();
+        book.limit_order(TraderId::from_str("S2"), Side::Sell, px(10002), qty(30), TimeInForce::Gtc).unwrap();
+
+        // 限价内只有30，不足50：整单取消且不影响订单簿
+        let (_, trades) = book.limit_order(TraderId::from_str("B"), Side::Buy, px(10001), qty(50), TimeInForce::Fok).unwrap();
+        assert!(trades.is_empty());
+        assert_eq!(book.best_ask(), Some(px(10000)));
+        assert_eq!(book.snapshot().active_orders, 2);
+
+        // 跨两个价位足量：全部成交
+        let (_, trades) = book.limit_order(TraderId::from_str("B"), Side::Buy, px(10002), qty(50), TimeInForce::Fok).unwrap();
+        assert_eq!(trades.iter().map(|t| t.quantity).sum::<Quantity>(), qty(50));
+        assert_eq!(book.best_ask(), Some(px(10002)));
+        assert_eq!(book.best_bid(), None);
+
+        // 卖方向同样适用
+        book.limit_order(TraderId::from_str("B"), Side::Buy, px(9990), qty(10), TimeInForce::Gtc).unwrap();
+        let (_, trades) = book.limit_order(TraderId::from_str("S"), Side::Sell, px(9990), qty(11), TimeInForce::Fok).unwrap();
+        assert!(trades.is_empty());
+        assert_eq!(book.best_bid(), Some(px(9990)));
+    }
+
+    #[test]
+    fn test_stop_limit_triggers_on_last_trade() {
+        let mut book = OrderBook::new();
+        let (stop_id, trades) = book.stop_limit_order(TraderId::from_str("STOP"), Side::Buy, px(10100), px(10200), qty(5));
+        assert!(trades.is_empty());
+        assert_eq!(book.stop_orders().len(), 1);
+
+        // 成交价未到触发价
+        book.limit_order(TraderId::from_str("S"), Side::Sell, px(10000), qty(10), TimeInForce::Gtc).unwrap();
+        book.limit_order(TraderId::from_str("B"), Side::Buy, px(10000), qty(10), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.stop_orders().len(), 1);
+
+        // 成交价10100穿越触发价：止损限价单以10200挂出并与10150的卖单成交
+        book.limit_order(TraderId::from_str("S"), Side::Sell, px(10100), qty(1), TimeInForce::Gtc).unwrap();
+        book.limit_order(TraderId::from_str("S"), Side::Sell, px(10150), qty(3), TimeInForce::Gtc).unwrap();
+        let (_, trades) = book.limit_order(TraderId::from_str("B"), Side::Buy, px(10100), qty(1), TimeInForce::Gtc).unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[1].buyer, TraderId::from_str("STOP"));
+        assert_eq!(trades[1].price, px(10150));
+        assert!(book.stop_orders().is_empty());
+
+        // 剩余2手以原订单ID挂在10200
+        assert_eq!(book.best_bid(), Some(px(10200)));
+        assert!(book.cancel_order(stop_id).is_ok());
+    }
+
+    #[test]
+    fn test_stop_market_cascade_and_cancel() {
+        let mut book = OrderBook::new();
+        book.limit_order(TraderId::from_str("B1"), Side::Buy, px(9900), qty(5), TimeInForce::Gtc).unwrap();
+        book.limit_order(TraderId::from_str("B2"), Side::Buy, px(9800), qty(5), TimeInForce::Gtc).unwrap();
+
+        book.stop_order(TraderId::from_str("ST1"), Side::Sell, px(9950), qty(5));
+        book.stop_order(TraderId::from_str("ST2"), Side::Sell, px(9900), qty(5));
+        let (cancelled, _) = book.stop_order(TraderId::from_str("ST3"), Side::Sell, px(9000), qty(5));
+        assert!(book.cancel_order(cancelled).is_ok());
+
+        // 9950成交触发ST1，ST1在9900成交又触发ST2
+        book.limit_order(TraderId::from_str("B0"), Side::Buy, px(9950), qty(1), TimeInForce::Gtc).unwrap();
+        let (_, trades) = book.limit_order(TraderId::from_str("S0"), Side::Sell, px(9950), qty(1), TimeInForce::Gtc).unwrap();
+        assert_eq!(trades.len(), 3);
+        assert_eq!(trades[1].seller, TraderId::from_str("ST1"));
+        assert_eq!(trades[2].seller, TraderId::from_str("ST2"));
+        assert_eq!(book.last_trade_price(), Some(px(9800)));
+        assert_eq!(book.best_bid(), None);
+        assert!(book.stop_orders().is_empty());
+    }
+
+    #[test]
+    fn test_depth_aggregates_levels() {
+        let mut book = OrderBook::new();
+        book.limit_order(TraderId::from_str("B1"), Side::Buy, px(9900), qty(10), TimeInForce::Gtc).unwrap();
+        book.limit_order(TraderId::from_str("B2"), Side::Buy, px(9900), qty(5), TimeInForce::Gtc).unwrap();
+        book.limit_order(TraderId::from_str("B3"), Side::Buy, px(9800), qty(7), TimeInForce::Gtc).unwrap();
+        let (cancelled, _) = book.limit_order(TraderId::from_str("B4"), Side::Buy, px(9850), qty(3), TimeInForce::Gtc).unwrap();
+        book.cancel_order(cancelled).unwrap();
+        book.limit_order(TraderId::from_str("S1"), Side::Sell, px(10000), qty(4), TimeInForce::Gtc).unwrap();
+        book.limit_order(TraderId::from_str("S2"), Side::Sell, px(10100), qty(6), TimeInForce::Gtc).unwrap();
+        book.limit_order(TraderId::from_str("S3"), Side::Sell, px(10200), qty(8), TimeInForce::Gtc).unwrap();
+
+        let depth = book.depth(2);
+        assert_eq!(
+            depth.bids,
+            vec![
+                DepthLevel { price: px(9900), quantity: qty(15), order_count: 2 },
+                DepthLevel { price: px(9800), quantity: qty(7), order_count: 1 },
+            ]
+        );
+        assert_eq!(depth.asks.len(), 2);
+        assert_eq!(depth.asks[0], DepthLevel { price: px(10000), quantity: qty(4), order_count: 1 });
+        assert_eq!(depth.asks[1].price, px(10100));
+
+        assert!(book.depth(0).bids.is_empty());
+        assert_eq!(book.depth(10).asks.len(), 3);
+    }
+
+    struct EventRecorder(std::sync::Arc<std::sync::Mutex<Vec<BookEvent>>>);
+
+    impl BookEventListener for EventRecorder {
+        fn on_event(&mut self, event: &BookEvent) {
+            self.0.lock().unwrap().push(*event);
+        }
+    }
+
+    #[test]
+    fn test_book_events_cover_every_mutation() {
+        let mut book = OrderBook::with_capacity(20_000, 100);
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        book.set_event_listener(Box::new(EventRecorder(events.clone())));
+
+        let seller = TraderId::from_str("SELLER");
+        let buyer = TraderId::from_str("BUYER");
+
+        let (ask, _) = book.limit_order(seller, Side::Sell, px(10000), qty(100), TimeInForce::Gtc).unwrap();
+        assert!(book.reduce_order(ask, qty(80)));
+        assert!(!book.reduce_order(ask, qty(80)));
+        let (bid, _) = book.limit_order(buyer, Side::Buy, px(10000), qty(30), TimeInForce::Gtc).unwrap();
+        // IOC剩余部分不挂单，不产生OrderAdded
+        let (ioc, _) = book.limit_order(buyer, Side::Buy, px(10000), qty(60), TimeInForce::Ioc).unwrap();
+        assert!(book.cancel_order(ask).is_err());
+
+        let (rest, _) = book.limit_order(buyer, Side::Buy, px(9990), qty(10), TimeInForce::Gtc).unwrap();
+        assert!(book.cancel_order(rest).is_ok());
+
+        let events = events.lock().unwrap();
+        assert_eq!(
+            *events,
+            vec![
+                BookEvent::OrderAdded {
+                    order_id: ask,
+                    trader: seller,
+                    side: Side::Sell,
+                    price: px(10000),
+                    quantity: qty(100),
+                },
+                BookEvent::OrderAmended {
+                    order_id: ask,
+                    side: Side::Sell,
+                    old_price: px(10000),
+                    new_price: px(10000),
+                    old_quantity: qty(100),
+                    new_quantity: qty(80),
+                    priority_retained: true,
+                },
+                BookEvent::OrderExecuted {
+                    order_id: ask,
+                    aggressor_id: bid,
+                    price: px(10000),
+                    quantity: qty(30),
+                    remaining: qty(50),
+                },
+                BookEvent::OrderExecuted {
+                    order_id: ask,
+                    aggressor_id: ioc,
+                    price: px(10000),
+                    quantity: qty(50),
+                    remaining: Quantity::ZERO,
+                },
+                BookEvent::OrderAdded {
+                    order_id: rest,
+                    trader: buyer,
+                    side: Side::Buy,
+                    price: px(9990),
+                    quantity: qty(10),
+                },
+                BookEvent::OrderCancelled { order_id: rest, quantity: qty(10) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replace_order_events_and_priority() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        book.set_event_listener(Box::new(EventRecorder(events.clone())));
+
+        let mm = TraderId::from_str("MM");
+        let (first, _) = book.limit_order(mm, Side::Buy, px(100), qty(10), TimeInForce::Gtc).unwrap();
+        let (second, _) = book.limit_order(mm, Side::Buy, px(100), qty(10), TimeInForce::Gtc).unwrap();
+        book.limit_order(mm, Side::Sell, px(105), qty(10), TimeInForce::Gtc).unwrap();
+
+        // 穿价、无变化、不存在的订单均被拒绝
+        assert!(!book.replace_order(first, px(105), qty(10)));
+        assert!(!book.replace_order(first, px(100), qty(10)));
+        assert!(!book.replace_order(999, px(100), qty(5)));
+
+        // 同价减量保留优先级；加量移到队尾
+        assert!(book.replace_order(second, px(100), qty(4)));
+        assert!(book.replace_order(first, px(100), qty(12)));
+        // 改价后成为新的最优买价
+        assert!(book.replace_order(second, px(102), qty(4)));
+        assert_eq!(book.best_bid(), Some(px(102)));
+        assert_eq!(book.order_quantity(second), Some(qty(4)));
+
+        let (_, trades) = book.limit_order(TraderId::from_str("TK"), Side::Sell, px(100), qty(6), TimeInForce::Ioc).unwrap();
+        assert_eq!(
+            trades.iter().map(|t| (t.maker_order_id, t.quantity)).collect::<Vec<_>>(),
+            vec![(second, qty(4)), (first, qty(2))]
+        );
+        assert_eq!(book.depth(1).bids[0], DepthLevel { price: px(100), quantity: qty(10), order_count: 1 });
+
+        let amended: Vec<_> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| matches!(event, BookEvent::OrderAmended { .. }))
+            .copied()
+            .collect();
+        assert_eq!(amended, vec![
+            BookEvent::OrderAmended {
+                order_id: second,
+                side: Side::Buy,
+                old_price: px(100),
+                new_price: px(100),
+                old_quantity: qty(10),
+                new_quantity: qty(4),
+                priority_retained: true,
+            },
+            BookEvent::OrderAmended {
+                order_id: first,
+                side: Side::Buy,
+                old_price: px(100),
+                new_price: px(100),
+                old_quantity: qty(10),
+                new_quantity: qty(12),
+                priority_retained: false,
+            },
+            BookEvent::OrderAmended {
+                order_id: second,
+                side: Side::Buy,
+                old_price: px(100),
+                new_price: px(102),
+                old_quantity: qty(4),
+                new_quantity: qty(4),
+                priority_retained: false,
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_sparse_ladder_high_prices() {
+        let mut book = OrderBook::with_ladder(LadderKind::Sparse, u32::MAX as usize, 100);
+        let seller = TraderId::from_str("SELLER");
+        let buyer = TraderId::from_str("BUYER");
+
+        book.limit_order(seller, Side::Sell, px(4_000_000_100), qty(5), TimeInForce::Gtc).unwrap();
+        book.limit_order(seller, Side::Sell, px(4_000_000_000), qty(5), TimeInForce::Gtc).unwrap();
+        book.limit_order(buyer, Side::Buy, px(3_999_999_000), qty(5), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.best_ask(), Some(px(4_000_000_000)));
+
+        let (_, trades) = book.limit_order(buyer, Side::Buy, px(4_000_000_100), qty(7), TimeInForce::Gtc).unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].price, px(4_000_000_000));
+        assert_eq!(trades[1].price, px(4_000_000_100));
+        assert_eq!(book.best_ask(), Some(px(4_000_000_100)));
+        assert_eq!(book.best_bid(), Some(px(3_999_999_000)));
+
+        let (_, trades) = book.stop_order(seller, Side::Sell, px(4_000_000_200), qty(5));
+        assert_eq!(trades.len(), 1);
+        assert_eq!(book.best_bid(), None);
+    }
+
+    #[test]
+    fn test_arena_slots_are_recycled() {
+        // 容量只有2个槽位，长时间运行也不会耗尽
+        let mut book = OrderBook::with_capacity(20_000, 2);
+        let seller = TraderId::from_str("SELLER");
+        let buyer = TraderId::from_str("BUYER");
+
+        for _ in 0..1_000 {
+            let (ask, _) = book.limit_order(seller, Side::Sell, px(10000), qty(10), TimeInForce::Gtc).unwrap();
+            let (cancelled, _) = book.limit_order(seller, Side::Sell, px(10000), qty(10), TimeInForce::Gtc).unwrap();
+            assert!(book.cancel_order(cancelled).is_ok());
+
+            let (_, trades) = book.limit_order(buyer, Side::Buy, px(10000), qty(10), TimeInForce::Gtc).unwrap();
+            assert_eq!(trades.len(), 1);
+            assert_eq!(book.order_quantity(ask), None);
+            assert!(book.cancel_order(ask).is_err());
+        }
+        assert_eq!(book.best_ask(), None);
+    }
+
+    #[test]
+    fn test_gtd_orders_expire() {
+        let mut book = OrderBook::with_capacity(20_000, 100);
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        book.set_event_listener(Box::new(EventRecorder(events.clone())));
+        let trader = TraderId::from_str("MM");
+
+        let (early, _) = book.limit_order(trader, Side::Sell, px(10000), qty(5), TimeInForce::Gtd(1_000)).unwrap();
+        let (late, _) = book.limit_order(trader, Side::Sell, px(10010), qty(5), TimeInForce::Gtd(2_000)).unwrap();
+        let (filled, _) = book.limit_order(trader, Side::Buy, px(9990), qty(3), TimeInForce::Gtd(500)).unwrap();
+        book.limit_order(trader, Side::Sell, px(9990), qty(3), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.next_expiry(), Some(500));
+
+        // 已成交的订单不再到期
+        assert!(book.expire_orders(999).is_empty());
+        assert_eq!(book.expire_orders(1_000), vec![early]);
+        assert_eq!(book.order_quantity(early), None);
+        assert_eq!(book.order_quantity(late), Some(qty(5)));
+        assert!(book.cancel_order(early).is_err());
+
+        assert_eq!(book.expire_orders(u64::MAX), vec![late]);
+        assert_eq!(book.next_expiry(), None);
+        assert!(book.depth(10).asks.is_empty());
+
+        let events = events.lock().unwrap();
+        assert!(events.contains(&BookEvent::OrderExpired { order_id: early, quantity: qty(5) }));
+        assert!(!events.iter().any(|e| matches!(e, BookEvent::OrderExpired { order_id, .. } if *order_id == filled)));
+    }
+
+    #[tokio::test]
+    async fn test_background_expiry_sweep() {
+        let book = Arc::new(Mutex::new(OrderBook::with_capacity(20_000, 100)));
+        let (order_id, _) =
+            book.lock().limit_order(TraderId::from_str("MM"), Side::Buy, px(9900), qty(1), TimeInForce::Gtd(1)).unwrap();
+
+        let sweep = OrderBook::spawn_expiry_sweep(Arc::clone(&book), Duration::from_millis(5));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        sweep.abort();
+
+        assert_eq!(book.lock().order_quantity(order_id), None);
     }
 
     #[test]
     fn test_spread() {
         let mut book = OrderBook::new();
 
-        book.limit_order(TraderId::from_str("B"), Side::Buy, 9900, 100);
-        book.limit_order(TraderId::from_str("S"), Side::Sell, 10100, 100);
+        book.limit_order(TraderId::from_str("B"), Side::Buy, px(9900), qty(100), TimeInForce::Gtc).unwrap();
+        book.limit_order(TraderId::from_str("S"), Side::Sell, px(10100), qty(100), TimeInForce::Gtc).unwrap();
 
-        assert_eq!(book.best_bid(), Some(9900));
-        assert_eq!(book.best_ask(), Some(10100));
+        assert_eq!(book.best_bid(), Some(px(9900)));
+        assert_eq!(book.best_ask(), Some(px(10100)));
         assert_eq!(book.spread(), Some(200));
-        assert_eq!(book.mid_price(), Some(10000));
+        assert_eq!(book.mid_price(), Some(px(10000)));
+    }
+
+    #[test]
+    fn test_apply_batch_matches_sequential() {
+        let limit = |trader: &str, side, price, quantity| Command::Limit {
+            trader: TraderId::from_str(trader),
+            side,
+            price: px(price),
+            quantity: qty(quantity),
+            tif: TimeInForce::Gtc,
+        };
+        let commands = [
+            limit("S1", Side::Sell, 10010, 5),
+            limit("S2", Side::Sell, 10020, 5),
+            limit("B1", Side::Buy, 10020, 7),
+            Command::Amend { order_id: 2, quantity: qty(2) },
+            Command::Cancel { order_id: 1 },
+            limit("B2", Side::Buy, 9990, 4),
+        ];
+
+        let mut batched = OrderBook::new();
+        let results = batched.apply_batch(&commands);
+
+        let mut sequential = OrderBook::new();
+        for (command, result) in commands.iter().zip(&results) {
+            match (command.execute(&mut sequential), result) {
+                (
+                    CommandResult::Accepted { order_id: a, trades: a_trades },
+                    CommandResult::Accepted { order_id: b, trades: b_trades },
+                ) => {
+                    assert_eq!(a, *b);
+                    assert!(a_trades.iter().zip(b_trades).all(|(a, b)| a.same_execution(b)));
+                }
+                (expected, result) => assert_eq!(&expected, result),
+            }
+        }
+        assert_eq!(results.len(), commands.len());
+        assert_eq!(batched.depth(10), sequential.depth(10));
+        assert_eq!(results[3], CommandResult::Amended { order_id: 2, success: true });
+    }
+
+    #[test]
+    fn test_export_import_state() {
+        let mut book = OrderBook::with_capacity(20_000, 64);
+        let a = TraderId::from_str("A");
+        let b = TraderId::from_str("B");
+        book.limit_order(a, Side::Buy, px(9990), qty(5), TimeInForce::Gtc).unwrap();
+        book.limit_order(b, Side::Buy, px(9990), qty(3), TimeInForce::Gtd(5_000)).unwrap();
+        book.limit_order(a, Side::Buy, px(9980), qty(4), TimeInForce::Gtc).unwrap();
+        let (cancelled, _) = book.limit_order(a, Side::Sell, px(10020), qty(9), TimeInForce::Gtc).unwrap();
+        book.limit_order(b, Side::Sell, px(10010), qty(6), TimeInForce::Gtc).unwrap();
+        book.limit_order(a, Side::Buy, px(10010), qty(2), TimeInForce::Gtc).unwrap();
+        book.cancel_order(cancelled).unwrap();
+        book.stop_order(b, Side::Buy, px(10050), qty(1));
+        book.stop_limit_order(a, Side::Sell, px(9950), px(9940), qty(2));
+
+        let state = book.export_state();
+        let mut standby = OrderBook::with_capacity(20_000, 64);
+        standby.import_state(&state).unwrap();
+
+        assert_eq!(standby.depth(10), book.depth(10));
+        assert_eq!(standby.next_order_id(), book.next_order_id());
+        assert_eq!(standby.next_trade_id(), book.next_trade_id());
+        assert_eq!(standby.last_trade_price(), book.last_trade_price());
+        assert_eq!(standby.next_expiry(), Some(5_000));
+        assert_eq!(standby.stop_orders().iter().collect::<Vec<_>>(), book.stop_orders().iter().collect::<Vec<_>>());
+        assert_eq!(standby.export_state(), state);
+
+        // 恢复后时间优先顺序不变：先成交A的9990买单
+        let (_, trades) = standby.limit_order(b, Side::Sell, px(9990), qty(6), TimeInForce::Gtc).unwrap();
+        assert_eq!(trades[0].buyer, a);
+        assert_eq!(trades[0].quantity, qty(5));
+        assert_eq!(trades[1].buyer, b);
+        assert_eq!(trades[0].trade_id, book.next_trade_id());
+
+        assert_eq!(standby.import_state(&state).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert!(OrderBook::with_capacity(20_000, 64).import_state(&state[..state.len() - 1]).is_err());
+        assert!(OrderBook::with_capacity(20_000, 64).import_state(b"nope").is_err());
+    }
+
+    #[test]
+    fn test_capacity_pressure_policy() {
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut book = OrderBook::with_capacity(20_000, 10);
+        book.set_event_listener(Box::new(EventRecorder(events.clone())));
+        let trader = TraderId::from_str("T");
+
+        let ids: Vec<_> = (0..10)
+            .map(|i| book.limit_order(trader, Side::Buy, px(9000 + i), qty(1), TimeInForce::Gtc).unwrap().0)
+            .collect();
+        let warnings: Vec<_> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match *event {
+                BookEvent::CapacityWarning { used, threshold_pct, .. } => Some((used, threshold_pct)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(warnings, vec![(8, 80), (10, 95)]);
+        assert_eq!(book.trading_mode(), TradingMode::CancelOnly);
+        assert_eq!(book.snapshot().arena_used, 10);
+
+        // 只撤单模式拒绝新订单，不panic
+        events.lock().unwrap().clear();
+        assert_eq!(
+            book.limit_order(trader, Side::Sell, px(9000), qty(1), TimeInForce::Gtc),
+            Err(OrderBookError::Rejected(RejectReason::CancelOnly))
+        );
+        assert!(matches!(
+            events.lock().unwrap()[0],
+            BookEvent::OrderRejected { reason: RejectReason::CancelOnly, .. }
+        ));
+        let command = Command::Limit { trader, side: Side::Buy, price: px(9000), quantity: qty(1), tif: TimeInForce::Gtc };
+        assert_eq!(command.execute(&mut book), CommandResult::Rejected(RejectReason::CancelOnly));
+
+        // 撤单回收槽位，利用率低于90%后自动恢复
+        book.cancel_order(ids[0]).unwrap();
+        assert_eq!(book.trading_mode(), TradingMode::CancelOnly);
+        book.cancel_order(ids[1]).unwrap();
+        assert_eq!(book.trading_mode(), TradingMode::Normal);
+        assert_eq!(book.arena_usage(), (8, 10));
+        assert_eq!(book.best_bid(), Some(px(9009)));
+        assert!(events.lock().unwrap().contains(&BookEvent::ModeChanged { mode: TradingMode::Normal }));
+
+        let (_, trades) = book.limit_order(trader, Side::Sell, px(9009), qty(1), TimeInForce::Gtc).unwrap();
+        assert_eq!(trades.len(), 1);
+    }
+
+    #[test]
+    fn test_price_out_of_range_rejected() {
+        let mut book = OrderBook::with_capacity(20_000, 16);
+        let trader = TraderId::from_str("T");
+        assert_eq!(
+            book.limit_order(trader, Side::Buy, px(20_000), qty(1), TimeInForce::Gtc),
+            Err(OrderBookError::PriceOutOfRange(px(20_000)))
+        );
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.check_new_order(Side::Sell, px(19_999)), Ok(()));
+
+        let command = Command::StopLimit {
+            trader,
+            side: Side::Sell,
+            stop_price: px(100),
+            limit_price: px(25_000),
+            quantity: qty(1),
+        };
+        assert_eq!(command.execute(&mut book), CommandResult::Rejected(RejectReason::PriceOutOfRange));
+        assert!(book.stop_orders().is_empty());
+    }
+
+    #[test]
+    fn test_typed_order_errors() {
+        let mut book = OrderBook::with_capacity(20_000, 2);
+        let trader = TraderId::from_str("T");
+        assert_eq!(
+            book.limit_order(trader, Side::Sell, px(100), Quantity::ZERO, TimeInForce::Gtc),
+            Err(OrderBookError::InvalidQuantity(Quantity::ZERO))
+        );
+        assert_eq!(book.cancel_order(42), Err(OrderBookError::UnknownOrder(42)));
+
+        // 手动恢复正常模式后内存池仍满：可挂单的订单被拒绝，IOC仍可成交
+        book.limit_order(trader, Side::Sell, px(100), qty(1), TimeInForce::Gtc).unwrap();
+        book.limit_order(trader, Side::Sell, px(101), qty(1), TimeInForce::Gtc).unwrap();
+        book.set_trading_mode(TradingMode::Normal);
+        assert_eq!(
+            book.limit_order(trader, Side::Buy, px(100), qty(1), TimeInForce::Gtc),
+            Err(OrderBookError::CapacityExceeded)
+        );
+        let (_, trades) = book.limit_order(trader, Side::Buy, px(100), qty(1), TimeInForce::Ioc).unwrap();
+        assert_eq!(trades.len(), 1);
+        book.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_price_band_rejects_far_orders() {
+        let mut book = OrderBook::with_capacity(20_000, 100);
+        book.set_price_band(Some(PriceBand::new(1_000)));
+        let trader = TraderId::from_str("T");
+        // 没有参考价时不检查
+        let (bid, _) = book.limit_order(trader, Side::Buy, px(1_000), qty(1), TimeInForce::Gtc).unwrap();
+        book.limit_order(trader, Side::Sell, px(1_010), qty(1), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.band_reference(), Some(px(1_005)));
+
+        assert_eq!(
+            book.limit_order(trader, Side::Buy, px(1_106), qty(1), TimeInForce::Gtc),
+            Err(OrderBookError::PriceOutsideBand { price: px(1_106), reference: px(1_005) })
+        );
+        assert_eq!(
+            Command::Limit { trader, side: Side::Sell, price: px(10_000), quantity: qty(1), tif: TimeInForce::Gtc }
+                .execute(&mut book),
+            CommandResult::Rejected(RejectReason::PriceBand)
+        );
+        book.limit_order(trader, Side::Buy, px(905), qty(1), TimeInForce::Gtc).unwrap();
+        assert!(!book.replace_order(bid, px(900), qty(1)));
+
+        // 关闭后不再检查
+        book.set_price_band(None);
+        assert!(book.replace_order(bid, px(900), qty(1)));
+        book.check_invariants().unwrap();
+    }
+
+    /// 参考成交价1000，卖方挂 1000x9、1050x5、1200x5，熔断幅度10%
+    fn breaker_book() -> OrderBook {
+        let mut book = OrderBook::with_capacity(20_000, 100);
+        book.set_circuit_breaker(Some(CircuitBreakerConfig {
+            max_move_bps: 1_000,
+            window: Duration::from_secs(60),
+        }));
+        let seller = TraderId::from_str("S");
+        book.limit_order(seller, Side::Sell, px(1_000), qty(10), TimeInForce::Gtc).unwrap();
+        book.limit_order(TraderId::from_str("B0"), Side::Buy, px(1_000), qty(1), TimeInForce::Gtc).unwrap();
+        book.limit_order(seller, Side::Sell, px(1_050), qty(5), TimeInForce::Gtc).unwrap();
+        book.limit_order(seller, Side::Sell, px(1_200), qty(5), TimeInForce::Gtc).unwrap();
+        book
+    }
+
+    #[test]
+    fn test_circuit_breaker_halts_and_reopens_with_auction() {
+        let mut book = breaker_book();
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        book.set_event_listener(Box::new(EventRecorder(events.clone())));
+        let buyer = TraderId::from_str("B1");
+
+        // 价格带上限1100：1200档不成交，剩余部分排队
+        let (sweeper, trades) = book.limit_order(buyer, Side::Buy, px(1_300), qty(20), TimeInForce::Gtc).unwrap();
+        assert_eq!(trades.iter().map(|t| (t.price, t.quantity)).collect::<Vec<_>>(), vec![(px(1_000), qty(9)), (px(1_050), qty(5))]);
+        assert_eq!(book.trading_mode(), TradingMode::Halted);
+        assert!(events.lock().unwrap().contains(&BookEvent::ModeChanged { mode: TradingMode::Halted }));
+        assert_eq!((book.queued_orders(), book.best_bid()), (1, None));
+
+        // 熔断期间：穿价IOC被拒绝，不穿价订单正常挂单，穿价GTC排队
+        assert_eq!(
+            book.limit_order(buyer, Side::Buy, px(1_200), qty(1), TimeInForce::Ioc),
+            Err(OrderBookError::Rejected(RejectReason::Halted))
+        );
+        assert!(events
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|event| matches!(event, BookEvent::OrderRejected { reason: RejectReason::Halted, .. })));
+        book.limit_order(TraderId::from_str("S2"), Side::Sell, px(1_250), qty(3), TimeInForce::Gtc).unwrap();
+        book.limit_order(buyer, Side::Buy, px(1_100), qty(2), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.best_bid(), Some(px(1_100)));
+        let (queued, _) = book.limit_order(buyer, Side::Buy, px(1_250), qty(4), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.queued_orders(), 2);
+        assert!(book.reduce_order(queued, qty(2)));
+        assert!(book.cancel_order(queued).is_ok());
+        assert_eq!(book.queued_orders(), 1);
+
+        // 竞价: 买6@1300 对 卖5@1200 + 3@1250，1250与1300成交量相同，取接近参考价1050者
+        events.lock().unwrap().clear();
+        let trades = book.reopen_auction();
+        assert_eq!(book.trading_mode(), TradingMode::Normal);
+        assert_eq!(trades.len(), 2);
+        assert!(trades.iter().all(|t| t.price == px(1_250) && t.taker_order_id == sweeper));
+        // 逐笔事件与成交一致，按竞价价格报告（包括1200档的挂单）
+        let executed: Vec<(Price, Quantity)> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match *event {
+                BookEvent::OrderExecuted { price, quantity, .. } => Some((price, quantity)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(executed, trades.iter().map(|t| (t.price, t.quantity)).collect::<Vec<_>>());
+        assert_eq!(trades.iter().map(|t| t.quantity.get()).sum::<u32>(), 6);
+        assert_eq!(book.last_trade_price(), Some(px(1_250)));
+        assert_eq!(book.queued_orders(), 0);
+        assert_eq!(book.depth(1).asks[0], DepthLevel { price: px(1_250), quantity: qty(2), order_count: 1 });
+    }
+
+    #[test]
+    fn test_indicative_price_in_pre_open() {
+        let mut book = OrderBook::with_capacity(20_000, 100);
+        let (buyer, seller) = (TraderId::from_str("B"), TraderId::from_str("S"));
+        book.limit_order(seller, Side::Sell, px(1_010), qty(4), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.indicative_price(), None);
+
+        // 开盘前: 穿价订单排队累积，参考价随之更新
+        book.set_trading_mode(TradingMode::Halted);
+        book.limit_order(buyer, Side::Buy, px(1_000), qty(2), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.indicative_price(), None);
+        book.limit_order(buyer, Side::Buy, px(1_020), qty(3), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.indicative_price(), Some(Uncross { price: px(1_010), volume: 3, imbalance: -1 }));
+        book.limit_order(seller, Side::Sell, px(990), qty(5), TimeInForce::Gtc).unwrap();
+        // 990与1000成交量和量差相同，没有参考价时取较低价格
+        let indicative = book.indicative_price().unwrap();
+        assert_eq!((indicative.price, indicative.volume, indicative.imbalance), (px(990), 5, 0));
+        assert!(book.trades().is_empty());
+
+        // 排队期间转入只撤单，参考价仍按排队订单报告
+        book.set_trading_mode(TradingMode::CancelOnly);
+        assert_eq!(book.indicative_price(), Some(indicative));
+        book.set_trading_mode(TradingMode::Halted);
+
+        // 开盘成交价与参考价一致
+        let trades = book.reopen_auction();
+        assert!(trades.iter().all(|t| t.price == indicative.price));
+        assert_eq!(trades.iter().map(|t| t.quantity.get() as u64).sum::<u64>(), indicative.volume);
+        assert_eq!(book.indicative_price(), None);
+    }
+
+    #[test]
+    fn test_circuit_breaker_resume() {
+        let mut book = breaker_book();
+        let buyer = TraderId::from_str("B1");
+        book.limit_order(buyer, Side::Buy, px(1_300), qty(20), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.trading_mode(), TradingMode::Halted);
+
+        // 恢复后参考价仍为1050，排队订单再次触发熔断
+        assert!(book.resume().is_empty());
+        assert_eq!((book.trading_mode(), book.queued_orders()), (TradingMode::Halted, 1));
+
+        book.set_circuit_breaker(None);
+        let trades = book.resume();
+        assert_eq!(trades.len(), 1);
+        assert_eq!((trades[0].price, trades[0].quantity), (px(1_200), qty(5)));
+        assert_eq!(book.trading_mode(), TradingMode::Normal);
+        assert_eq!(book.depth(1).bids[0], DepthLevel { price: px(1_300), quantity: qty(1), order_count: 1 });
+    }
+
+    #[test]
+    fn test_level_iterators() {
+        let mut book = OrderBook::with_capacity(20_000, 100);
+        let trader = TraderId::from_str("T");
+        for (side, price, quantity) in [
+            (Side::Buy, 9_990, 5),
+            (Side::Buy, 9_995, 3),
+            (Side::Buy, 9_995, 2),
+            (Side::Sell, 10_010, 4),
+            (Side::Sell, 10_005, 1),
+        ] {
+            book.limit_order(trader, side, px(price), qty(quantity), TimeInForce::Gtc).unwrap();
+        }
+        let (cancelled, _) = book.limit_order(trader, Side::Sell, px(10_001), qty(9), TimeInForce::Gtc).unwrap();
+        book.cancel_order(cancelled).unwrap();
+
+        let bids: Vec<_> = book.iter_bids().map(|l| (l.price.get(), l.quantity.get(), l.order_count)).collect();
+        assert_eq!(bids, vec![(9_995, 5, 2), (9_990, 5, 1)]);
+        let asks: Vec<_> = book.iter_asks().map(|l| (l.price.get(), l.quantity.get())).collect();
+        assert_eq!(asks, vec![(10_005, 1), (10_010, 4)]);
+        assert_eq!(book.iter_asks().next(), book.depth(1).asks.first().copied());
+        assert_eq!(OrderBook::new().iter_bids().count(), 0);
+    }
+
+    #[test]
+    fn test_instrument_spec_admission() {
+        use crate::orderbook::instrument::TickRounding;
+
+        let spec = InstrumentSpec::new(5, 2).with_price_range(px(100), px(10_000)).with_lot_size(10);
+        let mut book = OrderBook::with_spec(spec, 100);
+        let trader = TraderId::from_str("T");
+        assert_eq!(book.instrument().ladder_size(), 10_001);
+
+        for (price, quantity, reason) in [
+            (103, 10, RejectReason::OffTick),
+            (10_005, 10, RejectReason::PriceOutOfRange),
+            (105, 15, RejectReason::InvalidLotSize),
+        ] {
+            let command = Command::Limit { trader, side: Side::Buy, price: px(price), quantity: qty(quantity), tif: TimeInForce::Gtc };
+            assert_eq!(command.execute(&mut book), CommandResult::Rejected(reason));
+        }
+        assert!(book.depth(1).bids.is_empty());
+
+        // 被动取整：买单向下、卖单向上
+        let mut book = OrderBook::with_spec(spec.with_rounding(TickRounding::Passive), 100);
+        book.limit_order(trader, Side::Buy, px(203), qty(10), TimeInForce::Gtc).unwrap();
+        book.limit_order(trader, Side::Sell, px(207), qty(20), TimeInForce::Gtc).unwrap();
+        let depth = book.depth(1);
+        assert_eq!((depth.bids[0].price, depth.asks[0].price), (px(200), px(210)));
+    }
+
+    #[test]
+    fn test_level_totals_maintained() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let trader = TraderId::from_str("T");
+        let (a, _) = book.limit_order(trader, Side::Sell, px(101), qty(5), TimeInForce::Gtc).unwrap();
+        let (b, _) = book.limit_order(trader, Side::Sell, px(101), qty(7), TimeInForce::Gtc).unwrap();
+        let (c, _) = book.limit_order(trader, Side::Sell, px(101), qty(3), TimeInForce::Gtc).unwrap();
+        let level = |book: &OrderBook| book.level_at(Side::Sell, px(101)).map(|l| (l.quantity.get(), l.order_count));
+        assert_eq!(level(&book), Some((15, 3)));
+
+        // 部分成交、完全成交、撤单、减量、改价分别更新汇总
+        book.limit_order(trader, Side::Buy, px(101), qty(6), TimeInForce::Ioc).unwrap();
+        assert_eq!(level(&book), Some((9, 2)));
+        assert!(book.reduce_order(b, qty(4)));
+        assert_eq!(level(&book), Some((7, 2)));
+        assert!(book.cancel_order(c).is_ok());
+        assert_eq!(level(&book), Some((4, 1)));
+        assert!(book.replace_order(b, px(102), qty(4)));
+        assert_eq!(level(&book), None);
+        assert_eq!(book.level_at(Side::Sell, px(102)).map(|l| l.quantity), Some(qty(4)));
+        assert_eq!(book.order_quantity(a), None);
+
+        book.limit_order(trader, Side::Sell, px(102), qty(2), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.level_at(Side::Sell, px(102)).map(|l| (l.quantity.get(), l.order_count)), Some((6, 2)));
+        assert_eq!(book.level_at(Side::Buy, px(999)), None);
+    }
+
+    #[test]
+    fn test_cancel_unlinks_from_level() {
+        let mut book = OrderBook::with_capacity(1_000, 4);
+        let trader = TraderId::from_str("T");
+        let (a, _) = book.limit_order(trader, Side::Buy, px(100), qty(1), TimeInForce::Gtc).unwrap();
+        let (b, _) = book.limit_order(trader, Side::Buy, px(100), qty(2), TimeInForce::Gtc).unwrap();
+        let (c, _) = book.limit_order(trader, Side::Buy, px(100), qty(3), TimeInForce::Gtc).unwrap();
+        book.limit_order(trader, Side::Buy, px(99), qty(4), TimeInForce::Gtc).unwrap();
+
+        // 撤中间、撤头、撤尾，槽位立即归还，链表保持一致
+        assert!(book.cancel_order(b).is_ok());
+        book.check_invariants().unwrap();
+        assert!(book.cancel_order(a).is_ok());
+        book.check_invariants().unwrap();
+        assert_eq!(book.arena_usage().0, 2);
+        assert_eq!(book.level_at(Side::Buy, px(100)).map(|l| (l.quantity.get(), l.order_count)), Some((3, 1)));
+
+        assert!(book.cancel_order(c).is_ok());
+        book.check_invariants().unwrap();
+        assert_eq!(book.best_bid(), Some(px(99)));
+        assert_eq!(book.level_at(Side::Buy, px(100)), None);
+
+        // 空出的槽位可直接复用
+        let (_, trades) = book.limit_order(trader, Side::Sell, px(99), qty(4), TimeInForce::Gtc).unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(book.best_bid(), None);
+        book.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_estimate_fill() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let mm = TraderId::from_str("MM");
+        book.limit_order(mm, Side::Sell, px(101), qty(5), TimeInForce::Gtc).unwrap();
+        book.limit_order(mm, Side::Sell, px(101), qty(3), TimeInForce::Gtc).unwrap();
+        book.limit_order(mm, Side::Sell, px(103), qty(4), TimeInForce::Gtc).unwrap();
+        book.limit_order(mm, Side::Buy, px(99), qty(2), TimeInForce::Gtc).unwrap();
+        let depth = book.depth(10);
+
+        let estimate = book.estimate_fill(Side::Buy, qty(10));
+        assert_eq!(
+            estimate,
+            FillEstimate {
+                filled: qty(10),
+                unfilled: Quantity::ZERO,
+                notional: 101 * 8 + 103 * 2,
+                worst_price: Some(px(103)),
+                levels: 2,
+            }
+        );
+        assert!(estimate.is_complete());
+        assert_eq!(estimate.average_price(), Some(101.4));
+
+        // 深度不足时给出剩余数量
+        let estimate = book.estimate_fill(Side::Sell, qty(5));
+        assert_eq!((estimate.filled, estimate.unfilled, estimate.worst_price), (qty(2), qty(3), Some(px(99))));
+        assert!(!estimate.is_complete());
+
+        // 对手方为空
+        book.cancel_order(4).unwrap();
+        let estimate = book.estimate_fill(Side::Sell, qty(1));
+        assert_eq!((estimate.average_price(), estimate.worst_price, estimate.unfilled), (None, None, qty(1)));
+
+        // 估算不修改订单簿
+        assert_eq!(book.depth(10).asks, depth.asks);
+        book.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_trade_sink_hooks() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let (tx, rx) = crossbeam::channel::unbounded();
+        book.set_trade_sink(Box::new(tx));
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let collector = std::sync::Arc::clone(&seen);
+        book.add_trade_sink(Box::new(move |trade: &Trade| collector.lock().unwrap().push(trade.trade_id)));
+
+        let (mm, taker) = (TraderId::from_str("MM"), TraderId::from_str("TK"));
+        for price in 100..105 {
+            book.limit_order(mm, Side::Sell, px(price), qty(1), TimeInForce::Gtc).unwrap();
+        }
+        book.limit_order(taker, Side::Buy, px(102), qty(3), TimeInForce::Ioc).unwrap();
+        book.clear_trades();
+        book.limit_order(taker, Side::Buy, px(104), qty(2), TimeInForce::Ioc).unwrap();
+
+        // 两个输出都按序收到全部成交，序列号连续
+        let ids: Vec<_> = rx.try_iter().map(|trade| trade.trade_id).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+        assert_eq!(*seen.lock().unwrap(), ids);
+
+        // 移除后不再推送，接收端关闭也不影响撮合
+        assert!(book.take_trade_sink().is_some());
+        book.set_trade_sink(Box::new(crossbeam::channel::unbounded().0));
+        book.limit_order(mm, Side::Sell, px(100), qty(1), TimeInForce::Gtc).unwrap();
+        book.limit_order(taker, Side::Buy, px(100), qty(1), TimeInForce::Ioc).unwrap();
+        assert_eq!(seen.lock().unwrap().len(), 5);
+        assert_eq!(book.trades().last().map(|trade| trade.trade_id), Some(6));
+    }
+
+    #[test]
+    fn test_reduce_only_orders() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let (mm, trader) = (TraderId::from_str("MM"), TraderId::from_str("T"));
+        let reduce_only_rejected = Err(OrderBookError::Rejected(RejectReason::ReduceOnly));
+
+        // 没有持仓时只减仓订单被拒绝
+        assert_eq!(book.reduce_only_order(trader, Side::Sell, px(100), qty(1), TimeInForce::Gtc), reduce_only_rejected);
+
+        // 买入5手后卖出只减仓订单截断到5手，同方向挂单占用可减数量
+        book.limit_order(mm, Side::Sell, px(100), qty(5), TimeInForce::Gtc).unwrap();
+        book.limit_order(trader, Side::Buy, px(100), qty(5), TimeInForce::Ioc).unwrap();
+        assert_eq!(book.trader_stats(trader).net_position, 5);
+        book.limit_order(trader, Side::Sell, px(110), qty(2), TimeInForce::Gtc).unwrap();
+        let (order_id, _) = book.reduce_only_order(trader, Side::Sell, px(105), qty(10), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.order_quantity(order_id), Some(qty(3)));
+        assert_eq!(book.reduce_only_order(trader, Side::Sell, px(105), qty(1), TimeInForce::Gtc), reduce_only_rejected);
+        // 同方向持仓不能加仓
+        assert_eq!(book.reduce_only_order(trader, Side::Buy, px(90), qty(1), TimeInForce::Gtc), reduce_only_rejected);
+
+        // 外部载入的空头持仓可由买单减仓
+        book.set_position(mm, -7);
+        let (_, trades) = book.reduce_only_order(mm, Side::Buy, px(105), qty(9), TimeInForce::Ioc).unwrap();
+        assert_eq!(trades.iter().map(|trade| trade.quantity.get()).sum::<u32>(), 3);
+        assert_eq!(book.trader_stats(mm).net_position, -4);
+        assert_eq!(book.trader_stats(trader).net_position, 2);
+        book.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_trader_stats() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let (mm, taker) = (TraderId::from_str("MM"), TraderId::from_str("TK"));
+        let (bid, _) = book.limit_order(mm, Side::Buy, px(99), qty(10), TimeInForce::Gtc).unwrap();
+        let (ask, _) = book.limit_order(mm, Side::Sell, px(101), qty(8), TimeInForce::Gtc).unwrap();
+        book.limit_order(mm, Side::Sell, px(102), qty(5), TimeInForce::Gtc).unwrap();
+        assert_eq!(
+            book.trader_stats(mm),
+            TraderStats { open_orders: 3, open_buy_quantity: 10, open_sell_quantity: 13, ..TraderStats::default() }
+        );
+
+        // 部分成交、减量、改价、撤单和完全成交分别更新汇总
+        book.limit_order(taker, Side::Buy, px(101), qty(3), TimeInForce::Ioc).unwrap();
+        assert!(book.reduce_order(bid, qty(6)));
+        assert!(book.replace_order(ask, px(103), qty(7)));
+        let stats = book.trader_stats(mm);
+        assert_eq!((stats.open_orders, stats.open_quantity(Side::Buy), stats.open_quantity(Side::Sell)), (3, 6, 12));
+        assert_eq!(stats.executed_volume, 3);
+
+        assert!(book.cancel_order(bid).is_ok());
+        book.limit_order(taker, Side::Buy, px(103), qty(14), TimeInForce::Gtc).unwrap();
+        assert_eq!(
+            book.trader_stats(mm),
+            TraderStats { executed_volume: 15, net_position: -15, ..TraderStats::default() }
+        );
+        assert_eq!(
+            book.trader_stats(taker),
+            TraderStats { open_orders: 1, open_buy_quantity: 2, open_sell_quantity: 0, executed_volume: 15, net_position: 15 }
+        );
+        assert_eq!(book.trader_stats(TraderId::from_str("NOBODY")), TraderStats::default());
+        book.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_halt_pulls_all_orders() {
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let trader = TraderId::from_str("T");
+        let (bid, _) = book.limit_order(trader, Side::Buy, px(99), qty(5), TimeInForce::Gtc).unwrap();
+        let (ask, _) = book.limit_order(trader, Side::Sell, px(101), qty(5), TimeInForce::Gtd(u64::MAX)).unwrap();
+        let (stop, _) = book.stop_order(trader, Side::Buy, px(150), qty(1));
+        book.set_event_listener(Box::new(EventRecorder(events.clone())));
+
+        assert_eq!(book.halt(), vec![bid, ask, stop]);
+        assert_eq!(book.trading_mode(), TradingMode::CancelOnly);
+        assert_eq!(book.indicative_price(), None);
+        assert_eq!((book.best_bid(), book.best_ask(), book.arena_usage().0), (None, None, 0));
+        assert!(book.stop_orders().is_empty());
+        assert_eq!(book.trader_stats(trader).open_orders, 0);
+        let cancelled = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| matches!(event, BookEvent::OrderCancelled { .. }))
+            .count();
+        assert_eq!(cancelled, 2);
+
+        assert_eq!(
+            book.limit_order(trader, Side::Buy, px(99), qty(1), TimeInForce::Gtc),
+            Err(OrderBookError::Rejected(RejectReason::CancelOnly))
+        );
+        assert!(book.resume().is_empty());
+        assert!(book.limit_order(trader, Side::Buy, px(99), qty(1), TimeInForce::Gtc).is_ok());
+        book.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_injected_clock_stamps_trades() {
+        let clock = crate::timing::MockClock::new(1_000);
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        book.set_clock(Arc::new(clock.clone()));
+        let trader = TraderId::from_str("T");
+
+        book.limit_order(trader, Side::Sell, px(100), qty(2), TimeInForce::Gtc).unwrap();
+        let (_, trades) = book.limit_order(trader, Side::Buy, px(100), qty(1), TimeInForce::Ioc).unwrap();
+        assert_eq!(trades[0].timestamp_ns, 1_000);
+        clock.advance(Duration::from_nanos(500));
+        let (_, trades) = book.limit_order(trader, Side::Buy, px(100), qty(1), TimeInForce::Ioc).unwrap();
+        assert_eq!(trades[0].timestamp_ns, 1_500);
+        assert_eq!(book.clock().now_ns(), 1_500);
+    }
+
+    #[test]
+    fn test_bbo_listener() {
+        struct Recorder(Arc<Mutex<Vec<(Side, Option<Price>, Option<Price>)>>>);
+        impl BboListener for Recorder {
+            fn on_bbo_change(&mut self, side: Side, old: Option<Price>, new: Option<Price>) {
+                self.0.lock().push((side, old, new));
+            }
+        }
+
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        book.set_bbo_listener(Box::new(Recorder(Arc::clone(&changes))));
+        let trader = TraderId::from_str("T");
+
+        book.limit_order(trader, Side::Sell, px(105), qty(2), TimeInForce::Gtc).unwrap();
+        book.limit_order(trader, Side::Sell, px(106), qty(2), TimeInForce::Gtc).unwrap();
+        book.limit_order(trader, Side::Buy, px(100), qty(1), TimeInForce::Gtc).unwrap();
+        // 吃掉最优卖价档位
+        book.limit_order(trader, Side::Buy, px(105), qty(2), TimeInForce::Ioc).unwrap();
+
+        assert_eq!(
+            *changes.lock(),
+            vec![
+                (Side::Sell, None, Some(px(105))),
+                (Side::Buy, None, Some(px(100))),
+                (Side::Sell, Some(px(105)), Some(px(106))),
+            ]
+        );
+        assert!(book.take_bbo_listener().is_some());
+    }
+
+    #[test]
+    fn test_pro_rata_matching() {
+        let spec = InstrumentSpec::default().with_allocation(AllocationPolicy::ProRata { min_allocation: 2 });
+        let mut book = OrderBook::with_spec(spec.with_price_range(Price::MIN, px(999)), 100);
+        let (early, _) = book.limit_order(TraderId::from_str("A"), Side::Sell, px(100), qty(10), TimeInForce::Gtc).unwrap();
+        let (late, _) = book.limit_order(TraderId::from_str("B"), Side::Sell, px(100), qty(30), TimeInForce::Gtc).unwrap();
+
+        // 按挂单量比例: 10/40、30/40
+        let (_, trades) = book.limit_order(TraderId::from_str("T"), Side::Buy, px(100), qty(8), TimeInForce::Ioc).unwrap();
+        let fills: Vec<_> = trades.iter().map(|t| (t.maker_order_id, t.quantity.get())).collect();
+        assert_eq!(fills, vec![(early, 2), (late, 6)]);
+        assert_eq!(book.level_at(Side::Sell, px(100)).map(|l| (l.quantity.get(), l.order_count)), Some((32, 2)));
+
+        // 份额低于最小分配时归零，余量按时间顺序补齐
+        let (_, trades) = book.limit_order(TraderId::from_str("T"), Side::Buy, px(100), qty(3), TimeInForce::Ioc).unwrap();
+        let fills: Vec<_> = trades.iter().map(|t| (t.maker_order_id, t.quantity.get())).collect();
+        assert_eq!(fills, vec![(early, 1), (late, 2)]);
+
+        // 足以吃完整个档位时与FIFO相同
+        let (_, trades) = book.limit_order(TraderId::from_str("T"), Side::Buy, px(100), qty(29), TimeInForce::Ioc).unwrap();
+        assert_eq!(trades.iter().map(|t| t.quantity.get()).sum::<u32>(), 29);
+        assert!(book.depth(1).asks.is_empty());
+    }
+
+    #[test]
+    fn test_pro_rata_fills_whole_lots() {
+        let spec = InstrumentSpec::default()
+            .with_lot_size(10)
+            .with_allocation(AllocationPolicy::ProRata { min_allocation: 0 });
+        let mut book = OrderBook::with_spec(spec.with_price_range(Price::MIN, px(999)), 100);
+        for (trader, quantity) in [("A", 120), ("B", 200), ("C", 100)] {
+            book.limit_order(TraderId::from_str(trader), Side::Sell, px(100), qty(quantity), TimeInForce::Gtc).unwrap();
+        }
+
+        for incoming in [130, 70, 10] {
+            let (_, trades) = book.limit_order(TraderId::from_str("T"), Side::Buy, px(100), qty(incoming), TimeInForce::Ioc).unwrap();
+            assert!(trades.iter().all(|t| t.quantity.get() % 10 == 0), "{:?}", trades);
+            assert_eq!(trades.iter().map(|t| t.quantity.get()).sum::<u32>(), incoming);
+        }
+        assert!(book.depth(1).asks.iter().all(|level| level.quantity.get() % 10 == 0));
+    }
+
+    #[test]
+    fn test_reduce_order_keeps_whole_lots() {
+        let spec = InstrumentSpec::default().with_lot_size(10).with_price_range(Price::MIN, px(999));
+        let mut book = OrderBook::with_spec(spec, 100);
+        let (order_id, _) = book.limit_order(TraderId::from_str("A"), Side::Sell, px(100), qty(30), TimeInForce::Gtc).unwrap();
+
+        assert!(!book.reduce_order(order_id, qty(5)));
+        assert_eq!(
+            Command::Amend { order_id, quantity: qty(25) }.execute(&mut book),
+            CommandResult::Rejected(RejectReason::InvalidLotSize)
+        );
+        assert_eq!(book.level_at(Side::Sell, px(100)).map(|l| l.quantity.get()), Some(30));
+
+        assert!(book.reduce_order(order_id, qty(20)));
+        assert_eq!(
+            Command::Amend { order_id, quantity: qty(10) }.execute(&mut book),
+            CommandResult::Amended { order_id, success: true }
+        );
+        book.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_replace_order_validates_lot_and_tick() {
+        let spec = InstrumentSpec::new(5, 2).with_lot_size(10).with_price_range(Price::MIN, px(999));
+        let mut book = OrderBook::with_spec(spec, 100);
+        let (order_id, _) = book.limit_order(TraderId::from_str("A"), Side::Buy, px(100), qty(30), TimeInForce::Gtc).unwrap();
+
+        assert!(!book.replace_order(order_id, px(105), qty(25)));
+        assert!(!book.replace_order(order_id, px(100), qty(15)));
+        assert!(!book.replace_order(order_id, px(103), qty(30)));
+        assert_eq!(book.depth(1).bids[0], DepthLevel { price: px(100), quantity: qty(30), order_count: 1 });
+
+        assert!(book.replace_order(order_id, px(105), qty(40)));
+        assert_eq!(book.depth(1).bids[0], DepthLevel { price: px(105), quantity: qty(40), order_count: 1 });
+        book.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_pro_rata_with_odd_lot_resting_orders_makes_progress() {
+        let spec = InstrumentSpec::default().with_allocation(AllocationPolicy::ProRata { min_allocation: 0 });
+        let mut book = OrderBook::with_spec(spec.with_price_range(Price::MIN, px(999)), 100);
+        let makers: Vec<_> = [("A", 3), ("B", 5), ("C", 7)]
+            .into_iter()
+            .map(|(trader, quantity)| {
+                book.limit_order(TraderId::from_str(trader), Side::Sell, px(100), qty(quantity), TimeInForce::Gtc).unwrap().0
+            })
+            .collect();
+        // 规格改为每手10个后遗留的零股挂单：比例份额全部取整为0，也无法按整手补齐
+        book.spec.lot_size = 10;
+
+        let (_, trades) = book.limit_order(TraderId::from_str("T"), Side::Buy, px(100), qty(10), TimeInForce::Ioc).unwrap();
+        let fills: Vec<_> = trades.iter().map(|t| (t.maker_order_id, t.quantity.get())).collect();
+        assert_eq!(fills, vec![(makers[0], 3), (makers[1], 5), (makers[2], 2)]);
+        assert_eq!(book.level_at(Side::Sell, px(100)).map(|l| (l.quantity.get(), l.order_count)), Some((5, 1)));
+        book.check_invariants().unwrap();
+    }
+
+    #[cfg(feature = "latency-histogram")]
+    #[test]
+    fn test_latency_histogram() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let trader = TraderId::from_str("T");
+        for i in 0..10 {
+            let (order_id, _) = book.limit_order(trader, Side::Buy, px(100 + i), qty(1), TimeInForce::Gtc).unwrap();
+            book.cancel_order(order_id).unwrap();
+        }
+        let latency = book.snapshot().latency.unwrap();
+        assert_eq!((latency.limit_order.count, latency.cancel_order.count), (10, 10));
+        assert!(latency.limit_order.p50_ns <= latency.limit_order.max_ns);
+
+        book.reset_latency();
+        assert_eq!(book.latency_summary().unwrap().limit_order.count, 0);
+
+        // 运行时关闭采样
+        let runtime = crate::monitor::domain::admin::RuntimeConfig::new();
+        book.set_latency_flag(runtime.feature("latency_histograms", true));
+        runtime.set_feature("latency_histograms", false);
+        book.limit_order(trader, Side::Buy, px(100), qty(1), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.latency_summary().unwrap().limit_order.count, 0);
+        runtime.set_feature("latency_histograms", true);
+        book.limit_order(trader, Side::Buy, px(100), qty(1), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.latency_summary().unwrap().limit_order.count, 1);
+    }
+
+    mod invariants {
+        use super::*;
+        use proptest::prelude::*;
+
+        #[derive(Debug, Clone)]
+        enum Op {
+            Limit { side: Side, price: u32, quantity: u32, tif: TimeInForce },
+            Cancel(usize),
+            Amend(usize, u32),
+        }
+
+        fn op() -> impl Strategy<Value = Op> {
+            let side = prop_oneof![Just(Side::Buy), Just(Side::Sell)];
+            let tif = prop_oneof![4 => Just(TimeInForce::Gtc), 1 => Just(TimeInForce::Ioc), 1 => Just(TimeInForce::Fok)];
+            prop_oneof![
+                6 => (side, 90u32..110, 1u32..20, tif)
+                    .prop_map(|(side, price, quantity, tif)| Op::Limit { side, price, quantity, tif }),
+                2 => any::<usize>().prop_map(Op::Cancel),
+                1 => (any::<usize>(), 1u32..20).prop_map(|(pick, quantity)| Op::Amend(pick, quantity)),
+            ]
+        }
+
+        fn resting(book: &OrderBook) -> u64 {
+            book.iter_bids().chain(book.iter_asks()).map(|level| level.quantity.get() as u64).sum()
+        }
+
+        proptest! {
+            #[test]
+            fn prop_book_invariants(ops in prop::collection::vec(op(), 1..300)) {
+                let mut book = OrderBook::with_capacity(1_000, 10_000);
+                let mut accepted: Vec<OrderId> = Vec::new();
+
+                for op in ops {
+                    let before = resting(&book);
+                    let pick = |i: usize| (!accepted.is_empty()).then(|| accepted[i % accepted.len()]);
+                    match op {
+                        Op::Limit { side, price, quantity, tif } => {
+                            let (order_id, trades) = book.limit_order(TraderId::from_str("P"), side, px(price), qty(quantity), tif).unwrap();
+                            let filled: u64 = trades.iter().map(|t| t.quantity.get() as u64).sum();
+                            let residual = book.order_quantity(order_id).map_or(0, |q| q.get() as u64);
+                            prop_assert!(filled <= quantity as u64);
+                            if tif == TimeInForce::Gtc {
+                                prop_assert_eq!(residual, quantity as u64 - filled);
+                            } else {
+                                prop_assert_eq!(residual, 0);
+                            }
+                            if tif == TimeInForce::Fok {
+                                prop_assert!(filled == 0 || filled == quantity as u64);
+                            }
+                            // 数量守恒: 被吃掉的挂单量等于主动成交量
+                            prop_assert_eq!(resting(&book), before - filled + residual);
+                            accepted.push(order_id);
+                        }
+                        Op::Cancel(i) => {
+                            if let Some(order_id) = pick(i) {
+                                let remaining = book.order_quantity(order_id).map_or(0, |q| q.get() as u64);
+                                let cancelled = book.cancel_order(order_id).is_ok();
+                                prop_assert_eq!(cancelled, remaining > 0);
+                                prop_assert_eq!(resting(&book), before - remaining);
+                            }
+                        }
+                        Op::Amend(i, quantity) => {
+                            if let Some(order_id) = pick(i) {
+                                let remaining = book.order_quantity(order_id).map_or(0, |q| q.get() as u64);
+                                let reduced = book.reduce_order(order_id, qty(quantity));
+                                let expected = if reduced { before - remaining + quantity as u64 } else { before };
+                                prop_assert_eq!(resting(&book), expected);
+                            }
+                        }
+                    }
+                    if let Err(violation) = book.check_invariants() {
+                        return Err(TestCaseError::fail(violation));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_config() {
+        let spec = InstrumentSpec::new(5, 2).with_price_range(px(100), px(1_000)).with_lot_size(10);
+        let config = EngineConfig::new(spec).with_max_orders(8).with_price_band(PriceBand::new(500));
+        let mut book = OrderBook::with_config(config).unwrap();
+        assert_eq!(*book.instrument(), spec);
+        assert_eq!(book.arena_usage(), (0, 8));
+        assert_eq!(book.price_band(), Some(&PriceBand::new(500)));
+
+        // 同一进程内另一个参数不同的订单簿互不影响
+        let other = OrderBook::with_config(EngineConfig::default().with_max_orders(4)).unwrap();
+        assert_eq!((other.instrument().tick_size, other.arena_usage().1), (1, 4));
+
+        let trader = TraderId::from_str("T");
+        assert_eq!(
+            book.limit_order(trader, Side::Buy, px(103), qty(10), TimeInForce::Gtc).unwrap_err(),
+            OrderBookError::Rejected(RejectReason::OffTick)
+        );
+        assert!(OrderBook::with_config(EngineConfig::default().with_max_orders(0)).is_err());
+    }
+
+    #[test]
+    fn test_self_trade_prevention() {
+        let (mm, other) = (TraderId::from_str("MM"), TraderId::from_str("OTHER"));
+        let setup = |self_trade| {
+            let config = EngineConfig::new(InstrumentSpec::default().with_price_range(px(1), px(1_000)))
+                .with_max_orders(100)
+                .with_self_trade(self_trade);
+            let mut book = OrderBook::with_config(config).unwrap();
+            book.limit_order(other, Side::Sell, px(100), qty(2), TimeInForce::Gtc).unwrap();
+            let (own, _) = book.limit_order(mm, Side::Sell, px(100), qty(3), TimeInForce::Gtc).unwrap();
+            book.limit_order(other, Side::Sell, px(101), qty(4), TimeInForce::Gtc).unwrap();
+            (book, own)
+        };
+
+        let (mut book, _) = setup(SelfTradePrevention::Allow);
+        let (_, trades) = book.limit_order(mm, Side::Buy, px(101), qty(6), TimeInForce::Gtc).unwrap();
+        assert_eq!(trades.iter().map(|t| t.quantity.get()).collect::<Vec<_>>(), vec![2, 3, 1]);
+
+        // 撤销本人挂单，来单越过它继续成交
+        let (mut book, own) = setup(SelfTradePrevention::CancelResting);
+        let (_, trades) = book.limit_order(mm, Side::Buy, px(101), qty(6), TimeInForce::Gtc).unwrap();
+        assert_eq!(trades.iter().map(|t| (t.price.get(), t.quantity.get())).collect::<Vec<_>>(), vec![(100, 2), (101, 4)]);
+        assert_eq!(book.order_quantity(own), None);
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.trader_stats(mm).net_position, 6);
+        book.check_invariants().unwrap();
+
+        // 撤销来单剩余部分，本人挂单保留
+        let (mut book, own) = setup(SelfTradePrevention::CancelIncoming);
+        let (id, trades) = book.limit_order(mm, Side::Buy, px(101), qty(6), TimeInForce::Gtc).unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!((book.order_quantity(own), book.order_quantity(id)), (Some(qty(3)), None));
+        book.check_invariants().unwrap();
+
+        // FOK预检不计本人挂单
+        let (mut book, _) = setup(SelfTradePrevention::CancelResting);
+        let (_, trades) = book.limit_order(mm, Side::Buy, px(101), qty(7), TimeInForce::Fok).unwrap();
+        assert!(trades.is_empty());
+        assert_eq!(book.best_ask(), Some(px(100)));
+    }
+
+    #[test]
+    fn test_client_order_ids() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let (a, b) = (TraderId::from_str("A"), TraderId::from_str("B"));
+        let (first, _) = book.limit_order_with_client_id(a, 7, Side::Buy, px(100), qty(5), TimeInForce::Gtc).unwrap();
+        assert_eq!(book.client_order(a, 7), Some(first));
+
+        // 重发被拒绝，不产生新挂单；其他交易员可使用相同订单号
+        assert_eq!(
+            book.limit_order_with_client_id(a, 7, Side::Buy, px(100), qty(5), TimeInForce::Gtc),
+            Err(OrderBookError::DuplicateClientOrderId { client_order_id: 7, order_id: first })
+        );
+        assert_eq!(book.depth(1).bids[0].quantity, qty(5));
+        let (_, trades) = book.limit_order_with_client_id(b, 7, Side::Sell, px(100), qty(5), TimeInForce::Gtc).unwrap();
+        assert_eq!(trades.len(), 1);
+
+        // 成交后仍然去重；被拒绝的订单不占用订单号
+        let limit = |price| Command::Limit { trader: a, side: Side::Buy, price: px(price), quantity: qty(5), tif: TimeInForce::Gtc };
+        let command = limit(100);
+        assert_eq!(command.execute_with_client_id(&mut book, Some(7)), CommandResult::Rejected(RejectReason::DuplicateOrder));
+        let off_range = limit(5_000);
+        assert!(off_range.execute_with_client_id(&mut book, Some(8)).is_rejected());
+        assert_eq!(book.client_order(a, 8), None);
+        assert!(matches!(command.execute_with_client_id(&mut book, Some(8)), CommandResult::Accepted { .. }));
+
+        book.clear_client_orders();
+        assert_eq!(book.client_order(a, 7), None);
+    }
+
+    #[test]
+    fn test_prewarm_preallocates() {
+        let mut book = OrderBook::with_capacity(1_000, 64);
+        let trader = TraderId::from_str("T");
+        book.limit_order(trader, Side::Buy, px(100), qty(5), TimeInForce::Gtc).unwrap();
+        book.prewarm();
+
+        let stats = book.allocation_stats();
+        assert_eq!(stats.arena_capacity, 64);
+        assert!(stats.arena_reserved >= 64 && stats.index_capacity >= 64);
+        assert!(stats.ladder_bytes >= 2 * 1_000 * std::mem::size_of::<PricePoint>());
+        assert_eq!(stats.total_bytes(), stats.ladder_bytes + stats.arena_bytes + stats.index_bytes);
+
+        // 内容不变；填满内存池不再分配
+        assert_eq!(book.depth(1).bids[0].quantity, qty(5));
+        for i in 0..63 {
+            book.limit_order(trader, Side::Sell, px(200 + i), qty(1), TimeInForce::Gtc).unwrap();
+        }
+        assert_eq!(book.arena_usage(), (64, 64));
+        assert_eq!(book.allocation_stats(), stats);
+        book.check_invariants().unwrap();
     }
 }
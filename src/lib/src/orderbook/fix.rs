@@ -0,0 +1,799 @@
+/// FIX 4.4 下单编解码
+///
+/// 把标准FIX客户端的消息映射为引擎指令，并把执行结果编码为执行报告:
+/// - NewOrderSingle(D) -> `Command::Limit`/`Stop`/`StopLimit`
+/// - OrderCancelRequest(F) -> `Command::Cancel`
+/// - `CommandResult`/`Trade` -> ExecutionReport(8)，撤单失败 -> OrderCancelReject(9)
+///
+/// `FixSession`按会话维护ClOrdID与引擎订单ID的映射和累计成交量，
+/// 撤单可按OrigClOrdID或引擎OrderID定位订单。价格按`PriceConverter`在小数与tick之间转换。
+/// 会话层消息（登录、心跳、重传）不在本模块范围内。
+
+use super::command::{Command, CommandResult, RejectReason};
+use super::price_converter::PriceConverter;
+use super::types::{OrderId, Price, Quantity, Side, TimeInForce, Trade, TraderId};
+use crate::timing::{system_clock, Clock};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// 协议版本
+pub const BEGIN_STRING: &str = "FIX.4.4";
+
+/// 字段分隔符
+const SOH: u8 = 0x01;
+
+/// 常用标签
+pub mod tag {
+    pub const ACCOUNT: u32 = 1;
+    pub const AVG_PX: u32 = 6;
+    pub const BEGIN_STRING: u32 = 8;
+    pub const BODY_LENGTH: u32 = 9;
+    pub const CHECK_SUM: u32 = 10;
+    pub const CL_ORD_ID: u32 = 11;
+    pub const CUM_QTY: u32 = 14;
+    pub const EXEC_ID: u32 = 17;
+    pub const LAST_PX: u32 = 31;
+    pub const LAST_QTY: u32 = 32;
+    pub const MSG_SEQ_NUM: u32 = 34;
+    pub const MSG_TYPE: u32 = 35;
+    pub const ORDER_ID: u32 = 37;
+    pub const ORDER_QTY: u32 = 38;
+    pub const ORD_STATUS: u32 = 39;
+    pub const ORD_TYPE: u32 = 40;
+    pub const ORIG_CL_ORD_ID: u32 = 41;
+    pub const PRICE: u32 = 44;
+    pub const SENDER_COMP_ID: u32 = 49;
+    pub const SENDING_TIME: u32 = 52;
+    pub const SIDE: u32 = 54;
+    pub const SYMBOL: u32 = 55;
+    pub const TARGET_COMP_ID: u32 = 56;
+    pub const TEXT: u32 = 58;
+    pub const TIME_IN_FORCE: u32 = 59;
+    pub const TRANSACT_TIME: u32 = 60;
+    pub const STOP_PX: u32 = 99;
+    pub const CXL_REJ_REASON: u32 = 102;
+    pub const EXPIRE_TIME: u32 = 126;
+    pub const EXEC_TYPE: u32 = 150;
+    pub const LEAVES_QTY: u32 = 151;
+    pub const CXL_REJ_RESPONSE_TO: u32 = 434;
+}
+
+/// FIX编解码错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FixError {
+    #[error("malformed message: {0}")]
+    Malformed(String),
+    #[error("unsupported BeginString {0}")]
+    BeginString(String),
+    #[error("body length mismatch: declared {declared}, actual {actual}")]
+    BodyLength { declared: usize, actual: usize },
+    #[error("checksum mismatch: declared {declared:03}, actual {actual:03}")]
+    CheckSum { declared: u8, actual: u8 },
+    #[error("missing required tag {0}")]
+    MissingTag(u32),
+    #[error("invalid value for tag {tag}: {value}")]
+    InvalidValue { tag: u32, value: String },
+    #[error("unsupported message type {0}")]
+    UnsupportedMsgType(String),
+    #[error("duplicate ClOrdID {0}")]
+    DuplicateClOrdId(String),
+}
+
+/// 一条FIX消息（不含8/9/10三个框架字段）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixMessage {
+    pub msg_type: String,
+    /// 按出现顺序的(标签, 值)
+    pub fields: Vec<(u32, String)>,
+}
+
+impl FixMessage {
+    pub fn new(msg_type: &str) -> Self {
+        Self {
+            msg_type: msg_type.to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// 追加字段
+    pub fn with(mut self, tag: u32, value: impl ToString) -> Self {
+        self.fields.push((tag, value.to_string()));
+        self
+    }
+
+    /// 第一个匹配标签的值
+    pub fn get(&self, tag: u32) -> Option<&str> {
+        self.fields.iter().find(|(t, _)| *t == tag).map(|(_, v)| v.as_str())
+    }
+
+    fn require(&self, tag: u32) -> Result<&str, FixError> {
+        self.get(tag).ok_or(FixError::MissingTag(tag))
+    }
+
+    fn parse<T: FromStr>(&self, tag: u32) -> Result<T, FixError> {
+        let value = self.require(tag)?;
+        value.parse().map_err(|_| FixError::InvalidValue {
+            tag,
+            value: value.to_string(),
+        })
+    }
+
+    /// 编码（自动填充BodyLength和CheckSum）
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::with_capacity(64 + self.fields.len() * 16);
+        push_field(&mut body, tag::MSG_TYPE, &self.msg_type);
+        for (tag, value) in &self.fields {
+            push_field(&mut body, *tag, value);
+        }
+
+        let mut out = Vec::with_capacity(body.len() + 32);
+        push_field(&mut out, tag::BEGIN_STRING, BEGIN_STRING);
+        push_field(&mut out, tag::BODY_LENGTH, &body.len().to_string());
+        out.extend_from_slice(&body);
+        let checksum = checksum(&out);
+        push_field(&mut out, tag::CHECK_SUM, &format!("{:03}", checksum));
+        out
+    }
+
+    /// 解码并校验BeginString、BodyLength和CheckSum
+    pub fn decode(data: &[u8]) -> Result<Self, FixError> {
+        let mut fields = Vec::new();
+        let mut offsets = Vec::new();
+        let mut start = 0;
+        while start < data.len() {
+            let end = data[start..]
+                .iter()
+                .position(|&b| b == SOH)
+                .map(|pos| start + pos)
+                .ok_or_else(|| FixError::Malformed("missing trailing SOH".to_string()))?;
+            let field = std::str::from_utf8(&data[start..end])
+                .map_err(|_| FixError::Malformed("field is not valid UTF-8".to_string()))?;
+            let (tag, value) = field
+                .split_once('=')
+                .ok_or_else(|| FixError::Malformed(format!("field without '=': {}", field)))?;
+            let tag: u32 = tag
+                .parse()
+                .map_err(|_| FixError::Malformed(format!("invalid tag {}", tag)))?;
+            fields.push((tag, value.to_string()));
+            offsets.push(start);
+            start = end + 1;
+        }
+
+        if fields.len() < 4 || fields[0].0 != tag::BEGIN_STRING || fields[1].0 != tag::BODY_LENGTH {
+            return Err(FixError::Malformed("message must start with 8= and 9=".to_string()));
+        }
+        if fields[0].1 != BEGIN_STRING {
+            return Err(FixError::BeginString(fields[0].1.clone()));
+        }
+        let last = fields.len() - 1;
+        if fields[last].0 != tag::CHECK_SUM {
+            return Err(FixError::Malformed("message must end with 10=".to_string()));
+        }
+
+        let declared = fields[1].1.parse().map_err(|_| FixError::InvalidValue {
+            tag: tag::BODY_LENGTH,
+            value: fields[1].1.clone(),
+        })?;
+        let actual = offsets[last] - offsets[2];
+        if declared != actual {
+            return Err(FixError::BodyLength { declared, actual });
+        }
+        let declared = fields[last].1.parse().map_err(|_| FixError::InvalidValue {
+            tag: tag::CHECK_SUM,
+            value: fields[last].1.clone(),
+        })?;
+        let actual = checksum(&data[..offsets[last]]);
+        if declared != actual {
+            return Err(FixError::CheckSum { declared, actual });
+        }
+
+        if fields[2].0 != tag::MSG_TYPE {
+            return Err(FixError::Malformed("MsgType must follow BodyLength".to_string()));
+        }
+        let msg_type = fields[2].1.clone();
+        fields.truncate(last);
+        fields.drain(..3);
+        Ok(Self { msg_type, fields })
+    }
+}
+
+fn push_field(buf: &mut Vec<u8>, tag: u32, value: &str) {
+    buf.extend_from_slice(tag.to_string().as_bytes());
+    buf.push(b'=');
+    buf.extend_from_slice(value.as_bytes());
+    buf.push(SOH);
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, &b| sum.wrapping_add(b))
+}
+
+/// 解码后的业务请求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixRequest {
+    /// NewOrderSingle
+    NewOrder {
+        cl_ord_id: String,
+        symbol: String,
+        command: Command,
+    },
+    /// OrderCancelRequest（`order_id`为None表示找不到原订单）
+    Cancel {
+        cl_ord_id: String,
+        orig_cl_ord_id: String,
+        symbol: String,
+        order_id: Option<OrderId>,
+    },
+}
+
+impl FixRequest {
+    /// 对应的引擎指令（找不到原订单的撤单没有指令）
+    pub fn command(&self) -> Option<Command> {
+        match self {
+            FixRequest::NewOrder { command, .. } => Some(*command),
+            FixRequest::Cancel { order_id, .. } => order_id.map(|order_id| Command::Cancel { order_id }),
+        }
+    }
+}
+
+/// 会话内的订单状态
+#[derive(Debug, Clone)]
+struct OrderState {
+    cl_ord_id: String,
+    symbol: String,
+    side: Side,
+    quantity: u32,
+    cum_qty: u32,
+    /// 累计成交额（tick × 数量）
+    notional: u64,
+}
+
+impl OrderState {
+    fn leaves(&self) -> u32 {
+        self.quantity - self.cum_qty
+    }
+}
+
+/// 执行报告类型(150)与订单状态(39)
+#[derive(Debug, Clone, Copy)]
+enum Exec {
+    New,
+    Fill { price: Price, quantity: Quantity },
+    Cancelled,
+}
+
+/// FIX会话（一个客户端连接）
+#[derive(Debug)]
+pub struct FixSession {
+    sender_comp_id: String,
+    target_comp_id: String,
+    converter: PriceConverter,
+    next_seq: u64,
+    next_exec_id: u64,
+    orders: HashMap<OrderId, OrderState>,
+    by_cl_ord_id: HashMap<String, OrderId>,
+    clock: Arc<dyn Clock>,
+}
+
+impl FixSession {
+    /// `sender_comp_id`为引擎一侧的CompID
+    pub fn new(sender_comp_id: &str, target_comp_id: &str, converter: PriceConverter) -> Self {
+        Self {
+            sender_comp_id: sender_comp_id.to_string(),
+            target_comp_id: target_comp_id.to_string(),
+            converter,
+            next_seq: 1,
+            next_exec_id: 1,
+            orders: HashMap::new(),
+            by_cl_ord_id: HashMap::new(),
+            clock: system_clock(),
+        }
+    }
+
+    /// 设置TransactTime/SendingTime使用的时间源
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 未完结的订单数
+    pub fn open_orders(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// 客户端订单ID对应的引擎订单ID
+    pub fn order_id(&self, cl_ord_id: &str) -> Option<OrderId> {
+        self.by_cl_ord_id.get(cl_ord_id).copied()
+    }
+
+    /// 解码客户端消息
+    pub fn decode(&self, data: &[u8]) -> Result<FixRequest, FixError> {
+        let message = FixMessage::decode(data)?;
+        match message.msg_type.as_str() {
+            "D" => self.decode_new_order(&message),
+            "F" => self.decode_cancel(&message),
+            other => Err(FixError::UnsupportedMsgType(other.to_string())),
+        }
+    }
+
+    fn decode_new_order(&self, message: &FixMessage) -> Result<FixRequest, FixError> {
+        let cl_ord_id = message.require(tag::CL_ORD_ID)?.to_string();
+        if self.by_cl_ord_id.contains_key(&cl_ord_id) {
+            return Err(FixError::DuplicateClOrdId(cl_ord_id));
+        }
+        let trader = message
+            .get(tag::ACCOUNT)
+            .or_else(|| message.get(tag::SENDER_COMP_ID))
+            .map_or(TraderId::from_str(&self.target_comp_id), TraderId::from_str);
+        let side = match message.require(tag::SIDE)? {
+            "1" => Side::Buy,
+            "2" => Side::Sell,
+            value => return Err(invalid_value(tag::SIDE, value)),
+        };
+        let quantity = Quantity::new(message.parse(tag::ORDER_QTY)?)
+            .ok_or_else(|| invalid_value(tag::ORDER_QTY, "0"))?;
+
+        let command = match message.require(tag::ORD_TYPE)? {
+            "2" => Command::Limit {
+                trader,
+                side,
+                price: self.price(message, tag::PRICE)?,
+                quantity,
+                tif: time_in_force(message)?,
+            },
+            "3" => Command::Stop {
+                trader,
+                side,
+                stop_price: self.price(message, tag::STOP_PX)?,
+                quantity,
+            },
+            "4" => Command::StopLimit {
+                trader,
+                side,
+                stop_price: self.price(message, tag::STOP_PX)?,
+                limit_price: self.price(message, tag::PRICE)?,
+                quantity,
+            },
+            value => return Err(invalid_value(tag::ORD_TYPE, value)),
+        };
+
+        Ok(FixRequest::NewOrder {
+            cl_ord_id,
+            symbol: message.require(tag::SYMBOL)?.to_string(),
+            command,
+        })
+    }
+
+    fn decode_cancel(&self, message: &FixMessage) -> Result<FixRequest, FixError> {
+        let orig_cl_ord_id = message.require(tag::ORIG_CL_ORD_ID)?.to_string();
+        let order_id = match message.get(tag::ORDER_ID) {
+            Some(_) => Some(message.parse(tag::ORDER_ID)?),
+            None => self.order_id(&orig_cl_ord_id),
+        };
+        Ok(FixRequest::Cancel {
+            cl_ord_id: message.require(tag::CL_ORD_ID)?.to_string(),
+            orig_cl_ord_id,
+            symbol: message.require(tag::SYMBOL)?.to_string(),
+            order_id,
+        })
+    }
+
+    fn price(&self, message: &FixMessage, tag: u32) -> Result<Price, FixError> {
+        let value: f64 = message.parse(tag)?;
+        self.converter
+            .from_decimal(value)
+            .ok_or_else(|| invalid_value(tag, message.get(tag).unwrap_or_default()))
+    }
+
+    /// 把请求的执行结果编码为回报
+    ///
+    /// 新订单依次回报受理(New)、每笔成交(Trade)，不挂单的剩余部分回报撤销(Canceled)。
+    /// 结果中属于本会话其他挂单的被动成交一并回报。
+    pub fn on_result(&mut self, request: &FixRequest, result: &CommandResult) -> Vec<Vec<u8>> {
+        let mut reports = Vec::new();
+        match (request, result) {
+            (FixRequest::NewOrder { cl_ord_id, symbol, command }, CommandResult::Accepted { order_id, trades }) => {
+                let (side, quantity, rests) = match *command {
+                    Command::Limit { side, quantity, tif, .. } => (side, quantity, tif.rests()),
+                    Command::Stop { side, quantity, .. } | Command::StopLimit { side, quantity, .. } => {
+                        (side, quantity, true)
+                    }
+                    _ => return reports,
+                };
+                self.by_cl_ord_id.insert(cl_ord_id.clone(), *order_id);
+                self.orders.insert(*order_id, OrderState {
+                    cl_ord_id: cl_ord_id.clone(),
+                    symbol: symbol.clone(),
+                    side,
+                    quantity: quantity.get(),
+                    cum_qty: 0,
+                    notional: 0,
+                });
+                reports.push(self.report(*order_id, Exec::New).encode());
+                for trade in trades {
+                    reports.extend(self.on_trade(trade));
+                }
+                if !rests && self.orders.contains_key(order_id) {
+                    reports.push(self.report(*order_id, Exec::Cancelled).encode());
+                }
+            }
+            (FixRequest::NewOrder { cl_ord_id, symbol, command }, CommandResult::Rejected(reason)) => {
+                let side = match *command {
+                    Command::Limit { side, .. } | Command::Stop { side, .. } | Command::StopLimit { side, .. } => side,
+                    _ => Side::Buy,
+                };
+                reports.push(self.reject(cl_ord_id, symbol, side, *reason));
+            }
+            (FixRequest::Cancel { cl_ord_id, orig_cl_ord_id, .. }, CommandResult::Cancelled { order_id, success: true })
+                if self.orders.contains_key(order_id) =>
+            {
+                // 撤销回报使用撤单请求的ClOrdID
+                self.by_cl_ord_id.remove(orig_cl_ord_id);
+                if let Some(order) = self.orders.get_mut(order_id) {
+                    order.cl_ord_id = cl_ord_id.clone();
+                }
+                let report = self.report(*order_id, Exec::Cancelled).with(tag::ORIG_CL_ORD_ID, orig_cl_ord_id);
+                reports.push(report.encode());
+            }
+            (FixRequest::Cancel { .. }, _) => reports.push(self.cancel_reject(request)),
+            _ => {}
+        }
+        reports
+    }
+
+    /// 成交回报（本会话的主动方或被动方订单）
+    pub fn on_trade(&mut self, trade: &Trade) -> Vec<Vec<u8>> {
+        let fill = Exec::Fill {
+            price: trade.price,
+            quantity: trade.quantity,
+        };
+        let mut reports = Vec::new();
+        for order_id in [trade.taker_order_id, trade.maker_order_id] {
+            if self.orders.contains_key(&order_id) {
+                reports.push(self.report(order_id, fill).encode());
+            }
+        }
+        reports
+    }
+
+    /// 生成执行报告并更新订单状态，完结的订单从会话中移除
+    fn report(&mut self, order_id: OrderId, exec: Exec) -> FixMessage {
+        let order = self.orders.get_mut(&order_id).expect("report for unknown order");
+        if let Exec::Fill { price, quantity } = exec {
+            order.cum_qty += quantity.get();
+            order.notional += price.get() * quantity.get() as u64;
+        }
+        let order = order.clone();
+        let (exec_type, ord_status, leaves) = match exec {
+            Exec::New => ("0", "0", order.leaves()),
+            Exec::Fill { .. } if order.leaves() == 0 => ("F", "2", 0),
+            Exec::Fill { .. } => ("F", "1", order.leaves()),
+            Exec::Cancelled => ("4", "4", 0),
+        };
+        if leaves == 0 {
+            self.orders.remove(&order_id);
+            self.by_cl_ord_id.remove(&order.cl_ord_id);
+        }
+
+        let avg_ticks = if order.cum_qty > 0 {
+            order.notional as f64 / order.cum_qty as f64
+        } else {
+            0.0
+        };
+        let avg_px = self.converter.format_average(avg_ticks);
+        let mut message = self
+            .header("8")
+            .with(tag::ORDER_ID, order_id)
+            .with(tag::CL_ORD_ID, &order.cl_ord_id)
+            .with(tag::EXEC_ID, self.exec_id())
+            .with(tag::EXEC_TYPE, exec_type)
+            .with(tag::ORD_STATUS, ord_status)
+            .with(tag::SYMBOL, &order.symbol)
+            .with(tag::SIDE, side_code(order.side))
+            .with(tag::ORDER_QTY, order.quantity);
+        if let Exec::Fill { price, quantity } = exec {
+            message = message
+                .with(tag::LAST_PX, self.converter.format(price))
+                .with(tag::LAST_QTY, quantity);
+        }
+        message
+            .with(tag::LEAVES_QTY, leaves)
+            .with(tag::CUM_QTY, order.cum_qty)
+            .with(tag::AVG_PX, avg_px)
+            .with(tag::TRANSACT_TIME, utc_timestamp(self.clock.now_ns()))
+    }
+
+    fn reject(&mut self, cl_ord_id: &str, symbol: &str, side: Side, reason: RejectReason) -> Vec<u8> {
+        self.header("8")
+            .with(tag::ORDER_ID, "NONE")
+            .with(tag::CL_ORD_ID, cl_ord_id)
+            .with(tag::EXEC_ID, self.exec_id())
+            .with(tag::EXEC_TYPE, "8")
+            .with(tag::ORD_STATUS, "8")
+            .with(tag::SYMBOL, symbol)
+            .with(tag::SIDE, side_code(side))
+            .with(tag::LEAVES_QTY, 0)
+            .with(tag::CUM_QTY, 0)
+            .with(tag::AVG_PX, 0)
+            .with(tag::TEXT, reason)
+            .with(tag::TRANSACT_TIME, utc_timestamp(self.clock.now_ns()))
+            .encode()
+    }
+
+    fn cancel_reject(&mut self, request: &FixRequest) -> Vec<u8> {
+        let FixRequest::Cancel { cl_ord_id, orig_cl_ord_id, order_id, .. } = request else {
+            unreachable!("cancel reject for a new order");
+        };
+        self.header("9")
+            .with(tag::ORDER_ID, order_id.map_or("NONE".to_string(), |id| id.to_string()))
+            .with(tag::CL_ORD_ID, cl_ord_id)
+            .with(tag::ORIG_CL_ORD_ID, orig_cl_ord_id)
+            .with(tag::ORD_STATUS, "8")
+            .with(tag::CXL_REJ_RESPONSE_TO, "1")
+            // 1 = Unknown order
+            .with(tag::CXL_REJ_REASON, "1")
+            .encode()
+    }
+
+    fn header(&mut self, msg_type: &str) -> FixMessage {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        FixMessage::new(msg_type)
+            .with(tag::SENDER_COMP_ID, &self.sender_comp_id)
+            .with(tag::TARGET_COMP_ID, &self.target_comp_id)
+            .with(tag::MSG_SEQ_NUM, seq)
+            .with(tag::SENDING_TIME, utc_timestamp(self.clock.now_ns()))
+    }
+
+    fn exec_id(&mut self) -> u64 {
+        let id = self.next_exec_id;
+        self.next_exec_id += 1;
+        id
+    }
+}
+
+fn invalid_value(tag: u32, value: &str) -> FixError {
+    FixError::InvalidValue {
+        tag,
+        value: value.to_string(),
+    }
+}
+
+fn side_code(side: Side) -> &'static str {
+    match side {
+        Side::Buy => "1",
+        Side::Sell => "2",
+    }
+}
+
+fn time_in_force(message: &FixMessage) -> Result<TimeInForce, FixError> {
+    match message.get(tag::TIME_IN_FORCE).unwrap_or("1") {
+        // Day单按GTC处理
+        "0" | "1" => Ok(TimeInForce::Gtc),
+        "3" => Ok(TimeInForce::Ioc),
+        "4" => Ok(TimeInForce::Fok),
+        "6" => {
+            let value = message.require(tag::EXPIRE_TIME)?;
+            parse_utc_timestamp(value)
+                .map(TimeInForce::Gtd)
+                .ok_or_else(|| invalid_value(tag::EXPIRE_TIME, value))
+        }
+        value => Err(invalid_value(tag::TIME_IN_FORCE, value)),
+    }
+}
+
+/// 纳秒时间戳格式化为UTCTimestamp（YYYYMMDD-HH:MM:SS.sss）
+pub fn utc_timestamp(ns: u64) -> String {
+    let millis = ns / 1_000_000;
+    let secs = millis / 1000;
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let day_secs = secs % 86_400;
+    format!(
+        "{:04}{:02}{:02}-{:02}:{:02}:{:02}.{:03}",
+        year,
+        month,
+        day,
+        day_secs / 3600,
+        day_secs / 60 % 60,
+        day_secs % 60,
+        millis % 1000
+    )
+}
+
+/// 解析UTCTimestamp（毫秒部分可选）为纳秒时间戳
+pub fn parse_utc_timestamp(value: &str) -> Option<u64> {
+    let (date, time) = value.split_once('-')?;
+    if date.len() != 8 || !date.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let year: i64 = date[..4].parse().ok()?;
+    let month: u32 = date[4..6].parse().ok()?;
+    let day: u32 = date[6..].parse().ok()?;
+
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut parts = time.split(':').map(|part| part.parse::<u64>().ok());
+    let (hour, minute, second) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let nanos = if fraction.is_empty() {
+        0
+    } else if fraction.len() <= 9 && fraction.bytes().all(|b| b.is_ascii_digit()) {
+        fraction.parse::<u64>().ok()? * 10u64.pow(9 - fraction.len() as u32)
+    } else {
+        return None;
+    };
+
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(((days * 86_400 + hour * 3600 + minute * 60 + second) * 1_000_000_000) + nanos)
+}
+
+/// 公历日期转1970-01-01起的天数
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// 1970-01-01起的天数转公历日期
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::engine::OrderBook;
+    use crate::orderbook::types::{px, qty};
+
+    fn session() -> FixSession {
+        FixSession::new("ENGINE", "CLIENT", PriceConverter::cents())
+    }
+
+    fn new_order(cl_ord_id: &str, side: &str, price: &str, quantity: u32, tif: &str) -> Vec<u8> {
+        FixMessage::new("D")
+            .with(tag::SENDER_COMP_ID, "CLIENT")
+            .with(tag::TARGET_COMP_ID, "ENGINE")
+            .with(tag::MSG_SEQ_NUM, 1)
+            .with(tag::CL_ORD_ID, cl_ord_id)
+            .with(tag::ACCOUNT, "ACC1")
+            .with(tag::SYMBOL, "BTCUSDT")
+            .with(tag::SIDE, side)
+            .with(tag::ORDER_QTY, quantity)
+            .with(tag::ORD_TYPE, "2")
+            .with(tag::PRICE, price)
+            .with(tag::TIME_IN_FORCE, tif)
+            .encode()
+    }
+
+    fn decoded(report: &[u8]) -> FixMessage {
+        FixMessage::decode(report).unwrap()
+    }
+
+    #[test]
+    fn test_frame_roundtrip_and_validation() {
+        let data = FixMessage::new("0").with(tag::SENDER_COMP_ID, "A").encode();
+        assert_eq!(data, b"8=FIX.4.4\x019=10\x0135=0\x0149=A\x0110=187\x01");
+        assert_eq!(FixMessage::decode(&data).unwrap().get(tag::SENDER_COMP_ID), Some("A"));
+
+        let mut corrupted = data.clone();
+        corrupted[18] = b'1';
+        assert!(matches!(FixMessage::decode(&corrupted), Err(FixError::CheckSum { .. })));
+
+        let wrong_version = String::from_utf8(data.clone()).unwrap().replace("FIX.4.4", "FIX.4.2");
+        assert_eq!(
+            FixMessage::decode(wrong_version.as_bytes()),
+            Err(FixError::BeginString("FIX.4.2".to_string()))
+        );
+        assert!(matches!(FixMessage::decode(&data[..data.len() - 1]), Err(FixError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_new_order_maps_to_command() {
+        let session = session();
+        let request = session.decode(&new_order("C1", "1", "100.25", 10, "3")).unwrap();
+        assert_eq!(request, FixRequest::NewOrder {
+            cl_ord_id: "C1".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            command: Command::Limit {
+                trader: TraderId::from_str("ACC1"),
+                side: Side::Buy,
+                price: px(10025),
+                quantity: qty(10),
+                tif: TimeInForce::Ioc,
+            },
+        });
+
+        let gtd = FixMessage::decode(&new_order("C2", "2", "1.00", 1, "6"))
+            .unwrap()
+            .with(tag::EXPIRE_TIME, "20240102-03:04:05.006")
+            .encode();
+        let Some(Command::Limit { tif, .. }) = session.decode(&gtd).unwrap().command() else {
+            panic!("expected limit order");
+        };
+        assert_eq!(tif, TimeInForce::Gtd(1_704_164_645_006_000_000));
+        assert_eq!(utc_timestamp(1_704_164_645_006_000_000), "20240102-03:04:05.006");
+
+        assert_eq!(
+            session.decode(&new_order("C3", "7", "1.00", 1, "1")),
+            Err(FixError::InvalidValue { tag: tag::SIDE, value: "7".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_execution_reports() {
+        let mut book = OrderBook::with_capacity(100_000, 100);
+        let mut maker = session();
+        let mut taker = session();
+
+        let request = maker.decode(&new_order("M1", "2", "100.00", 10, "1")).unwrap();
+        let result = request.command().unwrap().execute(&mut book);
+        let reports = maker.on_result(&request, &result);
+        assert_eq!(reports.len(), 1);
+        let ack = decoded(&reports[0]);
+        assert_eq!((ack.msg_type.as_str(), ack.get(tag::EXEC_TYPE), ack.get(tag::LEAVES_QTY)), ("8", Some("0"), Some("10")));
+        assert_eq!(maker.order_id("M1"), Some(1));
+
+        // IOC部分成交：受理、成交、剩余撤销
+        let request = taker.decode(&new_order("T1", "1", "100.00", 15, "3")).unwrap();
+        let result = request.command().unwrap().execute(&mut book);
+        let reports: Vec<_> = taker.on_result(&request, &result).iter().map(|r| decoded(r)).collect();
+        let kinds: Vec<_> = reports.iter().map(|r| (r.get(tag::EXEC_TYPE).unwrap(), r.get(tag::ORD_STATUS).unwrap())).collect();
+        assert_eq!(kinds, vec![("0", "0"), ("F", "1"), ("4", "4")]);
+        assert_eq!(reports[1].get(tag::LAST_PX), Some("100.00"));
+        assert_eq!(reports[1].get(tag::CUM_QTY), Some("10"));
+        assert_eq!(taker.open_orders(), 0);
+
+        // 被动方由自己的会话回报
+        let CommandResult::Accepted { trades, .. } = &result else { panic!("expected accepted") };
+        let fills = maker.on_trade(&trades[0]);
+        let fill = decoded(&fills[0]);
+        assert_eq!((fill.get(tag::CL_ORD_ID), fill.get(tag::ORD_STATUS)), (Some("M1"), Some("2")));
+        assert_eq!(maker.open_orders(), 0);
+    }
+
+    #[test]
+    fn test_cancel_and_cancel_reject() {
+        let mut book = OrderBook::with_capacity(100_000, 100);
+        let mut session = session();
+
+        let request = session.decode(&new_order("C1", "1", "99.00", 5, "1")).unwrap();
+        let result = request.command().unwrap().execute(&mut book);
+        session.on_result(&request, &result);
+
+        let cancel = FixMessage::new("F")
+            .with(tag::CL_ORD_ID, "C2")
+            .with(tag::ORIG_CL_ORD_ID, "C1")
+            .with(tag::SYMBOL, "BTCUSDT")
+            .with(tag::SIDE, "1")
+            .encode();
+        let request = session.decode(&cancel).unwrap();
+        assert_eq!(request.command(), Some(Command::Cancel { order_id: 1 }));
+        let result = request.command().unwrap().execute(&mut book);
+        let report = decoded(&session.on_result(&request, &result)[0]);
+        assert_eq!(report.get(tag::EXEC_TYPE), Some("4"));
+        assert_eq!((report.get(tag::CL_ORD_ID), report.get(tag::ORIG_CL_ORD_ID)), (Some("C2"), Some("C1")));
+        assert!(book.depth(1).bids.is_empty());
+
+        // 原订单已完结
+        let request = session.decode(&cancel).unwrap();
+        assert_eq!(request.command(), None);
+        let reject = decoded(&session.on_result(&request, &CommandResult::Rejected(RejectReason::UnknownSymbol))[0]);
+        assert_eq!((reject.msg_type.as_str(), reject.get(tag::CXL_REJ_REASON)), ("9", Some("1")));
+    }
+}
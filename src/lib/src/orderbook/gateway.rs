@@ -0,0 +1,494 @@
+/// 订单网关
+///
+/// 网关在收到指令时打上接收时间戳，撮合前检查排队延迟：
+/// 超过配置的延迟预算时按配置拒绝（`TooLate`），避免基于过期意图执行，
+/// 被丢弃的指令计入统计。
+///
+/// 按会话提交时还会检查会话限流和引擎队列深度，超限时返回`ThrottleNotice`
+/// （当前限额和建议重试间隔），由接入层原样回送客户端。
+
+use super::command::{Command, CommandResult, RejectReason};
+use super::engine::OrderBook;
+use crate::monitor::domain::admin::RateLimit;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// 超出延迟预算时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LateAction {
+    /// 拒绝指令（TooLate）
+    #[default]
+    Reject,
+    /// 仍然执行，仅计数
+    Execute,
+}
+
+/// 网关配置
+#[derive(Debug, Clone, Default)]
+pub struct GatewayConfig {
+    /// 从接收到撮合的最大排队延迟（None表示不限制）
+    pub latency_budget: Option<Duration>,
+    /// 超出预算时的处理方式
+    pub late_action: LateAction,
+    /// 会话限流和队列深度限制
+    pub throttle: ThrottleConfig,
+}
+
+/// 限流配置
+#[derive(Debug, Clone)]
+pub struct ThrottleConfig {
+    /// 每个会话每秒允许的指令数（None表示不限流）
+    pub session_rate: Option<u32>,
+    /// 会话允许的突发指令数（0表示等于`session_rate`）
+    pub session_burst: u32,
+    /// 引擎排队指令数上限（None表示不限制）
+    pub max_queue_depth: Option<usize>,
+    /// 队列超限时建议的重试间隔
+    pub queue_retry_after: Duration,
+    /// 运行时可调的会话速率（设置后取代`session_rate`，每条指令读取一次）
+    pub runtime_rate: Option<RateLimit>,
+}
+
+impl ThrottleConfig {
+    /// 会话速率从运行时配置读取（如`RuntimeConfig::rate_limit("session", ..)`）
+    pub fn with_runtime_rate(mut self, rate: RateLimit) -> Self {
+        self.runtime_rate = Some(rate);
+        self
+    }
+
+    /// 当前每个会话每秒允许的指令数
+    #[inline]
+    pub fn current_session_rate(&self) -> Option<u32> {
+        match &self.runtime_rate {
+            Some(rate) => rate.per_second().map(|rate| rate.min(u32::MAX as u64) as u32),
+            None => self.session_rate,
+        }
+    }
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self {
+            session_rate: None,
+            session_burst: 0,
+            max_queue_depth: None,
+            queue_retry_after: Duration::from_millis(1),
+            runtime_rate: None,
+        }
+    }
+}
+
+/// 限流原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ThrottleReason {
+    /// 超出会话速率
+    SessionRate = 1,
+    /// 引擎队列过深
+    QueueDepth = 2,
+}
+
+/// 限流通知（代替静默拒绝回送客户端）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ThrottleNotice {
+    pub reason: ThrottleReason,
+    /// 当前限额（会话每秒指令数或队列深度上限）
+    pub limit: u64,
+    /// 建议的重试间隔
+    pub retry_after: Duration,
+}
+
+impl ThrottleNotice {
+    /// 编码长度
+    pub const ENCODED_LEN: usize = 1 + 8 + 8;
+
+    /// 编码为定长报文: 原因1 + 限额8 + 重试间隔（微秒）8，大端
+    pub fn encode(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut buf = [0u8; Self::ENCODED_LEN];
+        buf[0] = self.reason as u8;
+        buf[1..9].copy_from_slice(&self.limit.to_be_bytes());
+        buf[9..].copy_from_slice(&(self.retry_after.as_micros() as u64).to_be_bytes());
+        buf
+    }
+
+    /// 解码，格式错误时返回None
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let buf: &[u8; Self::ENCODED_LEN] = buf.get(..Self::ENCODED_LEN)?.try_into().ok()?;
+        let reason = match buf[0] {
+            1 => ThrottleReason::SessionRate,
+            2 => ThrottleReason::QueueDepth,
+            _ => return None,
+        };
+        Some(Self {
+            reason,
+            limit: u64::from_be_bytes(buf[1..9].try_into().ok()?),
+            retry_after: Duration::from_micros(u64::from_be_bytes(buf[9..].try_into().ok()?)),
+        })
+    }
+}
+
+/// 会话令牌桶
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// 按会话限流
+#[derive(Debug, Clone, Default)]
+pub struct SessionThrottle {
+    config: ThrottleConfig,
+    buckets: HashMap<u64, TokenBucket>,
+}
+
+impl SessionThrottle {
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self {
+            config,
+            buckets: HashMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn config(&self) -> &ThrottleConfig {
+        &self.config
+    }
+
+    /// 检查会话能否提交一条指令，通过时消耗一个令牌
+    pub fn check(&mut self, session: u64, queue_depth: usize, now: Instant) -> Result<(), ThrottleNotice> {
+        if let Some(max) = self.config.max_queue_depth
+            && queue_depth >= max
+        {
+            return Err(ThrottleNotice {
+                reason: ThrottleReason::QueueDepth,
+                limit: max as u64,
+                retry_after: self.config.queue_retry_after,
+            });
+        }
+
+        let Some(rate) = self.config.current_session_rate().filter(|&rate| rate > 0) else {
+            return Ok(());
+        };
+        let burst = match self.config.session_burst {
+            0 => rate,
+            burst => burst,
+        } as f64;
+        let bucket = self.buckets.entry(session).or_insert(TokenBucket {
+            tokens: burst,
+            updated_at: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate as f64).min(burst);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(ThrottleNotice {
+                reason: ThrottleReason::SessionRate,
+                limit: rate as u64,
+                retry_after: Duration::from_secs_f64((1.0 - bucket.tokens) / rate as f64),
+            })
+        }
+    }
+
+    /// 会话断开时清除其状态
+    pub fn remove_session(&mut self, session: u64) {
+        self.buckets.remove(&session);
+    }
+}
+
+/// 带接收时间戳的指令
+#[derive(Debug, Clone, Copy)]
+pub struct TimedCommand {
+    pub command: Command,
+    pub received_at: Instant,
+}
+
+impl TimedCommand {
+    /// 以当前时间作为接收时间
+    #[inline]
+    pub fn now(command: Command) -> Self {
+        Self {
+            command,
+            received_at: Instant::now(),
+        }
+    }
+}
+
+/// 网关统计
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GatewayStats {
+    /// 执行的指令数
+    pub commands_executed: u64,
+    /// 因超出延迟预算被拒绝的指令数
+    pub commands_shed: u64,
+    /// 超出延迟预算的指令数（包括仍然执行的）
+    pub commands_late: u64,
+    /// 因限流未执行的指令数
+    pub commands_throttled: u64,
+}
+
+#[derive(Default)]
+struct GatewayStatsInternal {
+    commands_executed: AtomicU64,
+    commands_shed: AtomicU64,
+    commands_late: AtomicU64,
+    commands_throttled: AtomicU64,
+}
+
+/// 订单网关（持有订单簿）
+pub struct OrderGateway {
+    book: OrderBook,
+    config: GatewayConfig,
+    stats: GatewayStatsInternal,
+    throttle: SessionThrottle,
+    /// 批量提交时复用的指令缓冲区
+    batch: Vec<Command>,
+}
+
+impl OrderGateway {
+    /// 创建订单网关
+    pub fn new(book: OrderBook, config: GatewayConfig) -> Self {
+        Self {
+            book,
+            throttle: SessionThrottle::new(config.throttle.clone()),
+            config,
+            stats: GatewayStatsInternal::default(),
+            batch: Vec::new(),
+        }
+    }
+
+    /// 获取订单簿
+    #[inline]
+    pub fn book(&self) -> &OrderBook {
+        &self.book
+    }
+
+    /// 获取可变订单簿
+    #[inline]
+    pub fn book_mut(&mut self) -> &mut OrderBook {
+        &mut self.book
+    }
+
+    /// 获取配置
+    #[inline]
+    pub fn config(&self) -> &GatewayConfig {
+        &self.config
+    }
+
+    /// 更新延迟预算
+    pub fn set_latency_budget(&mut self, budget: Option<Duration>) {
+        self.config.latency_budget = budget;
+    }
+
+    /// 提交指令（以当前时间检查延迟预算）
+    pub fn submit(&mut self, command: TimedCommand) -> CommandResult {
+        self.submit_at(command, Instant::now())
+    }
+
+    /// 提交指令，以`now`作为撮合开始时间检查延迟预算
+    pub fn submit_at(&mut self, command: TimedCommand, now: Instant) -> CommandResult {
+        if let Some(budget) = self.config.latency_budget
+            && now.saturating_duration_since(command.received_at) > budget
+        {
+            self.stats.commands_late.fetch_add(1, Ordering::Relaxed);
+            if self.config.late_action == LateAction::Reject {
+                self.stats.commands_shed.fetch_add(1, Ordering::Relaxed);
+                return CommandResult::Rejected(RejectReason::TooLate);
+            }
+        }
+
+        self.stats.commands_executed.fetch_add(1, Ordering::Relaxed);
+        command.command.execute(&mut self.book)
+    }
+
+    /// 按会话提交指令，`queue_depth`为提交时引擎排队的指令数
+    ///
+    /// 超出会话速率或队列深度时不执行，返回限流通知。
+    pub fn submit_session(
+        &mut self,
+        session: u64,
+        command: TimedCommand,
+        queue_depth: usize,
+    ) -> Result<CommandResult, ThrottleNotice> {
+        let now = Instant::now();
+        if let Err(notice) = self.throttle.check(session, queue_depth, now) {
+            self.stats.commands_throttled.fetch_add(1, Ordering::Relaxed);
+            return Err(notice);
+        }
+        Ok(self.submit_at(command, now))
+    }
+
+    /// 会话断开时清除其限流状态
+    pub fn close_session(&mut self, session: u64) {
+        self.throttle.remove_session(session);
+    }
+
+    /// 批量提交指令（定序器成批取出队列时使用），结果与指令一一对应
+    ///
+    /// 整批使用同一个撮合开始时间检查延迟预算；未设置预算时直接交给`apply_batch_into`。
+    pub fn submit_batch(&mut self, commands: &[TimedCommand]) -> Vec<CommandResult> {
+        let mut results = Vec::with_capacity(commands.len());
+        self.submit_batch_into(commands, Instant::now(), &mut results);
+        results
+    }
+
+    /// 批量提交指令，以`now`检查延迟预算，结果追加到`results`
+    pub fn submit_batch_into(&mut self, commands: &[TimedCommand], now: Instant, results: &mut Vec<CommandResult>) {
+        if self.config.latency_budget.is_none() {
+            self.batch.clear();
+            self.batch.extend(commands.iter().map(|timed| timed.command));
+            self.stats.commands_executed.fetch_add(commands.len() as u64, Ordering::Relaxed);
+            self.book.apply_batch_into(&self.batch, results);
+            return;
+        }
+
+        results.reserve(commands.len());
+        for &command in commands {
+            results.push(self.submit_at(command, now));
+        }
+    }
+
+    /// 从队列头部取出最多`max`条指令批量执行
+    pub fn drain_batch(&mut self, queue: &mut VecDeque<TimedCommand>, max: usize) -> Vec<CommandResult> {
+        let count = max.min(queue.len());
+        let batch: Vec<TimedCommand> = queue.drain(..count).collect();
+        self.submit_batch(&batch)
+    }
+
+    /// 获取统计信息
+    pub fn stats(&self) -> GatewayStats {
+        GatewayStats {
+            commands_executed: self.stats.commands_executed.load(Ordering::Relaxed),
+            commands_shed: self.stats.commands_shed.load(Ordering::Relaxed),
+            commands_late: self.stats.commands_late.load(Ordering::Relaxed),
+            commands_throttled: self.stats.commands_throttled.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty, Side, TimeInForce, TraderId};
+
+    fn buy(price: u32) -> Command {
+        Command::Limit {
+            trader: TraderId::from_str("B"),
+            side: Side::Buy,
+            price: px(price),
+            quantity: qty(1),
+            tif: TimeInForce::Gtc,
+        }
+    }
+
+    #[test]
+    fn test_late_commands_are_shed() {
+        let config = GatewayConfig {
+            latency_budget: Some(Duration::from_micros(100)),
+            late_action: LateAction::Reject,
+            ..Default::default()
+        };
+        let mut gateway = OrderGateway::new(OrderBook::with_capacity(20_000, 16), config);
+
+        let received_at = Instant::now();
+        let on_time = TimedCommand { command: buy(10000), received_at };
+        let stale = TimedCommand { command: buy(10001), received_at };
+
+        let result = gateway.submit_at(on_time, received_at + Duration::from_micros(50));
+        assert!(matches!(result, CommandResult::Accepted { .. }));
+
+        let result = gateway.submit_at(stale, received_at + Duration::from_millis(1));
+        assert!(matches!(result, CommandResult::Rejected(RejectReason::TooLate)));
+        assert_eq!(gateway.book().best_bid(), Some(px(10000)));
+
+        let stats = gateway.stats();
+        assert_eq!(stats.commands_executed, 1);
+        assert_eq!(stats.commands_shed, 1);
+        assert_eq!(stats.commands_late, 1);
+    }
+
+    #[test]
+    fn test_late_action_execute() {
+        let config = GatewayConfig {
+            latency_budget: Some(Duration::ZERO),
+            late_action: LateAction::Execute,
+            ..Default::default()
+        };
+        let mut gateway = OrderGateway::new(OrderBook::with_capacity(20_000, 16), config);
+
+        let command = TimedCommand::now(buy(10000));
+        let result = gateway.submit_at(command, command.received_at + Duration::from_millis(1));
+        assert!(!result.is_rejected());
+        assert_eq!(gateway.stats().commands_late, 1);
+        assert_eq!(gateway.stats().commands_shed, 0);
+    }
+
+    #[test]
+    fn test_drain_batch() {
+        let mut queue: VecDeque<_> = (0..5).map(|i| TimedCommand::now(buy(10000 + i))).collect();
+
+        let mut gateway = OrderGateway::new(OrderBook::with_capacity(20_000, 16), GatewayConfig::default());
+        let results = gateway.drain_batch(&mut queue, 3);
+        assert_eq!(results.len(), 3);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(gateway.book().best_bid(), Some(px(10002)));
+
+        // 设置预算时逐条检查，整批使用同一时间
+        gateway.set_latency_budget(Some(Duration::from_micros(100)));
+        let commands: Vec<_> = queue.drain(..).collect();
+        let now = commands[0].received_at + Duration::from_millis(1);
+        let mut results = Vec::new();
+        gateway.submit_batch_into(&commands, now, &mut results);
+        assert!(results.iter().all(CommandResult::is_rejected));
+        assert_eq!(gateway.stats().commands_executed, 3);
+        assert_eq!(gateway.stats().commands_shed, 2);
+    }
+
+    #[test]
+    fn test_session_throttle() {
+        let config = ThrottleConfig {
+            session_rate: Some(10),
+            session_burst: 2,
+            max_queue_depth: Some(4),
+            ..Default::default()
+        };
+        let mut throttle = SessionThrottle::new(config);
+        let t0 = Instant::now();
+
+        assert!(throttle.check(1, 0, t0).is_ok());
+        assert!(throttle.check(1, 0, t0).is_ok());
+        let notice = throttle.check(1, 0, t0).unwrap_err();
+        assert_eq!((notice.reason, notice.limit), (ThrottleReason::SessionRate, 10));
+        assert_eq!(notice.retry_after, Duration::from_millis(100));
+        // 其他会话不受影响，令牌按速率恢复
+        assert!(throttle.check(2, 0, t0).is_ok());
+        assert!(throttle.check(1, 0, t0 + Duration::from_millis(100)).is_ok());
+
+        let notice = throttle.check(3, 4, t0).unwrap_err();
+        assert_eq!((notice.reason, notice.limit), (ThrottleReason::QueueDepth, 4));
+        assert_eq!(ThrottleNotice::decode(&notice.encode()), Some(notice));
+        assert_eq!(ThrottleNotice::decode(&[9; ThrottleNotice::ENCODED_LEN]), None);
+    }
+
+    #[test]
+    fn test_submit_session_throttled() {
+        let config = GatewayConfig {
+            throttle: ThrottleConfig {
+                session_rate: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut gateway = OrderGateway::new(OrderBook::with_capacity(20_000, 16), config);
+        assert!(gateway.submit_session(7, TimedCommand::now(buy(10000)), 0).is_ok());
+        let notice = gateway.submit_session(7, TimedCommand::now(buy(10001)), 0).unwrap_err();
+        assert_eq!(notice.reason, ThrottleReason::SessionRate);
+        assert_eq!(gateway.book().best_bid(), Some(px(10000)));
+        assert_eq!(gateway.stats().commands_throttled, 1);
+        assert_eq!(gateway.stats().commands_executed, 1);
+    }
+}
@@ -0,0 +1,241 @@
+/// 订单簿深度热力图导出
+///
+/// 按固定间隔采样引擎的聚合深度，按价格桶累加挂单量，
+/// 形成 时间 × 价格桶 的数量矩阵（买卖分开），
+/// 可写入紧凑的二进制文件，或序列化为JSON经WS接口下发，用于热力图可视化。
+
+use super::engine::OrderBook;
+use super::types::{DepthLevel, Price};
+use serde::Serialize;
+use std::io::{self, Read, Write};
+
+/// 文件魔数
+const MAGIC: &[u8; 4] = b"RLHM";
+/// 文件格式版本
+const VERSION: u16 = 2;
+
+/// 热力图配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct HeatmapConfig {
+    /// 价格下界（含）
+    pub price_min: Price,
+    /// 价格桶宽度（tick数）
+    pub bucket_size: u32,
+    /// 价格桶数量
+    pub buckets: usize,
+    /// 每次采样读取的深度档数
+    pub depth_levels: usize,
+    /// 采样间隔（纳秒）
+    pub interval_ns: u64,
+}
+
+impl HeatmapConfig {
+    /// 价格所在的桶（超出范围时返回None）
+    #[inline]
+    pub fn bucket_of(&self, price: Price) -> Option<usize> {
+        if price < self.price_min || self.bucket_size == 0 {
+            return None;
+        }
+        let bucket = (price.ticks_above(self.price_min).unwrap_or(0) / self.bucket_size as u64) as usize;
+        (bucket < self.buckets).then_some(bucket)
+    }
+}
+
+/// 热力图矩阵（行为采样时刻，列为价格桶）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DepthHeatmap {
+    pub config: HeatmapConfig,
+    /// 各行采样时间戳（纳秒）
+    pub timestamps: Vec<u64>,
+    /// 买方数量矩阵（行优先，长度 = 行数 × 桶数）
+    pub bids: Vec<u64>,
+    /// 卖方数量矩阵（行优先）
+    pub asks: Vec<u64>,
+    #[serde(skip)]
+    last_sample_ns: Option<u64>,
+}
+
+impl DepthHeatmap {
+    /// 创建空热力图
+    pub fn new(config: HeatmapConfig) -> Self {
+        Self {
+            config,
+            timestamps: Vec::new(),
+            bids: Vec::new(),
+            asks: Vec::new(),
+            last_sample_ns: None,
+        }
+    }
+
+    /// 采样行数
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    /// 获取某行的买卖数量
+    pub fn row(&self, index: usize) -> Option<(&[u64], &[u64])> {
+        let buckets = self.config.buckets;
+        (index < self.rows()).then(|| {
+            let range = index * buckets..(index + 1) * buckets;
+            (&self.bids[range.clone()], &self.asks[range])
+        })
+    }
+
+    /// 距上次采样达到间隔时采样，返回是否采样
+    pub fn maybe_sample(&mut self, book: &OrderBook, now_ns: u64) -> bool {
+        if let Some(last) = self.last_sample_ns
+            && now_ns.saturating_sub(last) < self.config.interval_ns
+        {
+            return false;
+        }
+        self.sample(book, now_ns);
+        true
+    }
+
+    /// 立即采样一行
+    pub fn sample(&mut self, book: &OrderBook, now_ns: u64) {
+        let depth = book.depth(self.config.depth_levels);
+        self.timestamps.push(now_ns);
+        Self::accumulate(&self.config, &mut self.bids, &depth.bids);
+        Self::accumulate(&self.config, &mut self.asks, &depth.asks);
+        self.last_sample_ns = Some(now_ns);
+    }
+
+    fn accumulate(config: &HeatmapConfig, matrix: &mut Vec<u64>, levels: &[DepthLevel]) {
+        let start = matrix.len();
+        matrix.resize(start + config.buckets, 0);
+        for level in levels {
+            if let Some(bucket) = config.bucket_of(level.price) {
+                matrix[start + bucket] += level.quantity.get() as u64;
+            }
+        }
+    }
+
+    /// 写入二进制格式
+    ///
+    /// 格式（little-endian）:
+    /// - 4字节魔数 "RLHM" + 2字节版本
+    /// - 8字节价格下界 + 4字节桶宽度 + 4字节桶数量 + 4字节深度档数 + 8字节采样间隔
+    /// - 4字节行数
+    /// - 每行: 8字节时间戳 + 桶数量×8字节买方数量 + 桶数量×8字节卖方数量
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let config = &self.config;
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&config.price_min.get().to_le_bytes())?;
+        writer.write_all(&config.bucket_size.to_le_bytes())?;
+        writer.write_all(&(config.buckets as u32).to_le_bytes())?;
+        writer.write_all(&(config.depth_levels as u32).to_le_bytes())?;
+        writer.write_all(&config.interval_ns.to_le_bytes())?;
+        writer.write_all(&(self.rows() as u32).to_le_bytes())?;
+
+        for index in 0..self.rows() {
+            let (bids, asks) = self.row(index).unwrap();
+            writer.write_all(&self.timestamps[index].to_le_bytes())?;
+            for quantity in bids.iter().chain(asks) {
+                writer.write_all(&quantity.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 从二进制格式读取
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a heatmap file"));
+        }
+        let version = u16::from_le_bytes(read_array(reader)?);
+        if version != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported heatmap version {}", version),
+            ));
+        }
+
+        let config = HeatmapConfig {
+            price_min: Price::new(u64::from_le_bytes(read_array(reader)?))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "zero price_min"))?,
+            bucket_size: u32::from_le_bytes(read_array(reader)?),
+            buckets: u32::from_le_bytes(read_array(reader)?) as usize,
+            depth_levels: u32::from_le_bytes(read_array(reader)?) as usize,
+            interval_ns: u64::from_le_bytes(read_array(reader)?),
+        };
+        let rows = u32::from_le_bytes(read_array(reader)?) as usize;
+
+        let mut heatmap = Self::new(config);
+        for _ in 0..rows {
+            heatmap.timestamps.push(u64::from_le_bytes(read_array(reader)?));
+            for _ in 0..config.buckets {
+                heatmap.bids.push(u64::from_le_bytes(read_array(reader)?));
+            }
+            for _ in 0..config.buckets {
+                heatmap.asks.push(u64::from_le_bytes(read_array(reader)?));
+            }
+        }
+        heatmap.last_sample_ns = heatmap.timestamps.last().copied();
+        Ok(heatmap)
+    }
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty, Side, TimeInForce, TraderId};
+
+    fn config() -> HeatmapConfig {
+        HeatmapConfig {
+            price_min: px(9900),
+            bucket_size: 50,
+            buckets: 4,
+            depth_levels: 10,
+            interval_ns: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_samples_bucket_quantities() {
+        let mut book = OrderBook::with_capacity(20_000, 100);
+        let trader = TraderId::from_str("MM");
+        book.limit_order(trader, Side::Buy, px(9960), qty(3), TimeInForce::Gtc).unwrap();
+        book.limit_order(trader, Side::Buy, px(9990), qty(2), TimeInForce::Gtc).unwrap();
+        book.limit_order(trader, Side::Sell, px(10010), qty(7), TimeInForce::Gtc).unwrap();
+        book.limit_order(trader, Side::Sell, px(19999), qty(9), TimeInForce::Gtc).unwrap(); // 超出范围
+
+        let mut heatmap = DepthHeatmap::new(config());
+        assert!(heatmap.maybe_sample(&book, 0));
+        assert!(!heatmap.maybe_sample(&book, 500));
+        book.limit_order(trader, Side::Buy, px(9900), qty(1), TimeInForce::Gtc).unwrap();
+        assert!(heatmap.maybe_sample(&book, 1_000));
+
+        assert_eq!(heatmap.rows(), 2);
+        let (bids, asks) = heatmap.row(0).unwrap();
+        assert_eq!(bids, &[0, 5, 0, 0]);
+        assert_eq!(asks, &[0, 0, 7, 0]);
+        assert_eq!(heatmap.row(1).unwrap().0, &[1, 5, 0, 0]);
+    }
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let mut book = OrderBook::with_capacity(20_000, 100);
+        book.limit_order(TraderId::from_str("MM"), Side::Sell, px(10000), qty(4), TimeInForce::Gtc).unwrap();
+
+        let mut heatmap = DepthHeatmap::new(config());
+        heatmap.sample(&book, 42);
+        heatmap.sample(&book, 1_042);
+
+        let mut buf = Vec::new();
+        heatmap.write_to(&mut buf).unwrap();
+        let decoded = DepthHeatmap::read_from(&mut buf.as_slice()).unwrap();
+        assert_eq!(decoded, heatmap);
+        assert!(DepthHeatmap::read_from(&mut &buf[..buf.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+/// 品种规格
+///
+/// 每个订单簿一份。价格以u64整数表示（1个单位 = 10^-price_scale 报价货币），
+/// 新订单价格须落在[min_price, max_price]内且为tick_size的整数倍，数量须为lot_size的整数倍。
+/// 不在tick上的价格按`TickRounding`拒绝或向被动方向取整（买单向下、卖单向上）。
+/// 同价位挂单的成交分配按`AllocationPolicy`（时间优先或按挂单量比例）。
+
+use super::command::RejectReason;
+use super::price_converter::PriceConverter;
+use super::types::{Price, Quantity, Side};
+
+/// 不在tick上的价格的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TickRounding {
+    /// 拒绝（`RejectReason::OffTick`）
+    #[default]
+    Reject,
+    /// 向被动方向取整到tick（买单向下、卖单向上），不会使订单更激进
+    Passive,
+}
+
+/// 同价位挂单的成交分配方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AllocationPolicy {
+    /// 价格-时间优先
+    #[default]
+    Fifo,
+    /// 按挂单量比例分配（期货常用）
+    ///
+    /// 来单不足以吃完整个档位时，每笔挂单先分得`floor(来单量 × 挂单量 / 档位总量)`
+    /// 并向下取整到整手，低于`min_allocation`（数量）的分配归零；余量按时间顺序每笔一手轮流补齐，
+    /// 因此每笔分配都是整手。存在零股挂单而无法按整手补齐时，余量按时间顺序分给剩余挂单。
+    /// 来单足以吃完整个档位时与FIFO相同。
+    ProRata { min_allocation: u32 },
+}
+
+impl AllocationPolicy {
+    /// 按比例分配`incoming`到各挂单（`resting`为按时间顺序的挂单剩余量，总量须大于`incoming`）
+    ///
+    /// 分配量合计总等于`incoming`；来单量和挂单量都是`lot_size`的整数倍时，各分配量也是整手。
+    pub fn pro_rata(incoming: u32, resting: &[u32], min_allocation: u32, lot_size: u32) -> Vec<u32> {
+        let lot_size = lot_size.max(1);
+        let total: u64 = resting.iter().map(|&q| q as u64).sum();
+        let mut allocations: Vec<u32> = resting
+            .iter()
+            .map(|&q| {
+                let share = (incoming as u64 * q as u64 / total.max(1)) as u32;
+                let share = share - share % lot_size;
+                if share < min_allocation { 0 } else { share }
+            })
+            .collect();
+
+        // 余量按时间顺序每笔一手补齐
+        let mut leftover = incoming - allocations.iter().sum::<u32>();
+        while leftover >= lot_size {
+            let before = leftover;
+            for (allocation, &q) in allocations.iter_mut().zip(resting) {
+                if leftover < lot_size {
+                    break;
+                }
+                if q - *allocation >= lot_size {
+                    *allocation += lot_size;
+                    leftover -= lot_size;
+                }
+            }
+            if leftover == before {
+                break;
+            }
+        }
+        // 挂单不是整手时可能无法再按整手补齐：余量按时间顺序以挂单剩余量补足，保证每次撮合都有进展
+        for (allocation, &q) in allocations.iter_mut().zip(resting) {
+            let top_up = leftover.min(q - *allocation);
+            *allocation += top_up;
+            leftover -= top_up;
+        }
+        allocations
+    }
+}
+
+/// 品种规格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstrumentSpec {
+    /// 最小价格变动（价格单位数）
+    pub tick_size: u64,
+    /// 价格小数位数（1个价格单位 = 10^-price_scale）
+    pub price_scale: u32,
+    /// 最低可下单价格
+    pub min_price: Price,
+    /// 最高可下单价格
+    pub max_price: Price,
+    /// 数量最小单位
+    pub lot_size: u32,
+    /// 不在tick上的价格的处理方式
+    pub rounding: TickRounding,
+    /// 同价位成交分配方式
+    pub allocation: AllocationPolicy,
+}
+
+impl Default for InstrumentSpec {
+    /// 以分为单位、tick为1分、不限价格范围、每手1个
+    fn default() -> Self {
+        Self {
+            tick_size: 1,
+            price_scale: 2,
+            min_price: Price::MIN,
+            max_price: Price::MAX,
+            lot_size: 1,
+            rounding: TickRounding::Reject,
+            allocation: AllocationPolicy::Fifo,
+        }
+    }
+}
+
+impl InstrumentSpec {
+    /// 创建品种规格（不限价格范围、每手1个）
+    ///
+    /// # Panics
+    /// tick_size必须为正数
+    pub fn new(tick_size: u64, price_scale: u32) -> Self {
+        assert!(tick_size > 0, "tick size must be positive");
+        Self {
+            tick_size,
+            price_scale,
+            ..Self::default()
+        }
+    }
+
+    /// 设置价格范围
+    pub fn with_price_range(mut self, min_price: Price, max_price: Price) -> Self {
+        self.min_price = min_price;
+        self.max_price = max_price;
+        self
+    }
+
+    /// 设置每手数量
+    ///
+    /// # Panics
+    /// lot_size必须为正数
+    pub fn with_lot_size(mut self, lot_size: u32) -> Self {
+        assert!(lot_size > 0, "lot size must be positive");
+        self.lot_size = lot_size;
+        self
+    }
+
+    /// 设置不在tick上的价格的处理方式
+    pub fn with_rounding(mut self, rounding: TickRounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// 设置同价位成交分配方式
+    pub fn with_allocation(mut self, allocation: AllocationPolicy) -> Self {
+        self.allocation = allocation;
+        self
+    }
+
+    /// 价格是否在tick上
+    #[inline]
+    pub fn is_on_tick(&self, price: Price) -> bool {
+        price.get().is_multiple_of(self.tick_size)
+    }
+
+    /// 价格是否在允许范围内
+    #[inline]
+    pub fn in_range(&self, price: Price) -> bool {
+        price >= self.min_price && price <= self.max_price
+    }
+
+    /// 向被动方向取整到tick（取整后为0或溢出时返回None）
+    pub fn round_passive(&self, side: Side, price: Price) -> Option<Price> {
+        let remainder = price.get() % self.tick_size;
+        if remainder == 0 {
+            return Some(price);
+        }
+        match side {
+            Side::Buy => Price::new(price.get() - remainder),
+            Side::Sell => price.get().checked_add(self.tick_size - remainder).and_then(Price::new),
+        }
+    }
+
+    /// 校验新订单价格，按规格取整后返回实际使用的价格
+    pub fn normalize_price(&self, side: Side, price: Price) -> Result<Price, RejectReason> {
+        let price = match self.rounding {
+            _ if self.is_on_tick(price) => price,
+            TickRounding::Reject => return Err(RejectReason::OffTick),
+            TickRounding::Passive => self.round_passive(side, price).ok_or(RejectReason::PriceOutOfRange)?,
+        };
+        if !self.in_range(price) {
+            return Err(RejectReason::PriceOutOfRange);
+        }
+        Ok(price)
+    }
+
+    /// 校验数量是否为非零整手
+    #[inline]
+    pub fn check_quantity(&self, quantity: Quantity) -> Result<(), RejectReason> {
+        if quantity.is_zero() || !quantity.get().is_multiple_of(self.lot_size) {
+            return Err(RejectReason::InvalidLotSize);
+        }
+        Ok(())
+    }
+
+    /// 对应的小数价格转换器
+    pub fn converter(&self) -> PriceConverter {
+        PriceConverter::new(10f64.powi(-(self.price_scale as i32)), self.price_scale)
+    }
+
+    /// 价格阶梯所需容量（最高价格+1）
+    pub fn ladder_size(&self) -> usize {
+        self.max_price.as_index().saturating_add(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty};
+
+    #[test]
+    fn test_validate_and_round() {
+        let spec = InstrumentSpec::new(5, 2).with_price_range(px(100), px(1_000)).with_lot_size(10);
+        assert_eq!(spec.normalize_price(Side::Buy, px(105)), Ok(px(105)));
+        assert_eq!(spec.normalize_price(Side::Buy, px(107)), Err(RejectReason::OffTick));
+        assert_eq!(spec.normalize_price(Side::Buy, px(95)), Err(RejectReason::PriceOutOfRange));
+        assert_eq!(spec.normalize_price(Side::Sell, px(1_005)), Err(RejectReason::PriceOutOfRange));
+
+        let passive = spec.with_rounding(TickRounding::Passive);
+        assert_eq!(passive.normalize_price(Side::Buy, px(107)), Ok(px(105)));
+        assert_eq!(passive.normalize_price(Side::Sell, px(107)), Ok(px(110)));
+        assert_eq!(passive.normalize_price(Side::Buy, px(97)), Err(RejectReason::PriceOutOfRange));
+        assert_eq!(passive.round_passive(Side::Buy, px(3)), None);
+
+        assert_eq!(spec.check_quantity(qty(30)), Ok(()));
+        assert_eq!(spec.check_quantity(qty(25)), Err(RejectReason::InvalidLotSize));
+    }
+
+    #[test]
+    fn test_pro_rata_allocation() {
+        // 40/60比例，最小分配2手
+        assert_eq!(AllocationPolicy::pro_rata(10, &[40, 60], 2, 1), vec![4, 6]);
+        // 1手的份额低于最小分配归零，余量按时间顺序补齐
+        assert_eq!(AllocationPolicy::pro_rata(10, &[5, 90, 5], 2, 1), vec![1, 9, 0]);
+        assert_eq!(AllocationPolicy::pro_rata(3, &[1, 1, 10], 1, 1), vec![1, 0, 2]);
+        assert_eq!(AllocationPolicy::pro_rata(7, &[3, 3, 3], 0, 1).iter().sum::<u32>(), 7);
+    }
+
+    #[test]
+    fn test_pro_rata_allocation_in_whole_lots() {
+        // 比例份额为37、61、30，向下取整到30、60、30，余量一手补给最早的挂单
+        let allocations = AllocationPolicy::pro_rata(130, &[120, 200, 100], 0, 10);
+        assert_eq!(allocations, vec![40, 60, 30]);
+
+        for (incoming, resting) in [(70, vec![30, 30, 30]), (10, vec![50, 50]), (250, vec![10, 90, 20, 200])] {
+            let allocations = AllocationPolicy::pro_rata(incoming, &resting, 10, 10);
+            assert!(allocations.iter().all(|a| a % 10 == 0), "{:?}", allocations);
+            assert!(allocations.iter().zip(&resting).all(|(a, q)| a <= q));
+            assert_eq!(allocations.iter().sum::<u32>(), incoming);
+        }
+
+        // 零股挂单无法按整手补齐：余量按时间顺序分配
+        assert_eq!(AllocationPolicy::pro_rata(10, &[3, 5, 7], 0, 10), vec![3, 5, 2]);
+    }
+
+    #[test]
+    fn test_converter_and_ladder_size() {
+        let spec = InstrumentSpec::new(1, 4).with_price_range(Price::MIN, px(99_999));
+        assert_eq!(spec.converter().to_decimal(px(12_345)), 1.2345);
+        assert_eq!(spec.ladder_size(), 100_000);
+        assert_eq!(InstrumentSpec::default().ladder_size(), usize::MAX);
+    }
+}
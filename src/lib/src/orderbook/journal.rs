@@ -0,0 +1,449 @@
+/// 指令日志（事件溯源）
+///
+/// 每条进入撮合引擎的指令（下单/撤单/改单/到期扫描）先分配序列号并追加写入日志，
+/// 再在订单簿上执行。撮合是确定性的，从空订单簿按序重放日志即可重建完全相同的
+/// 订单簿状态（订单ID、成交ID和挂单队列顺序一致，成交时间戳除外）。
+///
+/// 使用日志时到期撤销须以`Command::Expire`提交，不要直接调用`expire_orders`
+/// 或`spawn_expiry_sweep`，否则重放无法复现。
+
+use super::command::{Command, CommandResult};
+use super::engine::OrderBook;
+use super::types::{Price, Quantity, Side, TimeInForce, TraderId};
+use crate::timing::{system_clock, Clock};
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+const TAG_LIMIT: u8 = 1;
+const TAG_STOP: u8 = 2;
+const TAG_STOP_LIMIT: u8 = 3;
+const TAG_CANCEL: u8 = 4;
+const TAG_AMEND: u8 = 5;
+const TAG_EXPIRE: u8 = 6;
+
+const TIF_GTC: u8 = 0;
+const TIF_IOC: u8 = 1;
+const TIF_FOK: u8 = 2;
+const TIF_GTD: u8 = 3;
+
+/// 日志记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalEntry {
+    /// 序列号（连续递增）
+    pub sequence: u64,
+    /// 写入时间（纳秒）
+    pub timestamp_ns: u64,
+    pub command: Command,
+}
+
+impl JournalEntry {
+    /// 序列化
+    ///
+    /// 格式（little-endian）:
+    /// - 8字节序列号 + 8字节时间戳 + 1字节指令类型
+    /// - 限价: 8字节交易员 + 1字节方向 + 8字节价格 + 4字节数量 + 1字节有效期 + 8字节GTD到期时间
+    /// - 止损: 8字节交易员 + 1字节方向 + 8字节触发价 + 4字节数量
+    /// - 止损限价: 8字节交易员 + 1字节方向 + 8字节触发价 + 8字节限价 + 4字节数量
+    /// - 撤单: 8字节订单ID
+    /// - 改单: 8字节订单ID + 4字节新数量
+    /// - 到期扫描: 8字节时间
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut buf = Vec::with_capacity(48);
+        buf.extend_from_slice(&self.sequence.to_le_bytes());
+        buf.extend_from_slice(&self.timestamp_ns.to_le_bytes());
+
+        match self.command {
+            Command::Limit { trader, side, price, quantity, tif } => {
+                buf.push(TAG_LIMIT);
+                buf.extend_from_slice(trader.as_bytes());
+                buf.push(side as u8);
+                buf.extend_from_slice(&price.get().to_le_bytes());
+                buf.extend_from_slice(&quantity.get().to_le_bytes());
+                let (tag, expires_at) = match tif {
+                    TimeInForce::Gtc => (TIF_GTC, 0),
+                    TimeInForce::Ioc => (TIF_IOC, 0),
+                    TimeInForce::Fok => (TIF_FOK, 0),
+                    TimeInForce::Gtd(expires_at) => (TIF_GTD, expires_at),
+                };
+                buf.push(tag);
+                buf.extend_from_slice(&expires_at.to_le_bytes());
+            }
+            Command::Stop { trader, side, stop_price, quantity } => {
+                buf.push(TAG_STOP);
+                buf.extend_from_slice(trader.as_bytes());
+                buf.push(side as u8);
+                buf.extend_from_slice(&stop_price.get().to_le_bytes());
+                buf.extend_from_slice(&quantity.get().to_le_bytes());
+            }
+            Command::StopLimit { trader, side, stop_price, limit_price, quantity } => {
+                buf.push(TAG_STOP_LIMIT);
+                buf.extend_from_slice(trader.as_bytes());
+                buf.push(side as u8);
+                buf.extend_from_slice(&stop_price.get().to_le_bytes());
+                buf.extend_from_slice(&limit_price.get().to_le_bytes());
+                buf.extend_from_slice(&quantity.get().to_le_bytes());
+            }
+            Command::Cancel { order_id } => {
+                buf.push(TAG_CANCEL);
+                buf.extend_from_slice(&order_id.to_le_bytes());
+            }
+            Command::Amend { order_id, quantity } => {
+                buf.push(TAG_AMEND);
+                buf.extend_from_slice(&order_id.to_le_bytes());
+                buf.extend_from_slice(&quantity.get().to_le_bytes());
+            }
+            Command::Expire { now_ns } => {
+                buf.push(TAG_EXPIRE);
+                buf.extend_from_slice(&now_ns.to_le_bytes());
+            }
+        }
+
+        writer.write_all(&buf)
+    }
+
+    /// 读取下一条记录，在记录边界处遇到文件结尾时返回None
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Option<Self>> {
+        let mut first = [0u8; 8];
+        let mut filled = 0;
+        while filled < first.len() {
+            match reader.read(&mut first[filled..])? {
+                0 if filled == 0 => return Ok(None),
+                0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                n => filled += n,
+            }
+        }
+
+        let sequence = u64::from_le_bytes(first);
+        let timestamp_ns = u64::from_le_bytes(read_array(reader)?);
+        let [tag] = read_array(reader)?;
+
+        let command = match tag {
+            TAG_LIMIT => {
+                let trader = TraderId::new(read_array(reader)?);
+                let side = read_side(reader)?;
+                let price = read_price(reader)?;
+                let quantity = read_quantity(reader)?;
+                let [tif] = read_array(reader)?;
+                let expires_at = u64::from_le_bytes(read_array(reader)?);
+                let tif = match tif {
+                    TIF_GTC => TimeInForce::Gtc,
+                    TIF_IOC => TimeInForce::Ioc,
+                    TIF_FOK => TimeInForce::Fok,
+                    TIF_GTD => TimeInForce::Gtd(expires_at),
+                    other => return Err(invalid(format!("unknown time in force {}", other))),
+                };
+                Command::Limit { trader, side, price, quantity, tif }
+            }
+            TAG_STOP => Command::Stop {
+                trader: TraderId::new(read_array(reader)?),
+                side: read_side(reader)?,
+                stop_price: read_price(reader)?,
+                quantity: read_quantity(reader)?,
+            },
+            TAG_STOP_LIMIT => Command::StopLimit {
+                trader: TraderId::new(read_array(reader)?),
+                side: read_side(reader)?,
+                stop_price: read_price(reader)?,
+                limit_price: read_price(reader)?,
+                quantity: read_quantity(reader)?,
+            },
+            TAG_CANCEL => Command::Cancel {
+                order_id: u64::from_le_bytes(read_array(reader)?),
+            },
+            TAG_AMEND => Command::Amend {
+                order_id: u64::from_le_bytes(read_array(reader)?),
+                quantity: read_quantity(reader)?,
+            },
+            TAG_EXPIRE => Command::Expire {
+                now_ns: u64::from_le_bytes(read_array(reader)?),
+            },
+            other => return Err(invalid(format!("unknown command tag {}", other))),
+        };
+
+        Ok(Some(Self { sequence, timestamp_ns, command }))
+    }
+}
+
+pub(super) fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub(super) fn read_array<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+pub(super) fn read_side<R: Read>(reader: &mut R) -> io::Result<Side> {
+    match read_array(reader)? {
+        [b'B'] => Ok(Side::Buy),
+        [b'S'] => Ok(Side::Sell),
+        [other] => Err(invalid(format!("unknown side {}", other))),
+    }
+}
+
+pub(super) fn read_price<R: Read>(reader: &mut R) -> io::Result<Price> {
+    Price::new(u64::from_le_bytes(read_array(reader)?)).ok_or_else(|| invalid("zero price".to_string()))
+}
+
+pub(super) fn read_quantity<R: Read>(reader: &mut R) -> io::Result<Quantity> {
+    Quantity::new(u32::from_le_bytes(read_array(reader)?)).ok_or_else(|| invalid("zero quantity".to_string()))
+}
+
+/// 只追加的指令日志写入器
+pub struct CommandJournal<W: Write> {
+    writer: W,
+    next_sequence: u64,
+    clock: Arc<dyn Clock>,
+}
+
+impl<W: Write> CommandJournal<W> {
+    /// 创建日志，序列号从1开始
+    pub fn new(writer: W) -> Self {
+        Self::with_sequence(writer, 1)
+    }
+
+    /// 在已有日志之后续写，`next_sequence`为下一条记录的序列号
+    pub fn with_sequence(writer: W, next_sequence: u64) -> Self {
+        Self { writer, next_sequence, clock: system_clock() }
+    }
+
+    /// 设置记录时间戳的时间源
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 追加一条指令，返回分配的序列号
+    pub fn append(&mut self, command: &Command) -> io::Result<u64> {
+        let entry = JournalEntry {
+            sequence: self.next_sequence,
+            timestamp_ns: self.clock.now_ns(),
+            command: *command,
+        };
+        entry.write_to(&mut self.writer)?;
+        self.next_sequence += 1;
+        Ok(entry.sequence)
+    }
+
+    /// 下一条记录的序列号
+    #[inline]
+    pub fn next_sequence(&self) -> u64 {
+        self.next_sequence
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// 日志读取器，按顺序迭代记录
+pub struct JournalReader<R: Read> {
+    reader: R,
+    done: bool,
+}
+
+impl<R: Read> JournalReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, done: false }
+    }
+}
+
+impl<R: Read> Iterator for JournalReader<R> {
+    type Item = io::Result<JournalEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match JournalEntry::read_from(&mut self.reader) {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// 先写日志再执行的订单簿
+pub struct JournaledBook<W: Write> {
+    book: OrderBook,
+    journal: CommandJournal<W>,
+}
+
+impl<W: Write> JournaledBook<W> {
+    pub fn new(book: OrderBook, journal: CommandJournal<W>) -> Self {
+        Self { book, journal }
+    }
+
+    /// 记录并执行指令；日志写入失败时不执行
+    pub fn submit(&mut self, command: Command) -> io::Result<CommandResult> {
+        self.journal.append(&command)?;
+        Ok(command.execute(&mut self.book))
+    }
+
+    #[inline]
+    pub fn book(&self) -> &OrderBook {
+        &self.book
+    }
+
+    #[inline]
+    pub fn journal(&self) -> &CommandJournal<W> {
+        &self.journal
+    }
+
+    pub fn journal_mut(&mut self) -> &mut CommandJournal<W> {
+        &mut self.journal
+    }
+
+    pub fn into_parts(self) -> (OrderBook, CommandJournal<W>) {
+        (self.book, self.journal)
+    }
+}
+
+impl OrderBook {
+    /// 从日志重建订单簿（默认容量）
+    pub fn replay<I>(entries: I) -> io::Result<Self>
+    where
+        I: IntoIterator<Item = JournalEntry>,
+    {
+        let mut book = Self::new();
+        book.replay_into(entries)?;
+        Ok(book)
+    }
+
+    /// 在当前订单簿上按序重放日志，返回最后一条记录的序列号（无记录时为0）
+    ///
+    /// 序列号不连续时返回`InvalidData`错误，此前的记录已执行。
+    pub fn replay_into<I>(&mut self, entries: I) -> io::Result<u64>
+    where
+        I: IntoIterator<Item = JournalEntry>,
+    {
+        let mut last: Option<u64> = None;
+        for entry in entries {
+            if let Some(last) = last
+                && entry.sequence != last + 1
+            {
+                return Err(invalid(format!(
+                    "journal gap: expected sequence {}, found {}",
+                    last + 1,
+                    entry.sequence
+                )));
+            }
+            entry.command.execute(self);
+            last = Some(entry.sequence);
+        }
+        Ok(last.unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty};
+
+    fn limit(trader: &str, side: Side, price: u32, quantity: u32, tif: TimeInForce) -> Command {
+        Command::Limit {
+            trader: TraderId::from_str(trader),
+            side,
+            price: px(price),
+            quantity: qty(quantity),
+            tif,
+        }
+    }
+
+    fn commands() -> Vec<Command> {
+        vec![
+            limit("A", Side::Sell, 10010, 5, TimeInForce::Gtc),
+            limit("B", Side::Sell, 10020, 7, TimeInForce::Gtd(1_000)),
+            limit("C", Side::Buy, 9990, 4, TimeInForce::Gtc),
+            limit("D", Side::Buy, 10010, 3, TimeInForce::Ioc),
+            Command::Amend { order_id: 3, quantity: qty(2) },
+            Command::Stop {
+                trader: TraderId::from_str("E"),
+                side: Side::Buy,
+                stop_price: px(10015),
+                quantity: qty(1),
+            },
+            Command::StopLimit {
+                trader: TraderId::from_str("F"),
+                side: Side::Sell,
+                stop_price: px(9980),
+                limit_price: px(9970),
+                quantity: qty(2),
+            },
+            Command::Cancel { order_id: 1 },
+            Command::Expire { now_ns: 1_000 },
+            limit("G", Side::Sell, 9990, 1, TimeInForce::Fok),
+        ]
+    }
+
+    #[test]
+    fn test_entry_roundtrip() {
+        let mut buf = Vec::new();
+        let entries: Vec<_> = commands()
+            .into_iter()
+            .enumerate()
+            .map(|(i, command)| JournalEntry {
+                sequence: i as u64 + 1,
+                timestamp_ns: 42,
+                command,
+            })
+            .collect();
+        for entry in &entries {
+            entry.write_to(&mut buf).unwrap();
+        }
+
+        let decoded: Vec<_> = JournalReader::new(buf.as_slice()).collect::<io::Result<_>>().unwrap();
+        assert_eq!(decoded, entries);
+
+        // 截断的记录报错
+        let mut reader = JournalReader::new(&buf[..buf.len() - 1]);
+        assert!(reader.by_ref().take(entries.len() - 1).all(|entry| entry.is_ok()));
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_replay_rebuilds_book() {
+        let mut live = JournaledBook::new(OrderBook::new(), CommandJournal::new(Vec::new()));
+        for command in commands() {
+            live.submit(command).unwrap();
+        }
+        assert_eq!(live.journal().next_sequence(), commands().len() as u64 + 1);
+
+        let (book, journal) = live.into_parts();
+        let log = journal.into_inner();
+        let entries: Vec<_> = JournalReader::new(log.as_slice()).collect::<io::Result<_>>().unwrap();
+        let replayed = OrderBook::replay(entries).unwrap();
+
+        assert_eq!(replayed.depth(10), book.depth(10));
+        assert_eq!(replayed.next_order_id(), book.next_order_id());
+        assert_eq!(replayed.next_trade_id(), book.next_trade_id());
+        assert_eq!(replayed.trades().len(), book.trades().len());
+        assert!(replayed.trades().iter().zip(book.trades()).all(|(a, b)| a.same_execution(b)));
+        assert_eq!(replayed.stop_orders().len(), book.stop_orders().len());
+    }
+
+    #[test]
+    fn test_replay_rejects_gap() {
+        let entry = |sequence| JournalEntry {
+            sequence,
+            timestamp_ns: 0,
+            command: Command::Expire { now_ns: 0 },
+        };
+        let mut book = OrderBook::new();
+        assert_eq!(book.replay_into([entry(5), entry(6)]).unwrap(), 6);
+        let err = book.replay_into([entry(1), entry(3)]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,395 @@
+/// 价格阶梯（价格点存储后端）
+///
+/// - `DenseLadder`: 按价格直接索引的数组，O(1)访问，适合活跃品种；
+///   分层位图标记非空价格，查找下一个非空价格为O(log₆₄ n)
+/// - `SparseLadder`: 只保存有挂单的价格（BTreeMap），适合不活跃或高价品种，
+///   内存与价格范围无关
+///
+/// 订单簿构造时选择后端。
+
+use super::types::{Price, PricePoint};
+use std::collections::BTreeMap;
+use std::hint;
+use std::mem;
+
+/// 价格阶梯后端类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LadderKind {
+    /// 稠密数组
+    #[default]
+    Dense,
+    /// 稀疏有序映射
+    Sparse,
+}
+
+impl LadderKind {
+    /// 创建对应的价格阶梯
+    pub fn build(self, max_price: usize) -> Box<dyn PriceLadder> {
+        match self {
+            LadderKind::Dense => Box::new(DenseLadder::new(max_price)),
+            LadderKind::Sparse => Box::new(SparseLadder::new(max_price)),
+        }
+    }
+}
+
+/// 价格阶梯接口（单方向）
+pub trait PriceLadder: Send {
+    /// 价格上界（不含）
+    fn max_price(&self) -> usize;
+
+    /// 可挂单的最高价格
+    fn highest_price(&self) -> Price {
+        Price::new(self.max_price().saturating_sub(1) as u64).unwrap_or(Price::MIN)
+    }
+
+    /// 获取价格点（不存在时返回None）
+    fn level(&self, price: Price) -> Option<&PricePoint>;
+
+    /// 获取可变价格点（不存在时创建）
+    fn level_mut(&mut self, price: Price) -> &mut PricePoint;
+
+    /// 价格点已清空时调用，稀疏后端借此释放条目
+    fn release(&mut self, _price: Price) {}
+
+    /// 查找不低于`price`的第一个非空价格
+    fn next_non_empty(&self, price: Price) -> Option<Price>;
+
+    /// 查找不高于`price`的第一个非空价格
+    fn prev_non_empty(&self, price: Price) -> Option<Price>;
+
+    /// 逐页写入已分配的存储，使其在撮合前驻留内存（不改变内容）
+    fn prewarm(&mut self) {}
+
+    /// 占用的存储字节数（稀疏后端为估算值）
+    fn allocated_bytes(&self) -> usize;
+}
+
+/// 分层占用位图
+///
+/// 第0层每位对应一个价格，上层每位表示下一层对应的字是否非零，
+/// 顶层只有一个字。1000万个价格只需4层，查找最多上行再下行各4次。
+#[derive(Debug, Clone)]
+struct OccupancyBitmap {
+    layers: Vec<Vec<u64>>,
+}
+
+impl OccupancyBitmap {
+    fn new(bits: usize) -> Self {
+        let mut layers = Vec::new();
+        let mut len = bits.max(1);
+        loop {
+            let words = len.div_ceil(64);
+            layers.push(vec![0u64; words]);
+            if words == 1 {
+                break;
+            }
+            len = words;
+        }
+        Self { layers }
+    }
+
+    /// 原值写回每个字（calloc分配的零页在首次写入时才真正分配）
+    fn prewarm(&mut self) {
+        for word in self.layers.iter_mut().flatten() {
+            *word = hint::black_box(*word);
+        }
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.layers.iter().map(|layer| layer.capacity() * mem::size_of::<u64>()).sum()
+    }
+
+    fn set(&mut self, mut pos: usize) {
+        for layer in &mut self.layers {
+            let word = &mut layer[pos / 64];
+            let was_empty = *word == 0;
+            *word |= 1 << (pos % 64);
+            if !was_empty {
+                break;
+            }
+            pos /= 64;
+        }
+    }
+
+    fn clear(&mut self, mut pos: usize) {
+        for layer in &mut self.layers {
+            let word = &mut layer[pos / 64];
+            *word &= !(1 << (pos % 64));
+            if *word != 0 {
+                break;
+            }
+            pos /= 64;
+        }
+    }
+
+    /// 不小于`pos`的第一个置位
+    fn next(&self, mut pos: usize) -> Option<usize> {
+        // 上行：在当前层找同一字内不低于pos的置位，没有则到上一层找后续的字
+        let mut level = 0;
+        let mut found = loop {
+            let layer = self.layers.get(level)?;
+            let word = *layer.get(pos / 64)? & (!0u64 << (pos % 64));
+            if word != 0 {
+                break (pos / 64) * 64 + word.trailing_zeros() as usize;
+            }
+            pos = pos / 64 + 1;
+            level += 1;
+        };
+        // 下行：取各层对应字的最低置位
+        while level > 0 {
+            level -= 1;
+            found = found * 64 + self.layers[level][found].trailing_zeros() as usize;
+        }
+        Some(found)
+    }
+
+    /// 不大于`pos`的最后一个置位
+    fn prev(&self, mut pos: usize) -> Option<usize> {
+        let mut level = 0;
+        let mut found = loop {
+            let layer = self.layers.get(level)?;
+            let index = (pos / 64).min(layer.len() - 1);
+            let mask = if index == pos / 64 { !0u64 >> (63 - pos % 64) } else { !0u64 };
+            let word = layer[index] & mask;
+            if word != 0 {
+                break index * 64 + 63 - word.leading_zeros() as usize;
+            }
+            pos = index.checked_sub(1)?;
+            level += 1;
+        };
+        while level > 0 {
+            level -= 1;
+            found = found * 64 + 63 - self.layers[level][found].leading_zeros() as usize;
+        }
+        Some(found)
+    }
+}
+
+/// 稠密数组价格阶梯
+pub struct DenseLadder {
+    points: Vec<PricePoint>,
+    /// 可能非空的价格（取得可变价格点时置位，释放时清除）
+    occupied: OccupancyBitmap,
+}
+
+impl DenseLadder {
+    /// 创建覆盖`0..max_price`的价格阶梯
+    pub fn new(max_price: usize) -> Self {
+        Self {
+            points: vec![PricePoint::default(); max_price],
+            occupied: OccupancyBitmap::new(max_price),
+        }
+    }
+}
+
+impl PriceLadder for DenseLadder {
+    #[inline]
+    fn max_price(&self) -> usize {
+        self.points.len()
+    }
+
+    #[inline]
+    fn level(&self, price: Price) -> Option<&PricePoint> {
+        self.points.get(price.as_index())
+    }
+
+    #[inline]
+    fn level_mut(&mut self, price: Price) -> &mut PricePoint {
+        let index = price.as_index();
+        let point = &mut self.points[index];
+        self.occupied.set(index);
+        point
+    }
+
+    #[inline]
+    fn release(&mut self, price: Price) {
+        let index = price.as_index();
+        if self.points.get(index).is_some_and(PricePoint::is_empty) {
+            self.occupied.clear(index);
+        }
+    }
+
+    /// 位图中的置位可能对应未释放的空价格点，遇到时跳过
+    fn next_non_empty(&self, price: Price) -> Option<Price> {
+        let mut start = price.as_index();
+        loop {
+            let index = self.occupied.next(start).filter(|&index| index < self.points.len())?;
+            if !self.points[index].is_empty() {
+                return Price::new(index as u64);
+            }
+            start = index + 1;
+        }
+    }
+
+    fn prev_non_empty(&self, price: Price) -> Option<Price> {
+        let mut end = price.as_index().min(self.points.len().checked_sub(1)?);
+        loop {
+            let index = self.occupied.prev(end)?;
+            if !self.points[index].is_empty() {
+                return Price::new(index as u64);
+            }
+            end = index.checked_sub(1)?;
+        }
+    }
+
+    fn prewarm(&mut self) {
+        for point in &mut self.points {
+            *point = hint::black_box(*point);
+        }
+        self.occupied.prewarm();
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.points.capacity() * mem::size_of::<PricePoint>() + self.occupied.allocated_bytes()
+    }
+}
+
+/// 稀疏价格阶梯（只保存非空价格）
+pub struct SparseLadder {
+    points: BTreeMap<Price, PricePoint>,
+    max_price: usize,
+}
+
+impl SparseLadder {
+    /// 创建价格上界为`max_price`的稀疏阶梯
+    pub fn new(max_price: usize) -> Self {
+        Self {
+            points: BTreeMap::new(),
+            max_price,
+        }
+    }
+
+    /// 当前保存的价格点数量
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// 是否没有任何价格点
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+}
+
+impl PriceLadder for SparseLadder {
+    #[inline]
+    fn max_price(&self) -> usize {
+        self.max_price
+    }
+
+    #[inline]
+    fn level(&self, price: Price) -> Option<&PricePoint> {
+        self.points.get(&price)
+    }
+
+    #[inline]
+    fn level_mut(&mut self, price: Price) -> &mut PricePoint {
+        assert!(price.as_index() < self.max_price, "price {} out of range", price);
+        self.points.entry(price).or_default()
+    }
+
+    fn release(&mut self, price: Price) {
+        if self.points.get(&price).is_some_and(PricePoint::is_empty) {
+            self.points.remove(&price);
+        }
+    }
+
+    fn next_non_empty(&self, price: Price) -> Option<Price> {
+        self.points
+            .range(price..)
+            .find(|(_, point)| !point.is_empty())
+            .map(|(&price, _)| price)
+    }
+
+    fn prev_non_empty(&self, price: Price) -> Option<Price> {
+        self.points
+            .range(..=price)
+            .rev()
+            .find(|(_, point)| !point.is_empty())
+            .map(|(&price, _)| price)
+    }
+
+    /// 只计键值，不含树节点开销
+    fn allocated_bytes(&self) -> usize {
+        self.points.len() * mem::size_of::<(Price, PricePoint)>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::px;
+
+    fn exercise(mut ladder: Box<dyn PriceLadder>) {
+        assert_eq!(ladder.next_non_empty(Price::MIN), None);
+        ladder.level_mut(px(100)).push_back(0);
+        ladder.level_mut(px(300)).push_back(1);
+
+        assert_eq!(ladder.next_non_empty(Price::MIN), Some(px(100)));
+        assert_eq!(ladder.next_non_empty(px(101)), Some(px(300)));
+        assert_eq!(ladder.next_non_empty(px(301)), None);
+        assert_eq!(ladder.prev_non_empty(Price::MAX), Some(px(300)));
+        assert_eq!(ladder.prev_non_empty(px(299)), Some(px(100)));
+        assert_eq!(ladder.prev_non_empty(px(99)), None);
+
+        *ladder.level_mut(px(100)) = PricePoint::default();
+        ladder.release(px(100));
+        assert!(ladder.level(px(100)).is_none_or(PricePoint::is_empty));
+        assert_eq!(ladder.next_non_empty(Price::MIN), Some(px(300)));
+    }
+
+    #[test]
+    fn test_dense_ladder() {
+        exercise(LadderKind::Dense.build(1_000));
+    }
+
+    #[test]
+    fn test_occupancy_bitmap() {
+        let mut bitmap = OccupancyBitmap::new(10_000_000);
+        assert_eq!(bitmap.layers.len(), 4);
+        assert_eq!((bitmap.next(0), bitmap.prev(9_999_999)), (None, None));
+
+        for pos in [3, 64, 4_095, 4_096, 262_143, 9_999_999] {
+            bitmap.set(pos);
+        }
+        assert_eq!(bitmap.next(0), Some(3));
+        assert_eq!(bitmap.next(4), Some(64));
+        assert_eq!(bitmap.next(4_096), Some(4_096));
+        assert_eq!(bitmap.next(4_097), Some(262_143));
+        assert_eq!(bitmap.next(262_144), Some(9_999_999));
+        assert_eq!(bitmap.prev(9_999_998), Some(262_143));
+        assert_eq!(bitmap.prev(4_095), Some(4_095));
+        assert_eq!(bitmap.prev(63), Some(3));
+        assert_eq!(bitmap.prev(2), None);
+        assert_eq!(bitmap.prev(usize::MAX / 2), Some(9_999_999));
+
+        bitmap.clear(4_096);
+        bitmap.clear(262_143);
+        assert_eq!(bitmap.next(4_096), Some(9_999_999));
+        assert_eq!(bitmap.prev(9_999_998), Some(4_095));
+    }
+
+    #[test]
+    fn test_dense_ladder_skips_stale_bits() {
+        let mut ladder = DenseLadder::new(1_000_000);
+        // 只取得可变引用而不挂单：位图置位但价格点为空
+        ladder.level_mut(px(10));
+        ladder.level_mut(px(999_000)).push_back(0);
+        assert_eq!(ladder.next_non_empty(Price::MIN), Some(px(999_000)));
+        assert_eq!(ladder.prev_non_empty(Price::MAX), Some(px(999_000)));
+        assert_eq!(ladder.prev_non_empty(px(998_999)), None);
+    }
+
+    #[test]
+    fn test_sparse_ladder() {
+        exercise(LadderKind::Sparse.build(1_000));
+
+        let mut ladder = SparseLadder::new(u32::MAX as usize);
+        ladder.level_mut(px(4_000_000_000)).push_back(0);
+        assert_eq!(ladder.len(), 1);
+        *ladder.level_mut(px(4_000_000_000)) = PricePoint::default();
+        ladder.release(px(4_000_000_000));
+        assert!(ladder.is_empty());
+    }
+}
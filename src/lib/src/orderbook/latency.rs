@@ -0,0 +1,133 @@
+/// 撮合引擎内的时延直方图
+///
+/// 开启`latency-histogram`特性后，`OrderBook`在`limit_order`和`cancel_order`内部
+/// 用HDR直方图记录每次调用的纳秒时延（计时来自`timing`的硬件计数器），
+/// 分位数通过`OrderBookSnapshot::latency`暴露，供运维观察尾部时延。
+/// 未开启时不计时，快照中该字段为None。
+
+use serde::Serialize;
+
+/// 单个操作的时延分位数（纳秒）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LatencyPercentiles {
+    /// 样本数
+    pub count: u64,
+    pub p50_ns: u64,
+    pub p90_ns: u64,
+    pub p99_ns: u64,
+    pub p999_ns: u64,
+    pub max_ns: u64,
+}
+
+/// 订单簿时延汇总
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LatencySummary {
+    pub limit_order: LatencyPercentiles,
+    pub cancel_order: LatencyPercentiles,
+}
+
+#[cfg(feature = "latency-histogram")]
+pub use recorder::LatencyRecorder;
+
+#[cfg(feature = "latency-histogram")]
+mod recorder {
+    use super::{LatencyPercentiles, LatencySummary};
+    use hdrhistogram::Histogram;
+
+    /// 可记录的最大时延（超出时按最大值记录）
+    const MAX_LATENCY_NS: u64 = 60_000_000_000;
+    /// 有效数字位数
+    const SIGNIFICANT_FIGURES: u8 = 3;
+
+    fn histogram() -> Histogram<u64> {
+        Histogram::new_with_bounds(1, MAX_LATENCY_NS, SIGNIFICANT_FIGURES).expect("valid histogram bounds")
+    }
+
+    fn percentiles(histogram: &Histogram<u64>) -> LatencyPercentiles {
+        if histogram.is_empty() {
+            return LatencyPercentiles::default();
+        }
+        LatencyPercentiles {
+            count: histogram.len(),
+            p50_ns: histogram.value_at_quantile(0.50),
+            p90_ns: histogram.value_at_quantile(0.90),
+            p99_ns: histogram.value_at_quantile(0.99),
+            p999_ns: histogram.value_at_quantile(0.999),
+            max_ns: histogram.max(),
+        }
+    }
+
+    /// 下单与撤单时延直方图
+    #[derive(Debug, Clone)]
+    pub struct LatencyRecorder {
+        limit_order: Histogram<u64>,
+        cancel_order: Histogram<u64>,
+    }
+
+    impl LatencyRecorder {
+        pub fn new() -> Self {
+            Self {
+                limit_order: histogram(),
+                cancel_order: histogram(),
+            }
+        }
+
+        #[inline]
+        pub fn record_limit(&mut self, latency_ns: u64) {
+            self.limit_order.saturating_record(latency_ns.max(1));
+        }
+
+        #[inline]
+        pub fn record_cancel(&mut self, latency_ns: u64) {
+            self.cancel_order.saturating_record(latency_ns.max(1));
+        }
+
+        /// 当前分位数
+        pub fn summary(&self) -> LatencySummary {
+            LatencySummary {
+                limit_order: percentiles(&self.limit_order),
+                cancel_order: percentiles(&self.cancel_order),
+            }
+        }
+
+        /// 清空样本（例如每个统计周期结束后）
+        pub fn reset(&mut self) {
+            self.limit_order.reset();
+            self.cancel_order.reset();
+        }
+    }
+
+    impl Default for LatencyRecorder {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+}
+
+#[cfg(all(test, feature = "latency-histogram"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles() {
+        let mut recorder = LatencyRecorder::new();
+        assert_eq!(recorder.summary(), LatencySummary::default());
+
+        for latency in 1..=1_000 {
+            recorder.record_limit(latency);
+        }
+        recorder.record_cancel(0);
+        recorder.record_cancel(u64::MAX);
+
+        let summary = recorder.summary();
+        assert_eq!(summary.limit_order.count, 1_000);
+        assert_eq!(summary.limit_order.p50_ns, 500);
+        assert_eq!(summary.limit_order.p99_ns, 990);
+        assert_eq!(summary.limit_order.max_ns, 1_000);
+        assert_eq!(summary.cancel_order.count, 2);
+        assert!(summary.cancel_order.max_ns >= 60_000_000_000);
+
+        recorder.reset();
+        assert_eq!(recorder.summary().limit_order.count, 0);
+    }
+}
@@ -0,0 +1,220 @@
+/// 多品种订单簿管理器
+///
+/// 按品种代码持有多个订单簿，按品种路由指令，
+/// 并在所有订单簿之间分配全局唯一的订单ID。
+
+use super::command::{Command, CommandResult, RejectReason};
+use super::config::{ConfigError, EngineConfig};
+use super::engine::{OrderBook, OrderBookSnapshot};
+use super::ladder::LadderKind;
+use super::types::OrderId;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 单个品种的统计
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolBookStats {
+    pub symbol: String,
+    #[serde(flatten)]
+    pub snapshot: OrderBookSnapshot,
+}
+
+/// 管理器汇总统计
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ManagerStats {
+    /// 下一个全局订单ID
+    pub next_order_id: OrderId,
+    /// 所有品种的活跃订单总数
+    pub active_orders: usize,
+    /// 所有品种的成交总数
+    pub total_trades: usize,
+    /// 各品种统计（按品种代码排序）
+    pub symbols: Vec<SymbolBookStats>,
+}
+
+/// 多品种订单簿管理器
+pub struct OrderBookManager {
+    books: HashMap<String, OrderBook>,
+    next_order_id: OrderId,
+    max_price: usize,
+    max_orders: usize,
+}
+
+impl OrderBookManager {
+    /// 创建管理器，新品种的订单簿使用给定容量
+    pub fn new(max_price: usize, max_orders: usize) -> Self {
+        Self {
+            books: HashMap::new(),
+            next_order_id: 1,
+            max_price,
+            max_orders,
+        }
+    }
+
+    /// 添加品种（稠密价格阶梯，已存在时返回false）
+    pub fn add_symbol(&mut self, symbol: &str) -> bool {
+        self.add_symbol_with_ladder(symbol, LadderKind::Dense)
+    }
+
+    /// 添加品种并指定价格阶梯后端（不活跃品种可使用稀疏阶梯节省内存）
+    pub fn add_symbol_with_ladder(&mut self, symbol: &str, ladder: LadderKind) -> bool {
+        if self.books.contains_key(symbol) {
+            return false;
+        }
+        let book = OrderBook::with_ladder(ladder, self.max_price, self.max_orders);
+        self.books.insert(symbol.to_string(), book);
+        true
+    }
+
+    /// 添加品种并使用独立的引擎配置（已存在时返回false）
+    pub fn add_symbol_with_config(&mut self, symbol: &str, config: EngineConfig) -> Result<bool, ConfigError> {
+        if self.books.contains_key(symbol) {
+            return Ok(false);
+        }
+        let book = OrderBook::with_config(config)?;
+        self.books.insert(symbol.to_string(), book);
+        Ok(true)
+    }
+
+    /// 移除品种，返回其订单簿
+    pub fn remove_symbol(&mut self, symbol: &str) -> Option<OrderBook> {
+        self.books.remove(symbol)
+    }
+
+    /// 品种数量
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.books.len()
+    }
+
+    /// 是否没有任何品种
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.books.is_empty()
+    }
+
+    /// 遍历品种代码
+    pub fn symbols(&self) -> impl Iterator<Item = &str> {
+        self.books.keys().map(String::as_str)
+    }
+
+    /// 获取品种的订单簿
+    #[inline]
+    pub fn book(&self, symbol: &str) -> Option<&OrderBook> {
+        self.books.get(symbol)
+    }
+
+    /// 获取品种的可变订单簿
+    ///
+    /// 直接在订单簿上下单会绕过全局订单ID分配，应只用于查询或配置。
+    #[inline]
+    pub fn book_mut(&mut self, symbol: &str) -> Option<&mut OrderBook> {
+        self.books.get_mut(symbol)
+    }
+
+    /// 下一个全局订单ID
+    #[inline]
+    pub fn next_order_id(&self) -> OrderId {
+        self.next_order_id
+    }
+
+    /// 将指令路由到品种的订单簿执行
+    ///
+    /// 未知品种返回`Rejected(UnknownSymbol)`。
+    pub fn submit(&mut self, symbol: &str, command: Command) -> CommandResult {
+        let Some(book) = self.books.get_mut(symbol) else {
+            return CommandResult::Rejected(RejectReason::UnknownSymbol);
+        };
+
+        // 订单簿从全局序号继续分配，执行后回收推进后的序号
+        book.set_next_order_id(self.next_order_id);
+        let result = command.execute(book);
+        self.next_order_id = book.next_order_id();
+        result
+    }
+
+    /// 汇总统计
+    pub fn stats(&self) -> ManagerStats {
+        let mut stats = ManagerStats {
+            next_order_id: self.next_order_id,
+            ..ManagerStats::default()
+        };
+
+        for (symbol, book) in &self.books {
+            let snapshot = book.snapshot();
+            stats.active_orders += snapshot.active_orders;
+            stats.total_trades += snapshot.total_trades;
+            stats.symbols.push(SymbolBookStats {
+                symbol: symbol.clone(),
+                snapshot,
+            });
+        }
+        stats.symbols.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty, Side, TimeInForce, TraderId};
+
+    fn limit(side: Side, price: u32, quantity: u32) -> Command {
+        Command::Limit {
+            trader: TraderId::from_str("T"),
+            side,
+            price: px(price),
+            quantity: qty(quantity),
+            tif: TimeInForce::Gtc,
+        }
+    }
+
+    fn order_id(result: CommandResult) -> OrderId {
+        match result {
+            CommandResult::Accepted { order_id, .. } => order_id,
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_routes_by_symbol_with_global_ids() {
+        let mut manager = OrderBookManager::new(20_000, 100);
+        assert!(manager.add_symbol("BTCUSDT"));
+        assert!(manager.add_symbol_with_ladder("ETHUSDT", LadderKind::Sparse));
+        assert!(!manager.add_symbol("BTCUSDT"));
+
+        let a = order_id(manager.submit("BTCUSDT", limit(Side::Sell, 10000, 5)));
+        let b = order_id(manager.submit("ETHUSDT", limit(Side::Sell, 3000, 5)));
+        let c = order_id(manager.submit("BTCUSDT", limit(Side::Buy, 10000, 2)));
+        assert_eq!((a, b, c), (1, 2, 3));
+
+        assert_eq!(manager.book("BTCUSDT").unwrap().best_ask(), Some(px(10000)));
+        assert_eq!(manager.book("ETHUSDT").unwrap().best_ask(), Some(px(3000)));
+
+        // 撤单只在所属品种生效
+        let result = manager.submit("ETHUSDT", Command::Cancel { order_id: a });
+        assert_eq!(result, CommandResult::Cancelled { order_id: a, success: false });
+
+        let result = manager.submit("SOLUSDT", limit(Side::Buy, 100, 1));
+        assert_eq!(result, CommandResult::Rejected(RejectReason::UnknownSymbol));
+    }
+
+    #[test]
+    fn test_consolidated_stats() {
+        let mut manager = OrderBookManager::new(20_000, 100);
+        manager.add_symbol("ETHUSDT");
+        manager.add_symbol("BTCUSDT");
+
+        manager.submit("BTCUSDT", limit(Side::Sell, 10000, 5));
+        manager.submit("BTCUSDT", limit(Side::Buy, 10000, 2));
+        manager.submit("ETHUSDT", limit(Side::Buy, 3000, 1));
+
+        let stats = manager.stats();
+        assert_eq!(stats.next_order_id, 4);
+        assert_eq!(stats.active_orders, 2);
+        assert_eq!(stats.total_trades, 1);
+        let symbols: Vec<&str> = stats.symbols.iter().map(|s| s.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["BTCUSDT", "ETHUSDT"]);
+    }
+}
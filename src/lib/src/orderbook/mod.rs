@@ -11,7 +11,7 @@
 //! 订单簿基于 Quant Cup 参考实现，并针对生产使用进行了增强：
 //! - 通过内存池分配器实现静态内存分配
 //! - 缓存行对齐的数据结构
-//! - 快速订单取消（单次内存写入）
+//! - O(1)订单取消（从价格档位双向链表摘除）
 //!
 //! # 示例
 //!
@@ -19,23 +19,80 @@
 //! use lib::orderbook::*;
 //!
 //! let mut book = OrderBook::new();
+//! let price = Price::new(10000).unwrap();
 //!
 //! // 放置卖单
 //! let seller = TraderId::from_str("SELLER1");
-//! book.limit_order(seller, Side::Sell, 10000, 100);
+//! book.limit_order(seller, Side::Sell, price, Quantity::new(100).unwrap(), TimeInForce::Gtc).unwrap();
 //!
 //! // 放置匹配的买单
 //! let buyer = TraderId::from_str("BUYER1");
-//! let (order_id, trades) = book.limit_order(buyer, Side::Buy, 10000, 50);
+//! let quantity = Quantity::new(50).unwrap();
+//! let (order_id, trades) = book.limit_order(buyer, Side::Buy, price, quantity, TimeInForce::Gtc).unwrap();
 //!
 //! assert_eq!(trades.len(), 1);
-//! assert_eq!(trades[0].quantity, 50);
+//! assert_eq!(trades[0].quantity, quantity);
 //! ```
 
+pub mod algo;    // 执行算法容器
+pub mod analytics;  // 订单簿分析指标
 pub mod arena;   // 内存池分配器
+pub mod audit;   // 成交审计日志（MPT）
+pub mod bars;    // 最新价与K线聚合
+pub mod breaker; // 波动熔断与集合竞价
+pub mod command; // 订单指令
+pub mod config;  // 撮合引擎配置
+pub mod delta;   // L2增量深度
 pub mod engine;  // 订单匹配引擎
+pub mod fix;     // FIX 4.4下单编解码
+pub mod gateway; // 订单网关
+pub mod heatmap; // 深度热力图导出
+pub mod instrument;  // 品种规格
+pub mod journal; // 指令日志与重放
+pub mod latency; // 时延直方图
+pub mod ladder;  // 价格阶梯后端
+pub mod manager; // 多品种订单簿管理
+pub mod order_map;  // 订单索引哈希表
+pub mod price_converter;  // 价格转换工具
+pub mod reconcile;  // 组播抓包与日志核对
+pub mod seqlock;  // 无锁快照读取
+pub mod shard;   // 分片多线程撮合
+pub mod shadow;  // 影子对比模式
+pub mod stop;    // 止损触发簿
+pub mod timetravel;  // 时间回溯订单簿
 pub mod types;   // 数据类型定义
+pub mod wal;     // 分段日志校验与压缩
 
 // 重新导出常用类型
-pub use engine::{OrderBook, OrderBookSnapshot};
-pub use types::{OrderEntry, OrderId, Price, Quantity, Side, Trade, TraderId};
+pub use algo::{AlgoEngine, AlgoId, AlgoKind, AlgoProgress, AlgoState, ParentOrder};
+pub use analytics::{BookAnalytics, TradeVwap};
+pub use audit::{DailyRoot, TradeAuditLog, TradeProof};
+pub use bars::{Bar, BarAggregator, BarSeries};
+pub use breaker::{CircuitBreakerConfig, PriceBand, Uncross, VolatilityGuard};
+pub use command::{Command, CommandResult, OrderBookError, RejectReason};
+pub use config::{ConfigError, EngineConfig, SelfTradePrevention};
+pub use delta::{DeltaAction, DeltaBatch, DeltaConflator, DepthDelta, DepthDeltaGenerator, DepthSnapshot};
+pub use engine::{AllocationStats, BboListener, BookEventListener, EngineCounters, LevelIter, OrderBook, OrderBookSnapshot, ResourcePolicy, TradeSink};
+pub use fix::{FixError, FixMessage, FixRequest, FixSession};
+pub use gateway::{
+    GatewayConfig, GatewayStats, LateAction, OrderGateway, SessionThrottle, ThrottleConfig, ThrottleNotice, ThrottleReason,
+    TimedCommand,
+};
+pub use heatmap::{DepthHeatmap, HeatmapConfig};
+pub use instrument::{AllocationPolicy, InstrumentSpec, TickRounding};
+pub use journal::{CommandJournal, JournalEntry, JournalReader, JournaledBook};
+pub use ladder::{DenseLadder, LadderKind, PriceLadder, SparseLadder};
+#[cfg(feature = "latency-histogram")]
+pub use latency::LatencyRecorder;
+pub use latency::{LatencyPercentiles, LatencySummary};
+pub use manager::{ManagerStats, OrderBookManager, SymbolBookStats};
+pub use order_map::OrderIndexMap;
+pub use price_converter::PriceConverter;
+pub use reconcile::{reconcile, wal_trades, Discrepancy, ReconcileReport, WalTrade};
+pub use seqlock::{snapshot_channel, SharedSnapshot, SnapshotReader, SnapshotWriter, TopOfBook};
+pub use shard::{ShardConfig, ShardError, ShardResult, ShardedEngine};
+pub use shadow::{Divergence, DivergenceKind, ShadowBook, ShadowConfig, ShadowEngine, ShadowStats};
+pub use stop::{StopBook, StopOrder};
+pub use timetravel::TimeTravelBook;
+pub use types::{BookDepth, BookEvent, DepthLevel, FillEstimate, OrderEntry, OrderId, Price, Quantity, Side, TimeInForce, Trade, TradeId, TraderId, TraderStats, TradingMode};
+pub use wal::{CompactReport, RecoveryReport, WalError, WalSummary, WalWriter};
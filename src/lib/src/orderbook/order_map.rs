@@ -0,0 +1,256 @@
+/// 订单索引专用哈希表
+///
+/// 以u64订单ID为键的开放寻址哈希表（线性探测 + 后移删除），
+/// 使用Fibonacci乘法哈希代替SipHash，插入/取消路径无需逐个比较墓碑。
+
+use super::types::OrderId;
+
+/// 空槽标记（该键值保留，不能作为订单ID）
+const EMPTY: OrderId = OrderId::MAX;
+
+/// 黄金分割乘数（Fibonacci哈希）
+const FIB_MULTIPLIER: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// 最小容量
+const MIN_CAPACITY: usize = 16;
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    key: OrderId,
+    value: usize,
+}
+
+const EMPTY_SLOT: Slot = Slot { key: EMPTY, value: 0 };
+
+/// 订单ID到内存池索引的开放寻址映射
+#[derive(Debug, Clone)]
+pub struct OrderIndexMap {
+    slots: Vec<Slot>,
+    len: usize,
+    mask: usize,
+    shift: u32,
+}
+
+impl OrderIndexMap {
+    /// 创建空映射
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// 创建可容纳`capacity`个条目而不扩容的映射
+    pub fn with_capacity(capacity: usize) -> Self {
+        // 负载因子不超过1/2
+        let slots = (capacity.saturating_mul(2)).max(MIN_CAPACITY).next_power_of_two();
+        Self {
+            slots: vec![EMPTY_SLOT; slots],
+            len: 0,
+            mask: slots - 1,
+            shift: 64 - slots.trailing_zeros(),
+        }
+    }
+
+    /// 条目数量
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 是否为空
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 不扩容情况下可容纳的条目数
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.len() / 2
+    }
+
+    /// 确保可容纳`capacity`个条目而不扩容
+    pub fn reserve(&mut self, capacity: usize) {
+        if capacity > self.capacity() {
+            self.rehash(capacity);
+        }
+    }
+
+    /// 占用的存储字节数
+    #[inline]
+    pub fn allocated_bytes(&self) -> usize {
+        self.slots.len() * std::mem::size_of::<Slot>()
+    }
+
+    /// 键的初始槽位
+    #[inline]
+    fn home(&self, key: OrderId) -> usize {
+        (key.wrapping_mul(FIB_MULTIPLIER) >> self.shift) as usize
+    }
+
+    /// 查找键所在槽位
+    #[inline]
+    fn find(&self, key: OrderId) -> Option<usize> {
+        if key == EMPTY {
+            return None;
+        }
+        let mut i = self.home(key);
+        loop {
+            let slot = &self.slots[i];
+            if slot.key == key {
+                return Some(i);
+            }
+            if slot.key == EMPTY {
+                return None;
+            }
+            i = (i + 1) & self.mask;
+        }
+    }
+
+    /// 获取键对应的值
+    #[inline]
+    pub fn get(&self, key: &OrderId) -> Option<&usize> {
+        self.find(*key).map(|i| &self.slots[i].value)
+    }
+
+    /// 是否包含键
+    #[inline]
+    pub fn contains_key(&self, key: &OrderId) -> bool {
+        self.find(*key).is_some()
+    }
+
+    /// 插入键值，返回旧值
+    ///
+    /// # Panics
+    /// 键为`OrderId::MAX`（保留值）时panic
+    pub fn insert(&mut self, key: OrderId, value: usize) -> Option<usize> {
+        assert!(key != EMPTY, "OrderId::MAX is reserved");
+
+        if (self.len + 1) * 2 > self.slots.len() {
+            self.grow();
+        }
+
+        let mut i = self.home(key);
+        loop {
+            let slot = &mut self.slots[i];
+            if slot.key == key {
+                return Some(std::mem::replace(&mut slot.value, value));
+            }
+            if slot.key == EMPTY {
+                *slot = Slot { key, value };
+                self.len += 1;
+                return None;
+            }
+            i = (i + 1) & self.mask;
+        }
+    }
+
+    /// 删除键，返回其值
+    ///
+    /// 使用后移删除，删除后探测链保持连续，不产生墓碑
+    pub fn remove(&mut self, key: &OrderId) -> Option<usize> {
+        let mut hole = self.find(*key)?;
+        let value = self.slots[hole].value;
+
+        let mut j = hole;
+        loop {
+            j = (j + 1) & self.mask;
+            let slot = self.slots[j];
+            if slot.key == EMPTY {
+                break;
+            }
+
+            // 若slot的初始槽位不在(hole, j]区间内，则可以前移填补空洞
+            let home = self.home(slot.key);
+            let stays = if hole <= j {
+                hole < home && home <= j
+            } else {
+                hole < home || home <= j
+            };
+            if !stays {
+                self.slots[hole] = slot;
+                hole = j;
+            }
+        }
+
+        self.slots[hole] = EMPTY_SLOT;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// 清空映射（保留容量）
+    pub fn clear(&mut self) {
+        self.slots.fill(EMPTY_SLOT);
+        self.len = 0;
+    }
+
+    /// 遍历所有条目（无序）
+    pub fn iter(&self) -> impl Iterator<Item = (OrderId, usize)> + '_ {
+        self.slots
+            .iter()
+            .filter(|s| s.key != EMPTY)
+            .map(|s| (s.key, s.value))
+    }
+
+    /// 容量翻倍并重新插入
+    fn grow(&mut self) {
+        self.rehash(self.slots.len());
+    }
+
+    /// 按新容量重建并重新插入
+    fn rehash(&mut self, capacity: usize) {
+        let old = std::mem::replace(self, Self::with_capacity(capacity));
+        for slot in old.slots.into_iter().filter(|s| s.key != EMPTY) {
+            self.insert(slot.key, slot.value);
+        }
+    }
+}
+
+impl Default for OrderIndexMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = OrderIndexMap::with_capacity(4);
+        assert_eq!(map.insert(1, 10), None);
+        assert_eq!(map.insert(2, 20), None);
+        assert_eq!(map.insert(1, 11), Some(10));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&1), Some(&11));
+        assert_eq!(map.remove(&1), Some(11));
+        assert_eq!(map.remove(&1), None);
+        assert_eq!(map.get(&2), Some(&20));
+        assert_eq!(map.get(&OrderId::MAX), None);
+    }
+
+    #[test]
+    fn test_matches_hashmap_under_churn() {
+        let mut map = OrderIndexMap::new();
+        let mut reference = HashMap::new();
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+
+        for i in 0..20_000u64 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let key = state % 2_000;
+            if i % 3 == 0 {
+                assert_eq!(map.remove(&key), reference.remove(&key));
+            } else {
+                assert_eq!(map.insert(key, i as usize), reference.insert(key, i as usize));
+            }
+        }
+
+        assert_eq!(map.len(), reference.len());
+        for (key, value) in &reference {
+            assert_eq!(map.get(key), Some(value));
+        }
+        assert_eq!(map.iter().count(), reference.len());
+    }
+}
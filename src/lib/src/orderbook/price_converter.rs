@@ -0,0 +1,141 @@
+/// 价格转换工具
+///
+/// 在引擎整数价格（tick）与展示用小数之间转换，
+/// 供行情编码、REST/WS接口和展示模块统一使用。
+
+use super::types::Price;
+
+/// 整数tick与小数价格之间的转换器
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceConverter {
+    /// 每个tick代表的报价货币金额（例如0.01）
+    tick_size: f64,
+    /// 报价精度（展示的小数位数）
+    precision: u32,
+}
+
+impl PriceConverter {
+    /// 创建新的价格转换器
+    ///
+    /// # Panics
+    /// tick_size必须为正数
+    pub fn new(tick_size: f64, precision: u32) -> Self {
+        assert!(tick_size > 0.0, "tick size must be positive");
+        Self { tick_size, precision }
+    }
+
+    /// 以分为单位的转换器（tick = 0.01，两位小数）
+    pub fn cents() -> Self {
+        Self::new(0.01, 2)
+    }
+
+    /// 获取tick大小
+    #[inline]
+    pub fn tick_size(&self) -> f64 {
+        self.tick_size
+    }
+
+    /// 获取报价精度
+    #[inline]
+    pub fn precision(&self) -> u32 {
+        self.precision
+    }
+
+    /// 整数价格转换为小数
+    #[inline]
+    pub fn to_decimal(&self, price: Price) -> f64 {
+        self.round(price.get() as f64 * self.tick_size)
+    }
+
+    /// 小数转换为整数价格（四舍五入到最近的tick）
+    ///
+    /// 负数、非有限值、舍入为0或超出Price范围时返回None
+    #[inline]
+    pub fn from_decimal(&self, value: f64) -> Option<Price> {
+        if !value.is_finite() || value < 0.0 {
+            return None;
+        }
+        let ticks = (value / self.tick_size).round();
+        if ticks > Price::MAX.get() as f64 {
+            return None;
+        }
+        Price::new(ticks as u64)
+    }
+
+    /// 检查小数价格是否恰好落在tick上
+    pub fn is_on_tick(&self, value: f64) -> bool {
+        match self.from_decimal(value) {
+            Some(price) => (self.to_decimal(price) - value).abs() < self.tick_size * 1e-6,
+            None => false,
+        }
+    }
+
+    /// 格式化为展示字符串（按报价精度）
+    pub fn format(&self, price: Price) -> String {
+        format!("{:.*}", self.precision as usize, self.to_decimal(price))
+    }
+
+    /// 按报价精度格式化tick数（例如价差，可以为0）
+    pub fn format_ticks(&self, ticks: u64) -> String {
+        format!("{:.*}", self.precision as usize, self.round(ticks as f64 * self.tick_size))
+    }
+
+    /// 格式化非整数tick数（例如成交均价），比报价精度多保留4位小数
+    pub fn format_average(&self, ticks: f64) -> String {
+        format!("{:.*}", self.precision as usize + 4, ticks * self.tick_size)
+    }
+
+    /// 解析展示字符串为整数价格
+    pub fn parse(&self, s: &str) -> Option<Price> {
+        s.trim().parse::<f64>().ok().and_then(|v| self.from_decimal(v))
+    }
+
+    /// 按报价精度四舍五入
+    #[inline]
+    fn round(&self, value: f64) -> f64 {
+        let scale = 10f64.powi(self.precision as i32);
+        (value * scale).round() / scale
+    }
+}
+
+impl Default for PriceConverter {
+    fn default() -> Self {
+        Self::cents()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::px;
+
+    #[test]
+    fn test_cents_roundtrip() {
+        let conv = PriceConverter::cents();
+        assert_eq!(conv.to_decimal(px(10050)), 100.5);
+        assert_eq!(conv.from_decimal(100.5), Some(px(10050)));
+        assert_eq!(conv.format(px(10050)), "100.50");
+        assert_eq!(conv.format_ticks(0), "0.00");
+        assert_eq!(conv.format_average(10050.5), "100.505000");
+        assert_eq!(conv.parse("100.50"), Some(px(10050)));
+    }
+
+    #[test]
+    fn test_tick_size() {
+        let conv = PriceConverter::new(0.5, 1);
+        assert_eq!(conv.to_decimal(px(3)), 1.5);
+        assert_eq!(conv.from_decimal(1.6), Some(px(3)));
+        assert!(conv.is_on_tick(1.5));
+        assert!(!conv.is_on_tick(1.6));
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let conv = PriceConverter::cents();
+        assert_eq!(conv.from_decimal(-1.0), None);
+        assert_eq!(conv.from_decimal(f64::NAN), None);
+        assert_eq!(conv.from_decimal(0.001), None);
+        assert_eq!(conv.from_decimal(1e20), None);
+        assert_eq!(conv.parse("abc"), None);
+    }
+}
@@ -0,0 +1,385 @@
+/// 组播抓包与指令日志核对
+///
+/// 从空订单簿重放同一交易时段的指令日志（WAL）得到权威成交序列，
+/// 再与组播抓包中的成交消息逐笔比对，报告:
+/// - 日志中有但行情未发布的成交（缺失）
+/// - 同一成交被发布多次（重复）
+/// - 内容与日志不一致的成交（不一致，时间戳除外）
+/// - 日志中不存在的成交（多余）
+/// - 行情序列号缺口与无法解码的消息
+///
+/// 每条差异都带有日志序列号和/或组播序列号，便于定位补发范围。
+/// 成交消息负载格式见`audit::encode_trade`，可用`trade_message`构造。
+
+use super::audit::{decode_trade, encode_trade};
+use super::engine::{OrderBook, TradeSink};
+use super::journal::{invalid, JournalEntry};
+use super::types::{Trade, TradeId};
+use crate::multicase::domain::multicast::{MessageType, MulticastMessage};
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+/// 构造成交行情消息
+pub fn trade_message(sequence: u64, timestamp_ns: u64, trade: &Trade) -> MulticastMessage {
+    MulticastMessage {
+        sequence,
+        timestamp_ns,
+        msg_type: MessageType::Trade,
+        payload: encode_trade(trade),
+    }
+}
+
+/// 日志重放产生的成交
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalTrade {
+    /// 产生该成交的指令序列号
+    pub journal_sequence: u64,
+    pub trade: Trade,
+}
+
+struct Collector(Arc<Mutex<Vec<Trade>>>);
+
+impl TradeSink for Collector {
+    fn on_trade(&mut self, trade: &Trade) {
+        self.0.lock().push(*trade);
+    }
+}
+
+/// 从空订单簿重放日志，返回全部成交（含止损触发产生的成交）
+///
+/// 日志读取错误或序列号不连续时返回错误。
+pub fn wal_trades<I>(entries: I) -> io::Result<Vec<WalTrade>>
+where
+    I: IntoIterator<Item = io::Result<JournalEntry>>,
+{
+    let collected = Arc::new(Mutex::new(Vec::new()));
+    let mut book = OrderBook::new();
+    book.set_trade_sink(Box::new(Collector(Arc::clone(&collected))));
+
+    let mut trades = Vec::new();
+    let mut last: Option<u64> = None;
+    for entry in entries {
+        let entry = entry?;
+        if let Some(last) = last
+            && entry.sequence != last + 1
+        {
+            return Err(invalid(format!(
+                "journal gap: expected sequence {}, found {}",
+                last + 1,
+                entry.sequence
+            )));
+        }
+        entry.command.execute(&mut book);
+        last = Some(entry.sequence);
+
+        trades.extend(collected.lock().drain(..).map(|trade| WalTrade {
+            journal_sequence: entry.sequence,
+            trade,
+        }));
+    }
+    Ok(trades)
+}
+
+/// 单条差异
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// 日志中的成交未出现在行情中
+    Missing { trade_id: TradeId, journal_sequence: u64 },
+    /// 同一成交被多次发布
+    Duplicated { trade_id: TradeId, feed_sequences: Vec<u64> },
+    /// 行情中的成交内容与日志不一致
+    Mismatched {
+        trade_id: TradeId,
+        journal_sequence: u64,
+        feed_sequence: u64,
+        expected: Trade,
+        captured: Trade,
+    },
+    /// 行情中的成交在日志中不存在
+    Unexpected { trade_id: TradeId, feed_sequence: u64 },
+    /// 成交消息无法解码
+    Undecodable { feed_sequence: u64 },
+    /// 行情序列号缺口 [from, to]
+    FeedGap { from: u64, to: u64 },
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::Missing { trade_id, journal_sequence } => {
+                write!(f, "MISSING trade #{} (journal seq {})", trade_id, journal_sequence)
+            }
+            Discrepancy::Duplicated { trade_id, feed_sequences } => {
+                write!(f, "DUPLICATED trade #{} (feed seqs {:?})", trade_id, feed_sequences)
+            }
+            Discrepancy::Mismatched { trade_id, journal_sequence, feed_sequence, expected, captured } => write!(
+                f,
+                "MISMATCHED trade #{} (journal seq {}, feed seq {}): expected [{}], captured [{}]",
+                trade_id, journal_sequence, feed_sequence, expected, captured
+            ),
+            Discrepancy::Unexpected { trade_id, feed_sequence } => {
+                write!(f, "UNEXPECTED trade #{} (feed seq {})", trade_id, feed_sequence)
+            }
+            Discrepancy::Undecodable { feed_sequence } => {
+                write!(f, "UNDECODABLE trade message (feed seq {})", feed_sequence)
+            }
+            Discrepancy::FeedGap { from, to } => write!(f, "FEED GAP seqs {}..={}", from, to),
+        }
+    }
+}
+
+/// 核对报告
+#[derive(Debug, Clone, Default)]
+pub struct ReconcileReport {
+    /// 日志成交笔数
+    pub wal_trades: usize,
+    /// 抓包中的成交消息数
+    pub feed_trades: usize,
+    /// 完全一致的成交笔数
+    pub matched: usize,
+    /// 差异（缺口、解码失败按行情顺序在前，其余按成交ID排序）
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl ReconcileReport {
+    /// 是否完全一致
+    pub fn is_clean(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+impl fmt::Display for ReconcileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "wal trades: {}, feed trades: {}, matched: {}, discrepancies: {}",
+            self.wal_trades,
+            self.feed_trades,
+            self.matched,
+            self.discrepancies.len()
+        )?;
+        for discrepancy in &self.discrepancies {
+            writeln!(f, "  {}", discrepancy)?;
+        }
+        Ok(())
+    }
+}
+
+/// 核对日志成交与抓包消息
+///
+/// 抓包中的非成交消息只参与序列号缺口检测。
+pub fn reconcile<I>(wal: &[WalTrade], capture: I) -> ReconcileReport
+where
+    I: IntoIterator<Item = MulticastMessage>,
+{
+    let mut report = ReconcileReport {
+        wal_trades: wal.len(),
+        ..Default::default()
+    };
+
+    // 成交ID -> 出现的 (行情序列号, 成交)
+    let mut captured: BTreeMap<TradeId, Vec<(u64, Trade)>> = BTreeMap::new();
+    let mut last_sequence: Option<u64> = None;
+    for message in capture {
+        if let Some(last) = last_sequence
+            && message.sequence > last + 1
+        {
+            report.discrepancies.push(Discrepancy::FeedGap {
+                from: last + 1,
+                to: message.sequence - 1,
+            });
+        }
+        last_sequence = Some(last_sequence.map_or(message.sequence, |last| last.max(message.sequence)));
+
+        if message.msg_type != MessageType::Trade {
+            continue;
+        }
+        report.feed_trades += 1;
+        match decode_trade(&message.payload) {
+            Ok(trade) => captured.entry(trade.trade_id).or_default().push((message.sequence, trade)),
+            Err(_) => report.discrepancies.push(Discrepancy::Undecodable {
+                feed_sequence: message.sequence,
+            }),
+        }
+    }
+
+    let expected: HashMap<TradeId, &WalTrade> = wal.iter().map(|w| (w.trade.trade_id, w)).collect();
+    let mut by_trade = Vec::new();
+
+    for w in wal {
+        if !captured.contains_key(&w.trade.trade_id) {
+            by_trade.push((
+                w.trade.trade_id,
+                Discrepancy::Missing {
+                    trade_id: w.trade.trade_id,
+                    journal_sequence: w.journal_sequence,
+                },
+            ));
+        }
+    }
+
+    for (&trade_id, copies) in &captured {
+        let Some(w) = expected.get(&trade_id) else {
+            by_trade.extend(copies.iter().map(|&(feed_sequence, _)| {
+                (trade_id, Discrepancy::Unexpected { trade_id, feed_sequence })
+            }));
+            continue;
+        };
+
+        if copies.len() > 1 {
+            by_trade.push((
+                trade_id,
+                Discrepancy::Duplicated {
+                    trade_id,
+                    feed_sequences: copies.iter().map(|&(sequence, _)| sequence).collect(),
+                },
+            ));
+        }
+
+        let mut consistent = true;
+        for &(feed_sequence, trade) in copies {
+            if !trade.same_execution(&w.trade) {
+                consistent = false;
+                by_trade.push((
+                    trade_id,
+                    Discrepancy::Mismatched {
+                        trade_id,
+                        journal_sequence: w.journal_sequence,
+                        feed_sequence,
+                        expected: w.trade,
+                        captured: trade,
+                    },
+                ));
+            }
+        }
+        if consistent && copies.len() == 1 {
+            report.matched += 1;
+        }
+    }
+
+    by_trade.sort_by_key(|&(trade_id, _)| trade_id);
+    report.discrepancies.extend(by_trade.into_iter().map(|(_, d)| d));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::command::Command;
+    use crate::orderbook::journal::CommandJournal;
+    use crate::orderbook::types::{px, qty, Side, TimeInForce, TraderId};
+
+    fn limit(trader: &str, side: Side, price: u32, quantity: u32) -> Command {
+        Command::Limit {
+            trader: TraderId::from_str(trader),
+            side,
+            price: px(price),
+            quantity: qty(quantity),
+            tif: TimeInForce::Gtc,
+        }
+    }
+
+    /// 写日志并返回 (日志字节, 实时撮合产生的成交)
+    fn session() -> (Vec<u8>, Vec<Trade>) {
+        let commands = [
+            limit("S1", Side::Sell, 100, 5),
+            limit("S2", Side::Sell, 101, 5),
+            limit("B1", Side::Buy, 101, 7),
+            limit("S3", Side::Sell, 99, 4),
+            limit("B2", Side::Buy, 102, 10),
+        ];
+        let mut journal = CommandJournal::new(Vec::new());
+        let mut book = OrderBook::new();
+        let mut trades = Vec::new();
+        for command in commands {
+            journal.append(&command).unwrap();
+            if let crate::orderbook::CommandResult::Accepted { trades: fills, .. } = command.execute(&mut book) {
+                trades.extend(fills);
+            }
+        }
+        (journal.into_inner(), trades)
+    }
+
+    fn wal(data: &[u8]) -> Vec<WalTrade> {
+        wal_trades(crate::orderbook::JournalReader::new(data)).unwrap()
+    }
+
+    #[test]
+    fn test_clean_session() {
+        let (journal, trades) = session();
+        let wal = wal(&journal);
+        assert_eq!(wal.len(), trades.len());
+        assert_eq!(wal[0].journal_sequence, 3);
+
+        let feed: Vec<_> = trades
+            .iter()
+            .enumerate()
+            .map(|(i, trade)| trade_message(i as u64 + 1, 0, trade))
+            .collect();
+        let report = reconcile(&wal, feed);
+        assert!(report.is_clean(), "{}", report);
+        assert_eq!(report.matched, trades.len());
+    }
+
+    #[test]
+    fn test_reports_each_kind() {
+        let (journal, trades) = session();
+        let wal = wal(&journal);
+        assert!(trades.len() >= 3);
+
+        let mut tampered = trades[1];
+        tampered.quantity = qty(1);
+        let mut phantom = trades[0];
+        phantom.trade_id = 999;
+
+        let mut undecodable = trade_message(6, 0, &trades[0]);
+        undecodable.payload.truncate(3);
+
+        let feed = vec![
+            trade_message(1, 0, &trades[0]),
+            trade_message(2, 0, &trades[0]),
+            trade_message(3, 0, &tampered),
+            // 4 丢失
+            trade_message(5, 0, &phantom),
+            undecodable,
+        ];
+        let report = reconcile(&wal, feed);
+
+        assert_eq!(report.feed_trades, 5);
+        assert_eq!(report.matched, 0);
+        assert_eq!(
+            report.discrepancies[..2],
+            [Discrepancy::FeedGap { from: 4, to: 4 }, Discrepancy::Undecodable { feed_sequence: 6 }]
+        );
+        assert!(report.discrepancies.contains(&Discrepancy::Duplicated {
+            trade_id: trades[0].trade_id,
+            feed_sequences: vec![1, 2],
+        }));
+        assert!(report.discrepancies.iter().any(|d| matches!(
+            d,
+            Discrepancy::Mismatched { trade_id, feed_sequence: 3, .. } if *trade_id == trades[1].trade_id
+        )));
+        for trade in &trades[2..] {
+            assert!(report.discrepancies.iter().any(|d| matches!(
+                d,
+                Discrepancy::Missing { trade_id, .. } if *trade_id == trade.trade_id
+            )));
+        }
+        assert!(report.discrepancies.contains(&Discrepancy::Unexpected {
+            trade_id: 999,
+            feed_sequence: 5,
+        }));
+        assert!(report.to_string().contains("MISSING"));
+    }
+
+    #[test]
+    fn test_wal_gap_is_error() {
+        let (journal, _) = session();
+        let mut entries: Vec<_> = crate::orderbook::JournalReader::new(journal.as_slice()).collect();
+        entries.remove(1).unwrap();
+        assert_eq!(wal_trades(entries).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
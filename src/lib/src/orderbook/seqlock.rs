@@ -0,0 +1,278 @@
+/// 无锁并发快照读取（seqlock）
+///
+/// 撮合线程是订单簿唯一的写者；行情线程只需要最优价和前N档深度，
+/// 不应为此锁住整个`OrderBook`。写者在每条指令后把前N档写入一块定长的原子字数组，
+/// 读者按序列号校验读取结果:
+/// - 写入前序列号置为奇数，写完置为下一个偶数
+/// - 读者读到奇数或前后序列号不一致时重试
+///
+/// 全部字段都是原子变量，读者与写者并发时不存在数据竞争；写者从不等待读者。
+///
+/// 字布局（每个u64）:
+/// - 0: 最新成交价（0表示无）
+/// - 1: 买方档数(高32位) | 卖方档数(低32位)
+/// - 之后买方、卖方各`levels`档，每档两个字: 价格, 数量(高32位) | 订单数(低32位)
+
+use super::engine::OrderBook;
+use super::types::{BookDepth, DepthLevel, Price, Quantity};
+use std::hint;
+use std::sync::Arc;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+
+/// 头部字数
+const HEADER_WORDS: usize = 2;
+/// 每档字数
+const LEVEL_WORDS: usize = 2;
+
+/// 最优价快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TopOfBook {
+    pub best_bid: Option<DepthLevel>,
+    pub best_ask: Option<DepthLevel>,
+    pub last_trade_price: Option<Price>,
+}
+
+/// 一次一致读取的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SharedSnapshot {
+    /// 已发布次数（每次`publish`加1）
+    pub version: u64,
+    pub last_trade_price: Option<Price>,
+    pub depth: BookDepth,
+}
+
+impl SharedSnapshot {
+    /// 快照深度的校验和（见`BookDepth::checksum`）
+    pub fn checksum(&self) -> u32 {
+        self.depth.checksum(self.depth.bids.len().max(self.depth.asks.len()))
+    }
+}
+
+struct Slots {
+    sequence: AtomicU64,
+    levels: usize,
+    words: Box<[AtomicU64]>,
+}
+
+impl Slots {
+    #[inline]
+    fn level_word(&self, side: usize, index: usize) -> usize {
+        HEADER_WORDS + (side * self.levels + index) * LEVEL_WORDS
+    }
+
+    /// 按seqlock协议读取；`read`只能读取原子字
+    fn read<T>(&self, mut read: impl FnMut(&Self) -> T) -> (u64, T) {
+        loop {
+            let before = self.sequence.load(Ordering::Acquire);
+            if before & 1 == 1 {
+                hint::spin_loop();
+                continue;
+            }
+            let value = read(self);
+            fence(Ordering::Acquire);
+            if self.sequence.load(Ordering::Relaxed) == before {
+                return (before / 2, value);
+            }
+        }
+    }
+
+    fn load_level(&self, side: usize, index: usize) -> Option<DepthLevel> {
+        let word = self.level_word(side, index);
+        let price = self.words[word].load(Ordering::Relaxed);
+        let packed = self.words[word + 1].load(Ordering::Relaxed);
+        Some(DepthLevel {
+            price: Price::new(price)?,
+            quantity: Quantity::new((packed >> 32) as u32)?,
+            order_count: packed as u32,
+        })
+    }
+
+    fn counts(&self) -> (usize, usize) {
+        let counts = self.words[1].load(Ordering::Relaxed);
+        (
+            ((counts >> 32) as usize).min(self.levels),
+            ((counts as u32) as usize).min(self.levels),
+        )
+    }
+}
+
+/// 创建快照通道，跟踪前`levels`档
+pub fn snapshot_channel(levels: usize) -> (SnapshotWriter, SnapshotReader) {
+    let words = (0..HEADER_WORDS + 2 * levels * LEVEL_WORDS).map(|_| AtomicU64::new(0)).collect();
+    let slots = Arc::new(Slots {
+        sequence: AtomicU64::new(0),
+        levels,
+        words,
+    });
+    (
+        SnapshotWriter {
+            slots: Arc::clone(&slots),
+        },
+        SnapshotReader { slots },
+    )
+}
+
+/// 写端（唯一，不可克隆，由撮合线程持有）
+pub struct SnapshotWriter {
+    slots: Arc<Slots>,
+}
+
+impl SnapshotWriter {
+    /// 发布订单簿当前前N档
+    pub fn publish(&mut self, book: &OrderBook) {
+        let slots = &*self.slots;
+        let last = book.last_trade_price().map_or(0, Price::get);
+
+        let sequence = slots.sequence.load(Ordering::Relaxed);
+        slots.sequence.store(sequence + 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        slots.words[0].store(last, Ordering::Relaxed);
+        let mut counts = [0u64; 2];
+        for (side, levels) in [book.iter_bids(), book.iter_asks()].into_iter().enumerate() {
+            for (index, level) in levels.take(slots.levels).enumerate() {
+                let word = slots.level_word(side, index);
+                let packed = (level.quantity.get() as u64) << 32 | level.order_count as u64;
+                slots.words[word].store(level.price.get(), Ordering::Relaxed);
+                slots.words[word + 1].store(packed, Ordering::Relaxed);
+                counts[side] += 1;
+            }
+        }
+        slots.words[1].store(counts[0] << 32 | counts[1], Ordering::Relaxed);
+
+        slots.sequence.store(sequence + 2, Ordering::Release);
+    }
+
+    /// 新建一个读端
+    pub fn reader(&self) -> SnapshotReader {
+        SnapshotReader {
+            slots: Arc::clone(&self.slots),
+        }
+    }
+}
+
+/// 读端（可克隆，可跨线程）
+#[derive(Clone)]
+pub struct SnapshotReader {
+    slots: Arc<Slots>,
+}
+
+impl SnapshotReader {
+    /// 跟踪的档数
+    pub fn levels(&self) -> usize {
+        self.slots.levels
+    }
+
+    /// 已发布次数
+    pub fn version(&self) -> u64 {
+        self.slots.sequence.load(Ordering::Acquire) / 2
+    }
+
+    /// 读取最优价
+    pub fn top_of_book(&self) -> TopOfBook {
+        let (_, top) = self.slots.read(|slots| {
+            let (bids, asks) = slots.counts();
+            TopOfBook {
+                best_bid: if bids > 0 { slots.load_level(0, 0) } else { None },
+                best_ask: if asks > 0 { slots.load_level(1, 0) } else { None },
+                last_trade_price: Price::new(slots.words[0].load(Ordering::Relaxed)),
+            }
+        });
+        top
+    }
+
+    /// 读取完整快照
+    pub fn snapshot(&self) -> SharedSnapshot {
+        let (version, (last_trade_price, depth)) = self.slots.read(|slots| {
+            let (bids, asks) = slots.counts();
+            let depth = BookDepth {
+                bids: (0..bids).filter_map(|index| slots.load_level(0, index)).collect(),
+                asks: (0..asks).filter_map(|index| slots.load_level(1, index)).collect(),
+            };
+            (Price::new(slots.words[0].load(Ordering::Relaxed)), depth)
+        });
+        SharedSnapshot {
+            version,
+            last_trade_price,
+            depth,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty, Side, TimeInForce, TraderId};
+    use std::sync::atomic::AtomicBool;
+
+    #[test]
+    fn test_publish_and_read() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let (mut writer, reader) = snapshot_channel(2);
+        assert_eq!(reader.top_of_book(), TopOfBook::default());
+
+        let mm = TraderId::from_str("MM");
+        book.limit_order(mm, Side::Buy, px(99), qty(5), TimeInForce::Gtc).unwrap();
+        book.limit_order(mm, Side::Buy, px(98), qty(1), TimeInForce::Gtc).unwrap();
+        book.limit_order(mm, Side::Buy, px(97), qty(1), TimeInForce::Gtc).unwrap();
+        book.limit_order(mm, Side::Sell, px(101), qty(3), TimeInForce::Gtc).unwrap();
+        book.limit_order(TraderId::from_str("TK"), Side::Sell, px(99), qty(2), TimeInForce::Ioc).unwrap();
+        writer.publish(&book);
+
+        let snapshot = reader.snapshot();
+        assert_eq!(snapshot.version, 1);
+        assert_eq!(snapshot.last_trade_price, Some(px(99)));
+        assert_eq!(snapshot.depth, book.depth(2));
+
+        let top = writer.reader().top_of_book();
+        assert_eq!(top.best_bid.map(|l| (l.price, l.quantity)), Some((px(99), qty(3))));
+        assert_eq!(top.best_ask.map(|l| l.price), Some(px(101)));
+
+        // 档位减少时不残留旧数据
+        book.cancel_order(4).unwrap();
+        writer.publish(&book);
+        assert_eq!(reader.top_of_book().best_ask, None);
+        assert_eq!(reader.snapshot().depth.asks, vec![]);
+    }
+
+    #[test]
+    fn test_concurrent_reads_are_consistent() {
+        let (mut writer, reader) = snapshot_channel(4);
+        let done = Arc::new(AtomicBool::new(false));
+
+        let readers: Vec<_> = (0..2)
+            .map(|_| {
+                let reader = reader.clone();
+                let done = Arc::clone(&done);
+                std::thread::spawn(move || {
+                    let mut reads = 0;
+                    while !done.load(Ordering::Relaxed) {
+                        // 写者每次发布的各档数量都等于买一价，撕裂的读取会被发现
+                        let snapshot = reader.snapshot();
+                        if let Some(best) = snapshot.depth.bids.first() {
+                            assert!(snapshot.depth.bids.iter().all(|l| l.quantity.get() as u64 == best.price.get()));
+                        }
+                        reads += 1;
+                    }
+                    reads
+                })
+            })
+            .collect();
+
+        let mm = TraderId::from_str("MM");
+        for round in 1..=2_000u32 {
+            let mut book = OrderBook::with_capacity(4_000, 16);
+            let price = 100 + round % 1_000;
+            for offset in 0..4 {
+                book.limit_order(mm, Side::Buy, px(price - offset), qty(price), TimeInForce::Gtc).unwrap();
+            }
+            writer.publish(&book);
+        }
+        done.store(true, Ordering::Relaxed);
+
+        for handle in readers {
+            assert!(handle.join().unwrap() > 0);
+        }
+        assert_eq!(reader.version(), 2_000);
+    }
+}
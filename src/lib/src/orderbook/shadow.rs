@@ -0,0 +1,289 @@
+/// 影子对比模式
+///
+/// 主引擎与影子引擎接收同一条有序指令流，逐条比较执行结果，
+/// 并按配置间隔比较前N档深度校验和；记录并打印第一次分歧，
+/// 用于在引擎重构（如位图索引重写）上线前验证行为一致。
+/// 对外返回的始终是主引擎的结果。
+
+use super::command::{Command, CommandResult};
+use super::engine::OrderBook;
+use super::types::{BookDepth, DepthLevel};
+
+/// 可参与影子对比的撮合引擎
+pub trait ShadowEngine {
+    /// 执行一条指令
+    fn apply(&mut self, command: &Command) -> CommandResult;
+
+    /// 获取前N档聚合深度
+    fn depth(&self, n: usize) -> BookDepth;
+}
+
+impl ShadowEngine for OrderBook {
+    #[inline]
+    fn apply(&mut self, command: &Command) -> CommandResult {
+        command.execute(self)
+    }
+
+    #[inline]
+    fn depth(&self, n: usize) -> BookDepth {
+        OrderBook::depth(self, n)
+    }
+}
+
+/// 深度校验和（FNV-1a，覆盖价格、数量和订单数）
+pub fn depth_checksum(depth: &BookDepth) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = OFFSET;
+    let mut feed = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    };
+
+    let mut feed_side = |levels: &[DepthLevel]| {
+        feed(&(levels.len() as u32).to_le_bytes());
+        for level in levels {
+            feed(&level.price.get().to_le_bytes());
+            feed(&level.quantity.get().to_le_bytes());
+            feed(&level.order_count.to_le_bytes());
+        }
+    };
+    feed_side(&depth.bids);
+    feed_side(&depth.asks);
+
+    hash
+}
+
+/// 执行结果是否一致（忽略成交时间戳）
+fn same_result(primary: &CommandResult, shadow: &CommandResult) -> bool {
+    match (primary, shadow) {
+        (
+            CommandResult::Accepted { order_id: a, trades: a_trades },
+            CommandResult::Accepted { order_id: b, trades: b_trades },
+        ) => {
+            a == b
+                && a_trades.len() == b_trades.len()
+                && a_trades.iter().zip(b_trades).all(|(a, b)| a.same_execution(b))
+        }
+        _ => primary == shadow,
+    }
+}
+
+/// 影子对比配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShadowConfig {
+    /// 参与校验和的深度档数
+    pub checksum_depth: usize,
+    /// 每隔多少条指令比较一次校验和（0表示不比较）
+    pub checksum_interval: u64,
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            checksum_depth: 10,
+            checksum_interval: 1,
+        }
+    }
+}
+
+/// 分歧类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DivergenceKind {
+    /// 指令执行结果（订单ID、成交）不同
+    Result {
+        primary: CommandResult,
+        shadow: CommandResult,
+    },
+    /// 深度校验和不同
+    Checksum { primary: u64, shadow: u64 },
+    /// 最终状态（`export_state`）不同（确定性回放校验）
+    State,
+}
+
+/// 主引擎与影子引擎的分歧记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// 指令序号（从1开始）
+    pub sequence: u64,
+    /// 触发分歧的指令
+    pub command: Command,
+    pub kind: DivergenceKind,
+}
+
+/// 影子对比统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShadowStats {
+    /// 已处理的指令数
+    pub commands: u64,
+    /// 已比较的校验和次数
+    pub checksums_compared: u64,
+    /// 发现的分歧数
+    pub divergences: u64,
+}
+
+/// 影子对比执行器
+pub struct ShadowBook<P: ShadowEngine, S: ShadowEngine> {
+    primary: P,
+    shadow: S,
+    config: ShadowConfig,
+    stats: ShadowStats,
+    first_divergence: Option<Divergence>,
+}
+
+impl<P: ShadowEngine, S: ShadowEngine> ShadowBook<P, S> {
+    /// 创建影子对比执行器
+    pub fn new(primary: P, shadow: S, config: ShadowConfig) -> Self {
+        Self {
+            primary,
+            shadow,
+            config,
+            stats: ShadowStats::default(),
+            first_divergence: None,
+        }
+    }
+
+    /// 获取主引擎
+    #[inline]
+    pub fn primary(&self) -> &P {
+        &self.primary
+    }
+
+    /// 获取影子引擎
+    #[inline]
+    pub fn shadow(&self) -> &S {
+        &self.shadow
+    }
+
+    /// 在两个引擎上执行指令，返回主引擎结果
+    pub fn submit(&mut self, command: Command) -> CommandResult {
+        self.stats.commands += 1;
+        let sequence = self.stats.commands;
+
+        let primary = self.primary.apply(&command);
+        let shadow = self.shadow.apply(&command);
+        if !same_result(&primary, &shadow) {
+            self.diverged(sequence, command, DivergenceKind::Result {
+                primary: primary.clone(),
+                shadow,
+            });
+        }
+
+        let interval = self.config.checksum_interval;
+        if interval > 0 && sequence.is_multiple_of(interval) {
+            self.stats.checksums_compared += 1;
+            let primary = depth_checksum(&self.primary.depth(self.config.checksum_depth));
+            let shadow = depth_checksum(&self.shadow.depth(self.config.checksum_depth));
+            if primary != shadow {
+                self.diverged(sequence, command, DivergenceKind::Checksum { primary, shadow });
+            }
+        }
+
+        primary
+    }
+
+    /// 第一次分歧（若有）
+    #[inline]
+    pub fn first_divergence(&self) -> Option<&Divergence> {
+        self.first_divergence.as_ref()
+    }
+
+    /// 获取统计信息
+    #[inline]
+    pub fn stats(&self) -> ShadowStats {
+        self.stats
+    }
+
+    /// 拆分为主引擎和影子引擎
+    pub fn into_inner(self) -> (P, S) {
+        (self.primary, self.shadow)
+    }
+
+    fn diverged(&mut self, sequence: u64, command: Command, kind: DivergenceKind) {
+        self.stats.divergences += 1;
+        if self.first_divergence.is_none() {
+            crate::runtime_log!(
+                Warn,
+                "Shadow divergence at command #{} ({:?}): {:?}",
+                sequence, command, kind
+            );
+            self.first_divergence = Some(Divergence {
+                sequence,
+                command,
+                kind,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty, Side, TimeInForce, TraderId};
+
+    fn limit(side: Side, price: u32, quantity: u32) -> Command {
+        Command::Limit {
+            trader: TraderId::from_str("T"),
+            side,
+            price: px(price),
+            quantity: qty(quantity),
+            tif: TimeInForce::Gtc,
+        }
+    }
+
+    /// 忽略撤单的错误实现
+    struct DropsCancels(OrderBook);
+
+    impl ShadowEngine for DropsCancels {
+        fn apply(&mut self, command: &Command) -> CommandResult {
+            match *command {
+                Command::Cancel { order_id } => CommandResult::Cancelled { order_id, success: true },
+                _ => self.0.apply(command),
+            }
+        }
+
+        fn depth(&self, n: usize) -> BookDepth {
+            self.0.depth(n)
+        }
+    }
+
+    #[test]
+    fn test_identical_engines_never_diverge() {
+        let mut shadow = ShadowBook::new(
+            OrderBook::with_capacity(20_000, 100),
+            OrderBook::with_capacity(20_000, 100),
+            ShadowConfig::default(),
+        );
+
+        shadow.submit(limit(Side::Sell, 10010, 5));
+        shadow.submit(limit(Side::Buy, 10010, 3));
+        shadow.submit(Command::Cancel { order_id: 1 });
+
+        assert_eq!(shadow.first_divergence(), None);
+        assert_eq!(shadow.stats().commands, 3);
+        assert_eq!(shadow.stats().checksums_compared, 3);
+    }
+
+    #[test]
+    fn test_first_divergence_is_recorded() {
+        let mut shadow = ShadowBook::new(
+            OrderBook::with_capacity(20_000, 100),
+            DropsCancels(OrderBook::with_capacity(20_000, 100)),
+            ShadowConfig::default(),
+        );
+
+        shadow.submit(limit(Side::Sell, 10010, 5));
+        let result = shadow.submit(Command::Cancel { order_id: 1 });
+        assert_eq!(result, CommandResult::Cancelled { order_id: 1, success: true });
+        shadow.submit(limit(Side::Buy, 10000, 1));
+
+        let divergence = shadow.first_divergence().unwrap();
+        assert_eq!(divergence.sequence, 2);
+        assert!(matches!(divergence.kind, DivergenceKind::Checksum { .. }));
+        // 后续每次校验和比较都不一致
+        assert_eq!(shadow.stats().divergences, 2);
+    }
+}
@@ -0,0 +1,354 @@
+/// 分片多线程撮合引擎
+///
+/// 每个品种的订单簿固定在一个工作线程上，路由前端按品种把指令写入该品种的SPSC队列，
+/// 工作线程轮询自己名下各品种的队列并执行指令，多个品种并行撮合而无需锁住任何订单簿。
+///
+/// - 路由前端（`&mut self`）是每个品种队列唯一的生产者，所属工作线程是唯一的消费者
+/// - 执行结果带品种和指令序号写入共享的结果通道
+/// - 订单ID高位为品种序号，各品种独立分配仍保证全局唯一
+
+use super::command::{Command, CommandResult};
+use super::engine::OrderBook;
+use super::ladder::LadderKind;
+use crossbeam::channel::{self, Receiver, Sender};
+use crossbeam::queue::ArrayQueue;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use thiserror::Error;
+
+/// 订单ID中品种序号的位移
+const SYMBOL_ID_SHIFT: u32 = 40;
+/// 空转多少轮后让出CPU
+const IDLE_SPINS: u32 = 64;
+
+/// 分片引擎配置
+#[derive(Debug, Clone)]
+pub struct ShardConfig {
+    /// 工作线程数
+    pub shards: usize,
+    /// 每个品种的指令队列容量
+    pub queue_capacity: usize,
+    /// 新品种订单簿的价格上界
+    pub max_price: usize,
+    /// 新品种订单簿的订单容量
+    pub max_orders: usize,
+    pub ladder: LadderKind,
+}
+
+impl Default for ShardConfig {
+    fn default() -> Self {
+        Self {
+            shards: 4,
+            queue_capacity: 4_096,
+            max_price: 100_000,
+            max_orders: 100_000,
+            ladder: LadderKind::Dense,
+        }
+    }
+}
+
+/// 分片引擎错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ShardError {
+    #[error("unknown symbol {0}")]
+    UnknownSymbol(String),
+    #[error("symbol {0} already exists")]
+    DuplicateSymbol(String),
+    #[error("command queue for {0} is full")]
+    QueueFull(String),
+    #[error("shard worker stopped")]
+    WorkerStopped,
+}
+
+/// 一条指令的执行结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardResult {
+    pub symbol: Arc<str>,
+    /// `submit`返回的品种内指令序号
+    pub sequence: u64,
+    pub result: CommandResult,
+}
+
+/// 品种的队列端点（路由前端与工作线程共享）
+struct SymbolQueue {
+    symbol: Arc<str>,
+    queue: ArrayQueue<(u64, Command)>,
+}
+
+/// 工作线程持有的品种
+struct PinnedBook {
+    queue: Arc<SymbolQueue>,
+    book: OrderBook,
+}
+
+/// 路由前端持有的品种
+struct Route {
+    queue: Arc<SymbolQueue>,
+    shard: usize,
+    next_sequence: u64,
+}
+
+struct Worker {
+    /// 新品种注册通道
+    register: Sender<PinnedBook>,
+    handle: JoinHandle<Vec<(Arc<str>, OrderBook)>>,
+}
+
+/// 分片多线程撮合引擎
+pub struct ShardedEngine {
+    config: ShardConfig,
+    routes: HashMap<String, Route>,
+    workers: Vec<Worker>,
+    results: Receiver<ShardResult>,
+    /// 已提交但尚未取走结果的指令数
+    outstanding: usize,
+    running: Arc<AtomicBool>,
+}
+
+impl ShardedEngine {
+    /// 启动`config.shards`个工作线程
+    ///
+    /// # Panics
+    /// shards和queue_capacity必须为正数
+    pub fn new(config: ShardConfig) -> Self {
+        assert!(config.shards > 0, "shards must be positive");
+        assert!(config.queue_capacity > 0, "queue_capacity must be positive");
+
+        let running = Arc::new(AtomicBool::new(true));
+        let (result_tx, results) = channel::unbounded();
+        let workers = (0..config.shards)
+            .map(|index| {
+                let (register, pending) = channel::unbounded();
+                let running = Arc::clone(&running);
+                let result_tx = result_tx.clone();
+                let handle = thread::Builder::new()
+                    .name(format!("shard-{}", index))
+                    .spawn(move || run_worker(pending, result_tx, running))
+                    .expect("failed to spawn shard worker");
+                Worker { register, handle }
+            })
+            .collect();
+
+        Self {
+            config,
+            routes: HashMap::new(),
+            workers,
+            results,
+            outstanding: 0,
+            running,
+        }
+    }
+
+    /// 添加品种（使用配置中的容量和价格阶梯），按品种数轮流分配到工作线程
+    pub fn add_symbol(&mut self, symbol: &str) -> Result<usize, ShardError> {
+        let book = OrderBook::with_ladder(self.config.ladder, self.config.max_price, self.config.max_orders);
+        self.add_book(symbol, book)
+    }
+
+    /// 添加已构造好的订单簿，返回其所在的工作线程序号
+    ///
+    /// 订单ID从`品种序号 << 40`起分配，保证跨品种唯一。
+    pub fn add_book(&mut self, symbol: &str, mut book: OrderBook) -> Result<usize, ShardError> {
+        if self.routes.contains_key(symbol) {
+            return Err(ShardError::DuplicateSymbol(symbol.to_string()));
+        }
+        let symbol_index = self.routes.len() as u64 + 1;
+        let shard = self.routes.len() % self.workers.len();
+        book.set_next_order_id((symbol_index << SYMBOL_ID_SHIFT) + 1);
+
+        let queue = Arc::new(SymbolQueue {
+            symbol: Arc::from(symbol),
+            queue: ArrayQueue::new(self.config.queue_capacity),
+        });
+        self.workers[shard]
+            .register
+            .send(PinnedBook { queue: Arc::clone(&queue), book })
+            .map_err(|_| ShardError::WorkerStopped)?;
+        self.routes.insert(symbol.to_string(), Route { queue, shard, next_sequence: 0 });
+        Ok(shard)
+    }
+
+    /// 品种所在的工作线程序号
+    pub fn shard_of(&self, symbol: &str) -> Option<usize> {
+        self.routes.get(symbol).map(|route| route.shard)
+    }
+
+    /// 品种数量
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// 把指令写入品种的队列，返回品种内指令序号（从0开始）
+    ///
+    /// 队列已满时返回`QueueFull`，调用方可稍后重试。
+    pub fn submit(&mut self, symbol: &str, command: Command) -> Result<u64, ShardError> {
+        let route = self
+            .routes
+            .get_mut(symbol)
+            .ok_or_else(|| ShardError::UnknownSymbol(symbol.to_string()))?;
+        let sequence = route.next_sequence;
+        route
+            .queue
+            .queue
+            .push((sequence, command))
+            .map_err(|_| ShardError::QueueFull(symbol.to_string()))?;
+        route.next_sequence += 1;
+        self.outstanding += 1;
+        Ok(sequence)
+    }
+
+    /// 已提交但尚未取走结果的指令数
+    #[inline]
+    pub fn outstanding(&self) -> usize {
+        self.outstanding
+    }
+
+    /// 取一条已就绪的执行结果（同一品种的结果按序号顺序到达）
+    pub fn try_recv(&mut self) -> Option<ShardResult> {
+        let result = self.results.try_recv().ok()?;
+        self.outstanding -= 1;
+        Some(result)
+    }
+
+    /// 等待已提交的全部指令执行完毕，返回其结果（按到达顺序）
+    pub fn drain(&mut self) -> Result<Vec<ShardResult>, ShardError> {
+        let mut collected = Vec::with_capacity(self.outstanding);
+        while self.outstanding > 0 {
+            match self.results.recv_timeout(Duration::from_millis(10)) {
+                Ok(result) => {
+                    self.outstanding -= 1;
+                    collected.push(result);
+                }
+                Err(_) if self.workers.iter().any(|worker| worker.handle.is_finished()) => {
+                    return Err(ShardError::WorkerStopped);
+                }
+                Err(_) => {}
+            }
+        }
+        Ok(collected)
+    }
+
+    /// 停止全部工作线程，返回各品种的订单簿（队列中未执行的指令被丢弃）
+    pub fn shutdown(mut self) -> Result<HashMap<String, OrderBook>, ShardError> {
+        self.running.store(false, Ordering::Release);
+        let mut books = HashMap::with_capacity(self.routes.len());
+        for worker in std::mem::take(&mut self.workers) {
+            drop(worker.register);
+            let pinned = worker.handle.join().map_err(|_| ShardError::WorkerStopped)?;
+            books.extend(pinned.into_iter().map(|(symbol, book)| (symbol.to_string(), book)));
+        }
+        Ok(books)
+    }
+}
+
+impl Drop for ShardedEngine {
+    /// 未调用`shutdown`时通知工作线程退出（不等待）
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+    }
+}
+
+/// 工作线程主循环
+fn run_worker(
+    pending: Receiver<PinnedBook>,
+    results: Sender<ShardResult>,
+    running: Arc<AtomicBool>,
+) -> Vec<(Arc<str>, OrderBook)> {
+    let mut books: Vec<PinnedBook> = Vec::new();
+    let mut idle = 0u32;
+
+    while running.load(Ordering::Acquire) {
+        books.extend(pending.try_iter());
+
+        let mut executed = false;
+        for pinned in &mut books {
+            while let Some((sequence, command)) = pinned.queue.queue.pop() {
+                let result = command.execute(&mut pinned.book);
+                let _ = results.send(ShardResult {
+                    symbol: Arc::clone(&pinned.queue.symbol),
+                    sequence,
+                    result,
+                });
+                executed = true;
+            }
+        }
+
+        if executed {
+            idle = 0;
+        } else if idle < IDLE_SPINS {
+            idle += 1;
+            std::hint::spin_loop();
+        } else {
+            thread::sleep(Duration::from_micros(50));
+        }
+    }
+
+    books.extend(pending.try_iter());
+    books
+        .into_iter()
+        .map(|pinned| (Arc::clone(&pinned.queue.symbol), pinned.book))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty, Side, TimeInForce, TraderId};
+
+    fn limit(side: Side, price: u32, quantity: u32) -> Command {
+        Command::Limit {
+            trader: TraderId::from_str("T"),
+            side,
+            price: px(price),
+            quantity: qty(quantity),
+            tif: TimeInForce::Gtc,
+        }
+    }
+
+    #[test]
+    fn test_routes_symbols_to_shards() {
+        let config = ShardConfig { shards: 2, max_price: 20_000, max_orders: 1_000, ..ShardConfig::default() };
+        let mut engine = ShardedEngine::new(config);
+        assert_eq!(engine.add_symbol("BTCUSDT"), Ok(0));
+        assert_eq!(engine.add_symbol("ETHUSDT"), Ok(1));
+        assert_eq!(engine.add_symbol("BTCUSDT"), Err(ShardError::DuplicateSymbol("BTCUSDT".into())));
+        assert_eq!(
+            engine.submit("SOLUSDT", limit(Side::Buy, 100, 1)),
+            Err(ShardError::UnknownSymbol("SOLUSDT".into()))
+        );
+
+        for i in 0..100 {
+            assert_eq!(engine.submit("BTCUSDT", limit(Side::Sell, 10_000 + i, 1)), Ok(i as u64));
+            engine.submit("ETHUSDT", limit(Side::Buy, 3_000 - i, 1)).unwrap();
+        }
+        engine.submit("BTCUSDT", limit(Side::Buy, 10_010, 5)).unwrap();
+
+        let results = engine.drain().unwrap();
+        assert_eq!(results.len(), 201);
+        let btc: Vec<_> = results.iter().filter(|r| &*r.symbol == "BTCUSDT").collect();
+        assert!(btc.windows(2).all(|pair| pair[0].sequence < pair[1].sequence));
+        match &btc[100].result {
+            CommandResult::Accepted { order_id, trades } => {
+                assert_eq!(*order_id >> SYMBOL_ID_SHIFT, 1);
+                assert_eq!(trades.len(), 5);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert_eq!(engine.outstanding(), 0);
+        assert!(engine.try_recv().is_none());
+
+        let books = engine.shutdown().unwrap();
+        assert_eq!(books["BTCUSDT"].best_ask(), Some(px(10_005)));
+        assert_eq!(books["ETHUSDT"].best_bid(), Some(px(3_000)));
+    }
+}
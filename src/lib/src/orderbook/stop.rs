@@ -0,0 +1,129 @@
+/// 止损触发簿
+///
+/// 止损单与止损限价单在触发前不进入订单簿，而是按触发价保存在本结构中；
+/// 最新成交价穿越触发价时由引擎取出并作为普通订单执行。
+/// - 买入止损：最新成交价 >= 触发价时激活
+/// - 卖出止损：最新成交价 <= 触发价时激活
+
+use super::types::{OrderId, Price, Quantity, Side, TraderId};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// 等待触发的止损单
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StopOrder {
+    pub order_id: OrderId,            // 订单ID
+    pub trader: TraderId,             // 交易员ID
+    pub side: Side,                   // 方向
+    pub stop_price: Price,            // 触发价
+    pub limit_price: Option<Price>,   // 限价（None表示止损市价单）
+    pub quantity: Quantity,           // 数量
+}
+
+impl StopOrder {
+    /// 给定最新成交价是否触发
+    #[inline]
+    pub fn is_triggered_by(&self, last_price: Price) -> bool {
+        match self.side {
+            Side::Buy => last_price >= self.stop_price,
+            Side::Sell => last_price <= self.stop_price,
+        }
+    }
+}
+
+/// 按触发价索引的止损单簿
+#[derive(Debug, Default)]
+pub struct StopBook {
+    /// 买入止损（触发价 -> 按时间排序的订单）
+    buy_stops: BTreeMap<Price, VecDeque<StopOrder>>,
+    /// 卖出止损
+    sell_stops: BTreeMap<Price, VecDeque<StopOrder>>,
+    /// 订单ID -> (方向, 触发价)，用于取消
+    index: HashMap<OrderId, (Side, Price)>,
+}
+
+impl StopBook {
+    /// 创建空的止损簿
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 等待触发的止损单数量
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// 是否没有等待触发的止损单
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// 是否包含指定订单
+    #[inline]
+    pub fn contains(&self, order_id: OrderId) -> bool {
+        self.index.contains_key(&order_id)
+    }
+
+    /// 登记止损单
+    pub fn insert(&mut self, order: StopOrder) {
+        self.index.insert(order.order_id, (order.side, order.stop_price));
+        self.side_mut(order.side)
+            .entry(order.stop_price)
+            .or_default()
+            .push_back(order);
+    }
+
+    /// 取消止损单
+    pub fn cancel(&mut self, order_id: OrderId) -> Option<StopOrder> {
+        let (side, stop_price) = self.index.remove(&order_id)?;
+        let stops = self.side_mut(side);
+        let level = stops.get_mut(&stop_price)?;
+        let pos = level.iter().position(|o| o.order_id == order_id)?;
+        let order = level.remove(pos);
+        if level.is_empty() {
+            stops.remove(&stop_price);
+        }
+        order
+    }
+
+    /// 取出被最新成交价触发的所有止损单
+    ///
+    /// 买入止损按触发价从低到高、卖出止损按触发价从高到低，同价位按登记顺序
+    pub fn take_triggered(&mut self, last_price: Price, out: &mut Vec<StopOrder>) {
+        let start = out.len();
+
+        while let Some(entry) = self.buy_stops.first_entry() {
+            if *entry.key() > last_price {
+                break;
+            }
+            out.extend(entry.remove());
+        }
+
+        while let Some(entry) = self.sell_stops.last_entry() {
+            if *entry.key() < last_price {
+                break;
+            }
+            out.extend(entry.remove());
+        }
+
+        for order in &out[start..] {
+            self.index.remove(&order.order_id);
+        }
+    }
+
+    /// 遍历等待触发的止损单
+    pub fn iter(&self) -> impl Iterator<Item = &StopOrder> {
+        self.buy_stops
+            .values()
+            .chain(self.sell_stops.values())
+            .flatten()
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut BTreeMap<Price, VecDeque<StopOrder>> {
+        match side {
+            Side::Buy => &mut self.buy_stops,
+            Side::Sell => &mut self.sell_stops,
+        }
+    }
+}
@@ -0,0 +1,213 @@
+/// 时间回溯订单簿（研究用）
+///
+/// 保存窗口起点的深度快照和有界的增量队列，`seek(timestamp)`可向前或向后移动到窗口内任意时刻，
+/// 用于在录制的增量行情上做微观结构研究。
+///
+/// 每个批次入队时按当时的最新状态计算逆操作，向后移动时按相反顺序应用逆操作，
+/// 移动代价与跨越的批次数成正比。窗口满时最早的批次并入起点快照。
+
+use super::delta::{DeltaBatch, DepthDelta};
+use super::types::BookDepth;
+use std::collections::VecDeque;
+
+/// 带时间戳的批次及其逆操作
+#[derive(Debug, Clone)]
+struct TimedBatch {
+    timestamp_ns: u64,
+    batch: DeltaBatch,
+    /// 逆操作（已按应用顺序倒排）
+    undo: Vec<DepthDelta>,
+}
+
+/// 时间回溯订单簿
+#[derive(Debug, Clone)]
+pub struct TimeTravelBook {
+    /// 窗口起点快照
+    base: BookDepth,
+    base_timestamp_ns: u64,
+    /// 最新状态（用于计算新批次的逆操作）
+    tip: BookDepth,
+    batches: VecDeque<TimedBatch>,
+    capacity: usize,
+    /// 当前视图
+    current: BookDepth,
+    /// 当前视图已应用的批次数
+    cursor: usize,
+}
+
+impl TimeTravelBook {
+    /// 以`timestamp_ns`时刻的快照为起点，最多保留`capacity`个批次
+    ///
+    /// # Panics
+    /// capacity必须为正数
+    pub fn new(base: BookDepth, timestamp_ns: u64, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            tip: base.clone(),
+            current: base.clone(),
+            base,
+            base_timestamp_ns: timestamp_ns,
+            batches: VecDeque::with_capacity(capacity),
+            capacity,
+            cursor: 0,
+        }
+    }
+
+    /// 追加批次（时间戳早于窗口内最新时刻时返回false并忽略）
+    pub fn push(&mut self, timestamp_ns: u64, batch: DeltaBatch) -> bool {
+        if timestamp_ns < self.end_timestamp_ns() {
+            return false;
+        }
+        let mut undo = Vec::with_capacity(batch.deltas.len());
+        for delta in &batch.deltas {
+            undo.push(delta.inverse(&self.tip));
+            delta.apply_to(&mut self.tip);
+        }
+        undo.reverse();
+
+        if self.batches.len() == self.capacity {
+            self.evict();
+        }
+        self.batches.push_back(TimedBatch { timestamp_ns, batch, undo });
+        true
+    }
+
+    /// 把最早的批次并入起点快照
+    fn evict(&mut self) {
+        let Some(oldest) = self.batches.pop_front() else {
+            return;
+        };
+        oldest.batch.apply_to(&mut self.base);
+        self.base_timestamp_ns = oldest.timestamp_ns;
+        match self.cursor {
+            // 当前视图就是旧起点，跟随前移
+            0 => oldest.batch.apply_to(&mut self.current),
+            _ => self.cursor -= 1,
+        }
+    }
+
+    /// 移动到`timestamp_ns`时刻（含该时刻的全部批次），早于窗口起点时返回None
+    pub fn seek(&mut self, timestamp_ns: u64) -> Option<&BookDepth> {
+        if timestamp_ns < self.base_timestamp_ns {
+            return None;
+        }
+        let target = self.batches.partition_point(|entry| entry.timestamp_ns <= timestamp_ns);
+        while self.cursor < target {
+            self.batches[self.cursor].batch.apply_to(&mut self.current);
+            self.cursor += 1;
+        }
+        while self.cursor > target {
+            self.cursor -= 1;
+            for delta in &self.batches[self.cursor].undo {
+                delta.apply_to(&mut self.current);
+            }
+        }
+        Some(&self.current)
+    }
+
+    /// 当前视图
+    #[inline]
+    pub fn book(&self) -> &BookDepth {
+        &self.current
+    }
+
+    /// 当前视图对应的时刻（最后应用的批次时间，未应用批次时为起点时间）
+    pub fn timestamp_ns(&self) -> u64 {
+        match self.cursor {
+            0 => self.base_timestamp_ns,
+            n => self.batches[n - 1].timestamp_ns,
+        }
+    }
+
+    /// 当前视图对应的行情序列号（未应用批次时为None）
+    pub fn sequence(&self) -> Option<u64> {
+        self.cursor.checked_sub(1).map(|index| self.batches[index].batch.sequence)
+    }
+
+    /// 窗口起点时刻
+    #[inline]
+    pub fn start_timestamp_ns(&self) -> u64 {
+        self.base_timestamp_ns
+    }
+
+    /// 窗口内最新时刻
+    pub fn end_timestamp_ns(&self) -> u64 {
+        self.batches.back().map_or(self.base_timestamp_ns, |entry| entry.timestamp_ns)
+    }
+
+    /// 窗口内批次数
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.batches.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.batches.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::delta::DepthDeltaGenerator;
+    use crate::orderbook::types::{px, qty};
+    use crate::orderbook::{Command, OrderBook, Side, TimeInForce, TraderId};
+
+    /// 录制一段增量行情，返回(时间戳, 批次, 该时刻的深度)
+    fn record() -> Vec<(u64, DeltaBatch, BookDepth)> {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let mut generator = DepthDeltaGenerator::new(5);
+        let trader = TraderId::from_str("T");
+        let commands = [
+            (Side::Sell, 105, 5),
+            (Side::Sell, 103, 2),
+            (Side::Buy, 99, 4),
+            (Side::Buy, 101, 1),
+            (Side::Buy, 103, 3),
+            (Side::Sell, 99, 6),
+            (Side::Sell, 102, 2),
+        ];
+        let mut recorded = Vec::new();
+        for (i, (side, price, quantity)) in commands.into_iter().enumerate() {
+            let command = Command::Limit { trader, side, price: px(price), quantity: qty(quantity), tif: TimeInForce::Gtc };
+            let (_, batch) = generator.execute(&mut book, &command);
+            recorded.push(((i as u64 + 1) * 10, batch.unwrap(), book.depth(5)));
+        }
+        recorded
+    }
+
+    #[test]
+    fn test_seek_forward_and_backward() {
+        let recorded = record();
+        let mut history = TimeTravelBook::new(BookDepth::default(), 0, 16);
+        for (timestamp, batch, _) in &recorded {
+            assert!(history.push(*timestamp, batch.clone()));
+        }
+        assert!(!history.push(5, recorded[0].1.clone()));
+
+        for &index in &[6, 2, 4, 0, 5, 1, 3] {
+            let (timestamp, _, depth) = &recorded[index];
+            assert_eq!(history.seek(*timestamp + 5), Some(depth));
+            assert_eq!(history.timestamp_ns(), *timestamp);
+        }
+        assert_eq!(history.seek(0), Some(&BookDepth::default()));
+        assert_eq!(history.sequence(), None);
+    }
+
+    #[test]
+    fn test_window_eviction() {
+        let recorded = record();
+        let mut history = TimeTravelBook::new(BookDepth::default(), 0, 3);
+        history.seek(0);
+        for (timestamp, batch, _) in &recorded {
+            history.push(*timestamp, batch.clone());
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.start_timestamp_ns(), 40);
+        assert_eq!(history.book(), &recorded[3].2);
+        assert_eq!(history.seek(39), None);
+        assert_eq!(history.seek(70), Some(&recorded[6].2));
+        assert_eq!(history.seek(40), Some(&recorded[3].2));
+    }
+}
@@ -3,7 +3,12 @@
 /// 本模块提供高性能订单簿基础类型，
 /// 针对低时延交易系统进行优化。
 
+use super::command::RejectReason;
+use super::wal::crc32;
+use serde::Serialize;
 use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
 
 /// 交易员标识符（8字节固定长度）
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -70,34 +75,286 @@ impl fmt::Display for Side {
     }
 }
 
+/// 订单有效期类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeInForce {
+    /// 一直有效直到取消（未成交部分挂单）
+    #[default]
+    Gtc,
+    /// 立即成交剩余取消（未成交部分不挂单）
+    Ioc,
+    /// 全部成交或全部取消（不允许部分成交）
+    Fok,
+    /// 指定时间前有效（纳秒时间戳），未成交部分挂单，到期后由`expire_orders`撤销
+    Gtd(u64),
+}
+
+impl TimeInForce {
+    /// 未成交部分是否挂单
+    #[inline]
+    pub fn rests(&self) -> bool {
+        matches!(self, TimeInForce::Gtc | TimeInForce::Gtd(_))
+    }
+
+    /// 到期时间（纳秒时间戳）
+    #[inline]
+    pub fn expires_at(&self) -> Option<u64> {
+        match *self {
+            TimeInForce::Gtd(expires_at) => Some(expires_at),
+            _ => None,
+        }
+    }
+}
+
 /// 订单标识符
 pub type OrderId = u64;
 
-/// 价格（以分为单位，避免浮点运算）
-pub type Price = u32;
+/// 价格（整数tick，例如以分为单位，避免浮点运算）
+///
+/// 构造时校验非零；与`Quantity`是不同类型，参数位置传错会在编译期报错。
+/// `Display`默认输出tick数，指定精度时按小数输出（`{:.2}`: 10050 -> "100.50"）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct Price(u64);
+
+impl Price {
+    /// 最低价格（1 tick）
+    pub const MIN: Price = Price(1);
+    /// 最高价格
+    pub const MAX: Price = Price(u64::MAX);
+
+    /// 创建价格（0返回None）
+    #[inline]
+    pub const fn new(ticks: u64) -> Option<Self> {
+        if ticks == 0 { None } else { Some(Self(ticks)) }
+    }
+
+    /// 获取tick数
+    #[inline]
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// 作为价格阶梯索引
+    #[inline]
+    pub const fn as_index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// 加上若干tick（溢出时返回None）
+    #[inline]
+    pub const fn checked_add(self, ticks: u64) -> Option<Self> {
+        match self.0.checked_add(ticks) {
+            Some(value) => Some(Self(value)),
+            None => None,
+        }
+    }
+
+    /// 减去若干tick（结果为0或下溢时返回None）
+    #[inline]
+    pub const fn checked_sub(self, ticks: u64) -> Option<Self> {
+        match self.0.checked_sub(ticks) {
+            Some(value) => Self::new(value),
+            None => None,
+        }
+    }
+
+    /// 乘以倍数（溢出或倍数为0时返回None）
+    #[inline]
+    pub const fn checked_mul(self, factor: u64) -> Option<Self> {
+        match self.0.checked_mul(factor) {
+            Some(value) => Self::new(value),
+            None => None,
+        }
+    }
+
+    /// 与较低价格之间的tick数（`self`低于`lower`时返回None）
+    #[inline]
+    pub const fn ticks_above(self, lower: Price) -> Option<u64> {
+        self.0.checked_sub(lower.0)
+    }
+
+    /// 两个价格的中间价（向下取整）
+    #[inline]
+    pub const fn midpoint(self, other: Price) -> Price {
+        Self(self.0 / 2 + other.0 / 2 + (self.0 % 2 + other.0 % 2) / 2)
+    }
+
+    /// 成交额（价格 × 数量，以tick计）
+    #[inline]
+    pub const fn notional(self, quantity: Quantity) -> u64 {
+        self.0.saturating_mul(quantity.0 as u64)
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.precision() {
+            None | Some(0) => write!(f, "{}", self.0),
+            Some(precision) => {
+                let precision = precision.min(19);
+                let scale = 10u64.pow(precision as u32);
+                let ticks = self.0;
+                write!(f, "{}.{:0width$}", ticks / scale, ticks % scale, width = precision)
+            }
+        }
+    }
+}
 
 /// 数量/规模
-pub type Quantity = u32;
+///
+/// 订单数量构造时校验非零；撮合过程中的剩余数量可以为`ZERO`。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct Quantity(u32);
+
+impl Quantity {
+    /// 零数量（已成交完或已撤销）
+    pub const ZERO: Quantity = Quantity(0);
+    /// 单位数量
+    pub const ONE: Quantity = Quantity(1);
+    /// 最大数量
+    pub const MAX: Quantity = Quantity(u32::MAX);
+
+    /// 创建数量（0返回None）
+    #[inline]
+    pub const fn new(lots: u32) -> Option<Self> {
+        if lots == 0 { None } else { Some(Self(lots)) }
+    }
+
+    /// 获取数值
+    #[inline]
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    /// 是否为零
+    #[inline]
+    pub const fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    /// 相加（溢出时返回None）
+    #[inline]
+    pub const fn checked_add(self, other: Quantity) -> Option<Self> {
+        match self.0.checked_add(other.0) {
+            Some(value) => Some(Self(value)),
+            None => None,
+        }
+    }
+
+    /// 相减（下溢时返回None，结果可以为零）
+    #[inline]
+    pub const fn checked_sub(self, other: Quantity) -> Option<Self> {
+        match self.0.checked_sub(other.0) {
+            Some(value) => Some(Self(value)),
+            None => None,
+        }
+    }
+
+    /// 乘以倍数（溢出时返回None）
+    #[inline]
+    pub const fn checked_mul(self, factor: u32) -> Option<Self> {
+        match self.0.checked_mul(factor) {
+            Some(value) => Some(Self(value)),
+            None => None,
+        }
+    }
+
+    /// 饱和相加
+    #[inline]
+    pub const fn saturating_add(self, other: Quantity) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+
+    /// 饱和相减（不足时为零）
+    #[inline]
+    pub const fn saturating_sub(self, other: Quantity) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Add for Quantity {
+    type Output = Quantity;
+
+    #[inline]
+    fn add(self, other: Quantity) -> Quantity {
+        self.checked_add(other).expect("quantity overflow")
+    }
+}
+
+impl Sub for Quantity {
+    type Output = Quantity;
+
+    #[inline]
+    fn sub(self, other: Quantity) -> Quantity {
+        self.checked_sub(other).expect("quantity underflow")
+    }
+}
+
+impl AddAssign for Quantity {
+    #[inline]
+    fn add_assign(&mut self, other: Quantity) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Quantity {
+    #[inline]
+    fn sub_assign(&mut self, other: Quantity) {
+        *self = *self - other;
+    }
+}
+
+impl Sum for Quantity {
+    fn sum<I: Iterator<Item = Quantity>>(iter: I) -> Quantity {
+        iter.fold(Quantity::ZERO, Add::add)
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// 测试用价格构造（0时panic）
+#[cfg(test)]
+pub(crate) fn px(ticks: u32) -> Price {
+    Price::new(ticks as u64).expect("price must be non-zero")
+}
+
+/// 测试用数量构造（0时panic）
+#[cfg(test)]
+pub(crate) fn qty(lots: u32) -> Quantity {
+    Quantity::new(lots).expect("quantity must be non-zero")
+}
+
+/// 成交标识符
+pub type TradeId = u64;
 
 /// 交易执行记录
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Trade {
-    pub buyer: TraderId,      // 买方
-    pub seller: TraderId,     // 卖方
-    pub price: Price,         // 成交价格
-    pub quantity: Quantity,   // 成交数量
+    pub trade_id: TradeId,         // 成交序列号（每个订单簿内从1起连续递增，下游据此检测漏收）
+    pub timestamp_ns: u64,         // 成交时间（纳秒）
+    pub buyer: TraderId,           // 买方
+    pub seller: TraderId,          // 卖方
+    pub price: Price,              // 成交价格
+    pub quantity: Quantity,        // 成交数量
+    pub aggressor_side: Side,      // 主动方方向
+    pub maker_order_id: OrderId,   // 被动方（挂单）订单ID
+    pub taker_order_id: OrderId,   // 主动方订单ID
 }
 
 impl Trade {
-    /// 创建新的交易记录
+    /// 除成交时间外是否相同（用于比较两次独立撮合的结果）
     #[inline]
-    pub fn new(buyer: TraderId, seller: TraderId, price: Price, quantity: Quantity) -> Self {
-        Self {
-            buyer,
-            seller,
-            price,
-            quantity,
-        }
+    pub fn same_execution(&self, other: &Trade) -> bool {
+        Trade {
+            timestamp_ns: other.timestamp_ns,
+            ..*self
+        } == *other
     }
 }
 
@@ -105,52 +362,264 @@ impl fmt::Display for Trade {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "TRADE: {} <- {} @ {} x {}",
-            self.buyer, self.seller, self.price, self.quantity
+            "TRADE #{}: {} <- {} @ {} x {} ({} aggressor, maker #{}, taker #{})",
+            self.trade_id,
+            self.buyer,
+            self.seller,
+            self.price,
+            self.quantity,
+            self.aggressor_side,
+            self.maker_order_id,
+            self.taker_order_id
         )
     }
 }
 
+/// 逐笔订单簿事件（L3/MBO）
+///
+/// 除`OrderAdded`和`OrderAmended`外，事件只携带订单ID，方向和价格由下游按订单ID关联。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookEvent {
+    /// 新订单挂入订单簿
+    OrderAdded {
+        order_id: OrderId,
+        trader: TraderId,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+    },
+    /// 挂单被成交
+    OrderExecuted {
+        order_id: OrderId,           // 被动方订单ID
+        aggressor_id: OrderId,       // 主动方订单ID
+        price: Price,
+        quantity: Quantity,          // 本次成交数量
+        remaining: Quantity,         // 成交后剩余数量
+    },
+    /// 挂单被撤销
+    OrderCancelled {
+        order_id: OrderId,
+        quantity: Quantity,          // 撤销时的剩余数量
+    },
+    /// GTD挂单到期被撤销
+    OrderExpired {
+        order_id: OrderId,
+        quantity: Quantity,          // 到期时的剩余数量
+    },
+    /// 挂单被修改（改量或改价），下游据此原地更新订单而非按撤单+新增推断
+    OrderAmended {
+        order_id: OrderId,
+        side: Side,
+        old_price: Price,
+        new_price: Price,
+        old_quantity: Quantity,
+        new_quantity: Quantity,
+        priority_retained: bool,     // true: 原位置不变；false: 移到新价格档位队尾
+    },
+    /// 新订单被拒绝（未挂单、未成交）
+    OrderRejected {
+        order_id: OrderId,
+        reason: RejectReason,
+    },
+    /// 订单内存池利用率达到告警阈值
+    CapacityWarning {
+        used: usize,
+        capacity: usize,
+        threshold_pct: u8,
+    },
+    /// 撮合模式变化
+    ModeChanged { mode: TradingMode },
+}
+
+/// 撮合模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum TradingMode {
+    /// 正常撮合
+    #[default]
+    Normal,
+    /// 只接受撤单和改单，拒绝新订单
+    CancelOnly,
+    /// 波动熔断：不穿价订单正常挂单，穿价的GTC/GTD订单排队等待重新开盘，
+    /// 需立即成交的穿价订单（IOC/FOK/止损市价）被拒绝
+    Halted,
+}
+
+/// 聚合价格档位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthLevel {
+    pub price: Price,           // 价格
+    pub quantity: Quantity,     // 该价格总挂单量
+    pub order_count: u32,       // 该价格有效订单数
+}
+
+/// 订单簿深度（各方向按从优到劣排序）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BookDepth {
+    pub bids: Vec<DepthLevel>,  // 买方档位（价格从高到低）
+    pub asks: Vec<DepthLevel>,  // 卖方档位（价格从低到高）
+}
+
+impl BookDepth {
+    /// 前`levels`档的CRC32校验和（OKX风格）
+    ///
+    /// 从最优档起买卖交替取`价格:数量`（整数tick和手数的十进制文本），
+    /// 某侧档位不足时跳过该侧，以`:`连接后计算CRC32。
+    /// 下游镜像订单簿按同样规则计算，与行情中的校验和比较即可发现丢包或应用错误。
+    pub fn checksum(&self, levels: usize) -> u32 {
+        let mut text = String::new();
+        for i in 0..levels.min(self.bids.len().max(self.asks.len())) {
+            for level in [self.bids.get(i), self.asks.get(i)].into_iter().flatten() {
+                if !text.is_empty() {
+                    text.push(':');
+                }
+                text.push_str(&format!("{}:{}", level.price.get(), level.quantity.get()));
+            }
+        }
+        crc32(text.as_bytes())
+    }
+}
+
+/// 假想订单的成交估算（见`OrderBook::estimate_fill`）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FillEstimate {
+    pub filled: Quantity,           // 可成交数量
+    pub unfilled: Quantity,         // 对手方深度不足时的剩余数量
+    pub notional: u128,             // 成交金额（价格tick × 数量）
+    pub worst_price: Option<Price>, // 最后吃到的价格档位（无成交时为None）
+    pub levels: u32,                // 吃掉的价格档位数
+}
+
+impl FillEstimate {
+    /// 成交均价（tick，无成交时为None）
+    #[inline]
+    pub fn average_price(&self) -> Option<f64> {
+        (self.filled.get() > 0).then(|| self.notional as f64 / self.filled.get() as f64)
+    }
+
+    /// 是否能全部成交
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.unfilled.get() == 0
+    }
+}
+
+/// 单个交易员的挂单与成交汇总
+///
+/// 只统计订单簿上的挂单；等待触发的止损单和熔断排队订单不计入。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TraderStats {
+    pub open_orders: u32,           // 挂单数
+    pub open_buy_quantity: u64,     // 买方挂单剩余数量合计
+    pub open_sell_quantity: u64,    // 卖方挂单剩余数量合计
+    pub executed_volume: u64,       // 累计成交数量（主动与被动）
+    pub net_position: i64,          // 净持仓（买入成交 - 卖出成交，可由`set_position`设定初值）
+}
+
+impl TraderStats {
+    /// 指定方向的挂单剩余数量合计
+    #[inline]
+    pub fn open_quantity(&self, side: Side) -> u64 {
+        match side {
+            Side::Buy => self.open_buy_quantity,
+            Side::Sell => self.open_sell_quantity,
+        }
+    }
+
+    #[inline]
+    fn open_quantity_mut(&mut self, side: Side) -> &mut u64 {
+        match side {
+            Side::Buy => &mut self.open_buy_quantity,
+            Side::Sell => &mut self.open_sell_quantity,
+        }
+    }
+
+    /// 计入新挂单
+    #[inline]
+    pub fn add_order(&mut self, side: Side, quantity: Quantity) {
+        *self.open_quantity_mut(side) += quantity.get() as u64;
+        self.open_orders += 1;
+    }
+
+    /// 扣减成交或减量的挂单数量
+    #[inline]
+    pub fn reduce(&mut self, side: Side, quantity: Quantity) {
+        *self.open_quantity_mut(side) -= quantity.get() as u64;
+    }
+
+    /// 移除挂单，`remaining`为其移除前的剩余数量
+    #[inline]
+    pub fn remove_order(&mut self, side: Side, remaining: Quantity) {
+        self.reduce(side, remaining);
+        self.open_orders -= 1;
+    }
+
+    /// 计入`side`方向的一笔成交
+    #[inline]
+    pub fn record_fill(&mut self, side: Side, quantity: Quantity) {
+        let quantity = quantity.get() as u64;
+        self.executed_volume += quantity;
+        match side {
+            Side::Buy => self.net_position += quantity as i64,
+            Side::Sell => self.net_position -= quantity as i64,
+        }
+    }
+
+    /// `side`方向还能只减仓的数量：反向持仓减去同方向已挂数量（已挂单全部成交也不会反向开仓）
+    #[inline]
+    pub fn reducible_quantity(&self, side: Side) -> u64 {
+        let position = match side {
+            Side::Buy => (-self.net_position).max(0),
+            Side::Sell => self.net_position.max(0),
+        };
+        (position as u64).saturating_sub(self.open_quantity(side))
+    }
+}
+
 /// 订单簿条目（64字节缓存行对齐以提升性能）
 #[derive(Debug, Clone, Copy)]
 #[repr(align(64))]
 pub struct OrderEntry {
     pub order_id: OrderId,           // 订单ID
     pub trader: TraderId,            // 交易员ID
+    pub side: Side,                  // 方向
+    pub price: Price,                // 挂单价格
     pub quantity: Quantity,          // 数量
     pub next_idx: Option<usize>,     // 链表中下一个订单的索引
+    pub prev_idx: Option<usize>,     // 链表中上一个订单的索引
 }
 
 impl OrderEntry {
     /// 创建新的订单条目
     #[inline]
-    pub fn new(order_id: OrderId, trader: TraderId, quantity: Quantity) -> Self {
+    pub fn new(order_id: OrderId, trader: TraderId, side: Side, price: Price, quantity: Quantity) -> Self {
         Self {
             order_id,
             trader,
+            side,
+            price,
             quantity,
             next_idx: None,
+            prev_idx: None,
         }
     }
 
     /// 检查订单是否仍然有效（数量>0）
     #[inline]
     pub fn is_active(&self) -> bool {
-        self.quantity > 0
-    }
-
-    /// 取消订单（通过将数量置零，单次内存写入，速度快）
-    #[inline]
-    pub fn cancel(&mut self) {
-        self.quantity = 0;
+        !self.quantity.is_zero()
     }
 }
 
-/// 订单簿中的价格点（链表头）
+/// 订单簿中的价格点（双向链表头尾和有效订单汇总）
+///
+/// 汇总量随挂单、成交、撤单和减量同步维护，档位数量和笔数查询为O(1)；
+/// 完全成交和撤销的订单立即从链表摘除，链表中只有有效订单。
 #[derive(Debug, Clone, Copy)]
 pub struct PricePoint {
     pub first_order_idx: Option<usize>,  // 该价格的第一个订单索引
     pub last_order_idx: Option<usize>,   // 该价格的最后一个订单索引
+    pub total_quantity: u64,             // 有效订单剩余数量合计
+    pub order_count: u32,                // 有效订单数
 }
 
 impl Default for PricePoint {
@@ -158,6 +627,8 @@ impl Default for PricePoint {
         Self {
             first_order_idx: None,
             last_order_idx: None,
+            total_quantity: 0,
+            order_count: 0,
         }
     }
 }
@@ -169,6 +640,32 @@ impl PricePoint {
         self.first_order_idx.is_none()
     }
 
+    /// 有效订单数量合计（超出`Quantity`范围时饱和）
+    #[inline]
+    pub fn quantity(&self) -> Quantity {
+        Quantity(self.total_quantity.min(u32::MAX as u64) as u32)
+    }
+
+    /// 计入新挂单
+    #[inline]
+    pub fn add_order(&mut self, quantity: Quantity) {
+        self.total_quantity += quantity.get() as u64;
+        self.order_count += 1;
+    }
+
+    /// 扣减成交或减量的数量
+    #[inline]
+    pub fn reduce(&mut self, quantity: Quantity) {
+        self.total_quantity -= quantity.get() as u64;
+    }
+
+    /// 移除订单（完全成交或撤销），`remaining`为其移除前的剩余数量
+    #[inline]
+    pub fn remove_order(&mut self, remaining: Quantity) {
+        self.reduce(remaining);
+        self.order_count -= 1;
+    }
+
     /// 在链表尾部添加订单
     #[inline]
     pub fn push_back(&mut self, idx: usize) {
@@ -185,3 +682,39 @@ impl PricePoint {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_and_quantity_reject_zero() {
+        assert_eq!(Price::new(0), None);
+        assert_eq!(Quantity::new(0), None);
+        assert_eq!(px(10000).checked_sub(10000), None);
+        assert_eq!(px(10000).checked_sub(1), Some(px(9999)));
+        assert_eq!(Price::MAX.checked_add(1), None);
+        assert_eq!(px(100).checked_mul(0), None);
+        assert_eq!(qty(5).checked_sub(qty(5)), Some(Quantity::ZERO));
+        assert_eq!(qty(5).checked_sub(qty(6)), None);
+        assert_eq!(Quantity::MAX.checked_mul(2), None);
+    }
+
+    #[test]
+    fn test_arithmetic_and_display() {
+        let mut remaining = qty(10);
+        remaining -= qty(4);
+        remaining += qty(1);
+        assert_eq!(remaining, qty(7));
+        assert_eq!([qty(1), qty(2)].into_iter().sum::<Quantity>(), qty(3));
+
+        assert_eq!(px(10100).ticks_above(px(9900)), Some(200));
+        assert_eq!(px(9900).midpoint(px(10101)), px(10000));
+        assert_eq!(px(10050).notional(qty(3)), 30150);
+
+        assert_eq!(px(10050).to_string(), "10050");
+        assert_eq!(format!("{:.2}", px(10050)), "100.50");
+        assert_eq!(format!("{:.3}", px(7)), "0.007");
+        assert_eq!(qty(42).to_string(), "42");
+    }
+}
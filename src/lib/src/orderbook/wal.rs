@@ -0,0 +1,634 @@
+/// 分段指令日志（WAL）的完整性校验与压缩
+///
+/// 指令日志按大小切分为段文件`{首条序列号:020}.wal`，每条记录:
+/// - 4字节记录体长度 + 4字节CRC32（覆盖记录体）+ `JournalEntry`编码
+///
+/// 快照文件`{序列号:020}.snap`保存执行完该序列号后的`export_state`输出，前置4字节CRC32。
+///
+/// - `verify`逐段校验CRC与序列号连续性，损坏时返回带最后完好序列号的错误，可截断到该处后恢复
+/// - `compact`把全部记录都不晚于最新快照的段合并为一个段文件，减少恢复时打开的文件数
+/// - `recover`在重启时截掉崩溃留下的半条记录，载入最新快照并重放其后的记录
+
+use super::command::{Command, CommandResult};
+use super::engine::OrderBook;
+use super::journal::JournalEntry;
+use crate::timing::{system_clock, Clock};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// 段文件扩展名
+pub const SEGMENT_EXT: &str = "wal";
+/// 快照文件扩展名
+pub const SNAPSHOT_EXT: &str = "snap";
+/// 默认段大小上限
+pub const DEFAULT_SEGMENT_BYTES: u64 = 64 * 1024 * 1024;
+
+/// 记录头长度：4字节长度 + 4字节CRC32
+const RECORD_HEADER_LEN: usize = 8;
+
+/// CRC32（IEEE 802.3，反射多项式0xEDB88320）查找表
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// 计算CRC32
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &b| CRC32_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+/// 日志损坏或读写失败
+///
+/// 损坏类错误携带`last_good`：此前的记录均完好且序列号连续（没有完好记录时为0）。
+#[derive(Debug, Error)]
+pub enum WalError {
+    #[error("wal io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("checksum mismatch in {segment:?} at offset {offset} (last good sequence {last_good})")]
+    Checksum { segment: PathBuf, offset: u64, last_good: u64 },
+    #[error("truncated record in {segment:?} at offset {offset} (last good sequence {last_good})")]
+    Truncated { segment: PathBuf, offset: u64, last_good: u64 },
+    #[error("undecodable record in {segment:?} at offset {offset}: {reason} (last good sequence {last_good})")]
+    Malformed { segment: PathBuf, offset: u64, reason: String, last_good: u64 },
+    #[error("sequence gap in {segment:?}: expected {expected}, found {found} (last good sequence {last_good})")]
+    Gap { segment: PathBuf, expected: u64, found: u64, last_good: u64 },
+    #[error("corrupt snapshot {0:?}")]
+    Snapshot(PathBuf),
+}
+
+impl WalError {
+    /// 最后一条完好记录的序列号（IO错误和快照损坏时为None）
+    pub fn last_good(&self) -> Option<u64> {
+        match self {
+            WalError::Checksum { last_good, .. }
+            | WalError::Truncated { last_good, .. }
+            | WalError::Malformed { last_good, .. }
+            | WalError::Gap { last_good, .. } => Some(*last_good),
+            WalError::Io(_) | WalError::Snapshot(_) => None,
+        }
+    }
+}
+
+/// 校验结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WalSummary {
+    pub segments: usize,
+    pub records: u64,
+    pub first_sequence: Option<u64>,
+    /// 最后一条记录的序列号（没有记录时为0）
+    pub last_sequence: u64,
+    /// 最新快照对应的序列号
+    pub snapshot: Option<u64>,
+}
+
+/// 压缩结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CompactReport {
+    /// 被合并的段数（0表示无需压缩）
+    pub merged_segments: usize,
+    /// 合并的记录数
+    pub merged_records: u64,
+    /// 依据的快照序列号
+    pub snapshot: Option<u64>,
+}
+
+/// 崩溃恢复结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RecoveryReport {
+    /// 载入的快照序列号
+    pub snapshot: Option<u64>,
+    /// 快照之后重放的记录数
+    pub replayed: u64,
+    /// 最后一条完好记录的序列号（没有记录时为0）
+    pub last_sequence: u64,
+    /// 从最后一段末尾截掉的字节数（写到一半的记录）
+    pub truncated_bytes: u64,
+}
+
+/// 分段日志写入器
+pub struct WalWriter {
+    dir: PathBuf,
+    segment_bytes: u64,
+    /// 每条指令执行前同步到磁盘
+    fsync: bool,
+    segment: Option<(BufWriter<File>, u64)>,
+    next_sequence: u64,
+    buf: Vec<u8>,
+    clock: Arc<dyn Clock>,
+}
+
+impl WalWriter {
+    /// 打开（或创建）日志目录，在已有记录之后续写
+    ///
+    /// 已有日志损坏时返回错误，不会在损坏的记录之后追加。
+    pub fn open(dir: impl AsRef<Path>, segment_bytes: u64) -> Result<Self, WalError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let summary = verify(&dir)?;
+        Ok(Self {
+            dir,
+            segment_bytes: segment_bytes.max(1),
+            fsync: false,
+            segment: None,
+            next_sequence: summary.last_sequence + 1,
+            buf: Vec::with_capacity(64),
+            clock: system_clock(),
+        })
+    }
+
+    /// 设置记录时间戳的时间源
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置`submit`是否在执行指令前把记录同步到磁盘（默认只写入缓冲区）
+    ///
+    /// 开启后`submit`返回时指令已落盘，进程或机器崩溃后`recover`能恢复全部已执行的指令。
+    pub fn with_fsync(mut self, fsync: bool) -> Self {
+        self.fsync = fsync;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 下一条记录的序列号
+    #[inline]
+    pub fn next_sequence(&self) -> u64 {
+        self.next_sequence
+    }
+
+    /// 追加一条指令，返回分配的序列号；当前段超过大小上限时切换到新段
+    pub fn append(&mut self, command: &Command) -> io::Result<u64> {
+        let entry = JournalEntry {
+            sequence: self.next_sequence,
+            timestamp_ns: self.clock.now_ns(),
+            command: *command,
+        };
+        self.buf.clear();
+        entry.write_to(&mut self.buf)?;
+
+        if self.segment.as_ref().is_none_or(|(_, written)| *written >= self.segment_bytes) {
+            self.roll()?;
+        }
+        let (writer, written) = self.segment.as_mut().expect("segment opened above");
+        writer.write_all(&(self.buf.len() as u32).to_le_bytes())?;
+        writer.write_all(&crc32(&self.buf).to_le_bytes())?;
+        writer.write_all(&self.buf)?;
+        *written += (RECORD_HEADER_LEN + self.buf.len()) as u64;
+
+        self.next_sequence += 1;
+        Ok(entry.sequence)
+    }
+
+    /// 记录并执行指令；日志写入（开启fsync时包括同步）失败时不执行
+    pub fn submit(&mut self, book: &mut OrderBook, command: Command) -> io::Result<CommandResult> {
+        self.append(&command)?;
+        if self.fsync {
+            self.sync()?;
+        }
+        Ok(command.execute(book))
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.segment.as_mut() {
+            Some((writer, _)) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// 刷新并同步到磁盘
+    pub fn sync(&mut self) -> io::Result<()> {
+        match self.segment.as_mut() {
+            Some((writer, _)) => {
+                writer.flush()?;
+                writer.get_ref().sync_data()
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// 写入订单簿快照，对应最后一条已追加的记录；返回快照路径
+    ///
+    /// 先写临时文件再改名，写入过程中崩溃不会留下半个快照。
+    pub fn write_snapshot(&mut self, book: &OrderBook) -> io::Result<PathBuf> {
+        self.sync()?;
+        let sequence = self.next_sequence - 1;
+        let state = book.export_state();
+        let path = file_path(&self.dir, sequence, SNAPSHOT_EXT);
+        let tmp = path.with_extension("snap.tmp");
+        {
+            let mut file = File::create(&tmp)?;
+            file.write_all(&crc32(&state).to_le_bytes())?;
+            file.write_all(&state)?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, &path)?;
+        Ok(path)
+    }
+
+    fn roll(&mut self) -> io::Result<()> {
+        self.flush()?;
+        let path = file_path(&self.dir, self.next_sequence, SEGMENT_EXT);
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        self.segment = Some((BufWriter::new(file), 0));
+        Ok(())
+    }
+}
+
+impl Drop for WalWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+fn file_path(dir: &Path, sequence: u64, ext: &str) -> PathBuf {
+    dir.join(format!("{:020}.{}", sequence, ext))
+}
+
+/// 列出目录中指定扩展名的文件及其序列号（升序）
+fn list(dir: &Path, ext: &str) -> io::Result<Vec<(u64, PathBuf)>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(ext) {
+            continue;
+        }
+        if let Some(sequence) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse().ok())
+        {
+            files.push((sequence, path));
+        }
+    }
+    files.sort_unstable();
+    Ok(files)
+}
+
+/// 按序扫描全部段，对每条完好记录调用`visit`；返回(段数, 记录数, 最后序列号)
+fn scan(dir: &Path, mut visit: impl FnMut(&Path, &JournalEntry, &[u8])) -> Result<(usize, u64, u64), WalError> {
+    let segments = list(dir, SEGMENT_EXT)?;
+    let mut last_good = 0;
+    let mut records = 0;
+
+    for (_, segment) in &segments {
+        let mut data = Vec::new();
+        File::open(segment)?.read_to_end(&mut data)?;
+
+        let mut offset = 0;
+        while offset < data.len() {
+            let truncated = || WalError::Truncated {
+                segment: segment.clone(),
+                offset: offset as u64,
+                last_good,
+            };
+            let header = data.get(offset..offset + RECORD_HEADER_LEN).ok_or_else(truncated)?;
+            let len = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+            let crc = u32::from_le_bytes(header[4..].try_into().unwrap());
+            let body_start = offset + RECORD_HEADER_LEN;
+            let body = data.get(body_start..body_start + len).ok_or_else(truncated)?;
+            if crc32(body) != crc {
+                return Err(WalError::Checksum {
+                    segment: segment.clone(),
+                    offset: offset as u64,
+                    last_good,
+                });
+            }
+
+            let malformed = |reason: String| WalError::Malformed {
+                segment: segment.clone(),
+                offset: offset as u64,
+                reason,
+                last_good,
+            };
+            let mut reader = body;
+            let entry = match JournalEntry::read_from(&mut reader) {
+                Ok(Some(entry)) if reader.is_empty() => entry,
+                Ok(Some(_)) => return Err(malformed("trailing bytes after entry".to_string())),
+                Ok(None) => return Err(malformed("empty record".to_string())),
+                Err(e) => return Err(malformed(e.to_string())),
+            };
+            if records > 0 && entry.sequence != last_good + 1 {
+                return Err(WalError::Gap {
+                    segment: segment.clone(),
+                    expected: last_good + 1,
+                    found: entry.sequence,
+                    last_good,
+                });
+            }
+
+            visit(segment, &entry, &data[offset..body_start + len]);
+            last_good = entry.sequence;
+            records += 1;
+            offset = body_start + len;
+        }
+    }
+    Ok((segments.len(), records, last_good))
+}
+
+/// 校验日志目录中全部段的CRC和序列号连续性，以及最新快照的CRC
+pub fn verify(dir: impl AsRef<Path>) -> Result<WalSummary, WalError> {
+    let dir = dir.as_ref();
+    let mut first_sequence = None;
+    let (segments, records, last_sequence) = scan(dir, |_, entry, _| {
+        first_sequence.get_or_insert(entry.sequence);
+    })?;
+
+    let snapshot = latest_snapshot(dir)?.map(|(sequence, _)| sequence);
+    Ok(WalSummary {
+        segments,
+        records,
+        first_sequence,
+        last_sequence,
+        snapshot,
+    })
+}
+
+/// 读取全部日志记录（校验方式同`verify`）
+pub fn read_entries(dir: impl AsRef<Path>) -> Result<Vec<JournalEntry>, WalError> {
+    let mut entries = Vec::new();
+    scan(dir.as_ref(), |_, entry, _| entries.push(*entry))?;
+    Ok(entries)
+}
+
+/// 读取并校验最新快照，返回(序列号, `export_state`数据)
+pub fn latest_snapshot(dir: impl AsRef<Path>) -> Result<Option<(u64, Vec<u8>)>, WalError> {
+    let Some((sequence, path)) = list(dir.as_ref(), SNAPSHOT_EXT)?.pop() else {
+        return Ok(None);
+    };
+    let data = fs::read(&path)?;
+    if data.len() < 4 || crc32(&data[4..]) != u32::from_le_bytes(data[..4].try_into().unwrap()) {
+        return Err(WalError::Snapshot(path));
+    }
+    Ok(Some((sequence, data[4..].to_vec())))
+}
+
+/// 把全部记录都不晚于最新快照的段合并为一个段
+///
+/// 只在日志完整时执行。合并结果先写入临时文件，改名覆盖最早的段后再删除其余被合并的段。
+/// 最后一个段可能仍在写入，不参与合并。
+pub fn compact(dir: impl AsRef<Path>) -> Result<CompactReport, WalError> {
+    let dir = dir.as_ref();
+    let summary = verify(dir)?;
+    let Some(snapshot) = summary.snapshot else {
+        return Ok(CompactReport::default());
+    };
+
+    let segments = list(dir, SEGMENT_EXT)?;
+    // 段i的记录范围为[segments[i].0, segments[i+1].0)，最后一个段不合并
+    let mergeable = segments
+        .windows(2)
+        .take_while(|pair| pair[1].0 <= snapshot + 1)
+        .count();
+    if mergeable < 2 {
+        return Ok(CompactReport {
+            snapshot: Some(snapshot),
+            ..CompactReport::default()
+        });
+    }
+    let merged: Vec<&PathBuf> = segments[..mergeable].iter().map(|(_, path)| path).collect();
+
+    let mut records = Vec::new();
+    let mut merged_records = 0;
+    scan(dir, |segment, _, record| {
+        if merged.iter().any(|path| path.as_path() == segment) {
+            records.extend_from_slice(record);
+            merged_records += 1;
+        }
+    })?;
+
+    let target = merged[0];
+    let tmp = target.with_extension("wal.tmp");
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(&records)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, target)?;
+    for path in &merged[1..] {
+        fs::remove_file(path)?;
+    }
+
+    Ok(CompactReport {
+        merged_segments: mergeable,
+        merged_records,
+        snapshot: Some(snapshot),
+    })
+}
+
+/// 崩溃恢复：重建订单簿（包括下一个订单ID）
+///
+/// 最后一段末尾写到一半或校验失败的记录视为崩溃时未完成的写入，截断后继续；
+/// 其他位置的损坏原样返回错误。`book`必须为空订单簿，有快照时先导入快照，
+/// 再按序重放快照之后的记录。恢复后可用`WalWriter::open`在末尾续写。
+pub fn recover(dir: impl AsRef<Path>, book: &mut OrderBook) -> Result<RecoveryReport, WalError> {
+    let dir = dir.as_ref();
+    let mut report = RecoveryReport::default();
+    if let Err(err) = verify(dir) {
+        let (WalError::Truncated { segment, offset, .. } | WalError::Checksum { segment, offset, .. }) = &err else {
+            return Err(err);
+        };
+        if list(dir, SEGMENT_EXT)?.last().map(|(_, path)| path) != Some(segment) {
+            return Err(err);
+        }
+        let file = OpenOptions::new().write(true).open(segment)?;
+        report.truncated_bytes = file.metadata()?.len() - offset;
+        file.set_len(*offset)?;
+        file.sync_all()?;
+    }
+
+    let mut after = 0;
+    if let Some((sequence, state)) = latest_snapshot(dir)? {
+        book.import_state(&state)?;
+        report.snapshot = Some(sequence);
+        after = sequence;
+    }
+    let tail: Vec<JournalEntry> = read_entries(dir)?.into_iter().filter(|entry| entry.sequence > after).collect();
+    if let Some(first) = tail.first()
+        && first.sequence != after + 1
+    {
+        return Err(WalError::Gap {
+            segment: dir.to_path_buf(),
+            expected: after + 1,
+            found: first.sequence,
+            last_good: after,
+        });
+    }
+    report.replayed = tail.len() as u64;
+    report.last_sequence = book.replay_into(tail)?.max(after);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty, Side, TimeInForce, TraderId};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rlob-wal-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn limit(price: u32) -> Command {
+        Command::Limit {
+            trader: TraderId::from_str("T"),
+            side: Side::Buy,
+            price: px(price),
+            quantity: qty(1),
+            tif: TimeInForce::Gtc,
+        }
+    }
+
+    /// 每段约两条记录
+    fn write_log(dir: &Path, count: u32) -> (WalWriter, OrderBook) {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let mut wal = WalWriter::open(dir, 80).unwrap();
+        for i in 0..count {
+            wal.submit(&mut book, limit(100 + i)).unwrap();
+        }
+        wal.flush().unwrap();
+        (wal, book)
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_verify_and_resume() {
+        let dir = temp_dir("verify");
+        let (wal, _) = write_log(&dir, 7);
+        drop(wal);
+
+        let summary = verify(&dir).unwrap();
+        assert_eq!(summary.records, 7);
+        assert_eq!((summary.first_sequence, summary.last_sequence), (Some(1), 7));
+        assert!(summary.segments > 1);
+
+        let mut wal = WalWriter::open(&dir, 80).unwrap();
+        assert_eq!(wal.append(&limit(200)).unwrap(), 8);
+        drop(wal);
+        let entries = read_entries(&dir).unwrap();
+        assert_eq!(entries.iter().map(|e| e.sequence).collect::<Vec<_>>(), (1..=8).collect::<Vec<_>>());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_corruption_reports_last_good_sequence() {
+        let dir = temp_dir("corrupt");
+        drop(write_log(&dir, 6));
+        let segments = list(&dir, SEGMENT_EXT).unwrap();
+
+        // 第二个段首条记录的记录体被改写
+        let (first, path) = &segments[1];
+        let mut data = fs::read(path).unwrap();
+        data[RECORD_HEADER_LEN + 2] ^= 0xFF;
+        fs::write(path, &data).unwrap();
+        let err = verify(&dir).unwrap_err();
+        assert!(matches!(err, WalError::Checksum { offset: 0, .. }), "{}", err);
+        assert_eq!(err.last_good(), Some(first - 1));
+        assert!(WalWriter::open(&dir, 80).is_err());
+
+        // 写到一半的记录
+        data[RECORD_HEADER_LEN + 2] ^= 0xFF;
+        data.truncate(data.len() - 3);
+        fs::write(path, &data).unwrap();
+        assert!(matches!(verify(&dir).unwrap_err(), WalError::Truncated { .. }));
+
+        // 丢失整个段
+        fs::remove_file(path).unwrap();
+        let err = verify(&dir).unwrap_err();
+        assert!(matches!(err, WalError::Gap { expected, .. } if expected == *first), "{}", err);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_compact_merges_segments_before_snapshot() {
+        let dir = temp_dir("compact");
+        let (mut wal, mut book) = write_log(&dir, 6);
+        wal.write_snapshot(&book).unwrap();
+        for i in 0..3 {
+            wal.submit(&mut book, limit(300 + i)).unwrap();
+        }
+        drop(wal);
+
+        let before = verify(&dir).unwrap();
+        assert_eq!(before.snapshot, Some(6));
+        let report = compact(&dir).unwrap();
+        assert!(report.merged_segments >= 2);
+        assert_eq!(report.merged_records, 6);
+
+        let after = verify(&dir).unwrap();
+        assert_eq!(after.records, 9);
+        assert_eq!(after.segments, before.segments - report.merged_segments + 1);
+        // 已压缩的目录再次压缩无事可做
+        assert_eq!(compact(&dir).unwrap().merged_segments, 0);
+
+        let (sequence, state) = latest_snapshot(&dir).unwrap().unwrap();
+        assert_eq!(sequence, 6);
+        let mut restored = OrderBook::with_capacity(1_000, 100);
+        restored.import_state(&state).unwrap();
+        let tail = read_entries(&dir).unwrap().into_iter().filter(|e| e.sequence > sequence);
+        restored.replay_into(tail).unwrap();
+        assert_eq!(restored.depth(20), book.depth(20));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_recover_after_crash() {
+        let dir = temp_dir("recover");
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let mut wal = WalWriter::open(&dir, 80).unwrap().with_fsync(true);
+        for i in 0..4 {
+            wal.submit(&mut book, limit(100 + i)).unwrap();
+        }
+        wal.write_snapshot(&book).unwrap();
+        let sell = Command::Limit {
+            trader: TraderId::from_str("S"),
+            side: Side::Sell,
+            price: px(102),
+            quantity: qty(1),
+            tif: TimeInForce::Gtc,
+        };
+        wal.submit(&mut book, sell).unwrap();
+        wal.submit(&mut book, limit(150)).unwrap();
+        drop(wal);
+
+        // 崩溃时最后一条记录只写了一半
+        let (_, last) = list(&dir, SEGMENT_EXT).unwrap().pop().unwrap();
+        let mut file = OpenOptions::new().append(true).open(&last).unwrap();
+        file.write_all(&[40, 0, 0, 0, 1, 2]).unwrap();
+        drop(file);
+
+        let mut restored = OrderBook::with_capacity(1_000, 100);
+        let report = recover(&dir, &mut restored).unwrap();
+        assert_eq!(report, RecoveryReport { snapshot: Some(4), replayed: 2, last_sequence: 6, truncated_bytes: 6 });
+        assert_eq!(restored.depth(20), book.depth(20));
+        assert_eq!(restored.next_order_id(), book.next_order_id());
+
+        // 恢复后在末尾续写
+        let mut wal = WalWriter::open(&dir, 80).unwrap();
+        assert_eq!(wal.append(&limit(160)).unwrap(), 7);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
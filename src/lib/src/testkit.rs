@@ -0,0 +1,584 @@
+/// 可复现的合成订单流
+///
+/// 按固定随机种子生成订单指令序列，供压测客户端、回测和性质测试构造压力场景:
+/// - 到达过程: 泊松到达（指数分布间隔），可叠加周期性突发
+/// - 价格: 中间价随机游走，被动单挂在中间价附近若干档内
+/// - 主动单: 按比例生成穿价IOC单
+/// - 撤单: 按比例从已受理的挂单中随机撤销
+///
+/// 撤单需要引擎分配的订单ID，调用方把每条指令的执行结果交给`observe`；
+/// 对确定性的订单簿而言，同一种子产生的整个指令序列完全相同。
+///
+/// `Recording`录制一段订单流及参考引擎逐条的结果指纹，回放到另一个引擎实例
+/// （或序列化后回放到新版本引擎）时逐条比较，用于在重构前后校验撮合确定性。
+
+use crate::orderbook::journal::JournalEntry;
+use crate::orderbook::shadow::depth_checksum;
+use crate::orderbook::{
+    Command, CommandResult, Divergence, DivergenceKind, OrderBook, OrderId, Price, Quantity, Side, TimeInForce, TraderId,
+};
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+/// 跟踪的挂单ID上限（超出时随机淘汰）
+const MAX_TRACKED_ORDERS: usize = 100_000;
+/// 录制文件魔数
+const RECORDING_MAGIC: &[u8; 4] = b"RREC";
+/// 结果指纹中参与深度校验和的档数
+const FINGERPRINT_DEPTH: usize = 10;
+
+/// 周期性突发
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BurstConfig {
+    /// 突发周期
+    pub every: Duration,
+    /// 每次突发的持续时间
+    pub length: Duration,
+    /// 突发期间到达率倍数
+    pub multiplier: f64,
+}
+
+/// 订单流参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowConfig {
+    /// 随机种子，相同种子产生相同序列
+    pub seed: u64,
+    /// 平均到达率（每秒指令数）
+    pub arrival_rate: f64,
+    /// 初始中间价
+    pub start_price: u32,
+    /// 中间价下限/上限
+    pub min_price: u32,
+    pub max_price: u32,
+    /// 每条指令后中间价移动的概率
+    pub walk_probability: f64,
+    /// 中间价单次最大移动档数
+    pub max_step: u32,
+    /// 被动单距中间价的最大档数
+    pub depth: u32,
+    /// 单笔数量范围
+    pub min_quantity: u32,
+    pub max_quantity: u32,
+    /// 撤单占全部指令的比例
+    pub cancel_ratio: f64,
+    /// 新订单中穿价IOC单的比例
+    pub marketable_ratio: f64,
+    /// 主动单最多穿过中间价的档数
+    pub aggression: u32,
+    /// 交易员数量（ID为`T0`、`T1`...）
+    pub traders: u32,
+    /// 突发（None表示平稳到达）
+    pub burst: Option<BurstConfig>,
+}
+
+impl Default for FlowConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            arrival_rate: 10_000.0,
+            start_price: 10_000,
+            min_price: 1,
+            max_price: 20_000,
+            walk_probability: 0.1,
+            max_step: 1,
+            depth: 10,
+            min_quantity: 1,
+            max_quantity: 100,
+            cancel_ratio: 0.3,
+            marketable_ratio: 0.1,
+            aggression: 2,
+            traders: 16,
+            burst: None,
+        }
+    }
+}
+
+/// 预置场景
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scenario {
+    /// 平稳做市
+    Calm,
+    /// 价格剧烈波动、主动单多
+    Volatile,
+    /// 每秒一次100ms、10倍到达率的突发
+    Burst,
+    /// 大量撤单
+    CancelStorm,
+}
+
+impl Scenario {
+    /// 场景对应的参数
+    pub fn config(self, seed: u64) -> FlowConfig {
+        let base = FlowConfig {
+            seed,
+            ..FlowConfig::default()
+        };
+        match self {
+            Scenario::Calm => base,
+            Scenario::Volatile => FlowConfig {
+                walk_probability: 0.5,
+                max_step: 5,
+                marketable_ratio: 0.4,
+                aggression: 10,
+                ..base
+            },
+            Scenario::Burst => FlowConfig {
+                burst: Some(BurstConfig {
+                    every: Duration::from_secs(1),
+                    length: Duration::from_millis(100),
+                    multiplier: 10.0,
+                }),
+                ..base
+            },
+            Scenario::CancelStorm => FlowConfig {
+                cancel_ratio: 0.8,
+                marketable_ratio: 0.05,
+                ..base
+            },
+        }
+    }
+}
+
+/// splitmix64 随机数发生器
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// [0, 1) 均匀分布
+    #[inline]
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// 以概率`p`返回true
+    #[inline]
+    pub fn chance(&mut self, p: f64) -> bool {
+        p > 0.0 && self.next_f64() < p
+    }
+
+    /// [low, high] 均匀分布整数
+    #[inline]
+    pub fn range(&mut self, low: u32, high: u32) -> u32 {
+        if high <= low {
+            return low;
+        }
+        low + (self.next_u64() % (high - low + 1) as u64) as u32
+    }
+
+    /// 均值为`mean`的指数分布
+    #[inline]
+    pub fn exponential(&mut self, mean: f64) -> f64 {
+        -mean * (1.0 - self.next_f64()).ln()
+    }
+}
+
+/// 生成的指令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlowEvent {
+    /// 相对起点的到达时间（纳秒）
+    pub timestamp_ns: u64,
+    pub command: Command,
+}
+
+/// 订单流生成器
+#[derive(Debug, Clone)]
+pub struct OrderFlow {
+    config: FlowConfig,
+    rng: SplitMix64,
+    now_ns: u64,
+    mid: u32,
+    live: Vec<OrderId>,
+}
+
+impl OrderFlow {
+    pub fn new(config: FlowConfig) -> Self {
+        Self {
+            rng: SplitMix64::new(config.seed),
+            now_ns: 0,
+            mid: config.start_price.clamp(config.min_price, config.max_price),
+            live: Vec::new(),
+            config,
+        }
+    }
+
+    /// 预置场景
+    pub fn scenario(scenario: Scenario, seed: u64) -> Self {
+        Self::new(scenario.config(seed))
+    }
+
+    pub fn config(&self) -> &FlowConfig {
+        &self.config
+    }
+
+    /// 当前中间价
+    pub fn mid(&self) -> u32 {
+        self.mid
+    }
+
+    /// 当前跟踪的可撤挂单数
+    pub fn live_orders(&self) -> usize {
+        self.live.len()
+    }
+
+    /// 记录指令执行结果（登记新挂单ID，供之后撤单）
+    pub fn observe(&mut self, result: &CommandResult) {
+        if let CommandResult::Accepted { order_id, .. } = result {
+            self.track(*order_id);
+        }
+    }
+
+    /// 登记已受理的订单ID（结果不是`CommandResult`时使用，如经TCP收到的受理回报）
+    pub fn track(&mut self, order_id: OrderId) {
+        if self.live.len() >= MAX_TRACKED_ORDERS {
+            let victim = self.rng.next_u64() as usize % self.live.len();
+            self.live.swap_remove(victim);
+        }
+        self.live.push(order_id);
+    }
+
+    /// 生成下一条指令
+    pub fn next_event(&mut self) -> FlowEvent {
+        self.advance_clock();
+        self.walk();
+
+        let command = if !self.live.is_empty() && self.rng.chance(self.config.cancel_ratio) {
+            let index = self.rng.next_u64() as usize % self.live.len();
+            Command::Cancel {
+                order_id: self.live.swap_remove(index),
+            }
+        } else {
+            self.new_order()
+        };
+
+        FlowEvent {
+            timestamp_ns: self.now_ns,
+            command,
+        }
+    }
+
+    /// 在订单簿上执行`count`条指令，返回各条指令及其结果
+    pub fn drive(&mut self, book: &mut OrderBook, count: usize) -> Vec<(FlowEvent, CommandResult)> {
+        (0..count)
+            .map(|_| {
+                let event = self.next_event();
+                let result = event.command.execute(book);
+                self.observe(&result);
+                (event, result)
+            })
+            .collect()
+    }
+
+    fn advance_clock(&mut self) {
+        let mut rate = self.config.arrival_rate.max(f64::MIN_POSITIVE);
+        if let Some(burst) = self.config.burst {
+            let every = burst.every.as_nanos() as u64;
+            if every > 0 && self.now_ns % every < burst.length.as_nanos() as u64 {
+                rate *= burst.multiplier.max(f64::MIN_POSITIVE);
+            }
+        }
+        let gap = self.rng.exponential(1e9 / rate);
+        self.now_ns += (gap as u64).max(1);
+    }
+
+    fn walk(&mut self) {
+        if !self.rng.chance(self.config.walk_probability) {
+            return;
+        }
+        let step = self.rng.range(1, self.config.max_step.max(1));
+        self.mid = if self.rng.chance(0.5) {
+            self.mid.saturating_add(step)
+        } else {
+            self.mid.saturating_sub(step)
+        }
+        .clamp(self.config.min_price.max(1), self.config.max_price);
+    }
+
+    fn new_order(&mut self) -> Command {
+        let side = if self.rng.chance(0.5) { Side::Buy } else { Side::Sell };
+        let trader = self.rng.range(0, self.config.traders.max(1) - 1);
+        let quantity = self.rng.range(self.config.min_quantity.max(1), self.config.max_quantity);
+
+        let (offset, tif) = if self.rng.chance(self.config.marketable_ratio) {
+            (-(self.rng.range(0, self.config.aggression) as i64), TimeInForce::Ioc)
+        } else {
+            (self.rng.range(1, self.config.depth.max(1)) as i64, TimeInForce::Gtc)
+        };
+        // 买单在中间价下方、卖单在上方；负偏移即穿价
+        let price = match side {
+            Side::Buy => self.mid as i64 - offset,
+            Side::Sell => self.mid as i64 + offset,
+        }
+        .clamp(self.config.min_price.max(1) as i64, self.config.max_price as i64);
+
+        Command::Limit {
+            trader: TraderId::from_str(&format!("T{}", trader)),
+            side,
+            price: Price::new(price as u64).unwrap_or(Price::MIN),
+            quantity: Quantity::new(quantity).unwrap_or(Quantity::ONE),
+            tif,
+        }
+    }
+}
+
+impl Iterator for OrderFlow {
+    type Item = FlowEvent;
+
+    fn next(&mut self) -> Option<FlowEvent> {
+        Some(self.next_event())
+    }
+}
+
+/// 指令执行结果与执行后前10档深度的指纹（FNV-1a，忽略成交时间戳）
+pub fn result_fingerprint(result: &CommandResult, book: &OrderBook) -> u64 {
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = depth_checksum(&book.depth(FINGERPRINT_DEPTH));
+    let mut feed = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    };
+    match result {
+        CommandResult::Accepted { order_id, trades } => {
+            feed(&[0]);
+            feed(&order_id.to_le_bytes());
+            for trade in trades {
+                feed(&trade.trade_id.to_le_bytes());
+                feed(trade.buyer.as_bytes());
+                feed(trade.seller.as_bytes());
+                feed(&trade.price.get().to_le_bytes());
+                feed(&trade.quantity.get().to_le_bytes());
+                feed(&[trade.aggressor_side as u8]);
+                feed(&trade.maker_order_id.to_le_bytes());
+                feed(&trade.taker_order_id.to_le_bytes());
+            }
+        }
+        CommandResult::Cancelled { order_id, success } => {
+            feed(&[1, *success as u8]);
+            feed(&order_id.to_le_bytes());
+        }
+        CommandResult::Amended { order_id, success } => {
+            feed(&[2, *success as u8]);
+            feed(&order_id.to_le_bytes());
+        }
+        CommandResult::Expired { order_ids } => {
+            feed(&[3]);
+            for order_id in order_ids {
+                feed(&order_id.to_le_bytes());
+            }
+        }
+        CommandResult::Rejected(reason) => {
+            feed(&[4]);
+            feed(reason.to_string().as_bytes());
+        }
+    }
+    hash
+}
+
+/// 确定性回放录制：指令序列、参考引擎逐条的结果指纹和最终状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+    pub events: Vec<FlowEvent>,
+    /// 每条指令的`result_fingerprint`
+    pub fingerprints: Vec<u64>,
+    /// 参考引擎执行完全部指令后的`export_state`
+    pub final_state: Vec<u8>,
+}
+
+impl Recording {
+    /// 用订单流在参考引擎上录制`count`条指令
+    pub fn capture(flow: &mut OrderFlow, book: &mut OrderBook, count: usize) -> Self {
+        let mut events = Vec::with_capacity(count);
+        let mut fingerprints = Vec::with_capacity(count);
+        for _ in 0..count {
+            let event = flow.next_event();
+            let result = event.command.execute(book);
+            flow.observe(&result);
+            fingerprints.push(result_fingerprint(&result, book));
+            events.push(event);
+        }
+        Self {
+            events,
+            fingerprints,
+            final_state: book.export_state(),
+        }
+    }
+
+    /// 在另一个引擎上回放，返回第一处分歧
+    ///
+    /// 逐条比较结果指纹（`DivergenceKind::Checksum`），全部一致后再比较最终状态。
+    pub fn replay(&self, book: &mut OrderBook) -> Result<(), Divergence> {
+        for (index, (event, &expected)) in self.events.iter().zip(&self.fingerprints).enumerate() {
+            let result = event.command.execute(book);
+            let actual = result_fingerprint(&result, book);
+            if actual != expected {
+                return Err(Divergence {
+                    sequence: index as u64 + 1,
+                    command: event.command,
+                    kind: DivergenceKind::Checksum { primary: expected, shadow: actual },
+                });
+            }
+        }
+        if book.export_state() != self.final_state {
+            let last = self.events.last().map_or(Command::Expire { now_ns: 0 }, |event| event.command);
+            return Err(Divergence {
+                sequence: self.events.len() as u64,
+                command: last,
+                kind: DivergenceKind::State,
+            });
+        }
+        Ok(())
+    }
+
+    /// 序列化（保存后可回放到新版本引擎）
+    ///
+    /// 格式（little-endian）: 4字节魔数 "RREC" + 4字节指令数；
+    /// 每条: `JournalEntry`编码（序列号为指令序号，时间戳为到达时间）+ 8字节指纹；
+    /// 最后4字节状态长度 + 状态。
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(RECORDING_MAGIC)?;
+        writer.write_all(&(self.events.len() as u32).to_le_bytes())?;
+        for (index, (event, fingerprint)) in self.events.iter().zip(&self.fingerprints).enumerate() {
+            let entry = JournalEntry {
+                sequence: index as u64 + 1,
+                timestamp_ns: event.timestamp_ns,
+                command: event.command,
+            };
+            entry.write_to(writer)?;
+            writer.write_all(&fingerprint.to_le_bytes())?;
+        }
+        writer.write_all(&(self.final_state.len() as u32).to_le_bytes())?;
+        writer.write_all(&self.final_state)
+    }
+
+    /// 反序列化
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        let mut word = [0u8; 4];
+        reader.read_exact(&mut word)?;
+        if &word != RECORDING_MAGIC {
+            return Err(invalid("not a replay recording"));
+        }
+        reader.read_exact(&mut word)?;
+        let count = u32::from_le_bytes(word) as usize;
+
+        let mut events = Vec::with_capacity(count);
+        let mut fingerprints = Vec::with_capacity(count);
+        for _ in 0..count {
+            let entry = JournalEntry::read_from(reader)?.ok_or_else(|| invalid("truncated recording"))?;
+            let mut fingerprint = [0u8; 8];
+            reader.read_exact(&mut fingerprint)?;
+            events.push(FlowEvent {
+                timestamp_ns: entry.timestamp_ns,
+                command: entry.command,
+            });
+            fingerprints.push(u64::from_le_bytes(fingerprint));
+        }
+
+        reader.read_exact(&mut word)?;
+        let mut final_state = vec![0u8; u32::from_le_bytes(word) as usize];
+        reader.read_exact(&mut final_state)?;
+        Ok(Self {
+            events,
+            fingerprints,
+            final_state,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 价格阶梯只需覆盖默认价格范围
+    fn book() -> OrderBook {
+        OrderBook::with_capacity(FlowConfig::default().max_price as usize + 1, 100_000)
+    }
+
+    #[test]
+    fn test_same_seed_same_flow() {
+        let run = |seed| {
+            let mut book = book();
+            OrderFlow::scenario(Scenario::Volatile, seed)
+                .drive(&mut book, 2_000)
+                .into_iter()
+                .map(|(event, _)| event)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn test_flow_shape() {
+        let mut book = book();
+        let mut flow = OrderFlow::new(FlowConfig {
+            seed: 1,
+            cancel_ratio: 0.5,
+            ..FlowConfig::default()
+        });
+        let events = flow.drive(&mut book, 10_000);
+
+        let cancels = events
+            .iter()
+            .filter(|(event, _)| matches!(event.command, Command::Cancel { .. }))
+            .count();
+        assert!((4_000..6_000).contains(&cancels), "cancels {}", cancels);
+        assert!(events.windows(2).all(|w| w[0].0.timestamp_ns < w[1].0.timestamp_ns));
+        assert!(events.iter().all(|(_, result)| !result.is_rejected()));
+
+        // 平均到达间隔约100µs
+        let elapsed = events.last().unwrap().0.timestamp_ns;
+        assert!((800_000_000..1_200_000_000).contains(&elapsed), "elapsed {}", elapsed);
+    }
+
+    #[test]
+    fn test_burst_raises_arrival_rate() {
+        let mut flow = OrderFlow::scenario(Scenario::Burst, 3);
+        let events: Vec<_> = flow.by_ref().take(50_000).collect();
+        let inside = events
+            .iter()
+            .filter(|event| event.timestamp_ns % 1_000_000_000 < 100_000_000)
+            .count();
+        let outside = events.len() - inside;
+        // 突发窗口占10%的时间，到达率10倍
+        assert!(inside > outside / 2, "inside {} outside {}", inside, outside);
+    }
+
+    #[test]
+    fn test_replay_is_deterministic() {
+        let mut reference = book();
+        let recording = Recording::capture(&mut OrderFlow::scenario(Scenario::Volatile, 11), &mut reference, 5_000);
+
+        let mut bytes = Vec::new();
+        recording.write_to(&mut bytes).unwrap();
+        let loaded = Recording::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(loaded, recording);
+        assert_eq!(loaded.replay(&mut book()), Ok(()));
+
+        // 第一条指令被改写后，回放在该处报告分歧
+        let mut altered = recording.clone();
+        if let Command::Limit { ref mut quantity, .. } = altered.events[0].command {
+            *quantity = Quantity::new(quantity.get() + 1).unwrap();
+        }
+        let divergence = altered.replay(&mut book()).unwrap_err();
+        assert_eq!(divergence.sequence, 1);
+        assert!(matches!(divergence.kind, DivergenceKind::Checksum { .. }));
+    }
+}
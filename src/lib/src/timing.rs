@@ -0,0 +1,463 @@
+/// 高精度时间源
+///
+/// `SystemTime::now()`每次调用都要进入vDSO/系统调用，开销较大，且会随NTP校时回拨。
+/// 这里在进程内校准一次硬件计数器（x86_64的不变TSC、aarch64的`cntvct_el0`），
+/// 之后只读计数器并换算为纳秒：单调递增，以校准时刻的墙钟时间为基准。
+/// 不支持的平台或TSC不可靠（非不变TSC）时退化为`Instant`。
+///
+/// 长时间运行后与墙钟可能有微小漂移，需要对齐墙钟时可调用`recalibrate`。
+/// 首次使用会阻塞约`CALIBRATION`时长完成校准，建议在启动时调用`calibrate()`。
+///
+/// 需要打时间戳的组件（撮合引擎、指令日志、FIX会话）通过`Clock` trait取时间，
+/// 默认`SystemClock`读全局时钟，测试中注入`MockClock`得到确定的时间戳。
+///
+/// 订阅端按生产端时间戳判断消息是否过期的`StalenessGuard`也放在这里。
+
+use parking_lot::RwLock;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// TSC校准时长
+pub const CALIBRATION: Duration = Duration::from_millis(10);
+
+/// 计数器到纳秒换算的定点小数位数
+const SCALE_SHIFT: u32 = 32;
+
+/// 时间源类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockSource {
+    /// x86_64 不变TSC
+    Tsc,
+    /// aarch64 虚拟计数器
+    Cntvct,
+    /// `std::time::Instant`
+    Instant,
+}
+
+/// 基准点：计数器读数与对应的墙钟时间
+#[derive(Debug, Clone, Copy)]
+struct Anchor {
+    ticks: u64,
+    epoch_ns: u64,
+}
+
+/// 已校准的纳秒时钟
+pub struct TscClock {
+    source: ClockSource,
+    /// 每计数的纳秒数（定点，左移`SCALE_SHIFT`位）
+    ns_per_tick: u64,
+    /// `Instant`回退时的起点
+    origin: Instant,
+    anchor: RwLock<Anchor>,
+}
+
+impl TscClock {
+    /// 检测可用的计数器并校准
+    pub fn new() -> Self {
+        let origin = Instant::now();
+        let (source, ns_per_tick) = match hw::source() {
+            Some(ClockSource::Cntvct) => (ClockSource::Cntvct, scale(1_000_000_000, hw::frequency())),
+            Some(ClockSource::Tsc) => match calibrate_tsc() {
+                Some(ns_per_tick) => (ClockSource::Tsc, ns_per_tick),
+                None => (ClockSource::Instant, 1 << SCALE_SHIFT),
+            },
+            _ => (ClockSource::Instant, 1 << SCALE_SHIFT),
+        };
+
+        let clock = Self {
+            source,
+            ns_per_tick,
+            origin,
+            anchor: RwLock::new(Anchor { ticks: 0, epoch_ns: 0 }),
+        };
+        clock.recalibrate();
+        clock
+    }
+
+    /// 时间源类型
+    pub fn source(&self) -> ClockSource {
+        self.source
+    }
+
+    /// 重新对齐墙钟（频率不变，只更新基准点）
+    ///
+    /// 对齐后时间戳可能跳变（包括回退），不要在需要单调性的测量过程中调用。
+    pub fn recalibrate(&self) {
+        let ticks = self.ticks();
+        let epoch_ns = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        *self.anchor.write() = Anchor { ticks, epoch_ns };
+    }
+
+    /// 原始计数器读数（用于测量耗时，配合`ticks_to_ns`）
+    #[inline]
+    pub fn ticks(&self) -> u64 {
+        match self.source {
+            ClockSource::Instant => self.origin.elapsed().as_nanos() as u64,
+            _ => hw::ticks(),
+        }
+    }
+
+    /// 计数差换算为纳秒
+    #[inline]
+    pub fn ticks_to_ns(&self, ticks: u64) -> u64 {
+        ((ticks as u128 * self.ns_per_tick as u128) >> SCALE_SHIFT) as u64
+    }
+
+    /// 自两次读数之间经过的纳秒数
+    #[inline]
+    pub fn elapsed_ns(&self, start_ticks: u64) -> u64 {
+        self.ticks_to_ns(self.ticks().saturating_sub(start_ticks))
+    }
+
+    /// 当前纳秒时间戳（自1970-01-01，单调递增）
+    #[inline]
+    pub fn now_ns(&self) -> u64 {
+        let anchor = *self.anchor.read();
+        anchor.epoch_ns + self.ticks_to_ns(self.ticks().saturating_sub(anchor.ticks))
+    }
+}
+
+impl Default for TscClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `ns / ticks`的定点表示
+fn scale(ns: u64, ticks: u64) -> u64 {
+    if ticks == 0 {
+        return 1 << SCALE_SHIFT;
+    }
+    (((ns as u128) << SCALE_SHIFT) / ticks as u128) as u64
+}
+
+/// 对照`Instant`测量TSC频率
+fn calibrate_tsc() -> Option<u64> {
+    let start = Instant::now();
+    let start_ticks = hw::ticks();
+    while start.elapsed() < CALIBRATION {
+        std::hint::spin_loop();
+    }
+    let elapsed = start.elapsed().as_nanos() as u64;
+    let ticks = hw::ticks().wrapping_sub(start_ticks);
+
+    // 计数器不走或倒退时不可用
+    (ticks > 0 && ticks < u64::MAX / 2).then(|| scale(elapsed, ticks))
+}
+
+#[cfg(target_arch = "x86_64")]
+mod hw {
+    use super::ClockSource;
+    use std::arch::x86_64::{__cpuid, _rdtsc};
+
+    /// 仅在CPU声明不变TSC（CPUID 0x80000007 EDX[8]）时使用
+    pub fn source() -> Option<ClockSource> {
+        let max_extended = __cpuid(0x8000_0000).eax;
+        if max_extended < 0x8000_0007 {
+            return None;
+        }
+        let invariant = __cpuid(0x8000_0007).edx & (1 << 8) != 0;
+        invariant.then_some(ClockSource::Tsc)
+    }
+
+    #[inline]
+    pub fn ticks() -> u64 {
+        // SAFETY: x86_64上RDTSC总是可用
+        unsafe { _rdtsc() }
+    }
+
+    pub fn frequency() -> u64 {
+        0
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod hw {
+    use super::ClockSource;
+    use std::arch::asm;
+
+    pub fn source() -> Option<ClockSource> {
+        (frequency() > 0).then_some(ClockSource::Cntvct)
+    }
+
+    #[inline]
+    pub fn ticks() -> u64 {
+        let ticks: u64;
+        // SAFETY: EL0可读cntvct_el0（Linux/macOS默认开放）
+        unsafe { asm!("mrs {}, cntvct_el0", out(reg) ticks, options(nomem, nostack)) };
+        ticks
+    }
+
+    pub fn frequency() -> u64 {
+        let frequency: u64;
+        // SAFETY: EL0可读cntfrq_el0
+        unsafe { asm!("mrs {}, cntfrq_el0", out(reg) frequency, options(nomem, nostack)) };
+        frequency
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod hw {
+    use super::ClockSource;
+
+    pub fn source() -> Option<ClockSource> {
+        None
+    }
+
+    pub fn ticks() -> u64 {
+        0
+    }
+
+    pub fn frequency() -> u64 {
+        0
+    }
+}
+
+/// 可注入的时间源
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// 当前纳秒时间戳（自1970-01-01）
+    fn now_ns(&self) -> u64;
+}
+
+/// 读全局时钟`now_ns()`的时间源
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now_ns(&self) -> u64 {
+        now_ns()
+    }
+}
+
+/// 手动推进的时间源（克隆共享同一时间）
+#[derive(Debug, Clone, Default)]
+pub struct MockClock {
+    now_ns: Arc<AtomicU64>,
+}
+
+impl MockClock {
+    pub fn new(now_ns: u64) -> Self {
+        Self { now_ns: Arc::new(AtomicU64::new(now_ns)) }
+    }
+
+    /// 设置当前时间
+    pub fn set(&self, now_ns: u64) {
+        self.now_ns.store(now_ns, Ordering::Relaxed);
+    }
+
+    /// 推进时间
+    pub fn advance(&self, by: Duration) {
+        self.now_ns.fetch_add(by.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+impl Clock for MockClock {
+    #[inline]
+    fn now_ns(&self) -> u64 {
+        self.now_ns.load(Ordering::Relaxed)
+    }
+}
+
+/// 默认时间源
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+static CLOCK: OnceLock<TscClock> = OnceLock::new();
+
+/// 全局时钟（首次调用时校准）
+#[inline]
+pub fn clock() -> &'static TscClock {
+    CLOCK.get_or_init(TscClock::new)
+}
+
+/// 启动时预先校准全局时钟，返回时间源类型
+pub fn calibrate() -> ClockSource {
+    clock().source()
+}
+
+/// 当前纳秒时间戳（自1970-01-01，单调递增）
+#[inline]
+pub fn now_ns() -> u64 {
+    clock().now_ns()
+}
+
+/// 原始计数器读数
+#[inline]
+pub fn ticks() -> u64 {
+    clock().ticks()
+}
+
+/// 自`start_ticks`以来经过的纳秒数
+#[inline]
+pub fn elapsed_ns(start_ticks: u64) -> u64 {
+    clock().elapsed_ns(start_ticks)
+}
+
+/// 消息时效策略
+///
+/// 生产端时间戳早于`ttl`的消息视为过期；`drop_stale`为true时在交给用户回调前丢弃，
+/// 否则只计数。生产端时钟快于本地（时间戳在未来）时按未过期处理。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StalenessPolicy {
+    /// 最大允许时延
+    pub ttl: Duration,
+    /// 是否丢弃过期消息
+    pub drop_stale: bool,
+}
+
+impl Default for StalenessPolicy {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(1),
+            drop_stale: true,
+        }
+    }
+}
+
+/// 按时效策略过滤消息并计数（可跨任务共享）
+#[derive(Debug, Default)]
+pub struct StalenessGuard {
+    policy: StalenessPolicy,
+    stale: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl StalenessGuard {
+    pub fn new(policy: StalenessPolicy) -> Self {
+        Self {
+            policy,
+            stale: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    pub fn policy(&self) -> StalenessPolicy {
+        self.policy
+    }
+
+    /// 以当前时间判断消息是否应交给回调
+    #[inline]
+    pub fn admit(&self, producer_ns: u64) -> bool {
+        self.admit_at(producer_ns, now_ns())
+    }
+
+    /// 以给定时间判断消息是否应交给回调
+    pub fn admit_at(&self, producer_ns: u64, now_ns: u64) -> bool {
+        if !self.observe_at(producer_ns, now_ns) || !self.policy.drop_stale {
+            return true;
+        }
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        false
+    }
+
+    /// 只判断并计数是否过期，不论策略都不丢弃（用于必须处理的消息）
+    #[inline]
+    pub fn observe(&self, producer_ns: u64) -> bool {
+        self.observe_at(producer_ns, now_ns())
+    }
+
+    /// 以给定时间判断是否过期并计数
+    pub fn observe_at(&self, producer_ns: u64, now_ns: u64) -> bool {
+        let stale = now_ns.saturating_sub(producer_ns) > self.policy.ttl.as_nanos() as u64;
+        if stale {
+            self.stale.fetch_add(1, Ordering::Relaxed);
+        }
+        stale
+    }
+
+    /// 过期消息数（含已丢弃）
+    pub fn stale(&self) -> u64 {
+        self.stale.load(Ordering::Relaxed)
+    }
+
+    /// 丢弃的过期消息数
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wall_ns() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64
+    }
+
+    #[test]
+    fn test_close_to_wall_clock() {
+        let clock = TscClock::new();
+        let diff = clock.now_ns().abs_diff(wall_ns());
+        assert!(diff < 50_000_000, "source {:?} off by {} ns", clock.source(), diff);
+    }
+
+    #[test]
+    fn test_monotonic() {
+        let mut last = now_ns();
+        for _ in 0..100_000 {
+            let now = now_ns();
+            assert!(now >= last);
+            last = now;
+        }
+    }
+
+    #[test]
+    fn test_elapsed_matches_sleep() {
+        let start = ticks();
+        std::thread::sleep(Duration::from_millis(20));
+        let elapsed = elapsed_ns(start);
+        assert!((15_000_000..500_000_000).contains(&elapsed), "elapsed {} ns", elapsed);
+    }
+
+    #[test]
+    fn test_staleness_guard() {
+        let guard = StalenessGuard::new(StalenessPolicy {
+            ttl: Duration::from_nanos(100),
+            drop_stale: true,
+        });
+        assert!(guard.admit_at(1_000, 1_100));
+        assert!(!guard.admit_at(1_000, 1_101));
+        // 生产端时钟超前
+        assert!(guard.admit_at(2_000, 1_000));
+        assert_eq!((guard.stale(), guard.dropped()), (1, 1));
+        assert!(guard.observe_at(0, 1_000));
+        assert_eq!((guard.stale(), guard.dropped()), (2, 1));
+
+        let counting = StalenessGuard::new(StalenessPolicy {
+            ttl: Duration::from_nanos(100),
+            drop_stale: false,
+        });
+        assert!(counting.admit_at(0, 1_000));
+        assert_eq!((counting.stale(), counting.dropped()), (1, 0));
+    }
+
+    #[test]
+    fn test_mock_clock() {
+        let clock = MockClock::new(1_000);
+        let shared: Arc<dyn Clock> = Arc::new(clock.clone());
+        assert_eq!(shared.now_ns(), 1_000);
+        clock.advance(Duration::from_micros(2));
+        assert_eq!(shared.now_ns(), 3_000);
+        clock.set(10);
+        assert_eq!(shared.now_ns(), 10);
+        assert!(SystemClock.now_ns().abs_diff(wall_ns()) < 50_000_000);
+    }
+
+    #[test]
+    fn test_scale() {
+        assert_eq!(scale(1_000, 1_000), 1 << SCALE_SHIFT);
+        let clock = TscClock {
+            source: ClockSource::Instant,
+            ns_per_tick: scale(1, 3),
+            origin: Instant::now(),
+            anchor: RwLock::new(Anchor { ticks: 0, epoch_ns: 0 }),
+        };
+        assert_eq!(clock.ticks_to_ns(3_000_000_000), 999_999_999);
+    }
+}
@@ -0,0 +1,35 @@
+//! 嵌入式时序存储
+//!
+//! 持久化引擎成交和合并行情，供分析和K线模块按时间区间查询。
+//!
+//! # 存储布局
+//!
+//! - 按UTC日期切分段文件：`<dir>/<YYYYMMDD>.seg`
+//! - 每个段文件附带稀疏时间索引：`<dir>/<YYYYMMDD>.idx`
+//!   （每隔`index_interval`条记录写入一个`(时间戳, 偏移量)`条目）
+//! - 记录按追加顺序写入，时间戳需单调不减
+//!
+//! # 示例
+//!
+//! ```no_run
+//! use lib::orderbook::{OrderBook, Price, Quantity, Side, TimeInForce, TraderId};
+//! use lib::tsdb::{StoreConfig, TimeSeriesStore, TradeRecord};
+//!
+//! let mut store = TimeSeriesStore::open("./data/tsdb", StoreConfig::default()).unwrap();
+//! let mut book = OrderBook::new();
+//! let (price, quantity) = (Price::new(10000).unwrap(), Quantity::new(5).unwrap());
+//! book.limit_order(TraderId::from_str("S"), Side::Sell, price, quantity, TimeInForce::Gtc).unwrap();
+//! let (_, trades) = book.limit_order(TraderId::from_str("B"), Side::Buy, price, quantity, TimeInForce::Gtc).unwrap();
+//! for trade in &trades {
+//!     store.append_trade(&TradeRecord::from_trade("BTCUSDT", trade.timestamp_ns, trade)).unwrap();
+//! }
+//! store.flush().unwrap();
+//!
+//! let trades = store.trades_between("BTCUSDT", 0, u64::MAX).unwrap();
+//! ```
+
+pub mod record;  // 记录类型与编解码
+pub mod store;   // 段文件存储与查询
+
+pub use record::{TickerRecord, TradeRecord};
+pub use store::{StoreConfig, StoreTradeSink, TimeSeriesStore};
@@ -0,0 +1,212 @@
+/// 时序存储记录类型
+///
+/// 记录二进制格式（little-endian）:
+/// - 1字节: 记录类型（1=成交, 2=行情）
+/// - 8字节: 时间戳（纳秒）
+/// - 1字节: 品种代码长度 + N字节品种代码
+/// - 类型相关字段
+
+use crate::orderbook::types::{Price, Quantity, Trade, TraderId};
+use std::io::{self, Read};
+
+/// 成交记录类型标识
+pub const RECORD_TRADE: u8 = 1;
+/// 行情记录类型标识
+pub const RECORD_TICKER: u8 = 2;
+
+/// 成交记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeRecord {
+    pub timestamp_ns: u64,    // 成交时间（纳秒）
+    pub symbol: String,       // 品种
+    pub buyer: TraderId,      // 买方
+    pub seller: TraderId,     // 卖方
+    pub price: Price,         // 成交价格
+    pub quantity: Quantity,   // 成交数量
+}
+
+impl TradeRecord {
+    /// 从引擎成交创建记录
+    pub fn from_trade(symbol: &str, timestamp_ns: u64, trade: &Trade) -> Self {
+        Self {
+            timestamp_ns,
+            symbol: symbol.to_string(),
+            buyer: trade.buyer,
+            seller: trade.seller,
+            price: trade.price,
+            quantity: trade.quantity,
+        }
+    }
+}
+
+/// 合并行情记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TickerRecord {
+    pub timestamp_ns: u64,         // 行情时间（纳秒）
+    pub symbol: String,            // 品种
+    pub last_price: Price,         // 最新价
+    pub best_bid: Option<Price>,   // 最佳买价
+    pub best_ask: Option<Price>,   // 最佳卖价
+}
+
+/// 存储中的一条记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Record {
+    Trade(TradeRecord),
+    Ticker(TickerRecord),
+}
+
+impl Record {
+    /// 获取记录时间戳
+    #[inline]
+    pub fn timestamp_ns(&self) -> u64 {
+        match self {
+            Record::Trade(r) => r.timestamp_ns,
+            Record::Ticker(r) => r.timestamp_ns,
+        }
+    }
+
+    /// 获取记录品种
+    #[inline]
+    pub fn symbol(&self) -> &str {
+        match self {
+            Record::Trade(r) => &r.symbol,
+            Record::Ticker(r) => &r.symbol,
+        }
+    }
+
+    /// 序列化追加到缓冲区
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        let (kind, timestamp_ns, symbol) = match self {
+            Record::Trade(r) => (RECORD_TRADE, r.timestamp_ns, &r.symbol),
+            Record::Ticker(r) => (RECORD_TICKER, r.timestamp_ns, &r.symbol),
+        };
+
+        let symbol = &symbol.as_bytes()[..symbol.len().min(u8::MAX as usize)];
+        buf.push(kind);
+        buf.extend_from_slice(&timestamp_ns.to_le_bytes());
+        buf.push(symbol.len() as u8);
+        buf.extend_from_slice(symbol);
+
+        match self {
+            Record::Trade(r) => {
+                buf.extend_from_slice(r.buyer.as_bytes());
+                buf.extend_from_slice(r.seller.as_bytes());
+                buf.extend_from_slice(&r.price.get().to_le_bytes());
+                buf.extend_from_slice(&r.quantity.get().to_le_bytes());
+            }
+            Record::Ticker(r) => {
+                buf.extend_from_slice(&r.last_price.get().to_le_bytes());
+                let presence = r.best_bid.is_some() as u8 | (r.best_ask.is_some() as u8) << 1;
+                buf.push(presence);
+                buf.extend_from_slice(&r.best_bid.map_or(0, Price::get).to_le_bytes());
+                buf.extend_from_slice(&r.best_ask.map_or(0, Price::get).to_le_bytes());
+            }
+        }
+    }
+
+    /// 从读取器反序列化一条记录
+    ///
+    /// 到达文件末尾时返回`Ok(None)`，末尾不完整的记录视为末尾
+    pub fn decode<R: Read>(reader: &mut R) -> io::Result<Option<Self>> {
+        let mut kind = [0u8; 1];
+        match reader.read_exact(&mut kind) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        match Self::decode_body(kind[0], reader) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            other => other.map(Some),
+        }
+    }
+
+    fn decode_body<R: Read>(kind: u8, reader: &mut R) -> io::Result<Self> {
+        let timestamp_ns = u64::from_le_bytes(read_array(reader)?);
+        let [len] = read_array::<_, 1>(reader)?;
+        let mut symbol = vec![0u8; len as usize];
+        reader.read_exact(&mut symbol)?;
+        let symbol = String::from_utf8(symbol)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid symbol"))?;
+
+        match kind {
+            RECORD_TRADE => Ok(Record::Trade(TradeRecord {
+                timestamp_ns,
+                symbol,
+                buyer: TraderId::new(read_array(reader)?),
+                seller: TraderId::new(read_array(reader)?),
+                price: read_price(reader)?,
+                quantity: Quantity::new(u32::from_le_bytes(read_array(reader)?))
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "zero trade quantity"))?,
+            })),
+            RECORD_TICKER => {
+                let last_price = read_price(reader)?;
+                let [presence] = read_array::<_, 1>(reader)?;
+                let bid = Price::new(u64::from_le_bytes(read_array(reader)?));
+                let ask = Price::new(u64::from_le_bytes(read_array(reader)?));
+                Ok(Record::Ticker(TickerRecord {
+                    timestamp_ns,
+                    symbol,
+                    last_price,
+                    best_bid: bid.filter(|_| presence & 0x01 != 0),
+                    best_ask: ask.filter(|_| presence & 0x02 != 0),
+                }))
+            }
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown record type {}", other),
+            )),
+        }
+    }
+}
+
+fn read_price<R: Read>(reader: &mut R) -> io::Result<Price> {
+    Price::new(u64::from_le_bytes(read_array(reader)?))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "zero price"))
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty};
+
+    #[test]
+    fn test_record_roundtrip() {
+        let trade = Record::Trade(TradeRecord {
+            timestamp_ns: 42,
+            symbol: "BTCUSDT".to_string(),
+            buyer: TraderId::from_str("BUYER"),
+            seller: TraderId::from_str("SELLER"),
+            price: px(10000),
+            quantity: qty(7),
+        });
+        let ticker = Record::Ticker(TickerRecord {
+            timestamp_ns: 43,
+            symbol: "BTCUSDT".to_string(),
+            last_price: px(10000),
+            best_bid: Some(px(9999)),
+            best_ask: None,
+        });
+
+        let mut buf = Vec::new();
+        trade.encode(&mut buf);
+        ticker.encode(&mut buf);
+
+        let mut reader = &buf[..];
+        assert_eq!(Record::decode(&mut reader).unwrap(), Some(trade));
+        assert_eq!(Record::decode(&mut reader).unwrap(), Some(ticker));
+        assert_eq!(Record::decode(&mut reader).unwrap(), None);
+
+        // 截断的记录视为末尾
+        let mut truncated = &buf[..buf.len() - 3];
+        assert!(Record::decode(&mut truncated).unwrap().is_some());
+        assert_eq!(Record::decode(&mut truncated).unwrap(), None);
+    }
+}
@@ -0,0 +1,427 @@
+/// 按日期分段的时序存储
+///
+/// 每个UTC日期一个段文件和一个稀疏索引文件，
+/// 查询时通过索引定位起始偏移量后顺序扫描。
+
+use super::record::{Record, TickerRecord, TradeRecord};
+use crate::orderbook::engine::TradeSink;
+use crate::orderbook::types::Trade;
+use parking_lot::Mutex;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// 每天的纳秒数
+const NANOS_PER_DAY: u64 = 86_400 * 1_000_000_000;
+
+/// 索引条目大小：8字节时间戳 + 8字节偏移量
+const INDEX_ENTRY_SIZE: usize = 16;
+
+/// 存储配置
+#[derive(Debug, Clone)]
+pub struct StoreConfig {
+    /// 每隔多少条记录写入一个索引条目
+    pub index_interval: usize,
+    /// flush时是否同步到磁盘（fsync）
+    pub sync_on_flush: bool,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            index_interval: 256,
+            sync_on_flush: false,
+        }
+    }
+}
+
+/// 当前写入的段文件
+struct ActiveSegment {
+    day: u64,
+    data: BufWriter<File>,
+    index: BufWriter<File>,
+    offset: u64,
+    since_index: usize,
+}
+
+/// 嵌入式时序存储
+pub struct TimeSeriesStore {
+    dir: PathBuf,
+    config: StoreConfig,
+    active: Option<ActiveSegment>,
+    buf: Vec<u8>,
+}
+
+impl TimeSeriesStore {
+    /// 打开（或创建）存储目录
+    pub fn open(dir: impl AsRef<Path>, config: StoreConfig) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            config,
+            active: None,
+            buf: Vec::with_capacity(64),
+        })
+    }
+
+    /// 获取存储目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 追加成交记录
+    pub fn append_trade(&mut self, record: &TradeRecord) -> io::Result<()> {
+        self.append(&Record::Trade(record.clone()))
+    }
+
+    /// 追加行情记录
+    pub fn append_ticker(&mut self, record: &TickerRecord) -> io::Result<()> {
+        self.append(&Record::Ticker(record.clone()))
+    }
+
+    /// 追加一条记录（时间戳需单调不减）
+    pub fn append(&mut self, record: &Record) -> io::Result<()> {
+        let timestamp_ns = record.timestamp_ns();
+        let day = timestamp_ns / NANOS_PER_DAY;
+
+        if self.active.as_ref().is_none_or(|s| s.day != day) {
+            self.rotate(day)?;
+        }
+
+        self.buf.clear();
+        record.encode(&mut self.buf);
+
+        let interval = self.config.index_interval.max(1);
+        let segment = self.active.as_mut().unwrap();
+
+        if segment.since_index >= interval {
+            segment.index.write_all(&timestamp_ns.to_le_bytes())?;
+            segment.index.write_all(&segment.offset.to_le_bytes())?;
+            segment.since_index = 0;
+        }
+
+        segment.data.write_all(&self.buf)?;
+        segment.offset += self.buf.len() as u64;
+        segment.since_index += 1;
+        Ok(())
+    }
+
+    /// 将缓冲数据写入文件
+    pub fn flush(&mut self) -> io::Result<()> {
+        if let Some(segment) = self.active.as_mut() {
+            segment.data.flush()?;
+            segment.index.flush()?;
+            if self.config.sync_on_flush {
+                segment.data.get_ref().sync_data()?;
+                segment.index.get_ref().sync_data()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 查询时间区间 [t0, t1] 内某品种的成交
+    pub fn trades_between(&mut self, symbol: &str, t0: u64, t1: u64) -> io::Result<Vec<TradeRecord>> {
+        let mut trades = Vec::new();
+        self.scan(t0, t1, |record| {
+            match record {
+                Record::Trade(trade) if trade.symbol == symbol => trades.push(trade),
+                _ => {}
+            }
+        })?;
+        Ok(trades)
+    }
+
+    /// 查询时间区间 [t0, t1] 内某品种的行情
+    pub fn tickers_between(&mut self, symbol: &str, t0: u64, t1: u64) -> io::Result<Vec<TickerRecord>> {
+        let mut tickers = Vec::new();
+        self.scan(t0, t1, |record| {
+            match record {
+                Record::Ticker(ticker) if ticker.symbol == symbol => tickers.push(ticker),
+                _ => {}
+            }
+        })?;
+        Ok(tickers)
+    }
+
+    /// 按时间顺序遍历区间 [t0, t1] 内的所有记录
+    pub fn scan<F>(&mut self, t0: u64, t1: u64, mut f: F) -> io::Result<()>
+    where
+        F: FnMut(Record),
+    {
+        if t0 > t1 {
+            return Ok(());
+        }
+        self.flush()?;
+
+        for day in self.segment_days()? {
+            if day < t0 / NANOS_PER_DAY || day > t1 / NANOS_PER_DAY {
+                continue;
+            }
+
+            let start = self.seek_offset(day, t0)?;
+            let mut file = File::open(self.segment_path(day, "seg"))?;
+            file.seek(SeekFrom::Start(start))?;
+            let mut reader = BufReader::new(file);
+
+            while let Some(record) = Record::decode(&mut reader)? {
+                let ts = record.timestamp_ns();
+                if ts > t1 {
+                    break;
+                }
+                if ts >= t0 {
+                    f(record);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 切换到新的段文件
+    fn rotate(&mut self, day: u64) -> io::Result<()> {
+        self.flush()?;
+
+        let data = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.segment_path(day, "seg"))?;
+        let index = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.segment_path(day, "idx"))?;
+        let offset = data.metadata()?.len();
+
+        self.active = Some(ActiveSegment {
+            day,
+            data: BufWriter::new(data),
+            index: BufWriter::new(index),
+            offset,
+            // 段内第一条记录总是写入索引
+            since_index: usize::MAX,
+        });
+        Ok(())
+    }
+
+    /// 通过稀疏索引查找不晚于t0的最近偏移量
+    fn seek_offset(&self, day: u64, t0: u64) -> io::Result<u64> {
+        let mut bytes = Vec::new();
+        match File::open(self.segment_path(day, "idx")) {
+            Ok(mut file) => {
+                file.read_to_end(&mut bytes)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        }
+
+        let entries: Vec<(u64, u64)> = bytes
+            .chunks_exact(INDEX_ENTRY_SIZE)
+            .map(|c| {
+                (
+                    u64::from_le_bytes(c[0..8].try_into().unwrap()),
+                    u64::from_le_bytes(c[8..16].try_into().unwrap()),
+                )
+            })
+            .collect();
+
+        let pos = entries.partition_point(|(ts, _)| *ts < t0);
+        Ok(if pos == 0 { 0 } else { entries[pos - 1].1 })
+    }
+
+    /// 列出已有段文件的日期（升序）
+    fn segment_days(&self) -> io::Result<Vec<u64>> {
+        let mut days = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("seg") {
+                continue;
+            }
+            if let Some(day) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(parse_date)
+            {
+                days.push(day);
+            }
+        }
+        days.sort_unstable();
+        Ok(days)
+    }
+
+    fn segment_path(&self, day: u64, ext: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", format_date(day), ext))
+    }
+}
+
+impl Drop for TimeSeriesStore {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// 将引擎成交写入时序存储的输出
+pub struct StoreTradeSink {
+    symbol: String,
+    store: Arc<Mutex<TimeSeriesStore>>,
+}
+
+impl StoreTradeSink {
+    /// 创建指定品种的成交输出
+    pub fn new(symbol: impl Into<String>, store: Arc<Mutex<TimeSeriesStore>>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            store,
+        }
+    }
+}
+
+impl TradeSink for StoreTradeSink {
+    fn on_trade(&mut self, trade: &Trade) {
+        // 按成交时间入库，回放或恢复的成交落在原始时间上
+        let record = TradeRecord::from_trade(&self.symbol, trade.timestamp_ns, trade);
+
+        if let Err(e) = self.store.lock().append_trade(&record) {
+            crate::runtime_log!(Warn, "Failed to persist trade: {}", e);
+        }
+    }
+}
+
+/// 天数（自1970-01-01）格式化为YYYYMMDD
+fn format_date(day: u64) -> String {
+    // Howard Hinnant的civil_from_days算法
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + (m <= 2) as i64;
+    format!("{:04}{:02}{:02}", y, m, d)
+}
+
+/// 解析YYYYMMDD为天数（自1970-01-01）
+fn parse_date(s: &str) -> Option<u64> {
+    if s.len() != 8 || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let y: i64 = s[0..4].parse().ok()?;
+    let m: i64 = s[4..6].parse().ok()?;
+    let d: i64 = s[6..8].parse().ok()?;
+
+    // days_from_civil
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = if m > 2 { m - 3 } else { m + 9 };
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    u64::try_from(era * 146_097 + doe - 719_468).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty, Side, TraderId};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rlob-tsdb-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn trade(symbol: &str, ts: u64, price: u32) -> TradeRecord {
+        TradeRecord {
+            timestamp_ns: ts,
+            symbol: symbol.to_string(),
+            buyer: TraderId::from_str("B"),
+            seller: TraderId::from_str("S"),
+            price: px(price),
+            quantity: qty(1),
+        }
+    }
+
+    #[test]
+    fn test_date_roundtrip() {
+        assert_eq!(format_date(0), "19700101");
+        assert_eq!(format_date(19_723), "20240101");
+        assert_eq!(parse_date("20240101"), Some(19_723));
+        assert_eq!(parse_date("bogus"), None);
+    }
+
+    #[test]
+    fn test_trades_between_across_segments() {
+        let dir = temp_dir("between");
+        let mut store = TimeSeriesStore::open(&dir, StoreConfig {
+            index_interval: 4,
+            sync_on_flush: false,
+        })
+        .unwrap();
+
+        let day = NANOS_PER_DAY;
+        for i in 0..50u64 {
+            store.append_trade(&trade("BTCUSDT", day + i * 1_000, 10000 + i as u32)).unwrap();
+            store.append_trade(&trade("ETHUSDT", day + i * 1_000, 2000)).unwrap();
+        }
+        store.append_trade(&trade("BTCUSDT", 2 * day + 5, 20000)).unwrap();
+        store
+            .append_ticker(&TickerRecord {
+                timestamp_ns: 2 * day + 6,
+                symbol: "BTCUSDT".to_string(),
+                last_price: px(20000),
+                best_bid: Some(px(19999)),
+                best_ask: Some(px(20001)),
+            })
+            .unwrap();
+
+        let trades = store.trades_between("BTCUSDT", day + 10_000, day + 19_000).unwrap();
+        assert_eq!(trades.len(), 10);
+        assert_eq!(trades[0].price, px(10010));
+        assert_eq!(trades[9].price, px(10019));
+
+        let trades = store.trades_between("BTCUSDT", day + 49_000, 3 * day).unwrap();
+        assert_eq!(trades.iter().map(|t| t.price).collect::<Vec<_>>(), vec![px(10049), px(20000)]);
+
+        let tickers = store.tickers_between("BTCUSDT", 0, u64::MAX).unwrap();
+        assert_eq!(tickers.len(), 1);
+
+        drop(store);
+
+        // 重新打开后数据仍可查询
+        let mut store = TimeSeriesStore::open(&dir, StoreConfig::default()).unwrap();
+        assert_eq!(store.trades_between("ETHUSDT", 0, u64::MAX).unwrap().len(), 50);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_trade_sink_stamps_execution_time() {
+        let dir = temp_dir("sink");
+        let store = Arc::new(Mutex::new(TimeSeriesStore::open(&dir, StoreConfig::default()).unwrap()));
+        let mut sink = StoreTradeSink::new("BTCUSDT", Arc::clone(&store));
+
+        // 回放的历史成交按原始成交时间入库
+        let executed_ns = NANOS_PER_DAY + 42;
+        sink.on_trade(&Trade {
+            trade_id: 1,
+            timestamp_ns: executed_ns,
+            buyer: TraderId::from_str("B"),
+            seller: TraderId::from_str("S"),
+            price: px(10000),
+            quantity: qty(3),
+            aggressor_side: Side::Buy,
+            maker_order_id: 1,
+            taker_order_id: 2,
+        });
+
+        let trades = store.lock().trades_between("BTCUSDT", executed_ns, executed_ns).unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!((trades[0].timestamp_ns, trades[0].quantity), (executed_ns, qty(3)));
+
+        drop(sink);
+        drop(store);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
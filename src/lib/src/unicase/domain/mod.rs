@@ -1 +1,2 @@
+pub mod topic;
 pub mod unicase;
\ No newline at end of file
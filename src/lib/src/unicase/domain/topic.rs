@@ -0,0 +1,406 @@
+/// 主题订阅（快照 + 增量）
+///
+/// 客户端订阅某个主题（如品种深度`depth.BTCUSDT`）时，服务端在登记表锁内
+/// 取快照、发送快照并登记订阅；此后只向该客户端推送序列号大于快照序列号的增量。
+/// 同一连接的发送队列是先进先出的，因此新客户端收到的第一条增量一定能直接应用在快照上。
+///
+/// 消息格式:
+/// - `Subscribe`/`Unsubscribe`: 负载为主题
+/// - `Snapshot`/`Delta`: `message_id`为序列号，负载为 1字节主题长度 + 主题 + 数据
+///
+/// 锁顺序: 订阅时先持有登记表锁再调用`SnapshotProvider`，
+/// 因此`publish`不能在持有快照数据源的锁时调用。
+///
+/// 客户端用`TopicConsumer`解码收到的快照/增量，可配置时效策略在回调前丢弃过期增量。
+/// 快照/增量的时间戳取自登记表注入的`Clock`（默认全局时钟）。
+
+use super::unicase::{MessageType, UnicastError, UnicastMessage};
+use crate::timing::{system_clock, Clock, StalenessGuard, StalenessPolicy};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 订阅者发送端
+pub trait TopicSink: Send + Sync {
+    /// 订阅者标识（连接ID）
+    fn id(&self) -> u64;
+
+    /// 发送消息（连接已断开时返回错误）
+    fn send(&self, message: &UnicastMessage) -> Result<(), UnicastError>;
+}
+
+/// 主题快照来源
+pub trait SnapshotProvider: Send + Sync {
+    /// 返回主题当前快照及其包含的最后一条增量的序列号；未知主题返回None
+    fn snapshot(&self, topic: &str) -> Option<(u64, Vec<u8>)>;
+}
+
+/// 品种深度主题名
+pub fn depth_topic(symbol: &str) -> String {
+    format!("depth.{}", symbol)
+}
+
+/// 编码快照/增量负载
+pub fn encode_topic_payload(topic: &str, data: &[u8]) -> Vec<u8> {
+    let topic = topic.as_bytes();
+    let topic = &topic[..topic.len().min(u8::MAX as usize)];
+
+    let mut buf = Vec::with_capacity(1 + topic.len() + data.len());
+    buf.push(topic.len() as u8);
+    buf.extend_from_slice(topic);
+    buf.extend_from_slice(data);
+    buf
+}
+
+/// 解码快照/增量负载，返回 (主题, 数据)
+pub fn decode_topic_payload(payload: &[u8]) -> Result<(&str, &[u8]), UnicastError> {
+    let (&len, rest) = payload
+        .split_first()
+        .ok_or_else(|| UnicastError::Deserialization("Empty topic payload".to_string()))?;
+    let topic = rest
+        .get(..len as usize)
+        .ok_or_else(|| UnicastError::Deserialization("Truncated topic".to_string()))?;
+    let topic = std::str::from_utf8(topic)
+        .map_err(|_| UnicastError::Deserialization("Invalid topic".to_string()))?;
+    Ok((topic, &rest[len as usize..]))
+}
+
+/// 构造快照/增量消息
+pub fn topic_message(msg_type: MessageType, topic: &str, sequence: u64, timestamp_ns: u64, data: &[u8]) -> UnicastMessage {
+    UnicastMessage {
+        message_id: sequence,
+        timestamp_ns,
+        msg_type,
+        payload: encode_topic_payload(topic, data),
+    }
+}
+
+struct Subscription<S> {
+    sink: S,
+    /// 已随快照送达的最后序列号
+    snapshot_sequence: u64,
+}
+
+/// 主题订阅登记表
+pub struct TopicRegistry<S: TopicSink> {
+    topics: Mutex<HashMap<String, Vec<Subscription<S>>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<S: TopicSink> TopicRegistry<S> {
+    pub fn new() -> Self {
+        Self {
+            topics: Mutex::new(HashMap::new()),
+            clock: system_clock(),
+        }
+    }
+
+    /// 设置时间源（通常与撮合引擎共用同一时钟）
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 订阅主题：发送当前快照并登记，返回快照序列号
+    ///
+    /// 未知主题返回`Ok(None)`且不登记；重复订阅会重新发送快照。
+    pub fn subscribe(
+        &self,
+        sink: S,
+        topic: &str,
+        provider: &dyn SnapshotProvider,
+    ) -> Result<Option<u64>, UnicastError> {
+        let mut topics = self.topics.lock();
+        let Some((sequence, data)) = provider.snapshot(topic) else {
+            return Ok(None);
+        };
+
+        sink.send(&topic_message(MessageType::Snapshot, topic, sequence, self.clock.now_ns(), &data))?;
+
+        let subscriptions = topics.entry(topic.to_string()).or_default();
+        subscriptions.retain(|subscription| subscription.sink.id() != sink.id());
+        subscriptions.push(Subscription {
+            sink,
+            snapshot_sequence: sequence,
+        });
+        Ok(Some(sequence))
+    }
+
+    /// 取消订阅
+    pub fn unsubscribe(&self, sink_id: u64, topic: &str) -> bool {
+        let mut topics = self.topics.lock();
+        let Some(subscriptions) = topics.get_mut(topic) else {
+            return false;
+        };
+        let before = subscriptions.len();
+        subscriptions.retain(|subscription| subscription.sink.id() != sink_id);
+        before != subscriptions.len()
+    }
+
+    /// 推送增量，返回送达的订阅者数
+    ///
+    /// 序列号不大于订阅快照序列号的增量不会发给该订阅者；发送失败的订阅者被移除。
+    pub fn publish(&self, topic: &str, sequence: u64, data: &[u8]) -> usize {
+        let mut topics = self.topics.lock();
+        let Some(subscriptions) = topics.get_mut(topic) else {
+            return 0;
+        };
+
+        let message = topic_message(MessageType::Delta, topic, sequence, self.clock.now_ns(), data);
+        let mut delivered = 0;
+        subscriptions.retain(|subscription| {
+            if sequence <= subscription.snapshot_sequence {
+                return true;
+            }
+            match subscription.sink.send(&message) {
+                Ok(()) => {
+                    delivered += 1;
+                    true
+                }
+                Err(_) => false,
+            }
+        });
+        delivered
+    }
+
+    /// 主题当前订阅者数
+    pub fn subscriber_count(&self, topic: &str) -> usize {
+        self.topics.lock().get(topic).map_or(0, Vec::len)
+    }
+}
+
+impl<S: TopicSink> Default for TopicRegistry<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 客户端收到的主题更新
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopicUpdate<'a> {
+    pub topic: &'a str,
+    pub sequence: u64,
+    /// 是否为快照（否则为增量）
+    pub snapshot: bool,
+    pub data: &'a [u8],
+}
+
+/// 主题消费统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TopicConsumerStats {
+    /// 收到的快照/增量数
+    pub received: u64,
+    /// 交给回调的数量
+    pub delivered: u64,
+    /// 超过时效的数量（含已丢弃）
+    pub stale: u64,
+    /// 因过期而丢弃的数量
+    pub dropped: u64,
+}
+
+/// 主题消费端（客户端侧）
+///
+/// 快照是后续增量的基准，过期时只计数不丢弃；时效策略只丢弃增量。
+pub struct TopicConsumer<F: FnMut(TopicUpdate<'_>)> {
+    callback: F,
+    staleness: Option<StalenessGuard>,
+    received: u64,
+    delivered: u64,
+}
+
+impl<F: FnMut(TopicUpdate<'_>)> TopicConsumer<F> {
+    pub fn new(callback: F) -> Self {
+        Self {
+            callback,
+            staleness: None,
+            received: 0,
+            delivered: 0,
+        }
+    }
+
+    /// 启用时效检查
+    pub fn with_staleness(mut self, policy: StalenessPolicy) -> Self {
+        self.staleness = Some(StalenessGuard::new(policy));
+        self
+    }
+
+    /// 处理一条消息，返回是否交给了回调
+    ///
+    /// 非快照/增量消息忽略（返回`Ok(false)`），负载无法解码时返回错误。
+    pub fn handle(&mut self, message: &UnicastMessage) -> Result<bool, UnicastError> {
+        let snapshot = match message.msg_type {
+            MessageType::Snapshot => true,
+            MessageType::Delta => false,
+            _ => return Ok(false),
+        };
+        let (topic, data) = decode_topic_payload(&message.payload)?;
+        self.received += 1;
+
+        if let Some(guard) = &self.staleness {
+            if snapshot {
+                guard.observe(message.timestamp_ns);
+            } else if !guard.admit(message.timestamp_ns) {
+                return Ok(false);
+            }
+        }
+
+        self.delivered += 1;
+        (self.callback)(TopicUpdate {
+            topic,
+            sequence: message.message_id,
+            snapshot,
+            data,
+        });
+        Ok(true)
+    }
+
+    pub fn stats(&self) -> TopicConsumerStats {
+        TopicConsumerStats {
+            received: self.received,
+            delivered: self.delivered,
+            stale: self.staleness.as_ref().map_or(0, StalenessGuard::stale),
+            dropped: self.staleness.as_ref().map_or(0, StalenessGuard::dropped),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::MockClock;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// 记录收到消息的订阅者
+    #[derive(Clone)]
+    struct Recorder {
+        id: u64,
+        messages: Arc<Mutex<Vec<UnicastMessage>>>,
+        closed: Arc<AtomicBool>,
+    }
+
+    impl Recorder {
+        fn new(id: u64) -> Self {
+            Self {
+                id,
+                messages: Arc::new(Mutex::new(Vec::new())),
+                closed: Arc::new(AtomicBool::new(false)),
+            }
+        }
+
+        fn received(&self) -> Vec<(MessageType, u64)> {
+            self.messages.lock().iter().map(|m| (m.msg_type, m.message_id)).collect()
+        }
+    }
+
+    impl TopicSink for Recorder {
+        fn id(&self) -> u64 {
+            self.id
+        }
+
+        fn send(&self, message: &UnicastMessage) -> Result<(), UnicastError> {
+            if self.closed.load(Ordering::Relaxed) {
+                return Err(UnicastError::Disconnected);
+            }
+            self.messages.lock().push(message.clone());
+            Ok(())
+        }
+    }
+
+    struct Book(Mutex<u64>);
+
+    impl SnapshotProvider for Book {
+        fn snapshot(&self, topic: &str) -> Option<(u64, Vec<u8>)> {
+            (topic == "depth.BTCUSDT").then(|| (*self.0.lock(), b"book".to_vec()))
+        }
+    }
+
+    #[test]
+    fn test_payload_roundtrip() {
+        let payload = encode_topic_payload("depth.BTCUSDT", b"data");
+        assert_eq!(decode_topic_payload(&payload).unwrap(), ("depth.BTCUSDT", &b"data"[..]));
+        assert!(decode_topic_payload(&payload[..5]).is_err());
+        assert!(decode_topic_payload(&[]).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_then_newer_deltas() {
+        let clock = MockClock::new(1_000);
+        let registry = TopicRegistry::new().with_clock(Arc::new(clock.clone()));
+        let book = Book(Mutex::new(5));
+        let topic = depth_topic("BTCUSDT");
+
+        let early = Recorder::new(1);
+        assert_eq!(registry.subscribe(early.clone(), &topic, &book).unwrap(), Some(5));
+        clock.set(2_000);
+        assert_eq!(registry.publish(&topic, 6, b"d6"), 1);
+        // 快照和增量按注入的时钟打时间戳
+        let timestamps: Vec<u64> = early.messages.lock().iter().map(|m| m.timestamp_ns).collect();
+        assert_eq!(timestamps, vec![1_000, 2_000]);
+
+        // 快照已包含序列号7，增量7只发给早订阅者
+        *book.0.lock() = 7;
+        let late = Recorder::new(2);
+        assert_eq!(registry.subscribe(late.clone(), &topic, &book).unwrap(), Some(7));
+        assert_eq!(registry.publish(&topic, 7, b"d7"), 1);
+        assert_eq!(registry.publish(&topic, 8, b"d8"), 2);
+
+        assert_eq!(
+            early.received(),
+            vec![(MessageType::Snapshot, 5), (MessageType::Delta, 6), (MessageType::Delta, 7), (MessageType::Delta, 8)]
+        );
+        assert_eq!(late.received(), vec![(MessageType::Snapshot, 7), (MessageType::Delta, 8)]);
+
+        let delta = &late.messages.lock()[1];
+        assert_eq!(decode_topic_payload(&delta.payload).unwrap(), (topic.as_str(), &b"d8"[..]));
+    }
+
+    #[test]
+    fn test_consumer_drops_stale_deltas() {
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&updates);
+        let mut consumer = TopicConsumer::new(move |update: TopicUpdate<'_>| {
+            seen.lock().push((update.topic.to_string(), update.sequence, update.snapshot));
+        })
+        .with_staleness(StalenessPolicy {
+            ttl: std::time::Duration::from_secs(1),
+            drop_stale: true,
+        });
+
+        let now = crate::timing::now_ns();
+        let aged = now - 5_000_000_000;
+        let topic = depth_topic("BTCUSDT");
+        assert!(consumer.handle(&topic_message(MessageType::Snapshot, &topic, 1, aged, b"s")).unwrap());
+        assert!(!consumer.handle(&topic_message(MessageType::Delta, &topic, 2, aged, b"d")).unwrap());
+        assert!(consumer.handle(&topic_message(MessageType::Delta, &topic, 3, now, b"d")).unwrap());
+
+        assert_eq!(*updates.lock(), vec![(topic.clone(), 1, true), (topic, 3, false)]);
+        assert_eq!(
+            consumer.stats(),
+            TopicConsumerStats {
+                received: 3,
+                delivered: 2,
+                stale: 2,
+                dropped: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_unknown_topic_and_cleanup() {
+        let registry = TopicRegistry::new();
+        let book = Book(Mutex::new(0));
+        let client = Recorder::new(1);
+        assert_eq!(registry.subscribe(client.clone(), "depth.ETHUSDT", &book).unwrap(), None);
+        assert!(client.received().is_empty());
+
+        registry.subscribe(client.clone(), "depth.BTCUSDT", &book).unwrap();
+        registry.subscribe(Recorder::new(2), "depth.BTCUSDT", &book).unwrap();
+        assert_eq!(registry.subscriber_count("depth.BTCUSDT"), 2);
+
+        client.closed.store(true, Ordering::Relaxed);
+        assert_eq!(registry.publish("depth.BTCUSDT", 1, b""), 1);
+        assert_eq!(registry.subscriber_count("depth.BTCUSDT"), 1);
+        assert!(registry.unsubscribe(2, "depth.BTCUSDT"));
+        assert!(!registry.unsubscribe(2, "depth.BTCUSDT"));
+    }
+}
@@ -8,6 +8,7 @@
 /// - 需要确认的关键消息
 
 use async_trait::async_trait;
+use serde::Serialize;
 use thiserror::Error;
 use std::net::SocketAddr;
 use std::time::Duration;
@@ -40,6 +41,16 @@ pub enum MessageType {
     Heartbeat = 5,
     /// 确认消息
     Ack = 6,
+    /// 订阅主题
+    Subscribe = 7,
+    /// 取消订阅
+    Unsubscribe = 8,
+    /// 主题快照
+    Snapshot = 9,
+    /// 主题增量
+    Delta = 10,
+    /// 限流通知（载荷见`orderbook::ThrottleNotice`）
+    Throttle = 11,
 }
 
 impl MessageType {
@@ -51,6 +62,11 @@ impl MessageType {
             4 => Some(Self::ConfigSync),
             5 => Some(Self::Heartbeat),
             6 => Some(Self::Ack),
+            7 => Some(Self::Subscribe),
+            8 => Some(Self::Unsubscribe),
+            9 => Some(Self::Snapshot),
+            10 => Some(Self::Delta),
+            11 => Some(Self::Throttle),
             _ => None,
         }
     }
@@ -174,7 +190,7 @@ pub trait TcpServer: Send + Sync {
 }
 
 /// 客户端统计
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ClientStats {
     /// 发送的消息数
     pub messages_sent: u64,
@@ -195,7 +211,7 @@ pub struct ClientStats {
 }
 
 /// 服务器统计
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ServerStats {
     /// 当前连接数
     pub active_connections: u64,
@@ -209,6 +225,8 @@ pub struct ServerStats {
     pub bytes_sent: u64,
     /// 接收的字节数
     pub bytes_received: u64,
+    /// 接收错误数（无法解析的消息和非法帧）
+    pub receive_errors: u64,
 }
 
 /// 单播错误
@@ -0,0 +1,204 @@
+/// 注入故障的TCP客户端（测试用）
+///
+/// TCP本身可靠有序，这里在消息粒度上模拟应用层丢失、重复和乱序，
+/// 以及发送延迟抖动，用于验证确认/重传逻辑。原始字节接口直接透传。
+
+use crate::multicase::outbound::chaos::{ChaosConfig, ChaosInjector, ChaosStats};
+use crate::unicase::domain::unicase::{ClientStats, TcpClient, UnicastError, UnicastMessage};
+use async_trait::async_trait;
+use std::collections::VecDeque;
+
+/// 接收方向的种子扰动，避免收发两侧故障序列相同
+const RECEIVE_SEED_SALT: u64 = 0x5DEE_CE66_D1CE_4E5B;
+
+/// 注入故障的TCP客户端
+pub struct ChaosTcpClient<C> {
+    inner: C,
+    outgoing: ChaosInjector<UnicastMessage>,
+    incoming: ChaosInjector<UnicastMessage>,
+    /// 已通过故障注入、等待`receive`取走的消息
+    ready: VecDeque<UnicastMessage>,
+}
+
+impl<C: TcpClient> ChaosTcpClient<C> {
+    pub fn new(inner: C, config: ChaosConfig) -> Self {
+        let incoming = ChaosConfig {
+            seed: config.seed ^ RECEIVE_SEED_SALT,
+            ..config.clone()
+        };
+        Self {
+            inner,
+            outgoing: ChaosInjector::new(config),
+            incoming: ChaosInjector::new(incoming),
+            ready: VecDeque::new(),
+        }
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// 发送所有扣留中的消息，并释放扣留的已接收消息
+    pub async fn flush(&mut self) -> Result<(), UnicastError> {
+        for message in self.outgoing.flush() {
+            self.inner.send(&message).await?;
+        }
+        self.ready.extend(self.incoming.flush());
+        Ok(())
+    }
+
+    /// 发送方向统计
+    pub fn send_chaos_stats(&self) -> ChaosStats {
+        self.outgoing.stats()
+    }
+
+    /// 接收方向统计
+    pub fn receive_chaos_stats(&self) -> ChaosStats {
+        self.incoming.stats()
+    }
+}
+
+#[async_trait]
+impl<C: TcpClient> TcpClient for ChaosTcpClient<C> {
+    async fn connect(&mut self) -> Result<(), UnicastError> {
+        self.inner.connect().await
+    }
+
+    async fn disconnect(&mut self) -> Result<(), UnicastError> {
+        self.inner.disconnect().await
+    }
+
+    async fn send(&mut self, message: &UnicastMessage) -> Result<(), UnicastError> {
+        let out = self.outgoing.apply(message.clone());
+        let delay = self.outgoing.jitter();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        for message in out {
+            self.inner.send(&message).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_raw(&mut self, data: &[u8]) -> Result<(), UnicastError> {
+        self.inner.send_raw(data).await
+    }
+
+    async fn receive(&mut self) -> Result<UnicastMessage, UnicastError> {
+        loop {
+            if let Some(message) = self.ready.pop_front() {
+                return Ok(message);
+            }
+            let message = self.inner.receive().await?;
+            self.ready.extend(self.incoming.apply(message));
+        }
+    }
+
+    async fn receive_raw(&mut self, buffer: &mut [u8]) -> Result<usize, UnicastError> {
+        self.inner.receive_raw(buffer).await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    fn stats(&self) -> ClientStats {
+        self.inner.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unicase::domain::unicase::MessageType;
+
+    /// 回环客户端：发送的消息原样进入接收队列
+    #[derive(Default)]
+    struct Loopback {
+        queue: VecDeque<UnicastMessage>,
+    }
+
+    #[async_trait]
+    impl TcpClient for Loopback {
+        async fn connect(&mut self) -> Result<(), UnicastError> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> Result<(), UnicastError> {
+            Ok(())
+        }
+
+        async fn send(&mut self, message: &UnicastMessage) -> Result<(), UnicastError> {
+            self.queue.push_back(message.clone());
+            Ok(())
+        }
+
+        async fn send_raw(&mut self, _data: &[u8]) -> Result<(), UnicastError> {
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<UnicastMessage, UnicastError> {
+            self.queue.pop_front().ok_or(UnicastError::Disconnected)
+        }
+
+        async fn receive_raw(&mut self, _buffer: &mut [u8]) -> Result<usize, UnicastError> {
+            Ok(0)
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        fn stats(&self) -> ClientStats {
+            ClientStats::default()
+        }
+    }
+
+    fn message(message_id: u64) -> UnicastMessage {
+        UnicastMessage {
+            message_id,
+            timestamp_ns: 0,
+            msg_type: MessageType::OrderCommand,
+            payload: Vec::new(),
+        }
+    }
+
+    async fn roundtrip(config: ChaosConfig) -> Vec<u64> {
+        let mut client = ChaosTcpClient::new(Loopback::default(), config);
+        for id in 1..=100 {
+            client.send(&message(id)).await.unwrap();
+        }
+        client.flush().await.unwrap();
+
+        let mut received = Vec::new();
+        while let Ok(message) = client.receive().await {
+            received.push(message.message_id);
+        }
+        client.flush().await.unwrap();
+        while let Ok(message) = client.receive().await {
+            received.push(message.message_id);
+        }
+        received
+    }
+
+    #[tokio::test]
+    async fn test_chaos_client_deterministic() {
+        let config = ChaosConfig {
+            drop_rate: 0.05,
+            duplicate_rate: 0.05,
+            reorder_rate: 0.1,
+            seed: 11,
+            ..Default::default()
+        };
+        let a = roundtrip(config.clone()).await;
+        let b = roundtrip(config).await;
+        assert_eq!(a, b);
+        assert_ne!(a, (1..=100).collect::<Vec<_>>());
+
+        assert_eq!(roundtrip(ChaosConfig::default()).await, (1..=100).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,379 @@
+/// 撮合引擎TCP服务
+///
+/// 基于`TcpUnicastServer`接收`OrderCommand`消息（载荷为OUCH风格报文，见`message::domain::ouch`），
+/// 在独占订单簿的撮合线程上执行，并通过原连接以`Ack`消息回复受理、成交、撤单和拒绝。
+/// 挂单被动成交时，成交回报发往下单的连接。撤单和减量改单只受理挂单所属连接发出的请求，
+/// 其它连接的请求以`REJECT_UNKNOWN_ORDER`拒绝；无法解码的报文以`REJECT_MALFORMED`拒绝。
+///
+/// 下单超出会话速率或撮合队列深度时不执行，回复`Throttle`消息（`ThrottleNotice`）；撤单不限流。
+/// 下单令牌作为客户端订单号去重：同一交易员重复的令牌以`DuplicateOrder`拒绝。
+
+use crate::message::domain::ouch::{OuchRequest, OuchResponse, REJECT_MALFORMED, REJECT_UNKNOWN_ORDER};
+use crate::orderbook::{CommandResult, OrderBook, OrderId, RejectReason, SessionThrottle, ThrottleConfig, ThrottleNotice};
+use crate::unicase::domain::unicase::{MessageType, ServerStats, TcpServer, UnicastError, UnicastMessage};
+use crate::unicase::outbound::tcp_server::{ClientSender, TcpUnicastServer};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+/// 撮合线程的输入
+enum Job {
+    Request(ClientSender, UnicastMessage),
+    Shutdown,
+}
+
+/// 撮合引擎服务器
+pub struct MatchingEngineServer {
+    server: TcpUnicastServer,
+    jobs: mpsc::Sender<Job>,
+    worker: Option<JoinHandle<OrderBook>>,
+}
+
+impl MatchingEngineServer {
+    /// 创建服务器并启动撮合线程，只受理品种为`symbol`的订单
+    pub fn new(listen_addr: SocketAddr, symbol: &str, book: OrderBook) -> Self {
+        Self::with_throttle(listen_addr, symbol, book, ThrottleConfig::default())
+    }
+
+    /// 创建带会话限流和队列深度限制的服务器
+    pub fn with_throttle(listen_addr: SocketAddr, symbol: &str, book: OrderBook, throttle: ThrottleConfig) -> Self {
+        let (jobs, rx) = mpsc::channel();
+        let pending = Arc::new(AtomicUsize::new(0));
+        let worker = EngineWorker::new(symbol.to_string(), book, throttle, Arc::clone(&pending));
+        let worker = thread::Builder::new()
+            .name(format!("engine-{}", symbol))
+            .spawn(move || worker.run(rx))
+            .expect("failed to spawn matching thread");
+
+        let mut server = TcpUnicastServer::new(listen_addr);
+        let handler_jobs = jobs.clone();
+        server.set_message_handler(move |client, message| {
+            if message.msg_type == MessageType::OrderCommand {
+                pending.fetch_add(1, Ordering::Relaxed);
+                let _ = handler_jobs.send(Job::Request(client.clone(), message));
+            }
+        });
+
+        Self {
+            server,
+            jobs,
+            worker: Some(worker),
+        }
+    }
+
+    /// 启动服务器
+    pub async fn start(&mut self) -> Result<(), UnicastError> {
+        self.server.start().await
+    }
+
+    /// 停止服务器
+    pub async fn stop(&mut self) -> Result<(), UnicastError> {
+        self.server.stop().await
+    }
+
+    /// 实际监听地址
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.local_addr()
+    }
+
+    /// 获取统计信息
+    pub fn stats(&self) -> ServerStats {
+        self.server.stats()
+    }
+
+    /// 结束撮合线程并取回订单簿（已排队的指令会先执行完）
+    pub fn into_book(mut self) -> OrderBook {
+        self.shutdown().expect("matching thread panicked")
+    }
+
+    fn shutdown(&mut self) -> Option<OrderBook> {
+        let worker = self.worker.take()?;
+        let _ = self.jobs.send(Job::Shutdown);
+        worker.join().ok()
+    }
+}
+
+impl Drop for MatchingEngineServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// 撮合线程状态
+struct EngineWorker {
+    symbol: String,
+    book: OrderBook,
+    /// 挂单所属连接（用于推送被动成交）
+    owners: HashMap<OrderId, ClientSender>,
+    throttle: SessionThrottle,
+    /// 已收到尚未执行的指令数
+    pending: Arc<AtomicUsize>,
+    buf: [u8; 64],
+}
+
+impl EngineWorker {
+    fn new(symbol: String, book: OrderBook, throttle: ThrottleConfig, pending: Arc<AtomicUsize>) -> Self {
+        Self {
+            symbol,
+            book,
+            owners: HashMap::new(),
+            throttle: SessionThrottle::new(throttle),
+            pending,
+            buf: [0; 64],
+        }
+    }
+
+    fn run(mut self, rx: mpsc::Receiver<Job>) -> OrderBook {
+        while let Ok(Job::Request(client, message)) = rx.recv() {
+            let queue_depth = self.pending.fetch_sub(1, Ordering::Relaxed) - 1;
+            self.handle(&client, &message, queue_depth);
+        }
+        self.book
+    }
+
+    fn handle(&mut self, client: &ClientSender, message: &UnicastMessage, queue_depth: usize) {
+        let request = match OuchRequest::decode(&message.payload) {
+            Ok((request, _)) => request,
+            Err(e) => {
+                crate::runtime_log!(Warn, "Invalid order command from client {}: {}", client.id(), e);
+                self.reply(client, message.message_id, &OuchResponse::Rejected { reference: 0, reason: REJECT_MALFORMED });
+                return;
+            }
+        };
+
+        // 订单号是顺序分配的，只允许挂单所属连接撤单或改单
+        if let OuchRequest::CancelOrder { order_id, .. } = request
+            && self.owners.get(&order_id).map(ClientSender::id) != Some(client.id())
+        {
+            self.reply(client, message.message_id, &OuchResponse::Rejected { reference: order_id, reason: REJECT_UNKNOWN_ORDER });
+            return;
+        }
+
+        if let OuchRequest::EnterOrder { .. } = request
+            && let Err(notice) = self.throttle.check(client.id(), queue_depth, Instant::now())
+        {
+            self.notify_throttled(client, message.message_id, &notice);
+            return;
+        }
+
+        let client_order_id = match request {
+            OuchRequest::EnterOrder { token, .. } => Some(token),
+            _ => None,
+        };
+        let result = match request.symbol() {
+            Some(symbol) if symbol != self.symbol => CommandResult::Rejected(RejectReason::UnknownSymbol),
+            _ => request.command().execute_with_client_id(&mut self.book, client_order_id),
+        };
+
+        for response in OuchResponse::for_result(&request, &result) {
+            self.reply(client, message.message_id, &response);
+        }
+
+        match result {
+            CommandResult::Accepted { order_id, trades } => {
+                for trade in &trades {
+                    let maker = trade.maker_order_id;
+                    if let Some(owner) = self.owners.get(&maker).cloned() {
+                        self.reply(&owner, message.message_id, &OuchResponse::executed(trade, maker));
+                        if self.book.order_quantity(maker).is_none() {
+                            self.owners.remove(&maker);
+                        }
+                    }
+                }
+                if self.book.order_quantity(order_id).is_some() {
+                    self.owners.insert(order_id, client.clone());
+                }
+            }
+            CommandResult::Cancelled { order_id, success: true } => {
+                self.owners.remove(&order_id);
+            }
+            _ => {}
+        }
+    }
+
+    fn notify_throttled(&mut self, client: &ClientSender, message_id: u64, notice: &ThrottleNotice) {
+        let message = UnicastMessage {
+            message_id,
+            timestamp_ns: self.book.clock().now_ns(),
+            msg_type: MessageType::Throttle,
+            payload: notice.encode().to_vec(),
+        };
+        if client.send(&message).is_err() {
+            self.throttle.remove_session(client.id());
+        }
+    }
+
+    fn reply(&mut self, client: &ClientSender, message_id: u64, response: &OuchResponse) {
+        let Ok(len) = response.encode(&mut self.buf) else {
+            return;
+        };
+        let message = UnicastMessage {
+            message_id,
+            timestamp_ns: self.book.clock().now_ns(),
+            msg_type: MessageType::Ack,
+            payload: self.buf[..len].to_vec(),
+        };
+        if client.send(&message).is_err() {
+            self.owners.retain(|_, owner| owner.id() != client.id());
+            self.throttle.remove_session(client.id());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::domain::ouch::reject_code;
+    use crate::message::domain::wire::alpha8;
+    use crate::orderbook::types::{px, qty};
+    use crate::orderbook::{Side, TimeInForce, TraderId};
+    use crate::timing::MockClock;
+    use crate::unicase::domain::unicase::{TcpClient, TcpConfig};
+    use crate::unicase::outbound::tcp_client::TcpUnicastClient;
+    use std::time::Duration;
+
+    async fn connect(addr: SocketAddr) -> TcpUnicastClient {
+        let mut client = TcpUnicastClient::new(TcpConfig {
+            server_addr: addr,
+            ..Default::default()
+        });
+        client.connect().await.unwrap();
+        client
+    }
+
+    async fn send(client: &mut TcpUnicastClient, message_id: u64, request: OuchRequest) {
+        let mut buf = [0u8; 64];
+        let len = request.encode(&mut buf).unwrap();
+        let message = UnicastMessage {
+            message_id,
+            timestamp_ns: 0,
+            msg_type: MessageType::OrderCommand,
+            payload: buf[..len].to_vec(),
+        };
+        client.send(&message).await.unwrap();
+    }
+
+    async fn receive(client: &mut TcpUnicastClient) -> (u64, OuchResponse) {
+        let message = tokio::time::timeout(Duration::from_secs(5), client.receive())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.msg_type, MessageType::Ack);
+        (message.message_id, OuchResponse::decode(&message.payload).unwrap().0)
+    }
+
+    fn enter(token: u64, symbol: &str, side: Side, price: u32, quantity: u32, tif: TimeInForce) -> OuchRequest {
+        OuchRequest::EnterOrder {
+            token,
+            trader: TraderId::from_str("T1"),
+            symbol: alpha8(symbol),
+            side,
+            quantity: qty(quantity),
+            price: px(price),
+            tif,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_orders_acks_and_fills() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        book.set_clock(Arc::new(MockClock::new(7_000)));
+        let mut server = MatchingEngineServer::new("127.0.0.1:0".parse().unwrap(), "BTCUSDT", book);
+        server.start().await.unwrap();
+        let addr = server.local_addr().unwrap();
+        let mut maker = connect(addr).await;
+        let mut taker = connect(addr).await;
+
+        send(&mut maker, 1, enter(11, "BTCUSDT", Side::Sell, 101, 10, TimeInForce::Gtc)).await;
+        let message = tokio::time::timeout(Duration::from_secs(5), maker.receive()).await.unwrap().unwrap();
+        // 回报按订单簿注入的时钟打时间戳
+        assert_eq!((message.message_id, message.timestamp_ns), (1, 7_000));
+        let accepted = OuchResponse::decode(&message.payload).unwrap().0;
+        let OuchResponse::Accepted { token: 11, order_id: ask } = accepted else {
+            panic!("unexpected response {:?}", accepted);
+        };
+
+        send(&mut taker, 2, enter(12, "ETHUSDT", Side::Buy, 101, 4, TimeInForce::Ioc)).await;
+        assert_eq!(
+            receive(&mut taker).await.1,
+            OuchResponse::Rejected { reference: 12, reason: reject_code(RejectReason::UnknownSymbol) }
+        );
+
+        send(&mut taker, 3, enter(13, "BTCUSDT", Side::Buy, 101, 4, TimeInForce::Ioc)).await;
+        let OuchResponse::Accepted { order_id: bid, .. } = receive(&mut taker).await.1 else {
+            panic!("expected accept");
+        };
+        let OuchResponse::Executed { order_id, quantity, price, .. } = receive(&mut taker).await.1 else {
+            panic!("expected taker fill");
+        };
+        assert_eq!((order_id, quantity, price), (bid, qty(4), px(101)));
+        let OuchResponse::Executed { order_id, quantity, .. } = receive(&mut maker).await.1 else {
+            panic!("expected maker fill");
+        };
+        assert_eq!((order_id, quantity), (ask, qty(4)));
+
+        send(&mut maker, 4, OuchRequest::CancelOrder { order_id: ask, quantity: 0 }).await;
+        assert_eq!(receive(&mut maker).await.1, OuchResponse::Canceled { order_id: ask, remaining: 0 });
+
+        // 重连后重发同一令牌的订单被拒绝
+        maker.disconnect().await.unwrap();
+        let mut maker = connect(addr).await;
+        send(&mut maker, 5, enter(11, "BTCUSDT", Side::Sell, 101, 10, TimeInForce::Gtc)).await;
+        assert_eq!(
+            receive(&mut maker).await.1,
+            OuchResponse::Rejected { reference: 11, reason: reject_code(RejectReason::DuplicateOrder) }
+        );
+
+        maker.disconnect().await.unwrap();
+        taker.disconnect().await.unwrap();
+        server.stop().await.unwrap();
+        let book = server.into_book();
+        assert_eq!(book.trades().len(), 1);
+        assert!(book.depth(1).asks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_only_owner_may_cancel_or_amend() {
+        let book = OrderBook::with_capacity(1_000, 100);
+        let mut server = MatchingEngineServer::new("127.0.0.1:0".parse().unwrap(), "BTCUSDT", book);
+        server.start().await.unwrap();
+        let addr = server.local_addr().unwrap();
+        let mut alice = connect(addr).await;
+        let mut bob = connect(addr).await;
+
+        send(&mut alice, 1, enter(21, "BTCUSDT", Side::Buy, 99, 10, TimeInForce::Gtc)).await;
+        let OuchResponse::Accepted { order_id, .. } = receive(&mut alice).await.1 else {
+            panic!("expected accept");
+        };
+
+        // 其它连接的撤单和减量改单都被拒绝
+        let rejected = OuchResponse::Rejected { reference: order_id, reason: REJECT_UNKNOWN_ORDER };
+        send(&mut bob, 2, OuchRequest::CancelOrder { order_id, quantity: 0 }).await;
+        assert_eq!(receive(&mut bob).await.1, rejected);
+        send(&mut bob, 3, OuchRequest::CancelOrder { order_id, quantity: 1 }).await;
+        assert_eq!(receive(&mut bob).await.1, rejected);
+
+        // 无法解码的报文也有回复
+        bob.send(&UnicastMessage {
+            message_id: 4,
+            timestamp_ns: 0,
+            msg_type: MessageType::OrderCommand,
+            payload: vec![b'?'; 3],
+        })
+        .await
+        .unwrap();
+        assert_eq!(receive(&mut bob).await, (4, OuchResponse::Rejected { reference: 0, reason: REJECT_MALFORMED }));
+
+        send(&mut alice, 5, OuchRequest::CancelOrder { order_id, quantity: 4 }).await;
+        assert_eq!(receive(&mut alice).await.1, OuchResponse::Canceled { order_id, remaining: 4 });
+
+        alice.disconnect().await.unwrap();
+        bob.disconnect().await.unwrap();
+        server.stop().await.unwrap();
+        let book = server.into_book();
+        assert_eq!(book.order_quantity(order_id), Some(qty(4)));
+    }
+}
@@ -1,2 +1,7 @@
+pub mod chaos;
+pub mod engine_server;
+pub mod snapshot_server;
 pub mod tcp_client;
-pub mod tcp_server;
\ No newline at end of file
+pub mod tcp_server;
+pub mod topic_server;
+pub mod trading_client;
\ No newline at end of file
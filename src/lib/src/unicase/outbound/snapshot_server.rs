@@ -0,0 +1,190 @@
+/// 深度快照查询服务
+///
+/// 基于`TcpUnicastServer`处理`QueryRequest`消息（负载为品种代码），
+/// 以`QueryResponse`回复该品种的深度快照（`DepthSnapshot`二进制编码），`message_id`与请求一致。
+/// 组播增量行情的晚加入者先经本服务取快照初始化镜像，再应用序列号更大的增量批次。
+/// 未知品种以`Ack`回复，负载为"UNKNOWN_SYMBOL"。
+///
+/// 快照由`DepthSnapshotSource`提供，通常读取与增量发布共用的`DepthDeltaGenerator`，
+/// 保证快照序列号与组播批次一致。
+
+use crate::orderbook::DepthSnapshot;
+use crate::unicase::domain::unicase::{MessageType, ServerStats, TcpClient, TcpServer, UnicastError, UnicastMessage};
+use crate::unicase::outbound::tcp_server::{ClientSender, TcpUnicastServer};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// 未知品种的回复负载
+pub const UNKNOWN_SYMBOL: &[u8] = b"UNKNOWN_SYMBOL";
+
+/// 深度快照来源
+pub trait DepthSnapshotSource: Send + Sync {
+    /// 品种当前快照；未知品种返回None
+    fn depth_snapshot(&self, symbol: &str) -> Option<DepthSnapshot>;
+}
+
+impl<F> DepthSnapshotSource for F
+where
+    F: Fn(&str) -> Option<DepthSnapshot> + Send + Sync,
+{
+    fn depth_snapshot(&self, symbol: &str) -> Option<DepthSnapshot> {
+        self(symbol)
+    }
+}
+
+/// 深度快照查询服务器
+pub struct SnapshotServer {
+    server: TcpUnicastServer,
+}
+
+impl SnapshotServer {
+    /// 创建服务器，查询时从`source`获取快照
+    pub fn new(listen_addr: SocketAddr, source: Arc<dyn DepthSnapshotSource>) -> Self {
+        let mut server = TcpUnicastServer::new(listen_addr);
+        server.set_message_handler(move |client, message| {
+            Self::handle(source.as_ref(), client, message);
+        });
+        Self { server }
+    }
+
+    fn handle(source: &dyn DepthSnapshotSource, client: &ClientSender, message: UnicastMessage) {
+        if message.msg_type != MessageType::QueryRequest {
+            return;
+        }
+        let Ok(symbol) = std::str::from_utf8(&message.payload) else {
+            crate::runtime_log!(Warn, "Invalid symbol from client {}", client.id());
+            return;
+        };
+
+        let (msg_type, payload) = match source.depth_snapshot(symbol) {
+            Some(snapshot) => {
+                let mut payload = Vec::new();
+                if let Err(e) = snapshot.write_to(&mut payload) {
+                    crate::runtime_log!(Warn, "Failed to encode snapshot of {}: {}", symbol, e);
+                    return;
+                }
+                (MessageType::QueryResponse, payload)
+            }
+            None => (MessageType::Ack, UNKNOWN_SYMBOL.to_vec()),
+        };
+        let reply = UnicastMessage {
+            message_id: message.message_id,
+            timestamp_ns: crate::timing::now_ns(),
+            msg_type,
+            payload,
+        };
+        let _ = client.send(&reply);
+    }
+
+    /// 启动服务器
+    pub async fn start(&mut self) -> Result<(), UnicastError> {
+        self.server.start().await
+    }
+
+    /// 停止服务器
+    pub async fn stop(&mut self) -> Result<(), UnicastError> {
+        self.server.stop().await
+    }
+
+    /// 实际监听地址
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.local_addr()
+    }
+
+    /// 获取统计信息
+    pub fn stats(&self) -> ServerStats {
+        self.server.stats()
+    }
+}
+
+/// 经已连接的客户端查询品种快照（未知品种返回None）
+///
+/// 等待与请求`message_id`相同的回复，期间收到的其他消息被丢弃。
+pub async fn fetch_snapshot<C: TcpClient + ?Sized>(
+    client: &mut C,
+    message_id: u64,
+    symbol: &str,
+) -> Result<Option<DepthSnapshot>, UnicastError> {
+    let request = UnicastMessage {
+        message_id,
+        timestamp_ns: crate::timing::now_ns(),
+        msg_type: MessageType::QueryRequest,
+        payload: symbol.as_bytes().to_vec(),
+    };
+    client.send(&request).await?;
+
+    loop {
+        let reply = client.receive().await?;
+        if reply.message_id != message_id {
+            continue;
+        }
+        return match reply.msg_type {
+            MessageType::QueryResponse => DepthSnapshot::read_from(&mut reply.payload.as_slice())
+                .map(Some)
+                .map_err(|e| UnicastError::Deserialization(e.to_string())),
+            MessageType::Ack if reply.payload == UNKNOWN_SYMBOL => Ok(None),
+            other => Err(UnicastError::InvalidMessageType(other.to_u8())),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::types::{px, qty};
+    use crate::orderbook::{BookDepth, Command, DepthDeltaGenerator, OrderBook, Side, TimeInForce, TraderId};
+    use crate::unicase::domain::unicase::TcpConfig;
+    use crate::unicase::outbound::tcp_client::TcpUnicastClient;
+    use parking_lot::Mutex;
+    use std::time::Duration;
+
+    fn limit(side: Side, price: u32, quantity: u32) -> Command {
+        Command::Limit {
+            trader: TraderId::from_str("T"),
+            side,
+            price: px(price),
+            quantity: qty(quantity),
+            tif: TimeInForce::Gtc,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_late_joiner_initializes_from_snapshot() {
+        let mut book = OrderBook::with_capacity(1_000, 100);
+        let generator = Arc::new(Mutex::new(DepthDeltaGenerator::new(5)));
+        for command in [limit(Side::Buy, 99, 5), limit(Side::Sell, 102, 3), limit(Side::Buy, 100, 2)] {
+            generator.lock().execute(&mut book, &command);
+        }
+
+        let shared = Arc::clone(&generator);
+        let source = Arc::new(move |symbol: &str| (symbol == "BTCUSDT").then(|| shared.lock().depth_snapshot()));
+        let mut server = SnapshotServer::new("127.0.0.1:0".parse().unwrap(), source);
+        server.start().await.unwrap();
+        let mut client = TcpUnicastClient::new(TcpConfig {
+            server_addr: server.local_addr().unwrap(),
+            ..Default::default()
+        });
+        client.connect().await.unwrap();
+
+        let fetch = fetch_snapshot(&mut client, 7, "ETHUSDT");
+        assert!(tokio::time::timeout(Duration::from_secs(5), fetch).await.unwrap().unwrap().is_none());
+
+        let fetch = fetch_snapshot(&mut client, 8, "BTCUSDT");
+        let snapshot = tokio::time::timeout(Duration::from_secs(5), fetch).await.unwrap().unwrap().unwrap();
+        assert_eq!(snapshot.sequence, 3);
+        assert_eq!(snapshot.depth, book.depth(5));
+        assert_eq!(snapshot.depth.checksum(5), snapshot.checksum);
+
+        // 在快照上应用之后的增量，镜像与引擎一致
+        let mut mirror: BookDepth = snapshot.depth;
+        let (_, batch) = generator.lock().execute(&mut book, &limit(Side::Sell, 100, 4));
+        let batch = batch.unwrap();
+        assert_eq!(batch.sequence, snapshot.sequence + 1);
+        batch.apply_to(&mut mirror);
+        assert!(batch.verify(&mirror, 5));
+        assert_eq!(mirror, book.depth(5));
+
+        client.disconnect().await.unwrap();
+        server.stop().await.unwrap();
+    }
+}
@@ -128,17 +128,17 @@ impl TcpUnicastClient {
             attempt += 1;
             self.stats.reconnect_count.fetch_add(1, Ordering::Relaxed);
 
-            eprintln!("Reconnect attempt {} after {:?}", attempt, delay);
+            crate::runtime_log!(Info, "Reconnect attempt {} after {:?}", attempt, delay);
             sleep(delay).await;
 
             // 尝试连接
             match self.connect_internal().await {
                 Ok(_) => {
-                    eprintln!("Reconnected successfully");
+                    crate::runtime_log!(Info, "Reconnected successfully");
                     return Ok(());
                 }
                 Err(e) => {
-                    eprintln!("Reconnect failed: {}", e);
+                    crate::runtime_log!(Info, "Reconnect failed: {}", e);
                 }
             }
 
@@ -168,7 +168,7 @@ impl TcpUnicastClient {
     }
 
     /// 反序列化消息
-    fn deserialize_message(data: &[u8]) -> Result<UnicastMessage, UnicastError> {
+    pub(crate) fn deserialize_message(data: &[u8]) -> Result<UnicastMessage, UnicastError> {
         if data.len() < 21 {
             return Err(UnicastError::Deserialization("Message too short".to_string()));
         }
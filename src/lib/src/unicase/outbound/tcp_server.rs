@@ -17,6 +17,37 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use parking_lot::RwLock;
 use crate::unicase::domain::unicase::{ServerStats, TcpServer, UnicastError, UnicastMessage};
+use crate::unicase::outbound::tcp_client::TcpUnicastClient;
+
+/// 收到客户端消息时的回调
+pub type MessageHandler = Arc<dyn Fn(&ClientSender, UnicastMessage) + Send + Sync>;
+
+/// 指定客户端的发送句柄（可克隆，连接断开后发送失败）
+#[derive(Clone)]
+pub struct ClientSender {
+    id: u64,
+    tx: mpsc::UnboundedSender<Vec<u8>>,
+}
+
+impl ClientSender {
+    /// 客户端ID
+    #[inline]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// 将消息放入该客户端的发送队列
+    pub fn send(&self, message: &UnicastMessage) -> Result<(), UnicastError> {
+        self.tx
+            .send(TcpUnicastServer::serialize_message(message))
+            .map_err(|_| UnicastError::Disconnected)
+    }
+
+    /// 连接是否已断开
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
 
 /// 客户端连接信息
 struct ClientConnection {
@@ -40,6 +71,18 @@ pub struct TcpUnicastServer {
     running: Arc<AtomicBool>,
     /// 统计信息
     stats: Arc<ServerStatsInternal>,
+    /// 消息回调
+    handler: Option<MessageHandler>,
+    /// 实际监听地址（启动后可用）
+    local_addr: Option<SocketAddr>,
+}
+
+/// 各连接任务共享的服务器状态
+#[derive(Clone)]
+struct ServerContext {
+    clients: Arc<RwLock<HashMap<u64, ClientConnection>>>,
+    stats: Arc<ServerStatsInternal>,
+    handler: Option<MessageHandler>,
 }
 
 /// 内部统计信息
@@ -50,6 +93,7 @@ struct ServerStatsInternal {
     messages_received: AtomicU64,
     bytes_sent: AtomicU64,
     bytes_received: AtomicU64,
+    receive_errors: AtomicU64,
 }
 
 impl Default for ServerStatsInternal {
@@ -61,6 +105,7 @@ impl Default for ServerStatsInternal {
             messages_received: AtomicU64::new(0),
             bytes_sent: AtomicU64::new(0),
             bytes_received: AtomicU64::new(0),
+            receive_errors: AtomicU64::new(0),
         }
     }
 }
@@ -74,19 +119,35 @@ impl TcpUnicastServer {
             next_client_id: Arc::new(AtomicU64::new(1)),
             running: Arc::new(AtomicBool::new(false)),
             stats: Arc::new(ServerStatsInternal::default()),
+            handler: None,
+            local_addr: None,
         }
     }
 
+    /// 设置消息回调（须在`start`之前调用）
+    pub fn set_message_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(&ClientSender, UnicastMessage) + Send + Sync + 'static,
+    {
+        self.handler = Some(Arc::new(handler));
+    }
+
+    /// 实际监听地址（绑定端口0时使用），未启动时返回None
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
     /// 处理单个客户端连接
     async fn handle_client(
-        client_id: u64,
-        mut stream: TcpStream,
+        stream: TcpStream,
         addr: SocketAddr,
+        sender: ClientSender,
         mut rx: mpsc::UnboundedReceiver<Vec<u8>>,
-        clients: Arc<RwLock<HashMap<u64, ClientConnection>>>,
-        stats: Arc<ServerStatsInternal>,
+        context: ServerContext,
     ) {
-        eprintln!("Client {} ({}) connected", client_id, addr);
+        let client_id = sender.id();
+        let ServerContext { clients, stats, handler } = context;
+        crate::runtime_log!(Info, "Client {} ({}) connected", client_id, addr);
 
         // 配置TCP选项
         let _ = stream.set_nodelay(true);
@@ -99,10 +160,10 @@ impl TcpUnicastServer {
         let stats_recv = stats.clone();
 
         // 发送任务
-        let send_task = tokio::spawn(async move {
+        let mut send_task = tokio::spawn(async move {
             while let Some(data) = rx.recv().await {
                 if let Err(e) = writer.write_all(&data).await {
-                    eprintln!("Failed to send to client {}: {}", client_id, e);
+                    crate::runtime_log!(Warn, "Failed to send to client {}: {}", client_id, e);
                     break;
                 }
                 stats_send.bytes_sent.fetch_add(data.len() as u64, Ordering::Relaxed);
@@ -111,50 +172,64 @@ impl TcpUnicastServer {
         });
 
         // 接收任务
-        let recv_task = tokio::spawn(async move {
+        let mut recv_task = tokio::spawn(async move {
             let mut len_buf = [0u8; 4];
 
             loop {
                 // 读取消息长度
                 if let Err(e) = reader.read_exact(&mut len_buf).await {
-                    eprintln!("Failed to read from client {}: {}", client_id, e);
+                    crate::runtime_log!(Warn, "Failed to read from client {}: {}", client_id, e);
                     break;
                 }
 
                 let msg_len = u32::from_be_bytes(len_buf) as usize;
+                if msg_len < len_buf.len() {
+                    crate::runtime_log!(Warn, "Invalid frame length {} from client {}", msg_len, client_id);
+                    stats_recv.receive_errors.fetch_add(1, Ordering::Relaxed);
+                    break;
+                }
 
                 // 读取完整消息
                 let mut msg_buf = vec![0u8; msg_len];
                 msg_buf[0..4].copy_from_slice(&len_buf);
 
                 if let Err(e) = reader.read_exact(&mut msg_buf[4..]).await {
-                    eprintln!("Failed to read message from client {}: {}", client_id, e);
+                    crate::runtime_log!(Warn, "Failed to read message from client {}: {}", client_id, e);
                     break;
                 }
 
                 stats_recv.bytes_received.fetch_add(msg_buf.len() as u64, Ordering::Relaxed);
                 stats_recv.messages_received.fetch_add(1, Ordering::Relaxed);
 
-                // 这里可以添加消息处理逻辑
-                // 例如: 解析消息并触发回调
+                if let Some(handler) = &handler {
+                    match TcpUnicastClient::deserialize_message(&msg_buf) {
+                        Ok(message) => handler(&sender, message),
+                        Err(e) => {
+                            crate::runtime_log!(Warn, "Failed to parse message from client {}: {}", client_id, e);
+                            stats_recv.receive_errors.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                }
             }
         });
 
-        // 等待任务完成
+        // 等待任务完成，任一方向结束即关闭另一方向（使持有的ClientSender发送失败）
         tokio::select! {
-            _ = send_task => {},
-            _ = recv_task => {},
+            _ = &mut send_task => {},
+            _ = &mut recv_task => {},
         }
+        send_task.abort();
+        recv_task.abort();
 
         // 清理客户端连接
         clients.write().remove(&client_id);
         stats.active_connections.fetch_sub(1, Ordering::Relaxed);
 
-        eprintln!("Client {} ({}) disconnected", client_id, addr);
+        crate::runtime_log!(Info, "Client {} ({}) disconnected", client_id, addr);
     }
 
     /// 序列化消息
-    fn serialize_message(message: &UnicastMessage) -> Vec<u8> {
+    pub(crate) fn serialize_message(message: &UnicastMessage) -> Vec<u8> {
         let mut buf = Vec::new();
 
         // 消息格式: [长度(4字节)][消息ID(8字节)][时间戳(8字节)][类型(1字节)][载荷]
@@ -179,14 +254,18 @@ impl TcpServer for TcpUnicastServer {
         }
 
         let listener = TcpListener::bind(self.listen_addr).await?;
+        self.local_addr = Some(listener.local_addr()?);
         self.running.store(true, Ordering::Relaxed);
 
-        eprintln!("TCP server listening on {}", self.listen_addr);
+        crate::runtime_log!(Info, "TCP server listening on {}", listener.local_addr()?);
 
-        let clients = self.clients.clone();
+        let context = ServerContext {
+            clients: self.clients.clone(),
+            stats: self.stats.clone(),
+            handler: self.handler.clone(),
+        };
         let next_client_id = self.next_client_id.clone();
         let running = self.running.clone();
-        let stats = self.stats.clone();
 
         tokio::spawn(async move {
             while running.load(Ordering::Relaxed) {
@@ -199,31 +278,23 @@ impl TcpServer for TcpUnicastServer {
                         let (tx, rx) = mpsc::unbounded_channel();
 
                         // 保存客户端连接
+                        let sender = ClientSender { id: client_id, tx: tx.clone() };
                         let connection = ClientConnection {
                             id: client_id,
                             addr,
                             tx,
                         };
-                        clients.write().insert(client_id, connection);
+                        context.clients.write().insert(client_id, connection);
 
                         // 更新统计
-                        stats.active_connections.fetch_add(1, Ordering::Relaxed);
-                        stats.total_connections.fetch_add(1, Ordering::Relaxed);
+                        context.stats.active_connections.fetch_add(1, Ordering::Relaxed);
+                        context.stats.total_connections.fetch_add(1, Ordering::Relaxed);
 
                         // 启动客户端处理任务
-                        let clients_clone = clients.clone();
-                        let stats_clone = stats.clone();
-                        tokio::spawn(Self::handle_client(
-                            client_id,
-                            stream,
-                            addr,
-                            rx,
-                            clients_clone,
-                            stats_clone,
-                        ));
+                        tokio::spawn(Self::handle_client(stream, addr, sender, rx, context.clone()));
                     }
                     Err(e) => {
-                        eprintln!("Failed to accept connection: {}", e);
+                        crate::runtime_log!(Warn, "Failed to accept connection: {}", e);
                     }
                 }
             }
@@ -247,7 +318,7 @@ impl TcpServer for TcpUnicastServer {
 
         for (client_id, client) in clients.iter() {
             if let Err(e) = client.tx.send(data.clone()) {
-                eprintln!("Failed to send to client {}: {}", client_id, e);
+                crate::runtime_log!(Warn, "Failed to send to client {}: {}", client_id, e);
             }
         }
 
@@ -275,6 +346,48 @@ impl TcpServer for TcpUnicastServer {
             messages_received: self.stats.messages_received.load(Ordering::Relaxed),
             bytes_sent: self.stats.bytes_sent.load(Ordering::Relaxed),
             bytes_received: self.stats.bytes_received.load(Ordering::Relaxed),
+            receive_errors: self.stats.receive_errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unicase::domain::unicase::MessageType;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_unparseable_messages_are_counted() {
+        let mut server = TcpUnicastServer::new("127.0.0.1:0".parse().unwrap());
+        let received = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&received);
+        server.set_message_handler(move |_, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        server.start().await.unwrap();
+        let mut stream = TcpStream::connect(server.local_addr().unwrap()).await.unwrap();
+
+        let valid = TcpUnicastServer::serialize_message(&UnicastMessage {
+            message_id: 1,
+            timestamp_ns: 0,
+            msg_type: MessageType::Heartbeat,
+            payload: vec![],
+        });
+        let mut bad_type = valid.clone();
+        bad_type[20] = 0xee;
+        stream.write_all(&bad_type).await.unwrap();
+        stream.write_all(&valid).await.unwrap();
+
+        for _ in 0..100 {
+            if received.load(Ordering::Relaxed) == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
         }
+        let stats = server.stats();
+        assert_eq!(received.load(Ordering::Relaxed), 1);
+        assert_eq!((stats.messages_received, stats.receive_errors), (2, 1));
+        server.stop().await.unwrap();
     }
 }
@@ -0,0 +1,183 @@
+/// 主题订阅TCP服务器
+///
+/// 基于`TcpUnicastServer`处理`Subscribe`/`Unsubscribe`消息：
+/// 订阅时立即推送当前快照，之后推送序列号更大的增量（见`domain::topic`）。
+/// 未知主题以`Ack`回复，负载为主题 + "UNKNOWN_TOPIC"。
+
+use crate::timing::{system_clock, Clock};
+use crate::unicase::domain::topic::{encode_topic_payload, SnapshotProvider, TopicRegistry, TopicSink};
+use crate::unicase::domain::unicase::{MessageType, ServerStats, TcpServer, UnicastError, UnicastMessage};
+use crate::unicase::outbound::tcp_server::{ClientSender, TcpUnicastServer};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+impl TopicSink for ClientSender {
+    #[inline]
+    fn id(&self) -> u64 {
+        ClientSender::id(self)
+    }
+
+    #[inline]
+    fn send(&self, message: &UnicastMessage) -> Result<(), UnicastError> {
+        ClientSender::send(self, message)
+    }
+}
+
+/// 主题订阅服务器
+pub struct TopicServer {
+    server: TcpUnicastServer,
+    registry: Arc<TopicRegistry<ClientSender>>,
+}
+
+impl TopicServer {
+    /// 创建服务器，订阅时从`provider`获取快照
+    pub fn new(listen_addr: SocketAddr, provider: Arc<dyn SnapshotProvider>) -> Self {
+        Self::with_clock(listen_addr, provider, system_clock())
+    }
+
+    /// 创建服务器，快照/增量按`clock`打时间戳（通常与撮合引擎共用同一时钟）
+    pub fn with_clock(listen_addr: SocketAddr, provider: Arc<dyn SnapshotProvider>, clock: Arc<dyn Clock>) -> Self {
+        let registry = Arc::new(TopicRegistry::new().with_clock(clock));
+        let mut server = TcpUnicastServer::new(listen_addr);
+
+        let handler_registry = Arc::clone(&registry);
+        server.set_message_handler(move |client, message| {
+            Self::handle(&handler_registry, provider.as_ref(), client, message);
+        });
+
+        Self { server, registry }
+    }
+
+    fn handle(
+        registry: &TopicRegistry<ClientSender>,
+        provider: &dyn SnapshotProvider,
+        client: &ClientSender,
+        message: UnicastMessage,
+    ) {
+        let Ok(topic) = std::str::from_utf8(&message.payload) else {
+            crate::runtime_log!(Warn, "Invalid topic from client {}", client.id());
+            return;
+        };
+
+        match message.msg_type {
+            MessageType::Subscribe => match registry.subscribe(client.clone(), topic, provider) {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    let reply = UnicastMessage {
+                        message_id: message.message_id,
+                        timestamp_ns: message.timestamp_ns,
+                        msg_type: MessageType::Ack,
+                        payload: encode_topic_payload(topic, b"UNKNOWN_TOPIC"),
+                    };
+                    let _ = client.send(&reply);
+                }
+                Err(e) => crate::runtime_log!(Warn, "Failed to send snapshot to client {}: {}", client.id(), e),
+            },
+            MessageType::Unsubscribe => {
+                registry.unsubscribe(client.id(), topic);
+            }
+            _ => {}
+        }
+    }
+
+    /// 启动服务器
+    pub async fn start(&mut self) -> Result<(), UnicastError> {
+        self.server.start().await
+    }
+
+    /// 停止服务器
+    pub async fn stop(&mut self) -> Result<(), UnicastError> {
+        self.server.stop().await
+    }
+
+    /// 实际监听地址
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.local_addr()
+    }
+
+    /// 推送增量，返回送达的订阅者数
+    pub fn publish(&self, topic: &str, sequence: u64, data: &[u8]) -> usize {
+        self.registry.publish(topic, sequence, data)
+    }
+
+    /// 订阅登记表
+    pub fn registry(&self) -> &Arc<TopicRegistry<ClientSender>> {
+        &self.registry
+    }
+
+    /// 获取统计信息
+    pub fn stats(&self) -> ServerStats {
+        self.server.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unicase::domain::topic::{decode_topic_payload, depth_topic};
+    use crate::unicase::domain::unicase::{TcpClient, TcpConfig};
+    use crate::unicase::outbound::tcp_client::TcpUnicastClient;
+    use parking_lot::Mutex;
+    use std::time::Duration;
+
+    struct Depth(Mutex<u64>);
+
+    impl SnapshotProvider for Depth {
+        fn snapshot(&self, topic: &str) -> Option<(u64, Vec<u8>)> {
+            (topic == "depth.BTCUSDT").then(|| (*self.0.lock(), b"full".to_vec()))
+        }
+    }
+
+    fn request(msg_type: MessageType, topic: &str) -> UnicastMessage {
+        UnicastMessage {
+            message_id: 1,
+            timestamp_ns: 0,
+            msg_type,
+            payload: topic.as_bytes().to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_receives_snapshot_then_deltas() {
+        let depth = Arc::new(Depth(Mutex::new(10)));
+        let mut server = TopicServer::new("127.0.0.1:0".parse().unwrap(), depth.clone());
+        server.start().await.unwrap();
+
+        let mut client = TcpUnicastClient::new(TcpConfig {
+            server_addr: server.local_addr().unwrap(),
+            ..Default::default()
+        });
+        client.connect().await.unwrap();
+
+        let topic = depth_topic("BTCUSDT");
+        client.send(&request(MessageType::Subscribe, "depth.ETHUSDT")).await.unwrap();
+        let reply = client.receive().await.unwrap();
+        assert_eq!(reply.msg_type, MessageType::Ack);
+
+        client.send(&request(MessageType::Subscribe, &topic)).await.unwrap();
+        let snapshot = client.receive().await.unwrap();
+        assert_eq!((snapshot.msg_type, snapshot.message_id), (MessageType::Snapshot, 10));
+        assert_eq!(decode_topic_payload(&snapshot.payload).unwrap(), (topic.as_str(), &b"full"[..]));
+
+        // 已包含在快照中的增量不再推送
+        for sequence in 9..=12 {
+            server.publish(&topic, sequence, &sequence.to_le_bytes());
+        }
+        for expected in 11..=12 {
+            let delta = tokio::time::timeout(Duration::from_secs(5), client.receive()).await.unwrap().unwrap();
+            assert_eq!((delta.msg_type, delta.message_id), (MessageType::Delta, expected));
+        }
+
+        client.send(&request(MessageType::Unsubscribe, &topic)).await.unwrap();
+        for _ in 0..50 {
+            if server.registry().subscriber_count(&topic) == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(server.registry().subscriber_count(&topic), 0);
+
+        client.disconnect().await.unwrap();
+        server.stop().await.unwrap();
+    }
+}
@@ -0,0 +1,243 @@
+/// 交易客户端SDK
+///
+/// 封装`TcpUnicastClient`，以OUCH风格报文下单/撤单（见`MatchingEngineServer`），
+/// 并按服务端的`Throttle`通知控制提交节奏：收到通知后在`retry_after`之前暂停提交，
+/// 会话限流时之后按通知中的限额匀速提交。
+
+use crate::message::domain::ouch::{OuchRequest, OuchResponse};
+use crate::orderbook::{ThrottleNotice, ThrottleReason};
+use crate::unicase::domain::unicase::{MessageType, TcpClient, TcpConfig, UnicastError, UnicastMessage};
+use crate::unicase::outbound::tcp_client::TcpUnicastClient;
+use std::time::{Duration, Instant};
+
+/// 按限流通知控制提交节奏
+#[derive(Debug, Clone, Default)]
+pub struct SubmissionPacer {
+    /// 两次提交之间的最小间隔（来自会话限额）
+    min_interval: Option<Duration>,
+    /// 在此之前不提交
+    not_before: Option<Instant>,
+    last_submit: Option<Instant>,
+}
+
+impl SubmissionPacer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 收到限流通知
+    pub fn on_throttle(&mut self, notice: &ThrottleNotice, now: Instant) {
+        let resume_at = now + notice.retry_after;
+        self.not_before = Some(self.not_before.map_or(resume_at, |at| at.max(resume_at)));
+        if notice.reason == ThrottleReason::SessionRate && notice.limit > 0 {
+            self.min_interval = Some(Duration::from_secs(1).div_f64(notice.limit as f64));
+        }
+    }
+
+    /// 距离下一次允许提交还需等待的时间
+    pub fn delay(&self, now: Instant) -> Duration {
+        let paced = match (self.last_submit, self.min_interval) {
+            (Some(last), Some(interval)) => (last + interval).saturating_duration_since(now),
+            _ => Duration::ZERO,
+        };
+        let backoff = self.not_before.map_or(Duration::ZERO, |at| at.saturating_duration_since(now));
+        paced.max(backoff)
+    }
+
+    /// 记录一次提交
+    pub fn on_submit(&mut self, now: Instant) {
+        self.last_submit = Some(now);
+        if self.not_before.is_some_and(|at| at <= now) {
+            self.not_before = None;
+        }
+    }
+
+    /// 当前匀速提交间隔（未收到会话限流通知时为None）
+    pub fn min_interval(&self) -> Option<Duration> {
+        self.min_interval
+    }
+
+    /// 清除节奏限制（如服务端限额调整后）
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// 客户端收到的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradingEvent {
+    /// 受理、成交、撤单或拒绝
+    Response { message_id: u64, response: OuchResponse },
+    /// 指令被限流，未执行
+    Throttled { message_id: u64, notice: ThrottleNotice },
+}
+
+/// 交易客户端
+pub struct TradingClient {
+    client: TcpUnicastClient,
+    pacer: SubmissionPacer,
+    next_message_id: u64,
+}
+
+impl TradingClient {
+    pub fn new(config: TcpConfig) -> Self {
+        Self {
+            client: TcpUnicastClient::new(config),
+            pacer: SubmissionPacer::new(),
+            next_message_id: 1,
+        }
+    }
+
+    pub async fn connect(&mut self) -> Result<(), UnicastError> {
+        self.client.connect().await
+    }
+
+    pub async fn disconnect(&mut self) -> Result<(), UnicastError> {
+        self.client.disconnect().await
+    }
+
+    /// 按节奏提交指令（必要时等待），返回消息ID
+    pub async fn submit(&mut self, request: &OuchRequest) -> Result<u64, UnicastError> {
+        let delay = self.pacer.delay(Instant::now());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+
+        let mut buf = [0u8; 64];
+        let len = request
+            .encode(&mut buf)
+            .map_err(|e| UnicastError::Serialization(e.to_string()))?;
+        let message_id = self.next_message_id;
+        self.next_message_id += 1;
+        let message = UnicastMessage {
+            message_id,
+            timestamp_ns: crate::timing::now_ns(),
+            msg_type: MessageType::OrderCommand,
+            payload: buf[..len].to_vec(),
+        };
+        self.client.send(&message).await?;
+        self.pacer.on_submit(Instant::now());
+        Ok(message_id)
+    }
+
+    /// 接收下一个事件（忽略其他类型的消息），限流通知会更新提交节奏
+    pub async fn next_event(&mut self) -> Result<TradingEvent, UnicastError> {
+        loop {
+            let message = self.client.receive().await?;
+            match message.msg_type {
+                MessageType::Ack => {
+                    let (response, _) = OuchResponse::decode(&message.payload)
+                        .map_err(|e| UnicastError::Deserialization(e.to_string()))?;
+                    return Ok(TradingEvent::Response {
+                        message_id: message.message_id,
+                        response,
+                    });
+                }
+                MessageType::Throttle => {
+                    let notice = ThrottleNotice::decode(&message.payload)
+                        .ok_or_else(|| UnicastError::Deserialization("Invalid throttle notice".to_string()))?;
+                    self.pacer.on_throttle(&notice, Instant::now());
+                    return Ok(TradingEvent::Throttled {
+                        message_id: message.message_id,
+                        notice,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 提交节奏状态
+    pub fn pacer(&self) -> &SubmissionPacer {
+        &self.pacer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::domain::wire::alpha8;
+    use crate::orderbook::types::{px, qty};
+    use crate::orderbook::{OrderBook, Side, ThrottleConfig, TimeInForce, TraderId};
+    use crate::unicase::outbound::engine_server::MatchingEngineServer;
+
+    fn bid(token: u64) -> OuchRequest {
+        OuchRequest::EnterOrder {
+            token,
+            trader: TraderId::from_str("T1"),
+            symbol: alpha8("BTCUSDT"),
+            side: Side::Buy,
+            quantity: qty(1),
+            price: px(100),
+            tif: TimeInForce::Gtc,
+        }
+    }
+
+    #[test]
+    fn test_pacer_backoff_and_interval() {
+        let mut pacer = SubmissionPacer::new();
+        let t0 = Instant::now();
+        assert_eq!(pacer.delay(t0), Duration::ZERO);
+
+        let notice = ThrottleNotice {
+            reason: ThrottleReason::SessionRate,
+            limit: 4,
+            retry_after: Duration::from_millis(100),
+        };
+        pacer.on_throttle(&notice, t0);
+        assert_eq!(pacer.delay(t0), Duration::from_millis(100));
+        assert_eq!(pacer.min_interval(), Some(Duration::from_millis(250)));
+
+        let t1 = t0 + Duration::from_millis(100);
+        pacer.on_submit(t1);
+        assert_eq!(pacer.delay(t1 + Duration::from_millis(50)), Duration::from_millis(200));
+
+        pacer.reset();
+        assert_eq!(pacer.delay(t1), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_throttle_notice_paces_client() {
+        let throttle = ThrottleConfig {
+            session_rate: Some(20),
+            session_burst: 1,
+            ..Default::default()
+        };
+        let book = OrderBook::with_capacity(1_000, 100);
+        let mut server = MatchingEngineServer::with_throttle("127.0.0.1:0".parse().unwrap(), "BTCUSDT", book, throttle);
+        server.start().await.unwrap();
+
+        let mut client = TradingClient::new(TcpConfig {
+            server_addr: server.local_addr().unwrap(),
+            ..Default::default()
+        });
+        client.connect().await.unwrap();
+
+        client.submit(&bid(1)).await.unwrap();
+        assert!(matches!(
+            client.next_event().await.unwrap(),
+            TradingEvent::Response { response: OuchResponse::Accepted { token: 1, .. }, .. }
+        ));
+
+        // 突发额度用尽，服务端回送限流通知而不是静默丢弃
+        let throttled_id = client.submit(&bid(2)).await.unwrap();
+        let TradingEvent::Throttled { message_id, notice } = client.next_event().await.unwrap() else {
+            panic!("expected throttle notice");
+        };
+        assert_eq!((message_id, notice.reason, notice.limit), (throttled_id, ThrottleReason::SessionRate, 20));
+        assert_eq!(client.pacer().min_interval(), Some(Duration::from_millis(50)));
+
+        // 客户端按通知等待后重试成功
+        let started = Instant::now();
+        client.submit(&bid(3)).await.unwrap();
+        assert!(started.elapsed() >= notice.retry_after.saturating_sub(Duration::from_millis(5)));
+        assert!(matches!(
+            client.next_event().await.unwrap(),
+            TradingEvent::Response { response: OuchResponse::Accepted { token: 3, .. }, .. }
+        ));
+
+        client.disconnect().await.unwrap();
+        server.stop().await.unwrap();
+        assert_eq!(server.into_book().depth(1).bids[0].quantity, qty(2));
+    }
+}
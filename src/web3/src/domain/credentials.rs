@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Formatter};
+use thiserror::Error;
+
+/// Errors that can occur while loading exchange credentials
+#[derive(Debug, Error)]
+pub enum CredentialsError {
+    #[error("Credentials not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid credentials: {0}")]
+    Invalid(String),
+
+    #[error("Failed to decrypt credentials: {0}")]
+    Decryption(String),
+
+    #[error("Credential store error: {0}")]
+    Store(String),
+}
+
+/// API credentials for a single exchange account
+///
+/// `Debug` output is redacted so keys never end up in logs.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiCredentials {
+    pub api_key: String,
+    pub secret_key: String,
+    /// Extra passphrase required by some exchanges (e.g. Bitget)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<String>,
+}
+
+impl ApiCredentials {
+    pub fn new(api_key: impl Into<String>, secret_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            secret_key: secret_key.into(),
+            passphrase: None,
+        }
+    }
+
+    pub fn with_passphrase(mut self, passphrase: impl Into<String>) -> Self {
+        self.passphrase = Some(passphrase.into());
+        self
+    }
+}
+
+impl Debug for ApiCredentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiCredentials")
+            .field("api_key", &redact(&self.api_key))
+            .field("secret_key", &"***")
+            .field("passphrase", &self.passphrase.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+/// Keep only a short prefix of the key for identification
+fn redact(key: &str) -> String {
+    let prefix: String = key.chars().take(4).collect();
+    format!("{}***", prefix)
+}
+
+/// Source of exchange API credentials
+///
+/// Trading and user-data gateways receive credentials through this
+/// interface instead of reading keys from configuration files; the
+/// infrastructure layer provides env var, encrypted file and (behind the
+/// `keychain` feature) OS keychain implementations.
+pub trait CredentialsProvider: Send + Sync {
+    /// Load credentials for an exchange (e.g. "binance", "bitget")
+    fn credentials(&self, exchange: &str) -> Result<ApiCredentials, CredentialsError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let credentials = ApiCredentials::new("abcd1234", "topsecret").with_passphrase("pass");
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("abcd***"));
+        assert!(!debug.contains("1234"));
+        assert!(!debug.contains("topsecret"));
+        assert!(!debug.contains("pass\""));
+    }
+}
@@ -4,7 +4,7 @@ pub mod symbol;
 pub mod ticker;
 
 // Re-export for convenience
-pub use orderbook::{OrderBook, OrderBookLevel};
+pub use orderbook::{BookNormalizationError, OrderBook, OrderBookLevel, OrderBookMetadata};
 pub use price::{Price, Quantity};
 pub use symbol::Symbol;
 pub use ticker::Ticker;
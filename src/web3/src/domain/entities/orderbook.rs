@@ -1,6 +1,8 @@
 use super::{price::{Price, Quantity}, symbol::Symbol};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
+use thiserror::Error;
 
 /// OrderBookLevel represents a single price level in the order book
 /// Optimized for low-latency with inline functions
@@ -26,6 +28,31 @@ impl Display for OrderBookLevel {
     }
 }
 
+/// Metadata describing how an order book snapshot was obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderBookMetadata {
+    /// Depth requested by the caller
+    pub requested_depth: usize,
+    /// Exchange-native limit actually sent to the exchange
+    pub exchange_limit: usize,
+    /// Whether the book was truncated to exactly `requested_depth`
+    pub truncated: bool,
+}
+
+/// Errors raised when an exchange book cannot be repaired by normalization
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum BookNormalizationError {
+    #[error("Invalid {side} level: price {price}, quantity {quantity}")]
+    InvalidLevel {
+        side: &'static str,
+        price: f64,
+        quantity: f64,
+    },
+
+    #[error("Crossed book: best bid {bid} > best ask {ask}")]
+    Crossed { bid: f64, ask: f64 },
+}
+
 /// OrderBook represents the limit order book depth for a trading pair
 /// Supports up to 100 levels on each side (bid/ask)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -38,6 +65,9 @@ pub struct OrderBook {
     pub asks: Vec<OrderBookLevel>,
     /// Timestamp in milliseconds
     pub timestamp: u64,
+    /// Request metadata (set by gateways for REST snapshots)
+    #[serde(default)]
+    pub metadata: Option<OrderBookMetadata>,
 }
 
 impl OrderBook {
@@ -53,7 +83,46 @@ impl OrderBook {
             bids,
             asks,
             timestamp,
+            metadata: None,
+        }
+    }
+
+    /// Create an order book from untrusted exchange levels
+    ///
+    /// Exchange ordering is not relied upon: levels are validated, zero-quantity
+    /// levels are dropped, duplicate prices are merged, bids are sorted descending
+    /// and asks ascending, and each side is capped to `max_depth` when given.
+    /// Books whose best bid is above the best ask are rejected.
+    pub fn normalized(
+        symbol: Symbol,
+        bids: Vec<OrderBookLevel>,
+        asks: Vec<OrderBookLevel>,
+        timestamp: u64,
+        max_depth: Option<usize>,
+    ) -> Result<Self, BookNormalizationError> {
+        let bids = normalize_side("bid", bids, max_depth, |a, b| b.total_cmp(&a))?;
+        let asks = normalize_side("ask", asks, max_depth, |a, b| a.total_cmp(&b))?;
+        if let (Some(bid), Some(ask)) = (bids.first(), asks.first()) {
+            if bid.price.value() > ask.price.value() {
+                return Err(BookNormalizationError::Crossed {
+                    bid: bid.price.value(),
+                    ask: ask.price.value(),
+                });
+            }
         }
+        Ok(Self::new(symbol, bids, asks, timestamp))
+    }
+
+    /// Attach request metadata
+    pub fn with_metadata(mut self, metadata: OrderBookMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Keep at most `depth` levels on each side
+    pub fn truncate(&mut self, depth: usize) {
+        self.bids.truncate(depth);
+        self.asks.truncate(depth);
     }
 
     /// Get the best bid price (highest buy price)
@@ -90,6 +159,39 @@ impl OrderBook {
     }
 }
 
+/// Validate, filter, sort, merge and cap one side of the book
+fn normalize_side(
+    side: &'static str,
+    mut levels: Vec<OrderBookLevel>,
+    max_depth: Option<usize>,
+    order: impl Fn(f64, f64) -> Ordering,
+) -> Result<Vec<OrderBookLevel>, BookNormalizationError> {
+    for level in &levels {
+        let (price, quantity) = (level.price.value(), level.quantity.value());
+        if !price.is_finite() || price <= 0.0 || !quantity.is_finite() || quantity < 0.0 {
+            return Err(BookNormalizationError::InvalidLevel { side, price, quantity });
+        }
+    }
+
+    levels.retain(|level| level.quantity.is_positive());
+    levels.sort_by(|a, b| order(a.price.value(), b.price.value()));
+
+    let mut merged: Vec<OrderBookLevel> = Vec::with_capacity(levels.len());
+    for level in levels {
+        match merged.last_mut() {
+            Some(last) if last.price == level.price => {
+                last.quantity = Quantity::new(last.quantity.value() + level.quantity.value());
+            }
+            _ => merged.push(level),
+        }
+    }
+
+    if let Some(depth) = max_depth {
+        merged.truncate(depth);
+    }
+    Ok(merged)
+}
+
 impl Display for OrderBook {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "OrderBook for {}", self.symbol)?;
@@ -138,4 +240,53 @@ mod tests {
         assert_eq!(ob.best_ask(), Some(Price::new(50001.0)));
         assert_eq!(ob.spread(), Some(1.0));
     }
+
+    #[test]
+    fn test_truncate() {
+        let levels: Vec<OrderBookLevel> = (0..5)
+            .map(|i| OrderBookLevel::new(Price::new(100.0 + i as f64), Quantity::new(1.0)))
+            .collect();
+        let mut ob = OrderBook::new(Symbol::new("BTCUSDT"), levels.clone(), levels, 1234567890);
+
+        ob.truncate(3);
+        assert_eq!(ob.bid_depth(), 3);
+        assert_eq!(ob.ask_depth(), 3);
+        assert_eq!(ob.best_bid(), Some(Price::new(100.0)));
+    }
+
+    fn level(price: f64, quantity: f64) -> OrderBookLevel {
+        OrderBookLevel::new(Price::new(price), Quantity::new(quantity))
+    }
+
+    #[test]
+    fn test_normalized_repairs_levels() {
+        let ob = OrderBook::normalized(
+            Symbol::new("BTCUSDT"),
+            vec![level(99.0, 1.0), level(100.0, 0.0), level(101.0, 2.0), level(99.0, 0.5), level(98.0, 1.0)],
+            vec![level(104.0, 1.0), level(102.0, 3.0), level(103.0, 0.0)],
+            1234567890,
+            Some(2),
+        )
+        .unwrap();
+
+        assert_eq!(ob.bids, vec![level(101.0, 2.0), level(99.0, 1.5)]);
+        assert_eq!(ob.asks, vec![level(102.0, 3.0), level(104.0, 1.0)]);
+    }
+
+    #[test]
+    fn test_normalized_rejects_bad_books() {
+        let crossed = OrderBook::normalized(
+            Symbol::new("BTCUSDT"),
+            vec![level(101.0, 1.0)],
+            vec![level(100.0, 1.0)],
+            0,
+            None,
+        );
+        assert_eq!(crossed, Err(BookNormalizationError::Crossed { bid: 101.0, ask: 100.0 }));
+
+        let invalid = OrderBook::normalized(Symbol::new("BTCUSDT"), vec![level(f64::NAN, 1.0)], vec![], 0, None);
+        assert!(matches!(invalid, Err(BookNormalizationError::InvalidLevel { side: "bid", .. })));
+        let negative = OrderBook::normalized(Symbol::new("BTCUSDT"), vec![], vec![level(100.0, -1.0)], 0, None);
+        assert!(matches!(negative, Err(BookNormalizationError::InvalidLevel { side: "ask", .. })));
+    }
 }
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use thiserror::Error;
 
-use crate::domain::entities::{OrderBook, Symbol, Ticker};
+use crate::domain::entities::{BookNormalizationError, OrderBook, Symbol, Ticker};
 
 /// Errors that can occur during market data operations
 #[derive(Debug, Error)]
@@ -23,6 +23,12 @@ pub enum MarketDataError {
 
     #[error("Subscription error: {0}")]
     SubscriptionError(String),
+
+    #[error("Subscription rejected: {0}")]
+    SubscriptionRejected(String),
+
+    #[error("Invalid order book: {0}")]
+    InvalidOrderBook(#[from] BookNormalizationError),
 }
 
 /// Gateway interface for receiving real-time market data
@@ -48,7 +54,9 @@ pub trait MarketDataGateway: Send + Sync {
     /// * `depth` - Number of levels to retrieve (default: 100, max: 100)
     ///
     /// # Returns
-    /// Returns an OrderBook with up to `depth` levels on both bid and ask sides
+    /// Returns an OrderBook fetched with the closest exchange-native limit.
+    /// Unless the gateway is configured for exact depth, this may contain more
+    /// than `depth` levels; `OrderBook::metadata` records the limit used.
     ///
     /// # Example
     /// ```
@@ -1,2 +1,4 @@
+pub mod credentials;
 pub mod entities;
 pub mod gateways;
+pub mod universe;
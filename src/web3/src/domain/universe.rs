@@ -0,0 +1,301 @@
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::domain::entities::{Symbol, Ticker};
+use crate::domain::gateways::{MarketDataError, MarketDataGateway};
+
+fn default_depth() -> usize {
+    100
+}
+
+fn default_interval_ms() -> u64 {
+    1000
+}
+
+/// Settings applied to every symbol unless overridden
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct SymbolSettings {
+    /// Order book depth to request
+    #[serde(default = "default_depth")]
+    pub depth: usize,
+    /// Snapshot/publish interval in milliseconds
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+}
+
+impl Default for SymbolSettings {
+    fn default() -> Self {
+        Self {
+            depth: default_depth(),
+            interval_ms: default_interval_ms(),
+        }
+    }
+}
+
+/// Per-symbol override; unset fields fall back to the universe defaults
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct SymbolOverride {
+    pub depth: Option<usize>,
+    pub interval_ms: Option<u64>,
+}
+
+/// Set of symbols to track, loaded from configuration
+///
+/// Symbols are listed explicitly and/or selected with glob patterns
+/// (`*` matches any run of characters, `?` a single character) that are
+/// expanded against the exchange's instrument catalog.
+///
+/// # Example
+/// ```
+/// use web3::domain::universe::Universe;
+///
+/// let universe = Universe::from_json(r#"{
+///     "symbols": ["BTCUSDT"],
+///     "patterns": ["*ETH*"],
+///     "defaults": { "depth": 50, "interval_ms": 500 },
+///     "overrides": { "BTCUSDT": { "depth": 100 } }
+/// }"#).unwrap();
+/// assert!(universe.matches("ETHBTC"));
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Universe {
+    /// Explicitly listed symbols
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    /// Glob patterns expanded against the instrument catalog
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Default per-symbol settings
+    #[serde(default)]
+    pub defaults: SymbolSettings,
+    /// Per-symbol overrides keyed by symbol
+    #[serde(default)]
+    pub overrides: HashMap<String, SymbolOverride>,
+}
+
+/// A resolved universe member with its effective settings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniverseEntry {
+    pub symbol: Symbol,
+    pub depth: usize,
+    pub interval: Duration,
+}
+
+impl Universe {
+    /// Parse a universe from its JSON configuration
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Create a universe from an explicit symbol list
+    pub fn from_symbols<I, S>(symbols: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            symbols: symbols.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Check whether a symbol belongs to the universe
+    pub fn matches(&self, symbol: &str) -> bool {
+        let symbol = symbol.to_uppercase();
+        self.symbols.iter().any(|s| s.eq_ignore_ascii_case(&symbol))
+            || self
+                .patterns
+                .iter()
+                .any(|p| glob_match(p.to_uppercase().as_bytes(), symbol.as_bytes()))
+    }
+
+    /// Effective settings for a symbol after applying overrides
+    pub fn settings_for(&self, symbol: &Symbol) -> SymbolSettings {
+        let overrides = self
+            .overrides
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(symbol.as_str()))
+            .map(|(_, o)| *o)
+            .unwrap_or_default();
+
+        SymbolSettings {
+            depth: overrides.depth.unwrap_or(self.defaults.depth),
+            interval_ms: overrides.interval_ms.unwrap_or(self.defaults.interval_ms),
+        }
+    }
+
+    /// Resolve the universe into concrete entries
+    ///
+    /// Explicit symbols come first in configuration order, followed by
+    /// catalog symbols matching any pattern in catalog order. Duplicates
+    /// are dropped.
+    pub fn resolve(&self, catalog: &[Symbol]) -> Vec<UniverseEntry> {
+        let mut seen = HashSet::new();
+        let explicit = self.symbols.iter().map(|s| Symbol::new(s.as_str()));
+        let matched = catalog.iter().filter(|s| {
+            self.patterns
+                .iter()
+                .any(|p| glob_match(p.to_uppercase().as_bytes(), s.as_str().as_bytes()))
+        });
+
+        explicit
+            .chain(matched.cloned())
+            .filter(|symbol| seen.insert(symbol.clone()))
+            .map(|symbol| {
+                let settings = self.settings_for(&symbol);
+                UniverseEntry {
+                    symbol,
+                    depth: settings.depth,
+                    interval: Duration::from_millis(settings.interval_ms),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Glob match supporting `*` and `?`
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == b'?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((sp, st)) = star {
+            // Let the last `*` absorb one more character
+            p = sp + 1;
+            t = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Subscription pacing used to stay under exchange connection/message limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubscriptionPacing {
+    /// Subscriptions issued back-to-back before pausing
+    pub burst: usize,
+    /// Pause between bursts
+    pub interval: Duration,
+}
+
+impl Default for SubscriptionPacing {
+    /// Binance allows 5 incoming messages per second per connection
+    fn default() -> Self {
+        Self {
+            burst: 5,
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Outcome of a universe bootstrap
+pub struct BootstrapReport<G> {
+    /// Entries that were subscribed, with the gateway serving each
+    pub subscribed: Vec<(UniverseEntry, G)>,
+    /// Entries whose subscription failed
+    pub failed: Vec<(UniverseEntry, MarketDataError)>,
+}
+
+impl<G> BootstrapReport<G> {
+    /// Whether every symbol in the universe was subscribed
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Subscribe to every entry of a resolved universe with staggered pacing
+///
+/// Exchange gateways hold one stream per instance, so `make_gateway` is
+/// called once per entry. Every ticker is forwarded to `on_ticker`. A
+/// failed subscription is recorded in the report and does not abort the
+/// bootstrap.
+pub async fn bootstrap<G, F>(
+    entries: Vec<UniverseEntry>,
+    pacing: SubscriptionPacing,
+    mut make_gateway: F,
+    on_ticker: Arc<dyn Fn(Ticker) + Send + Sync>,
+) -> BootstrapReport<G>
+where
+    G: MarketDataGateway,
+    F: FnMut(&UniverseEntry) -> G,
+{
+    let burst = pacing.burst.max(1);
+    let mut report = BootstrapReport {
+        subscribed: Vec::with_capacity(entries.len()),
+        failed: Vec::new(),
+    };
+
+    for (i, entry) in entries.into_iter().enumerate() {
+        if i > 0 && i % burst == 0 && !pacing.interval.is_zero() {
+            sleep(pacing.interval).await;
+        }
+
+        let gateway = make_gateway(&entry);
+        let callback = Arc::clone(&on_ticker);
+        match gateway
+            .subscribe_ticker(entry.symbol.clone(), Box::new(move |ticker| callback(ticker)))
+            .await
+        {
+            Ok(()) => report.subscribed.push((entry, gateway)),
+            Err(e) => {
+                eprintln!("⚠️  Failed to subscribe {}: {}", entry.symbol, e);
+                report.failed.push((entry, e));
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"*USDT", b"BTCUSDT"));
+        assert!(glob_match(b"BTC*", b"BTCUSDT"));
+        assert!(glob_match(b"?TCUSDT", b"BTCUSDT"));
+        assert!(glob_match(b"*", b"ANY"));
+        assert!(!glob_match(b"*USDC", b"BTCUSDT"));
+        assert!(!glob_match(b"BTC", b"BTCUSDT"));
+    }
+
+    #[test]
+    fn test_resolve_with_patterns_and_overrides() {
+        let universe = Universe::from_json(
+            r#"{
+                "symbols": ["btcusdt", "SOLUSDT"],
+                "patterns": ["*USDT"],
+                "defaults": { "depth": 20, "interval_ms": 250 },
+                "overrides": { "ETHUSDT": { "depth": 50 } }
+            }"#,
+        )
+        .unwrap();
+
+        let catalog: Vec<Symbol> = ["ETHBTC", "BTCUSDT", "ETHUSDT"]
+            .into_iter()
+            .map(Symbol::new)
+            .collect();
+        let entries = universe.resolve(&catalog);
+
+        let symbols: Vec<&str> = entries.iter().map(|e| e.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["BTCUSDT", "SOLUSDT", "ETHUSDT"]);
+        assert_eq!(entries[0].depth, 20);
+        assert_eq!(entries[0].interval, Duration::from_millis(250));
+        assert_eq!(entries[2].depth, 50);
+        assert_eq!(entries[2].interval, Duration::from_millis(250));
+    }
+}
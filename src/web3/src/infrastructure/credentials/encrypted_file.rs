@@ -0,0 +1,295 @@
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use crate::domain::credentials::{ApiCredentials, CredentialsError, CredentialsProvider};
+
+const FORMAT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// On-disk envelope of an encrypted credentials file
+///
+/// The plaintext is a JSON map of exchange name to `ApiCredentials`,
+/// encrypted with AES-256-GCM under a key derived from the passphrase
+/// with PBKDF2-HMAC-SHA256.
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedFile {
+    version: u32,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Reads credentials from a passphrase-encrypted file
+///
+/// The file is decrypted once when opened; the passphrase itself is not
+/// retained.
+pub struct EncryptedFileCredentialsProvider {
+    credentials: HashMap<String, ApiCredentials>,
+}
+
+impl EncryptedFileCredentialsProvider {
+    /// Default PBKDF2 iteration count for newly sealed files
+    pub const DEFAULT_ITERATIONS: u32 = 600_000;
+
+    /// Lowest PBKDF2 iteration count accepted when sealing or opening
+    pub const MIN_ITERATIONS: u32 = 100_000;
+
+    /// Open and decrypt a credentials file
+    pub fn open(path: impl AsRef<Path>, passphrase: &str) -> Result<Self, CredentialsError> {
+        let sealed = fs::read_to_string(path.as_ref()).map_err(|e| {
+            CredentialsError::Store(format!("{}: {}", path.as_ref().display(), e))
+        })?;
+        Self::from_sealed(&sealed, passphrase)
+    }
+
+    /// Decrypt credentials from the contents of a sealed file
+    pub fn from_sealed(sealed: &str, passphrase: &str) -> Result<Self, CredentialsError> {
+        let file: SealedFile = serde_json::from_str(sealed)
+            .map_err(|e| CredentialsError::Invalid(format!("malformed credentials file: {}", e)))?;
+        if file.version != FORMAT_VERSION {
+            return Err(CredentialsError::Invalid(format!(
+                "unsupported credentials file version {}",
+                file.version
+            )));
+        }
+
+        check_iterations(file.iterations)?;
+
+        let salt = decode(&file.salt)?;
+        let nonce: [u8; NONCE_LEN] = decode(&file.nonce)?
+            .try_into()
+            .map_err(|_| CredentialsError::Invalid("bad nonce length".to_string()))?;
+        let ciphertext = decode(&file.ciphertext)?;
+
+        let cipher = cipher(passphrase, &salt, file.iterations);
+        let plaintext = cipher
+            .decrypt(&Nonce::from(nonce), ciphertext.as_ref())
+            .map_err(|_| CredentialsError::Decryption("wrong passphrase or corrupted file".to_string()))?;
+
+        let credentials: HashMap<String, ApiCredentials> = serde_json::from_slice(&plaintext)
+            .map_err(|e| CredentialsError::Invalid(e.to_string()))?;
+        Ok(Self {
+            credentials: normalize(credentials)?,
+        })
+    }
+
+    /// Encrypt credentials into the sealed file format
+    pub fn seal(
+        credentials: &HashMap<String, ApiCredentials>,
+        passphrase: &str,
+        iterations: u32,
+    ) -> Result<String, CredentialsError> {
+        check_iterations(iterations)?;
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        let plaintext = serde_json::to_vec(&normalize(credentials.clone())?)
+            .map_err(|e| CredentialsError::Invalid(e.to_string()))?;
+        let ciphertext = cipher(passphrase, &salt, iterations)
+            .encrypt(&Nonce::from(nonce), plaintext.as_ref())
+            .map_err(|e| CredentialsError::Store(e.to_string()))?;
+
+        let file = SealedFile {
+            version: FORMAT_VERSION,
+            iterations,
+            salt: BASE64.encode(salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        };
+        serde_json::to_string_pretty(&file).map_err(|e| CredentialsError::Store(e.to_string()))
+    }
+
+    /// Encrypt credentials and write them to `path` (owner-only, atomically replaced)
+    pub fn write(
+        path: impl AsRef<Path>,
+        credentials: &HashMap<String, ApiCredentials>,
+        passphrase: &str,
+    ) -> Result<(), CredentialsError> {
+        let sealed = Self::seal(credentials, passphrase, Self::DEFAULT_ITERATIONS)?;
+        write_sealed(path.as_ref(), &sealed)
+    }
+}
+
+/// Atomically write a sealed file
+///
+/// The contents go to a temporary sibling that is renamed into place, so
+/// readers never see a partial file. On unix the file is owner-only (0600).
+fn write_sealed(path: &Path, sealed: &str) -> Result<(), CredentialsError> {
+    let tmp = path.with_extension("tmp");
+    let written = (|| {
+        // A stale temp file could carry looser permissions; always create afresh
+        let _ = fs::remove_file(&tmp);
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&tmp)?;
+        file.write_all(sealed.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written.map_err(|e| CredentialsError::Store(format!("{}: {}", path.display(), e)))
+}
+
+impl CredentialsProvider for EncryptedFileCredentialsProvider {
+    fn credentials(&self, exchange: &str) -> Result<ApiCredentials, CredentialsError> {
+        self.credentials
+            .get(&exchange.to_lowercase())
+            .cloned()
+            .ok_or_else(|| CredentialsError::NotFound(exchange.to_string()))
+    }
+}
+
+/// Exchange names are matched case-insensitively, so keys are stored lowercase
+fn normalize(
+    credentials: HashMap<String, ApiCredentials>,
+) -> Result<HashMap<String, ApiCredentials>, CredentialsError> {
+    let mut normalized = HashMap::with_capacity(credentials.len());
+    for (exchange, creds) in credentials {
+        let key = exchange.to_lowercase();
+        if normalized.insert(key, creds).is_some() {
+            return Err(CredentialsError::Invalid(format!(
+                "duplicate credentials for exchange {}",
+                exchange
+            )));
+        }
+    }
+    Ok(normalized)
+}
+
+fn check_iterations(iterations: u32) -> Result<(), CredentialsError> {
+    if iterations < EncryptedFileCredentialsProvider::MIN_ITERATIONS {
+        return Err(CredentialsError::Invalid(format!(
+            "PBKDF2 iteration count {} is below the minimum of {}",
+            iterations,
+            EncryptedFileCredentialsProvider::MIN_ITERATIONS
+        )));
+    }
+    Ok(())
+}
+
+fn cipher(passphrase: &str, salt: &[u8], iterations: u32) -> Aes256Gcm {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, &mut key);
+    Aes256Gcm::new(&Key::<Aes256Gcm>::from(key))
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, CredentialsError> {
+    BASE64
+        .decode(value)
+        .map_err(|e| CredentialsError::Invalid(format!("bad base64: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: u32 = EncryptedFileCredentialsProvider::MIN_ITERATIONS;
+
+    fn sample() -> HashMap<String, ApiCredentials> {
+        HashMap::from([
+            ("binance".to_string(), ApiCredentials::new("bkey", "bsecret")),
+            (
+                "bitget".to_string(),
+                ApiCredentials::new("gkey", "gsecret").with_passphrase("gphrase"),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_seal_and_open_roundtrip() {
+        let sealed = EncryptedFileCredentialsProvider::seal(&sample(), "hunter2", MIN).unwrap();
+        assert!(!sealed.contains("bsecret"));
+
+        let provider = EncryptedFileCredentialsProvider::from_sealed(&sealed, "hunter2").unwrap();
+        assert_eq!(provider.credentials("Bitget").unwrap(), sample()["bitget"]);
+        assert!(matches!(
+            provider.credentials("okx"),
+            Err(CredentialsError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_wrong_passphrase_is_rejected() {
+        let sealed = EncryptedFileCredentialsProvider::seal(&sample(), "hunter2", MIN).unwrap();
+        assert!(matches!(
+            EncryptedFileCredentialsProvider::from_sealed(&sealed, "hunter3"),
+            Err(CredentialsError::Decryption(_))
+        ));
+    }
+
+    #[test]
+    fn test_low_iteration_count_is_rejected() {
+        assert!(matches!(
+            EncryptedFileCredentialsProvider::seal(&sample(), "hunter2", MIN - 1),
+            Err(CredentialsError::Invalid(_))
+        ));
+
+        // A file tampered down to a single iteration must not be opened
+        let sealed = EncryptedFileCredentialsProvider::seal(&sample(), "hunter2", MIN).unwrap();
+        let mut file: SealedFile = serde_json::from_str(&sealed).unwrap();
+        file.iterations = 1;
+        let tampered = serde_json::to_string(&file).unwrap();
+        match EncryptedFileCredentialsProvider::from_sealed(&tampered, "hunter2") {
+            Err(CredentialsError::Invalid(msg)) => assert!(msg.contains("iteration"), "{}", msg),
+            other => panic!("expected Invalid, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_exchange_names_are_case_insensitive() {
+        let credentials = HashMap::from([("Binance".to_string(), ApiCredentials::new("bkey", "bsecret"))]);
+        let sealed = EncryptedFileCredentialsProvider::seal(&credentials, "hunter2", MIN).unwrap();
+        let provider = EncryptedFileCredentialsProvider::from_sealed(&sealed, "hunter2").unwrap();
+        assert_eq!(provider.credentials("binance").unwrap(), credentials["Binance"]);
+        assert_eq!(provider.credentials("BINANCE").unwrap(), credentials["Binance"]);
+
+        let clash = HashMap::from([
+            ("OKX".to_string(), ApiCredentials::new("a", "b")),
+            ("okx".to_string(), ApiCredentials::new("c", "d")),
+        ]);
+        assert!(matches!(
+            EncryptedFileCredentialsProvider::seal(&clash, "hunter2", MIN),
+            Err(CredentialsError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_is_owner_only() {
+        let dir = std::env::temp_dir().join(format!("rlob-credentials-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("secrets.json");
+        let sealed = EncryptedFileCredentialsProvider::seal(&sample(), "hunter2", MIN).unwrap();
+        write_sealed(&path, &sealed).unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert!(!path.with_extension("tmp").exists());
+        let provider = EncryptedFileCredentialsProvider::open(&path, "hunter2").unwrap();
+        assert_eq!(provider.credentials("binance").unwrap(), sample()["binance"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
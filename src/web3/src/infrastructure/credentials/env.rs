@@ -0,0 +1,91 @@
+use std::env;
+
+use crate::domain::credentials::{ApiCredentials, CredentialsError, CredentialsProvider};
+
+/// Reads credentials from environment variables
+///
+/// For exchange `binance` and the default prefix this looks up
+/// `WEB3_BINANCE_API_KEY`, `WEB3_BINANCE_SECRET_KEY` and the optional
+/// `WEB3_BINANCE_PASSPHRASE`.
+pub struct EnvCredentialsProvider {
+    prefix: String,
+}
+
+impl EnvCredentialsProvider {
+    /// Default variable prefix
+    pub const DEFAULT_PREFIX: &'static str = "WEB3";
+
+    pub fn new() -> Self {
+        Self::with_prefix(Self::DEFAULT_PREFIX)
+    }
+
+    pub fn with_prefix(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+
+    /// Environment variable name for a credential field
+    pub fn var_name(&self, exchange: &str, field: &str) -> String {
+        format!("{}_{}_{}", self.prefix, exchange, field).to_uppercase()
+    }
+
+    fn var(&self, exchange: &str, field: &str) -> Option<String> {
+        env::var(self.var_name(exchange, field))
+            .ok()
+            .filter(|value| !value.is_empty())
+    }
+}
+
+impl Default for EnvCredentialsProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CredentialsProvider for EnvCredentialsProvider {
+    fn credentials(&self, exchange: &str) -> Result<ApiCredentials, CredentialsError> {
+        let api_key = self.var(exchange, "API_KEY");
+        let secret_key = self.var(exchange, "SECRET_KEY");
+
+        match (api_key, secret_key) {
+            (Some(api_key), Some(secret_key)) => Ok(ApiCredentials {
+                api_key,
+                secret_key,
+                passphrase: self.var(exchange, "PASSPHRASE"),
+            }),
+            (None, None) => Err(CredentialsError::NotFound(exchange.to_string())),
+            _ => Err(CredentialsError::Invalid(format!(
+                "{} and {} must both be set",
+                self.var_name(exchange, "API_KEY"),
+                self.var_name(exchange, "SECRET_KEY")
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_prefixed_variables() {
+        let provider = EnvCredentialsProvider::with_prefix("WEB3_ENV_TEST");
+        env::set_var("WEB3_ENV_TEST_BITGET_API_KEY", "key");
+        env::set_var("WEB3_ENV_TEST_BITGET_SECRET_KEY", "secret");
+        env::set_var("WEB3_ENV_TEST_BITGET_PASSPHRASE", "phrase");
+
+        let credentials = provider.credentials("bitget").unwrap();
+        assert_eq!(credentials, ApiCredentials::new("key", "secret").with_passphrase("phrase"));
+
+        env::remove_var("WEB3_ENV_TEST_BITGET_SECRET_KEY");
+        assert!(matches!(
+            provider.credentials("bitget"),
+            Err(CredentialsError::Invalid(_))
+        ));
+        assert!(matches!(
+            provider.credentials("binance"),
+            Err(CredentialsError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,45 @@
+use keyring::Entry;
+
+use crate::domain::credentials::{ApiCredentials, CredentialsError, CredentialsProvider};
+
+/// Reads credentials from the OS keychain (requires the `keychain` feature)
+///
+/// Each exchange is stored as one entry under `service`, with the account
+/// name set to the exchange and the password holding the credentials JSON.
+pub struct KeychainCredentialsProvider {
+    service: String,
+}
+
+impl KeychainCredentialsProvider {
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    /// Store credentials for an exchange in the keychain
+    pub fn store(&self, exchange: &str, credentials: &ApiCredentials) -> Result<(), CredentialsError> {
+        let json = serde_json::to_string(credentials)
+            .map_err(|e| CredentialsError::Invalid(e.to_string()))?;
+        self.entry(exchange)?
+            .set_password(&json)
+            .map_err(|e| CredentialsError::Store(e.to_string()))
+    }
+
+    fn entry(&self, exchange: &str) -> Result<Entry, CredentialsError> {
+        Entry::new(&self.service, exchange).map_err(|e| CredentialsError::Store(e.to_string()))
+    }
+}
+
+impl CredentialsProvider for KeychainCredentialsProvider {
+    fn credentials(&self, exchange: &str) -> Result<ApiCredentials, CredentialsError> {
+        let json = match self.entry(exchange)?.get_password() {
+            Ok(json) => json,
+            Err(keyring::Error::NoEntry) => {
+                return Err(CredentialsError::NotFound(exchange.to_string()))
+            }
+            Err(e) => return Err(CredentialsError::Store(e.to_string())),
+        };
+        serde_json::from_str(&json).map_err(|e| CredentialsError::Invalid(e.to_string()))
+    }
+}
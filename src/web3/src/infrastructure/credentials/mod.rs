@@ -0,0 +1,38 @@
+pub mod encrypted_file;
+pub mod env;
+#[cfg(feature = "keychain")]
+pub mod keychain;
+
+// Re-export for convenience
+pub use encrypted_file::EncryptedFileCredentialsProvider;
+pub use env::EnvCredentialsProvider;
+#[cfg(feature = "keychain")]
+pub use keychain::KeychainCredentialsProvider;
+
+use crate::domain::credentials::{ApiCredentials, CredentialsError, CredentialsProvider};
+
+/// Tries each provider in order, returning the first credentials found
+///
+/// Only `NotFound` falls through to the next provider; decryption or store
+/// errors are returned immediately so a broken store is never masked.
+pub struct ChainedCredentialsProvider {
+    providers: Vec<Box<dyn CredentialsProvider>>,
+}
+
+impl ChainedCredentialsProvider {
+    pub fn new(providers: Vec<Box<dyn CredentialsProvider>>) -> Self {
+        Self { providers }
+    }
+}
+
+impl CredentialsProvider for ChainedCredentialsProvider {
+    fn credentials(&self, exchange: &str) -> Result<ApiCredentials, CredentialsError> {
+        for provider in &self.providers {
+            match provider.credentials(exchange) {
+                Err(CredentialsError::NotFound(_)) => continue,
+                result => return result,
+            }
+        }
+        Err(CredentialsError::NotFound(exchange.to_string()))
+    }
+}
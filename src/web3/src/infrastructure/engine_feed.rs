@@ -0,0 +1,253 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use lib::orderbook::{
+    BookDepth, DepthLevel, OrderBook as EngineBook, PriceConverter, SnapshotReader,
+};
+use tokio::sync::mpsc;
+
+use crate::domain::entities::{OrderBook, OrderBookLevel, Price, Quantity, Symbol};
+
+/// Configuration for the market-by-price feed
+#[derive(Debug, Clone)]
+pub struct MbpFeedConfig {
+    /// Number of price levels published on each side
+    pub depth: usize,
+    /// Minimum time between two published books
+    pub interval: Duration,
+    /// Size of one engine lot in base-asset units
+    pub lot_size: f64,
+    /// Skip publishing when the book has not changed since the last update
+    pub suppress_unchanged: bool,
+}
+
+impl Default for MbpFeedConfig {
+    fn default() -> Self {
+        Self {
+            depth: 20,
+            interval: Duration::from_millis(100),
+            lot_size: 1.0,
+            suppress_unchanged: true,
+        }
+    }
+}
+
+/// Market-by-price feed generator
+///
+/// Converts the matching engine's integer tick/lot depth into the
+/// exchange-style `OrderBook` entity used by the gateways, so that
+/// the internal engine can be observed through the same types as the
+/// Binance and Bitget feeds.
+///
+/// The feed is pull-based: call `poll` with the current time and the
+/// engine depth, and it returns a book only when the configured cadence
+/// has elapsed (and, optionally, when something changed).
+#[derive(Debug, Clone)]
+pub struct MbpFeed {
+    symbol: Symbol,
+    config: MbpFeedConfig,
+    converter: PriceConverter,
+    last_published_ms: Option<u64>,
+    last_depth: Option<BookDepth>,
+    published: u64,
+}
+
+impl MbpFeed {
+    /// Create a feed for `symbol`, converting engine ticks with `converter`
+    pub fn new(symbol: Symbol, converter: PriceConverter, config: MbpFeedConfig) -> Self {
+        Self {
+            symbol,
+            config,
+            converter,
+            last_published_ms: None,
+            last_depth: None,
+            published: 0,
+        }
+    }
+
+    /// Create a feed using the engine's instrument specification for price conversion
+    pub fn for_engine(symbol: Symbol, book: &EngineBook, config: MbpFeedConfig) -> Self {
+        Self::new(symbol, book.instrument().converter(), config)
+    }
+
+    /// Feed configuration
+    #[inline]
+    pub fn config(&self) -> &MbpFeedConfig {
+        &self.config
+    }
+
+    /// Number of books published so far
+    #[inline]
+    pub fn published(&self) -> u64 {
+        self.published
+    }
+
+    /// Convert an engine depth into an `OrderBook` entity, truncated to the configured depth
+    pub fn convert(&self, depth: &BookDepth, timestamp_ms: u64) -> OrderBook {
+        let levels = |side: &[DepthLevel]| -> Vec<OrderBookLevel> {
+            side.iter()
+                .take(self.config.depth)
+                .map(|level| {
+                    OrderBookLevel::new(
+                        Price::new(self.converter.to_decimal(level.price)),
+                        Quantity::new(level.quantity.get() as f64 * self.config.lot_size),
+                    )
+                })
+                .collect()
+        };
+        OrderBook::new(
+            self.symbol.clone(),
+            levels(&depth.bids),
+            levels(&depth.asks),
+            timestamp_ms,
+        )
+    }
+
+    /// Publish the engine depth if the cadence has elapsed
+    ///
+    /// Returns `None` when called before `interval` has passed since the
+    /// previous publication, or when the book is unchanged and
+    /// `suppress_unchanged` is set.
+    pub fn poll(&mut self, depth: &BookDepth, now_ms: u64) -> Option<OrderBook> {
+        if let Some(last) = self.last_published_ms {
+            if now_ms.saturating_sub(last) < self.config.interval.as_millis() as u64 {
+                return None;
+            }
+        }
+        if self.config.suppress_unchanged && self.last_depth.as_ref() == Some(depth) {
+            return None;
+        }
+
+        self.last_published_ms = Some(now_ms);
+        self.last_depth = Some(depth.clone());
+        self.published += 1;
+        Some(self.convert(depth, now_ms))
+    }
+
+    /// Publish directly from an engine book owned by the caller
+    pub fn poll_engine(&mut self, book: &EngineBook, now_ms: u64) -> Option<OrderBook> {
+        let depth = book.depth(self.config.depth);
+        self.poll(&depth, now_ms)
+    }
+
+    /// Run the feed on a timer, reading the engine through a seqlock snapshot reader
+    ///
+    /// The matching thread keeps publishing into the matching `SnapshotWriter`;
+    /// this task never blocks it. Stops once the receiver is dropped.
+    pub async fn run(mut self, reader: SnapshotReader, sink: mpsc::Sender<OrderBook>) {
+        let mut ticker = tokio::time::interval(self.config.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            if sink.is_closed() {
+                return;
+            }
+            // The timer already enforces the cadence; poll only filters unchanged books
+            self.last_published_ms = None;
+            let snapshot = reader.snapshot();
+            if let Some(book) = self.poll(&snapshot.depth, now_millis()) {
+                if sink.send(book).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib::orderbook::{
+        snapshot_channel, InstrumentSpec, Price as Ticks, Quantity as Lots, Side, TimeInForce,
+        TraderId,
+    };
+
+    fn engine() -> EngineBook {
+        let spec = InstrumentSpec::new(1, 2)
+            .with_price_range(Ticks::new(1).unwrap(), Ticks::new(1_000_000).unwrap());
+        let mut book = EngineBook::with_spec(spec, 100);
+        let trader = TraderId::from_str("MM");
+        for (side, price, quantity) in [
+            (Side::Buy, 10_000, 3),
+            (Side::Buy, 9_999, 1),
+            (Side::Buy, 9_998, 2),
+            (Side::Sell, 10_001, 4),
+            (Side::Sell, 10_003, 5),
+        ] {
+            book.limit_order(
+                trader,
+                side,
+                Ticks::new(price).unwrap(),
+                Lots::new(quantity).unwrap(),
+                TimeInForce::Gtc,
+            )
+            .unwrap();
+        }
+        book
+    }
+
+    #[test]
+    fn test_converts_engine_depth() {
+        let book = engine();
+        let config = MbpFeedConfig { depth: 2, lot_size: 0.01, ..MbpFeedConfig::default() };
+        let mut feed = MbpFeed::for_engine(Symbol::new("BTCUSDT"), &book, config);
+
+        let published = feed.poll_engine(&book, 1_000).unwrap();
+        assert_eq!(published.symbol, Symbol::new("BTCUSDT"));
+        assert_eq!(published.timestamp, 1_000);
+        assert_eq!(published.bid_depth(), 2);
+        assert_eq!(published.ask_depth(), 2);
+        assert_eq!(published.bids[0].price, Price::new(100.0));
+        assert_eq!(published.bids[1].price, Price::new(99.99));
+        assert_eq!(published.asks[0].price, Price::new(100.01));
+        assert_eq!(published.asks[1].quantity, Quantity::new(0.05));
+    }
+
+    #[test]
+    fn test_cadence_and_change_suppression() {
+        let mut book = engine();
+        let mut feed = MbpFeed::for_engine(Symbol::new("BTCUSDT"), &book, MbpFeedConfig::default());
+
+        assert!(feed.poll_engine(&book, 1_000).is_some());
+        // Too early
+        assert!(feed.poll_engine(&book, 1_050).is_none());
+        // Unchanged
+        assert!(feed.poll_engine(&book, 1_200).is_none());
+
+        book.limit_order(
+            TraderId::from_str("T"),
+            Side::Buy,
+            Ticks::new(10_001).unwrap(),
+            Lots::new(4).unwrap(),
+            TimeInForce::Ioc,
+        )
+        .unwrap();
+        let published = feed.poll_engine(&book, 1_300).unwrap();
+        assert_eq!(published.asks[0].price, Price::new(100.03));
+        assert_eq!(feed.published(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_from_snapshot_reader() {
+        let book = engine();
+        let (mut writer, reader) = snapshot_channel(5);
+        writer.publish(&book);
+
+        let config = MbpFeedConfig { interval: Duration::from_millis(5), ..MbpFeedConfig::default() };
+        let feed = MbpFeed::for_engine(Symbol::new("BTCUSDT"), &book, config);
+        let (tx, mut rx) = mpsc::channel(4);
+        let task = tokio::spawn(feed.run(reader, tx));
+
+        let published = rx.recv().await.unwrap();
+        assert_eq!(published.bid_depth(), 3);
+        assert_eq!(published.bids[0].price, Price::new(100.0));
+        drop(rx);
+        task.await.unwrap();
+    }
+}
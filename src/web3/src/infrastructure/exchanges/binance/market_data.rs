@@ -1,17 +1,18 @@
 use async_trait::async_trait;
 use futures_util::StreamExt;
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::net::TcpStream;
 use tokio::sync::Mutex;
-use tokio::time::{sleep, Duration};
+use tokio::time::sleep;
 use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 
 use crate::domain::{
-    entities::{OrderBook, Symbol, Ticker},
+    entities::{OrderBook, OrderBookMetadata, Symbol, Ticker},
     gateways::{MarketDataError, MarketDataGateway},
 };
 
+use crate::infrastructure::exchanges::reconnect::{ReconnectConfig, ReconnectGovernor, SupervisorCallback};
 use super::types::{BinanceOrderBookResponse, BinanceTickerResponse};
 
 /// Binance WebSocket endpoints (with fallback support)
@@ -26,33 +27,39 @@ const BINANCE_WS_URLS: &[&str] = &[
 /// Binance REST API base URL
 const BINANCE_REST_API_URL: &str = "https://api.binance.com";
 
-const MAX_RECONNECT_ATTEMPTS: u32 = 10;
-const RECONNECT_DELAY_MS: u64 = 3000;
-
 type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
 
 /// Binance implementation of MarketDataGateway
 ///
 /// Features:
 /// - Multiple endpoint fallback
-/// - Automatic reconnection
+/// - Automatic reconnection with jittered backoff and circuit breaker
 /// - Low-latency message processing
 /// - Thread-safe connection management
 pub struct BinanceMarketDataGateway {
     ws_stream: Arc<Mutex<Option<WsStream>>>,
     connected: Arc<AtomicBool>,
-    reconnect_count: Arc<AtomicU32>,
+    reconnect: Arc<ReconnectGovernor>,
     symbol: Arc<Mutex<Option<Symbol>>>,
+    ws_urls: Arc<Vec<String>>,
+    exact_depth: bool,
 }
 
 impl BinanceMarketDataGateway {
     /// Create a new Binance gateway instance
     pub fn new() -> Self {
+        Self::with_reconnect_config(ReconnectConfig::default())
+    }
+
+    /// Create a new Binance gateway instance with a custom reconnect policy
+    pub fn with_reconnect_config(config: ReconnectConfig) -> Self {
         Self {
             ws_stream: Arc::new(Mutex::new(None)),
             connected: Arc::new(AtomicBool::new(false)),
-            reconnect_count: Arc::new(AtomicU32::new(0)),
+            reconnect: Arc::new(ReconnectGovernor::new(config)),
             symbol: Arc::new(Mutex::new(None)),
+            ws_urls: Arc::new(BINANCE_WS_URLS.iter().map(|u| u.to_string()).collect()),
+            exact_depth: false,
         }
     }
 
@@ -63,7 +70,7 @@ impl BinanceMarketDataGateway {
         // Try each endpoint until one succeeds
         let mut last_error = None;
 
-        for base_url in BINANCE_WS_URLS {
+        for base_url in self.ws_urls.iter() {
             // Using single stream format: wss://stream.binance.com:9443/ws/btcusdt@ticker
             let url = format!("{}/{}@ticker", base_url, symbol_lower);
             println!("⏳ Attempting to connect to: {}", url);
@@ -72,7 +79,7 @@ impl BinanceMarketDataGateway {
                 Ok((ws_stream, _)) => {
                     println!("✅ Successfully connected to Binance WebSocket");
                     self.connected.store(true, Ordering::SeqCst);
-                    self.reconnect_count.store(0, Ordering::SeqCst);
+                    self.reconnect.on_connected();
                     return Ok(ws_stream);
                 }
                 Err(e) => {
@@ -101,25 +108,71 @@ impl BinanceMarketDataGateway {
                 .clone()
         };
 
-        let attempts = self.reconnect_count.fetch_add(1, Ordering::SeqCst);
+        loop {
+            // Jittered backoff; pauses for longer once the reconnect budget is spent
+            let delay = self.reconnect.next_delay()?;
+
+            if self.reconnect.is_circuit_open() {
+                println!("⛔ Reconnect budget exhausted, pausing for {:?}", delay);
+            } else {
+                println!(
+                    "🔄 Attempting to reconnect in {:?}... (attempt {})",
+                    delay,
+                    self.reconnect.attempts()
+                );
+            }
+
+            sleep(delay).await;
 
-        if attempts >= MAX_RECONNECT_ATTEMPTS {
-            return Err(MarketDataError::ReconnectionFailed(attempts));
+            match self.connect_ws(&symbol).await {
+                Ok(new_stream) => {
+                    let mut stream_lock = self.ws_stream.lock().await;
+                    *stream_lock = Some(new_stream);
+                    break;
+                }
+                Err(e) => {
+                    eprintln!("⚠️  Reconnect attempt failed: {}", e);
+                }
+            }
         }
 
-        println!(
-            "🔄 Attempting to reconnect... (attempt {}/{})",
-            attempts + 1,
-            MAX_RECONNECT_ATTEMPTS
-        );
+        Ok(())
+    }
 
-        sleep(Duration::from_millis(RECONNECT_DELAY_MS)).await;
+    /// Override the WebSocket endpoints (e.g. to point at a local mock server)
+    pub fn with_ws_urls(mut self, urls: Vec<String>) -> Self {
+        self.ws_urls = Arc::new(urls);
+        self
+    }
 
-        let new_stream = self.connect_ws(&symbol).await?;
-        let mut stream_lock = self.ws_stream.lock().await;
-        *stream_lock = Some(new_stream);
+    /// Map a requested depth to the closest exchange-native limit
+    ///
+    /// Binance supports: 5, 10, 20, 50, 100, 500, 1000, 5000
+    pub fn native_depth_limit(depth: usize) -> usize {
+        match depth {
+            0..=5 => 5,
+            6..=10 => 10,
+            11..=20 => 20,
+            21..=50 => 50,
+            51..=100 => 100,
+            101..=500 => 500,
+            501..=1000 => 1000,
+            _ => 5000,
+        }
+    }
 
-        Ok(())
+    /// Truncate REST order books to exactly the requested depth
+    ///
+    /// By default the exchange-native limit closest to the requested depth is
+    /// returned as-is (e.g. requesting 30 levels returns 50).
+    pub fn with_exact_depth(mut self, exact: bool) -> Self {
+        self.exact_depth = exact;
+        self
+    }
+
+    /// Register a supervisor notified when the reconnect circuit opens or closes
+    pub fn set_supervisor(&self, supervisor: SupervisorCallback) {
+        self.reconnect.set_supervisor(supervisor);
     }
 }
 
@@ -152,8 +205,10 @@ impl MarketDataGateway for BinanceMarketDataGateway {
         // Clone Arc references for spawned task
         let ws_stream_arc = Arc::clone(&self.ws_stream);
         let connected_arc = Arc::clone(&self.connected);
-        let reconnect_count_arc = Arc::clone(&self.reconnect_count);
+        let reconnect_arc = Arc::clone(&self.reconnect);
         let symbol_arc = Arc::clone(&self.symbol);
+        let ws_urls_arc = Arc::clone(&self.ws_urls);
+        let exact_depth = self.exact_depth;
 
         // Spawn async task to handle incoming messages
         tokio::spawn(async move {
@@ -195,8 +250,10 @@ impl MarketDataGateway for BinanceMarketDataGateway {
                         let gateway = BinanceMarketDataGateway {
                             ws_stream: Arc::clone(&ws_stream_arc),
                             connected: Arc::clone(&connected_arc),
-                            reconnect_count: Arc::clone(&reconnect_count_arc),
+                            reconnect: Arc::clone(&reconnect_arc),
                             symbol: Arc::clone(&symbol_arc),
+                            ws_urls: Arc::clone(&ws_urls_arc),
+                            exact_depth,
                         };
 
                         if let Err(e) = gateway.handle_reconnect().await {
@@ -212,8 +269,10 @@ impl MarketDataGateway for BinanceMarketDataGateway {
                         let gateway = BinanceMarketDataGateway {
                             ws_stream: Arc::clone(&ws_stream_arc),
                             connected: Arc::clone(&connected_arc),
-                            reconnect_count: Arc::clone(&reconnect_count_arc),
+                            reconnect: Arc::clone(&reconnect_arc),
                             symbol: Arc::clone(&symbol_arc),
+                            ws_urls: Arc::clone(&ws_urls_arc),
+                            exact_depth,
                         };
 
                         if let Err(e) = gateway.handle_reconnect().await {
@@ -260,18 +319,7 @@ impl MarketDataGateway for BinanceMarketDataGateway {
         symbol: Symbol,
         depth: usize,
     ) -> Result<OrderBook, MarketDataError> {
-        // Validate depth parameter (Binance supports: 5, 10, 20, 50, 100, 500, 1000, 5000)
-        // For our use case, we'll use the closest valid depth
-        let valid_depth = match depth {
-            0..=5 => 5,
-            6..=10 => 10,
-            11..=20 => 20,
-            21..=50 => 50,
-            51..=100 => 100,
-            101..=500 => 500,
-            501..=1000 => 1000,
-            _ => 5000,
-        };
+        let valid_depth = Self::native_depth_limit(depth);
 
         // Construct REST API URL
         let url = format!(
@@ -301,6 +349,15 @@ impl MarketDataGateway for BinanceMarketDataGateway {
             .map_err(|e| MarketDataError::InvalidMessage(format!("Failed to parse response: {}", e)))?;
 
         // Convert to domain entity
-        orderbook_response.to_orderbook(symbol)
+        let mut orderbook = orderbook_response.to_orderbook(symbol)?;
+        if self.exact_depth {
+            orderbook.truncate(depth);
+        }
+
+        Ok(orderbook.with_metadata(OrderBookMetadata {
+            requested_depth: depth,
+            exchange_limit: valid_depth,
+            truncated: self.exact_depth,
+        }))
     }
 }
@@ -1,4 +1,4 @@
 mod market_data;
-mod types;
+pub mod types;
 
 pub use market_data::BinanceMarketDataGateway;
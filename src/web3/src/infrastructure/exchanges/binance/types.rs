@@ -136,6 +136,6 @@ impl BinanceOrderBookResponse {
             .unwrap()
             .as_millis() as u64;
 
-        Ok(OrderBook::new(symbol, bids?, asks?, timestamp))
+        Ok(OrderBook::normalized(symbol, bids?, asks?, timestamp, None)?)
     }
 }
@@ -1,18 +1,22 @@
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::net::TcpStream;
 use tokio::sync::Mutex;
-use tokio::time::{sleep, Duration, interval};
+use tokio::time::{sleep, timeout, Duration, interval};
 use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 
 use crate::domain::{
-    entities::{OrderBook, Symbol, Ticker},
+    entities::{OrderBook, OrderBookMetadata, Symbol, Ticker},
     gateways::{MarketDataError, MarketDataGateway},
 };
 
-use super::types::{BitgetOrderBookResponse, BitgetSubscription, BitgetTickerResponse};
+use crate::infrastructure::exchanges::reconnect::{ReconnectConfig, ReconnectGovernor, SupervisorCallback};
+use super::types::{
+    BitgetEvent, BitgetOrderBookResponse, BitgetSubscription, BitgetSubscriptionArg,
+    BitgetTickerResponse,
+};
 
 /// Bitget WebSocket endpoints
 const BITGET_WS_URLS: &[&str] = &[
@@ -23,9 +27,8 @@ const BITGET_WS_URLS: &[&str] = &[
 /// Bitget REST API base URL
 const BITGET_REST_API_URL: &str = "https://api.bitget.com";
 
-const MAX_RECONNECT_ATTEMPTS: u32 = 10;
-const RECONNECT_DELAY_MS: u64 = 3000;
 const PING_INTERVAL_SECS: u64 = 25; // Bitget requires ping every 30s
+const SUBSCRIBE_ACK_TIMEOUT_SECS: u64 = 10;
 
 type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
 
@@ -33,24 +36,33 @@ type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
 ///
 /// Features:
 /// - Multiple endpoint fallback
-/// - Automatic reconnection
+/// - Automatic reconnection with jittered backoff and circuit breaker
 /// - Ping/pong heartbeat mechanism
 /// - Low-latency message processing
 pub struct BitgetMarketDataGateway {
     ws_stream: Arc<Mutex<Option<WsStream>>>,
     connected: Arc<AtomicBool>,
-    reconnect_count: Arc<AtomicU32>,
+    reconnect: Arc<ReconnectGovernor>,
     symbol: Arc<Mutex<Option<Symbol>>>,
+    ws_urls: Arc<Vec<String>>,
+    exact_depth: bool,
 }
 
 impl BitgetMarketDataGateway {
     /// Create a new Bitget gateway instance
     pub fn new() -> Self {
+        Self::with_reconnect_config(ReconnectConfig::default())
+    }
+
+    /// Create a new Bitget gateway instance with a custom reconnect policy
+    pub fn with_reconnect_config(config: ReconnectConfig) -> Self {
         Self {
             ws_stream: Arc::new(Mutex::new(None)),
             connected: Arc::new(AtomicBool::new(false)),
-            reconnect_count: Arc::new(AtomicU32::new(0)),
+            reconnect: Arc::new(ReconnectGovernor::new(config)),
             symbol: Arc::new(Mutex::new(None)),
+            ws_urls: Arc::new(BITGET_WS_URLS.iter().map(|u| u.to_string()).collect()),
+            exact_depth: false,
         }
     }
 
@@ -58,10 +70,10 @@ impl BitgetMarketDataGateway {
     async fn connect_ws(&self, symbol: &Symbol) -> Result<WsStream, MarketDataError> {
         let mut last_error = None;
 
-        for base_url in BITGET_WS_URLS {
+        for base_url in self.ws_urls.iter() {
             println!("⏳ [Bitget] Attempting to connect to: {}", base_url);
 
-            match connect_async(*base_url).await {
+            match connect_async(base_url.as_str()).await {
                 Ok((mut ws_stream, _)) => {
                     println!("✅ [Bitget] Successfully connected to WebSocket");
 
@@ -75,10 +87,13 @@ impl BitgetMarketDataGateway {
                         .await
                         .map_err(|e| MarketDataError::WebSocketError(e.to_string()))?;
 
+                    // Only report success after a positive subscription ack
+                    Self::await_subscription_ack(&mut ws_stream, &subscription.args[0]).await?;
+
                     println!("📡 [Bitget] Subscribed to {} ticker", symbol);
 
                     self.connected.store(true, Ordering::SeqCst);
-                    self.reconnect_count.store(0, Ordering::SeqCst);
+                    self.reconnect.on_connected();
 
                     return Ok(ws_stream);
                 }
@@ -98,6 +113,45 @@ impl BitgetMarketDataGateway {
         )))
     }
 
+    /// Wait for the subscription ack (or error event) for `subscription`
+    async fn await_subscription_ack(
+        ws_stream: &mut WsStream,
+        subscription: &BitgetSubscriptionArg,
+    ) -> Result<(), MarketDataError> {
+        let wait = async {
+            while let Some(message) = ws_stream.next().await {
+                let text = match message {
+                    Ok(Message::Text(text)) => text,
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => continue,
+                    Err(e) => return Err(MarketDataError::WebSocketError(e.to_string())),
+                };
+
+                // Anything that isn't an event (pong, early data) is skipped
+                let Ok(event) = serde_json::from_str::<BitgetEvent>(&text) else {
+                    continue;
+                };
+
+                if let Some(error) = event.to_error() {
+                    return Err(error);
+                }
+                if event.acknowledges(subscription) {
+                    return Ok(());
+                }
+            }
+
+            Err(MarketDataError::SubscriptionError(
+                "Connection closed before subscription ack".to_string(),
+            ))
+        };
+
+        timeout(Duration::from_secs(SUBSCRIBE_ACK_TIMEOUT_SECS), wait)
+            .await
+            .map_err(|_| {
+                MarketDataError::SubscriptionError("Timed out waiting for subscription ack".to_string())
+            })?
+    }
+
     /// Handle reconnection logic
     async fn handle_reconnect(&self) -> Result<(), MarketDataError> {
         let symbol = {
@@ -108,25 +162,68 @@ impl BitgetMarketDataGateway {
                 .clone()
         };
 
-        let attempts = self.reconnect_count.fetch_add(1, Ordering::SeqCst);
+        loop {
+            // Jittered backoff; pauses for longer once the reconnect budget is spent
+            let delay = self.reconnect.next_delay()?;
+
+            if self.reconnect.is_circuit_open() {
+                println!("⛔ [Bitget] Reconnect budget exhausted, pausing for {:?}", delay);
+            } else {
+                println!(
+                    "🔄 [Bitget] Attempting to reconnect in {:?}... (attempt {})",
+                    delay,
+                    self.reconnect.attempts()
+                );
+            }
+
+            sleep(delay).await;
 
-        if attempts >= MAX_RECONNECT_ATTEMPTS {
-            return Err(MarketDataError::ReconnectionFailed(attempts));
+            match self.connect_ws(&symbol).await {
+                Ok(new_stream) => {
+                    let mut stream_lock = self.ws_stream.lock().await;
+                    *stream_lock = Some(new_stream);
+                    break;
+                }
+                Err(e @ MarketDataError::SubscriptionRejected(_)) => return Err(e),
+                Err(e) => {
+                    eprintln!("⚠️  [Bitget] Reconnect attempt failed: {}", e);
+                }
+            }
         }
 
-        println!(
-            "🔄 [Bitget] Attempting to reconnect... (attempt {}/{})",
-            attempts + 1,
-            MAX_RECONNECT_ATTEMPTS
-        );
+        Ok(())
+    }
 
-        sleep(Duration::from_millis(RECONNECT_DELAY_MS)).await;
+    /// Override the WebSocket endpoints (e.g. to point at a local mock server)
+    pub fn with_ws_urls(mut self, urls: Vec<String>) -> Self {
+        self.ws_urls = Arc::new(urls);
+        self
+    }
 
-        let new_stream = self.connect_ws(&symbol).await?;
-        let mut stream_lock = self.ws_stream.lock().await;
-        *stream_lock = Some(new_stream);
+    /// Map a requested depth to the closest exchange-native limit
+    ///
+    /// Bitget supports depths: 5, 15, 50, 100
+    pub fn native_depth_limit(depth: usize) -> usize {
+        match depth {
+            0..=5 => 5,
+            6..=15 => 15,
+            16..=50 => 50,
+            _ => 100,
+        }
+    }
 
-        Ok(())
+    /// Truncate REST order books to exactly the requested depth
+    ///
+    /// By default the exchange-native limit closest to the requested depth is
+    /// returned as-is (e.g. requesting 30 levels returns 50).
+    pub fn with_exact_depth(mut self, exact: bool) -> Self {
+        self.exact_depth = exact;
+        self
+    }
+
+    /// Register a supervisor notified when the reconnect circuit opens or closes
+    pub fn set_supervisor(&self, supervisor: SupervisorCallback) {
+        self.reconnect.set_supervisor(supervisor);
     }
 }
 
@@ -159,8 +256,10 @@ impl MarketDataGateway for BitgetMarketDataGateway {
         // Clone Arc references for spawned tasks
         let ws_stream_arc = Arc::clone(&self.ws_stream);
         let connected_arc = Arc::clone(&self.connected);
-        let reconnect_count_arc = Arc::clone(&self.reconnect_count);
+        let reconnect_arc = Arc::clone(&self.reconnect);
         let symbol_arc = Arc::clone(&self.symbol);
+        let ws_urls_arc = Arc::clone(&self.ws_urls);
+        let exact_depth = self.exact_depth;
 
         // Spawn ping task for heartbeat
         let ws_stream_ping = Arc::clone(&self.ws_stream);
@@ -218,13 +317,17 @@ impl MarketDataGateway for BitgetMarketDataGateway {
                                     }
                                 }
                             }
-                            Err(e) => {
-                                // Ignore subscription confirmation and other non-ticker messages
-                                if !text.contains("\"event\":\"subscribe\"") {
+                            Err(e) => match serde_json::from_str::<BitgetEvent>(&text) {
+                                Ok(event) => {
+                                    if let Some(error) = event.to_error() {
+                                        eprintln!("⚠️  [Bitget] {}", error);
+                                    }
+                                }
+                                Err(_) => {
                                     eprintln!("⚠️  [Bitget] Error parsing ticker response: {}", e);
                                     eprintln!("⚠️  [Bitget] Raw message: {}", text);
                                 }
-                            }
+                            },
                         }
                     }
                     Some(Ok(Message::Close(_))) => {
@@ -235,8 +338,10 @@ impl MarketDataGateway for BitgetMarketDataGateway {
                         let gateway = BitgetMarketDataGateway {
                             ws_stream: Arc::clone(&ws_stream_arc),
                             connected: Arc::clone(&connected_arc),
-                            reconnect_count: Arc::clone(&reconnect_count_arc),
+                            reconnect: Arc::clone(&reconnect_arc),
                             symbol: Arc::clone(&symbol_arc),
+                            ws_urls: Arc::clone(&ws_urls_arc),
+                            exact_depth,
                         };
 
                         if let Err(e) = gateway.handle_reconnect().await {
@@ -252,8 +357,10 @@ impl MarketDataGateway for BitgetMarketDataGateway {
                         let gateway = BitgetMarketDataGateway {
                             ws_stream: Arc::clone(&ws_stream_arc),
                             connected: Arc::clone(&connected_arc),
-                            reconnect_count: Arc::clone(&reconnect_count_arc),
+                            reconnect: Arc::clone(&reconnect_arc),
                             symbol: Arc::clone(&symbol_arc),
+                            ws_urls: Arc::clone(&ws_urls_arc),
+                            exact_depth,
                         };
 
                         if let Err(e) = gateway.handle_reconnect().await {
@@ -300,14 +407,7 @@ impl MarketDataGateway for BitgetMarketDataGateway {
         symbol: Symbol,
         depth: usize,
     ) -> Result<OrderBook, MarketDataError> {
-        // Bitget supports depths: 5, 15, 50, 100
-        // Map requested depth to closest valid value
-        let valid_depth = match depth {
-            0..=5 => 5,
-            6..=15 => 15,
-            16..=50 => 50,
-            _ => 100,
-        };
+        let valid_depth = Self::native_depth_limit(depth);
 
         // Construct REST API URL
         // Reference: https://www.bitget.com/api-doc/spot/market/Get-Orderbook
@@ -338,6 +438,15 @@ impl MarketDataGateway for BitgetMarketDataGateway {
             .map_err(|e| MarketDataError::InvalidMessage(format!("Failed to parse response: {}", e)))?;
 
         // Convert to domain entity
-        orderbook_response.to_orderbook(symbol)
+        let mut orderbook = orderbook_response.to_orderbook(symbol)?;
+        if self.exact_depth {
+            orderbook.truncate(depth);
+        }
+
+        Ok(orderbook.with_metadata(OrderBookMetadata {
+            requested_depth: depth,
+            exchange_limit: valid_depth,
+            truncated: self.exact_depth,
+        }))
     }
 }
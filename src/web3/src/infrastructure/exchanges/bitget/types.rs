@@ -47,7 +47,7 @@ pub struct BitgetTickerResponse {
     pub data: Vec<BitgetTickerData>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BitgetResponseArg {
     pub inst_type: String,
@@ -55,6 +55,79 @@ pub struct BitgetResponseArg {
     pub inst_id: String,
 }
 
+/// Bitget WebSocket event (subscription ack or error)
+/// Based on: https://www.bitget.com/api-doc/common/websocket-intro
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum BitgetEvent {
+    /// Subscription confirmed
+    Subscribe { arg: BitgetResponseArg },
+
+    /// Unsubscription confirmed
+    Unsubscribe { arg: BitgetResponseArg },
+
+    /// Request failed (bad symbol, bad channel, ...)
+    Error {
+        #[serde(default)]
+        arg: Option<BitgetResponseArg>,
+        code: BitgetErrorCode,
+        msg: String,
+    },
+}
+
+/// Bitget error code (sent as a number on WebSocket, as a string on REST)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum BitgetErrorCode {
+    Number(i64),
+    Text(String),
+}
+
+impl std::fmt::Display for BitgetErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BitgetErrorCode::Number(code) => write!(f, "{}", code),
+            BitgetErrorCode::Text(code) => write!(f, "{}", code),
+        }
+    }
+}
+
+impl BitgetEvent {
+    /// Check whether this event acknowledges the given subscription
+    pub fn acknowledges(&self, subscription: &BitgetSubscriptionArg) -> bool {
+        match self {
+            BitgetEvent::Subscribe { arg } => arg.matches(subscription),
+            _ => false,
+        }
+    }
+
+    /// Convert an error event into a domain error
+    pub fn to_error(&self) -> Option<MarketDataError> {
+        match self {
+            BitgetEvent::Error { arg, code, msg } => {
+                let target = arg
+                    .as_ref()
+                    .map(|a| format!("{}:{}", a.channel, a.inst_id))
+                    .unwrap_or_else(|| "unknown".to_string());
+                Some(MarketDataError::SubscriptionRejected(format!(
+                    "Bitget {} ({}): {}",
+                    target, code, msg
+                )))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl BitgetResponseArg {
+    /// Check whether this argument refers to the given subscription
+    pub fn matches(&self, subscription: &BitgetSubscriptionArg) -> bool {
+        self.inst_type.eq_ignore_ascii_case(&subscription.inst_type)
+            && self.channel == subscription.channel
+            && self.inst_id.eq_ignore_ascii_case(&subscription.inst_id)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BitgetTickerData {
@@ -221,6 +294,34 @@ impl BitgetOrderBookResponse {
                     .as_millis() as u64
             });
 
-        Ok(OrderBook::new(symbol, bids?, asks?, timestamp))
+        Ok(OrderBook::normalized(symbol, bids?, asks?, timestamp, None)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_subscribe_ack() {
+        let text = r#"{"event":"subscribe","arg":{"instType":"SPOT","channel":"ticker","instId":"BTCUSDT"}}"#;
+        let event: BitgetEvent = serde_json::from_str(text).unwrap();
+
+        let subscription = BitgetSubscription::ticker("btcusdt");
+        assert!(event.acknowledges(&subscription.args[0]));
+        assert!(event.to_error().is_none());
+    }
+
+    #[test]
+    fn test_parse_error_event() {
+        let text = r#"{"event":"error","arg":{"instType":"SPOT","channel":"ticker","instId":"NOPE"},"code":30001,"msg":"instType:SPOT,channel:ticker,instId:NOPE doesn't exist"}"#;
+        let event: BitgetEvent = serde_json::from_str(text).unwrap();
+
+        let subscription = BitgetSubscription::ticker("NOPE");
+        assert!(!event.acknowledges(&subscription.args[0]));
+        assert!(matches!(
+            event.to_error(),
+            Some(MarketDataError::SubscriptionRejected(msg)) if msg.contains("30001")
+        ));
     }
 }
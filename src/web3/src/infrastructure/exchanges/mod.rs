@@ -1,2 +1,3 @@
 pub mod binance;
 pub mod bitget;
+pub mod reconnect;
@@ -0,0 +1,305 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::domain::gateways::MarketDataError;
+
+/// Reconnection policy shared by the exchange gateways
+///
+/// Replaces the fixed reconnect delay with:
+/// - Exponential backoff with jitter, so gateways don't reconnect in lockstep
+/// - A reconnect budget per time window
+/// - A circuit breaker that pauses attempts once the budget is spent
+#[derive(Debug, Clone)]
+pub struct ReconnectConfig {
+    /// Delay before the first reconnect attempt
+    pub initial_delay: Duration,
+    /// Upper bound for the backoff delay
+    pub max_delay: Duration,
+    /// Backoff multiplier applied after each failed attempt
+    pub backoff_multiplier: f64,
+    /// Jitter as a fraction of the delay (0.0 = none, 1.0 = full jitter)
+    pub jitter: f64,
+    /// Maximum number of attempts allowed within `budget_window`
+    pub budget: u32,
+    /// Time window the reconnect budget applies to
+    pub budget_window: Duration,
+    /// How long the circuit stays open once the budget is exhausted
+    pub circuit_open_duration: Duration,
+    /// Maximum consecutive attempts before giving up (None = never give up)
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            backoff_multiplier: 2.0,
+            jitter: 0.5,
+            budget: 5,
+            budget_window: Duration::from_secs(60),
+            circuit_open_duration: Duration::from_secs(120),
+            max_attempts: Some(10),
+        }
+    }
+}
+
+/// Events reported to the supervisor
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReconnectEvent {
+    /// Reconnect budget exhausted; attempts are paused for `pause`
+    CircuitOpened { attempts_in_window: u32, pause: Duration },
+    /// A connection succeeded after the circuit had been opened
+    CircuitClosed,
+    /// Maximum consecutive attempts reached; the gateway gives up
+    GaveUp { attempts: u32 },
+}
+
+/// Supervisor callback invoked on circuit breaker transitions
+pub type SupervisorCallback = Arc<dyn Fn(ReconnectEvent) + Send + Sync>;
+
+struct GovernorState {
+    /// Consecutive failed attempts since the last successful connection
+    consecutive: u32,
+    /// Start times of attempts within the current budget window
+    window: Vec<Instant>,
+    /// Whether the circuit is currently open
+    circuit_open: bool,
+    /// xorshift state used for jitter
+    rng: u64,
+}
+
+/// Computes reconnect delays and enforces the reconnect budget
+pub struct ReconnectGovernor {
+    config: ReconnectConfig,
+    state: Mutex<GovernorState>,
+    supervisor: Mutex<Option<SupervisorCallback>>,
+}
+
+impl ReconnectGovernor {
+    /// Create a new governor with the given policy
+    pub fn new(config: ReconnectConfig) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x9E37_79B9_7F4A_7C15);
+
+        Self {
+            config,
+            state: Mutex::new(GovernorState {
+                consecutive: 0,
+                window: Vec::new(),
+                circuit_open: false,
+                rng: seed | 1,
+            }),
+            supervisor: Mutex::new(None),
+        }
+    }
+
+    /// Get the reconnect policy
+    pub fn config(&self) -> &ReconnectConfig {
+        &self.config
+    }
+
+    /// Register the supervisor notified on circuit breaker transitions
+    pub fn set_supervisor(&self, supervisor: SupervisorCallback) {
+        *self.supervisor.lock().unwrap() = Some(supervisor);
+    }
+
+    /// Number of consecutive failed attempts
+    pub fn attempts(&self) -> u32 {
+        self.state.lock().unwrap().consecutive
+    }
+
+    /// Whether attempts are currently paused by the circuit breaker
+    pub fn is_circuit_open(&self) -> bool {
+        self.state.lock().unwrap().circuit_open
+    }
+
+    /// Register a new attempt and return how long to wait before making it
+    pub fn next_delay(&self) -> Result<Duration, MarketDataError> {
+        self.next_delay_at(Instant::now())
+    }
+
+    /// Same as [`next_delay`](Self::next_delay) with an explicit clock
+    pub fn next_delay_at(&self, now: Instant) -> Result<Duration, MarketDataError> {
+        let (result, event) = {
+            let mut state = self.state.lock().unwrap();
+
+            if let Some(max) = self.config.max_attempts {
+                if state.consecutive >= max {
+                    let attempts = state.consecutive;
+                    (
+                        Err(MarketDataError::ReconnectionFailed(attempts)),
+                        Some(ReconnectEvent::GaveUp { attempts }),
+                    )
+                } else {
+                    self.schedule(&mut state, now)
+                }
+            } else {
+                self.schedule(&mut state, now)
+            }
+        };
+
+        if let Some(event) = event {
+            self.notify(event);
+        }
+
+        result
+    }
+
+    /// Reset backoff after a successful connection
+    pub fn on_connected(&self) {
+        let was_open = {
+            let mut state = self.state.lock().unwrap();
+            state.consecutive = 0;
+            std::mem::replace(&mut state.circuit_open, false)
+        };
+
+        if was_open {
+            self.notify(ReconnectEvent::CircuitClosed);
+        }
+    }
+
+    fn schedule(
+        &self,
+        state: &mut GovernorState,
+        now: Instant,
+    ) -> (Result<Duration, MarketDataError>, Option<ReconnectEvent>) {
+        let window = self.config.budget_window;
+        state.window.retain(|t| now.duration_since(*t) < window);
+
+        let backoff = self.backoff(state);
+        state.consecutive += 1;
+
+        if state.window.len() as u32 >= self.config.budget {
+            // Budget exhausted: pause until the window drains, then retry
+            let attempts_in_window = state.window.len() as u32;
+            let pause = self.config.circuit_open_duration.max(backoff);
+            state.window.clear();
+            state.window.push(now + pause);
+            state.circuit_open = true;
+
+            return (
+                Ok(pause),
+                Some(ReconnectEvent::CircuitOpened { attempts_in_window, pause }),
+            );
+        }
+
+        state.window.push(now + backoff);
+        (Ok(backoff), None)
+    }
+
+    /// Jittered exponential backoff for the current attempt
+    fn backoff(&self, state: &mut GovernorState) -> Duration {
+        let base = self.config.initial_delay.as_secs_f64()
+            * self.config.backoff_multiplier.powi(state.consecutive as i32);
+        let base = base.min(self.config.max_delay.as_secs_f64());
+
+        // Equal jitter: keep (1 - jitter) of the delay, randomize the rest
+        let jitter = self.config.jitter.clamp(0.0, 1.0);
+        let random = Self::next_random(&mut state.rng);
+        Duration::from_secs_f64(base * (1.0 - jitter) + base * jitter * random)
+    }
+
+    /// xorshift64, returns a value in [0, 1)
+    fn next_random(rng: &mut u64) -> f64 {
+        let mut x = *rng;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        *rng = x;
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn notify(&self, event: ReconnectEvent) {
+        let supervisor = self.supervisor.lock().unwrap().clone();
+        if let Some(supervisor) = supervisor {
+            supervisor(event);
+        }
+    }
+}
+
+impl Default for ReconnectGovernor {
+    fn default() -> Self {
+        Self::new(ReconnectConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ReconnectConfig {
+        ReconnectConfig {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            backoff_multiplier: 2.0,
+            jitter: 0.5,
+            budget: 3,
+            budget_window: Duration::from_secs(60),
+            circuit_open_duration: Duration::from_secs(10),
+            max_attempts: None,
+        }
+    }
+
+    #[test]
+    fn test_backoff_grows_with_jitter_bounds() {
+        let governor = ReconnectGovernor::new(ReconnectConfig { budget: 100, ..config() });
+        let now = Instant::now();
+
+        for attempt in 0..6 {
+            let delay = governor.next_delay_at(now).unwrap().as_secs_f64();
+            let base = (0.1 * 2f64.powi(attempt)).min(1.0);
+            assert!(delay >= base * 0.5 - 1e-9 && delay <= base + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_budget_opens_circuit_and_notifies() {
+        let governor = ReconnectGovernor::new(config());
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        governor.set_supervisor(Arc::new(move |event| sink.lock().unwrap().push(event)));
+
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert!(governor.next_delay_at(now).unwrap() < Duration::from_secs(1));
+        }
+        assert!(!governor.is_circuit_open());
+
+        let pause = governor.next_delay_at(now).unwrap();
+        assert_eq!(pause, Duration::from_secs(10));
+        assert!(governor.is_circuit_open());
+
+        governor.on_connected();
+        assert!(!governor.is_circuit_open());
+        assert_eq!(governor.attempts(), 0);
+
+        let events = events.lock().unwrap();
+        assert_eq!(
+            *events,
+            vec![
+                ReconnectEvent::CircuitOpened { attempts_in_window: 3, pause },
+                ReconnectEvent::CircuitClosed,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_attempts() {
+        let governor = ReconnectGovernor::new(ReconnectConfig {
+            budget: 100,
+            max_attempts: Some(2),
+            ..config()
+        });
+
+        let now = Instant::now();
+        assert!(governor.next_delay_at(now).is_ok());
+        assert!(governor.next_delay_at(now).is_ok());
+        assert!(matches!(
+            governor.next_delay_at(now),
+            Err(MarketDataError::ReconnectionFailed(2))
+        ));
+    }
+}
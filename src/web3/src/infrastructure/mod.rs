@@ -1 +1,3 @@
+pub mod credentials;
+pub mod engine_feed;
 pub mod exchanges;
@@ -1,2 +1,4 @@
 pub mod domain;
 pub mod infrastructure;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
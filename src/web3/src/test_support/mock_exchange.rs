@@ -0,0 +1,271 @@
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+/// Exchange message dialect spoken by the mock server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockDialect {
+    /// Binance single-stream format (no subscription handshake)
+    Binance,
+    /// Bitget v2 public channel format (subscribe op + ack, text ping/pong)
+    Bitget,
+}
+
+/// Frames pushed to every connected client
+#[derive(Debug, Clone)]
+enum MockFrame {
+    Text(String),
+    Disconnect,
+}
+
+/// Local WebSocket server imitating an exchange market data endpoint
+///
+/// Used by gateway integration tests to exercise parsing and reconnection
+/// logic hermetically:
+/// - Subscription acks and rejections (Bitget)
+/// - Ticker pushes in the exchange's native format
+/// - Forced disconnects
+/// - Malformed frames
+pub struct MockExchangeServer {
+    addr: SocketAddr,
+    dialect: MockDialect,
+    frames: broadcast::Sender<MockFrame>,
+    connections: Arc<AtomicU32>,
+    received: Arc<Mutex<Vec<String>>>,
+    rejected: Arc<Mutex<HashSet<String>>>,
+    accept_task: JoinHandle<()>,
+}
+
+impl MockExchangeServer {
+    /// Start a mock server on an ephemeral localhost port
+    pub async fn start(dialect: MockDialect) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let (frames, _) = broadcast::channel(1024);
+        let connections = Arc::new(AtomicU32::new(0));
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let rejected = Arc::new(Mutex::new(HashSet::new()));
+
+        let frames_accept = frames.clone();
+        let connections_accept = Arc::clone(&connections);
+        let received_accept = Arc::clone(&received);
+        let rejected_accept = Arc::clone(&rejected);
+
+        let accept_task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let frames_rx = frames_accept.subscribe();
+                let connections = Arc::clone(&connections_accept);
+                let received = Arc::clone(&received_accept);
+                let rejected = Arc::clone(&rejected_accept);
+
+                tokio::spawn(async move {
+                    let Ok(ws) = accept_async(stream).await else {
+                        return;
+                    };
+                    connections.fetch_add(1, Ordering::SeqCst);
+                    Self::serve(ws, dialect, frames_rx, received, rejected).await;
+                });
+            }
+        });
+
+        Ok(Self {
+            addr,
+            dialect,
+            frames,
+            connections,
+            received,
+            rejected,
+            accept_task,
+        })
+    }
+
+    /// Base WebSocket URL to configure the gateway with
+    pub fn ws_url(&self) -> String {
+        match self.dialect {
+            MockDialect::Binance => format!("ws://{}/ws", self.addr),
+            MockDialect::Bitget => format!("ws://{}/v2/ws/public", self.addr),
+        }
+    }
+
+    /// Number of WebSocket connections accepted so far
+    pub fn connection_count(&self) -> u32 {
+        self.connections.load(Ordering::SeqCst)
+    }
+
+    /// Text frames received from clients (subscriptions, pings)
+    pub fn received_messages(&self) -> Vec<String> {
+        self.received.lock().unwrap().clone()
+    }
+
+    /// Answer subscriptions for `symbol` with an error event (Bitget)
+    pub fn reject_symbol(&self, symbol: &str) {
+        self.rejected.lock().unwrap().insert(symbol.to_uppercase());
+    }
+
+    /// Push a ticker update in the server's dialect to all clients
+    pub fn send_ticker(&self, symbol: &str, last: f64, bid: f64, ask: f64) {
+        let text = match self.dialect {
+            MockDialect::Binance => Self::binance_ticker(symbol, last, bid, ask),
+            MockDialect::Bitget => Self::bitget_ticker(symbol, last, bid, ask),
+        };
+        self.send_raw(text);
+    }
+
+    /// Push an arbitrary (possibly malformed) text frame to all clients
+    pub fn send_raw(&self, text: impl Into<String>) {
+        let _ = self.frames.send(MockFrame::Text(text.into()));
+    }
+
+    /// Push a frame that is not valid JSON
+    pub fn send_malformed(&self) {
+        self.send_raw("{not json");
+    }
+
+    /// Close every current connection from the server side
+    pub fn disconnect_all(&self) {
+        let _ = self.frames.send(MockFrame::Disconnect);
+    }
+
+    /// Binance 24hr ticker stream payload
+    pub fn binance_ticker(symbol: &str, last: f64, bid: f64, ask: f64) -> String {
+        json!({
+            "e": "24hrTicker",
+            "E": Self::now_ms(),
+            "s": symbol.to_uppercase(),
+            "c": last.to_string(),
+            "b": bid.to_string(),
+            "B": "1.0",
+            "a": ask.to_string(),
+            "A": "1.0",
+        })
+        .to_string()
+    }
+
+    /// Bitget ticker channel payload
+    pub fn bitget_ticker(symbol: &str, last: f64, bid: f64, ask: f64) -> String {
+        json!({
+            "action": "snapshot",
+            "arg": { "instType": "SPOT", "channel": "ticker", "instId": symbol.to_uppercase() },
+            "data": [{
+                "instId": symbol.to_uppercase(),
+                "lastPr": last.to_string(),
+                "bidPr": bid.to_string(),
+                "askPr": ask.to_string(),
+                "bidSz": "1.0",
+                "askSz": "1.0",
+                "open24h": last.to_string(),
+                "high24h": last.to_string(),
+                "low24h": last.to_string(),
+                "change24h": "0",
+                "ts": Self::now_ms().to_string(),
+            }],
+        })
+        .to_string()
+    }
+
+    /// Handle one client connection until it closes or a disconnect is forced
+    async fn serve<S>(
+        ws: tokio_tungstenite::WebSocketStream<S>,
+        dialect: MockDialect,
+        mut frames: broadcast::Receiver<MockFrame>,
+        received: Arc<Mutex<Vec<String>>>,
+        rejected: Arc<Mutex<HashSet<String>>>,
+    ) where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        let (mut sink, mut stream) = ws.split();
+
+        loop {
+            tokio::select! {
+                incoming = stream.next() => {
+                    let text = match incoming {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Ping(data))) => {
+                            let _ = sink.send(Message::Pong(data)).await;
+                            continue;
+                        }
+                        Some(Ok(_)) => continue,
+                        _ => break,
+                    };
+
+                    received.lock().unwrap().push(text.clone());
+
+                    let reply = Self::reply(dialect, &text, &rejected.lock().unwrap());
+                    if let Some(reply) = reply {
+                        if sink.send(Message::Text(reply)).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                frame = frames.recv() => {
+                    match frame {
+                        Ok(MockFrame::Text(text)) => {
+                            if sink.send(Message::Text(text)).await.is_err() {
+                                break;
+                            }
+                        }
+                        Ok(MockFrame::Disconnect) | Err(broadcast::error::RecvError::Closed) => {
+                            let _ = sink.send(Message::Close(None)).await;
+                            break;
+                        }
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Dialect-specific reply to a client request
+    fn reply(dialect: MockDialect, text: &str, rejected: &HashSet<String>) -> Option<String> {
+        match dialect {
+            MockDialect::Binance => None,
+            MockDialect::Bitget => {
+                if text == "ping" {
+                    return Some("pong".to_string());
+                }
+
+                let request: serde_json::Value = serde_json::from_str(text).ok()?;
+                if request["op"] == "subscribe" {
+                    let arg = request["args"].get(0).cloned().unwrap_or_default();
+                    let inst_id = arg["instId"].as_str().unwrap_or_default().to_uppercase();
+
+                    if rejected.contains(&inst_id) {
+                        Some(
+                            json!({
+                                "event": "error",
+                                "arg": arg,
+                                "code": 30001,
+                                "msg": format!("instType:SPOT,channel:ticker,instId:{} doesn't exist", inst_id),
+                            })
+                            .to_string(),
+                        )
+                    } else {
+                        Some(json!({ "event": "subscribe", "arg": arg }).to_string())
+                    }
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn now_ms() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    }
+}
+
+impl Drop for MockExchangeServer {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
@@ -0,0 +1,4 @@
+pub mod mock_exchange;
+
+// Re-export for convenience
+pub use mock_exchange::{MockDialect, MockExchangeServer};
@@ -0,0 +1,155 @@
+/// Golden-file decoding tests for exchange message dialects
+///
+/// Each fixture under `tests/fixtures/<exchange>/` is a captured WS or REST
+/// payload; the file name prefix selects the decoder (`ticker_`, `depth_`,
+/// `event_`). The decoded domain entity (or error) is serialized to JSON and
+/// compared with the sibling `.expected.json` file.
+///
+/// Regenerate expectations after an intentional parser change with:
+/// `UPDATE_GOLDEN=1 cargo test -p web3 --test golden_decoding_test`
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use web3::domain::entities::Symbol;
+use web3::infrastructure::exchanges::binance::types::{
+    BinanceOrderBookResponse, BinanceTickerResponse,
+};
+use web3::infrastructure::exchanges::bitget::types::{
+    BitgetEvent, BitgetOrderBookResponse, BitgetTickerResponse,
+};
+
+fn fixture_dir(exchange: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(exchange)
+}
+
+fn error(message: impl ToString) -> Value {
+    json!({ "error": message.to_string() })
+}
+
+fn to_value<T: serde::Serialize>(entity: T) -> Value {
+    serde_json::to_value(entity).expect("domain entities are serializable")
+}
+
+fn decode_binance(kind: &str, raw: &str) -> Value {
+    match kind {
+        "ticker" => match serde_json::from_str::<BinanceTickerResponse>(raw) {
+            Ok(response) => match response.to_ticker() {
+                Ok(ticker) => to_value(ticker),
+                Err(e) => error(e),
+            },
+            Err(e) => error(format!("decode: {}", e)),
+        },
+        "depth" => match serde_json::from_str::<BinanceOrderBookResponse>(raw) {
+            Ok(response) => match response.to_orderbook(Symbol::new("BTCUSDT")) {
+                Ok(mut book) => {
+                    // Binance REST depth carries no timestamp; the parser stamps local time
+                    book.timestamp = 0;
+                    to_value(book)
+                }
+                Err(e) => error(e),
+            },
+            Err(e) => error(format!("decode: {}", e)),
+        },
+        other => panic!("unknown binance fixture kind: {}", other),
+    }
+}
+
+fn decode_bitget(kind: &str, raw: &str) -> Value {
+    match kind {
+        "ticker" => match serde_json::from_str::<BitgetTickerResponse>(raw) {
+            Ok(response) => {
+                let tickers: Vec<Value> = response
+                    .data
+                    .iter()
+                    .map(|data| match data.to_ticker() {
+                        Ok(ticker) => to_value(ticker),
+                        Err(e) => error(e),
+                    })
+                    .collect();
+                Value::Array(tickers)
+            }
+            Err(e) => error(format!("decode: {}", e)),
+        },
+        "depth" => match serde_json::from_str::<BitgetOrderBookResponse>(raw) {
+            Ok(response) => match response.to_orderbook(Symbol::new("BTCUSDT")) {
+                Ok(book) => to_value(book),
+                Err(e) => error(e),
+            },
+            Err(e) => error(format!("decode: {}", e)),
+        },
+        "event" => match serde_json::from_str::<BitgetEvent>(raw) {
+            Ok(event @ BitgetEvent::Error { .. }) => error(event.to_error().unwrap()),
+            Ok(BitgetEvent::Subscribe { arg }) => json!({
+                "subscribed": { "channel": arg.channel, "inst_id": arg.inst_id }
+            }),
+            Ok(BitgetEvent::Unsubscribe { arg }) => json!({
+                "unsubscribed": { "channel": arg.channel, "inst_id": arg.inst_id }
+            }),
+            Err(e) => error(format!("decode: {}", e)),
+        },
+        other => panic!("unknown bitget fixture kind: {}", other),
+    }
+}
+
+fn run_golden(exchange: &str, decode: fn(&str, &str) -> Value) {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(fixture_dir(exchange))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            name.ends_with(".json") && !name.ends_with(".expected.json")
+        })
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "no fixtures for {}", exchange);
+
+    let mut failures = Vec::new();
+    for path in &fixtures {
+        let stem = path.file_stem().unwrap().to_str().unwrap();
+        let kind = stem.split('_').next().unwrap();
+        let raw = fs::read_to_string(path).unwrap();
+        let actual = decode(kind, &raw);
+
+        let expected_path = path.with_extension("expected.json");
+        if update {
+            let pretty = serde_json::to_string_pretty(&actual).unwrap();
+            fs::write(&expected_path, pretty + "\n").unwrap();
+            continue;
+        }
+
+        let expected: Value = match fs::read_to_string(&expected_path) {
+            Ok(text) => serde_json::from_str(&text).unwrap(),
+            Err(_) => {
+                failures.push(format!("{}: missing {}", stem, expected_path.display()));
+                continue;
+            }
+        };
+        if actual != expected {
+            failures.push(format!(
+                "{}:\n  expected: {}\n  actual:   {}",
+                stem, expected, actual
+            ));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} golden mismatches:\n{}",
+        exchange,
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn test_binance_golden_fixtures() {
+    run_golden("binance", decode_binance);
+}
+
+#[test]
+fn test_bitget_golden_fixtures() {
+    run_golden("bitget", decode_bitget);
+}
@@ -0,0 +1,122 @@
+/// Gateway integration tests against the local mock exchange server
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout};
+use web3::domain::{
+    entities::{Symbol, Ticker},
+    gateways::{MarketDataError, MarketDataGateway},
+};
+use web3::infrastructure::exchanges::{
+    binance::BinanceMarketDataGateway, bitget::BitgetMarketDataGateway,
+    reconnect::ReconnectConfig,
+};
+use web3::test_support::{MockDialect, MockExchangeServer};
+
+fn fast_reconnect() -> ReconnectConfig {
+    ReconnectConfig {
+        initial_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(50),
+        max_attempts: Some(5),
+        ..ReconnectConfig::default()
+    }
+}
+
+fn ticker_channel() -> (
+    Box<dyn Fn(Ticker) + Send + Sync>,
+    mpsc::UnboundedReceiver<Ticker>,
+) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let callback = Box::new(move |ticker| {
+        let _ = tx.send(ticker);
+    });
+    (callback, rx)
+}
+
+async fn wait_for_connections(server: &MockExchangeServer, count: u32) {
+    timeout(Duration::from_secs(5), async {
+        while server.connection_count() < count {
+            sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("gateway did not connect in time");
+}
+
+async fn next_ticker(rx: &mut mpsc::UnboundedReceiver<Ticker>) -> Ticker {
+    timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("no ticker received in time")
+        .expect("ticker channel closed")
+}
+
+#[tokio::test]
+async fn test_binance_parses_tickers_and_skips_malformed_frames() {
+    let server = MockExchangeServer::start(MockDialect::Binance).await.unwrap();
+    let gateway = BinanceMarketDataGateway::with_reconnect_config(fast_reconnect())
+        .with_ws_urls(vec![server.ws_url()]);
+
+    let (callback, mut rx) = ticker_channel();
+    gateway.subscribe_ticker(Symbol::new("BTCUSDT"), callback).await.unwrap();
+    wait_for_connections(&server, 1).await;
+
+    server.send_malformed();
+    server.send_ticker("BTCUSDT", 50000.0, 49999.0, 50001.0);
+
+    let ticker = next_ticker(&mut rx).await;
+    assert_eq!(ticker.symbol, Symbol::new("BTCUSDT"));
+    assert_eq!(ticker.price.value(), 50000.0);
+    assert_eq!(ticker.spread(), Some(2.0));
+}
+
+#[tokio::test]
+async fn test_binance_reconnects_after_forced_disconnect() {
+    let server = MockExchangeServer::start(MockDialect::Binance).await.unwrap();
+    let gateway = BinanceMarketDataGateway::with_reconnect_config(fast_reconnect())
+        .with_ws_urls(vec![server.ws_url()]);
+
+    let (callback, mut rx) = ticker_channel();
+    gateway.subscribe_ticker(Symbol::new("BTCUSDT"), callback).await.unwrap();
+    wait_for_connections(&server, 1).await;
+
+    server.disconnect_all();
+    wait_for_connections(&server, 2).await;
+
+    server.send_ticker("BTCUSDT", 51000.0, 50999.0, 51001.0);
+    assert_eq!(next_ticker(&mut rx).await.price.value(), 51000.0);
+    assert!(gateway.is_connected());
+}
+
+#[tokio::test]
+async fn test_bitget_subscribes_and_parses_tickers() {
+    let server = MockExchangeServer::start(MockDialect::Bitget).await.unwrap();
+    let gateway = BitgetMarketDataGateway::with_reconnect_config(fast_reconnect())
+        .with_ws_urls(vec![server.ws_url()]);
+
+    let (callback, mut rx) = ticker_channel();
+    gateway.subscribe_ticker(Symbol::new("BTCUSDT"), callback).await.unwrap();
+    wait_for_connections(&server, 1).await;
+
+    server.send_malformed();
+    server.send_ticker("BTCUSDT", 50000.0, 49999.5, 50000.5);
+
+    let ticker = next_ticker(&mut rx).await;
+    assert_eq!(ticker.price.value(), 50000.0);
+    assert_eq!(ticker.spread(), Some(1.0));
+
+    let received = server.received_messages();
+    assert!(received.iter().any(|m| m.contains("\"op\":\"subscribe\"")));
+}
+
+#[tokio::test]
+async fn test_bitget_rejected_subscription_fails() {
+    let server = MockExchangeServer::start(MockDialect::Bitget).await.unwrap();
+    server.reject_symbol("NOPEUSDT");
+    let gateway = BitgetMarketDataGateway::with_reconnect_config(fast_reconnect())
+        .with_ws_urls(vec![server.ws_url()]);
+
+    let (callback, _rx) = ticker_channel();
+    let result = gateway.subscribe_ticker(Symbol::new("NOPEUSDT"), callback).await;
+
+    assert!(matches!(result, Err(MarketDataError::SubscriptionRejected(_))));
+    assert!(!gateway.is_connected());
+}
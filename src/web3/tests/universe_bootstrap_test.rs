@@ -0,0 +1,52 @@
+/// Universe bootstrap against the local mock exchange server
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout};
+use web3::domain::universe::{bootstrap, SubscriptionPacing, Universe};
+use web3::infrastructure::exchanges::binance::BinanceMarketDataGateway;
+use web3::test_support::{MockDialect, MockExchangeServer};
+
+#[tokio::test]
+async fn test_bootstrap_subscribes_whole_universe_with_pacing() {
+    let server = MockExchangeServer::start(MockDialect::Binance).await.unwrap();
+    let entries = Universe::from_symbols(["BTCUSDT", "ETHUSDT", "SOLUSDT"]).resolve(&[]);
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let pacing = SubscriptionPacing {
+        burst: 2,
+        interval: Duration::from_millis(100),
+    };
+    let url = server.ws_url();
+
+    let started = Instant::now();
+    let report = bootstrap(
+        entries,
+        pacing,
+        |_| BinanceMarketDataGateway::new().with_ws_urls(vec![url.clone()]),
+        Arc::new(move |ticker| {
+            let _ = tx.send(ticker);
+        }),
+    )
+    .await;
+
+    assert!(report.is_complete());
+    assert_eq!(report.subscribed.len(), 3);
+    // Third subscription starts a new burst and must wait one interval
+    assert!(started.elapsed() >= Duration::from_millis(100));
+
+    timeout(Duration::from_secs(5), async {
+        while server.connection_count() < 3 {
+            sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("gateways did not connect in time");
+
+    server.send_ticker("ETHUSDT", 3000.0, 2999.0, 3001.0);
+    let ticker = timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("no ticker received in time")
+        .unwrap();
+    assert_eq!(ticker.symbol.as_str(), "ETHUSDT");
+}